@@ -0,0 +1,91 @@
+/*
+ * C API for the `jsonschema` crate.
+ *
+ * JSON texts are passed as UTF-8 bytes with their length and don't need to be NUL-terminated.
+ * Objects returned by this library are owned by the caller and must be released with the
+ * matching `*_free` function.
+ */
+#ifndef JSONSCHEMA_H
+#define JSONSCHEMA_H
+
+#include <stddef.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* A compiled schema. */
+typedef struct JsonschemaValidator JsonschemaValidator;
+
+/* All errors of a validated instance. */
+typedef struct JsonschemaErrors JsonschemaErrors;
+
+/* The version of this library. The string is static. */
+const char *jsonschema_version(void);
+
+/*
+ * Compile a schema, detecting its draft from `$schema`.
+ *
+ * Returns NULL if the schema is not valid JSON or not a valid schema, and sets `*error` to a
+ * message to release with `jsonschema_string_free` unless `error` is NULL.
+ */
+JsonschemaValidator *jsonschema_validator_new(const char *schema, size_t len, char **error);
+
+/* Release a validator. Does nothing if `validator` is NULL. */
+void jsonschema_validator_free(JsonschemaValidator *validator);
+
+/*
+ * Check whether an instance is valid.
+ *
+ * Returns 1 if it is, 0 if it is not, and -1 if either argument is NULL or the instance is not
+ * valid JSON.
+ */
+int jsonschema_is_valid(const JsonschemaValidator *validator, const char *instance, size_t len);
+
+/*
+ * Validate an instance and collect all of its errors, which are empty if it is valid.
+ *
+ * Returns NULL if either argument is NULL or the instance is not valid JSON, and sets `*error`
+ * to a message to release with `jsonschema_string_free` unless `error` is NULL.
+ */
+JsonschemaErrors *jsonschema_validate(const JsonschemaValidator *validator,
+                                      const char *instance,
+                                      size_t len,
+                                      char **error);
+
+/* The number of errors, or 0 if `errors` is NULL. */
+size_t jsonschema_errors_count(const JsonschemaErrors *errors);
+
+/*
+ * Details of the error at `index`, or NULL if it is out of range.
+ *
+ * The strings are owned by `errors` and valid until they are released.
+ */
+const char *jsonschema_errors_message(const JsonschemaErrors *errors, size_t index);
+/* JSON Pointer to the invalid part of the instance. */
+const char *jsonschema_errors_instance_location(const JsonschemaErrors *errors, size_t index);
+/* JSON Pointer to the schema keyword that caused the error. */
+const char *jsonschema_errors_keyword_location(const JsonschemaErrors *errors, size_t index);
+/* The name of the keyword, e.g. `type`. NULL for errors caused by `false` subschemas. */
+const char *jsonschema_errors_keyword(const JsonschemaErrors *errors, size_t index);
+
+/* Release errors. Does nothing if `errors` is NULL. */
+void jsonschema_errors_free(JsonschemaErrors *errors);
+
+/* Release a string returned through an `error` out-parameter. Does nothing if `string` is NULL. */
+void jsonschema_string_free(char *string);
+
+/*
+ * Allocate `len` bytes, e.g. for a WebAssembly host to write JSON texts into linear memory.
+ * Returns NULL if `len` is 0.
+ */
+char *jsonschema_alloc(size_t len);
+
+/* Release memory allocated by `jsonschema_alloc` with the same `len`. */
+void jsonschema_dealloc(char *pointer, size_t len);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* JSONSCHEMA_H */
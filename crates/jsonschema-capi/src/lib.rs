@@ -0,0 +1,469 @@
+//! A C API for `jsonschema`.
+//!
+//! Schemas and instances are passed as UTF-8 encoded JSON texts together with their lengths in
+//! bytes, so they don't need to be NUL-terminated. Every object returned by this library is owned
+//! by the caller and must be released with the matching `*_free` function. See
+//! `include/jsonschema.h` for the declarations.
+//!
+//! Errors are reported through an optional `char **error` out-parameter, which receives a message
+//! to release with [`jsonschema_string_free`].
+#![allow(unsafe_code)]
+use std::{
+    ffi::{c_char, c_int, CString},
+    mem,
+    panic::{self, AssertUnwindSafe},
+    ptr, slice,
+};
+
+use jsonschema::{BatchError, Validator};
+use serde_json::Value;
+
+const VERSION: &str = concat!(env!("CARGO_PKG_VERSION"), "\0");
+
+/// A compiled schema.
+pub struct JsonschemaValidator(Validator);
+
+/// All errors of a validated instance.
+pub struct JsonschemaErrors(Vec<ErrorEntry>);
+
+struct ErrorEntry {
+    message: CString,
+    instance_location: CString,
+    keyword_location: Option<CString>,
+    keyword: Option<CString>,
+}
+
+impl From<BatchError> for ErrorEntry {
+    fn from(error: BatchError) -> ErrorEntry {
+        ErrorEntry {
+            message: c_string(error.error()),
+            instance_location: c_string(error.instance_location()),
+            keyword_location: error.keyword_location().map(c_string),
+            keyword: error.keyword().map(c_string),
+        }
+    }
+}
+
+/// Strings from JSON documents may contain NUL characters, which C strings can't.
+fn c_string(value: &str) -> CString {
+    CString::new(value.replace('\0', "\\u0000")).expect("NUL characters are escaped")
+}
+
+/// Panics must not unwind into foreign code.
+fn guard<T>(default: T, f: impl FnOnce() -> T) -> T {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or(default)
+}
+
+/// # Safety
+///
+/// `error` must be NULL or valid for writes.
+unsafe fn set_error(error: *mut *mut c_char, message: &str) {
+    if !error.is_null() {
+        *error = c_string(message).into_raw();
+    }
+}
+
+/// # Safety
+///
+/// `text` must be NULL or point to `len` readable bytes.
+unsafe fn parse(text: *const c_char, len: usize) -> Result<Value, String> {
+    if text.is_null() {
+        return Err("Unexpected NULL pointer".to_string());
+    }
+    let bytes = slice::from_raw_parts(text.cast::<u8>(), len);
+    serde_json::from_slice(bytes).map_err(|error| format!("Invalid JSON: {error}"))
+}
+
+/// The version of this library as a static NUL-terminated string.
+#[no_mangle]
+pub extern "C" fn jsonschema_version() -> *const c_char {
+    VERSION.as_ptr().cast()
+}
+
+/// Compile a schema, detecting its draft from `$schema`.
+///
+/// Returns NULL if the schema is not valid JSON or not a valid schema, and sets `*error` unless
+/// `error` is NULL.
+///
+/// # Safety
+///
+/// `schema` must point to `len` readable bytes and `error` must be NULL or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn jsonschema_validator_new(
+    schema: *const c_char,
+    len: usize,
+    error: *mut *mut c_char,
+) -> *mut JsonschemaValidator {
+    let result = guard(Err("Unexpected panic".to_string()), || {
+        let schema = parse(schema, len)?;
+        jsonschema::validator_for(&schema).map_err(|error| error.to_string())
+    });
+    match result {
+        Ok(validator) => Box::into_raw(Box::new(JsonschemaValidator(validator))),
+        Err(message) => {
+            set_error(error, &message);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Release a validator. Does nothing if `validator` is NULL.
+///
+/// # Safety
+///
+/// `validator` must be NULL or returned by [`jsonschema_validator_new`] and not released yet.
+#[no_mangle]
+pub unsafe extern "C" fn jsonschema_validator_free(validator: *mut JsonschemaValidator) {
+    if !validator.is_null() {
+        drop(Box::from_raw(validator));
+    }
+}
+
+/// Check whether an instance is valid.
+///
+/// Returns 1 if it is, 0 if it is not, and -1 if either argument is NULL or the instance is not
+/// valid JSON.
+///
+/// # Safety
+///
+/// `validator` must be NULL or a live validator, and `instance` must point to `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn jsonschema_is_valid(
+    validator: *const JsonschemaValidator,
+    instance: *const c_char,
+    len: usize,
+) -> c_int {
+    let Some(validator) = validator.as_ref() else {
+        return -1;
+    };
+    guard(-1, || match parse(instance, len) {
+        Ok(instance) => c_int::from(validator.0.is_valid(&instance)),
+        Err(_) => -1,
+    })
+}
+
+/// Validate an instance and collect all of its errors, which are empty if it is valid.
+///
+/// Returns NULL if either argument is NULL or the instance is not valid JSON, and sets `*error`
+/// unless `error` is NULL.
+///
+/// # Safety
+///
+/// `validator` must be NULL or a live validator, `instance` must point to `len` readable bytes,
+/// and `error` must be NULL or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn jsonschema_validate(
+    validator: *const JsonschemaValidator,
+    instance: *const c_char,
+    len: usize,
+    error: *mut *mut c_char,
+) -> *mut JsonschemaErrors {
+    let Some(validator) = validator.as_ref() else {
+        set_error(error, "Unexpected NULL pointer");
+        return ptr::null_mut();
+    };
+    let result = guard(Err("Unexpected panic".to_string()), || {
+        let instance = parse(instance, len)?;
+        Ok(validator
+            .0
+            .iter_errors(&instance)
+            .map(|error| ErrorEntry::from(BatchError::from(&error)))
+            .collect())
+    });
+    match result {
+        Ok(errors) => Box::into_raw(Box::new(JsonschemaErrors(errors))),
+        Err(message) => {
+            set_error(error, &message);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// The number of errors, or 0 if `errors` is NULL.
+///
+/// # Safety
+///
+/// `errors` must be NULL or returned by [`jsonschema_validate`] and not released yet.
+#[no_mangle]
+pub unsafe extern "C" fn jsonschema_errors_count(errors: *const JsonschemaErrors) -> usize {
+    errors.as_ref().map_or(0, |errors| errors.0.len())
+}
+
+/// # Safety
+///
+/// `errors` must be NULL or returned by [`jsonschema_validate`] and not released yet.
+unsafe fn field(
+    errors: *const JsonschemaErrors,
+    index: usize,
+    field: impl FnOnce(&ErrorEntry) -> Option<&CString>,
+) -> *const c_char {
+    errors
+        .as_ref()
+        .and_then(|errors| errors.0.get(index))
+        .and_then(field)
+        .map_or(ptr::null(), |value| value.as_ptr())
+}
+
+/// The message of the error at `index`, or NULL if it is out of range.
+///
+/// The string is owned by `errors` and valid until they are released.
+///
+/// # Safety
+///
+/// `errors` must be NULL or returned by [`jsonschema_validate`] and not released yet.
+#[no_mangle]
+pub unsafe extern "C" fn jsonschema_errors_message(
+    errors: *const JsonschemaErrors,
+    index: usize,
+) -> *const c_char {
+    field(errors, index, |error| Some(&error.message))
+}
+
+/// JSON Pointer to the invalid part of the instance for the error at `index`, or NULL if it is
+/// out of range.
+///
+/// The string is owned by `errors` and valid until they are released.
+///
+/// # Safety
+///
+/// `errors` must be NULL or returned by [`jsonschema_validate`] and not released yet.
+#[no_mangle]
+pub unsafe extern "C" fn jsonschema_errors_instance_location(
+    errors: *const JsonschemaErrors,
+    index: usize,
+) -> *const c_char {
+    field(errors, index, |error| Some(&error.instance_location))
+}
+
+/// JSON Pointer to the schema keyword that caused the error at `index`, or NULL if it is out of
+/// range.
+///
+/// The string is owned by `errors` and valid until they are released.
+///
+/// # Safety
+///
+/// `errors` must be NULL or returned by [`jsonschema_validate`] and not released yet.
+#[no_mangle]
+pub unsafe extern "C" fn jsonschema_errors_keyword_location(
+    errors: *const JsonschemaErrors,
+    index: usize,
+) -> *const c_char {
+    field(errors, index, |error| error.keyword_location.as_ref())
+}
+
+/// The name of the schema keyword that caused the error at `index`, e.g. `type`, or NULL if it
+/// is out of range or the error is caused by a `false` subschema.
+///
+/// The string is owned by `errors` and valid until they are released.
+///
+/// # Safety
+///
+/// `errors` must be NULL or returned by [`jsonschema_validate`] and not released yet.
+#[no_mangle]
+pub unsafe extern "C" fn jsonschema_errors_keyword(
+    errors: *const JsonschemaErrors,
+    index: usize,
+) -> *const c_char {
+    field(errors, index, |error| error.keyword.as_ref())
+}
+
+/// Release errors. Does nothing if `errors` is NULL.
+///
+/// # Safety
+///
+/// `errors` must be NULL or returned by [`jsonschema_validate`] and not released yet.
+#[no_mangle]
+pub unsafe extern "C" fn jsonschema_errors_free(errors: *mut JsonschemaErrors) {
+    if !errors.is_null() {
+        drop(Box::from_raw(errors));
+    }
+}
+
+/// Release a string returned through an `error` out-parameter. Does nothing if `string` is NULL.
+///
+/// # Safety
+///
+/// `string` must be NULL or returned by this library and not released yet.
+#[no_mangle]
+pub unsafe extern "C" fn jsonschema_string_free(string: *mut c_char) {
+    if !string.is_null() {
+        drop(CString::from_raw(string));
+    }
+}
+
+/// Allocate `len` bytes, e.g. for a WebAssembly host to write JSON texts into linear memory.
+///
+/// Returns NULL if `len` is 0.
+#[no_mangle]
+pub extern "C" fn jsonschema_alloc(len: usize) -> *mut c_char {
+    if len == 0 {
+        return ptr::null_mut();
+    }
+    let mut buffer = Vec::<u8>::with_capacity(len);
+    let pointer = buffer.as_mut_ptr();
+    mem::forget(buffer);
+    pointer.cast()
+}
+
+/// Release memory allocated by [`jsonschema_alloc`]. Does nothing if `pointer` is NULL.
+///
+/// # Safety
+///
+/// `pointer` must be NULL or returned by [`jsonschema_alloc`] with the same `len` and not
+/// released yet.
+#[no_mangle]
+pub unsafe extern "C" fn jsonschema_dealloc(pointer: *mut c_char, len: usize) {
+    if !pointer.is_null() {
+        drop(Vec::from_raw_parts(pointer.cast::<u8>(), 0, len));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        ffi::{c_char, CStr},
+        ptr,
+    };
+
+    use super::*;
+
+    fn to_str<'a>(value: *const c_char) -> Option<&'a str> {
+        (!value.is_null()).then(|| {
+            unsafe { CStr::from_ptr(value) }
+                .to_str()
+                .expect("Valid UTF-8")
+        })
+    }
+
+    fn compile(schema: &str) -> *mut JsonschemaValidator {
+        unsafe { jsonschema_validator_new(schema.as_ptr().cast(), schema.len(), ptr::null_mut()) }
+    }
+
+    #[test]
+    fn validate() {
+        let validator =
+            compile(r#"{"properties": {"name": {"type": "string"}}, "required": ["id"]}"#);
+        assert!(!validator.is_null());
+        let valid = r#"{"id": 1, "name": "a"}"#;
+        let invalid = r#"{"name": 1}"#;
+        unsafe {
+            assert_eq!(
+                jsonschema_is_valid(validator, valid.as_ptr().cast(), valid.len()),
+                1
+            );
+            assert_eq!(
+                jsonschema_is_valid(validator, invalid.as_ptr().cast(), invalid.len()),
+                0
+            );
+            let errors = jsonschema_validate(
+                validator,
+                invalid.as_ptr().cast(),
+                invalid.len(),
+                ptr::null_mut(),
+            );
+            assert_eq!(jsonschema_errors_count(errors), 2);
+            let mut messages = Vec::new();
+            for index in 0..2 {
+                messages.push((
+                    to_str(jsonschema_errors_message(errors, index)),
+                    to_str(jsonschema_errors_instance_location(errors, index)),
+                    to_str(jsonschema_errors_keyword_location(errors, index)),
+                    to_str(jsonschema_errors_keyword(errors, index)),
+                ));
+            }
+            messages.sort_unstable();
+            assert_eq!(
+                messages,
+                [
+                    (
+                        Some("\"id\" is a required property"),
+                        Some(""),
+                        Some("/required"),
+                        Some("required")
+                    ),
+                    (
+                        Some("1 is not of type \"string\""),
+                        Some("/name"),
+                        Some("/properties/name/type"),
+                        Some("type")
+                    ),
+                ]
+            );
+            assert!(jsonschema_errors_message(errors, 2).is_null());
+            jsonschema_errors_free(errors);
+            jsonschema_validator_free(validator);
+        }
+    }
+
+    #[test]
+    fn invalid_schema() {
+        let schema = r#"{"type": 42}"#;
+        let mut error = ptr::null_mut();
+        unsafe {
+            let validator =
+                jsonschema_validator_new(schema.as_ptr().cast(), schema.len(), &mut error);
+            assert!(validator.is_null());
+            assert!(to_str(error).is_some_and(|error| error.contains("is not valid")));
+            jsonschema_string_free(error);
+        }
+    }
+
+    #[test]
+    fn invalid_json() {
+        let validator = compile("{}");
+        let instance = "{";
+        let mut error = ptr::null_mut();
+        unsafe {
+            assert_eq!(
+                jsonschema_is_valid(validator, instance.as_ptr().cast(), instance.len()),
+                -1
+            );
+            let errors = jsonschema_validate(
+                validator,
+                instance.as_ptr().cast(),
+                instance.len(),
+                &mut error,
+            );
+            assert!(errors.is_null());
+            assert!(to_str(error).is_some_and(|error| error.starts_with("Invalid JSON")));
+            jsonschema_string_free(error);
+            assert_eq!(
+                jsonschema_is_valid(ptr::null(), instance.as_ptr().cast(), 1),
+                -1
+            );
+            jsonschema_validator_free(validator);
+        }
+    }
+
+    #[test]
+    fn nul_characters() {
+        let validator = compile(r#"{"additionalProperties": {"type": "integer"}}"#);
+        let instance = r#"{"a\u0000": "b"}"#;
+        unsafe {
+            let errors = jsonschema_validate(
+                validator,
+                instance.as_ptr().cast(),
+                instance.len(),
+                ptr::null_mut(),
+            );
+            assert_eq!(
+                to_str(jsonschema_errors_instance_location(errors, 0)),
+                Some("/a\\u0000")
+            );
+            jsonschema_errors_free(errors);
+            jsonschema_validator_free(validator);
+        }
+    }
+
+    #[test]
+    fn allocation() {
+        let buffer = jsonschema_alloc(16);
+        assert!(!buffer.is_null());
+        unsafe { jsonschema_dealloc(buffer, 16) };
+        assert!(jsonschema_alloc(0).is_null());
+        assert_eq!(
+            to_str(jsonschema_version()),
+            Some(env!("CARGO_PKG_VERSION"))
+        );
+    }
+}
@@ -0,0 +1,264 @@
+//! The `bundle` subcommand, embedding the documents a schema references into the schema itself.
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    error::Error,
+};
+
+use jsonschema::analyze::REFERENCE_KEYWORDS;
+use referencing::{uri, Draft, Registry, Resource, Retrieve, Uri};
+use serde_json::{Map, Value};
+
+use crate::pointer::push_location;
+
+/// Turn `schema`, located at `base_uri`, into a compound schema document following the 2020-12
+/// bundling process.
+///
+/// Every document it references, directly or via other referenced documents, is embedded in
+/// `$defs` (`definitions` before 2019-09) under its canonical URI, which it keeps as `$id`.
+/// Local files are identified relative to the root document instead, so the bundle does not
+/// depend on where they are located on this machine. References to other documents are rewritten
+/// to these identifiers, so they resolve to the embedded resources wherever the bundle is
+/// located.
+pub(crate) fn bundle(
+    schema: Value,
+    base_uri: &str,
+    draft: Option<Draft>,
+    retriever: impl Retrieve + 'static,
+    resources: Vec<(String, Resource)>,
+) -> Result<Value, Box<dyn Error>> {
+    let draft = match draft {
+        Some(draft) => draft,
+        None => Draft::default().detect(&schema)?,
+    };
+    let base = uri::from_str(base_uri)?;
+    let registry = Registry::options()
+        .retriever(retriever)
+        .draft(draft)
+        .build(
+            resources
+                .into_iter()
+                .chain([(base_uri.to_string(), draft.create_resource(schema.clone()))]),
+        )?;
+    let root = canonical_uri(&schema, &base, draft)?;
+    let mut bundler = Bundler {
+        registry: &registry,
+        base_uri,
+        root: root.clone(),
+        canonical: HashMap::new(),
+        embedded: BTreeMap::new(),
+    };
+    let mut schema = schema;
+    for uri in [&base, &root] {
+        bundler
+            .canonical
+            .insert(uri.as_str().to_string(), root.clone());
+    }
+    bundler.rewrite_document(&mut schema, &root, draft)?;
+    if bundler.embedded.is_empty() {
+        return Ok(schema);
+    }
+    let Value::Object(object) = &mut schema else {
+        // Boolean schemas do not reference anything
+        return Ok(schema);
+    };
+    let definitions = if draft >= Draft::Draft201909 {
+        "$defs"
+    } else {
+        "definitions"
+    };
+    let Value::Object(definitions) = object
+        .entry(definitions)
+        .or_insert_with(|| Value::Object(Map::new()))
+    else {
+        return Err(format!("`{definitions}` is not an object").into());
+    };
+    for (uri, contents) in bundler.embedded {
+        definitions.insert(uri, contents);
+    }
+    Ok(schema)
+}
+
+struct Bundler<'r> {
+    registry: &'r Registry,
+    base_uri: &'r str,
+    /// Canonical URI of the root document.
+    root: Uri<String>,
+    /// Canonical URIs of the documents seen so far, by the URIs they are referenced with.
+    canonical: HashMap<String, Uri<String>>,
+    /// Embedded documents by their identifier in the bundle.
+    embedded: BTreeMap<String, Value>,
+}
+
+impl Bundler<'_> {
+    /// Rewrite references to other documents in `document`, identified by `uri`, to the
+    /// identifiers of these documents in the bundle and embed them.
+    fn rewrite_document(
+        &mut self,
+        document: &mut Value,
+        uri: &Uri<String>,
+        draft: Draft,
+    ) -> Result<(), Box<dyn Error>> {
+        let mut resources = HashSet::from([uri.as_str().to_string()]);
+        let mut references = Vec::new();
+        collect(
+            document,
+            uri,
+            draft,
+            &mut String::new(),
+            &mut resources,
+            &mut references,
+        )?;
+        for (location, base, resolved) in references {
+            let target = without_fragment(&resolved)?;
+            if resources.contains(target.as_str()) {
+                continue;
+            }
+            let canonical = self.embed(target.as_str())?;
+            let canonical =
+                relative_to(&base, &canonical).unwrap_or_else(|| canonical.as_str().to_string());
+            if let Some(reference) = document.pointer_mut(&location) {
+                *reference = Value::String(match resolved.fragment() {
+                    Some(fragment) => format!("{canonical}#{}", fragment.as_str()),
+                    None => canonical,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Embed the document at `uri` unless it already is, returning its canonical URI.
+    fn embed(&mut self, uri: &str) -> Result<Uri<String>, Box<dyn Error>> {
+        if let Some(canonical) = self.canonical.get(uri) {
+            return Ok(canonical.clone());
+        }
+        let resolved = self.registry.try_resolver(self.base_uri)?.lookup(uri)?;
+        let draft = resolved.draft();
+        let mut document = match resolved.contents() {
+            // Boolean documents cannot declare their URI
+            Value::Bool(true) => Value::Object(Map::new()),
+            Value::Bool(false) => serde_json::json!({"not": {}}),
+            contents => contents.clone(),
+        };
+        let canonical = canonical_uri(&document, &uri::from_str(uri)?, draft)?;
+        self.canonical.insert(uri.to_string(), canonical.clone());
+        // Embedded identifiers are resolved against the root document
+        let key =
+            relative_to(&self.root, &canonical).unwrap_or_else(|| canonical.as_str().to_string());
+        if self.embedded.contains_key(&key) {
+            return Ok(canonical);
+        }
+        // Documents may reference each other, so they are registered before being rewritten
+        self.embedded.insert(key.clone(), Value::Null);
+        self.rewrite_document(&mut document, &canonical, draft)?;
+        if let Value::Object(object) = &mut document {
+            object.insert(id_keyword(draft).to_string(), Value::String(key.clone()));
+        }
+        self.embedded.insert(key, document);
+        Ok(canonical)
+    }
+}
+
+/// Add the URIs of the resources in `value`, located at `location` in its document, to
+/// `resources`, and the locations of its references with their base and resolved URIs to
+/// `references`.
+fn collect(
+    value: &Value,
+    base: &Uri<String>,
+    draft: Draft,
+    location: &mut String,
+    resources: &mut HashSet<String>,
+    references: &mut Vec<(String, Uri<String>, Uri<String>)>,
+) -> Result<(), Box<dyn Error>> {
+    let Some(object) = value.as_object() else {
+        return Ok(());
+    };
+    let base = match draft.id_of(value) {
+        Some(id) => {
+            let uri = without_fragment(&uri::resolve_against(&base.borrow(), id)?)?;
+            resources.insert(uri.as_str().to_string());
+            uri
+        }
+        None => base.clone(),
+    };
+    for &keyword in REFERENCE_KEYWORDS {
+        if let Some(reference) = object.get(keyword).and_then(Value::as_str) {
+            references.push((
+                format!("{location}/{keyword}"),
+                base.clone(),
+                uri::resolve_against(&base.borrow(), reference)?,
+            ));
+        }
+    }
+    for subresource in draft.subresources_of(value) {
+        let length = location.len();
+        push_location(object, subresource, location);
+        collect(subresource, &base, draft, location, resources, references)?;
+        location.truncate(length);
+    }
+    Ok(())
+}
+
+/// The URI a document identifies itself with, or the one it was retrieved from.
+fn canonical_uri(
+    document: &Value,
+    retrieved: &Uri<String>,
+    draft: Draft,
+) -> Result<Uri<String>, Box<dyn Error>> {
+    match draft.id_of(document) {
+        Some(id) => without_fragment(&uri::resolve_against(&retrieved.borrow(), id)?),
+        None => Ok(retrieved.clone()),
+    }
+}
+
+fn id_keyword(draft: Draft) -> &'static str {
+    if draft == Draft::Draft4 {
+        "id"
+    } else {
+        "$id"
+    }
+}
+
+/// `target` as a reference relative to `base` if both are local files, so that bundles do not
+/// contain paths on the machine they were created on.
+fn relative_to(base: &Uri<String>, target: &Uri<String>) -> Option<String> {
+    let is_file = |uri: &Uri<String>| uri.scheme().as_str() == "file" && uri.query().is_none();
+    if !is_file(base)
+        || !is_file(target)
+        || base.authority().map(|authority| authority.as_str())
+            != target.authority().map(|authority| authority.as_str())
+    {
+        return None;
+    }
+    let base = base.path().as_str();
+    let directories: Vec<&str> = base[..base.rfind('/')?].split('/').collect();
+    let segments: Vec<&str> = target.path().as_str().split('/').collect();
+    let (file, target_directories) = segments.split_last()?;
+    let common = directories
+        .iter()
+        .zip(target_directories)
+        .take_while(|(base, target)| base == target)
+        .count();
+    let mut relative = "../".repeat(directories.len() - common);
+    for directory in &target_directories[common..] {
+        relative.push_str(directory);
+        relative.push('/');
+    }
+    relative.push_str(file);
+    // A leading segment with a colon would be read as a scheme
+    if relative
+        .split('/')
+        .next()
+        .is_some_and(|segment| segment.contains(':'))
+    {
+        relative.insert_str(0, "./");
+    }
+    Some(relative)
+}
+
+fn without_fragment(uri: &Uri<String>) -> Result<Uri<String>, Box<dyn Error>> {
+    let string = uri.as_str();
+    let document = string
+        .split_once('#')
+        .map_or(string, |(document, _)| document);
+    Ok(uri::from_str(document)?)
+}
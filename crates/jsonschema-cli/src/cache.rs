@@ -0,0 +1,111 @@
+//! On-disk cache of remote documents, used with `--allow-remote`.
+use std::{
+    env,
+    fmt::Write as _,
+    fs, io,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use jsonschema::{DefaultRetriever, Retrieve, Uri};
+use ring::digest::{digest, SHA256};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// How long cached documents are used without `--cache-ttl`.
+pub(crate) const DEFAULT_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Remote documents stored as files named after the SHA-256 hash of their URIs.
+#[derive(Clone)]
+pub(crate) struct DiskCache {
+    dir: PathBuf,
+    ttl: Duration,
+    /// Whether documents are only read from the cache, regardless of their age.
+    offline: bool,
+}
+
+impl DiskCache {
+    /// A cache in `$XDG_CACHE_HOME/jsonschema`, or in `~/.cache/jsonschema` if unset, and `None`
+    /// if there is no home directory either.
+    pub(crate) fn new(ttl: Duration, offline: bool) -> Option<DiskCache> {
+        let base = env::var_os("XDG_CACHE_HOME")
+            .map(PathBuf::from)
+            .filter(|path| path.is_absolute())
+            .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".cache")))?;
+        Some(DiskCache {
+            dir: base.join("jsonschema"),
+            ttl,
+            offline,
+        })
+    }
+
+    fn path(&self, uri: &Uri<String>) -> PathBuf {
+        let mut name = String::with_capacity(69);
+        for byte in digest(&SHA256, uri.as_str().as_bytes()).as_ref() {
+            let _ = write!(name, "{byte:02x}");
+        }
+        name.push_str(".json");
+        self.dir.join(name)
+    }
+
+    /// A cached document that is recent enough, or any cached one when offline.
+    fn read(&self, path: &Path, uri: &Uri<String>) -> Option<Value> {
+        let modified = fs::metadata(path).and_then(|metadata| metadata.modified());
+        let fresh = self.offline
+            || modified.is_ok_and(|modified| {
+                SystemTime::now()
+                    .duration_since(modified)
+                    .map_or(true, |age| age < self.ttl)
+            });
+        if !fresh {
+            return None;
+        }
+        let entry: Entry = serde_json::from_slice(&fs::read(path).ok()?).ok()?;
+        // Guards against files written by other tools or older versions under the same name
+        (entry.uri == uri.as_str()).then_some(entry.document)
+    }
+
+    /// Write via a temporary file, so concurrent runs never read partial documents.
+    fn write(&self, path: &Path, uri: &Uri<String>, document: &Value) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let temporary = path.with_extension(format!("{}.tmp", std::process::id()));
+        let entry = EntryRef {
+            uri: uri.as_str(),
+            document,
+        };
+        fs::write(&temporary, serde_json::to_vec(&entry)?)?;
+        fs::rename(temporary, path)
+    }
+}
+
+/// A cached document along with the URI it was retrieved from.
+#[derive(Deserialize)]
+struct Entry {
+    uri: String,
+    document: Value,
+}
+
+#[derive(Serialize)]
+struct EntryRef<'a> {
+    uri: &'a str,
+    document: &'a Value,
+}
+
+impl Retrieve for DiskCache {
+    fn retrieve(
+        &self,
+        uri: &Uri<String>,
+    ) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+        let path = self.path(uri);
+        if let Some(document) = self.read(&path, uri) {
+            return Ok(document);
+        }
+        if self.offline {
+            return Err("the document is not cached and `--offline` forbids retrieving it".into());
+        }
+        let document = DefaultRetriever.retrieve(uri)?;
+        // Failing to cache a document does not prevent using it
+        let _ = self.write(&path, uri, &document);
+        Ok(document)
+    }
+}
@@ -0,0 +1,96 @@
+//! ANSI colors of the text output, enabled with `--color`.
+use serde_json::Value;
+use std::io;
+
+/// Maximum number of characters of instance value excerpts.
+const EXCERPT_LENGTH: usize = 40;
+
+#[derive(Clone, Copy)]
+pub(crate) enum Style {
+    Valid,
+    Invalid,
+    Location,
+    Keyword,
+    Excerpt,
+}
+
+impl Style {
+    fn code(self) -> &'static str {
+        match self {
+            Style::Valid => "1;32",
+            Style::Invalid => "1;31",
+            Style::Location => "36",
+            Style::Keyword => "33",
+            Style::Excerpt => "2",
+        }
+    }
+}
+
+/// Wrap `text` in the escape sequences of `style`.
+pub(crate) fn paint(text: &str, style: Style) -> String {
+    format!("\x1b[{}m{text}\x1b[0m", style.code())
+}
+
+/// Compact JSON of `value`, shortened to [`EXCERPT_LENGTH`] characters.
+pub(crate) fn excerpt(value: &Value) -> String {
+    let mut excerpt = Excerpt::default();
+    // Fails once the excerpt is full, so the rest of large values is not serialized
+    let _ = serde_json::to_writer(&mut excerpt, value);
+    let mut text = String::from_utf8(excerpt.bytes).expect("Serialized JSON is valid UTF-8");
+    if excerpt.truncated {
+        text.push('…');
+    }
+    text
+}
+
+/// Collects the first [`EXCERPT_LENGTH`] characters written to it and rejects the rest.
+#[derive(Default)]
+struct Excerpt {
+    bytes: Vec<u8>,
+    chars: usize,
+    truncated: bool,
+}
+
+impl io::Write for Excerpt {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        for &byte in buf {
+            // Continuation bytes do not start a character
+            if byte & 0xC0 != 0x80 {
+                if self.chars == EXCERPT_LENGTH {
+                    self.truncated = true;
+                    return Err(io::Error::new(io::ErrorKind::Other, "Excerpt is full"));
+                }
+                self.chars += 1;
+            }
+            self.bytes.push(byte);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::excerpt;
+    use serde_json::json;
+
+    #[test]
+    fn excerpts() {
+        assert_eq!(excerpt(&json!({"a": 1})), r#"{"a":1}"#);
+        assert_eq!(
+            excerpt(&json!("é".repeat(50))),
+            format!("\"{}…", "é".repeat(39))
+        );
+        assert_eq!(
+            excerpt(&json!("é".repeat(38))),
+            format!("\"{}\"", "é".repeat(38))
+        );
+        assert_eq!(
+            excerpt(&json!(vec![1; 10_000])),
+            format!("[{}…", "1,".repeat(19) + "1")
+        );
+    }
+}
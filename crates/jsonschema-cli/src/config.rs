@@ -0,0 +1,148 @@
+//! Configuration file associating instance files with schemas.
+//!
+//! ```toml
+//! [schemas]
+//! "k8s/**/*.json" = "schemas/k8s.json"
+//! "*.json" = "schemas/default.json"
+//! ```
+//!
+//! Globs match instance paths relative to the instance directory, and the first matching rule
+//! wins. Schema paths are relative to the configuration file.
+//!
+//! The same rules can be given as a JSON schema map with `--schema-map`, where schemas may also
+//! be URIs:
+//!
+//! ```json
+//! {"k8s/**/*.json": "https://example.com/k8s.json", "*.json": "schemas/default.json"}
+//! ```
+use std::{
+    error::Error,
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use globset::{GlobBuilder, GlobMatcher};
+use serde::{
+    de::{MapAccess, Visitor},
+    Deserialize, Deserializer,
+};
+
+/// Name of the configuration file looked up in the instance directory.
+pub(crate) const DEFAULT_CONFIG: &str = "jsonschema.toml";
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawConfig {
+    #[serde(default)]
+    schemas: toml::Table,
+}
+
+/// The members of a schema map, in file order, as they decide which rule matches first.
+struct SchemaMap(Vec<(String, serde_json::Value)>);
+
+impl<'de> Deserialize<'de> for SchemaMap {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct MapVisitor;
+
+        impl<'de> Visitor<'de> for MapVisitor {
+            type Value = SchemaMap;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("an object mapping globs to schemas")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<SchemaMap, A::Error> {
+                let mut members = Vec::new();
+                while let Some(member) = map.next_entry()? {
+                    members.push(member);
+                }
+                Ok(SchemaMap(members))
+            }
+        }
+
+        deserializer.deserialize_map(MapVisitor)
+    }
+}
+
+struct Rule {
+    matcher: GlobMatcher,
+    schema: PathBuf,
+}
+
+impl Rule {
+    fn new(pattern: &str, schema: PathBuf) -> Result<Rule, Box<dyn Error>> {
+        let matcher = GlobBuilder::new(pattern)
+            .literal_separator(true)
+            .build()
+            .map_err(|error| format!("Invalid glob `{pattern}`: {error}"))?
+            .compile_matcher();
+        Ok(Rule { matcher, schema })
+    }
+}
+
+pub(crate) struct Config {
+    rules: Vec<Rule>,
+}
+
+impl Config {
+    pub(crate) fn from_path(path: &Path) -> Result<Config, Box<dyn Error>> {
+        let raw: RawConfig = toml::from_str(&fs::read_to_string(path)?)
+            .map_err(|error| format!("Invalid config file {}: {error}", path.display()))?;
+        let root = path.parent().unwrap_or_else(|| Path::new(""));
+        let mut rules = Vec::with_capacity(raw.schemas.len());
+        for (pattern, schema) in raw.schemas {
+            let Some(schema) = schema.as_str() else {
+                return Err(format!("Schema for `{pattern}` must be a path").into());
+            };
+            rules.push(Rule::new(&pattern, root.join(schema))?);
+        }
+        Ok(Config { rules })
+    }
+
+    /// Read the rules of a JSON schema map, whose schemas are URIs or paths relative to it.
+    pub(crate) fn from_schema_map(path: &Path) -> Result<Config, Box<dyn Error>> {
+        let SchemaMap(members) = serde_json::from_str(&fs::read_to_string(path)?)
+            .map_err(|error| format!("Invalid schema map {}: {error}", path.display()))?;
+        let root = path.parent().unwrap_or_else(|| Path::new(""));
+        let mut rules = Vec::with_capacity(members.len());
+        for (pattern, schema) in members {
+            let Some(schema) = schema.as_str() else {
+                return Err(format!("Schema for `{pattern}` must be a path or URI").into());
+            };
+            let schema = if schema_uri(Path::new(schema)).is_some() {
+                PathBuf::from(schema)
+            } else {
+                root.join(schema)
+            };
+            rules.push(Rule::new(&pattern, schema)?);
+        }
+        Ok(Config { rules })
+    }
+
+    /// The schema of the first rule matching `path`.
+    pub(crate) fn schema_for(&self, path: &Path) -> Option<&Path> {
+        let path = path.strip_prefix(".").unwrap_or(path);
+        self.rules
+            .iter()
+            .find(|rule| rule.matcher.is_match(path))
+            .map(|rule| rule.schema.as_path())
+    }
+
+    /// Schemas used by any rule.
+    pub(crate) fn schemas(&self) -> impl Iterator<Item = &Path> {
+        self.rules.iter().map(|rule| rule.schema.as_path())
+    }
+}
+
+/// The URI a schema map refers to a schema with, rather than a path.
+///
+/// Single-letter schemes are not accepted, as they are Windows drive letters.
+pub(crate) fn schema_uri(schema: &Path) -> Option<&str> {
+    let uri = schema.to_str()?;
+    let (scheme, _) = uri.split_once(':')?;
+    let mut chars = scheme.chars();
+    let valid = scheme.len() > 1
+        && chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+    valid.then_some(uri)
+}
@@ -0,0 +1,207 @@
+//! The `deref` subcommand, replacing every reference in a schema with the schema it points to.
+use std::{collections::HashSet, error::Error};
+
+use jsonschema::analyze::REFERENCE_KEYWORDS;
+use referencing::{Draft, Registry, Resolver, Resource, Retrieve};
+use serde_json::{Map, Value};
+
+use crate::pointer::push_segment;
+
+/// Keywords identifying resources or declaring anchors, removed from inlined copies of schemas
+/// as they would be duplicated wherever a schema is referenced more than once.
+const IDENTIFYING_KEYWORDS: [&str; 7] = [
+    "$id",
+    "id",
+    "$schema",
+    "$anchor",
+    "$dynamicAnchor",
+    "$recursiveAnchor",
+    "$vocabulary",
+];
+
+/// Turn `schema`, located at `base_uri`, into an equivalent schema without references.
+///
+/// Each `$ref`, `$dynamicRef` and `$recursiveRef` is replaced with a copy of the schema it
+/// resolves to statically, which is dereferenced in turn. Before 2019-09, keywords next to `$ref`
+/// are ignored and dropped, afterwards the copy is added to `allOf`. Inlined copies lose their
+/// identifiers, anchors and definitions, which nothing refers to anymore.
+///
+/// Recursive schemas can not be expanded this way and fail, as do references nested deeper than
+/// `max_depth`.
+pub(crate) fn dereference(
+    schema: &Value,
+    base_uri: &str,
+    draft: Option<Draft>,
+    retriever: impl Retrieve + 'static,
+    resources: Vec<(String, Resource)>,
+    max_depth: usize,
+) -> Result<Value, Box<dyn Error>> {
+    let draft = match draft {
+        Some(draft) => draft,
+        None => Draft::default().detect(schema)?,
+    };
+    let registry = Registry::options()
+        .retriever(retriever)
+        .draft(draft)
+        .build(
+            resources
+                .into_iter()
+                .chain([(base_uri.to_string(), draft.create_resource(schema.clone()))]),
+        )?;
+    let mut dereferencer = Dereferencer {
+        max_depth,
+        expanding: Vec::new(),
+    };
+    dereferencer.expand(
+        schema,
+        &registry.try_resolver(base_uri)?,
+        draft,
+        &mut String::new(),
+        false,
+    )
+}
+
+struct Dereferencer {
+    max_depth: usize,
+    /// Schemas being inlined with the locations of their references, from the outermost one, to
+    /// detect recursion.
+    expanding: Vec<(*const Value, String)>,
+}
+
+impl Dereferencer {
+    /// A copy of `value`, located at `location` in the output, with its references inlined.
+    fn expand(
+        &mut self,
+        value: &Value,
+        resolver: &Resolver<'_>,
+        draft: Draft,
+        location: &mut String,
+        inlined: bool,
+    ) -> Result<Value, Box<dyn Error>> {
+        let Value::Object(object) = value else {
+            return Ok(value.clone());
+        };
+        let resolver = resolver.in_subresource(draft.create_resource_ref(value))?;
+        let subresources = draft
+            .subresources_of(value)
+            .map(|subresource| subresource as *const Value)
+            .collect::<HashSet<_>>();
+        let mut expanded = Map::new();
+        let mut target = None;
+        for (keyword, value) in object {
+            if REFERENCE_KEYWORDS.contains(&keyword.as_str()) {
+                if let Some(reference) = value.as_str() {
+                    target = Some(self.inline(reference, &resolver, location)?);
+                    continue;
+                }
+            }
+            if inlined
+                && (IDENTIFYING_KEYWORDS.contains(&keyword.as_str())
+                    || keyword == "$defs"
+                    || keyword == "definitions")
+            {
+                continue;
+            }
+            let length = location.len();
+            push_segment(location, keyword);
+            let value = self.expand_member(value, &subresources, &resolver, draft, location)?;
+            location.truncate(length);
+            expanded.insert(keyword.clone(), value);
+        }
+        Ok(match target {
+            None => Value::Object(expanded),
+            Some(target) if expanded.is_empty() || draft <= Draft::Draft7 => target,
+            Some(target) => {
+                match expanded
+                    .entry("allOf")
+                    .or_insert_with(|| Value::Array(Vec::new()))
+                {
+                    Value::Array(all_of) => all_of.push(target),
+                    _ => return Err(format!("`allOf` at `#{location}` is not an array").into()),
+                }
+                Value::Object(expanded)
+            }
+        })
+    }
+
+    /// A copy of the keyword value `value`, expanding the subresources it is or contains.
+    fn expand_member(
+        &mut self,
+        value: &Value,
+        subresources: &HashSet<*const Value>,
+        resolver: &Resolver<'_>,
+        draft: Draft,
+        location: &mut String,
+    ) -> Result<Value, Box<dyn Error>> {
+        if subresources.contains(&(value as *const Value)) {
+            return self.expand(value, resolver, draft, location, false);
+        }
+        let mut expand_nested = |segment: &str, nested: &Value, location: &mut String| {
+            if subresources.contains(&(nested as *const Value)) {
+                let length = location.len();
+                push_segment(location, segment);
+                let expanded = self.expand(nested, resolver, draft, location, false);
+                location.truncate(length);
+                expanded
+            } else {
+                Ok(nested.clone())
+            }
+        };
+        Ok(match value {
+            Value::Array(items) => Value::Array(
+                items
+                    .iter()
+                    .enumerate()
+                    .map(|(index, item)| expand_nested(&index.to_string(), item, location))
+                    .collect::<Result<_, _>>()?,
+            ),
+            Value::Object(members) => Value::Object(
+                members
+                    .iter()
+                    .map(|(name, member)| {
+                        Ok((name.clone(), expand_nested(name, member, location)?))
+                    })
+                    .collect::<Result<_, Box<dyn Error>>>()?,
+            ),
+            _ => value.clone(),
+        })
+    }
+
+    /// A dereferenced copy of the schema `reference` points to.
+    fn inline(
+        &mut self,
+        reference: &str,
+        resolver: &Resolver<'_>,
+        location: &str,
+    ) -> Result<Value, Box<dyn Error>> {
+        let resolved = resolver.lookup(reference)?;
+        let contents: *const Value = resolved.contents();
+        if let Some((_, outer)) = self
+            .expanding
+            .iter()
+            .find(|(expanding, _)| std::ptr::eq(*expanding, contents))
+        {
+            return Err(format!(
+                "Cannot dereference the recursive reference at `#{outer}`, the schema it points to refers to itself at `#{location}`"
+            )
+            .into());
+        }
+        if self.expanding.len() >= self.max_depth {
+            return Err(format!(
+                "Dereferencing `{reference}` at `#{location}` exceeds the maximum depth of {} nested references",
+                self.max_depth
+            )
+            .into());
+        }
+        self.expanding.push((contents, location.to_string()));
+        let expanded = self.expand(
+            resolved.contents(),
+            resolved.resolver(),
+            resolved.draft(),
+            &mut location.to_string(),
+            true,
+        );
+        self.expanding.pop();
+        expanded
+    }
+}
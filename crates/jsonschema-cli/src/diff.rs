@@ -0,0 +1,505 @@
+//! The `diff` subcommand, classifying the changes between two versions of a schema by whether
+//! instances valid against the old one stay valid against the new one.
+//!
+//! Keywords are compared at the same locations in both schemas, without following references.
+//! Changes whose effect cannot be determined this way, e.g. to `pattern` or `oneOf`, are
+//! considered breaking.
+use std::collections::BTreeSet;
+
+use jsonschema::analyze::{ANNOTATION_KEYWORDS, SCHEMA_KEYWORDS};
+use referencing::Draft;
+use serde_json::{json, Map, Value};
+
+use crate::pointer::push_segment;
+
+/// Keywords that reject more instances as their value grows.
+const LOWER_BOUNDS: [&str; 6] = [
+    "minimum",
+    "exclusiveMinimum",
+    "minLength",
+    "minItems",
+    "minContains",
+    "minProperties",
+];
+
+/// Keywords that reject more instances as their value shrinks.
+const UPPER_BOUNDS: [&str; 6] = [
+    "maximum",
+    "exclusiveMaximum",
+    "maxLength",
+    "maxItems",
+    "maxContains",
+    "maxProperties",
+];
+
+static TRUE: Value = Value::Bool(true);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Compatibility {
+    /// Some instances valid against the old schema may be invalid against the new one.
+    Breaking,
+    /// Instances valid against the old schema are valid against the new one.
+    Compatible,
+}
+
+impl Compatibility {
+    fn as_str(self) -> &'static str {
+        match self {
+            Compatibility::Breaking => "breaking",
+            Compatibility::Compatible => "compatible",
+        }
+    }
+}
+
+pub(crate) struct Change {
+    pub(crate) compatibility: Compatibility,
+    /// `added`, `removed`, or `changed`.
+    pub(crate) kind: &'static str,
+    /// JSON Pointer to the keyword or subschema in the schemas.
+    pub(crate) location: String,
+    pub(crate) message: String,
+}
+
+impl Change {
+    pub(crate) fn to_json(&self) -> Value {
+        json!({
+            "compatibility": self.compatibility.as_str(),
+            "change": self.kind,
+            "location": self.location,
+            "message": self.message,
+        })
+    }
+}
+
+impl std::fmt::Display for Change {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.compatibility.as_str(), self.message)
+    }
+}
+
+/// Compare `old` and `new`, interpreted according to `draft`, returning the changes ordered by
+/// location.
+pub(crate) fn diff(old: &Value, new: &Value, draft: Draft) -> Vec<Change> {
+    let mut differ = Differ {
+        draft,
+        changes: Vec::new(),
+    };
+    differ.schema(old, new, &mut String::new());
+    let mut changes = differ.changes;
+    changes.sort_by(|left, right| left.location.cmp(&right.location));
+    changes
+}
+
+struct Differ {
+    draft: Draft,
+    changes: Vec<Change>,
+}
+
+impl Differ {
+    fn record(&mut self, breaking: bool, kind: &'static str, location: &str, message: String) {
+        self.changes.push(Change {
+            compatibility: if breaking {
+                Compatibility::Breaking
+            } else {
+                Compatibility::Compatible
+            },
+            kind,
+            location: location.to_string(),
+            message,
+        });
+    }
+
+    fn schema(&mut self, old: &Value, new: &Value, location: &mut String) {
+        if old == new {
+            return;
+        }
+        let empty = Map::new();
+        let (old_object, new_object) = match (old, new) {
+            (_, Value::Bool(false)) => {
+                return self.record(
+                    true,
+                    "changed",
+                    location,
+                    "Schema changed to `false`".to_string(),
+                );
+            }
+            (Value::Bool(false), _) => {
+                return self.record(
+                    false,
+                    "changed",
+                    location,
+                    "Schema changed from `false`".to_string(),
+                );
+            }
+            (Value::Object(old), Value::Object(new)) => (old, new),
+            (Value::Object(old), _) => (old, &empty),
+            (_, Value::Object(new)) => (&empty, new),
+            _ => return,
+        };
+        let keywords: BTreeSet<&String> = old_object.keys().chain(new_object.keys()).collect();
+        for keyword in keywords {
+            let length = location.len();
+            push_segment(location, keyword);
+            self.keyword(
+                keyword,
+                old_object.get(keyword),
+                new_object.get(keyword),
+                (old_object, new_object),
+                location,
+            );
+            location.truncate(length);
+        }
+    }
+
+    fn keyword(
+        &mut self,
+        keyword: &str,
+        old: Option<&Value>,
+        new: Option<&Value>,
+        parents: (&Map<String, Value>, &Map<String, Value>),
+        location: &mut String,
+    ) {
+        if old == new {
+            return;
+        }
+        match keyword {
+            "properties" | "patternProperties" | "dependentSchemas" => {
+                self.subschemas(keyword, old, new, parents, location);
+            }
+            "$defs" | "definitions" => self.definitions(old, new, location),
+            "items" if old.is_some_and(Value::is_array) || new.is_some_and(Value::is_array) => {
+                self.branches(keyword, old, new, true, location);
+            }
+            // Absent subschemas are equivalent to `true`, except for these, whose absence
+            // differs from `true` or which have no validation semantics
+            keyword
+                if SCHEMA_KEYWORDS.contains(&keyword)
+                    && !matches!(keyword, "contains" | "contentSchema" | "if" | "not") =>
+            {
+                self.schema(old.unwrap_or(&TRUE), new.unwrap_or(&TRUE), location);
+            }
+            "contains" if old.is_some() && new.is_some() => {
+                self.schema(old.unwrap_or(&TRUE), new.unwrap_or(&TRUE), location);
+            }
+            "allOf" | "prefixItems" => self.branches(keyword, old, new, true, location),
+            "anyOf" => self.branches(keyword, old, new, false, location),
+            keyword if LOWER_BOUNDS.contains(&keyword) => {
+                self.bound(keyword, old, new, true, location);
+            }
+            keyword if UPPER_BOUNDS.contains(&keyword) => {
+                self.bound(keyword, old, new, false, location);
+            }
+            "required" => self.members(keyword, old, new, true, location),
+            "enum" if old.is_some() && new.is_some() => {
+                self.members(keyword, old, new, false, location);
+            }
+            "type" if old.is_some() && new.is_some() => self.types(old, new, location),
+            "multipleOf" => {
+                if let (Some(old), Some(new)) =
+                    (old.and_then(Value::as_f64), new.and_then(Value::as_f64))
+                {
+                    // Multiples of the old value are multiples of any of its divisors
+                    let relaxed = new != 0.0 && (old / new).fract() == 0.0;
+                    self.changed(keyword, old, new, !relaxed, location);
+                } else {
+                    self.generic(keyword, old, new, true, location);
+                }
+            }
+            "uniqueItems" => {
+                let enabled = |value: Option<&Value>| value == Some(&Value::Bool(true));
+                self.generic(keyword, old, new, enabled(new) && !enabled(old), location);
+            }
+            "dependentRequired" | "dependencies" => self.dependencies(keyword, old, new, location),
+            keyword
+                if ANNOTATION_KEYWORDS.contains(&keyword)
+                    || !self.draft.is_known_keyword(keyword) =>
+            {
+                self.generic(keyword, old, new, false, location);
+            }
+            _ => self.generic(keyword, old, new, new.is_some(), location),
+        }
+    }
+
+    /// Report a keyword that was added, removed, or changed as a whole.
+    fn generic(
+        &mut self,
+        keyword: &str,
+        old: Option<&Value>,
+        new: Option<&Value>,
+        breaking: bool,
+        location: &str,
+    ) {
+        let (kind, message) = match (old, new) {
+            (None, Some(new)) => ("added", format!("`{keyword}` added with {}", excerpt(new))),
+            (Some(old), None) => (
+                "removed",
+                format!("`{keyword}` removed, was {}", excerpt(old)),
+            ),
+            (Some(old), Some(new)) => (
+                "changed",
+                format!(
+                    "`{keyword}` changed from {} to {}",
+                    excerpt(old),
+                    excerpt(new)
+                ),
+            ),
+            (None, None) => return,
+        };
+        self.record(breaking, kind, location, message);
+    }
+
+    fn changed(&mut self, keyword: &str, old: f64, new: f64, breaking: bool, location: &str) {
+        self.record(
+            breaking,
+            "changed",
+            location,
+            format!("`{keyword}` changed from {old} to {new}"),
+        );
+    }
+
+    fn bound(
+        &mut self,
+        keyword: &str,
+        old: Option<&Value>,
+        new: Option<&Value>,
+        lower: bool,
+        location: &str,
+    ) {
+        if let (Some(old), Some(new)) = (old.and_then(Value::as_f64), new.and_then(Value::as_f64)) {
+            let breaking = if lower { new > old } else { new < old };
+            return self.changed(keyword, old, new, breaking, location);
+        }
+        // Draft 4 exclusive bounds are booleans, which only tighten the bound when `true`
+        let breaking = match new {
+            Some(Value::Bool(exclusive)) => *exclusive,
+            Some(_) => true,
+            None => false,
+        };
+        self.generic(keyword, old, new, breaking, location);
+    }
+
+    /// Compare the named subschemas of `properties` and similar keywords.
+    fn subschemas(
+        &mut self,
+        keyword: &str,
+        old: Option<&Value>,
+        new: Option<&Value>,
+        (old_parent, new_parent): (&Map<String, Value>, &Map<String, Value>),
+        location: &mut String,
+    ) {
+        let (Some(old), Some(new)) = (as_map(old), as_map(new)) else {
+            return self.generic(keyword, old, new, true, location);
+        };
+        let closed = |parent: &Map<String, Value>| {
+            keyword == "properties"
+                && parent.get("additionalProperties") == Some(&Value::Bool(false))
+        };
+        let names: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+        for name in names {
+            let length = location.len();
+            push_segment(location, name);
+            match (old.get(name), new.get(name)) {
+                (None, Some(_)) if closed(old_parent) => self.record(
+                    false,
+                    "added",
+                    location,
+                    format!("Property `{name}` is now allowed"),
+                ),
+                (Some(_), None) if closed(new_parent) => self.record(
+                    true,
+                    "removed",
+                    location,
+                    format!("Property `{name}` is no longer allowed"),
+                ),
+                (old, new) => self.schema(old.unwrap_or(&TRUE), new.unwrap_or(&TRUE), location),
+            }
+            location.truncate(length);
+        }
+    }
+
+    /// Compare definitions, which only constrain instances through references to them.
+    fn definitions(&mut self, old: Option<&Value>, new: Option<&Value>, location: &mut String) {
+        let empty = Map::new();
+        let old = as_map(old).unwrap_or(&empty);
+        let new = as_map(new).unwrap_or(&empty);
+        let names: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+        for name in names {
+            let length = location.len();
+            push_segment(location, name);
+            match (old.get(name), new.get(name)) {
+                (Some(old), Some(new)) => self.schema(old, new, location),
+                (None, _) => self.record(
+                    false,
+                    "added",
+                    location,
+                    format!("Definition `{name}` added"),
+                ),
+                (_, None) => self.record(
+                    false,
+                    "removed",
+                    location,
+                    format!("Definition `{name}` removed"),
+                ),
+            }
+            location.truncate(length);
+        }
+    }
+
+    /// Compare arrays of subschemas by position. Added branches of `allOf` tighten the schema,
+    /// while added branches of `anyOf` relax it.
+    fn branches(
+        &mut self,
+        keyword: &str,
+        old: Option<&Value>,
+        new: Option<&Value>,
+        conjunctive: bool,
+        location: &mut String,
+    ) {
+        let empty = Vec::new();
+        let (old, new) = match (old, new) {
+            (Some(Value::Array(old)), Some(Value::Array(new))) => (old, new),
+            (None, Some(Value::Array(new))) if conjunctive => (&empty, new),
+            (Some(Value::Array(old)), None) if conjunctive => (old, &empty),
+            _ => return self.generic(keyword, old, new, new.is_some(), location),
+        };
+        for idx in 0..old.len().max(new.len()) {
+            let length = location.len();
+            push_segment(location, &idx.to_string());
+            match (old.get(idx), new.get(idx)) {
+                (Some(old), Some(new)) => self.schema(old, new, location),
+                (old, new) if conjunctive => {
+                    self.schema(old.unwrap_or(&TRUE), new.unwrap_or(&TRUE), location);
+                }
+                (None, _) => self.record(
+                    false,
+                    "added",
+                    location,
+                    format!("`{keyword}` branch added"),
+                ),
+                (_, None) => self.record(
+                    true,
+                    "removed",
+                    location,
+                    format!("`{keyword}` branch removed"),
+                ),
+            }
+            location.truncate(length);
+        }
+    }
+
+    /// Compare arrays whose added members either tighten the schema, as for `required`, or
+    /// relax it, as for `enum`.
+    fn members(
+        &mut self,
+        keyword: &str,
+        old: Option<&Value>,
+        new: Option<&Value>,
+        tightening: bool,
+        location: &str,
+    ) {
+        let empty = Vec::new();
+        let (Some(old), Some(new)) = (
+            old.map_or(Some(&empty), Value::as_array),
+            new.map_or(Some(&empty), Value::as_array),
+        ) else {
+            return self.generic(keyword, old, new, true, location);
+        };
+        for value in new.iter().filter(|value| !old.contains(value)) {
+            self.record(
+                tightening,
+                "added",
+                location,
+                format!("`{keyword}` now includes {}", excerpt(value)),
+            );
+        }
+        for value in old.iter().filter(|value| !new.contains(value)) {
+            self.record(
+                !tightening,
+                "removed",
+                location,
+                format!("`{keyword}` no longer includes {}", excerpt(value)),
+            );
+        }
+    }
+
+    fn types(&mut self, old: Option<&Value>, new: Option<&Value>, location: &str) {
+        let (Some(old), Some(new)) = (old.and_then(type_names), new.and_then(type_names)) else {
+            return self.generic("type", old, new, true, location);
+        };
+        // Integers are numbers, so switching between them is a change of a single type
+        let covers = |types: &[&str], name: &str| {
+            types.contains(&name) || (name == "integer" && types.contains(&"number"))
+        };
+        for name in new.iter().filter(|name| !covers(&old, name)) {
+            self.record(
+                false,
+                "added",
+                location,
+                format!("`type` now includes \"{name}\""),
+            );
+        }
+        for name in old.iter().filter(|name| !covers(&new, name)) {
+            self.record(
+                true,
+                "removed",
+                location,
+                format!("`type` no longer includes \"{name}\""),
+            );
+        }
+    }
+
+    /// Compare `dependentRequired`, and `dependencies` which may contain both property names and
+    /// subschemas.
+    fn dependencies(
+        &mut self,
+        keyword: &str,
+        old: Option<&Value>,
+        new: Option<&Value>,
+        location: &mut String,
+    ) {
+        let empty = Map::new();
+        let (Some(old), Some(new)) = (
+            old.map_or(Some(&empty), Value::as_object),
+            new.map_or(Some(&empty), Value::as_object),
+        ) else {
+            return self.generic(keyword, old, new, true, location);
+        };
+        let names: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+        for name in names {
+            let length = location.len();
+            push_segment(location, name);
+            let (old, new) = (old.get(name), new.get(name));
+            if old.or(new).is_some_and(Value::is_array) {
+                self.members("required", old, new, true, location);
+            } else {
+                self.schema(old.unwrap_or(&TRUE), new.unwrap_or(&TRUE), location);
+            }
+            location.truncate(length);
+        }
+    }
+}
+
+fn as_map(value: Option<&Value>) -> Option<&Map<String, Value>> {
+    value.and_then(Value::as_object)
+}
+
+fn type_names(value: &Value) -> Option<Vec<&str>> {
+    match value {
+        Value::String(name) => Some(vec![name.as_str()]),
+        Value::Array(names) => names.iter().map(Value::as_str).collect(),
+        _ => None,
+    }
+}
+
+/// A compact rendering of `value` for messages, shortened if it is long.
+fn excerpt(value: &Value) -> String {
+    const MAX_LENGTH: usize = 60;
+    let text = value.to_string();
+    if text.chars().count() <= MAX_LENGTH {
+        text
+    } else {
+        let mut text: String = text.chars().take(MAX_LENGTH).collect();
+        text.push('…');
+        text
+    }
+}
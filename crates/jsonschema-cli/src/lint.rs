@@ -0,0 +1,443 @@
+//! The `lint` subcommand, flagging constructs that are allowed in a schema but likely do not do
+//! what its author intended.
+use std::fmt;
+
+use jsonschema::analyze::{
+    draft_name, ANNOTATION_KEYWORDS, DEFINITION_KEYWORDS, REFERENCE_KEYWORDS,
+};
+use percent_encoding::percent_decode_str;
+use referencing::Draft;
+use serde_json::{json, Map, Value};
+
+use crate::pointer::{push_location, push_segment};
+
+/// Keywords that only apply to instances of some types, with those types and their plural.
+const TYPE_SPECIFIC: [(&[&str], &[&str], &str); 4] = [
+    (
+        &[
+            "minimum",
+            "maximum",
+            "exclusiveMinimum",
+            "exclusiveMaximum",
+            "multipleOf",
+        ],
+        &["number", "integer"],
+        "numbers",
+    ),
+    (
+        &["minLength", "maxLength", "pattern"],
+        &["string"],
+        "strings",
+    ),
+    (
+        &[
+            "items",
+            "prefixItems",
+            "additionalItems",
+            "minItems",
+            "maxItems",
+            "uniqueItems",
+            "contains",
+            "minContains",
+            "maxContains",
+            "unevaluatedItems",
+        ],
+        &["array"],
+        "arrays",
+    ),
+    (
+        &[
+            "properties",
+            "patternProperties",
+            "additionalProperties",
+            "required",
+            "minProperties",
+            "maxProperties",
+            "propertyNames",
+            "dependencies",
+            "dependentRequired",
+            "dependentSchemas",
+            "unevaluatedProperties",
+        ],
+        &["object"],
+        "objects",
+    ),
+];
+
+/// Lower and upper bounds that cannot be satisfied if the lower one is greater, with the type of
+/// instances they apply to.
+const BOUNDS: [(&str, &str, &str); 5] = [
+    ("minimum", "maximum", "number"),
+    ("minLength", "maxLength", "string"),
+    ("minItems", "maxItems", "array"),
+    ("minContains", "maxContains", "array"),
+    ("minProperties", "maxProperties", "object"),
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Severity {
+    /// The construct makes the schema reject instances it most likely should accept.
+    Error,
+    /// The construct is ignored or unusual.
+    Warning,
+}
+
+impl Severity {
+    fn as_str(self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        }
+    }
+}
+
+pub(crate) struct Finding {
+    pub(crate) severity: Severity,
+    /// A stable identifier of the check, e.g. `unknown-keyword`.
+    pub(crate) rule: &'static str,
+    /// JSON Pointer to the keyword or subschema in the schema.
+    pub(crate) location: String,
+    pub(crate) message: String,
+}
+
+impl Finding {
+    pub(crate) fn to_json(&self) -> Value {
+        json!({
+            "severity": self.severity.as_str(),
+            "rule": self.rule,
+            "location": self.location,
+            "message": self.message,
+        })
+    }
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}[{}]: {}",
+            self.severity.as_str(),
+            self.rule,
+            self.message
+        )
+    }
+}
+
+/// Check `schema`, interpreted according to `draft`, returning the findings ordered by location.
+pub(crate) fn lint(schema: &Value, draft: Draft) -> Vec<Finding> {
+    let mut linter = Linter {
+        draft,
+        findings: Vec::new(),
+        references: Vec::new(),
+        definitions: Vec::new(),
+    };
+    if let Some(object) = schema.as_object() {
+        if !object.contains_key("$schema") {
+            linter.report(
+                Severity::Warning,
+                "missing-schema",
+                String::new(),
+                "The schema does not declare its draft in `$schema`".to_string(),
+            );
+        }
+    }
+    linter.walk(schema, &mut String::new(), 0);
+    linter.check_definitions();
+    let mut findings = linter.findings;
+    findings.sort_by(|left, right| left.location.cmp(&right.location));
+    findings
+}
+
+struct Linter {
+    draft: Draft,
+    findings: Vec<Finding>,
+    /// Decoded JSON Pointer fragments of all references in the document.
+    references: Vec<String>,
+    /// Locations of definitions without identifiers, with their pointers relative to the
+    /// resource containing them.
+    definitions: Vec<(String, String)>,
+}
+
+impl Linter {
+    fn report(
+        &mut self,
+        severity: Severity,
+        rule: &'static str,
+        location: String,
+        message: String,
+    ) {
+        self.findings.push(Finding {
+            severity,
+            rule,
+            location,
+            message,
+        });
+    }
+
+    /// Check the subschema at `location` and its subresources, where `resource` is the length of
+    /// the location of the closest resource containing it.
+    fn walk(&mut self, value: &Value, location: &mut String, resource: usize) {
+        let Some(object) = value.as_object() else {
+            return;
+        };
+        let resource = if self.draft.id_of(value).is_some() {
+            location.len()
+        } else {
+            resource
+        };
+        for &keyword in REFERENCE_KEYWORDS {
+            if let Some((_, fragment)) = object
+                .get(keyword)
+                .and_then(Value::as_str)
+                .and_then(|reference| reference.split_once('#'))
+            {
+                self.references.push(
+                    percent_decode_str(fragment)
+                        .decode_utf8_lossy()
+                        .into_owned(),
+                );
+            }
+        }
+        for &keyword in DEFINITION_KEYWORDS {
+            if keyword == "$defs" && self.draft < Draft::Draft201909 {
+                continue;
+            }
+            let Some(definitions) = object.get(keyword).and_then(Value::as_object) else {
+                continue;
+            };
+            for (name, definition) in definitions {
+                if is_identified(definition, self.draft) {
+                    continue;
+                }
+                let mut pointer = location.clone();
+                push_segment(&mut pointer, keyword);
+                push_segment(&mut pointer, name);
+                let relative = pointer[resource..].to_string();
+                self.definitions.push((pointer, relative));
+            }
+        }
+        self.check_keywords(object, location);
+        self.check_types(object, location);
+        self.check_satisfiable(object, location);
+        for subresource in self.draft.subresources_of(value) {
+            let length = location.len();
+            push_location(object, subresource, location);
+            self.walk(subresource, location, resource);
+            location.truncate(length);
+        }
+    }
+
+    fn check_keywords(&mut self, object: &Map<String, Value>, location: &str) {
+        let draft = self.draft;
+        for keyword in object.keys() {
+            let message = match keyword.as_str() {
+                "prefixItems" if draft < Draft::Draft202012 => format!(
+                    "`prefixItems` is not a keyword in {}, use an array of schemas in `items`",
+                    draft_name(draft)
+                ),
+                "additionalItems" if draft == Draft::Draft202012 => format!(
+                    "`additionalItems` is not a keyword in {}, use `items` next to `prefixItems`",
+                    draft_name(draft)
+                ),
+                // Keywords without validation semantics are not reported as unknown
+                keyword
+                    if !draft.is_known_keyword(keyword)
+                        && !ANNOTATION_KEYWORDS.contains(&keyword)
+                        && !DEFINITION_KEYWORDS.contains(&keyword)
+                        && keyword != "$vocabulary" =>
+                {
+                    format!("Unknown keyword `{keyword}` in {}", draft_name(draft))
+                }
+                _ => continue,
+            };
+            self.report(
+                Severity::Warning,
+                "unknown-keyword",
+                keyword_location(location, keyword),
+                message,
+            );
+        }
+        if draft == Draft::Draft202012 && object.get("items").is_some_and(Value::is_array) {
+            self.report(
+                Severity::Error,
+                "items-array",
+                keyword_location(location, "items"),
+                format!(
+                    "`items` must be a single schema in {}, use `prefixItems` for an array of schemas",
+                    draft_name(draft)
+                ),
+            );
+        }
+        let mut ignored = Vec::new();
+        if draft >= Draft::Draft7 && !object.contains_key("if") {
+            for keyword in ["then", "else"] {
+                if object.contains_key(keyword) {
+                    ignored.push((keyword, "without `if`".to_string()));
+                }
+            }
+        }
+        if draft < Draft::Draft202012
+            && object.contains_key("additionalItems")
+            && !object.get("items").is_some_and(Value::is_array)
+        {
+            ignored.push((
+                "additionalItems",
+                "unless `items` is an array of schemas".to_string(),
+            ));
+        }
+        if draft >= Draft::Draft201909 && !object.contains_key("contains") {
+            for keyword in ["minContains", "maxContains"] {
+                if object.contains_key(keyword) {
+                    ignored.push((keyword, "without `contains`".to_string()));
+                }
+            }
+        }
+        if draft <= Draft::Draft7 && object.contains_key("$ref") {
+            for keyword in object.keys() {
+                if draft.is_known_keyword(keyword)
+                    && !matches!(keyword.as_str(), "$ref" | "$schema" | "$id" | "id")
+                {
+                    ignored.push((keyword, format!("next to `$ref` in {}", draft_name(draft))));
+                }
+            }
+        }
+        for (keyword, reason) in ignored {
+            self.report(
+                Severity::Warning,
+                "ignored-keyword",
+                keyword_location(location, keyword),
+                format!("`{keyword}` is ignored {reason}"),
+            );
+        }
+    }
+
+    /// Report keywords that do not apply to any of the types the subschema allows.
+    fn check_types(&mut self, object: &Map<String, Value>, location: &str) {
+        let types: Vec<&str> = match object.get("type") {
+            Some(Value::String(name)) => vec![name.as_str()],
+            Some(Value::Array(names)) => names.iter().filter_map(Value::as_str).collect(),
+            _ => return,
+        };
+        for (keywords, applicable, plural) in TYPE_SPECIFIC {
+            if types.iter().any(|name| applicable.contains(name)) {
+                continue;
+            }
+            for keyword in keywords {
+                if object.contains_key(*keyword) {
+                    self.report(
+                        Severity::Warning,
+                        "type-mismatch",
+                        keyword_location(location, keyword),
+                        format!(
+                            "`{keyword}` only applies to {plural}, but `type` is {}",
+                            object["type"]
+                        ),
+                    );
+                }
+            }
+        }
+    }
+
+    /// Report keywords that no instance of the type they apply to can satisfy.
+    fn check_satisfiable(&mut self, object: &Map<String, Value>, location: &str) {
+        for (lower, upper, kind) in BOUNDS {
+            if let (Some(low), Some(high)) = (
+                object.get(lower).and_then(Value::as_f64),
+                object.get(upper).and_then(Value::as_f64),
+            ) {
+                if low > high {
+                    self.report(
+                        Severity::Error,
+                        "unsatisfiable",
+                        keyword_location(location, lower),
+                        format!(
+                            "`{lower}` ({}) is greater than `{upper}` ({}), so no {kind} is valid",
+                            object[lower], object[upper]
+                        ),
+                    );
+                }
+            }
+        }
+        if object
+            .get("enum")
+            .and_then(Value::as_array)
+            .is_some_and(Vec::is_empty)
+        {
+            self.report(
+                Severity::Error,
+                "unsatisfiable",
+                keyword_location(location, "enum"),
+                "`enum` is empty, so no value is valid".to_string(),
+            );
+        }
+        if let (Some(required), Some(properties)) = (
+            object.get("required").and_then(Value::as_array),
+            object.get("properties").and_then(Value::as_object),
+        ) {
+            for property in required.iter().filter_map(Value::as_str) {
+                if properties.get(property) == Some(&Value::Bool(false)) {
+                    self.report(
+                        Severity::Error,
+                        "unsatisfiable",
+                        keyword_location(location, "required"),
+                        format!(
+                            "`{property}` is required, but its schema in `properties` is `false`, so no object is valid"
+                        ),
+                    );
+                }
+            }
+        }
+    }
+
+    /// Report definitions that no reference in the document points to or into.
+    fn check_definitions(&mut self) {
+        let mut definitions = std::mem::take(&mut self.definitions);
+        definitions.sort();
+        let mut unreachable: Vec<String> = Vec::new();
+        for (pointer, relative) in definitions {
+            // Definitions nested in unreachable ones are only reported once
+            if unreachable
+                .iter()
+                .any(|parent| pointer.starts_with(&format!("{parent}/")))
+            {
+                continue;
+            }
+            let referenced = self.references.iter().any(|fragment| {
+                fragment == &relative || fragment.starts_with(&format!("{relative}/"))
+            });
+            if !referenced {
+                unreachable.push(pointer);
+            }
+        }
+        for pointer in unreachable {
+            self.report(
+                Severity::Warning,
+                "unreachable-subschema",
+                pointer,
+                "The definition is not referenced anywhere in the schema".to_string(),
+            );
+        }
+    }
+}
+
+/// Whether a subschema can be referenced by an identifier rather than by its location.
+fn is_identified(value: &Value, draft: Draft) -> bool {
+    value.as_object().is_some_and(|object| {
+        draft.id_of(value).is_some()
+            || ["$anchor", "$dynamicAnchor", "$recursiveAnchor"]
+                .iter()
+                .any(|keyword| object.contains_key(*keyword))
+            || ["$id", "id"].iter().any(|keyword| {
+                object
+                    .get(*keyword)
+                    .and_then(Value::as_str)
+                    .is_some_and(|id| id.starts_with('#'))
+            })
+    })
+}
+
+fn keyword_location(location: &str, keyword: &str) -> String {
+    let mut location = location.to_string();
+    push_segment(&mut location, keyword);
+    location
+}
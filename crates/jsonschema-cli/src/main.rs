@@ -1,35 +1,112 @@
-#![allow(clippy::print_stdout)]
+#![allow(clippy::print_stdout, clippy::print_stderr)]
 use std::{
-    fs::File,
-    io::BufReader,
-    path::{Path, PathBuf},
+    collections::{hash_map::Entry, BTreeMap, HashMap, HashSet},
+    env,
+    error::Error,
+    fs::{self, File},
+    io::{self, BufRead, BufReader, IsTerminal, Read, Write},
+    num::NonZeroUsize,
+    path::{Component, Path, PathBuf},
     process::ExitCode,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc, OnceLock,
+    },
+    thread,
+    time::{Duration, Instant},
 };
 
-use clap::{ArgAction, Parser, ValueEnum};
-use percent_encoding::{percent_encode, AsciiSet, CONTROLS};
+use clap::{ArgAction, Parser, Subcommand, ValueEnum};
+use globset::GlobBuilder;
+use percent_encoding::{percent_decode_str, percent_encode, AsciiSet, CONTROLS};
+use serde::Deserialize;
+use serde_json::json;
+use walkdir::WalkDir;
+
+use jsonschema::{
+    generate::Generator, BasicOutput, BatchError, DefaultRetriever, DocumentCache, Resource,
+    Retrieve, Uri,
+};
+
+use crate::{
+    cache::DiskCache,
+    color::Style,
+    config::{schema_uri, Config, DEFAULT_CONFIG},
+    messages::Catalog,
+    report::{Annotation, InstanceReport, Outcome, SchemaError},
+    timings::TimedRetriever,
+};
+
+mod bundle;
+mod cache;
+mod color;
+mod config;
+mod deref;
+mod diff;
+mod lint;
+mod messages;
+mod migrate;
+mod pointer;
+mod relaxed;
+mod report;
+mod strict;
+mod timings;
 
 #[derive(Parser)]
-#[command(name = "jsonschema")]
+#[command(name = "jsonschema", subcommand_negates_reqs = true)]
+#[allow(clippy::struct_excessive_bools)]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// A path to a JSON instance (i.e. filename.json) to validate (may be specified multiple times).
+    /// Use `-` to read the instance from stdin.
+    /// Glob patterns (i.e. 'data/**/*.json') are expanded to the matching files in file name order.
     #[arg(short = 'i', long = "instance")]
     instances: Option<Vec<PathBuf>>,
 
-    /// The JSON Schema to validate with (i.e. schema.json).
-    #[arg(value_parser, required_unless_present("version"))]
+    /// A directory to search for JSON instances recursively.
+    #[arg(long = "instance-dir")]
+    instance_dir: Option<PathBuf>,
+
+    /// A config file associating instance files with schemas (defaults to `jsonschema.toml` in the instance directory).
+    #[arg(long = "config")]
+    config: Option<PathBuf>,
+
+    /// A JSON file mapping instance path globs to the schema paths or URIs to validate them with.
+    /// The first matching glob wins, and each schema is compiled once.
+    #[arg(long = "schema-map", conflicts_with = "config")]
+    schema_map: Option<PathBuf>,
+
+    /// The JSON Schema to validate with (i.e. schema.json). Use `-` to read the schema from stdin.
+    #[arg(
+        value_parser,
+        required_unless_present_any(["version", "instance_dir", "schema_map", "batch"])
+    )]
     schema: Option<PathBuf>,
 
+    /// A directory of schemas to search recursively for `.json`, `.yaml`, and `.yml` files, which
+    /// `$ref`s can then refer to by their `$id`. Can be repeated.
+    #[arg(long = "schema-dir", global = true)]
+    schema_dirs: Vec<PathBuf>,
+
+    /// The base URI to resolve relative `$ref`s against, instead of the schema file location.
+    #[arg(long = "base-uri", global = true)]
+    base_uri: Option<String>,
+
     /// Which JSON Schema draft to enforce.
     #[arg(
         short = 'd',
         long = "draft",
         value_enum,
+        global = true,
+        env = "JSONSCHEMA_DRAFT",
         help = "Enforce a specific JSON Schema draft"
     )]
     draft: Option<Draft>,
 
-    /// Enable validation of `format` keywords.
+    /// Enable validation of `format` keywords. Without either flag, `JSONSCHEMA_ASSERT_FORMAT`
+    /// set to `true` or `false` decides.
     #[arg(
         long = "assert-format",
         action = ArgAction::SetTrue,
@@ -47,11 +124,213 @@ struct Cli {
     )]
     no_assert_format: Option<bool>,
 
+    /// Write a JSON report of all instances to this file.
+    #[arg(long = "report-file")]
+    report_file: Option<PathBuf>,
+
+    /// Write a JSON report per instance into this directory.
+    #[arg(long = "report-dir")]
+    report_dir: Option<PathBuf>,
+
+    /// How to print validation results to stdout.
+    #[arg(long = "output", value_enum, default_value_t = Output::Text)]
+    output: Output,
+
+    /// How to print errors of invalid schemas in the text output, e.g. `json` for editor integrations.
+    #[arg(long = "diagnostics", value_enum, default_value_t = Diagnostics::Text)]
+    diagnostics: Diagnostics,
+
+    /// When to color the text output.
+    #[arg(
+        long = "color",
+        value_enum,
+        default_value_t = ColorChoice::Auto,
+        env = "JSONSCHEMA_COLOR"
+    )]
+    color: ColorChoice,
+
+    /// How instance files are formatted (defaults to `yaml` for `.yaml` and `.yml` files, `cbor` for `.cbor` files, `msgpack` for `.msgpack` and `.mpk` files, and to `json` otherwise).
+    #[arg(
+        long = "format",
+        visible_alias = "instance-format",
+        value_enum,
+        conflicts_with = "batch"
+    )]
+    format: Option<InstanceFormat>,
+
+    /// Validate only the value at this JSON Pointer within each instance, e.g. `/spec/template`.
+    /// Error locations remain relative to the whole instance.
+    #[arg(
+        long = "instance-pointer",
+        value_name = "POINTER",
+        value_parser = parse_pointer
+    )]
+    instance_pointer: Option<String>,
+
+    /// Report duplicate keys, lone surrogates, and numbers losing precision in instances as errors.
+    #[arg(long = "strict-json")]
+    strict_json: bool,
+
+    /// Accept comments and trailing commas (JSONC) in JSON schemas and instances.
+    #[arg(long = "relaxed-json", global = true)]
+    relaxed_json: bool,
+
+    /// Read documents referenced via URIs starting with a prefix from files in a local directory
+    /// instead, e.g. `--map https://example.com/schemas/=./schemas/`. The prefix covers whole
+    /// path segments and references can not point outside of the directory. Can be repeated.
+    #[arg(
+        long = "map",
+        value_name = "PREFIX=DIR",
+        value_parser = parse_map,
+        global = true
+    )]
+    maps: Vec<(String, PathBuf)>,
+
+    /// Retrieve documents referenced via `http(s)://` URIs, which are rejected otherwise.
+    /// Retrieved documents are cached in `$XDG_CACHE_HOME/jsonschema`, unless `--no-cache` is set.
+    #[arg(long = "allow-remote", global = true, env = "JSONSCHEMA_ALLOW_REMOTE")]
+    allow_remote: bool,
+
+    /// Retrieve remote documents on every run, without reading or writing the cache.
+    #[arg(
+        long = "no-cache",
+        requires = "allow_remote",
+        conflicts_with_all = ["cache_ttl", "offline"],
+        global = true
+    )]
+    no_cache: bool,
+
+    /// How many seconds cached remote documents are used before retrieving them again (defaults to a day).
+    #[arg(long = "cache-ttl", requires = "allow_remote", global = true)]
+    cache_ttl: Option<u64>,
+
+    /// Only use cached remote documents, without accessing the network.
+    #[arg(long = "offline", requires = "allow_remote", global = true)]
+    offline: bool,
+
+    /// Print schema compilation, resource retrieval, and instance validation times to stderr.
+    #[arg(long = "timings")]
+    timings: bool,
+
+    /// Replace validation error messages with templates from this TOML message catalog.
+    #[arg(long = "lang", env = "JSONSCHEMA_LANG")]
+    lang: Option<PathBuf>,
+
+    /// Write subschemas that did not accept any instance, per schema, to this file.
+    #[arg(long = "coverage", conflicts_with = "batch")]
+    coverage: Option<PathBuf>,
+
+    /// Stop after this many invalid instances.
+    #[arg(long = "max-failures", conflicts_with = "batch")]
+    max_failures: Option<NonZeroUsize>,
+
+    /// Print at most this many errors per invalid instance, followed by the number of remaining ones.
+    #[arg(long = "max-errors", conflicts_with = "batch")]
+    max_errors: Option<NonZeroUsize>,
+
+    /// Also print the annotations collected for valid instances, such as titles, descriptions,
+    /// matched `anyOf` branches, and evaluated properties.
+    #[arg(
+        long = "verbose",
+        conflicts_with_all(["output", "batch", "quiet", "list_invalid"])
+    )]
+    verbose: bool,
+
+    /// Print nothing to stdout, the exit code tells whether all instances are valid.
+    #[arg(
+        short = 'q',
+        long = "quiet",
+        conflicts_with_all(["output", "batch", "list_invalid"])
+    )]
+    quiet: bool,
+
+    /// Only print the names of invalid instance files, one per line.
+    #[arg(short = 'l', long = "list-invalid", conflicts_with_all(["output", "batch"]))]
+    list_invalid: bool,
+
+    /// Number of threads validating instances (defaults to the number of CPUs).
+    #[arg(short = 'j', long = "jobs", conflicts_with = "batch")]
+    jobs: Option<NonZeroUsize>,
+
+    /// Read newline-delimited JSON commands (`{"schema": "...", "instance_path": "..."}`) from stdin and write one JSON result per line.
+    #[arg(
+        long = "batch",
+        conflicts_with_all(["schema", "instances", "instance_dir", "schema_map", "report_file", "report_dir", "output"])
+    )]
+    batch: bool,
+
     /// Show program's version number and exit.
     #[arg(short = 'v', long = "version")]
     version: bool,
 }
 
+#[derive(Subcommand)]
+enum Command {
+    /// Embed all documents the schema references into it and print the resulting schema, which
+    /// resolves without retrieving anything.
+    Bundle {
+        /// The JSON Schema to bundle. Use `-` to read the schema from stdin.
+        schema: PathBuf,
+    },
+    /// Replace every reference in the schema with a copy of the schema it points to and print the
+    /// result, for tools that do not support references.
+    Deref {
+        /// The JSON Schema to dereference. Use `-` to read the schema from stdin.
+        schema: PathBuf,
+
+        /// Fail if inlining a reference requires following more nested references than this.
+        #[arg(long = "max-depth", default_value_t = 64)]
+        max_depth: usize,
+    },
+    /// Print random instances that are valid against the schema, one JSON document per line.
+    Generate {
+        /// The JSON Schema to generate instances for. Use `-` to read the schema from stdin.
+        schema: PathBuf,
+
+        /// How many instances to generate.
+        #[arg(long = "count", default_value_t = 1)]
+        count: usize,
+
+        /// Seed for the random choices, so that the same instances are generated again. Random
+        /// by default.
+        #[arg(long = "seed")]
+        seed: Option<u64>,
+    },
+    /// Report constructs that are allowed in the schema but likely do not do what was intended,
+    /// such as unknown or ignored keywords and unsatisfiable combinations.
+    Lint {
+        /// The JSON Schema to check. Use `-` to read the schema from stdin.
+        schema: PathBuf,
+
+        /// Print findings as `text` lines or as a single `json` object.
+        #[arg(long = "output", value_enum, default_value_t = ReportFormat::Text)]
+        output: ReportFormat,
+    },
+    /// Rewrite the schema for a newer draft and print it, along with anything that has to be
+    /// migrated by hand.
+    Migrate {
+        /// The JSON Schema to migrate. Use `-` to read the schema from stdin.
+        schema: PathBuf,
+
+        /// The draft to migrate to.
+        #[arg(long = "to", value_enum, default_value = "2020")]
+        to: Draft,
+    },
+    /// Report the changes between two versions of a schema, and whether instances valid against
+    /// the old one may be invalid against the new one.
+    Diff {
+        /// The old version of the JSON Schema.
+        old: PathBuf,
+
+        /// The new version of the JSON Schema.
+        new: PathBuf,
+
+        /// Print changes as `text` lines or as a single `json` object.
+        #[arg(long = "output", value_enum, default_value_t = ReportFormat::Text)]
+        output: ReportFormat,
+    },
+}
+
 #[derive(ValueEnum, Clone, Copy, Debug)]
 enum Draft {
     #[clap(name = "4")]
@@ -66,6 +345,86 @@ enum Draft {
     Draft202012,
 }
 
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum Output {
+    /// Human-readable lines per instance.
+    Text,
+    /// A single JSON report of all instances, shaped like `--report-file` reports.
+    Json,
+    /// A SARIF 2.1.0 log, e.g. for code scanning dashboards.
+    Sarif,
+    /// A `JUnit` XML report, with a test case per instance, e.g. for CI servers.
+    Junit,
+    /// A Test Anything Protocol stream, with a test point per instance, e.g. for `prove`.
+    Tap,
+}
+
+/// The output of subcommands that report on schemas.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum ReportFormat {
+    /// A line per entry, with its location in the schema.
+    Text,
+    /// A single JSON object.
+    Json,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum Diagnostics {
+    /// A `Schema is invalid` line with the error message.
+    Text,
+    /// A JSON object per invalid schema, with the schema file, the location of the error in the schema, the keyword containing it, and the message.
+    Json,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum ColorChoice {
+    /// Color if stdout is a terminal and `NO_COLOR` is not set.
+    Auto,
+    /// Always color, e.g. when piping into `less -R`.
+    Always,
+    /// Never color.
+    Never,
+}
+
+impl ColorChoice {
+    fn enabled(self) -> bool {
+        match self {
+            ColorChoice::Auto => {
+                io::stdout().is_terminal()
+                    && std::env::var_os("NO_COLOR").map_or(true, |value| value.is_empty())
+            }
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum InstanceFormat {
+    /// A single JSON document per file.
+    Json,
+    /// A JSON document per line, also known as JSON Lines, with each line validated separately.
+    Ndjson,
+    /// A single YAML document per file.
+    Yaml,
+    /// A single CBOR item per file.
+    Cbor,
+    /// A single `MessagePack` value per file.
+    Msgpack,
+}
+
+impl InstanceFormat {
+    /// The format of an instance file without an explicit `--format`.
+    fn detect(path: &Path) -> InstanceFormat {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("yaml" | "yml") => InstanceFormat::Yaml,
+            Some("cbor") => InstanceFormat::Cbor,
+            Some("msgpack" | "mpk") => InstanceFormat::Msgpack,
+            _ => InstanceFormat::Json,
+        }
+    }
+}
+
 impl From<Draft> for jsonschema::Draft {
     fn from(d: Draft) -> jsonschema::Draft {
         match d {
@@ -78,14 +437,243 @@ impl From<Draft> for jsonschema::Draft {
     }
 }
 
+/// The path standing for stdin.
+const STDIN: &str = "-";
+
+/// How many retrieved documents are kept for schemas that reference them, beyond those that
+/// compiled schemas still use.
+const DOCUMENT_CACHE_CAPACITY: usize = 64;
+
+fn is_stdin(path: &Path) -> bool {
+    path == Path::new(STDIN)
+}
+
+/// Stdin contents, read once so the same instance can be validated against several schemas.
+fn stdin_bytes() -> io::Result<&'static [u8]> {
+    static BYTES: OnceLock<Vec<u8>> = OnceLock::new();
+    if let Some(bytes) = BYTES.get() {
+        return Ok(bytes);
+    }
+    let mut bytes = Vec::new();
+    io::stdin().lock().read_to_end(&mut bytes)?;
+    Ok(BYTES.get_or_init(|| bytes))
+}
+
+fn stdin_text() -> io::Result<&'static str> {
+    std::str::from_utf8(stdin_bytes()?)
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+}
+
+fn read_text(path: &Path) -> io::Result<String> {
+    if is_stdin(path) {
+        stdin_text().map(str::to_string)
+    } else {
+        fs::read_to_string(path)
+    }
+}
+
+fn read_bytes(path: &Path) -> io::Result<Vec<u8>> {
+    if is_stdin(path) {
+        stdin_bytes().map(<[u8]>::to_vec)
+    } else {
+        fs::read(path)
+    }
+}
+
 fn read_json(
     path: &Path,
-) -> Result<serde_json::Result<serde_json::Value>, Box<dyn std::error::Error>> {
+    relaxed_json: bool,
+) -> Result<serde_json::Result<serde_json::Value>, Box<dyn Error>> {
+    if relaxed_json {
+        return Ok(serde_json::from_str(&relaxed::strip(&read_text(path)?)));
+    }
+    if is_stdin(path) {
+        return Ok(serde_json::from_str(stdin_text()?));
+    }
     let file = File::open(path)?;
     let reader = BufReader::new(file);
     Ok(serde_json::from_reader(reader))
 }
 
+/// Read a schema, from YAML if the file extension says so.
+fn read_schema(path: &Path, relaxed_json: bool) -> Result<serde_json::Value, Box<dyn Error>> {
+    if !is_stdin(path) && matches!(InstanceFormat::detect(path), InstanceFormat::Yaml) {
+        Ok(serde_norway::from_str(&read_text(path)?)?)
+    } else {
+        Ok(read_json(path, relaxed_json)??)
+    }
+}
+
+/// Read the schemas in `dirs`, keyed by their file URIs, so that they are also registered by the
+/// `$id`s they declare.
+fn read_schema_dirs(
+    dirs: &[PathBuf],
+    draft: Option<Draft>,
+    relaxed_json: bool,
+) -> Result<Vec<(String, Resource)>, Box<dyn Error>> {
+    let mut resources = Vec::new();
+    for dir in dirs {
+        if !dir.is_dir() {
+            return Err(format!("{} is not a directory", dir.display()).into());
+        }
+        for entry in WalkDir::new(dir).sort_by_file_name() {
+            let entry = entry?;
+            let path = entry.path();
+            let is_schema = path.extension().is_some_and(|extension| {
+                matches!(extension.to_str(), Some("json" | "yaml" | "yml"))
+            });
+            if !entry.file_type().is_file() || !is_schema {
+                continue;
+            }
+            let contents = read_schema(path, relaxed_json)
+                .map_err(|error| format!("{}: {error}", path.display()))?;
+            let resource = match draft {
+                Some(draft) => jsonschema::Draft::from(draft).create_resource(contents),
+                None => Resource::from_contents(contents)
+                    .map_err(|error| format!("{}: {error}", path.display()))?,
+            };
+            resources.push((path_to_uri(path), resource));
+        }
+    }
+    Ok(resources)
+}
+
+/// Whether and how documents referenced via `http(s)` URIs are retrieved.
+#[derive(Clone)]
+pub(crate) enum Remote {
+    Forbidden,
+    /// Without a cache, with `--no-cache` or if there is no cache directory.
+    Uncached,
+    Cached(DiskCache),
+}
+
+fn parse_map(value: &str) -> Result<(String, PathBuf), String> {
+    match value.split_once('=') {
+        Some((prefix, dir)) if !prefix.is_empty() && !dir.is_empty() => {
+            // Prefixes cover whole path segments, `https://example.com/schemas` is the same as
+            // `https://example.com/schemas/`
+            let mut prefix = prefix.to_string();
+            if !prefix.ends_with('/') {
+                prefix.push('/');
+            }
+            Ok((prefix, PathBuf::from(dir)))
+        }
+        _ => Err(format!("expected `PREFIX=DIR`, got `{value}`")),
+    }
+}
+
+fn parse_pointer(value: &str) -> Result<String, String> {
+    if value.is_empty() || value.starts_with('/') {
+        Ok(value.to_string())
+    } else {
+        Err(format!(
+            "expected a JSON Pointer starting with `/`, got `{value}`"
+        ))
+    }
+}
+
+/// Retrieves referenced schemas, reading local files as YAML or relaxed JSON if needed.
+#[derive(Clone)]
+pub(crate) struct SchemaRetriever {
+    pub(crate) relaxed_json: bool,
+    pub(crate) remote: Remote,
+    /// URI prefixes and the directories with the documents they refer to, from `--map`.
+    pub(crate) maps: Vec<(String, PathBuf)>,
+}
+
+impl SchemaRetriever {
+    /// The local file of a document referenced via a `--map` prefix, using the longest one.
+    fn mapped_path(
+        &self,
+        uri: &Uri<String>,
+    ) -> Result<Option<PathBuf>, Box<dyn std::error::Error + Send + Sync>> {
+        let Some((dir, rest)) = self
+            .maps
+            .iter()
+            .filter_map(|(prefix, dir)| Some((dir, uri.as_str().strip_prefix(prefix.as_str())?)))
+            .min_by_key(|(_, rest)| rest.len())
+        else {
+            return Ok(None);
+        };
+        let rest = percent_decode_str(rest).decode_utf8()?;
+        let mut path = dir.clone();
+        for component in Path::new(rest.as_ref()).components() {
+            match component {
+                Component::Normal(part) => path.push(part),
+                Component::CurDir => {}
+                Component::ParentDir | Component::RootDir | Component::Prefix(_) => {
+                    return Err(format!("'{uri}' points outside of {}", dir.display()).into());
+                }
+            }
+        }
+        Ok(Some(path))
+    }
+}
+
+impl Retrieve for SchemaRetriever {
+    fn retrieve(
+        &self,
+        uri: &Uri<String>,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+        if uri.scheme().as_str() == "file" {
+            let path = PathBuf::from(
+                percent_decode_str(uri.path().as_str())
+                    .decode_utf8()?
+                    .as_ref(),
+            );
+            if matches!(InstanceFormat::detect(&path), InstanceFormat::Yaml) {
+                return Ok(serde_norway::from_str(&fs::read_to_string(path)?)?);
+            }
+            if self.relaxed_json {
+                return Ok(serde_json::from_str(&relaxed::strip(&fs::read_to_string(
+                    path,
+                )?))?);
+            }
+        }
+        if let Some(path) = self.mapped_path(uri)? {
+            let text = fs::read_to_string(&path)
+                .map_err(|error| format!("{}: {error}", path.display()))?;
+            return Ok(
+                if matches!(InstanceFormat::detect(&path), InstanceFormat::Yaml) {
+                    serde_norway::from_str(&text)?
+                } else if self.relaxed_json {
+                    serde_json::from_str(&relaxed::strip(&text))?
+                } else {
+                    serde_json::from_str(&text)?
+                },
+            );
+        }
+        if matches!(uri.scheme().as_str(), "http" | "https") {
+            return match &self.remote {
+                Remote::Forbidden => {
+                    Err("retrieving remote documents requires `--allow-remote`".into())
+                }
+                Remote::Uncached => DefaultRetriever.retrieve(uri),
+                Remote::Cached(cache) => cache.retrieve(uri),
+            };
+        }
+        DefaultRetriever.retrieve(uri)
+    }
+}
+
+/// How an instance is referred to in human-readable output.
+fn display_name(path: &Path) -> std::borrow::Cow<'_, str> {
+    if is_stdin(path) {
+        "<stdin>".into()
+    } else {
+        path.to_string_lossy()
+    }
+}
+
+/// How a file is referred to in machine-readable reports.
+fn artifact_uri(path: &Path) -> String {
+    if is_stdin(path) {
+        STDIN.to_string()
+    } else {
+        path_to_uri(path)
+    }
+}
+
 fn path_to_uri(path: &std::path::Path) -> String {
     const SEGMENT: &AsciiSet = &CONTROLS
         .add(b' ')
@@ -119,7 +707,7 @@ fn path_to_uri(path: &std::path::Path) -> String {
     }
     #[cfg(target_os = "windows")]
     {
-        use std::path::{Component, Prefix};
+        use std::path::Prefix;
         let mut components = path.components();
 
         match components.next() {
@@ -148,73 +736,1242 @@ fn path_to_uri(path: &std::path::Path) -> String {
     result
 }
 
-fn validate_instances(
+/// Whether an instance argument is a glob pattern rather than a path.
+fn is_glob(path: &Path) -> bool {
+    path.to_str()
+        .is_some_and(|path| path.contains(['*', '?', '[', '{']))
+}
+
+/// Replace glob patterns with the files they match, in file name order.
+fn expand_globs(instances: Vec<PathBuf>) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let mut expanded = Vec::with_capacity(instances.len());
+    for instance in instances {
+        // Existing files are kept even if their names look like patterns
+        if !is_glob(&instance) || instance.is_file() {
+            expanded.push(instance);
+            continue;
+        }
+        let pattern = instance.to_string_lossy();
+        let matcher = GlobBuilder::new(&pattern)
+            .literal_separator(true)
+            .build()
+            .map_err(|error| format!("Invalid glob `{pattern}`: {error}"))?
+            .compile_matcher();
+        // Only walk the part of the tree that can match
+        let root: PathBuf = instance
+            .components()
+            .take_while(|component| !is_glob(component.as_os_str().as_ref()))
+            .collect();
+        let walk_root = if root.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            root.as_path()
+        };
+        let count = expanded.len();
+        for entry in WalkDir::new(walk_root).sort_by_file_name() {
+            let entry = entry?;
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            let path = if root.as_os_str().is_empty() {
+                entry.path().strip_prefix(".").unwrap_or(entry.path())
+            } else {
+                entry.path()
+            };
+            if matcher.is_match(path) {
+                expanded.push(path.to_path_buf());
+            }
+        }
+        if expanded.len() == count {
+            return Err(format!("No files match `{pattern}`").into());
+        }
+    }
+    Ok(expanded)
+}
+
+/// Pair each instance with the schema to validate it with.
+///
+/// Files in `instance_dir` are included if a config rule matches them, or if they have the
+/// `.json` extension and there is a default schema.
+fn collect_instances(
     instances: &[PathBuf],
-    schema_path: &Path,
+    instance_dir: Option<&Path>,
+    schema: Option<&Path>,
+    config: Option<&Config>,
+) -> Result<Vec<(PathBuf, PathBuf)>, Box<dyn Error>> {
+    let mut pairs = Vec::new();
+    for instance in instances {
+        let Some(schema) = config
+            .and_then(|config| config.schema_for(instance))
+            .or(schema)
+        else {
+            return Err(format!("No schema for {}", instance.display()).into());
+        };
+        pairs.push((instance.clone(), schema.to_path_buf()));
+    }
+    if let Some(dir) = instance_dir {
+        // Schemas inside the directory are not instances
+        let schemas: Vec<_> = schema
+            .into_iter()
+            .chain(config.into_iter().flat_map(Config::schemas))
+            .filter_map(|path| path.canonicalize().ok())
+            .collect();
+        for entry in WalkDir::new(dir).sort_by_file_name() {
+            let entry = entry?;
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            let path = entry.path();
+            let relative = path.strip_prefix(dir).unwrap_or(path);
+            let matched = config.and_then(|config| config.schema_for(relative));
+            let Some(schema) = matched.or_else(|| {
+                schema.filter(|_| {
+                    path.extension()
+                        .is_some_and(|extension| extension == "json")
+                })
+            }) else {
+                continue;
+            };
+            if path
+                .canonicalize()
+                .is_ok_and(|path| schemas.contains(&path))
+            {
+                continue;
+            }
+            pairs.push((path.to_path_buf(), schema.to_path_buf()));
+        }
+    }
+    Ok(pairs)
+}
+
+/// Compiled validators by schema path.
+struct Validators {
+    base_uri: Option<String>,
     draft: Option<Draft>,
     assert_format: Option<bool>,
-) -> Result<bool, Box<dyn std::error::Error>> {
-    let mut success = true;
-
-    let schema_json = read_json(schema_path)??;
-    let base_uri = path_to_uri(schema_path);
-    let base_uri = referencing::uri::from_str(&base_uri)?;
-    let mut options = jsonschema::options().with_base_uri(base_uri);
-    if let Some(draft) = draft {
-        options = options.with_draft(draft.into());
-    }
-    if let Some(assert_format) = assert_format {
-        options = options.should_validate_formats(assert_format);
-    }
-    match options.build(&schema_json) {
-        Ok(validator) => {
-            for instance in instances {
-                let instance_json = read_json(instance)??;
-                let mut errors = validator.iter_errors(&instance_json);
-                let filename = instance.to_string_lossy();
-                if let Some(first) = errors.next() {
-                    success = false;
-                    println!("{filename} - INVALID. Errors:");
-                    println!("1. {first}");
-                    for (i, error) in errors.enumerate() {
-                        println!("{}. {error}", i + 2);
+    timings: bool,
+    retriever: SchemaRetriever,
+    /// Schemas from `--schema-dir`.
+    schemas: Vec<(String, Resource)>,
+    /// Documents retrieved for any of the schemas.
+    documents: DocumentCache,
+    cache: HashMap<PathBuf, Result<jsonschema::Validator, SchemaError>>,
+}
+
+impl Validators {
+    fn new(
+        base_uri: Option<String>,
+        draft: Option<Draft>,
+        assert_format: Option<bool>,
+        timings: bool,
+        retriever: SchemaRetriever,
+        schemas: Vec<(String, Resource)>,
+    ) -> Validators {
+        Validators {
+            base_uri,
+            draft,
+            assert_format,
+            timings,
+            retriever,
+            schemas,
+            documents: DocumentCache::new(DOCUMENT_CACHE_CAPACITY),
+            cache: HashMap::new(),
+        }
+    }
+
+    /// The validator for a schema that [`Validators::get`] was already called for.
+    fn compiled(&self, schema_path: &Path) -> &Result<jsonschema::Validator, SchemaError> {
+        &self.cache[schema_path]
+    }
+
+    /// The validator for a schema, or the reason why the schema is invalid.
+    fn get(
+        &mut self,
+        schema_path: &Path,
+    ) -> Result<&Result<jsonschema::Validator, SchemaError>, Box<dyn Error>> {
+        match self.cache.entry(schema_path.to_path_buf()) {
+            Entry::Occupied(entry) => Ok(entry.into_mut()),
+            Entry::Vacant(entry) => {
+                let uri = schema_uri(schema_path);
+                let schema_json = match uri {
+                    Some(uri) => self
+                        .retriever
+                        .retrieve(&referencing::uri::from_str(uri)?)
+                        .map_err(|error| format!("{uri}: {error}"))?,
+                    None => read_schema(schema_path, self.retriever.relaxed_json)?,
+                };
+                let mut options = jsonschema::options();
+                // A schema read from stdin has no location to resolve relative references against
+                let base_uri = match &self.base_uri {
+                    Some(base_uri) => Some(base_uri.clone()),
+                    None if uri.is_some() => uri.map(str::to_string),
+                    None if is_stdin(schema_path) => None,
+                    None => Some(path_to_uri(schema_path)),
+                };
+                if let Some(base_uri) = base_uri {
+                    options = options.with_base_uri(referencing::uri::from_str(&base_uri)?);
+                }
+                if let Some(draft) = self.draft {
+                    options = options.with_draft(draft.into());
+                }
+                if let Some(assert_format) = self.assert_format {
+                    options = options.should_validate_formats(assert_format);
+                }
+                options = options
+                    .with_resources(self.schemas.iter().cloned())
+                    .with_document_cache(self.documents.clone());
+                let retriever = self.retriever.clone();
+                if self.timings {
+                    options = options.with_retriever(TimedRetriever(retriever));
+                } else {
+                    options = options.with_retriever(retriever);
+                }
+                let start = Instant::now();
+                let draft = self.draft.map(Into::into);
+                let validator = options
+                    .build(&schema_json)
+                    .map_err(|error| SchemaError::new(&error, meta_errors(&schema_json, draft)));
+                if self.timings {
+                    eprintln!(
+                        "{} - compiled in {:?}",
+                        display_name(schema_path),
+                        start.elapsed()
+                    );
+                }
+                Ok(entry.insert(validator))
+            }
+        }
+    }
+}
+
+/// Every error of `schema` against its meta-schema, or the one of `draft` if given.
+///
+/// Compilation stops at the first error, while editors and reports show all of them. Schemas
+/// with unknown meta-schemas have none.
+fn meta_errors(
+    schema: &serde_json::Value,
+    draft: Option<jsonschema::Draft>,
+) -> Vec<jsonschema::ValidationError<'_>> {
+    let draft = match draft {
+        Some(draft) => draft,
+        None => match jsonschema::Draft::default().detect(schema) {
+            Ok(draft) => draft,
+            Err(_) => return Vec::new(),
+        },
+    };
+    let validator: &jsonschema::Validator = match draft {
+        jsonschema::Draft::Draft4 => &jsonschema::draft4::meta::VALIDATOR,
+        jsonschema::Draft::Draft6 => &jsonschema::draft6::meta::VALIDATOR,
+        jsonschema::Draft::Draft7 => &jsonschema::draft7::meta::VALIDATOR,
+        jsonschema::Draft::Draft201909 => &jsonschema::draft201909::meta::VALIDATOR,
+        _ => &jsonschema::draft202012::meta::VALIDATOR,
+    };
+    validator.iter_errors(schema).collect()
+}
+
+/// Settings for checking each instance.
+#[derive(Clone, Copy)]
+#[allow(clippy::struct_excessive_bools)]
+struct CheckOptions<'a> {
+    strict_json: bool,
+    relaxed_json: bool,
+    timings: bool,
+    catalog: Option<&'a Catalog>,
+    /// Whether to keep excerpts of invalid values for the text output.
+    excerpts: bool,
+    /// Whether to collect the annotations of valid instances.
+    annotations: bool,
+    /// The value to validate within each instance, from `--instance-pointer`.
+    pointer: Option<&'a str>,
+}
+
+fn check(
+    validator: &Result<jsonschema::Validator, SchemaError>,
+    instance: &Path,
+    format: InstanceFormat,
+    options: CheckOptions<'_>,
+    coverage: Option<&mut jsonschema::Coverage>,
+) -> Result<Outcome, Box<dyn Error>> {
+    let validator = match validator {
+        Ok(validator) => validator,
+        Err(error) => return Ok(Outcome::InvalidSchema(error.clone())),
+    };
+    let mut errors = Vec::new();
+    // Strict checks are about JSON syntax only
+    let instance_json = if format == InstanceFormat::Yaml {
+        serde_norway::from_str(&read_text(instance)?)?
+    } else if format == InstanceFormat::Cbor {
+        ciborium::from_reader(read_bytes(instance)?.as_slice())
+            .map_err(|error| format!("Invalid CBOR: {error}"))?
+    } else if format == InstanceFormat::Msgpack {
+        rmp_serde::from_slice(&read_bytes(instance)?)
+            .map_err(|error| format!("Invalid MessagePack: {error}"))?
+    } else if options.strict_json {
+        let text = read_text(instance)?;
+        errors.extend(strict::check(&text).into_iter().map(report::strict_error));
+        match serde_json::from_str(&text) {
+            Ok(instance_json) => instance_json,
+            // Lone surrogates are syntax errors for the parser
+            Err(_) if !errors.is_empty() => return Ok(Outcome::invalid(errors)),
+            Err(error) => return Err(error.into()),
+        }
+    } else {
+        read_json(instance, options.relaxed_json)??
+    };
+    check_value(
+        validator,
+        &instance_json,
+        errors,
+        &display_name(instance),
+        options,
+        coverage,
+    )
+}
+
+/// Validate a parsed instance, after `errors` found while parsing it.
+///
+/// An instance without a value at `--instance-pointer` is an input error, as it lacks the
+/// expected envelope rather than being invalid. [`check_lines`] reports such lines on their own
+/// instead.
+fn check_value(
+    validator: &jsonschema::Validator,
+    instance: &serde_json::Value,
+    mut errors: Vec<BatchError>,
+    name: &str,
+    options: CheckOptions<'_>,
+    coverage: Option<&mut jsonschema::Coverage>,
+) -> Result<Outcome, Box<dyn Error>> {
+    let pointer = options.pointer.unwrap_or_default();
+    let Some(instance) = instance.pointer(pointer) else {
+        return Err(format!("{name}: Instance has no value at `{pointer}`").into());
+    };
+    if let Some(coverage) = coverage {
+        validator.record_coverage(instance, coverage);
+    }
+    let start = Instant::now();
+    // Errors found while parsing are not about a value
+    let mut excerpts = Vec::new();
+    if options.excerpts {
+        excerpts.resize(errors.len(), None);
+    }
+    for error in validator.iter_errors(instance) {
+        if options.excerpts {
+            excerpts.push(Some(color::excerpt(&error.instance)));
+        }
+        errors.push(report::nested_error(
+            report::validation_error(&error, options.catalog),
+            pointer,
+        ));
+    }
+    if options.timings {
+        eprintln!("{name} - validated in {:?}", start.elapsed());
+    }
+    if errors.is_empty() {
+        let mut outcome = Outcome::valid();
+        if let (true, BasicOutput::Valid(units)) =
+            (options.annotations, validator.apply(instance).basic())
+        {
+            outcome = Outcome::Valid {
+                annotations: units
+                    .iter()
+                    .map(|unit| Annotation {
+                        instance_location: format!("{pointer}{}", unit.instance_location()),
+                        keyword_location: unit.keyword_location().as_str().to_string(),
+                        value: unit.value().into_owned(),
+                    })
+                    .collect(),
+            };
+        }
+        Ok(outcome)
+    } else {
+        Ok(Outcome::Invalid { errors, excerpts })
+    }
+}
+
+/// Check every non-blank line of a newline-delimited JSON file as a separate instance, reading
+/// one line at a time.
+///
+/// Outcomes are passed to `record` with their line numbers until it returns `false`, which is
+/// then returned. Unlike whole files, lines that are not valid JSON are reported as invalid.
+fn check_lines(
+    validator: &Result<jsonschema::Validator, SchemaError>,
+    instance: &Path,
+    options: CheckOptions<'_>,
+    mut coverage: Option<&mut jsonschema::Coverage>,
+    mut record: impl FnMut(Option<usize>, Outcome) -> bool,
+) -> Result<bool, Box<dyn Error>> {
+    let validator = match validator {
+        Ok(validator) => validator,
+        // There is no point in reading the file
+        Err(error) => return Ok(record(None, Outcome::InvalidSchema(error.clone()))),
+    };
+    let reader: Box<dyn BufRead> = if is_stdin(instance) {
+        Box::new(io::Cursor::new(stdin_text()?))
+    } else {
+        Box::new(BufReader::new(File::open(instance)?))
+    };
+    let name = display_name(instance);
+    let pointer = options.pointer.unwrap_or_default();
+    for (index, line) in reader.lines().enumerate() {
+        let mut line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let number = index + 1;
+        let mut errors = Vec::new();
+        if options.strict_json {
+            errors.extend(strict::check(&line).into_iter().map(report::strict_error));
+        }
+        if options.relaxed_json {
+            line = relaxed::strip(&line);
+        }
+        let outcome = match serde_json::from_str::<serde_json::Value>(&line) {
+            // A single record without the envelope does not stop checking the others
+            Ok(value) if value.pointer(pointer).is_none() => {
+                errors.push(BatchError::new(
+                    "",
+                    None,
+                    format!("Instance has no value at `{pointer}`"),
+                ));
+                Outcome::invalid(errors)
+            }
+            Ok(value) => check_value(
+                validator,
+                &value,
+                errors,
+                &format!("{name}:{number}"),
+                options,
+                coverage.as_deref_mut(),
+            )?,
+            Err(_) if !errors.is_empty() => Outcome::invalid(errors),
+            Err(error) => Outcome::invalid(vec![BatchError::new(
+                "",
+                None,
+                format!("Invalid JSON: {error}"),
+            )]),
+        };
+        if !record(Some(number), outcome) {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Check an instance file, passing outcomes to `record` as [`check_lines`] does.
+fn check_file(
+    validator: &Result<jsonschema::Validator, SchemaError>,
+    instance: &Path,
+    format: Option<InstanceFormat>,
+    options: CheckOptions<'_>,
+    coverage: Option<&mut jsonschema::Coverage>,
+    mut record: impl FnMut(Option<usize>, Outcome) -> bool,
+) -> Result<bool, Box<dyn Error>> {
+    match format.unwrap_or_else(|| InstanceFormat::detect(instance)) {
+        InstanceFormat::Ndjson => check_lines(validator, instance, options, coverage, record),
+        format => Ok(record(
+            None,
+            check(validator, instance, format, options, coverage)?,
+        )),
+    }
+}
+
+/// Collects instance outcomes in the order of the instances.
+struct Recorder {
+    text: bool,
+    /// How invalid schemas are printed in the text output.
+    diagnostics: Diagnostics,
+    /// Whether to print the name of each invalid instance file once, instead of the text output.
+    list_invalid: bool,
+    color: bool,
+    max_failures: Option<NonZeroUsize>,
+    /// How many errors are printed per instance, reports keep all of them.
+    max_errors: Option<NonZeroUsize>,
+    failures: usize,
+    reported: HashSet<PathBuf>,
+    listed: HashSet<PathBuf>,
+    reports: Vec<InstanceReport>,
+}
+
+impl Recorder {
+    fn new(
+        text: bool,
+        diagnostics: Diagnostics,
+        list_invalid: bool,
+        color: bool,
+        max_failures: Option<NonZeroUsize>,
+        max_errors: Option<NonZeroUsize>,
+    ) -> Recorder {
+        Recorder {
+            text,
+            diagnostics,
+            list_invalid,
+            color,
+            max_failures,
+            max_errors,
+            failures: 0,
+            reported: HashSet::new(),
+            listed: HashSet::new(),
+            reports: Vec::new(),
+        }
+    }
+
+    /// Start recording the outcomes of an instance validated against `schema`.
+    fn start(&mut self, schema: &Path, validator: &Result<jsonschema::Validator, SchemaError>) {
+        // Every schema is reported once if invalid
+        if let (true, Err(error)) = (self.text, validator) {
+            if !self.reported.insert(schema.to_path_buf()) {
+                return;
+            }
+            if self.diagnostics == Diagnostics::Json {
+                for diagnostic in error.diagnostics(schema) {
+                    println!("{diagnostic}");
+                }
+            } else if let [_, _, ..] = error.errors() {
+                println!("Schema is invalid. Errors:");
+                for (number, error) in error.errors().iter().enumerate() {
+                    println!("{}. {}", number + 1, error.error());
+                }
+            } else {
+                println!("Schema is invalid. Error: {error}");
+            }
+        }
+    }
+
+    /// Record an outcome, returning whether to continue with the next instance.
+    fn record(
+        &mut self,
+        instance: &Path,
+        schema: &Path,
+        line: Option<usize>,
+        outcome: Outcome,
+    ) -> bool {
+        let filename = display_name(instance);
+        let filename = match line {
+            Some(line) => format!("{filename}:{line}"),
+            None => filename.into_owned(),
+        };
+        let paint = |text: &str, style| {
+            if self.color {
+                color::paint(text, style)
+            } else {
+                text.to_string()
+            }
+        };
+        let location = |location: &str| {
+            paint(
+                if location.is_empty() {
+                    "(root)"
+                } else {
+                    location
+                },
+                Style::Location,
+            )
+        };
+        match &outcome {
+            Outcome::Valid { annotations } if self.text => {
+                if annotations.is_empty() {
+                    println!("{filename} - {}", paint("VALID", Style::Valid));
+                } else {
+                    println!(
+                        "{filename} - {}. Annotations:",
+                        paint("VALID", Style::Valid)
+                    );
+                    for (i, annotation) in annotations.iter().enumerate() {
+                        println!(
+                            "{}. {} {}: {}",
+                            i + 1,
+                            location(&annotation.instance_location),
+                            paint(&format!("#{}", annotation.keyword_location), Style::Keyword),
+                            annotation.value
+                        );
+                    }
+                }
+            }
+            Outcome::Invalid { errors, excerpts } if self.text => {
+                println!("{filename} - {}. Errors:", paint("INVALID", Style::Invalid));
+                let shown = self.max_errors.map_or(errors.len(), NonZeroUsize::get);
+                for (i, error) in errors.iter().take(shown).enumerate() {
+                    println!("{}. {}", i + 1, error.error());
+                    let mut details = location(error.instance_location());
+                    if let Some(keyword) = error.keyword() {
+                        details = format!("{details} {}", paint(keyword, Style::Keyword));
+                    }
+                    if let Some(Some(excerpt)) = excerpts.get(i) {
+                        details = format!("{details}: {}", paint(excerpt, Style::Excerpt));
                     }
+                    println!("   {details}");
+                }
+                if errors.len() > shown {
+                    println!("... and {} more error(s)", errors.len() - shown);
+                }
+            }
+            _ => {}
+        }
+        let report = InstanceReport {
+            instance: instance.to_path_buf(),
+            line,
+            schema: schema.to_path_buf(),
+            outcome,
+        };
+        if !report.is_valid() {
+            self.failures += 1;
+            // Invalid lines of the same file, or other schemas for it, do not list it again
+            if self.list_invalid && self.listed.insert(instance.to_path_buf()) {
+                println!("{}", display_name(instance));
+            }
+        }
+        self.reports.push(report);
+        if self
+            .max_failures
+            .is_some_and(|max_failures| self.failures >= max_failures.get())
+        {
+            if self.text {
+                println!("Stopped after {} invalid instance(s)", self.failures);
+            }
+            return false;
+        }
+        true
+    }
+}
+
+fn validate_instances(
+    mut pairs: Vec<(PathBuf, PathBuf)>,
+    validators: &mut Validators,
+    options: CheckOptions<'_>,
+    format: Option<InstanceFormat>,
+    mut recorder: Recorder,
+    jobs: NonZeroUsize,
+    coverage: Option<&mut BTreeMap<PathBuf, jsonschema::Coverage>>,
+) -> Result<Vec<InstanceReport>, Box<dyn Error>> {
+    // Schemas are compiled upfront, and one that can not be read fails in place of its first
+    // instance, after the outcomes of the previous ones
+    let mut schema_error = None;
+    for (index, (_, schema)) in pairs.iter().enumerate() {
+        if let Err(error) = validators.get(schema) {
+            schema_error = Some((index, error));
+            break;
+        }
+    }
+    if let Some((index, _)) = &schema_error {
+        pairs.truncate(*index);
+    }
+    let validators = &*validators;
+    recorder.reports.reserve(pairs.len());
+
+    let completed = if jobs.get() == 1 || pairs.len() < 2 || coverage.is_some() {
+        // Coverage is recorded into one collection per schema, so it is not shared across threads
+        validate_sequentially(&pairs, validators, options, format, &mut recorder, coverage)?
+    } else {
+        validate_in_parallel(&pairs, validators, options, format, jobs, &mut recorder)?
+    };
+    match schema_error {
+        Some((_, error)) if completed => Err(error),
+        _ => Ok(recorder.reports),
+    }
+}
+
+/// Validate instances one after the other, returning whether all of them were validated.
+fn validate_sequentially(
+    pairs: &[(PathBuf, PathBuf)],
+    validators: &Validators,
+    options: CheckOptions<'_>,
+    format: Option<InstanceFormat>,
+    recorder: &mut Recorder,
+    mut coverage: Option<&mut BTreeMap<PathBuf, jsonschema::Coverage>>,
+) -> Result<bool, Box<dyn Error>> {
+    for (instance, schema) in pairs {
+        let validator = validators.compiled(schema);
+        recorder.start(schema, validator);
+        let schema_coverage = match (coverage.as_deref_mut(), validator) {
+            (Some(coverage), Ok(validator)) => Some(
+                coverage
+                    .entry(schema.clone())
+                    .or_insert_with(|| validator.coverage()),
+            ),
+            _ => None,
+        };
+        let proceed = check_file(
+            validator,
+            instance,
+            format,
+            options,
+            schema_coverage,
+            |line, outcome| recorder.record(instance, schema, line, outcome),
+        )?;
+        if !proceed {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Validate instances across `jobs` threads, recording their outcomes in order, and return
+/// whether all of them were validated.
+fn validate_in_parallel(
+    pairs: &[(PathBuf, PathBuf)],
+    validators: &Validators,
+    options: CheckOptions<'_>,
+    format: Option<InstanceFormat>,
+    jobs: NonZeroUsize,
+    recorder: &mut Recorder,
+) -> Result<bool, Box<dyn Error>> {
+    let next = AtomicUsize::new(0);
+    let stop = AtomicBool::new(false);
+    thread::scope(|scope| {
+        let (sender, receiver) = mpsc::channel();
+        for _ in 0..jobs.get().min(pairs.len()) {
+            let sender = sender.clone();
+            let (next, stop) = (&next, &stop);
+            scope.spawn(move || {
+                while !stop.load(Ordering::Relaxed) {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some((instance, schema)) = pairs.get(index) else {
+                        break;
+                    };
+                    let mut outcomes = Vec::new();
+                    let result = check_file(
+                        validators.compiled(schema),
+                        instance,
+                        format,
+                        options,
+                        None,
+                        |line, outcome| {
+                            outcomes.push((line, outcome));
+                            !stop.load(Ordering::Relaxed)
+                        },
+                    )
+                    .map(|_| outcomes)
+                    .map_err(|error| error.to_string());
+                    if sender.send((index, result)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(sender);
+        // Outcomes arrive in any order and are held back until all previous ones are recorded
+        let mut pending = BTreeMap::new();
+        let mut expected = 0;
+        let completed = 'receive: {
+            for (index, result) in receiver {
+                pending.insert(index, result);
+                while let Some(result) = pending.remove(&expected) {
+                    let (instance, schema) = &pairs[expected];
+                    expected += 1;
+                    recorder.start(schema, validators.compiled(schema));
+                    let outcomes = match result {
+                        Ok(outcomes) => outcomes,
+                        Err(error) => break 'receive Err(error),
+                    };
+                    for (line, outcome) in outcomes {
+                        if !recorder.record(instance, schema, line, outcome) {
+                            break 'receive Ok(false);
+                        }
+                    }
+                }
+            }
+            Ok(true)
+        };
+        // Let the remaining threads finish early
+        stop.store(true, Ordering::Relaxed);
+        completed.map_err(Into::into)
+    })
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BatchCommand {
+    /// Echoed back in the result, so callers can match results to commands.
+    #[serde(default)]
+    id: Option<serde_json::Value>,
+    schema: PathBuf,
+    instance_path: PathBuf,
+}
+
+/// Run validation commands from stdin, writing one result per line to stdout.
+///
+/// Compiled schemas are kept across commands, and malformed commands are reported as results
+/// without stopping the batch.
+fn run_batch(
+    validators: &mut Validators,
+    options: CheckOptions<'_>,
+) -> Result<Status, Box<dyn Error>> {
+    let mut status = Status::Valid;
+    let mut stdout = io::stdout().lock();
+    for line in io::stdin().lock().lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let (id, mut result, result_status) = match serde_json::from_str::<BatchCommand>(&line) {
+            Ok(command) => {
+                let result = if is_stdin(&command.instance_path) {
+                    Err("stdin is reserved for batch commands".into())
                 } else {
-                    println!("{filename} - VALID");
+                    validators.get(&command.schema).and_then(|validator| {
+                        let format = InstanceFormat::detect(&command.instance_path);
+                        check(validator, &command.instance_path, format, options, None)
+                    })
+                };
+                match result {
+                    Ok(outcome) => {
+                        let report = InstanceReport {
+                            instance: command.instance_path,
+                            line: None,
+                            schema: command.schema,
+                            outcome,
+                        };
+                        let result = serde_json::to_value(report.to_outcome())?;
+                        (command.id, result, Status::of(&report))
+                    }
+                    Err(error) => (
+                        command.id,
+                        json!({"error": error.to_string()}),
+                        Status::Error,
+                    ),
                 }
             }
+            Err(error) => (
+                None,
+                json!({"error": format!("Invalid command: {error}")}),
+                Status::Error,
+            ),
+        };
+        status = status.max(result_status);
+        if let Some(id) = id {
+            result["id"] = id;
         }
-        Err(error) => {
-            println!("Schema is invalid. Error: {error}");
-            success = false;
+        writeln!(stdout, "{result}")?;
+        // Callers may wait for the result before sending the next command
+        stdout.flush()?;
+    }
+    Ok(status)
+}
+
+/// The retriever for references in schemas, according to `--allow-remote`, `--map`, and related
+/// options.
+fn schema_retriever(cli: &Cli) -> Result<SchemaRetriever, Box<dyn Error>> {
+    let remote = if cli.allow_remote && cli.no_cache {
+        Remote::Uncached
+    } else if cli.allow_remote {
+        let ttl = cli
+            .cache_ttl
+            .map_or(cache::DEFAULT_TTL, Duration::from_secs);
+        match DiskCache::new(ttl, cli.offline) {
+            Some(cache) => Remote::Cached(cache),
+            None if cli.offline => {
+                return Err("`--offline` requires a cache directory, set `XDG_CACHE_HOME`".into())
+            }
+            None => Remote::Uncached,
+        }
+    } else {
+        Remote::Forbidden
+    };
+    Ok(SchemaRetriever {
+        relaxed_json: cli.relaxed_json,
+        remote,
+        maps: cli.maps.clone(),
+    })
+}
+
+fn run_bundle(cli: &Cli, schema_path: &Path) -> Result<bool, Box<dyn Error>> {
+    let schema = read_schema(schema_path, cli.relaxed_json)?;
+    let base_uri = match &cli.base_uri {
+        Some(base_uri) => base_uri.clone(),
+        // Without a location, only absolute references can be bundled
+        None if is_stdin(schema_path) => "json-schema:///".to_string(),
+        None => path_to_uri(schema_path),
+    };
+    let bundled = bundle::bundle(
+        schema,
+        &base_uri,
+        cli.draft.map(Into::into),
+        schema_retriever(cli)?,
+        read_schema_dirs(&cli.schema_dirs, cli.draft, cli.relaxed_json)?,
+    )?;
+    println!("{}", serde_json::to_string_pretty(&bundled)?);
+    Ok(true)
+}
+
+fn run_deref(cli: &Cli, schema_path: &Path, max_depth: usize) -> Result<bool, Box<dyn Error>> {
+    let schema = read_schema(schema_path, cli.relaxed_json)?;
+    let base_uri = match &cli.base_uri {
+        Some(base_uri) => base_uri.clone(),
+        None if is_stdin(schema_path) => "json-schema:///".to_string(),
+        None => path_to_uri(schema_path),
+    };
+    let dereferenced = deref::dereference(
+        &schema,
+        &base_uri,
+        cli.draft.map(Into::into),
+        schema_retriever(cli)?,
+        read_schema_dirs(&cli.schema_dirs, cli.draft, cli.relaxed_json)?,
+        max_depth,
+    )?;
+    println!("{}", serde_json::to_string_pretty(&dereferenced)?);
+    Ok(true)
+}
+
+fn run_generate(
+    cli: &Cli,
+    schema_path: &Path,
+    count: usize,
+    seed: Option<u64>,
+) -> Result<bool, Box<dyn Error>> {
+    let schema = read_schema(schema_path, cli.relaxed_json)?;
+    let base_uri = match &cli.base_uri {
+        Some(base_uri) => base_uri.clone(),
+        None if is_stdin(schema_path) => "json-schema:///".to_string(),
+        None => path_to_uri(schema_path),
+    };
+    let schemas = read_schema_dirs(&cli.schema_dirs, cli.draft, cli.relaxed_json)?;
+    // Generated formats are meant to be valid, so they are checked too
+    let mut validators = Validators::new(
+        cli.base_uri.clone(),
+        cli.draft,
+        Some(true),
+        false,
+        schema_retriever(cli)?,
+        schemas.clone(),
+    );
+    let validator = match validators.get(schema_path)? {
+        Ok(validator) => validator,
+        Err(error) => return Err(InvalidSchemaError(error.clone()).into()),
+    };
+    let seed = seed.unwrap_or_else(|| {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |elapsed| {
+                elapsed.as_secs() ^ u64::from(elapsed.subsec_nanos())
+            })
+    });
+    let draft = match cli.draft {
+        Some(draft) => draft.into(),
+        None => referencing::Draft::default().detect(&schema)?,
+    };
+    let registry = referencing::Registry::options()
+        .retriever(schema_retriever(cli)?)
+        .draft(draft)
+        .build(
+            schemas
+                .into_iter()
+                .chain([(base_uri.clone(), draft.create_resource(schema))]),
+        )?;
+    let mut generator = Generator::new(&registry, &base_uri, seed)?;
+    let instances = (0..count)
+        .map(|_| generator.generate(validator))
+        .collect::<Result<Vec<_>, _>>()?;
+    for instance in instances {
+        println!("{instance}");
+    }
+    Ok(true)
+}
+
+fn run_lint(cli: &Cli, schema_path: &Path, format: ReportFormat) -> Result<bool, Box<dyn Error>> {
+    let schema = read_schema(schema_path, cli.relaxed_json)?;
+    let draft = match cli.draft {
+        Some(draft) => draft.into(),
+        None => referencing::Draft::default().detect(&schema)?,
+    };
+    let findings = lint::lint(&schema, draft);
+    let name = schema_path.to_string_lossy();
+    match format {
+        ReportFormat::Text => {
+            for finding in &findings {
+                println!("{name}#{}: {finding}", finding.location);
+            }
+        }
+        ReportFormat::Json => println!(
+            "{}",
+            json!({
+                "schema": name,
+                "findings": findings.iter().map(lint::Finding::to_json).collect::<Vec<_>>(),
+            })
+        ),
+    }
+    Ok(findings
+        .iter()
+        .all(|finding| finding.severity != lint::Severity::Error))
+}
+
+fn run_diff(
+    cli: &Cli,
+    old_path: &Path,
+    new_path: &Path,
+    format: ReportFormat,
+) -> Result<bool, Box<dyn Error>> {
+    if is_stdin(old_path) && is_stdin(new_path) {
+        return Err("stdin can not provide both schemas".into());
+    }
+    let old = read_schema(old_path, cli.relaxed_json)?;
+    let new = read_schema(new_path, cli.relaxed_json)?;
+    let draft = match cli.draft {
+        Some(draft) => draft.into(),
+        None => referencing::Draft::default().detect(&new)?,
+    };
+    let changes = diff::diff(&old, &new, draft);
+    match format {
+        ReportFormat::Text => {
+            for change in &changes {
+                println!("#{}: {change}", change.location);
+            }
+        }
+        ReportFormat::Json => println!(
+            "{}",
+            json!({
+                "old": old_path.to_string_lossy(),
+                "new": new_path.to_string_lossy(),
+                "changes": changes.iter().map(diff::Change::to_json).collect::<Vec<_>>(),
+            })
+        ),
+    }
+    Ok(changes
+        .iter()
+        .all(|change| change.compatibility != diff::Compatibility::Breaking))
+}
+
+fn run_migrate(cli: &Cli, schema_path: &Path, to: Draft) -> Result<bool, Box<dyn Error>> {
+    let schema = read_schema(schema_path, cli.relaxed_json)?;
+    let from = match cli.draft {
+        Some(draft) => draft.into(),
+        None => referencing::Draft::default().detect(&schema)?,
+    };
+    let migration = migrate::migrate(schema, from, to.into())?;
+    println!("{}", serde_json::to_string_pretty(&migration.schema)?);
+    for note in &migration.notes {
+        eprintln!("{note}");
+    }
+    Ok(true)
+}
+
+/// The exit code of the process, the most severe status of all instances.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Status {
+    /// All instances are valid, or the subcommand succeeded.
+    Valid = 0,
+    /// At least one instance is invalid, or the subcommand found problems.
+    Invalid = 1,
+    /// At least one schema could not be compiled.
+    InvalidSchema = 2,
+    /// A file could not be read or parsed, or the options are invalid.
+    Error = 3,
+}
+
+impl Status {
+    fn of(report: &InstanceReport) -> Status {
+        match &report.outcome {
+            Outcome::Valid { .. } => Status::Valid,
+            Outcome::Invalid { .. } => Status::Invalid,
+            Outcome::InvalidSchema(error) => Status::of_schema(error),
         }
     }
-    Ok(success)
+
+    /// Retrieval failures while compiling a schema are I/O errors rather than invalid schemas.
+    fn of_schema(error: &SchemaError) -> Status {
+        if error.is_unretrievable() {
+            Status::Error
+        } else {
+            Status::InvalidSchema
+        }
+    }
+}
+
+impl From<bool> for Status {
+    fn from(valid: bool) -> Status {
+        if valid {
+            Status::Valid
+        } else {
+            Status::Invalid
+        }
+    }
+}
+
+impl From<Status> for ExitCode {
+    fn from(status: Status) -> ExitCode {
+        ExitCode::from(status as u8)
+    }
+}
+
+/// A schema that subcommands need to compile is invalid, exiting with
+/// [`Status::InvalidSchema`].
+#[derive(Debug)]
+struct InvalidSchemaError(SchemaError);
+
+impl std::fmt::Display for InvalidSchemaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Schema is invalid: {}", self.0)
+    }
+}
+
+impl Error for InvalidSchemaError {}
+
+fn run(cli: &Cli) -> Result<Status, Box<dyn Error>> {
+    let succeeded = match &cli.command {
+        Some(Command::Bundle { schema }) => run_bundle(cli, schema),
+        Some(Command::Deref { schema, max_depth }) => run_deref(cli, schema, *max_depth),
+        Some(Command::Generate {
+            schema,
+            count,
+            seed,
+        }) => run_generate(cli, schema, *count, *seed),
+        Some(Command::Lint { schema, output }) => run_lint(cli, schema, *output),
+        Some(Command::Diff { old, new, output }) => run_diff(cli, old, new, *output),
+        Some(Command::Migrate { schema, to }) => run_migrate(cli, schema, *to),
+        None => return run_check(cli),
+    };
+    succeeded.map(Status::from)
+}
+
+/// Validate instances, the default command.
+fn run_check(cli: &Cli) -> Result<Status, Box<dyn Error>> {
+    // Checked here rather than by clap, as these options also apply to subcommands
+    if cli.base_uri.is_some() && cli.batch {
+        return Err("`--base-uri` cannot be used with `--batch`".into());
+    }
+    if cli.relaxed_json && cli.strict_json {
+        return Err("`--relaxed-json` cannot be used with `--strict-json`".into());
+    }
+    // - Some(true)  if --assert-format
+    // - Some(false) if --no-assert-format
+    // - JSONSCHEMA_ASSERT_FORMAT if neither, and it is set
+    // - None        otherwise (use builder’s default)
+    let assert_format = match (cli.assert_format, cli.no_assert_format) {
+        (Some(true), _) => Some(true),
+        (_, Some(true)) => Some(false),
+        // Empty values are ignored, like for the variables handled by clap
+        _ => match env::var("JSONSCHEMA_ASSERT_FORMAT")
+            .ok()
+            .filter(|value| !value.is_empty())
+        {
+            Some(value) => Some(value.parse().map_err(|_| {
+                format!(
+                    "Invalid JSONSCHEMA_ASSERT_FORMAT value `{value}`, expected `true` or `false`"
+                )
+            })?),
+            None => cli.assert_format.or(cli.no_assert_format),
+        },
+    };
+    let mut validators = Validators::new(
+        cli.base_uri.clone(),
+        cli.draft,
+        assert_format,
+        cli.timings,
+        schema_retriever(cli)?,
+        read_schema_dirs(&cli.schema_dirs, cli.draft, cli.relaxed_json)?,
+    );
+    let catalog = cli.lang.as_deref().map(Catalog::from_path).transpose()?;
+    let text = cli.output == Output::Text && !cli.quiet && !cli.list_invalid;
+    let color = text && !cli.batch && cli.color.enabled();
+    let options = CheckOptions {
+        strict_json: cli.strict_json,
+        relaxed_json: cli.relaxed_json,
+        timings: cli.timings,
+        catalog: catalog.as_ref(),
+        excerpts: text && !cli.batch,
+        annotations: cli.verbose,
+        pointer: cli.instance_pointer.as_deref(),
+    };
+    if cli.batch {
+        return run_batch(&mut validators, options);
+    }
+    let config = if let Some(path) = &cli.schema_map {
+        Some(Config::from_schema_map(path)?)
+    } else {
+        let config_path = cli.config.clone().or_else(|| {
+            cli.instance_dir
+                .as_ref()
+                .map(|dir| dir.join(DEFAULT_CONFIG))
+                .filter(|path| path.is_file())
+        });
+        config_path.as_deref().map(Config::from_path).transpose()?
+    };
+    let instances = expand_globs(cli.instances.clone().unwrap_or_default())?;
+    let schema_from_stdin = cli.schema.as_deref().is_some_and(is_stdin);
+    if schema_from_stdin && instances.iter().any(|path| is_stdin(path)) {
+        return Err("stdin can not provide both the schema and an instance".into());
+    }
+    let pairs = collect_instances(
+        &instances,
+        cli.instance_dir.as_deref(),
+        cli.schema.as_deref(),
+        config.as_ref(),
+    )?;
+    let mut coverage = cli.coverage.as_ref().map(|_| BTreeMap::new());
+    let reports = validate_instances(
+        pairs,
+        &mut validators,
+        options,
+        cli.format,
+        Recorder::new(
+            text,
+            cli.diagnostics,
+            cli.list_invalid,
+            color,
+            cli.max_failures,
+            cli.max_errors,
+        ),
+        cli.jobs
+            .unwrap_or_else(|| thread::available_parallelism().unwrap_or(NonZeroUsize::MIN)),
+        coverage.as_mut(),
+    )?;
+    match cli.output {
+        Output::Text => {}
+        Output::Junit => report::write_junit(io::stdout().lock(), &reports)?,
+        Output::Tap => report::write_tap(io::stdout().lock(), &reports)?,
+        Output::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(&report::combine(&reports))?
+        ),
+        Output::Sarif => println!(
+            "{}",
+            serde_json::to_string_pretty(&report::sarif(&reports))?
+        ),
+    }
+    if let (Some(path), Some(coverage)) = (&cli.coverage, &coverage) {
+        report::write_coverage(path, coverage)?;
+    }
+    if let Some(path) = &cli.report_file {
+        report::write_file(path, &reports)?;
+    }
+    if let Some(dir) = &cli.report_dir {
+        report::write_dir(dir, &reports)?;
+    }
+    Ok(reports
+        .iter()
+        .map(Status::of)
+        .max()
+        .unwrap_or(Status::Valid))
 }
 
 fn main() -> ExitCode {
-    let config = Cli::parse();
+    // Clap exits with 2 on usage errors, which is reserved for invalid schemas
+    let cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        Err(error) => {
+            let _ = error.print();
+            return if error.use_stderr() {
+                Status::Error.into()
+            } else {
+                ExitCode::SUCCESS
+            };
+        }
+    };
 
-    if config.version {
+    if cli.version {
         println!(concat!("Version: ", env!("CARGO_PKG_VERSION")));
         return ExitCode::SUCCESS;
     }
 
-    if let Some(schema) = config.schema {
-        if let Some(instances) = config.instances {
-            // - Some(true)  if --assert-format
-            // - Some(false) if --no-assert-format
-            // - None        if neither (use builder’s default)
-            let assert_format = config.assert_format.or(config.no_assert_format);
-            return match validate_instances(&instances, &schema, config.draft, assert_format) {
-                Ok(true) => ExitCode::SUCCESS,
-                Ok(false) => ExitCode::FAILURE,
-                Err(error) => {
-                    println!("Error: {error}");
-                    ExitCode::FAILURE
-                }
-            };
+    match run(&cli) {
+        Ok(status) => status.into(),
+        Err(error) => {
+            if !cli.quiet {
+                println!("Error: {error}");
+            }
+            match error.downcast_ref::<InvalidSchemaError>() {
+                Some(InvalidSchemaError(error)) => Status::of_schema(error).into(),
+                None => Status::Error.into(),
+            }
         }
     }
-    ExitCode::SUCCESS
 }
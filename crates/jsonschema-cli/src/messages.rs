@@ -0,0 +1,36 @@
+//! Message catalogs replacing the default text of validation errors.
+//!
+//! ```toml
+//! [messages]
+//! required = "La propriété {property} est requise"
+//! minLength = "{instance} doit contenir au moins {limit} caractères"
+//! ```
+//!
+//! Keys are error kind names, e.g. `type` or `oneOfNotValid`, and values are templates with
+//! placeholders for the error parameters. Errors without a template keep their default text.
+use std::{collections::HashMap, error::Error, fs, path::Path};
+
+use jsonschema::ValidationError;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Catalog {
+    #[serde(default)]
+    messages: HashMap<String, String>,
+}
+
+impl Catalog {
+    pub(crate) fn from_path(path: &Path) -> Result<Catalog, Box<dyn Error>> {
+        toml::from_str(&fs::read_to_string(path)?)
+            .map_err(|error| format!("Invalid message catalog {}: {error}", path.display()).into())
+    }
+
+    /// The error message from the matching template, if any.
+    pub(crate) fn message(&self, error: &ValidationError<'_>) -> String {
+        match self.messages.get(error.kind.name()) {
+            Some(template) => error.format_with(template),
+            None => error.to_string(),
+        }
+    }
+}
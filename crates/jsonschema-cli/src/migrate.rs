@@ -0,0 +1,366 @@
+//! The `migrate` subcommand, rewriting a schema for a newer draft.
+use std::{error::Error, fmt};
+
+use jsonschema::analyze::{draft_name, is_subschema_keyword, REF_SIBLINGS};
+use referencing::Draft;
+use serde_json::{Map, Value};
+
+use crate::pointer::push_segment;
+
+pub(crate) struct Migration {
+    pub(crate) schema: Value,
+    /// Constructs that have to be migrated by hand.
+    pub(crate) notes: Vec<Note>,
+}
+
+pub(crate) struct Note {
+    /// JSON Pointer to the subschema in the migrated schema.
+    pub(crate) location: String,
+    pub(crate) message: String,
+}
+
+impl fmt::Display for Note {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{}: {}", self.location, self.message)
+    }
+}
+
+/// Rewrite `schema`, written for `from`, to use the keywords of `to` instead, keeping what it
+/// accepts.
+pub(crate) fn migrate(schema: Value, from: Draft, to: Draft) -> Result<Migration, Box<dyn Error>> {
+    if to < from {
+        return Err(format!(
+            "Cannot migrate from {} to the older {}",
+            draft_name(from),
+            draft_name(to)
+        )
+        .into());
+    }
+    let mut migrator = Migrator {
+        from,
+        to,
+        recursive: from == Draft::Draft201909 && has_recursive_anchor(&schema),
+        renames: Vec::new(),
+        notes: Vec::new(),
+    };
+    let mut schema = schema;
+    if let Value::Object(object) = &mut schema {
+        object.insert(
+            "$schema".to_string(),
+            Value::String(meta_schema(to).to_string()),
+        );
+    }
+    migrator.schema(&mut schema, &mut String::new(), &mut String::new());
+    if !migrator.renames.is_empty() {
+        migrator.rewrite_references(&mut schema);
+    }
+    Ok(Migration {
+        schema,
+        notes: migrator.notes,
+    })
+}
+
+struct Migrator {
+    from: Draft,
+    to: Draft,
+    /// Whether `$recursiveRef`s have a `$recursiveAnchor` to extend, and become `$dynamicRef`s.
+    recursive: bool,
+    /// Locations of moved keywords, before and after the migration.
+    renames: Vec<(String, String)>,
+    notes: Vec<Note>,
+}
+
+impl Migrator {
+    /// Migrate the subschema at `old` in the original schema, and `new` in the migrated one.
+    fn schema(&mut self, value: &mut Value, old: &mut String, new: &mut String) {
+        let Value::Object(object) = value else {
+            return;
+        };
+        let mut moved = Vec::new();
+        if self.from == Draft::Draft4 {
+            self.identifiers(object);
+            for (limit, exclusive) in [
+                ("maximum", "exclusiveMaximum"),
+                ("minimum", "exclusiveMinimum"),
+            ] {
+                // Boolean exclusive bounds turn their inclusive counterpart into a number
+                if let Some(Value::Bool(enabled)) = object.get(exclusive) {
+                    let enabled = *enabled;
+                    object.remove(exclusive);
+                    if enabled {
+                        if let Some(limit) = object.remove(limit) {
+                            object.insert(exclusive.to_string(), limit);
+                        }
+                    }
+                }
+            }
+        }
+        if self.from <= Draft::Draft7 && self.to >= Draft::Draft201909 {
+            if object.contains_key("$ref") {
+                let siblings: Vec<String> = object
+                    .keys()
+                    .filter(|keyword| {
+                        *keyword != "$ref" && !REF_SIBLINGS.contains(&keyword.as_str())
+                    })
+                    .map(|keyword| format!("`{keyword}`"))
+                    .collect();
+                if !siblings.is_empty() {
+                    self.note(
+                        new,
+                        format!(
+                            "{} next to `$ref` had no effect before Draft 2019-09 and now apply, remove them to keep the previous behavior",
+                            siblings.join(", ")
+                        ),
+                    );
+                }
+            }
+            if let Some(definitions) = object.remove("definitions") {
+                if object.contains_key("$defs") {
+                    object.insert("definitions".to_string(), definitions);
+                    self.note(
+                        new,
+                        "Both `definitions` and `$defs` are present, merge them by hand"
+                            .to_string(),
+                    );
+                } else {
+                    object.insert("$defs".to_string(), definitions);
+                    moved.push(("$defs", "definitions"));
+                }
+            }
+            if self.dependencies(object, new) {
+                moved.push(("dependentSchemas", "dependencies"));
+            }
+        }
+        if self.from <= Draft::Draft201909 && self.to == Draft::Draft202012 {
+            if object.get("items").is_some_and(Value::is_array) {
+                let items = object.remove("items").expect("Checked above");
+                if let Some(additional) = object.remove("additionalItems") {
+                    object.insert("items".to_string(), additional);
+                    moved.push(("items", "additionalItems"));
+                }
+                object.insert("prefixItems".to_string(), items);
+                moved.push(("prefixItems", "items"));
+            }
+            self.recursion(object, new);
+        }
+        if let Some(Value::String(uri)) = object.get_mut("$schema") {
+            // Nested resources may declare their own draft
+            if is_draft(uri) {
+                *uri = meta_schema(self.to).to_string();
+            }
+        }
+        for (to, from) in &moved {
+            self.renames
+                .push((format!("{old}/{from}"), format!("{new}/{to}")));
+        }
+        for (keyword, value) in object.iter_mut() {
+            if !is_subschema_keyword(keyword) {
+                continue;
+            }
+            let original = moved
+                .iter()
+                .find(|(to, _)| *to == keyword)
+                .map_or(keyword.as_str(), |(_, from)| from);
+            let (old_length, new_length) = (old.len(), new.len());
+            push_segment(old, original);
+            push_segment(new, keyword);
+            match value {
+                Value::Array(items) => {
+                    for (idx, item) in items.iter_mut().enumerate() {
+                        let (old_length, new_length) = (old.len(), new.len());
+                        push_segment(old, &idx.to_string());
+                        push_segment(new, &idx.to_string());
+                        self.schema(item, old, new);
+                        old.truncate(old_length);
+                        new.truncate(new_length);
+                    }
+                }
+                Value::Object(members)
+                    if matches!(
+                        keyword.as_str(),
+                        "$defs"
+                            | "definitions"
+                            | "dependencies"
+                            | "dependentSchemas"
+                            | "patternProperties"
+                            | "properties"
+                    ) =>
+                {
+                    for (name, member) in members.iter_mut() {
+                        let (old_length, new_length) = (old.len(), new.len());
+                        push_segment(old, name);
+                        push_segment(new, name);
+                        self.schema(member, old, new);
+                        old.truncate(old_length);
+                        new.truncate(new_length);
+                    }
+                }
+                _ => self.schema(value, old, new),
+            }
+            old.truncate(old_length);
+            new.truncate(new_length);
+        }
+    }
+
+    /// Turn Draft 4 `id`s into `$id`s, or into `$anchor`s for plain-name fragments from 2019-09.
+    fn identifiers(&mut self, object: &mut Map<String, Value>) {
+        if object.contains_key("$id") || !object.get("id").is_some_and(Value::is_string) {
+            return;
+        }
+        let Some(Value::String(id)) = object.remove("id") else {
+            return;
+        };
+        match id.strip_prefix('#') {
+            Some(anchor) if self.to >= Draft::Draft201909 => {
+                object.insert("$anchor".to_string(), Value::String(anchor.to_string()));
+            }
+            _ => {
+                object.insert("$id".to_string(), Value::String(id));
+            }
+        }
+    }
+
+    /// Split `dependencies` into `dependentRequired` for property names and `dependentSchemas`
+    /// for subschemas, returning whether there are any of the latter.
+    fn dependencies(&mut self, object: &mut Map<String, Value>, new: &str) -> bool {
+        if !object.get("dependencies").is_some_and(Value::is_object) {
+            return false;
+        }
+        if object.contains_key("dependentRequired") || object.contains_key("dependentSchemas") {
+            self.note(
+                new,
+                "`dependencies` next to `dependentRequired` or `dependentSchemas`, merge them by hand"
+                    .to_string(),
+            );
+            return false;
+        }
+        let Some(Value::Object(dependencies)) = object.remove("dependencies") else {
+            return false;
+        };
+        let mut required = Map::new();
+        let mut schemas = Map::new();
+        for (name, dependency) in dependencies {
+            if dependency.is_array() {
+                required.insert(name, dependency);
+            } else {
+                schemas.insert(name, dependency);
+            }
+        }
+        if !required.is_empty() {
+            object.insert("dependentRequired".to_string(), Value::Object(required));
+        }
+        if schemas.is_empty() {
+            return false;
+        }
+        object.insert("dependentSchemas".to_string(), Value::Object(schemas));
+        true
+    }
+
+    /// Replace `$recursiveAnchor` and `$recursiveRef` with their 2020-12 counterparts.
+    fn recursion(&mut self, object: &mut Map<String, Value>, new: &str) {
+        if let Some(anchor) = object.remove("$recursiveAnchor") {
+            if anchor == Value::Bool(true) {
+                object.insert(
+                    "$dynamicAnchor".to_string(),
+                    Value::String("meta".to_string()),
+                );
+            }
+        }
+        let Some(reference) = object.remove("$recursiveRef") else {
+            return;
+        };
+        if object.contains_key("$ref") {
+            object.insert("$recursiveRef".to_string(), reference);
+            self.note(
+                new,
+                "`$recursiveRef` next to `$ref`, replace it with a `$dynamicRef` by hand"
+                    .to_string(),
+            );
+        } else if self.recursive {
+            object.insert(
+                "$dynamicRef".to_string(),
+                Value::String("#meta".to_string()),
+            );
+        } else {
+            // Without a `$recursiveAnchor` to extend, it is a plain reference
+            object.insert("$ref".to_string(), reference);
+        }
+    }
+
+    /// Point references within the document to the new locations of moved subschemas.
+    fn rewrite_references(&self, value: &mut Value) {
+        match value {
+            Value::Object(object) => {
+                for (keyword, value) in object.iter_mut() {
+                    match value {
+                        Value::String(reference) if keyword == "$ref" => {
+                            if let Some(rewritten) = self.rewrite(reference) {
+                                *reference = rewritten;
+                            }
+                        }
+                        _ if matches!(
+                            keyword.as_str(),
+                            "const" | "default" | "enum" | "examples"
+                        ) => {}
+                        _ => self.rewrite_references(value),
+                    }
+                }
+            }
+            Value::Array(items) => {
+                for item in items {
+                    self.rewrite_references(item);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn rewrite(&self, reference: &str) -> Option<String> {
+        let pointer = reference.strip_prefix('#')?;
+        let (from, to) = self
+            .renames
+            .iter()
+            .filter(|(from, _)| {
+                pointer
+                    .strip_prefix(from.as_str())
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+            })
+            .max_by_key(|(from, _)| from.len())?;
+        Some(format!("#{to}{}", &pointer[from.len()..]))
+    }
+
+    fn note(&mut self, location: &str, message: String) {
+        self.notes.push(Note {
+            location: location.to_string(),
+            message,
+        });
+    }
+}
+
+fn has_recursive_anchor(value: &Value) -> bool {
+    match value {
+        Value::Object(object) => {
+            object.get("$recursiveAnchor") == Some(&Value::Bool(true))
+                || object.values().any(has_recursive_anchor)
+        }
+        Value::Array(items) => items.iter().any(has_recursive_anchor),
+        _ => false,
+    }
+}
+
+/// Whether `uri` refers to one of the standard meta-schemas rather than a custom one.
+fn is_draft(uri: &str) -> bool {
+    ["http://json-schema.org/", "https://json-schema.org/"]
+        .iter()
+        .any(|prefix| uri.starts_with(prefix))
+}
+
+fn meta_schema(draft: Draft) -> &'static str {
+    match draft {
+        Draft::Draft4 => "http://json-schema.org/draft-04/schema#",
+        Draft::Draft6 => "http://json-schema.org/draft-06/schema#",
+        Draft::Draft7 => "http://json-schema.org/draft-07/schema#",
+        Draft::Draft201909 => "https://json-schema.org/draft/2019-09/schema",
+        _ => "https://json-schema.org/draft/2020-12/schema",
+    }
+}
@@ -0,0 +1,38 @@
+//! Building JSON Pointers to subschemas while walking a schema.
+use serde_json::{Map, Value};
+
+/// Append the JSON Pointer from `parent` to `child`, which is one of its subresources nested at
+/// most two levels deep, e.g. in `items`, `allOf/0` or `properties/name`.
+pub(crate) fn push_location(parent: &Map<String, Value>, child: &Value, location: &mut String) {
+    for (keyword, value) in parent {
+        if std::ptr::eq(value, child) {
+            push_segment(location, keyword);
+            return;
+        }
+        match value {
+            Value::Array(items) => {
+                if let Some(index) = items.iter().position(|item| std::ptr::eq(item, child)) {
+                    push_segment(location, keyword);
+                    push_segment(location, &index.to_string());
+                    return;
+                }
+            }
+            Value::Object(members) => {
+                if let Some((name, _)) = members
+                    .iter()
+                    .find(|(_, member)| std::ptr::eq(*member, child))
+                {
+                    push_segment(location, keyword);
+                    push_segment(location, name);
+                    return;
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+pub(crate) fn push_segment(location: &mut String, segment: &str) {
+    location.push('/');
+    location.push_str(&segment.replace('~', "~0").replace('/', "~1"));
+}
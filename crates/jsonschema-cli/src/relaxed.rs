@@ -0,0 +1,103 @@
+//! Relaxed JSON syntax accepted with `--relaxed-json`, as used by hand-written config files.
+//!
+//! Comments and trailing commas, as allowed by JSONC, are replaced with whitespace, so the parser
+//! reports any remaining syntax errors at their original lines and columns. Other JSON5 syntax,
+//! e.g. unquoted keys or single-quoted strings, is not accepted.
+
+/// Blank out `//` and `/* */` comments, and commas before a closing bracket or brace.
+pub(crate) fn strip(text: &str) -> String {
+    let mut bytes = text.as_bytes().to_vec();
+    blank_comments(&mut bytes);
+    blank_trailing_commas(&mut bytes);
+    // Only ASCII bytes are replaced with ASCII whitespace, and multi-byte characters are only
+    // ever blanked as a whole
+    String::from_utf8(bytes).expect("Stripping keeps UTF-8 valid")
+}
+
+/// Blank a byte, keeping line breaks so positions stay the same.
+fn blank(byte: &mut u8) {
+    if *byte != b'\n' {
+        *byte = b' ';
+    }
+}
+
+/// Call `visit` with the position of every byte outside of strings.
+fn outside_strings(bytes: &mut [u8], mut visit: impl FnMut(&mut [u8], usize) -> usize) {
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut position = 0;
+    while position < bytes.len() {
+        let byte = bytes[position];
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if byte == b'\\' {
+                escaped = true;
+            } else if byte == b'"' {
+                in_string = false;
+            }
+            position += 1;
+        } else if byte == b'"' {
+            in_string = true;
+            position += 1;
+        } else {
+            position = visit(bytes, position);
+        }
+    }
+}
+
+fn blank_comments(bytes: &mut [u8]) {
+    outside_strings(bytes, |bytes, start| {
+        let end = match bytes.get(start..start + 2) {
+            Some(b"//") => bytes[start..]
+                .iter()
+                .position(|byte| *byte == b'\n')
+                .map_or(bytes.len(), |offset| start + offset),
+            Some(b"/*") => bytes[start + 2..]
+                .windows(2)
+                .position(|window| window == b"*/")
+                .map_or(bytes.len(), |offset| start + 2 + offset + 2),
+            _ => return start + 1,
+        };
+        bytes[start..end].iter_mut().for_each(blank);
+        end
+    });
+}
+
+fn blank_trailing_commas(bytes: &mut [u8]) {
+    outside_strings(bytes, |bytes, start| {
+        if bytes[start] == b',' {
+            let next = bytes[start + 1..]
+                .iter()
+                .find(|byte| !byte.is_ascii_whitespace());
+            if matches!(next, Some(b']' | b'}')) {
+                bytes[start] = b' ';
+            }
+        }
+        start + 1
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::strip;
+
+    #[test]
+    fn comments_and_trailing_commas() {
+        let text =
+            "{\n  // port\n  \"port\": 1, /* default */\n  \"hosts\": [\"a//b\", \"/*c*/\",],\n}";
+        let stripped = strip(text);
+        assert_eq!(stripped.len(), text.len());
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&stripped).unwrap(),
+            serde_json::json!({"port": 1, "hosts": ["a//b", "/*c*/"]})
+        );
+    }
+
+    #[test]
+    fn positions_are_kept() {
+        let error =
+            serde_json::from_str::<serde_json::Value>(&strip("/* é\n */ [1,, 2]")).unwrap_err();
+        assert_eq!((error.line(), error.column()), (2, 8));
+    }
+}
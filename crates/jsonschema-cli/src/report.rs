@@ -0,0 +1,566 @@
+//! Machine-readable validation reports.
+use std::{
+    collections::{BTreeMap, HashMap},
+    error::Error,
+    fs,
+    io::{self, Write},
+    path::{Component, Path, PathBuf},
+};
+
+use jsonschema::{
+    error::ValidationErrorKind, BatchError, BatchReport, Coverage, InstanceOutcome, ValidationError,
+};
+use serde_json::json;
+
+use crate::{messages::Catalog, strict::Issue};
+
+pub(crate) enum Outcome {
+    Valid {
+        /// Annotations collected by the schema, if `--verbose` is given.
+        annotations: Vec<Annotation>,
+    },
+    Invalid {
+        errors: Vec<BatchError>,
+        /// Excerpts of the values of schema errors, by error index, for the text output.
+        excerpts: Vec<Option<String>>,
+    },
+    InvalidSchema(SchemaError),
+}
+
+/// Why a schema could not be compiled.
+#[derive(Clone, Debug)]
+pub(crate) struct SchemaError {
+    message: String,
+    /// Every error of the schema, located within it. The instance location of each error is the
+    /// invalid part of the schema and the keyword location is within its meta-schema.
+    errors: Vec<BatchError>,
+    /// Whether a document the schema references could not be retrieved, e.g. a missing file,
+    /// rather than the schema itself being invalid.
+    unretrievable: bool,
+}
+
+impl SchemaError {
+    /// The compilation `error`, along with all `meta_errors` of the schema against its
+    /// meta-schema, if it is invalid there.
+    pub(crate) fn new<'a>(
+        error: &ValidationError<'_>,
+        meta_errors: impl IntoIterator<Item = ValidationError<'a>>,
+    ) -> SchemaError {
+        let mut errors: Vec<BatchError> = Vec::new();
+        for error in meta_errors {
+            let error = schema_error(&error);
+            // Meta-schemas of vocabularies repeat checks, e.g. the type of every subschema
+            if !errors.iter().any(|known| {
+                known.instance_location() == error.instance_location()
+                    && known.error() == error.error()
+            }) {
+                errors.push(error);
+            }
+        }
+        if errors.is_empty() {
+            errors.push(schema_error(error));
+        }
+        let unretrievable = matches!(
+            &error.kind,
+            ValidationErrorKind::Referencing(referencing::Error::Unretrievable { .. })
+                | ValidationErrorKind::ReferencingVia {
+                    error: referencing::Error::Unretrievable { .. },
+                    ..
+                }
+        );
+        SchemaError {
+            message: error.to_string(),
+            errors,
+            unretrievable,
+        }
+    }
+
+    pub(crate) fn errors(&self) -> &[BatchError] {
+        &self.errors
+    }
+
+    /// Whether compiling failed because a referenced document could not be retrieved.
+    pub(crate) fn is_unretrievable(&self) -> bool {
+        self.unretrievable
+    }
+
+    /// JSON diagnostics for `--diagnostics json`, e.g. for editors to highlight the invalid parts.
+    pub(crate) fn diagnostics<'a>(
+        &'a self,
+        schema: &'a Path,
+    ) -> impl Iterator<Item = serde_json::Value> + 'a {
+        self.errors.iter().map(move |error| {
+            let mut diagnostic = json!({
+                "schema": crate::display_name(schema),
+                "location": error.instance_location(),
+                "message": error.error(),
+            });
+            if let Some(keyword) = error.keyword() {
+                diagnostic["keyword"] = json!(keyword);
+            }
+            diagnostic
+        })
+    }
+}
+
+impl std::fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// A schema error located within the schema, which is validated as an instance of its
+/// meta-schema.
+fn schema_error(error: &ValidationError<'_>) -> BatchError {
+    let batch_error = BatchError::new(
+        error.instance_path.as_str(),
+        Some(error.schema_path.as_str().to_string()),
+        error.to_string(),
+    );
+    match schema_keyword(error.schema_path.as_str()) {
+        Some(keyword) => batch_error.with_keyword(keyword),
+        None => batch_error,
+    }
+}
+
+/// The keyword of the schema containing the invalid part, i.e. the last property described by
+/// `properties` in the meta-schema location of the error, e.g. `properties` for
+/// `/allOf/1/$ref/properties/properties/additionalProperties/$dynamicRef/type`.
+fn schema_keyword(meta_schema_location: &str) -> Option<String> {
+    let mut keyword = None;
+    let mut segments = meta_schema_location.split('/').skip(1);
+    while let Some(segment) = segments.next() {
+        if segment == "properties" {
+            keyword = segments.next();
+        }
+    }
+    keyword.map(|keyword| keyword.replace("~1", "/").replace("~0", "~"))
+}
+
+/// An annotation produced while validating an instance, e.g. a `title` or a matched `anyOf` branch.
+pub(crate) struct Annotation {
+    pub(crate) instance_location: String,
+    pub(crate) keyword_location: String,
+    pub(crate) value: serde_json::Value,
+}
+
+impl Outcome {
+    pub(crate) fn valid() -> Outcome {
+        Outcome::Valid {
+            annotations: Vec::new(),
+        }
+    }
+
+    pub(crate) fn invalid(errors: Vec<BatchError>) -> Outcome {
+        Outcome::Invalid {
+            errors,
+            excerpts: Vec::new(),
+        }
+    }
+}
+
+pub(crate) fn validation_error(
+    error: &ValidationError<'_>,
+    catalog: Option<&Catalog>,
+) -> BatchError {
+    let batch_error = BatchError::from(error);
+    match catalog {
+        Some(catalog) => batch_error.with_error(catalog.message(error)),
+        None => batch_error,
+    }
+}
+
+/// `error` of the value at `pointer` within an instance, located within the whole instance.
+pub(crate) fn nested_error(error: BatchError, pointer: &str) -> BatchError {
+    if pointer.is_empty() {
+        return error;
+    }
+    let nested = BatchError::new(
+        format!("{pointer}{}", error.instance_location()),
+        error.keyword_location().map(str::to_string),
+        error.error(),
+    );
+    match error.keyword() {
+        Some(keyword) => nested.with_keyword(keyword),
+        None => nested,
+    }
+}
+
+/// Errors not caused by a schema keyword.
+pub(crate) fn strict_error(issue: Issue) -> BatchError {
+    // Reports show the location next to the message
+    BatchError::new(issue.location.as_str(), None, issue.message)
+}
+
+/// The validation result of a single instance.
+pub(crate) struct InstanceReport {
+    pub(crate) instance: PathBuf,
+    /// The line of the instance in a newline-delimited JSON file.
+    pub(crate) line: Option<usize>,
+    pub(crate) schema: PathBuf,
+    pub(crate) outcome: Outcome,
+}
+
+impl InstanceReport {
+    pub(crate) fn is_valid(&self) -> bool {
+        matches!(self.outcome, Outcome::Valid { .. })
+    }
+
+    /// The instance file, followed by the line for records of newline-delimited JSON files.
+    pub(crate) fn name(&self) -> String {
+        let instance = self.instance.to_string_lossy();
+        match self.line {
+            Some(line) => format!("{instance}:{line}"),
+            None => instance.into_owned(),
+        }
+    }
+
+    pub(crate) fn to_outcome(&self) -> InstanceOutcome {
+        let instance = self.name();
+        let outcome = match &self.outcome {
+            Outcome::Valid { .. } => InstanceOutcome::new(instance, []),
+            Outcome::Invalid { errors, .. } => {
+                InstanceOutcome::new(instance, errors.iter().cloned())
+            }
+            Outcome::InvalidSchema(error) => {
+                InstanceOutcome::invalid_schema(instance, error.to_string())
+                    .with_schema_errors(error.errors().iter().cloned())
+            }
+        };
+        outcome.with_schema(self.schema.to_string_lossy())
+    }
+}
+
+/// Combine all reports into one.
+pub(crate) fn combine(reports: &[InstanceReport]) -> BatchReport {
+    reports.iter().map(InstanceReport::to_outcome).collect()
+}
+
+/// A SARIF 2.1.0 log with a result per error.
+///
+/// Rule IDs are keyword names, and JSON Pointers to the invalid parts of instances are logical
+/// locations, as SARIF regions need line and column numbers.
+pub(crate) fn sarif(reports: &[InstanceReport]) -> serde_json::Value {
+    let mut rules = Vec::new();
+    let mut results = Vec::new();
+    let mut invalid_schemas = Vec::new();
+    for report in reports {
+        match &report.outcome {
+            Outcome::Invalid { errors, .. } => {
+                let uri = crate::artifact_uri(&report.instance);
+                for error in errors {
+                    let rule = match (error.keyword(), error.keyword_location()) {
+                        (Some(keyword), _) => keyword,
+                        (None, Some(_)) => "falseSchema",
+                        // Only strict JSON errors are not caused by the schema
+                        (None, None) => "strictJson",
+                    };
+                    let mut result = sarif_result(&mut rules, rule, &uri, error.error());
+                    if let Some(line) = report.line {
+                        result["locations"][0]["physicalLocation"]["region"] =
+                            json!({"startLine": line});
+                    }
+                    result["locations"][0]["logicalLocations"] =
+                        json!([{"fullyQualifiedName": error.instance_location()}]);
+                    if let Some(keyword_location) = error.keyword_location() {
+                        result["properties"] = json!({"keywordLocation": keyword_location});
+                    }
+                    results.push(result);
+                }
+            }
+            // Every schema is reported once if invalid
+            Outcome::InvalidSchema(error) if !invalid_schemas.contains(&&report.schema) => {
+                invalid_schemas.push(&report.schema);
+                let uri = crate::artifact_uri(&report.schema);
+                for error in error.errors() {
+                    let mut result = sarif_result(&mut rules, "invalidSchema", &uri, error.error());
+                    result["locations"][0]["logicalLocations"] =
+                        json!([{"fullyQualifiedName": error.instance_location()}]);
+                    if let Some(keyword) = error.keyword() {
+                        result["properties"] = json!({"keyword": keyword});
+                    }
+                    results.push(result);
+                }
+            }
+            Outcome::Valid { .. } | Outcome::InvalidSchema(_) => {}
+        }
+    }
+    let rules: Vec<_> = rules.into_iter().map(|id| json!({"id": id})).collect();
+    json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "jsonschema-cli",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": "https://github.com/Stranger6667/jsonschema",
+                    "rules": rules,
+                }
+            },
+            "results": results,
+        }]
+    })
+}
+
+fn sarif_result(
+    rules: &mut Vec<String>,
+    rule: &str,
+    uri: &str,
+    message: &str,
+) -> serde_json::Value {
+    if !rules.iter().any(|known| known == rule) {
+        rules.push(rule.to_string());
+    }
+    json!({
+        "ruleId": rule,
+        "level": "error",
+        "message": {"text": message},
+        "locations": [{"physicalLocation": {"artifactLocation": {"uri": uri}}}],
+    })
+}
+
+/// Write a `JUnit` XML report with a test suite per schema and a test case per instance.
+///
+/// Each error is a `failure` of its test case, while an invalid schema is an `error`.
+pub(crate) fn write_junit(mut writer: impl Write, reports: &[InstanceReport]) -> io::Result<()> {
+    let mut suites: Vec<(&Path, Vec<&InstanceReport>)> = Vec::new();
+    for report in reports {
+        match suites
+            .iter_mut()
+            .find(|(schema, _)| *schema == report.schema)
+        {
+            Some((_, cases)) => cases.push(report),
+            None => suites.push((&report.schema, vec![report])),
+        }
+    }
+    writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    let all: Vec<_> = reports.iter().collect();
+    writeln!(writer, r#"<testsuites name="jsonschema" {}>"#, counts(&all))?;
+    for (schema, cases) in &suites {
+        let schema = escape_xml(&schema.to_string_lossy());
+        writeln!(writer, r#"  <testsuite name="{schema}" {}>"#, counts(cases))?;
+        for case in cases {
+            let name = escape_xml(&case.name());
+            write!(
+                writer,
+                r#"    <testcase name="{name}" classname="{schema}""#
+            )?;
+            match &case.outcome {
+                Outcome::Valid { .. } => writeln!(writer, "/>")?,
+                Outcome::Invalid { errors, .. } => {
+                    writeln!(writer, ">")?;
+                    for error in errors {
+                        let instance_location = error.instance_location();
+                        let details = match error.keyword_location() {
+                            Some(keyword_location) => format!(
+                                "Instance location: \"{instance_location}\", keyword location: \"{keyword_location}\""
+                            ),
+                            None => format!("Instance location: \"{instance_location}\""),
+                        };
+                        writeln!(
+                            writer,
+                            r#"      <failure message="{}" type="{}">{}</failure>"#,
+                            escape_xml(error.error()),
+                            escape_xml(error.keyword().unwrap_or("error")),
+                            escape_xml(&details),
+                        )?;
+                    }
+                    writeln!(writer, "    </testcase>")?;
+                }
+                Outcome::InvalidSchema(error) => {
+                    writeln!(writer, ">")?;
+                    for error in error.errors() {
+                        writeln!(
+                            writer,
+                            r#"      <error message="{}" type="invalidSchema">{}</error>"#,
+                            escape_xml(error.error()),
+                            escape_xml(&format!(
+                                "Schema location: \"{}\"",
+                                error.instance_location()
+                            )),
+                        )?;
+                    }
+                    writeln!(writer, "    </testcase>")?;
+                }
+            }
+        }
+        writeln!(writer, "  </testsuite>")?;
+    }
+    writeln!(writer, "</testsuites>")
+}
+
+fn counts(reports: &[&InstanceReport]) -> String {
+    let failures = reports
+        .iter()
+        .filter(|report| matches!(report.outcome, Outcome::Invalid { .. }))
+        .count();
+    let errors = reports
+        .iter()
+        .filter(|report| matches!(report.outcome, Outcome::InvalidSchema(_)))
+        .count();
+    format!(
+        r#"tests="{}" failures="{failures}" errors="{errors}""#,
+        reports.len()
+    )
+}
+
+fn escape_xml(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for ch in value.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // Not allowed in XML 1.0 at all
+            '\u{0}'..='\u{8}' | '\u{b}' | '\u{c}' | '\u{e}'..='\u{1f}' => {
+                escaped.push(char::REPLACEMENT_CHARACTER);
+            }
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+/// Write a Test Anything Protocol (version 14) stream with a test point per instance.
+///
+/// Errors are listed in a YAML diagnostics block below their test point.
+pub(crate) fn write_tap(mut writer: impl Write, reports: &[InstanceReport]) -> io::Result<()> {
+    writeln!(writer, "TAP version 14")?;
+    writeln!(writer, "1..{}", reports.len())?;
+    for (number, report) in reports.iter().enumerate() {
+        let number = number + 1;
+        // `#` starts a directive, such as `# SKIP`, in descriptions
+        let description = report.name().replace('\\', "\\\\").replace('#', "\\#");
+        if report.is_valid() {
+            writeln!(writer, "ok {number} - {description}")?;
+            continue;
+        }
+        writeln!(writer, "not ok {number} - {description}")?;
+        writeln!(writer, "  ---")?;
+        writeln!(
+            writer,
+            "  schema: {}",
+            yaml_string(&report.schema.to_string_lossy())
+        )?;
+        match &report.outcome {
+            Outcome::Valid { .. } => {}
+            Outcome::Invalid { errors, .. } => {
+                writeln!(writer, "  errors:")?;
+                for error in errors {
+                    writeln!(writer, "    - message: {}", yaml_string(error.error()))?;
+                    writeln!(
+                        writer,
+                        "      instanceLocation: {}",
+                        yaml_string(error.instance_location())
+                    )?;
+                    if let Some(keyword_location) = error.keyword_location() {
+                        writeln!(
+                            writer,
+                            "      keywordLocation: {}",
+                            yaml_string(keyword_location)
+                        )?;
+                    }
+                    if let Some(keyword) = error.keyword() {
+                        writeln!(writer, "      keyword: {}", yaml_string(keyword))?;
+                    }
+                }
+            }
+            Outcome::InvalidSchema(error) => {
+                writeln!(writer, "  message: {}", yaml_string("Invalid schema"))?;
+                writeln!(writer, "  error: {}", yaml_string(&error.to_string()))?;
+            }
+        }
+        writeln!(writer, "  ...")?;
+    }
+    Ok(())
+}
+
+/// JSON strings are valid YAML scalars and need no further quoting rules.
+fn yaml_string(value: &str) -> String {
+    serde_json::Value::from(value).to_string()
+}
+
+/// Write all reports into a single file.
+pub(crate) fn write_file(path: &Path, reports: &[InstanceReport]) -> Result<(), Box<dyn Error>> {
+    fs::write(path, serde_json::to_string_pretty(&combine(reports))?)?;
+    Ok(())
+}
+
+/// Write a report per instance, mirroring the instance path inside `dir`.
+///
+/// E.g. the report for `data/user.json` is written to `<dir>/data/user.json.json`, and the one
+/// for line 3 of `data/users.ndjson` to `<dir>/data/users.ndjson.3.json`. Instances whose paths
+/// mirror to the same report, e.g. `a/x.json` and `../a/x.json`, are an error, and nothing is
+/// written then.
+pub(crate) fn write_dir(dir: &Path, reports: &[InstanceReport]) -> Result<(), Box<dyn Error>> {
+    let mut paths: HashMap<PathBuf, &InstanceReport> = HashMap::with_capacity(reports.len());
+    for report in reports {
+        let path = report_path(dir, report);
+        if let Some(other) = paths.get(&path) {
+            if (&other.instance, other.line) != (&report.instance, report.line) {
+                return Err(format!(
+                    "Reports of `{}` and `{}` would both be written to `{}`",
+                    other.instance.display(),
+                    report.instance.display(),
+                    path.display()
+                )
+                .into());
+            }
+        }
+        paths.insert(path, report);
+    }
+    for (path, report) in paths {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&path, serde_json::to_string_pretty(&report.to_outcome())?)?;
+    }
+    Ok(())
+}
+
+/// Where [`write_dir`] writes the report of an instance.
+fn report_path(dir: &Path, report: &InstanceReport) -> PathBuf {
+    // Only plain components, so reports never end up outside of `dir`
+    let mut path: PathBuf = report
+        .instance
+        .components()
+        .filter(|component| matches!(component, Component::Normal(_)))
+        .collect();
+    if path.as_os_str().is_empty() {
+        // E.g. `.`, which has no name to extend
+        path.push("instance");
+    }
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    if let Some(line) = report.line {
+        name.push(format!(".{line}"));
+    }
+    name.push(".json");
+    path.set_file_name(name);
+    dir.join(path)
+}
+
+/// Write subschemas that did not accept any instance, per schema.
+pub(crate) fn write_coverage(
+    path: &Path,
+    coverage: &BTreeMap<PathBuf, Coverage>,
+) -> Result<(), Box<dyn Error>> {
+    let schemas: Vec<_> = coverage
+        .iter()
+        .map(|(schema, coverage)| {
+            json!({
+                "schema": schema.to_string_lossy(),
+                "subschemas": coverage.subschemas().len(),
+                "covered": coverage.covered().count(),
+                "uncovered": coverage.uncovered().collect::<Vec<_>>(),
+            })
+        })
+        .collect();
+    fs::write(
+        path,
+        serde_json::to_string_pretty(&json!({ "schemas": schemas }))?,
+    )?;
+    Ok(())
+}
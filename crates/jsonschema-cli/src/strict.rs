@@ -0,0 +1,307 @@
+//! Detection of JSON constructs that the lenient parser silently accepts or alters.
+//!
+//! Duplicate keys are collapsed to the last value, lone surrogates are rejected without
+//! a location, and numbers that don't fit into `i64`, `u64` or `f64` are rounded.
+use std::collections::HashSet;
+
+use jsonschema::paths::Location;
+
+/// Nesting deeper than this is rejected by the parser anyway.
+const MAX_DEPTH: usize = 128;
+
+pub(crate) struct Issue {
+    pub(crate) location: Location,
+    pub(crate) message: String,
+}
+
+/// Find strictness issues in a JSON document.
+///
+/// Scanning stops at the first syntax error, which is left to the parser to report.
+pub(crate) fn check(text: &str) -> Vec<Issue> {
+    let mut scanner = Scanner {
+        bytes: text.as_bytes(),
+        position: 0,
+        issues: Vec::new(),
+    };
+    scanner.value(&Location::new(), 0);
+    scanner.issues
+}
+
+struct Scanner<'a> {
+    bytes: &'a [u8],
+    position: usize,
+    issues: Vec<Issue>,
+}
+
+impl Scanner<'_> {
+    fn peek(&mut self) -> Option<u8> {
+        while let Some(byte) = self.bytes.get(self.position) {
+            if !matches!(byte, b' ' | b'\t' | b'\n' | b'\r') {
+                return Some(*byte);
+            }
+            self.position += 1;
+        }
+        None
+    }
+
+    fn expect(&mut self, expected: u8) -> Option<()> {
+        if self.peek()? == expected {
+            self.position += 1;
+            Some(())
+        } else {
+            None
+        }
+    }
+
+    fn value(&mut self, location: &Location, depth: usize) -> Option<()> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        match self.peek()? {
+            b'{' => self.object(location, depth),
+            b'[' => self.array(location, depth),
+            b'"' => self.string(location).map(|_| ()),
+            b'-' | b'0'..=b'9' => self.number(location),
+            _ => {
+                // `true`, `false` and `null`
+                while self
+                    .bytes
+                    .get(self.position)
+                    .is_some_and(u8::is_ascii_alphabetic)
+                {
+                    self.position += 1;
+                }
+                Some(())
+            }
+        }
+    }
+
+    fn object(&mut self, location: &Location, depth: usize) -> Option<()> {
+        self.expect(b'{')?;
+        let mut keys = HashSet::new();
+        if self.peek()? == b'}' {
+            self.position += 1;
+            return Some(());
+        }
+        loop {
+            let key = self.string(location)?;
+            let member = location.join(&key);
+            self.expect(b':')?;
+            self.value(&member, depth + 1)?;
+            if !keys.insert(key) {
+                self.issues.push(Issue {
+                    location: member,
+                    message: "Duplicate object key".to_string(),
+                });
+            }
+            match self.peek()? {
+                b',' => self.position += 1,
+                b'}' => {
+                    self.position += 1;
+                    return Some(());
+                }
+                _ => return None,
+            }
+        }
+    }
+
+    fn array(&mut self, location: &Location, depth: usize) -> Option<()> {
+        self.expect(b'[')?;
+        if self.peek()? == b']' {
+            self.position += 1;
+            return Some(());
+        }
+        let mut index = 0;
+        loop {
+            self.value(&location.join(index), depth + 1)?;
+            index += 1;
+            match self.peek()? {
+                b',' => self.position += 1,
+                b']' => {
+                    self.position += 1;
+                    return Some(());
+                }
+                _ => return None,
+            }
+        }
+    }
+
+    fn hex_escape(&mut self) -> Option<u16> {
+        let digits = self.bytes.get(self.position..self.position + 4)?;
+        let code = u16::from_str_radix(std::str::from_utf8(digits).ok()?, 16).ok()?;
+        self.position += 4;
+        Some(code)
+    }
+
+    fn string(&mut self, location: &Location) -> Option<String> {
+        self.expect(b'"')?;
+        let mut decoded = Vec::new();
+        loop {
+            let byte = *self.bytes.get(self.position)?;
+            self.position += 1;
+            match byte {
+                b'"' => return Some(String::from_utf8_lossy(&decoded).into_owned()),
+                b'\\' => {
+                    let escape = *self.bytes.get(self.position)?;
+                    self.position += 1;
+                    let decoded_char = match escape {
+                        b'n' => '\n',
+                        b't' => '\t',
+                        b'r' => '\r',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'u' => {
+                            let code = self.hex_escape()?;
+                            let low = if (0xD800..0xDC00).contains(&code)
+                                && self.bytes.get(self.position..self.position + 2)
+                                    == Some(b"\\u".as_slice())
+                            {
+                                // Consumed only if it completes the surrogate pair
+                                let position = self.position;
+                                self.position += 2;
+                                match self.hex_escape() {
+                                    Some(low) if (0xDC00..0xE000).contains(&low) => Some(low),
+                                    _ => {
+                                        self.position = position;
+                                        None
+                                    }
+                                }
+                            } else {
+                                None
+                            };
+                            match low {
+                                Some(low) => {
+                                    let code = 0x10000
+                                        + ((u32::from(code) - 0xD800) << 10)
+                                        + (u32::from(low) - 0xDC00);
+                                    char::from_u32(code)?
+                                }
+                                _ => {
+                                    if let Some(decoded) = char::from_u32(u32::from(code)) {
+                                        decoded
+                                    } else {
+                                        self.issues.push(Issue {
+                                            location: location.clone(),
+                                            message: format!(
+                                                "Lone surrogate \\u{code:04x} in string"
+                                            ),
+                                        });
+                                        char::REPLACEMENT_CHARACTER
+                                    }
+                                }
+                            }
+                        }
+                        other => char::from(other),
+                    };
+                    let mut buffer = [0; 4];
+                    decoded.extend_from_slice(decoded_char.encode_utf8(&mut buffer).as_bytes());
+                }
+                _ => decoded.push(byte),
+            }
+        }
+    }
+
+    fn number(&mut self, location: &Location) -> Option<()> {
+        let start = self.position;
+        while self
+            .bytes
+            .get(self.position)
+            .is_some_and(|byte| matches!(byte, b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9'))
+        {
+            self.position += 1;
+        }
+        let literal = std::str::from_utf8(&self.bytes[start..self.position]).ok()?;
+        if !is_exact(literal) {
+            self.issues.push(Issue {
+                location: location.clone(),
+                message: format!("Number {literal} can't be represented without losing precision"),
+            });
+        }
+        Some(())
+    }
+}
+
+/// Whether the number literal is represented exactly after parsing.
+fn is_exact(literal: &str) -> bool {
+    if !literal.contains(['.', 'e', 'E']) {
+        return literal.parse::<i64>().is_ok() || literal.parse::<u64>().is_ok();
+    }
+    let Ok(value) = literal.parse::<f64>() else {
+        return true;
+    };
+    // Out of range numbers are rejected by the parser
+    !value.is_finite() || decimal(literal) == decimal(&format!("{:e}", value.abs()))
+}
+
+/// Significant digits and exponent of a decimal number, e.g. `("15", -1)` for `1.50`.
+fn decimal(literal: &str) -> Option<(String, i64)> {
+    let literal = literal.trim_start_matches('-');
+    let (mantissa, exponent) = match literal.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (mantissa, exponent.parse::<i64>().ok()?),
+        None => (literal, 0),
+    };
+    let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let mut exponent = exponent.checked_sub(i64::try_from(fraction.len()).ok()?)?;
+    let digits = format!("{integer}{fraction}");
+    let digits = digits.trim_start_matches('0');
+    let significant = digits.trim_end_matches('0');
+    if significant.is_empty() {
+        return Some((String::new(), 0));
+    }
+    exponent = exponent.checked_add(i64::try_from(digits.len() - significant.len()).ok()?)?;
+    Some((significant.to_string(), exponent))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check, is_exact};
+
+    #[test]
+    fn issues() {
+        let issues: Vec<_> = check(
+            r#"{"a": 1, "b": [{"c": "\ud800\ud83d\ude00"}, 123456789012345678901234], "a": "😀"}"#,
+        )
+        .into_iter()
+        .map(|issue| (issue.location.to_string(), issue.message))
+        .collect();
+        assert_eq!(
+            issues,
+            vec![
+                (
+                    "/b/0/c".to_string(),
+                    "Lone surrogate \\ud800 in string".to_string()
+                ),
+                (
+                    "/b/1".to_string(),
+                    "Number 123456789012345678901234 can't be represented without losing precision"
+                        .to_string()
+                ),
+                ("/a".to_string(), "Duplicate object key".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn exact_numbers() {
+        for literal in [
+            "0",
+            "-0",
+            "18446744073709551615",
+            "-9223372036854775808",
+            "0.1",
+            "1.50",
+            "1e2",
+            "-2.5E-3",
+            "100.0",
+        ] {
+            assert!(is_exact(literal), "{literal}");
+        }
+        for literal in [
+            "18446744073709551616",
+            "0.10000000000000000001",
+            "1.2345678901234567890e10",
+        ] {
+            assert!(!is_exact(literal), "{literal}");
+        }
+    }
+}
@@ -0,0 +1,20 @@
+//! Timing of the stages reported with `--timings`.
+use std::time::Instant;
+
+use jsonschema::{Retrieve, Uri};
+use serde_json::Value;
+
+/// Reports the time spent retrieving each external resource.
+pub(crate) struct TimedRetriever(pub(crate) crate::SchemaRetriever);
+
+impl Retrieve for TimedRetriever {
+    fn retrieve(
+        &self,
+        uri: &Uri<String>,
+    ) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+        let start = Instant::now();
+        let result = self.0.retrieve(uri);
+        eprintln!("{uri} - retrieved in {:?}", start.elapsed());
+        result
+    }
+}
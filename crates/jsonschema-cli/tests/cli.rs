@@ -395,3 +395,2272 @@ fn test_format_enforcement_via_cli_flag() {
     );
     assert_snapshot!("format_enforcement_enabled", out);
 }
+
+#[test]
+fn test_instance_dir_with_config() {
+    let dir = tempdir().unwrap();
+    fs::create_dir_all(dir.path().join("k8s/apps")).unwrap();
+    fs::create_dir_all(dir.path().join("schemas")).unwrap();
+    create_temp_file(
+        &dir,
+        "jsonschema.toml",
+        r#"
+        [schemas]
+        "k8s/**/*.json" = "schemas/k8s.json"
+        "*.json" = "schemas/config.json"
+        "#,
+    );
+    create_temp_file(&dir, "schemas/k8s.json", r#"{"required": ["kind"]}"#);
+    create_temp_file(&dir, "schemas/config.json", r#"{"type": "array"}"#);
+    create_temp_file(
+        &dir,
+        "k8s/apps/deployment.json",
+        r#"{"kind": "Deployment"}"#,
+    );
+    create_temp_file(&dir, "k8s/service.json", "{}");
+    create_temp_file(&dir, "settings.json", "[]");
+    create_temp_file(&dir, "notes.txt", "Not JSON");
+
+    let mut cmd = cli();
+    cmd.arg("--instance-dir").arg(dir.path());
+    let output = cmd.output().unwrap();
+    assert!(!output.status.success());
+    let root = dir.path().to_str().unwrap();
+    let sanitized = sanitize_output(String::from_utf8_lossy(&output.stdout).to_string(), &[root]);
+    assert_snapshot!(sanitized);
+}
+
+#[test]
+fn test_instance_dir_with_default_schema() {
+    let dir = tempdir().unwrap();
+    fs::create_dir_all(dir.path().join("data")).unwrap();
+    let schema = create_temp_file(&dir, "schema.json", r#"{"type": "object"}"#);
+    create_temp_file(&dir, "data/a.json", "{}");
+    create_temp_file(&dir, "data/b.json", "[]");
+
+    let mut cmd = cli();
+    cmd.arg(&schema).arg("--instance-dir").arg(dir.path());
+    let output = cmd.output().unwrap();
+    assert!(!output.status.success());
+    let root = dir.path().to_str().unwrap();
+    let sanitized = sanitize_output(String::from_utf8_lossy(&output.stdout).to_string(), &[root]);
+    assert_snapshot!(sanitized);
+}
+
+#[test]
+fn test_instance_without_schema() {
+    let dir = tempdir().unwrap();
+    let config = create_temp_file(
+        &dir,
+        "config.toml",
+        r#"
+        [schemas]
+        "*.yaml" = "schema.json"
+        "#,
+    );
+    let instance = create_temp_file(&dir, "instance.json", "{}");
+
+    let mut cmd = cli();
+    cmd.arg("--config")
+        .arg(&config)
+        .arg("--instance-dir")
+        .arg(dir.path())
+        .arg("--instance")
+        .arg(&instance);
+    let output = cmd.output().unwrap();
+    assert!(!output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        format!("Error: No schema for {instance}\n")
+    );
+}
+
+#[test]
+fn test_report_file() {
+    let dir = tempdir().unwrap();
+    let schema = create_temp_file(
+        &dir,
+        "schema.json",
+        r#"{"properties": {"name": {"type": "string"}}}"#,
+    );
+    let valid = create_temp_file(&dir, "valid.json", r#"{"name": "John Doe"}"#);
+    let invalid = create_temp_file(&dir, "invalid.json", r#"{"name": 123}"#);
+    let report = dir.path().join("report.json");
+
+    let mut cmd = cli();
+    cmd.arg(&schema)
+        .arg("--instance")
+        .arg(&valid)
+        .arg("--instance")
+        .arg(&invalid)
+        .arg("--report-file")
+        .arg(&report);
+    let output = cmd.output().unwrap();
+    assert!(!output.status.success());
+    // Human-readable output is unchanged
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        format!(
+            "{valid} - VALID\n{invalid} - INVALID. Errors:\n1. 123 is not of type \"string\"\n   /name type: 123\n"
+        )
+    );
+    let report: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(report).unwrap()).unwrap();
+    assert_eq!(
+        report,
+        serde_json::json!({
+            "valid": false,
+            "statistics": {"instances": 2, "valid": 1, "invalid": 1, "schemaErrors": 0, "errors": 1},
+            "instances": [
+                {"instance": valid, "schema": schema, "valid": true},
+                {
+                    "instance": invalid,
+                    "schema": schema,
+                    "valid": false,
+                    "errors": [{
+                        "instanceLocation": "/name",
+                        "keywordLocation": "/properties/name/type",
+                        "keyword": "type",
+                        "error": "123 is not of type \"string\""
+                    }]
+                }
+            ]
+        })
+    );
+}
+
+#[test]
+fn test_output_json() {
+    let dir = tempdir().unwrap();
+    let schema = create_temp_file(&dir, "schema.json", r#"{"required": ["name"]}"#);
+    let valid = create_temp_file(&dir, "valid.json", r#"{"name": "John Doe"}"#);
+    let invalid = create_temp_file(&dir, "invalid.json", "{}");
+
+    let mut cmd = cli();
+    cmd.arg(&schema)
+        .arg("--instance")
+        .arg(&valid)
+        .arg("--instance")
+        .arg(&invalid)
+        .arg("--output")
+        .arg("json");
+    let output = cmd.output().unwrap();
+    assert!(!output.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        report,
+        serde_json::json!({
+            "valid": false,
+            "statistics": {"instances": 2, "valid": 1, "invalid": 1, "schemaErrors": 0, "errors": 1},
+            "instances": [
+                {"instance": valid, "schema": schema, "valid": true},
+                {
+                    "instance": invalid,
+                    "schema": schema,
+                    "valid": false,
+                    "errors": [{
+                        "instanceLocation": "",
+                        "keywordLocation": "/required",
+                        "keyword": "required",
+                        "error": "\"name\" is a required property"
+                    }]
+                }
+            ]
+        })
+    );
+}
+
+#[test]
+fn test_output_sarif() {
+    let dir = tempdir().unwrap();
+    let schema = create_temp_file(
+        &dir,
+        "schema.json",
+        r#"{"properties": {"name": {"type": "string"}}, "required": ["id"]}"#,
+    );
+    let valid = create_temp_file(&dir, "valid.json", r#"{"id": 1}"#);
+    let invalid = create_temp_file(&dir, "invalid.json", r#"{"name": 1, "name": 2}"#);
+
+    let mut cmd = cli();
+    cmd.arg(&schema)
+        .arg("--instance")
+        .arg(&valid)
+        .arg("--instance")
+        .arg(&invalid)
+        .arg("--strict-json")
+        .arg("--output")
+        .arg("sarif");
+    let output = cmd.output().unwrap();
+    assert!(!output.status.success());
+    let log: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let uri = format!("file://{}", fs::canonicalize(&invalid).unwrap().display());
+    let location = |pointer: &str| {
+        serde_json::json!([{
+            "physicalLocation": {"artifactLocation": {"uri": uri}},
+            "logicalLocations": [{"fullyQualifiedName": pointer}]
+        }])
+    };
+    assert_eq!(log["version"], "2.1.0");
+    assert_eq!(
+        log["runs"][0]["tool"]["driver"]["rules"],
+        serde_json::json!([{"id": "strictJson"}, {"id": "type"}, {"id": "required"}])
+    );
+    assert_eq!(
+        log["runs"][0]["results"],
+        serde_json::json!([
+            {
+                "ruleId": "strictJson",
+                "level": "error",
+                "message": {"text": "Duplicate object key"},
+                "locations": location("/name")
+            },
+            {
+                "ruleId": "type",
+                "level": "error",
+                "message": {"text": "2 is not of type \"string\""},
+                "locations": location("/name"),
+                "properties": {"keywordLocation": "/properties/name/type"}
+            },
+            {
+                "ruleId": "required",
+                "level": "error",
+                "message": {"text": "\"id\" is a required property"},
+                "locations": location(""),
+                "properties": {"keywordLocation": "/required"}
+            }
+        ])
+    );
+}
+
+#[test]
+fn test_output_junit() {
+    let dir = tempdir().unwrap();
+    fs::create_dir_all(dir.path().join("schemas")).unwrap();
+    create_temp_file(
+        &dir,
+        "jsonschema.toml",
+        r#"
+        [schemas]
+        "broken/*.json" = "schemas/broken.json"
+        "*.json" = "schemas/user.json"
+        "#,
+    );
+    create_temp_file(
+        &dir,
+        "schemas/user.json",
+        r#"{"properties": {"name": {"type": "integer"}}, "required": ["id"]}"#,
+    );
+    create_temp_file(&dir, "schemas/broken.json", r#"{"type": 42}"#);
+    fs::create_dir_all(dir.path().join("broken")).unwrap();
+    create_temp_file(&dir, "broken/a.json", "{}");
+    create_temp_file(&dir, "invalid.json", r#"{"name": "<&>"}"#);
+    create_temp_file(&dir, "valid.json", r#"{"id": 1}"#);
+
+    let mut cmd = cli();
+    cmd.arg("--instance-dir")
+        .arg(dir.path())
+        .arg("--output")
+        .arg("junit");
+    let output = cmd.output().unwrap();
+    assert!(!output.status.success());
+    let root = dir.path().to_str().unwrap();
+    let sanitized = sanitize_output(String::from_utf8_lossy(&output.stdout).to_string(), &[root]);
+    assert_snapshot!(sanitized);
+}
+
+#[test]
+fn test_output_tap() {
+    let dir = tempdir().unwrap();
+    fs::create_dir_all(dir.path().join("schemas")).unwrap();
+    create_temp_file(
+        &dir,
+        "jsonschema.toml",
+        r#"
+        [schemas]
+        "broken/*.json" = "schemas/broken.json"
+        "*.json" = "schemas/user.json"
+        "#,
+    );
+    create_temp_file(
+        &dir,
+        "schemas/user.json",
+        r#"{"properties": {"name": {"type": "integer"}}, "required": ["id"]}"#,
+    );
+    create_temp_file(&dir, "schemas/broken.json", r#"{"type": 42}"#);
+    fs::create_dir_all(dir.path().join("broken")).unwrap();
+    create_temp_file(&dir, "broken/a.json", "{}");
+    create_temp_file(&dir, "invalid#1.json", r#"{"name": "a\"b"}"#);
+    create_temp_file(&dir, "valid.json", r#"{"id": 1}"#);
+
+    let mut cmd = cli();
+    cmd.arg("--instance-dir")
+        .arg(dir.path())
+        .arg("--output")
+        .arg("tap");
+    let output = cmd.output().unwrap();
+    assert!(!output.status.success());
+    let root = dir.path().to_str().unwrap();
+    let sanitized = sanitize_output(String::from_utf8_lossy(&output.stdout).to_string(), &[root]);
+    assert_snapshot!(sanitized);
+}
+
+#[test]
+fn test_report_dir() {
+    let dir = tempdir().unwrap();
+    fs::create_dir_all(dir.path().join("data")).unwrap();
+    let schema = create_temp_file(&dir, "schema.json", r#"{"type": "invalid"}"#);
+    create_temp_file(&dir, "data/instance.json", "{}");
+    let reports = dir.path().join("reports");
+
+    let mut cmd = cli();
+    cmd.current_dir(dir.path())
+        .arg(&schema)
+        .arg("--instance")
+        .arg("data/instance.json")
+        .arg("--report-dir")
+        .arg(&reports);
+    let output = cmd.output().unwrap();
+    assert!(!output.status.success());
+    let report: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(reports.join("data/instance.json.json")).unwrap())
+            .unwrap();
+    assert_eq!(report["instance"], "data/instance.json");
+    assert_eq!(report["valid"], false);
+    assert!(report["schemaError"]
+        .as_str()
+        .unwrap()
+        .contains("is not valid under any of the schemas"));
+
+    // Distinct instances mirroring to the same report
+    fs::create_dir_all(dir.path().join("b/data")).unwrap();
+    create_temp_file(&dir, "b/data/instance.json", "{}");
+    let mut cmd = cli();
+    cmd.current_dir(dir.path().join("b"))
+        .arg(&schema)
+        .arg("--instance")
+        .arg("data/instance.json")
+        .arg("--instance")
+        .arg("../data/instance.json")
+        .arg("--report-dir")
+        .arg(dir.path().join("collisions"));
+    let output = cmd.output().unwrap();
+    assert_eq!(output.status.code(), Some(3));
+    assert!(String::from_utf8_lossy(&output.stdout).contains(
+        "Reports of `data/instance.json` and `../data/instance.json` would both be written to"
+    ));
+    assert!(!dir.path().join("collisions").exists());
+}
+
+#[test]
+fn test_instance_from_stdin() {
+    let dir = tempdir().unwrap();
+    let schema = create_temp_file(&dir, "schema.json", r#"{"type": "object"}"#);
+    let file = create_temp_file(&dir, "instance.json", "{}");
+
+    let mut cmd = cli();
+    cmd.arg(&schema)
+        .arg("-i")
+        .arg(&file)
+        .arg("-i")
+        .arg("-")
+        .write_stdin("[1]");
+    let output = cmd.output().unwrap();
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(
+        stdout,
+        format!(
+            "{file} - VALID\n<stdin> - INVALID. Errors:\n1. [1] is not of type \"object\"\n   (root) type: [1]\n"
+        )
+    );
+
+    // Piped data is only read if requested
+    let mut cmd = cli();
+    cmd.arg(&schema).write_stdin("[1]");
+    let output = cmd.output().unwrap();
+    assert!(output.status.success());
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("<stdin>"));
+
+    let mut cmd = cli();
+    cmd.arg(&schema)
+        .arg("-i")
+        .arg("-")
+        .arg("--output")
+        .arg("json")
+        .write_stdin("[]");
+    let output = cmd.output().unwrap();
+    assert!(!output.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["instances"][0]["instance"], "-");
+}
+
+#[test]
+fn test_batch_rejects_stdin_instance() {
+    let dir = tempdir().unwrap();
+    let schema = create_temp_file(&dir, "schema.json", r#"{"type": "object"}"#);
+    let command = serde_json::json!({"schema": schema, "instance_path": "-"});
+
+    let mut cmd = cli();
+    cmd.arg("--batch").write_stdin(format!("{command}\n"));
+    let output = cmd.output().unwrap();
+    assert!(!output.status.success());
+    let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(result["error"], "stdin is reserved for batch commands");
+}
+
+#[test]
+fn test_instance_glob() {
+    let dir = tempdir().unwrap();
+    let schema = create_temp_file(&dir, "schema.json", r#"{"type": "object"}"#);
+    fs::create_dir_all(dir.path().join("data/nested")).unwrap();
+    fs::write(dir.path().join("data/b.json"), "{}").unwrap();
+    fs::write(dir.path().join("data/a.json"), "[]").unwrap();
+    fs::write(dir.path().join("data/nested/c.json"), "{}").unwrap();
+    fs::write(dir.path().join("data/notes.txt"), "").unwrap();
+    let data = dir.path().join("data");
+    let data = data.to_str().unwrap();
+
+    let mut cmd = cli();
+    cmd.arg(&schema).arg("-i").arg(format!("{data}/**/*.json"));
+    let output = cmd.output().unwrap();
+    assert!(!output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        format!(
+            "{data}/a.json - INVALID. Errors:\n1. [] is not of type \"object\"\n   (root) type: []\n{data}/b.json - VALID\n{data}/nested/c.json - VALID\n"
+        )
+    );
+
+    // `*` does not match across directories
+    let mut cmd = cli();
+    cmd.arg(&schema).arg("-i").arg(format!("{data}/*/*.json"));
+    let output = cmd.output().unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        format!("{data}/nested/c.json - VALID\n")
+    );
+
+    let mut cmd = cli();
+    cmd.arg(&schema).arg("-i").arg(format!("{data}/*.yaml"));
+    let output = cmd.output().unwrap();
+    assert!(!output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        format!("Error: No files match `{data}/*.yaml`\n")
+    );
+}
+
+#[test]
+fn test_ndjson_instances() {
+    let dir = tempdir().unwrap();
+    let schema = create_temp_file(&dir, "schema.json", r#"{"type": "object"}"#);
+    let records = create_temp_file(&dir, "records.ndjson", "{}\n[]\n\n{\"a\": \n{\"b\": 1}\n");
+
+    let mut cmd = cli();
+    cmd.arg(&schema)
+        .arg("--format")
+        .arg("ndjson")
+        .arg("-i")
+        .arg(&records);
+    let output = cmd.output().unwrap();
+    assert!(!output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        format!(
+            "{records}:1 - VALID\n\
+             {records}:2 - INVALID. Errors:\n1. [] is not of type \"object\"\n   (root) type: []\n\
+             {records}:4 - INVALID. Errors:\n1. Invalid JSON: EOF while parsing a value at line 1 column 6\n   (root)\n\
+             {records}:5 - VALID\n"
+        )
+    );
+
+    let mut cmd = cli();
+    cmd.arg(&schema)
+        .arg("-i")
+        .arg("-")
+        .arg("--format")
+        .arg("ndjson")
+        .arg("--max-failures")
+        .arg("1")
+        .arg("--output")
+        .arg("sarif")
+        .write_stdin("{}\n1\n2\n");
+    let output = cmd.output().unwrap();
+    assert!(!output.status.success());
+    let log: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let results = log["runs"][0]["results"].as_array().unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(
+        results[0]["locations"][0]["physicalLocation"],
+        serde_json::json!({"artifactLocation": {"uri": "-"}, "region": {"startLine": 2}})
+    );
+
+    let mut cmd = cli();
+    cmd.arg(&schema)
+        .arg("--format")
+        .arg("ndjson")
+        .arg("--output")
+        .arg("json")
+        .arg("-i")
+        .arg(&records);
+    let output = cmd.output().unwrap();
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let instances: Vec<_> = report["instances"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|instance| instance["instance"].as_str().unwrap().to_string())
+        .collect();
+    assert_eq!(
+        instances,
+        ["1", "2", "4", "5"].map(|line| format!("{records}:{line}"))
+    );
+}
+
+#[test]
+fn test_yaml_instances() {
+    let dir = tempdir().unwrap();
+    let schema = create_temp_file(
+        &dir,
+        "schema.json",
+        r#"{"properties": {"replicas": {"type": "integer"}}}"#,
+    );
+    let valid = create_temp_file(&dir, "valid.yaml", "kind: Deployment\nreplicas: 3\n");
+    let invalid = create_temp_file(&dir, "invalid.yml", "replicas: three\n");
+
+    let mut cmd = cli();
+    cmd.arg(&schema)
+        .arg("-i")
+        .arg(&valid)
+        .arg("-i")
+        .arg(&invalid);
+    let output = cmd.output().unwrap();
+    assert!(!output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        format!(
+            "{valid} - VALID\n{invalid} - INVALID. Errors:\n1. \"three\" is not of type \"integer\"\n   /replicas type: \"three\"\n"
+        )
+    );
+
+    let mut cmd = cli();
+    cmd.arg(&schema)
+        .arg("-i")
+        .arg("-")
+        .arg("--format")
+        .arg("yaml")
+        .write_stdin("replicas: 1\n");
+    let output = cmd.output().unwrap();
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "<stdin> - VALID\n");
+}
+
+#[test]
+fn test_binary_instances() {
+    let dir = tempdir().unwrap();
+    let schema = create_temp_file(
+        &dir,
+        "schema.json",
+        r#"{"properties": {"port": {"type": "integer"}}}"#,
+    );
+    // {"port": 8080}
+    let cbor = dir.path().join("valid.cbor");
+    fs::write(&cbor, b"\xa1\x64port\x19\x1f\x90").unwrap();
+    // {"port": "x"}
+    let msgpack = dir.path().join("invalid.msgpack");
+    fs::write(&msgpack, b"\x81\xa4port\xa1x").unwrap();
+
+    let mut cmd = cli();
+    cmd.arg(&schema)
+        .arg("-i")
+        .arg(&cbor)
+        .arg("-i")
+        .arg(&msgpack);
+    let output = cmd.output().unwrap();
+    assert!(!output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        format!(
+            "{} - VALID\n{} - INVALID. Errors:\n1. \"x\" is not of type \"integer\"\n   /port type: \"x\"\n",
+            cbor.display(),
+            msgpack.display()
+        )
+    );
+
+    let mut cmd = cli();
+    cmd.arg(&schema)
+        .arg("-i")
+        .arg("-")
+        .arg("--instance-format")
+        .arg("cbor")
+        .write_stdin(b"\xa1\x64port\x19\x1f\x90".to_vec());
+    let output = cmd.output().unwrap();
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "<stdin> - VALID\n");
+
+    let mut cmd = cli();
+    cmd.arg(&schema)
+        .arg("-i")
+        .arg("-")
+        .arg("--format")
+        .arg("msgpack")
+        .write_stdin(b"\xc1".to_vec());
+    let output = cmd.output().unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with("Error: Invalid MessagePack: "));
+}
+
+#[test]
+fn test_relaxed_json() {
+    let dir = tempdir().unwrap();
+    let schema = create_temp_file(
+        &dir,
+        "schema.json",
+        "{\n  // Ports are checked in defs.json\n  \"properties\": {\"port\": {\"$ref\": \"defs.json\"}},\n}",
+    );
+    create_temp_file(
+        &dir,
+        "defs.json",
+        "{\"type\": \"integer\", /* TCP */ \"maximum\": 65535,}",
+    );
+    let valid = create_temp_file(&dir, "valid.json", "{\"port\": 8080, // HTTP\n}");
+    let invalid = create_temp_file(&dir, "invalid.json", "{\"port\": 70000,}");
+
+    let mut cmd = cli();
+    cmd.arg(&schema)
+        .arg("--relaxed-json")
+        .arg("-i")
+        .arg(&valid)
+        .arg("-i")
+        .arg(&invalid);
+    let output = cmd.output().unwrap();
+    assert!(!output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        format!(
+            "{valid} - VALID\n{invalid} - INVALID. Errors:\n1. 70000 is greater than the maximum of 65535\n   /port maximum: 70000\n"
+        )
+    );
+
+    let mut cmd = cli();
+    cmd.arg(&schema).arg("-i").arg(&valid);
+    let output = cmd.output().unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with("Error: "));
+}
+
+#[test]
+fn test_yaml_schema() {
+    let dir = tempdir().unwrap();
+    let schema = create_temp_file(
+        &dir,
+        "schema.yaml",
+        "type: object\nproperties:\n  port:\n    $ref: 'defs.yml#/port'\n",
+    );
+    create_temp_file(
+        &dir,
+        "defs.yml",
+        "port:\n  type: integer\n  maximum: 65535\n",
+    );
+    let valid = create_temp_file(&dir, "valid.json", r#"{"port": 8080}"#);
+    let invalid = create_temp_file(&dir, "invalid.json", r#"{"port": 70000}"#);
+
+    let mut cmd = cli();
+    cmd.arg(&schema)
+        .arg("-i")
+        .arg(&valid)
+        .arg("-i")
+        .arg(&invalid);
+    let output = cmd.output().unwrap();
+    assert!(!output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        format!(
+            "{valid} - VALID\n{invalid} - INVALID. Errors:\n1. 70000 is greater than the maximum of 65535\n   /port maximum: 70000\n"
+        )
+    );
+}
+
+#[test]
+fn test_schema_from_stdin() {
+    let dir = tempdir().unwrap();
+    create_temp_file(&dir, "defs.json", r#"{"type": "integer"}"#);
+    let valid = create_temp_file(&dir, "valid.json", "1");
+    let invalid = create_temp_file(&dir, "invalid.json", "\"a\"");
+    let base_uri = format!("file://{}/", dir.path().display());
+
+    let mut cmd = cli();
+    cmd.arg("-")
+        .arg("--base-uri")
+        .arg(&base_uri)
+        .arg("-i")
+        .arg(&valid)
+        .arg("-i")
+        .arg(&invalid)
+        .write_stdin(r#"{"$ref": "defs.json"}"#);
+    let output = cmd.output().unwrap();
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(
+        stdout,
+        format!(
+            "{valid} - VALID\n{invalid} - INVALID. Errors:\n1. \"a\" is not of type \"integer\"\n   (root) type: \"a\"\n"
+        )
+    );
+
+    let mut cmd = cli();
+    cmd.arg("-")
+        .arg("-i")
+        .arg(&valid)
+        .write_stdin(r#"{"type": "integer"}"#);
+    let output = cmd.output().unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        format!("{valid} - VALID\n")
+    );
+}
+
+#[test]
+fn test_schema_and_instance_from_stdin() {
+    let mut cmd = cli();
+    cmd.arg("-").arg("-i").arg("-").write_stdin("{}");
+    let output = cmd.output().unwrap();
+    assert!(!output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "Error: stdin can not provide both the schema and an instance\n"
+    );
+}
+
+#[test]
+fn test_batch() {
+    let dir = tempdir().unwrap();
+    let schema = create_temp_file(&dir, "schema.json", r#"{"type": "object"}"#);
+    let valid = create_temp_file(&dir, "valid.json", "{}");
+    let invalid = create_temp_file(&dir, "invalid.json", "[]");
+    let commands = [
+        serde_json::json!({"id": 1, "schema": schema, "instance_path": valid}),
+        serde_json::json!({"id": 2, "schema": schema, "instance_path": invalid}),
+    ]
+    .iter()
+    .map(ToString::to_string)
+    .collect::<Vec<_>>()
+    .join("\n");
+
+    let mut cmd = cli();
+    cmd.arg("--batch")
+        .write_stdin(format!("{commands}\n\n{{\"schema\": 42}}\n"));
+    let output = cmd.output().unwrap();
+    assert!(!output.status.success());
+    let results: Vec<serde_json::Value> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(results.len(), 3);
+    assert_eq!(
+        results[0],
+        serde_json::json!({"id": 1, "instance": valid, "schema": schema, "valid": true})
+    );
+    assert_eq!(results[1]["id"], 2);
+    assert_eq!(results[1]["valid"], false);
+    assert_eq!(
+        results[1]["errors"][0]["error"],
+        "[] is not of type \"object\""
+    );
+    assert!(results[2]["error"]
+        .as_str()
+        .unwrap()
+        .starts_with("Invalid command"));
+}
+
+#[test]
+fn test_max_failures() {
+    let dir = tempdir().unwrap();
+    let schema = create_temp_file(&dir, "schema.json", r#"{"type": "object"}"#);
+    let instances: Vec<_> = ["{}", "[]", "{}", "1", "2"]
+        .iter()
+        .enumerate()
+        .map(|(i, content)| create_temp_file(&dir, &format!("instance{i}.json"), content))
+        .collect();
+
+    let mut cmd = cli();
+    cmd.arg(&schema).arg("--max-failures").arg("2");
+    for instance in &instances {
+        cmd.arg("--instance").arg(instance);
+    }
+    let output = cmd.output().unwrap();
+    assert!(!output.status.success());
+    let names: Vec<&str> = instances.iter().map(String::as_str).collect();
+    let sanitized = sanitize_output(String::from_utf8_lossy(&output.stdout).to_string(), &names);
+    assert_snapshot!(sanitized);
+}
+
+#[test]
+fn test_max_errors() {
+    let dir = tempdir().unwrap();
+    let schema = create_temp_file(
+        &dir,
+        "schema.json",
+        r#"{"items": {"type": "string"}, "maxItems": 1}"#,
+    );
+    let many = create_temp_file(&dir, "many.json", "[1, 2, 3, 4]");
+    let few = create_temp_file(&dir, "few.json", "[1]");
+    let report = dir.path().join("report.json");
+
+    let output = cli()
+        .arg(&schema)
+        .arg("--max-errors")
+        .arg("2")
+        .arg("--report-file")
+        .arg(&report)
+        .arg("--instance")
+        .arg(&many)
+        .arg("--instance")
+        .arg(&few)
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        format!(
+            "{many} - INVALID. Errors:\n1. 1 is not of type \"string\"\n   /0 type: 1\n2. 2 is not of type \"string\"\n   /1 type: 2\n... and 3 more error(s)\n{few} - INVALID. Errors:\n1. 1 is not of type \"string\"\n   /0 type: 1\n"
+        )
+    );
+    // Reports keep all errors
+    let report: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(report).unwrap()).unwrap();
+    assert_eq!(report["statistics"]["errors"], 6);
+}
+
+#[test]
+fn test_color() {
+    let dir = tempdir().unwrap();
+    let schema = create_temp_file(
+        &dir,
+        "schema.json",
+        r#"{"properties": {"name": {"type": "string"}}, "required": ["id"]}"#,
+    );
+    let valid = create_temp_file(&dir, "valid.json", r#"{"id": 1}"#);
+    let invalid = create_temp_file(
+        &dir,
+        "invalid.json",
+        r#"{"name": ["a long list of values that does not fit", 1]}"#,
+    );
+
+    let run = |color: &str| {
+        let output = cli()
+            .arg(&schema)
+            .arg("--color")
+            .arg(color)
+            .arg("--instance")
+            .arg(&valid)
+            .arg("--instance")
+            .arg(&invalid)
+            .output()
+            .unwrap();
+        assert!(!output.status.success());
+        String::from_utf8(output.stdout).unwrap()
+    };
+    assert_eq!(
+        run("always"),
+        format!(
+            "{valid} - \x1b[1;32mVALID\x1b[0m\n\
+             {invalid} - \x1b[1;31mINVALID\x1b[0m. Errors:\n\
+             1. [\"a long list of values that does not fit\",1] is not of type \"string\"\n   \
+             \x1b[36m/name\x1b[0m \x1b[33mtype\x1b[0m: \x1b[2m[\"a long list of values that does not fi…\x1b[0m\n\
+             2. \"id\" is a required property\n   \
+             \x1b[36m(root)\x1b[0m \x1b[33mrequired\x1b[0m: \x1b[2m{{\"name\":[\"a long list of values that doe…\x1b[0m\n"
+        )
+    );
+    // Without colors, the same details are shown
+    assert_eq!(
+        run("never"),
+        format!(
+            "{valid} - VALID\n{invalid} - INVALID. Errors:\n\
+             1. [\"a long list of values that does not fit\",1] is not of type \"string\"\n   \
+             /name type: [\"a long list of values that does not fi…\n\
+             2. \"id\" is a required property\n   \
+             (root) required: {{\"name\":[\"a long list of values that doe…\n"
+        )
+    );
+}
+
+#[test]
+fn test_verbose() {
+    let dir = tempdir().unwrap();
+    let schema = create_temp_file(
+        &dir,
+        "schema.json",
+        r#"{"title": "Person", "properties": {"id": {"anyOf": [{"type": "string", "title": "Slug"}, {"type": "integer"}]}}}"#,
+    );
+    let slug = create_temp_file(&dir, "slug.json", r#"{"id": "x"}"#);
+    let empty = create_temp_file(&dir, "empty.json", "[]");
+    let invalid = create_temp_file(&dir, "invalid.json", r#"{"id": null}"#);
+
+    let output = cli()
+        .arg(&schema)
+        .arg("--verbose")
+        .arg("--instance")
+        .arg(&slug)
+        .arg("--instance")
+        .arg(&empty)
+        .arg("--instance")
+        .arg(&invalid)
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(
+        stdout.lines().take(5).collect::<Vec<_>>().join("\n"),
+        format!(
+            "{slug} - VALID. Annotations:\n1. (root) #: {{\"title\":\"Person\"}}\n2. (root) #/properties: [\"id\"]\n3. /id #/properties/id/anyOf/0: {{\"title\":\"Slug\"}}\n{empty} - VALID. Annotations:"
+        )
+    );
+    assert!(stdout.contains(&format!("{invalid} - INVALID. Errors:\n")));
+}
+
+/// Serve `body` as JSON for a single request on a local port, returning the base URL.
+fn serve_once(body: &'static str) -> String {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut request = [0; 4096];
+        let _ = std::io::Read::read(&mut stream, &mut request).unwrap();
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        );
+        std::io::Write::write_all(&mut stream, response.as_bytes()).unwrap();
+    });
+    format!("http://{address}")
+}
+
+#[test]
+fn test_allow_remote() {
+    let dir = tempdir().unwrap();
+    let base = serve_once(r#"{"type": "integer"}"#);
+    let schema = create_temp_file(
+        &dir,
+        "schema.json",
+        &format!(r#"{{"$ref": "{base}/integer.json"}}"#),
+    );
+    let instance = create_temp_file(&dir, "instance.json", "1");
+
+    // Remote documents are not retrieved by default
+    let output = cli()
+        .arg(&schema)
+        .arg("--instance")
+        .arg(&instance)
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        format!("Schema is invalid. Error: Resource '{base}/integer.json' is not present in a registry and retrieving it failed: retrieving remote documents requires `--allow-remote`\n")
+    );
+
+    let output = cli()
+        .arg(&schema)
+        .arg("--allow-remote")
+        .arg("--instance")
+        .arg(&instance)
+        .env("XDG_CACHE_HOME", dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        format!("{instance} - VALID\n")
+    );
+}
+
+#[test]
+fn test_remote_cache() {
+    let dir = tempdir().unwrap();
+    let cache = tempdir().unwrap();
+    let base = serve_once(r#"{"type": "integer"}"#);
+    let schema = create_temp_file(
+        &dir,
+        "schema.json",
+        &format!(r#"{{"$ref": "{base}/integer.json"}}"#),
+    );
+    let instance = create_temp_file(&dir, "instance.json", "1");
+    let run = |args: &[&str]| {
+        let output = cli()
+            .arg(&schema)
+            .arg("--allow-remote")
+            .args(args)
+            .arg("--instance")
+            .arg(&instance)
+            .env("XDG_CACHE_HOME", cache.path())
+            .output()
+            .unwrap();
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+    let valid = format!("{instance} - VALID\n");
+
+    // The server answers once, later runs use the cache
+    assert_eq!(run(&[]), valid);
+    assert_eq!(
+        cache.path().join("jsonschema").read_dir().unwrap().count(),
+        1
+    );
+    assert_eq!(run(&[]), valid);
+    assert_eq!(run(&["--offline", "--cache-ttl", "0"]), valid);
+    // Expired documents are retrieved again
+    assert!(run(&["--cache-ttl", "0"]).starts_with(&format!(
+        "Schema is invalid. Error: Resource '{base}/integer.json' is not present in a registry and retrieving it failed: error sending request"
+    )));
+    // So are all documents without the cache
+    assert!(run(&["--no-cache"]).starts_with(&format!(
+        "Schema is invalid. Error: Resource '{base}/integer.json' is not present in a registry and retrieving it failed: error sending request"
+    )));
+    // Cached documents are only used for the URI they were retrieved from
+    let entry = cache
+        .path()
+        .join("jsonschema")
+        .read_dir()
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap()
+        .path();
+    let contents = fs::read_to_string(&entry).unwrap();
+    assert!(contents.contains(&format!(r#""uri":"{base}/integer.json""#)));
+    fs::write(&entry, contents.replace("integer.json", "number.json")).unwrap();
+    assert!(run(&["--offline"]).contains("the document is not cached"));
+
+    let other = create_temp_file(
+        &dir,
+        "other.json",
+        &format!(r#"{{"$ref": "{base}/other.json"}}"#),
+    );
+    let output = cli()
+        .arg(&other)
+        .arg("--allow-remote")
+        .arg("--offline")
+        .arg("--instance")
+        .arg(&instance)
+        .env("XDG_CACHE_HOME", cache.path())
+        .output()
+        .unwrap();
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        format!("Schema is invalid. Error: Resource '{base}/other.json' is not present in a registry and retrieving it failed: the document is not cached and `--offline` forbids retrieving it\n")
+    );
+}
+
+#[test]
+fn test_schema_dir() {
+    let dir = tempdir().unwrap();
+    let schemas = dir.path().join("schemas");
+    fs::create_dir_all(schemas.join("nested")).unwrap();
+    fs::write(
+        schemas.join("nested").join("name.yaml"),
+        "$id: https://example.com/name.json\ntype: string\nminLength: 1\n",
+    )
+    .unwrap();
+    let schema = create_temp_file(
+        &dir,
+        "schema.json",
+        r#"{"properties": {"name": {"$ref": "https://example.com/name.json"}}}"#,
+    );
+    let valid = create_temp_file(&dir, "valid.json", r#"{"name": "a"}"#);
+    let invalid = create_temp_file(&dir, "invalid.json", r#"{"name": ""}"#);
+
+    let output = cli()
+        .arg(&schema)
+        .arg("--schema-dir")
+        .arg(&schemas)
+        .arg("--instance")
+        .arg(&valid)
+        .arg("--instance")
+        .arg(&invalid)
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        format!(
+            "{valid} - VALID\n{invalid} - INVALID. Errors:\n1. \"\" is shorter than 1 character\n   /name minLength: \"\"\n"
+        )
+    );
+
+    let output = cli()
+        .arg(&schema)
+        .arg("--schema-dir")
+        .arg(dir.path().join("missing"))
+        .arg("--instance")
+        .arg(&valid)
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("missing is not a directory"));
+}
+
+#[test]
+fn test_map() {
+    let dir = tempdir().unwrap();
+    let schemas = dir.path().join("schemas");
+    fs::create_dir_all(schemas.join("defs")).unwrap();
+    fs::write(
+        schemas.join("item.json"),
+        r#"{"properties": {"name": {"$ref": "defs/name.json"}}}"#,
+    )
+    .unwrap();
+    fs::write(
+        schemas.join("defs").join("name.json"),
+        r#"{"type": "string"}"#,
+    )
+    .unwrap();
+    let schema = create_temp_file(
+        &dir,
+        "schema.json",
+        r#"{"items": {"$ref": "https://example.com/schemas/item.json"}}"#,
+    );
+    let instance = create_temp_file(&dir, "instance.json", r#"[{"name": 1}]"#);
+
+    // Mapped documents are read without `--allow-remote`, including relative references in them
+    let output = cli()
+        .arg(&schema)
+        .arg("--map")
+        .arg(format!(
+            "https://example.com/schemas/={}",
+            schemas.display()
+        ))
+        .arg("--instance")
+        .arg(&instance)
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        format!(
+            "{instance} - INVALID. Errors:\n1. 1 is not of type \"string\"\n   /0/name type: 1\n"
+        )
+    );
+
+    let output = cli()
+        .arg(&schema)
+        .arg("--map")
+        .arg(format!(
+            "https://example.com/={}",
+            dir.path().join("missing").display()
+        ))
+        .arg("--instance")
+        .arg(&instance)
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with("Schema is invalid. Error: Resource 'https://example.com/schemas/item.json' is not present in a registry and retrieving it failed: "));
+    assert!(stdout.contains(&format!(
+        "{}",
+        dir.path().join("missing/schemas/item.json").display()
+    )));
+
+    // Prefixes without a trailing slash cover whole path segments
+    let output = cli()
+        .arg(&schema)
+        .arg("--map")
+        .arg(format!("https://example.com/schemas={}", schemas.display()))
+        .arg("--instance")
+        .arg(&instance)
+        .output()
+        .unwrap();
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        format!(
+            "{instance} - INVALID. Errors:\n1. 1 is not of type \"string\"\n   /0/name type: 1\n"
+        )
+    );
+
+    // Mapped references can not escape the mapped directory
+    let escaping = create_temp_file(
+        &dir,
+        "escaping.json",
+        r#"{"$ref": "https://example.com/schemas/..%2Fschema.json"}"#,
+    );
+    let output = cli()
+        .arg(&escaping)
+        .arg("--map")
+        .arg(format!(
+            "https://example.com/schemas/={}",
+            schemas.display()
+        ))
+        .arg("--instance")
+        .arg(&instance)
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains(&format!("points outside of {}", schemas.display())),
+        "{stdout}"
+    );
+
+    let output = cli()
+        .arg(&schema)
+        .arg("--map")
+        .arg("https://example.com/")
+        .arg("--instance")
+        .arg(&instance)
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("expected `PREFIX=DIR`"));
+}
+
+#[test]
+fn test_bundle() {
+    let dir = tempdir().unwrap();
+    fs::create_dir_all(dir.path().join("defs")).unwrap();
+    fs::create_dir_all(dir.path().join("remote")).unwrap();
+    fs::write(
+        dir.path().join("defs").join("name.json"),
+        r#"{"type": "string", "allOf": [{"$ref": "https://example.com/tag.json"}, {"$ref": "../common.json"}]}"#,
+    )
+    .unwrap();
+    fs::write(dir.path().join("common.json"), r#"{"maxLength": 10}"#).unwrap();
+    fs::write(
+        dir.path().join("remote").join("tag.json"),
+        r#"{"$id": "https://example.com/tag.json", "minLength": 1, "$defs": {"kind": {"enum": ["a"]}}}"#,
+    )
+    .unwrap();
+    let schema = create_temp_file(
+        &dir,
+        "schema.json",
+        r##"{"properties": {"name": {"$ref": "defs/name.json"}, "kind": {"$ref": "https://example.com/tag.json#/$defs/kind"}, "self": {"$ref": "#"}}}"##,
+    );
+
+    let output = cli()
+        .arg("bundle")
+        .arg(&schema)
+        .arg("--map")
+        .arg(format!(
+            "https://example.com/={}",
+            dir.path().join("remote").display()
+        ))
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let bundled: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    // Local files are identified relative to the root document
+    assert_eq!(bundled["properties"]["name"]["$ref"], "defs/name.json");
+    assert_eq!(bundled["$defs"]["defs/name.json"]["$id"], "defs/name.json");
+    assert_eq!(
+        bundled["$defs"]["defs/name.json"]["allOf"][1]["$ref"],
+        "../common.json"
+    );
+    assert_eq!(bundled["$defs"]["common.json"]["$id"], "common.json");
+    assert!(!bundled.to_string().contains("file://"));
+    assert_eq!(
+        bundled["properties"]["kind"]["$ref"],
+        "https://example.com/tag.json#/$defs/kind"
+    );
+    assert_eq!(
+        bundled["$defs"]["https://example.com/tag.json"]["minLength"],
+        1
+    );
+    assert_eq!(bundled["properties"]["self"]["$ref"], "#");
+
+    // The bundle validates from anywhere, without reading or retrieving other documents
+    let other = tempdir().unwrap();
+    let bundle = create_temp_file(&other, "bundle.json", &bundled.to_string());
+    let instance = create_temp_file(&other, "instance.json", r#"{"name": "", "kind": "a"}"#);
+    let output = cli()
+        .arg(&bundle)
+        .arg("--instance")
+        .arg(&instance)
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        format!(
+            "{instance} - INVALID. Errors:\n1. \"\" is shorter than 1 character\n   /name minLength: \"\"\n"
+        )
+    );
+}
+
+#[test]
+fn test_diagnostics() {
+    let dir = tempdir().unwrap();
+    let schema = create_temp_file(
+        &dir,
+        "schema.json",
+        r#"{"properties": {"tags": {"items": {"minLength": -1}}}}"#,
+    );
+    let instance = create_temp_file(&dir, "instance.json", "{}");
+
+    let output = cli()
+        .arg(&schema)
+        .arg("--instance")
+        .arg(&instance)
+        .arg("--diagnostics")
+        .arg("json")
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let diagnostic: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        diagnostic,
+        serde_json::json!({
+            "schema": schema,
+            "location": "/properties/tags/items/minLength",
+            "keyword": "minLength",
+            "message": "-1 is less than the minimum of 0",
+        })
+    );
+
+    // Every error is reported, with the keyword containing it
+    let schema = create_temp_file(
+        &dir,
+        "schema.json",
+        r#"{"properties": {"age": 5}, "minLength": -1}"#,
+    );
+    let output = cli()
+        .arg(&schema)
+        .arg("--instance")
+        .arg(&instance)
+        .arg("--diagnostics")
+        .arg("json")
+        .output()
+        .unwrap();
+    let diagnostics: Vec<serde_json::Value> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let mut found: Vec<_> = diagnostics
+        .iter()
+        .map(|diagnostic| {
+            (
+                diagnostic["location"].as_str().unwrap(),
+                diagnostic["keyword"].as_str().unwrap(),
+            )
+        })
+        .collect();
+    found.sort_unstable();
+    assert_eq!(
+        found,
+        [
+            ("/minLength", "minLength"),
+            ("/properties/age", "properties")
+        ]
+    );
+
+    let output = cli()
+        .arg(&schema)
+        .arg("--instance")
+        .arg(&instance)
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.starts_with("Schema is invalid. Errors:\n1. "),
+        "{stdout}"
+    );
+    assert!(stdout.contains("\n2. "), "{stdout}");
+
+    let output = cli()
+        .arg(&schema)
+        .arg("--instance")
+        .arg(&instance)
+        .arg("--output")
+        .arg("sarif")
+        .output()
+        .unwrap();
+    let sarif: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let mut locations: Vec<_> = sarif["runs"][0]["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|result| {
+            result["locations"][0]["logicalLocations"][0]["fullyQualifiedName"]
+                .as_str()
+                .unwrap()
+        })
+        .collect();
+    locations.sort_unstable();
+    assert_eq!(locations, ["/minLength", "/properties/age"]);
+
+    let output = cli()
+        .arg(&schema)
+        .arg("--instance")
+        .arg(&instance)
+        .arg("--output")
+        .arg("json")
+        .output()
+        .unwrap();
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        report["instances"][0]["schemaErrors"]
+            .as_array()
+            .unwrap()
+            .len(),
+        2
+    );
+}
+
+#[test]
+fn test_environment_variables() {
+    let dir = tempdir().unwrap();
+    let schema = create_temp_file(&dir, "schema.json", r#"{"format": "email"}"#);
+    let instance = create_temp_file(&dir, "instance.json", r#""x""#);
+
+    let output = cli()
+        .arg(&schema)
+        .arg("--instance")
+        .arg(&instance)
+        .env("JSONSCHEMA_ASSERT_FORMAT", "true")
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        format!(
+            "{instance} - INVALID. Errors:\n1. \"x\" is not a \"email\"\n   (root) format: \"x\"\n"
+        )
+    );
+
+    // Flags take precedence
+    let output = cli()
+        .arg(&schema)
+        .arg("--instance")
+        .arg(&instance)
+        .arg("--no-assert-format")
+        .env("JSONSCHEMA_ASSERT_FORMAT", "true")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let output = cli()
+        .arg(&schema)
+        .arg("--instance")
+        .arg(&instance)
+        .env("JSONSCHEMA_ASSERT_FORMAT", "sometimes")
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "Error: Invalid JSONSCHEMA_ASSERT_FORMAT value `sometimes`, expected `true` or `false`\n"
+    );
+
+    // Draft 4 has no `const`
+    let schema = create_temp_file(&dir, "const.json", r#"{"const": 1}"#);
+    let instance = create_temp_file(&dir, "two.json", "2");
+    let output = cli()
+        .arg(&schema)
+        .arg("--instance")
+        .arg(&instance)
+        .env("JSONSCHEMA_DRAFT", "4")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_lint() {
+    let dir = tempdir().unwrap();
+    let schema = create_temp_file(
+        &dir,
+        "schema.json",
+        r##"{"type": "string", "minimum": 1, "then": {}, "$defs": {"used": {}, "unused": {}}, "properties": {"name": {"$ref": "#/$defs/used"}}, "maxLength": 1, "minLength": 2}"##,
+    );
+
+    let output = cli().arg("lint").arg(&schema).output().unwrap();
+    assert!(!output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        format!(
+            "{schema}#: warning[missing-schema]: The schema does not declare its draft in `$schema`\n\
+             {schema}#/$defs/unused: warning[unreachable-subschema]: The definition is not referenced anywhere in the schema\n\
+             {schema}#/minLength: error[unsatisfiable]: `minLength` (2) is greater than `maxLength` (1), so no string is valid\n\
+             {schema}#/minimum: warning[type-mismatch]: `minimum` only applies to numbers, but `type` is \"string\"\n\
+             {schema}#/properties: warning[type-mismatch]: `properties` only applies to objects, but `type` is \"string\"\n\
+             {schema}#/then: warning[ignored-keyword]: `then` is ignored without `if`\n"
+        )
+    );
+
+    // Warnings alone do not fail, and findings are available as JSON
+    let schema = create_temp_file(
+        &dir,
+        "draft7.json",
+        r##"{"$schema": "http://json-schema.org/draft-07/schema#", "prefixItems": [], "properties": {"name": {"$ref": "#/definitions/name", "minLength": 1}}, "definitions": {"name": {}}}"##,
+    );
+    let output = cli()
+        .arg("lint")
+        .arg(&schema)
+        .arg("--output")
+        .arg("json")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        report,
+        serde_json::json!({
+            "schema": schema,
+            "findings": [
+                {
+                    "severity": "warning",
+                    "rule": "unknown-keyword",
+                    "location": "/prefixItems",
+                    "message": "`prefixItems` is not a keyword in Draft 7, use an array of schemas in `items`"
+                },
+                {
+                    "severity": "warning",
+                    "rule": "ignored-keyword",
+                    "location": "/properties/name/minLength",
+                    "message": "`minLength` is ignored next to `$ref` in Draft 7"
+                }
+            ]
+        })
+    );
+}
+
+#[test]
+fn test_schema_map() {
+    let dir = tempdir().unwrap();
+    for subdir in ["schemas", "remote", "numbers", "names"] {
+        fs::create_dir_all(dir.path().join(subdir)).unwrap();
+    }
+    fs::write(
+        dir.path().join("schemas").join("string.json"),
+        r#"{"type": "string"}"#,
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("remote").join("number.json"),
+        r#"{"type": "number"}"#,
+    )
+    .unwrap();
+    // Members are matched in file order, not in key order
+    fs::write(
+        dir.path().join("map.json"),
+        r#"{"numbers/*.json": "https://example.com/number.json", "*/*.json": "schemas/string.json"}"#,
+    )
+    .unwrap();
+    fs::write(dir.path().join("numbers").join("a.json"), "1").unwrap();
+    fs::write(dir.path().join("names").join("b.json"), r#""b""#).unwrap();
+    fs::write(dir.path().join("names").join("c.json"), "2").unwrap();
+
+    let output = cli()
+        .current_dir(dir.path())
+        .args([
+            "--schema-map",
+            "map.json",
+            "--map",
+            "https://example.com/=remote",
+        ])
+        .args([
+            "-i",
+            "numbers/a.json",
+            "-i",
+            "names/b.json",
+            "-i",
+            "names/c.json",
+        ])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "numbers/a.json - VALID\nnames/b.json - VALID\nnames/c.json - INVALID. Errors:\n1. 2 is not of type \"string\"\n   (root) type: 2\n"
+    );
+
+    // Instances without a matching glob need a default schema
+    let output = cli()
+        .current_dir(dir.path())
+        .args(["--schema-map", "map.json", "-i", "map.json"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "Error: No schema for map.json\n"
+    );
+}
+
+#[test]
+fn test_diff() {
+    let dir = tempdir().unwrap();
+    let old = create_temp_file(
+        &dir,
+        "old.json",
+        r#"{"type": "object", "required": ["id"], "additionalProperties": false, "properties": {"id": {"type": "integer", "minimum": 0}, "name": {"maxLength": 10}, "tags": {}}, "anyOf": [{"required": ["id"]}, {"required": ["name"]}], "title": "Old"}"#,
+    );
+    let new = create_temp_file(
+        &dir,
+        "new.json",
+        r#"{"type": "object", "required": ["id", "name"], "additionalProperties": false, "properties": {"id": {"type": "number", "minimum": 1}, "name": {"maxLength": 20, "pattern": "^[a-z]+$"}, "email": {}}, "anyOf": [{"required": ["id"]}], "title": "New"}"#,
+    );
+
+    let output = cli().arg("diff").arg(&old).arg(&new).output().unwrap();
+    assert!(!output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "#/anyOf/1: breaking: `anyOf` branch removed\n\
+         #/properties/email: compatible: Property `email` is now allowed\n\
+         #/properties/id/minimum: breaking: `minimum` changed from 0 to 1\n\
+         #/properties/id/type: compatible: `type` now includes \"number\"\n\
+         #/properties/name/maxLength: compatible: `maxLength` changed from 10 to 20\n\
+         #/properties/name/pattern: breaking: `pattern` added with \"^[a-z]+$\"\n\
+         #/properties/tags: breaking: Property `tags` is no longer allowed\n\
+         #/required: breaking: `required` now includes \"name\"\n\
+         #/title: compatible: `title` changed from \"Old\" to \"New\"\n"
+    );
+
+    // Compatible changes alone do not fail, and are available as JSON
+    let relaxed = create_temp_file(
+        &dir,
+        "relaxed.json",
+        r#"{"type": ["object", "null"], "required": ["id"], "additionalProperties": false, "properties": {"id": {"type": "integer", "minimum": 0}, "name": {"maxLength": 10}, "tags": {}}, "anyOf": [{"required": ["id"]}, {"required": ["name"]}, {"required": ["tags"]}], "title": "Old"}"#,
+    );
+    let output = cli()
+        .arg("diff")
+        .arg(&old)
+        .arg(&relaxed)
+        .arg("--output")
+        .arg("json")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        report,
+        serde_json::json!({
+            "old": old,
+            "new": relaxed,
+            "changes": [
+                {
+                    "compatibility": "compatible",
+                    "change": "added",
+                    "location": "/anyOf/2",
+                    "message": "`anyOf` branch added"
+                },
+                {
+                    "compatibility": "compatible",
+                    "change": "added",
+                    "location": "/type",
+                    "message": "`type` now includes \"null\""
+                }
+            ]
+        })
+    );
+}
+
+#[test]
+fn test_migrate() {
+    let dir = tempdir().unwrap();
+    let schema = create_temp_file(
+        &dir,
+        "schema.json",
+        r##"{
+            "$schema": "http://json-schema.org/draft-04/schema#",
+            "id": "https://example.com/schema.json",
+            "definitions": {"positive": {"minimum": 0, "exclusiveMinimum": true}},
+            "properties": {
+                "size": {"$ref": "#/definitions/positive", "type": "integer"},
+                "pair": {"items": [{"$ref": "#/definitions/positive"}, {}], "additionalItems": false}
+            },
+            "dependencies": {"a": ["b"], "c": {"required": ["d"]}}
+        }"##,
+    );
+
+    let output = cli().arg("migrate").arg(&schema).output().unwrap();
+    assert!(output.status.success());
+    let migrated: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        migrated,
+        serde_json::json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "$id": "https://example.com/schema.json",
+            "$defs": {"positive": {"exclusiveMinimum": 0}},
+            "properties": {
+                "size": {"$ref": "#/$defs/positive", "type": "integer"},
+                "pair": {"prefixItems": [{"$ref": "#/$defs/positive"}, {}], "items": false}
+            },
+            "dependentRequired": {"a": ["b"]},
+            "dependentSchemas": {"c": {"required": ["d"]}}
+        })
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr),
+        "#/properties/size: `type` next to `$ref` had no effect before Draft 2019-09 and now apply, remove them to keep the previous behavior\n"
+    );
+
+    let output = cli()
+        .arg("migrate")
+        .arg(&schema)
+        .arg("--to")
+        .arg("6")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let migrated: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(migrated["$id"], "https://example.com/schema.json");
+    assert_eq!(migrated["definitions"]["positive"]["exclusiveMinimum"], 0);
+    assert_eq!(migrated["properties"]["pair"]["additionalItems"], false);
+}
+
+#[test]
+fn test_deref() {
+    let dir = tempdir().unwrap();
+    create_temp_file(
+        &dir,
+        "other.json",
+        r##"{"type": "string", "$defs": {"tag": {"$anchor": "tag", "items": {"$ref": "#/$defs/item"}}, "item": {"const": 1}}}"##,
+    );
+    let schema = create_temp_file(
+        &dir,
+        "schema.json",
+        r##"{"properties": {"id": {"$ref": "#/$defs/id"}, "name": {"$ref": "other.json", "minLength": 1}, "tags": {"$ref": "other.json#tag"}}, "$defs": {"id": {"type": "integer"}}}"##,
+    );
+
+    let output = cli().arg("deref").arg(&schema).output().unwrap();
+    assert!(output.status.success());
+    let dereferenced: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        dereferenced["properties"],
+        serde_json::json!({
+            "id": {"type": "integer"},
+            "name": {"minLength": 1, "allOf": [{"type": "string"}]},
+            "tags": {"items": {"const": 1}}
+        })
+    );
+}
+
+#[test]
+fn test_deref_recursive() {
+    let dir = tempdir().unwrap();
+    let schema = create_temp_file(
+        &dir,
+        "schema.json",
+        r##"{"properties": {"next": {"$ref": "#"}}}"##,
+    );
+
+    let output = cli().arg("deref").arg(&schema).output().unwrap();
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("recursive reference at `#/properties/next`"));
+}
+
+#[test]
+fn test_deref_max_depth() {
+    let dir = tempdir().unwrap();
+    let schema = create_temp_file(
+        &dir,
+        "schema.json",
+        r##"{"$ref": "#/$defs/a", "$defs": {"a": {"$ref": "#/$defs/b"}, "b": {"type": "null"}}}"##,
+    );
+
+    let output = cli()
+        .arg("deref")
+        .arg(&schema)
+        .arg("--max-depth")
+        .arg("1")
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("exceeds the maximum depth of 1 nested references"));
+
+    let output = cli().arg("deref").arg(&schema).output().unwrap();
+    assert!(output.status.success());
+    let dereferenced: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(dereferenced["allOf"], serde_json::json!([{"type": "null"}]));
+}
+
+#[test]
+fn test_generate() {
+    let dir = tempdir().unwrap();
+    let schema = create_temp_file(
+        &dir,
+        "schema.json",
+        r##"{
+            "type": "object",
+            "required": ["id", "email", "tags", "kind"],
+            "properties": {
+                "id": {"type": "integer", "minimum": 1, "maximum": 10},
+                "email": {"type": "string", "format": "email"},
+                "tags": {"type": "array", "items": {"$ref": "#/$defs/tag"}, "minItems": 1, "uniqueItems": true},
+                "kind": {"enum": ["a", "b"]},
+                "price": {"type": "number", "exclusiveMinimum": 0, "multipleOf": 0.5}
+            },
+            "additionalProperties": false,
+            "$defs": {"tag": {"type": "string", "minLength": 2, "maxLength": 4}}
+        }"##,
+    );
+
+    let output = cli()
+        .arg("generate")
+        .arg(&schema)
+        .arg("--count")
+        .arg("20")
+        .arg("--seed")
+        .arg("7")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+    assert_eq!(stdout.lines().count(), 20);
+    for line in stdout.lines() {
+        let instance: serde_json::Value = serde_json::from_str(line).unwrap();
+        let id = instance["id"].as_u64().unwrap();
+        assert!((1..=10).contains(&id));
+        assert!(instance["email"].as_str().unwrap().contains('@'));
+        assert!(["a", "b"].contains(&instance["kind"].as_str().unwrap()));
+        assert!(!instance["tags"].as_array().unwrap().is_empty());
+    }
+
+    // The same seed generates the same instances
+    let again = cli()
+        .arg("generate")
+        .arg(&schema)
+        .arg("--count")
+        .arg("20")
+        .arg("--seed")
+        .arg("7")
+        .output()
+        .unwrap();
+    assert_eq!(String::from_utf8_lossy(&again.stdout), stdout);
+}
+
+#[test]
+fn test_generate_unsatisfiable() {
+    let dir = tempdir().unwrap();
+    let schema = create_temp_file(
+        &dir,
+        "schema.json",
+        r#"{"allOf": [{"type": "integer"}, {"type": "string"}]}"#,
+    );
+
+    let output = cli().arg("generate").arg(&schema).output().unwrap();
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Could not generate a valid instance"));
+}
+
+#[test]
+fn test_exit_codes() {
+    let dir = tempdir().unwrap();
+    let schema = create_temp_file(&dir, "schema.json", r#"{"type": "integer"}"#);
+    let invalid_schema = create_temp_file(&dir, "invalid.json", r#"{"type": "invalid"}"#);
+    let valid = create_temp_file(&dir, "valid.json", "1");
+    let invalid = create_temp_file(&dir, "instance.json", "\"a\"");
+    let malformed = create_temp_file(&dir, "malformed.json", "{");
+    let unretrievable = create_temp_file(&dir, "unretrievable.json", r#"{"$ref": "missing.json"}"#);
+
+    let code = |args: &[&str]| cli().args(args).output().unwrap().status.code();
+    assert_eq!(code(&[&schema, "-i", &valid]), Some(0));
+    assert_eq!(code(&[&schema, "-i", &invalid]), Some(1));
+    assert_eq!(code(&[&invalid_schema, "-i", &valid]), Some(2));
+    assert_eq!(code(&[&malformed, "-i", &valid]), Some(3));
+    assert_eq!(code(&[&schema, "-i", "missing.json"]), Some(3));
+    assert_eq!(code(&[&schema, "--unknown"]), Some(3));
+    assert_eq!(code(&["--help"]), Some(0));
+    assert_eq!(code(&[&schema, "-i", &malformed]), Some(3));
+    assert_eq!(code(&[&schema, "-i", &valid, "-i", &invalid]), Some(1));
+    assert_eq!(code(&["generate", &invalid_schema]), Some(2));
+    // Referenced documents that cannot be read are I/O errors, not invalid schemas
+    assert_eq!(code(&[&unretrievable, "-i", &valid]), Some(3));
+    assert_eq!(code(&["generate", &unretrievable]), Some(3));
+}
+
+#[test]
+fn test_batch_exit_code() {
+    let dir = tempdir().unwrap();
+    let schema = create_temp_file(&dir, "schema.json", r#"{"type": "integer"}"#);
+    let invalid_schema = create_temp_file(&dir, "invalid.json", r#"{"type": "invalid"}"#);
+    let instance = create_temp_file(&dir, "instance.json", "\"a\"");
+    let command = |schema: &str| {
+        serde_json::json!({"schema": schema, "instance_path": instance}).to_string() + "\n"
+    };
+
+    let output = cli()
+        .arg("--batch")
+        .write_stdin(command(&schema) + &command(&invalid_schema))
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    let output = cli()
+        .arg("--batch")
+        .write_stdin(command(&schema) + "not a command\n")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(3));
+}
+
+#[test]
+fn test_instance_pointer() {
+    let dir = tempdir().unwrap();
+    let schema = create_temp_file(
+        &dir,
+        "schema.json",
+        r#"{"properties": {"items": {"items": {"type": "string"}}}, "required": ["name"]}"#,
+    );
+    let valid = create_temp_file(
+        &dir,
+        "valid.json",
+        r#"{"kind": "Job", "spec": {"template": {"name": "a"}}}"#,
+    );
+    let invalid = create_temp_file(
+        &dir,
+        "invalid.json",
+        r#"{"name": "a", "spec": {"template": {"items": [{}]}}}"#,
+    );
+
+    let output = cli()
+        .arg(&schema)
+        .arg("-i")
+        .arg(&valid)
+        .arg("--instance-pointer")
+        .arg("/spec/template")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    // Error locations are relative to the whole instance
+    let output = cli()
+        .arg(&schema)
+        .arg("-i")
+        .arg(&invalid)
+        .arg("--instance-pointer")
+        .arg("/spec/template")
+        .arg("--output")
+        .arg("json")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let locations: Vec<_> = report["instances"][0]["errors"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|error| error["instanceLocation"].as_str().unwrap())
+        .collect();
+    assert_eq!(locations, ["/spec/template/items/0", "/spec/template"]);
+
+    // Instances without a value at the pointer lack the expected envelope
+    let output = cli()
+        .arg(&schema)
+        .arg("-i")
+        .arg(&valid)
+        .arg("--instance-pointer")
+        .arg("/spec/missing")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(3));
+    assert!(String::from_utf8_lossy(&output.stdout)
+        .contains("Instance has no value at `/spec/missing`"));
+
+    // Records without a value at the pointer are reported on their own
+    let records = create_temp_file(
+        &dir,
+        "records.ndjson",
+        "{\"spec\": {\"template\": {\"name\": \"a\"}}}\n{\"kind\": \"Job\"}\n{\"spec\": {\"template\": {}}}\n",
+    );
+    let output = cli()
+        .arg(&schema)
+        .arg("--format")
+        .arg("ndjson")
+        .arg("-i")
+        .arg(&records)
+        .arg("--instance-pointer")
+        .arg("/spec/template")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains(&format!("{records}:1 - VALID")));
+    assert!(stdout.contains(&format!(
+        "{records}:2 - INVALID. Errors:\n1. Instance has no value at `/spec/template`"
+    )));
+    assert!(stdout.contains(&format!("{records}:3 - INVALID")));
+
+    let output = cli()
+        .arg(&schema)
+        .arg("-i")
+        .arg(&valid)
+        .arg("--instance-pointer")
+        .arg("spec")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(3));
+}
+
+#[test]
+fn test_quiet() {
+    let dir = tempdir().unwrap();
+    let schema = create_temp_file(&dir, "schema.json", r#"{"type": "object"}"#);
+    let valid = create_temp_file(&dir, "valid.json", "{}");
+    let invalid = create_temp_file(&dir, "invalid.json", "[]");
+
+    let output = cli()
+        .arg(&schema)
+        .arg("-q")
+        .arg("--instance")
+        .arg(&valid)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "");
+
+    let output = cli()
+        .arg(&schema)
+        .arg("--quiet")
+        .arg("--instance")
+        .arg(&valid)
+        .arg("--instance")
+        .arg(&invalid)
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "");
+    // Errors are not printed either
+    let output = cli()
+        .arg(&schema)
+        .arg("-q")
+        .arg("--instance")
+        .arg(dir.path().join("missing.json"))
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "");
+}
+
+#[test]
+fn test_list_invalid() {
+    let dir = tempdir().unwrap();
+    let schema = create_temp_file(&dir, "schema.json", r#"{"type": "object"}"#);
+    let valid = create_temp_file(&dir, "valid.json", "{}");
+    let invalid = create_temp_file(&dir, "invalid.json", "[]");
+    let lines = create_temp_file(&dir, "lines.ndjson", "[]\n{}\n1\n");
+
+    let output = cli()
+        .arg(&schema)
+        .arg("-l")
+        .arg("--instance")
+        .arg(&valid)
+        .arg("--instance")
+        .arg(&invalid)
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        format!("{invalid}\n")
+    );
+    // Files with several invalid lines are listed once
+    let output = cli()
+        .arg(&schema)
+        .arg("--list-invalid")
+        .arg("--format")
+        .arg("ndjson")
+        .arg("--instance")
+        .arg(&lines)
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        format!("{lines}\n")
+    );
+}
+
+#[test]
+fn test_jobs() {
+    let dir = tempdir().unwrap();
+    let schema = create_temp_file(&dir, "schema.json", r#"{"type": "object"}"#);
+    let instances: Vec<_> = (0..40)
+        .map(|i| {
+            let content = if i % 3 == 0 { "[]" } else { "{}" };
+            create_temp_file(&dir, &format!("instance{i}.json"), content)
+        })
+        .collect();
+
+    let run = |args: &[&str]| {
+        let mut cmd = cli();
+        cmd.arg(&schema).args(args);
+        for instance in &instances {
+            cmd.arg("--instance").arg(instance);
+        }
+        let output = cmd.output().unwrap();
+        assert!(!output.status.success());
+        String::from_utf8(output.stdout).unwrap()
+    };
+    // Outcomes are printed in the order of the instances, regardless of the number of threads
+    assert_eq!(run(&["--jobs", "4"]), run(&["--jobs", "1"]));
+    assert_eq!(
+        run(&["-j", "4", "--max-failures", "5"]),
+        run(&["-j", "1", "--max-failures", "5"])
+    );
+    assert!(
+        run(&["-j", "4", "--max-failures", "5"]).ends_with("Stopped after 5 invalid instance(s)\n")
+    );
+}
+
+#[test]
+fn test_strict_json() {
+    let dir = tempdir().unwrap();
+    let schema = create_temp_file(&dir, "schema.json", r#"{"required": ["name"]}"#);
+    let duplicate = create_temp_file(
+        &dir,
+        "duplicate.json",
+        r#"{"name": "a", "id": 18446744073709551616, "name": "b"}"#,
+    );
+    let surrogate = create_temp_file(&dir, "surrogate.json", r#"{"name": "\ud800"}"#);
+
+    // Lenient by default
+    let mut cmd = cli();
+    cmd.arg(&schema).arg("--instance").arg(&duplicate);
+    let output = cmd.output().unwrap();
+    assert!(output.status.success());
+
+    let mut cmd = cli();
+    cmd.arg(&schema)
+        .arg("--strict-json")
+        .arg("--instance")
+        .arg(&duplicate)
+        .arg("--instance")
+        .arg(&surrogate);
+    let output = cmd.output().unwrap();
+    assert!(!output.status.success());
+    let sanitized = sanitize_output(
+        String::from_utf8_lossy(&output.stdout).to_string(),
+        &[&duplicate, &surrogate],
+    );
+    assert_snapshot!(sanitized);
+}
+
+#[test]
+fn test_lang() {
+    let dir = tempdir().unwrap();
+    let schema = create_temp_file(
+        &dir,
+        "schema.json",
+        r#"{"required": ["name"], "properties": {"id": {"type": "integer", "minimum": 1}}}"#,
+    );
+    let catalog = create_temp_file(
+        &dir,
+        "fr.toml",
+        r#"
+[messages]
+required = "La propriété {property} est requise"
+type = "{instance} n'est pas du type {types} ({instance_path})"
+"#,
+    );
+    let instance = create_temp_file(&dir, "instance.json", r#"{"id": "a"}"#);
+
+    let mut cmd = cli();
+    cmd.arg(&schema)
+        .arg("--lang")
+        .arg(&catalog)
+        .arg("--instance")
+        .arg(&instance);
+    let output = cmd.output().unwrap();
+    assert!(!output.status.success());
+    let sanitized = sanitize_output(
+        String::from_utf8_lossy(&output.stdout).to_string(),
+        &[&instance],
+    );
+    assert_snapshot!(sanitized);
+}
+
+#[test]
+fn test_lang_invalid_catalog() {
+    let dir = tempdir().unwrap();
+    let schema = create_temp_file(&dir, "schema.json", "{}");
+    let catalog = create_temp_file(
+        &dir,
+        "fr.toml",
+        "[messages]
+required = 1
+",
+    );
+    let instance = create_temp_file(&dir, "instance.json", "{}");
+
+    let mut cmd = cli();
+    cmd.arg(&schema)
+        .arg("--lang")
+        .arg(&catalog)
+        .arg("--instance")
+        .arg(&instance);
+    let output = cmd.output().unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Invalid message catalog"));
+}
+
+#[test]
+fn test_timings() {
+    let dir = tempdir().unwrap();
+    let schema = create_temp_file(&dir, "schema.json", r#"{"$ref": "other.json"}"#);
+    create_temp_file(&dir, "other.json", r#"{"type": "object"}"#);
+    let instance = create_temp_file(&dir, "instance.json", "{}");
+
+    let mut cmd = cli();
+    cmd.arg(&schema)
+        .arg("--instance")
+        .arg(&instance)
+        .arg("--timings");
+    let output = cmd.output().unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        format!("{instance} - VALID\n")
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let lines: Vec<_> = stderr.lines().collect();
+    assert_eq!(lines.len(), 3, "{stderr}");
+    assert!(lines[0].contains("other.json - retrieved in "), "{stderr}");
+    assert!(
+        lines[1].starts_with(&format!("{schema} - compiled in ")),
+        "{stderr}"
+    );
+    assert!(
+        lines[2].starts_with(&format!("{instance} - validated in ")),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn test_coverage() {
+    let dir = tempdir().unwrap();
+    let schema = create_temp_file(
+        &dir,
+        "schema.json",
+        r#"{"properties": {"id": {"anyOf": [{"type": "integer"}, {"type": "string"}]}, "tags": {"items": {"type": "string"}}}}"#,
+    );
+    let first = create_temp_file(&dir, "first.json", r#"{"id": 1}"#);
+    let second = create_temp_file(&dir, "second.json", r#"{"id": 2, "tags": []}"#);
+    let coverage = dir.path().join("coverage.json");
+
+    let mut cmd = cli();
+    cmd.arg(&schema)
+        .arg("--instance")
+        .arg(&first)
+        .arg("--instance")
+        .arg(&second)
+        .arg("--coverage")
+        .arg(&coverage);
+    let output = cmd.output().unwrap();
+    assert!(output.status.success());
+    let coverage: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(coverage).unwrap()).unwrap();
+    assert_eq!(
+        coverage,
+        serde_json::json!({
+            "schemas": [{
+                "schema": schema,
+                "subschemas": 6,
+                "covered": 4,
+                "uncovered": ["/properties/id/anyOf/1", "/properties/tags/items"]
+            }]
+        })
+    );
+}
@@ -70,12 +70,21 @@ class ValidationErrorKind:
 
     class AdditionalProperties:
         unexpected: list[str]
+        suggestions: dict[str, str]
 
-    class AnyOf: ...
+    class AnyOf:
+        context: list[list["ValidationError"]]
 
     class BacktrackLimitExceeded:
         error: str
 
+    class Cancelled: ...
+
+    class CompilationLimitExceeded:
+        kind: str
+        limit: int
+        cycle: list[str]
+
     class Constant:
         expected_value: JSONType
 
@@ -90,6 +99,14 @@ class ValidationErrorKind:
     class Custom:
         message: str
 
+    class DateOutOfBounds:
+        format: str
+        bound: str
+
+    class DependentRequired:
+        property: str
+        missing: list[str]
+
     class Enum:
         options: list[JSONType]
 
@@ -131,27 +148,40 @@ class ValidationErrorKind:
     class MinProperties:
         limit: int
 
+    class MissingProperties:
+        properties: list[str]
+
     class MultipleOf:
         multiple_of: float
 
     class Not:
         schema: JSONType
+        matched: list[str]
 
     class OneOfMultipleValid: ...
-    class OneOfNotValid: ...
+    class OneOfNotValid:
+        context: list[list["ValidationError"]]
 
     class Pattern:
         pattern: str
 
     class PropertyNames:
+        property: str
         error: "ValidationError"
 
     class Required:
         property: str
 
+    class SizeLimitExceeded:
+        kind: str
+        limit: int
+
     class Type:
         types: list[str]
 
+    class UndefinedVariable:
+        name: str
+
     class UnevaluatedItems:
         unexpected: list[int]
 
@@ -11,7 +11,7 @@ use pyo3::{
     exceptions::{self, PyValueError},
     ffi::PyUnicode_AsUTF8AndSize,
     prelude::*,
-    types::{PyAny, PyDict, PyList, PyString, PyType},
+    types::{IntoPyDict, PyAny, PyDict, PyList, PyString, PyType},
     wrap_pyfunction,
 };
 use regex::{FancyRegexOptions, RegexOptions};
@@ -103,41 +103,130 @@ impl ReferencingError {
 #[pyclass]
 #[derive(Debug)]
 enum ValidationErrorKind {
-    AdditionalItems { limit: usize },
-    AdditionalProperties { unexpected: Py<PyList> },
-    AnyOf {},
-    BacktrackLimitExceeded { error: String },
-    Constant { expected_value: PyObject },
+    AdditionalItems {
+        limit: usize,
+    },
+    AdditionalProperties {
+        unexpected: Py<PyList>,
+        suggestions: Py<PyDict>,
+    },
+    AnyOf {
+        context: Py<PyList>,
+    },
+    BacktrackLimitExceeded {
+        error: String,
+    },
+    Cancelled {},
+    CompilationLimitExceeded {
+        kind: String,
+        limit: usize,
+        cycle: Vec<String>,
+    },
+    Constant {
+        expected_value: PyObject,
+    },
     Contains {},
-    ContentEncoding { content_encoding: String },
-    ContentMediaType { content_media_type: String },
-    Custom { message: String },
-    Enum { options: PyObject },
-    ExclusiveMaximum { limit: PyObject },
-    ExclusiveMinimum { limit: PyObject },
+    ContentEncoding {
+        content_encoding: String,
+    },
+    ContentMediaType {
+        content_media_type: String,
+    },
+    Custom {
+        message: String,
+    },
+    DateOutOfBounds {
+        format: String,
+        bound: String,
+    },
+    DependentRequired {
+        property: String,
+        missing: Py<PyList>,
+    },
+    Enum {
+        options: PyObject,
+    },
+    ExclusiveMaximum {
+        limit: PyObject,
+    },
+    ExclusiveMinimum {
+        limit: PyObject,
+    },
     FalseSchema {},
-    Format { format: String },
-    FromUtf8 { error: String },
-    MaxItems { limit: u64 },
-    Maximum { limit: PyObject },
-    MaxLength { limit: u64 },
-    MaxProperties { limit: u64 },
-    MinItems { limit: u64 },
-    Minimum { limit: PyObject },
-    MinLength { limit: u64 },
-    MinProperties { limit: u64 },
-    MultipleOf { multiple_of: f64 },
-    Not { schema: PyObject },
+    Format {
+        format: String,
+    },
+    FromUtf8 {
+        error: String,
+    },
+    MaxItems {
+        limit: u64,
+    },
+    Maximum {
+        limit: PyObject,
+    },
+    MaxLength {
+        limit: u64,
+    },
+    MaxProperties {
+        limit: u64,
+    },
+    MinItems {
+        limit: u64,
+    },
+    Minimum {
+        limit: PyObject,
+    },
+    MinLength {
+        limit: u64,
+    },
+    MinProperties {
+        limit: u64,
+    },
+    MissingProperties {
+        properties: Py<PyList>,
+    },
+    MultipleOf {
+        multiple_of: f64,
+    },
+    Not {
+        schema: PyObject,
+        matched: Py<PyList>,
+    },
     OneOfMultipleValid {},
-    OneOfNotValid {},
-    Pattern { pattern: String },
-    PropertyNames { error: Py<ValidationError> },
-    Required { property: PyObject },
-    Type { types: Py<PyList> },
-    UnevaluatedItems { unexpected: Py<PyList> },
-    UnevaluatedProperties { unexpected: Py<PyList> },
+    OneOfNotValid {
+        context: Py<PyList>,
+    },
+    Pattern {
+        pattern: String,
+    },
+    PropertyNames {
+        property: String,
+        error: Py<ValidationError>,
+    },
+    Required {
+        property: PyObject,
+    },
+    SizeLimitExceeded {
+        kind: String,
+        limit: usize,
+    },
+    Type {
+        types: Py<PyList>,
+    },
+    UndefinedVariable {
+        name: String,
+    },
+    UnevaluatedItems {
+        unexpected: Py<PyList>,
+    },
+    UnevaluatedProperties {
+        unexpected: Py<PyList>,
+    },
     UniqueItems {},
-    Referencing { error: Py<ReferencingError> },
+    Referencing {
+        error: Py<ReferencingError>,
+    },
 }
 
 impl ValidationErrorKind {
@@ -150,17 +239,33 @@ impl ValidationErrorKind {
             jsonschema::error::ValidationErrorKind::AdditionalItems { limit } => {
                 ValidationErrorKind::AdditionalItems { limit }
             }
-            jsonschema::error::ValidationErrorKind::AdditionalProperties { unexpected } => {
-                ValidationErrorKind::AdditionalProperties {
-                    unexpected: PyList::new(py, unexpected)?.unbind(),
+            jsonschema::error::ValidationErrorKind::AdditionalProperties {
+                unexpected,
+                suggestions,
+            } => ValidationErrorKind::AdditionalProperties {
+                unexpected: PyList::new(py, unexpected)?.unbind(),
+                suggestions: suggestions.into_py_dict(py)?.unbind(),
+            },
+            jsonschema::error::ValidationErrorKind::AnyOf { context } => {
+                ValidationErrorKind::AnyOf {
+                    context: into_context(py, context, mask)?,
                 }
             }
-            jsonschema::error::ValidationErrorKind::AnyOf => ValidationErrorKind::AnyOf {},
             jsonschema::error::ValidationErrorKind::BacktrackLimitExceeded { error } => {
                 ValidationErrorKind::BacktrackLimitExceeded {
                     error: error.to_string(),
                 }
             }
+            jsonschema::error::ValidationErrorKind::Cancelled => ValidationErrorKind::Cancelled {},
+            jsonschema::error::ValidationErrorKind::CompilationLimitExceeded {
+                kind,
+                limit,
+                cycle,
+            } => ValidationErrorKind::CompilationLimitExceeded {
+                kind: kind.as_str().to_string(),
+                limit,
+                cycle,
+            },
             jsonschema::error::ValidationErrorKind::Constant { expected_value } => {
                 ValidationErrorKind::Constant {
                     expected_value: pythonize::pythonize(py, &expected_value)?.unbind(),
@@ -176,6 +281,18 @@ impl ValidationErrorKind {
             jsonschema::error::ValidationErrorKind::Custom { message } => {
                 ValidationErrorKind::Custom { message }
             }
+            jsonschema::error::ValidationErrorKind::DateOutOfBounds { format, bound } => {
+                ValidationErrorKind::DateOutOfBounds {
+                    format,
+                    bound: bound.as_str().to_string(),
+                }
+            }
+            jsonschema::error::ValidationErrorKind::DependentRequired { property, missing } => {
+                ValidationErrorKind::DependentRequired {
+                    property,
+                    missing: PyList::new(py, missing)?.unbind(),
+                }
+            }
             jsonschema::error::ValidationErrorKind::Enum { options } => ValidationErrorKind::Enum {
                 options: pythonize::pythonize(py, &options)?.unbind(),
             },
@@ -228,38 +345,36 @@ impl ValidationErrorKind {
             jsonschema::error::ValidationErrorKind::MinProperties { limit } => {
                 ValidationErrorKind::MinProperties { limit }
             }
+            jsonschema::error::ValidationErrorKind::MissingProperties { properties } => {
+                ValidationErrorKind::MissingProperties {
+                    properties: PyList::new(py, properties)?.unbind(),
+                }
+            }
             jsonschema::error::ValidationErrorKind::MultipleOf { multiple_of } => {
                 ValidationErrorKind::MultipleOf { multiple_of }
             }
-            jsonschema::error::ValidationErrorKind::Not { schema } => ValidationErrorKind::Not {
-                schema: pythonize::pythonize(py, &schema)?.unbind(),
-            },
+            jsonschema::error::ValidationErrorKind::Not { schema, matched } => {
+                ValidationErrorKind::Not {
+                    schema: pythonize::pythonize(py, &schema)?.unbind(),
+                    matched: PyList::new(py, matched.iter().map(|location| location.as_str()))?
+                        .unbind(),
+                }
+            }
             jsonschema::error::ValidationErrorKind::OneOfMultipleValid => {
                 ValidationErrorKind::OneOfMultipleValid {}
             }
-            jsonschema::error::ValidationErrorKind::OneOfNotValid => {
-                ValidationErrorKind::OneOfNotValid {}
+            jsonschema::error::ValidationErrorKind::OneOfNotValid { context } => {
+                ValidationErrorKind::OneOfNotValid {
+                    context: into_context(py, context, mask)?,
+                }
             }
             jsonschema::error::ValidationErrorKind::Pattern { pattern } => {
                 ValidationErrorKind::Pattern { pattern }
             }
-            jsonschema::error::ValidationErrorKind::PropertyNames { error } => {
+            jsonschema::error::ValidationErrorKind::PropertyNames { property, error } => {
                 ValidationErrorKind::PropertyNames {
-                    error: {
-                        let (message, verbose_message, schema_path, instance_path, kind, instance) =
-                            into_validation_error_args(py, *error, mask)?;
-                        Py::new(
-                            py,
-                            ValidationError {
-                                message,
-                                verbose_message,
-                                schema_path,
-                                instance_path,
-                                kind: Py::new(py, kind)?,
-                                instance,
-                            },
-                        )?
-                    },
+                    property,
+                    error: into_validation_error(py, *error, mask)?,
                 }
             }
             jsonschema::error::ValidationErrorKind::Required { property } => {
@@ -267,6 +382,15 @@ impl ValidationErrorKind {
                     property: pythonize::pythonize(py, &property)?.unbind(),
                 }
             }
+            jsonschema::error::ValidationErrorKind::SizeLimitExceeded { kind, limit } => {
+                ValidationErrorKind::SizeLimitExceeded {
+                    kind: kind.as_str().to_string(),
+                    limit,
+                }
+            }
+            jsonschema::error::ValidationErrorKind::UndefinedVariable { name } => {
+                ValidationErrorKind::UndefinedVariable { name }
+            }
             jsonschema::error::ValidationErrorKind::Type { kind } => ValidationErrorKind::Type {
                 types: {
                     match kind {
@@ -302,6 +426,16 @@ impl ValidationErrorKind {
                     )?,
                 }
             }
+            jsonschema::error::ValidationErrorKind::ReferencingVia { error, breadcrumbs } => {
+                ValidationErrorKind::Referencing {
+                    error: Py::new(
+                        py,
+                        ReferencingError {
+                            message: format!("{error} (via {breadcrumbs})"),
+                        },
+                    )?,
+                }
+            }
         })
     }
 }
@@ -369,6 +503,42 @@ fn into_validation_error_args(
         instance,
     ))
 }
+fn into_validation_error(
+    py: Python<'_>,
+    error: jsonschema::ValidationError<'_>,
+    mask: Option<&str>,
+) -> PyResult<Py<ValidationError>> {
+    let (message, verbose_message, schema_path, instance_path, kind, instance) =
+        into_validation_error_args(py, error, mask)?;
+    Py::new(
+        py,
+        ValidationError {
+            message,
+            verbose_message,
+            schema_path,
+            instance_path,
+            kind: Py::new(py, kind)?,
+            instance,
+        },
+    )
+}
+fn into_context(
+    py: Python<'_>,
+    context: Vec<Vec<jsonschema::ValidationError<'_>>>,
+    mask: Option<&str>,
+) -> PyResult<Py<PyList>> {
+    let branches = context
+        .into_iter()
+        .map(|errors| {
+            let errors = errors
+                .into_iter()
+                .map(|error| into_validation_error(py, error, mask))
+                .collect::<PyResult<Vec<_>>>()?;
+            PyList::new(py, errors)
+        })
+        .collect::<PyResult<Vec<_>>>()?;
+    Ok(PyList::new(py, branches)?.unbind())
+}
 fn into_py_err(
     py: Python<'_>,
     error: jsonschema::ValidationError<'_>,
@@ -143,6 +143,12 @@ def test_paths():
     ["schema", "instance", "kind", "attrs"],
     [
         ({"maxItems": 1}, [1, 2], ValidationErrorKind.MaxItems, {"limit": 1}),
+        (
+            {"properties": {"timeout_ms": {}}, "additionalProperties": False},
+            {"timeoutms": 1},
+            ValidationErrorKind.AdditionalProperties,
+            {"unexpected": ["timeoutms"], "suggestions": {"timeoutms": "timeout_ms"}},
+        ),
         ({"anyOf": [{"type": "string"}, {"type": "number"}]}, True, ValidationErrorKind.AnyOf, {}),
         ({"const": "test"}, "wrong", ValidationErrorKind.Constant, {"expected_value": "test"}),
         ({"contains": {"type": "string"}}, [1, 2, 3], ValidationErrorKind.Contains, {}),
@@ -29,6 +29,12 @@ impl AnchorKey {
     pub(crate) fn new(uri: Arc<Uri<String>>, name: AnchorName) -> Self {
         Self { uri, name }
     }
+    pub(crate) fn uri(&self) -> &Uri<String> {
+        &self.uri
+    }
+    pub(crate) fn name(&self) -> &str {
+        self.name.as_str()
+    }
 }
 
 #[derive(Copy, Clone, Hash, PartialEq, Eq)]
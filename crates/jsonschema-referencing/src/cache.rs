@@ -92,6 +92,41 @@ impl SharedUriCache {
         Ok(new)
     }
 
+    pub(crate) fn resolve_many(
+        &self,
+        base: &Uri<&str>,
+        uris: &[&str],
+    ) -> Result<Vec<Arc<Uri<String>>>, Error> {
+        // Equivalent to hashing `(base, uri)` tuples, without hashing `base` for every URI
+        let mut base_hasher = AHasher::default();
+        base.as_str().hash(&mut base_hasher);
+        let hashes: Vec<u64> = uris
+            .iter()
+            .map(|uri| {
+                let mut hasher = base_hasher.clone();
+                uri.hash(&mut hasher);
+                hasher.finish()
+            })
+            .collect();
+
+        let mut resolved: Vec<Option<Arc<Uri<String>>>> = {
+            let cache = self.cache.read();
+            hashes.iter().map(|hash| cache.get(hash).cloned()).collect()
+        };
+        let mut new = Vec::new();
+        for ((slot, uri), hash) in resolved.iter_mut().zip(uris).zip(&hashes) {
+            if slot.is_none() {
+                let uri = Arc::new(uri::resolve_against(base, uri)?);
+                new.push((*hash, Arc::clone(&uri)));
+                *slot = Some(uri);
+            }
+        }
+        if !new.is_empty() {
+            self.cache.write().extend(new);
+        }
+        Ok(resolved.into_iter().flatten().collect())
+    }
+
     pub(crate) fn into_local(self) -> UriCache {
         UriCache {
             cache: self.cache.into_inner(),
@@ -0,0 +1,182 @@
+//! Retrieved documents shared between registries.
+use std::{
+    collections::VecDeque,
+    fmt,
+    sync::{Arc, Weak},
+};
+
+use ahash::AHashMap;
+use fluent_uri::Uri;
+use parking_lot::Mutex;
+use serde_json::Value;
+
+use crate::Retrieved;
+
+/// A cache of retrieved documents that registries built with it share instead of retrieving
+/// them again.
+///
+/// Registries, and validators built on top of them, hold strong references to the documents they
+/// use. The cache itself only keeps the `capacity` most recently used documents alive, while other
+/// documents are dropped as soon as no registry uses them anymore. This bounds memory usage in
+/// long-running processes that build many transient registries referencing the same documents.
+///
+/// Documents with identical contents are stored once, even if retrieved from different URIs, so
+/// the registries using any of these URIs share the same document and the resources within it.
+///
+/// Clones share the same underlying cache.
+///
+/// ```rust
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use referencing::{DocumentCache, Registry, Resource};
+/// use serde_json::json;
+///
+/// # struct ExampleRetriever;
+/// # impl referencing::Retrieve for ExampleRetriever {
+/// #     fn retrieve(
+/// #         &self,
+/// #         _: &referencing::Uri<String>,
+/// #     ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+/// #         Ok(json!({"type": "string"}))
+/// #     }
+/// # }
+/// let cache = DocumentCache::new(16);
+/// // Registries built with the same cache retrieve `http://example.com/remote` only once
+/// let registry = Registry::options()
+///     .retriever(ExampleRetriever)
+///     .document_cache(cache.clone())
+///     .build([(
+///         "http://example.com/root",
+///         Resource::from_contents(json!({"$ref": "http://example.com/remote"}))?,
+///     )])?;
+/// assert!(cache.contains(&referencing::uri::from_str("http://example.com/remote")?));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct DocumentCache {
+    inner: Arc<Mutex<CacheState>>,
+}
+
+struct CacheState {
+    capacity: usize,
+    /// Documents along with the number of bytes read to retrieve them, if known, and their digest.
+    documents: AHashMap<Uri<String>, (Weak<Value>, Option<usize>, u64)>,
+    /// Documents by their digest, so identical ones retrieved from other URIs are stored once.
+    contents: AHashMap<u64, Vec<Weak<Value>>>,
+    /// Recently used documents, from the least to the most recent one.
+    recent: VecDeque<(Uri<String>, Arc<Value>)>,
+}
+
+impl DocumentCache {
+    /// Create a cache that keeps up to `capacity` unused documents alive.
+    #[must_use]
+    pub fn new(capacity: usize) -> DocumentCache {
+        DocumentCache {
+            inner: Arc::new(Mutex::new(CacheState {
+                capacity,
+                documents: AHashMap::new(),
+                contents: AHashMap::new(),
+                recent: VecDeque::new(),
+            })),
+        }
+    }
+    /// Number of cached documents that are still alive.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.inner
+            .lock()
+            .documents
+            .values()
+            .filter(|(document, ..)| document.strong_count() > 0)
+            .count()
+    }
+    /// Whether there are no cached documents alive.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// Whether a document retrieved from `uri` is still alive.
+    #[must_use]
+    pub fn contains(&self, uri: &Uri<String>) -> bool {
+        self.inner
+            .lock()
+            .documents
+            .get(uri)
+            .is_some_and(|(document, ..)| document.strong_count() > 0)
+    }
+    /// Drop all documents that are not used by any registry.
+    pub fn clear(&self) {
+        let mut state = self.inner.lock();
+        state.recent.clear();
+        state.prune();
+    }
+    pub(crate) fn get(&self, uri: &Uri<String>) -> Option<(Arc<Value>, Option<usize>, u64)> {
+        let mut state = self.inner.lock();
+        let (document, size, digest) = state.documents.get(uri)?;
+        let (document, size, digest) = (document.upgrade()?, *size, *digest);
+        state.touch(uri, &document);
+        Some((document, size, digest))
+    }
+    /// Store a document retrieved from `uri`, or reuse a live document with the same contents.
+    pub(crate) fn insert(&self, uri: &Uri<String>, retrieved: Retrieved) -> Arc<Value> {
+        let Retrieved {
+            document,
+            size,
+            digest,
+        } = retrieved;
+        let mut state = self.inner.lock();
+        state.prune();
+        let candidates = state.contents.entry(digest).or_default();
+        // Different contents may have the same digest
+        let document = if let Some(existing) = candidates
+            .iter()
+            .filter_map(Weak::upgrade)
+            .find(|existing| **existing == document)
+        {
+            existing
+        } else {
+            let document = Arc::new(document);
+            candidates.push(Arc::downgrade(&document));
+            document
+        };
+        state
+            .documents
+            .insert(uri.clone(), (Arc::downgrade(&document), size, digest));
+        state.touch(uri, &document);
+        document
+    }
+}
+
+impl CacheState {
+    /// Forget documents that are not used anymore.
+    fn prune(&mut self) {
+        self.documents
+            .retain(|_, (document, ..)| document.strong_count() > 0);
+        self.contents.retain(|_, candidates| {
+            candidates.retain(|document| document.strong_count() > 0);
+            !candidates.is_empty()
+        });
+    }
+    /// Mark a document as the most recently used one.
+    fn touch(&mut self, uri: &Uri<String>, document: &Arc<Value>) {
+        if self.capacity == 0 {
+            return;
+        }
+        if let Some(position) = self.recent.iter().position(|(recent, _)| recent == uri) {
+            self.recent.remove(position);
+        } else if self.recent.len() == self.capacity {
+            self.recent.pop_front();
+        }
+        self.recent.push_back((uri.clone(), Arc::clone(document)));
+    }
+}
+
+impl fmt::Debug for DocumentCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.inner.lock();
+        f.debug_struct("DocumentCache")
+            .field("capacity", &state.capacity)
+            .field("documents", &state.documents.len())
+            .finish()
+    }
+}
@@ -1,6 +1,7 @@
 use core::fmt;
 use std::{num::ParseIntError, str::Utf8Error};
 
+use crate::Draft;
 use fluent_uri::{
     error::{ParseError, ResolveError},
     Uri,
@@ -32,6 +33,53 @@ pub enum Error {
     InvalidUri(UriError),
     /// An unknown JSON Schema specification was encountered.
     UnknownSpecification { specification: String },
+    /// A known JSON Schema specification was encountered, but support for it is disabled.
+    DisabledSpecification { draft: Draft },
+    /// Retrieving a resource would exceed one of the registry limits.
+    LimitExceeded {
+        uri: String,
+        kind: LimitKind,
+        limit: usize,
+    },
+    /// A resource declares an identifier violating the specification, found with
+    /// [`RegistryOptions::strict_identifiers`](crate::RegistryOptions::strict_identifiers).
+    InvalidIdentifier {
+        /// The document containing the identifier.
+        uri: String,
+        /// JSON Pointer to the subschema declaring the identifier within the document.
+        location: String,
+        keyword: String,
+        value: String,
+        issue: IdentifierIssue,
+    },
+}
+
+/// What is wrong with an identifier rejected by strict identifier checks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum IdentifierIssue {
+    /// The value is not a URI reference.
+    InvalidUri,
+    /// The URI differs from its normalized form, e.g. by an uppercase scheme or dot segments.
+    NotNormalized { normalized: String },
+    /// `$id` contains a fragment that is not allowed by the draft.
+    Fragment,
+    /// An anchor name contains invalid characters.
+    InvalidAnchor,
+}
+
+/// The quantity guarded by a registry limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LimitKind {
+    /// Number of retrieved documents.
+    Documents,
+    /// Total size of retrieved documents, in bytes read by the retriever.
+    ///
+    /// Documents that are not read as text, see [`Retrieved`](crate::Retrieved), count with the
+    /// size of their compact JSON serialization.
+    TotalBytes,
+    /// Length of the chain of references leading from an input resource to a retrieved document.
+    ReferenceDepth,
 }
 
 impl Error {
@@ -73,6 +121,34 @@ impl Error {
         }
     }
 
+    pub(crate) fn disabled_specification(draft: Draft) -> Error {
+        Error::DisabledSpecification { draft }
+    }
+
+    pub(crate) fn limit_exceeded(uri: impl Into<String>, kind: LimitKind, limit: usize) -> Error {
+        Error::LimitExceeded {
+            uri: uri.into(),
+            kind,
+            limit,
+        }
+    }
+
+    pub(crate) fn invalid_identifier(
+        uri: impl Into<String>,
+        location: impl Into<String>,
+        keyword: impl Into<String>,
+        value: impl Into<String>,
+        issue: IdentifierIssue,
+    ) -> Error {
+        Error::InvalidIdentifier {
+            uri: uri.into(),
+            location: location.into(),
+            keyword: keyword.into(),
+            value: value.into(),
+            issue,
+        }
+    }
+
     pub(crate) fn unretrievable(
         uri: impl Into<String>,
         source: Box<dyn std::error::Error + Send + Sync>,
@@ -137,6 +213,40 @@ impl fmt::Display for Error {
             Error::UnknownSpecification { specification } => {
                 f.write_fmt(format_args!("Unknown specification: {specification}"))
             }
+            Error::DisabledSpecification { draft } => f.write_fmt(format_args!(
+                "Support for {draft:?} is disabled, enable the `{}` feature",
+                draft.feature()
+            )),
+            Error::LimitExceeded { uri, kind, limit } => match kind {
+                LimitKind::Documents => f.write_fmt(format_args!(
+                    "Retrieving '{uri}' exceeds the limit of {limit} retrieved documents"
+                )),
+                LimitKind::TotalBytes => f.write_fmt(format_args!(
+                    "Retrieving '{uri}' exceeds the limit of {limit} bytes in retrieved documents"
+                )),
+                LimitKind::ReferenceDepth => f.write_fmt(format_args!(
+                    "Retrieving '{uri}' exceeds the reference depth limit of {limit}"
+                )),
+            },
+            Error::InvalidIdentifier {
+                uri,
+                location,
+                keyword,
+                value,
+                issue,
+            } => {
+                f.write_fmt(format_args!(
+                    "'{keyword}' value '{value}' at '{location}' in '{uri}' "
+                ))?;
+                match issue {
+                    IdentifierIssue::InvalidUri => f.write_str("is not a valid URI reference"),
+                    IdentifierIssue::NotNormalized { normalized } => f.write_fmt(format_args!(
+                        "is not normalized, expected '{normalized}'"
+                    )),
+                    IdentifierIssue::Fragment => f.write_str("contains a fragment"),
+                    IdentifierIssue::InvalidAnchor => f.write_str("is not a valid anchor name"),
+                }
+            }
         }
     }
 }
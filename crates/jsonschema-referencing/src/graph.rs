@@ -0,0 +1,80 @@
+//! Export of resources and the references between them.
+use fluent_uri::Uri;
+use serde_json::Value;
+
+use crate::{uri, Draft, Error};
+
+/// Resources of a [`Registry`](crate::Registry) and the links between them.
+///
+/// Created by [`Registry::reference_graph`](crate::Registry::reference_graph).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReferenceGraph {
+    /// Canonical URIs of all resources, sorted.
+    pub nodes: Vec<String>,
+    /// Links between resources, sorted by source and target.
+    pub edges: Vec<ReferenceEdge>,
+}
+
+/// A link from a resource to a location in the same or another resource.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ReferenceEdge {
+    /// Canonical URI of the resource containing the link.
+    pub source: String,
+    /// Resolved URI of the linked location, including the fragment if any.
+    ///
+    /// Targets are not guaranteed to be nodes of the graph, e.g. `$schema` pointing to a
+    /// meta-schema that was never loaded into the registry.
+    pub target: String,
+    /// What kind of link this is.
+    pub kind: ReferenceKind,
+}
+
+/// The keyword that establishes a [`ReferenceEdge`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ReferenceKind {
+    /// `$ref`.
+    Ref,
+    /// `$dynamicRef` or `$recursiveRef`.
+    DynamicRef,
+    /// `$schema`.
+    Schema,
+    /// `$anchor`, `$dynamicAnchor`, or a plain-name fragment in `$id`. The edge links the
+    /// resource to the location named by the anchor.
+    Anchor,
+}
+
+const REFERENCE_KEYWORDS: [(&str, ReferenceKind); 4] = [
+    ("$ref", ReferenceKind::Ref),
+    ("$dynamicRef", ReferenceKind::DynamicRef),
+    ("$recursiveRef", ReferenceKind::DynamicRef),
+    ("$schema", ReferenceKind::Schema),
+];
+
+/// Collect links from a resource, without descending into embedded resources.
+pub(crate) fn collect_edges(
+    base: &Uri<String>,
+    contents: &Value,
+    draft: Draft,
+    edges: &mut Vec<ReferenceEdge>,
+) -> Result<(), Error> {
+    if let Some(object) = contents.as_object() {
+        for (keyword, kind) in REFERENCE_KEYWORDS {
+            if let Some(reference) = object.get(keyword).and_then(Value::as_str) {
+                edges.push(ReferenceEdge {
+                    source: base.as_str().to_string(),
+                    target: uri::resolve_against(&base.borrow(), reference)?
+                        .as_str()
+                        .to_string(),
+                    kind,
+                });
+            }
+        }
+    }
+    for subresource in draft.subresources_of(contents) {
+        // Embedded resources are separate nodes
+        if draft.id_of(subresource).is_none() {
+            collect_edges(base, subresource, draft, edges)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,162 @@
+//! A registry shared between threads that grows over time.
+use std::sync::Arc;
+
+use parking_lot::{Mutex, RwLock};
+
+use crate::{DefaultRetriever, Draft, Error, Registry, Resource, Retrieve};
+
+/// A shared [`Registry`] that can be extended while other threads use it.
+///
+/// Readers take snapshots via [`RegistryHandle::load`], which are never modified. Additions build
+/// a new registry from the current snapshot and atomically replace it, so readers only wait for
+/// the swap itself and never for resources being processed. Concurrent additions are applied one
+/// after another, and none of them is lost.
+///
+/// Clones share the same underlying registry.
+///
+/// ```rust
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use referencing::{Draft, Registry, RegistryHandle};
+/// use serde_json::json;
+///
+/// let handle = RegistryHandle::new(Registry::try_new(
+///     "urn:a",
+///     Draft::Draft202012.create_resource(json!({"type": "string"})),
+/// )?);
+/// let before = handle.load();
+/// handle.add_resource("urn:b", Draft::Draft202012.create_resource(json!({"type": "integer"})))?;
+///
+/// // Existing snapshots are unaffected
+/// assert!(before.try_resolver("urn:b")?.lookup("").is_err());
+/// assert!(handle.load().try_resolver("urn:b")?.lookup("").is_ok());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct RegistryHandle {
+    inner: Arc<HandleState>,
+}
+
+#[derive(Debug)]
+struct HandleState {
+    current: RwLock<Arc<Registry>>,
+    /// Serializes additions, so none of them builds on an outdated snapshot.
+    writer: Mutex<()>,
+}
+
+impl RegistryHandle {
+    /// Create a handle sharing `registry`.
+    #[must_use]
+    pub fn new(registry: Registry) -> RegistryHandle {
+        RegistryHandle {
+            inner: Arc::new(HandleState {
+                current: RwLock::new(Arc::new(registry)),
+                writer: Mutex::new(()),
+            }),
+        }
+    }
+    /// The current registry.
+    #[must_use]
+    pub fn load(&self) -> Arc<Registry> {
+        Arc::clone(&self.inner.current.read())
+    }
+    /// Add a resource and make the resulting registry current.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the URI is invalid or if there's an issue processing the resource. The
+    /// current registry is left unchanged in this case.
+    pub fn add_resource(
+        &self,
+        uri: impl AsRef<str>,
+        resource: Resource,
+    ) -> Result<Arc<Registry>, Error> {
+        let draft = resource.draft();
+        self.add_resources([(uri, resource)], &DefaultRetriever, draft)
+    }
+    /// Add resources, retrieving the external resources they reference with `retriever`, and
+    /// make the resulting registry current.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any URI is invalid or if there's an issue processing the resources.
+    /// The current registry is left unchanged in this case.
+    pub fn add_resources(
+        &self,
+        pairs: impl IntoIterator<Item = (impl AsRef<str>, Resource)>,
+        retriever: &dyn Retrieve,
+        draft: Draft,
+    ) -> Result<Arc<Registry>, Error> {
+        let _writer = self.inner.writer.lock();
+        let registry = Registry::clone(&self.load());
+        let registry =
+            Arc::new(registry.try_with_resources_and_retriever(pairs, retriever, draft)?);
+        *self.inner.current.write() = Arc::clone(&registry);
+        Ok(registry)
+    }
+}
+
+impl From<Registry> for RegistryHandle {
+    fn from(registry: Registry) -> RegistryHandle {
+        RegistryHandle::new(registry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use serde_json::json;
+
+    use super::RegistryHandle;
+    use crate::{Draft, Registry};
+
+    #[test]
+    fn concurrent_additions() {
+        let handle = RegistryHandle::new(
+            Registry::try_new("urn:root", Draft::Draft202012.create_resource(json!({})))
+                .expect("Invalid resources"),
+        );
+        thread::scope(|scope| {
+            for thread in 0..4 {
+                let handle = handle.clone();
+                scope.spawn(move || {
+                    for idx in 0..25 {
+                        let uri = format!("urn:{thread}-{idx}");
+                        handle
+                            .add_resource(&uri, Draft::Draft202012.create_resource(json!({})))
+                            .expect("Invalid resource");
+                        let registry = handle.load();
+                        let resolver = registry.try_resolver("urn:root").expect("Invalid URI");
+                        resolver.lookup(&uri).expect("Added resource is visible");
+                    }
+                });
+            }
+        });
+        let registry = handle.load();
+        let resolver = registry.try_resolver("urn:root").expect("Invalid URI");
+        for thread in 0..4 {
+            for idx in 0..25 {
+                resolver
+                    .lookup(&format!("urn:{thread}-{idx}"))
+                    .expect("No addition is lost");
+            }
+        }
+    }
+
+    #[test]
+    fn failed_addition() {
+        let handle = RegistryHandle::from(
+            Registry::try_new("urn:root", Draft::Draft202012.create_resource(json!({})))
+                .expect("Invalid resources"),
+        );
+        let before = handle.load();
+        handle
+            .add_resource(
+                "http://example.com/broken",
+                Draft::Draft202012.create_resource(json!({"$ref": "http://example.com/missing"})),
+            )
+            .expect_err("Retrieval should fail");
+        assert!(std::sync::Arc::ptr_eq(&before, &handle.load()));
+    }
+}
@@ -0,0 +1,230 @@
+//! Strict checks of resource identifiers, see [`RegistryOptions::strict_identifiers`].
+//!
+//! [`RegistryOptions::strict_identifiers`]: crate::RegistryOptions::strict_identifiers
+use fluent_uri::{encoding::EStr, Uri, UriRef};
+use serde_json::{Map, Value};
+
+use crate::{error::IdentifierIssue, resource::InnerResourcePtr, Draft, Error};
+
+/// Check `$id`, `$anchor` and `$dynamicAnchor` values of a document and all its subresources.
+pub(crate) fn check(uri: &Uri<String>, resource: &InnerResourcePtr) -> Result<(), Error> {
+    check_at(
+        uri,
+        resource.contents(),
+        resource.draft(),
+        &mut String::new(),
+    )
+}
+
+fn check_at(
+    uri: &Uri<String>,
+    contents: &Value,
+    draft: Draft,
+    location: &mut String,
+) -> Result<(), Error> {
+    let Some(object) = contents.as_object() else {
+        return Ok(());
+    };
+    let invalid = |keyword: &str, value: &str, issue| {
+        Err(Error::invalid_identifier(
+            uri.as_str(),
+            location.as_str(),
+            keyword,
+            value,
+            issue,
+        ))
+    };
+    let id_keyword = if draft == Draft::Draft4 { "id" } else { "$id" };
+    // Before 2019-09, identifiers next to `$ref` are ignored
+    let ignored = draft <= Draft::Draft7 && object.contains_key("$ref");
+    if let (Some(id), false) = (object.get(id_keyword).and_then(Value::as_str), ignored) {
+        if let Some(issue) = check_id(id, draft) {
+            return invalid(id_keyword, id, issue);
+        }
+    }
+    let anchor_keywords: &[&str] = match draft {
+        Draft::Draft4 | Draft::Draft6 | Draft::Draft7 => &[],
+        Draft::Draft201909 => &["$anchor"],
+        Draft::Draft202012 => &["$anchor", "$dynamicAnchor"],
+    };
+    for keyword in anchor_keywords {
+        if let Some(anchor) = object.get(*keyword).and_then(Value::as_str) {
+            if !is_anchor_name(anchor, draft) {
+                return invalid(keyword, anchor, IdentifierIssue::InvalidAnchor);
+            }
+        }
+    }
+    for subresource in draft.subresources_of(contents) {
+        let length = location.len();
+        push_location(object, subresource, location);
+        check_at(uri, subresource, draft, location)?;
+        location.truncate(length);
+    }
+    Ok(())
+}
+
+fn check_id(id: &str, draft: Draft) -> Option<IdentifierIssue> {
+    let Ok(parsed) = UriRef::parse(id) else {
+        return Some(IdentifierIssue::InvalidUri);
+    };
+    if let Some(fragment) = parsed.fragment().map(EStr::as_str) {
+        if draft >= Draft::Draft201909 {
+            // Only an empty fragment is allowed
+            if !fragment.is_empty() {
+                return Some(IdentifierIssue::Fragment);
+            }
+        } else if fragment.starts_with('/') {
+            return Some(IdentifierIssue::Fragment);
+        } else if !fragment.is_empty() && !is_anchor_name(fragment, draft) {
+            // Earlier drafts declare plain name anchors as fragments
+            return Some(IdentifierIssue::InvalidAnchor);
+        }
+    }
+    let normalized = parsed.normalize();
+    if normalized.as_str() == id {
+        None
+    } else {
+        Some(IdentifierIssue::NotNormalized {
+            normalized: normalized.into_string(),
+        })
+    }
+}
+
+/// Whether `name` is a valid plain name anchor, i.e. `[A-Za-z_][-A-Za-z0-9._]*` in 2020-12 and
+/// `[A-Za-z][-A-Za-z0-9.:_]*` in earlier drafts.
+fn is_anchor_name(name: &str, draft: Draft) -> bool {
+    let mut chars = name.chars();
+    let Some(first) = chars.next() else {
+        return false;
+    };
+    if draft == Draft::Draft202012 {
+        (first.is_ascii_alphabetic() || first == '_')
+            && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '.' | '_'))
+    } else {
+        first.is_ascii_alphabetic()
+            && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '.' | ':' | '_'))
+    }
+}
+
+/// Append the JSON Pointer from `parent` to `child`, which is one of its subresources nested at
+/// most two levels deep, e.g. in `items`, `allOf/0` or `properties/name`.
+fn push_location(parent: &Map<String, Value>, child: &Value, location: &mut String) {
+    for (keyword, value) in parent {
+        if std::ptr::eq(value, child) {
+            push_segment(location, keyword);
+            return;
+        }
+        match value {
+            Value::Array(items) => {
+                if let Some(index) = items.iter().position(|item| std::ptr::eq(item, child)) {
+                    push_segment(location, keyword);
+                    push_segment(location, &index.to_string());
+                    return;
+                }
+            }
+            Value::Object(members) => {
+                if let Some((name, _)) = members
+                    .iter()
+                    .find(|(_, member)| std::ptr::eq(*member, child))
+                {
+                    push_segment(location, keyword);
+                    push_segment(location, name);
+                    return;
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn push_segment(location: &mut String, segment: &str) {
+    location.push('/');
+    location.push_str(&segment.replace('~', "~0").replace('/', "~1"));
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    use crate::{Draft, Error, RegistryOptions, Resource};
+
+    fn build(schema: Value) -> Result<(), Error> {
+        RegistryOptions::new()
+            .strict_identifiers(true)
+            .build([(
+                "http://example.com/root.json",
+                Resource::from_contents(schema)?,
+            )])
+            .map(|_| ())
+    }
+
+    #[test_case(&json!({"$schema": "https://json-schema.org/draft/2020-12/schema", "$id": "http://example.com/a.json", "$defs": {"b": {"$id": "b.json#", "$anchor": "_b", "$dynamicAnchor": "node"}}}))]
+    #[test_case(&json!({"$schema": "https://json-schema.org/draft/2019-09/schema", "$anchor": "a:b"}))]
+    #[test_case(&json!({"$schema": "http://json-schema.org/draft-07/schema#", "definitions": {"a": {"$id": "#foo"}, "b": {"$id": "#/ignored", "$ref": "#"}}}))]
+    #[test_case(&json!({"$schema": "http://json-schema.org/draft-04/schema#", "properties": {"a": {"id": "other.json#bar"}}}))]
+    fn valid(schema: &Value) {
+        build(schema.clone()).expect("Valid identifiers");
+    }
+
+    #[test_case(
+        &json!({"$defs": {"a": {"properties": {"x/y": {"$id": "http://example.com/b.json#foo"}}}}}),
+        "'$id' value 'http://example.com/b.json#foo' at '/$defs/a/properties/x~1y' in 'http://example.com/root.json' contains a fragment"
+    )]
+    #[test_case(
+        &json!({"allOf": [{}, {"$id": "HTTP://Example.com/a/./b.json"}]}),
+        "'$id' value 'HTTP://Example.com/a/./b.json' at '/allOf/1' in 'http://example.com/root.json' is not normalized, expected 'http://example.com/a/b.json'"
+    )]
+    #[test_case(
+        &json!({"$id": "http://exa mple.com"}),
+        "'$id' value 'http://exa mple.com' at '' in 'http://example.com/root.json' is not a valid URI reference"
+    )]
+    #[test_case(
+        &json!({"items": {"$anchor": "1st"}}),
+        "'$anchor' value '1st' at '/items' in 'http://example.com/root.json' is not a valid anchor name"
+    )]
+    #[test_case(
+        &json!({"$schema": "http://json-schema.org/draft-07/schema#", "items": [{"$id": "#/items/0"}]}),
+        "'$id' value '#/items/0' at '/items/0' in 'http://example.com/root.json' contains a fragment"
+    )]
+    #[test_case(
+        &json!({"$schema": "http://json-schema.org/draft-06/schema#", "$id": "#a b"}),
+        "'$id' value '#a b' at '' in 'http://example.com/root.json' is not a valid URI reference"
+    )]
+    fn invalid(schema: &Value, expected: &str) {
+        let error = build(schema.clone()).expect_err("Invalid identifier");
+        assert!(matches!(error, Error::InvalidIdentifier { .. }));
+        assert_eq!(error.to_string(), expected);
+    }
+
+    #[test]
+    fn strict_for_added_resources() {
+        let registry = RegistryOptions::new()
+            .strict_identifiers(true)
+            .build([(
+                "http://example.com/root.json",
+                Resource::from_contents(json!({})).expect("Valid resource"),
+            )])
+            .expect("Valid identifiers");
+        let error = registry
+            .try_with_resource(
+                "http://example.com/other.json",
+                Draft::Draft202012.create_resource(json!({"$anchor": "1st"})),
+            )
+            .expect_err("Invalid identifier");
+        assert!(matches!(error, Error::InvalidIdentifier { .. }));
+    }
+
+    #[test]
+    fn lenient_by_default() {
+        let schema =
+            json!({"$defs": {"a": {"$id": "HTTP://Example.com/a.json", "$anchor": "1st"}}});
+        RegistryOptions::new()
+            .draft(Draft::Draft202012)
+            .build([(
+                "http://example.com/root.json",
+                Resource::from_contents(schema).expect("Valid resource"),
+            )])
+            .expect("Identifiers are not checked");
+    }
+}
@@ -3,8 +3,12 @@
 //! An implementation-agnostic JSON reference resolution library for Rust.
 mod anchors;
 mod cache;
+mod documents;
 mod error;
+mod graph;
+mod handle;
 mod hasher;
+mod identifiers;
 mod list;
 pub mod meta;
 mod registry;
@@ -17,13 +21,16 @@ pub mod uri;
 mod vocabularies;
 
 pub(crate) use anchors::Anchor;
-pub use error::{Error, UriError};
+pub use documents::DocumentCache;
+pub use error::{Error, IdentifierIssue, LimitKind, UriError};
 pub use fluent_uri::{Iri, IriRef, Uri, UriRef};
+pub use graph::{ReferenceEdge, ReferenceGraph, ReferenceKind};
+pub use handle::RegistryHandle;
 pub use list::List;
 pub use registry::{parse_index, pointer, Registry, RegistryOptions, SPECIFICATIONS};
-pub use resolver::{Resolved, Resolver};
+pub use resolver::{Breadcrumb, Breadcrumbs, Resolved, Resolver};
 pub use resource::{unescape_segment, Resource, ResourceRef};
-pub use retriever::{DefaultRetriever, Retrieve};
+pub use retriever::{DefaultRetriever, DocumentTooLarge, Retrieve, Retrieved, UriRewriter};
 pub(crate) use segments::Segments;
 pub use specification::Draft;
 pub use vocabularies::{Vocabulary, VocabularySet};
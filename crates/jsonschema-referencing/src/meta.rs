@@ -1,16 +1,18 @@
 //! Built-in JSON Schema meta-schemas.
 //!
 //! This module provides access to the official JSON Schema meta-schemas for different draft versions.
-use std::sync::Arc;
-
 use once_cell::sync::Lazy;
 use serde_json::Value;
 
+use crate::{Draft, Error};
+
+// Unused if every draft is disabled
+#[allow(unused_macros)]
 macro_rules! schema {
     ($vis:vis $name:ident, $path:expr) => {
-        $vis static $name: once_cell::sync::Lazy<Arc<serde_json::Value>> =
+        $vis static $name: once_cell::sync::Lazy<std::sync::Arc<serde_json::Value>> =
             once_cell::sync::Lazy::new(|| {
-                Arc::new(serde_json::from_slice(include_bytes!($path)).expect("Invalid schema"))
+                std::sync::Arc::new(serde_json::from_slice(include_bytes!($path)).expect("Invalid schema"))
             });
     };
     ($name:ident, $path:expr) => {
@@ -18,72 +20,97 @@ macro_rules! schema {
     };
 }
 
+#[cfg(feature = "draft4")]
 schema!(pub DRAFT4, "../metaschemas/draft4.json");
+#[cfg(feature = "draft6")]
 schema!(pub DRAFT6, "../metaschemas/draft6.json");
+#[cfg(feature = "draft7")]
 schema!(pub DRAFT7, "../metaschemas/draft7.json");
+#[cfg(feature = "draft201909")]
 schema!(pub DRAFT201909, "../metaschemas/draft2019-09/schema.json");
+#[cfg(feature = "draft201909")]
 schema!(
     pub DRAFT201909_APPLICATOR,
     "../metaschemas/draft2019-09/meta/applicator.json"
 );
+#[cfg(feature = "draft201909")]
 schema!(
     pub DRAFT201909_CONTENT,
     "../metaschemas/draft2019-09/meta/content.json"
 );
+#[cfg(feature = "draft201909")]
 schema!(
     pub DRAFT201909_CORE,
     "../metaschemas/draft2019-09/meta/core.json"
 );
+#[cfg(feature = "draft201909")]
 schema!(
     pub DRAFT201909_FORMAT,
     "../metaschemas/draft2019-09/meta/format.json"
 );
+#[cfg(feature = "draft201909")]
 schema!(
     pub DRAFT201909_META_DATA,
     "../metaschemas/draft2019-09/meta/meta-data.json"
 );
+#[cfg(feature = "draft201909")]
 schema!(
     pub DRAFT201909_VALIDATION,
     "../metaschemas/draft2019-09/meta/validation.json"
 );
+#[cfg(feature = "draft202012")]
 schema!(pub DRAFT202012, "../metaschemas/draft2020-12/schema.json");
+#[cfg(feature = "draft202012")]
 schema!(
     pub DRAFT202012_CORE,
     "../metaschemas/draft2020-12/meta/core.json"
 );
+#[cfg(feature = "draft202012")]
 schema!(
     pub DRAFT202012_APPLICATOR,
     "../metaschemas/draft2020-12/meta/applicator.json"
 );
+#[cfg(feature = "draft202012")]
 schema!(
     pub DRAFT202012_UNEVALUATED,
     "../metaschemas/draft2020-12/meta/unevaluated.json"
 );
+#[cfg(feature = "draft202012")]
 schema!(
     pub DRAFT202012_VALIDATION,
     "../metaschemas/draft2020-12/meta/validation.json"
 );
+#[cfg(feature = "draft202012")]
 schema!(
     pub DRAFT202012_META_DATA,
     "../metaschemas/draft2020-12/meta/meta-data.json"
 );
+#[cfg(feature = "draft202012")]
 schema!(
     pub DRAFT202012_FORMAT_ANNOTATION,
     "../metaschemas/draft2020-12/meta/format-annotation.json"
 );
+#[cfg(feature = "draft202012")]
 schema!(
     pub DRAFT202012_FORMAT_ASSERTION,
     "../metaschemas/draft2020-12/meta/format-assertion.json"
 );
+#[cfg(feature = "draft202012")]
 schema!(
     pub DRAFT202012_CONTENT,
     "../metaschemas/draft2020-12/meta/content.json"
 );
-pub(crate) static META_SCHEMAS: Lazy<[(&'static str, &'static Value); 18]> = Lazy::new(|| {
-    [
-        ("http://json-schema.org/draft-04/schema#", &*DRAFT4),
-        ("http://json-schema.org/draft-06/schema#", &*DRAFT6),
-        ("http://json-schema.org/draft-07/schema#", &*DRAFT7),
+pub(crate) static META_SCHEMAS: Lazy<Vec<(&'static str, &'static Value)>> = Lazy::new(|| {
+    #[allow(unused_mut)]
+    let mut schemas: Vec<(&'static str, &'static Value)> = Vec::with_capacity(18);
+    #[cfg(feature = "draft4")]
+    schemas.push(("http://json-schema.org/draft-04/schema#", &*DRAFT4));
+    #[cfg(feature = "draft6")]
+    schemas.push(("http://json-schema.org/draft-06/schema#", &*DRAFT6));
+    #[cfg(feature = "draft7")]
+    schemas.push(("http://json-schema.org/draft-07/schema#", &*DRAFT7));
+    #[cfg(feature = "draft201909")]
+    schemas.extend::<[(&str, &Value); 7]>([
         (
             "https://json-schema.org/draft/2019-09/schema",
             &*DRAFT201909,
@@ -112,6 +139,9 @@ pub(crate) static META_SCHEMAS: Lazy<[(&'static str, &'static Value); 18]> = Laz
             "https://json-schema.org/draft/2019-09/meta/validation",
             &*DRAFT201909_VALIDATION,
         ),
+    ]);
+    #[cfg(feature = "draft202012")]
+    schemas.extend::<[(&str, &Value); 8]>([
         (
             "https://json-schema.org/draft/2020-12/schema",
             &*DRAFT202012,
@@ -144,5 +174,93 @@ pub(crate) static META_SCHEMAS: Lazy<[(&'static str, &'static Value); 18]> = Laz
             "https://json-schema.org/draft/2020-12/meta/content",
             &*DRAFT202012_CONTENT,
         ),
-    ]
+    ]);
+    schemas
 });
+
+/// The top-level meta-schema of a draft.
+///
+/// ```rust
+/// use referencing::{meta, Draft};
+///
+/// let schema = meta::schema_for(Draft::Draft7)?;
+/// assert_eq!(schema["$id"], "http://json-schema.org/draft-07/schema#");
+/// # Ok::<(), referencing::Error>(())
+/// ```
+///
+/// # Errors
+///
+/// If support for `draft` is disabled via cargo features, see [`Draft::is_enabled`].
+pub fn schema_for(draft: Draft) -> Result<&'static Value, Error> {
+    match draft {
+        #[cfg(feature = "draft4")]
+        Draft::Draft4 => Ok(&DRAFT4),
+        #[cfg(feature = "draft6")]
+        Draft::Draft6 => Ok(&DRAFT6),
+        #[cfg(feature = "draft7")]
+        Draft::Draft7 => Ok(&DRAFT7),
+        #[cfg(feature = "draft201909")]
+        Draft::Draft201909 => Ok(&DRAFT201909),
+        #[cfg(feature = "draft202012")]
+        Draft::Draft202012 => Ok(&DRAFT202012),
+        #[allow(unreachable_patterns)]
+        _ => Err(Error::disabled_specification(draft)),
+    }
+}
+
+/// A bundled meta-schema, including vocabulary meta-schemas, by its URI.
+///
+/// A trailing empty fragment is ignored, so both `http://json-schema.org/draft-07/schema` and
+/// `http://json-schema.org/draft-07/schema#` are found.
+///
+/// ```rust
+/// use referencing::meta;
+///
+/// let schema = meta::lookup("https://json-schema.org/draft/2020-12/meta/validation");
+/// assert!(schema.is_some());
+/// assert!(meta::lookup("https://example.com/schema").is_none());
+/// ```
+#[must_use]
+pub fn lookup(uri: &str) -> Option<&'static Value> {
+    let uri = uri.trim_end_matches('#');
+    META_SCHEMAS
+        .iter()
+        .find(|(known, _)| known.trim_end_matches('#') == uri)
+        .map(|(_, schema)| *schema)
+}
+
+/// All bundled meta-schemas with their URIs.
+pub fn all() -> impl Iterator<Item = (&'static str, &'static Value)> {
+    META_SCHEMAS.iter().copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+
+    use super::{all, lookup, schema_for};
+    use crate::Draft;
+
+    #[test_case(Draft::Draft4)]
+    #[test_case(Draft::Draft6)]
+    #[test_case(Draft::Draft7)]
+    #[test_case(Draft::Draft201909)]
+    #[test_case(Draft::Draft202012)]
+    fn schema_for_draft(draft: Draft) {
+        let schema = schema_for(draft).expect("Enabled draft");
+        let id = schema
+            .get("$id")
+            .or_else(|| schema.get("id"))
+            .and_then(|id| id.as_str())
+            .expect("Meta-schemas have an ID");
+        assert!(std::ptr::eq(lookup(id).expect("Bundled"), schema));
+        assert_eq!(Draft::default().detect(schema).expect("Known draft"), draft);
+    }
+
+    #[test]
+    fn all_can_be_looked_up() {
+        for (uri, schema) in all() {
+            assert!(std::ptr::eq(lookup(uri).expect("Bundled"), schema));
+        }
+    }
+}
@@ -1,6 +1,8 @@
 use std::{
     collections::{hash_map::Entry, HashSet, VecDeque},
+    fmt,
     hash::{Hash, Hasher},
+    io,
     pin::Pin,
     sync::Arc,
 };
@@ -13,20 +15,25 @@ use serde_json::Value;
 use crate::{
     anchors::{AnchorKey, AnchorKeyRef},
     cache::{SharedUriCache, UriCache},
+    graph::{self, ReferenceEdge, ReferenceGraph, ReferenceKind},
     hasher::BuildNoHashHasher,
+    identifiers,
     list::List,
     meta,
     resource::{unescape_segment, InnerResourcePtr, JsonSchemaResource},
     uri,
     vocabularies::{self, VocabularySet},
-    Anchor, DefaultRetriever, Draft, Error, Resolver, Resource, ResourceRef, Retrieve,
+    Anchor, DefaultRetriever, DocumentCache, DocumentTooLarge, Draft, Error, LimitKind, Resolver,
+    Resource, ResourceRef, Retrieve, Retrieved, UriRewriter,
 };
 
-/// An owned-or-refstatic wrapper for JSON `Value`.
+/// An owned, refstatic, or shared wrapper for JSON `Value`.
 #[derive(Debug)]
 pub(crate) enum ValueWrapper {
     Owned(Value),
     StaticRef(&'static Value),
+    /// A document shared via [`DocumentCache`].
+    Shared(Arc<Value>),
 }
 
 impl AsRef<Value> for ValueWrapper {
@@ -34,6 +41,7 @@ impl AsRef<Value> for ValueWrapper {
         match self {
             ValueWrapper::Owned(value) => value,
             ValueWrapper::StaticRef(value) => value,
+            ValueWrapper::Shared(value) => value,
         }
     }
 }
@@ -43,9 +51,9 @@ impl AsRef<Value> for ValueWrapper {
 type DocumentStore = AHashMap<Arc<Uri<String>>, Pin<Arc<ValueWrapper>>>;
 type ResourceMap = AHashMap<Arc<Uri<String>>, InnerResourcePtr>;
 
-/// Pre-loaded registry containing all JSON Schema meta-schemas and their vocabularies
+/// Pre-loaded registry containing the meta-schemas and vocabularies of all enabled drafts
 pub static SPECIFICATIONS: Lazy<Registry> = Lazy::new(|| {
-    let pairs = meta::META_SCHEMAS.into_iter().map(|(uri, schema)| {
+    let pairs = meta::META_SCHEMAS.iter().map(|&(uri, schema)| {
         (
             uri,
             ResourceRef::from_contents(schema).expect("Invalid resource"),
@@ -53,8 +61,8 @@ pub static SPECIFICATIONS: Lazy<Registry> = Lazy::new(|| {
     });
 
     // The capacity is known upfront
-    let mut documents = DocumentStore::with_capacity(18);
-    let mut resources = ResourceMap::with_capacity(18);
+    let mut documents = DocumentStore::with_capacity(meta::META_SCHEMAS.len());
+    let mut resources = ResourceMap::with_capacity(meta::META_SCHEMAS.len());
     let mut anchors = AHashMap::with_capacity(8);
     let mut resolution_cache = UriCache::with_capacity(35);
     process_meta_schemas(
@@ -70,6 +78,7 @@ pub static SPECIFICATIONS: Lazy<Registry> = Lazy::new(|| {
         resources,
         anchors,
         resolution_cache: resolution_cache.into_shared(),
+        policy: RetrievalPolicy::default(),
     }
 });
 
@@ -174,6 +183,8 @@ pub struct Registry {
     pub(crate) resources: ResourceMap,
     anchors: AHashMap<AnchorKey, Anchor>,
     resolution_cache: SharedUriCache,
+    /// Applied again when resources are added to the registry.
+    policy: RetrievalPolicy,
 }
 
 impl Clone for Registry {
@@ -183,6 +194,7 @@ impl Clone for Registry {
             resources: self.resources.clone(),
             anchors: self.anchors.clone(),
             resolution_cache: self.resolution_cache.clone(),
+            policy: self.policy.clone(),
         }
     }
 }
@@ -191,6 +203,7 @@ impl Clone for Registry {
 pub struct RegistryOptions<R> {
     retriever: R,
     draft: Draft,
+    policy: RetrievalPolicy,
 }
 
 impl<R> RegistryOptions<R> {
@@ -200,6 +213,101 @@ impl<R> RegistryOptions<R> {
         self.draft = draft;
         self
     }
+    /// Limit the documents retrieved while building the [`Registry`].
+    ///
+    /// * `max_documents` - The number of retrieved documents.
+    /// * `max_total_bytes` - The total size of retrieved documents, in bytes read by the retriever.
+    /// * `max_ref_depth` - The length of reference chains between retrieved documents, i.e.
+    ///   documents referenced by the input resources have depth 1, documents referenced by them
+    ///   have depth 2, etc.
+    ///
+    /// Input resources are not counted. Building fails with [`Error::LimitExceeded`] as soon as
+    /// any limit is exceeded. Retrievers overriding [`Retrieve::retrieve_bounded`] stop reading
+    /// a document as soon as its text exceeds the remaining size budget. There are no limits by
+    /// default.
+    ///
+    /// The limits also apply to documents retrieved when resources are added to the built
+    /// registry, e.g. via [`Registry::try_with_resources`], counting from zero each time.
+    #[must_use]
+    pub fn limits(
+        mut self,
+        max_documents: usize,
+        max_total_bytes: usize,
+        max_ref_depth: usize,
+    ) -> Self {
+        self.policy.limits = Limits {
+            documents: max_documents,
+            total_bytes: max_total_bytes,
+            ref_depth: max_ref_depth,
+        };
+        self
+    }
+    /// Share retrieved documents with other registries built with the same [`DocumentCache`].
+    ///
+    /// Documents found in the cache are not retrieved again, and newly retrieved documents are
+    /// added to it. They still count towards the [`limits`](RegistryOptions::limits).
+    #[must_use]
+    pub fn document_cache(mut self, cache: DocumentCache) -> Self {
+        self.policy.cache = Some(cache);
+        self
+    }
+    /// Reject resources declaring identifiers that violate the specification.
+    ///
+    /// When enabled, building fails with [`Error::InvalidIdentifier`], pointing at the offending
+    /// subschema, if a `$id` is not a normalized URI reference or contains a fragment not allowed
+    /// by its draft, or if an `$anchor` or `$dynamicAnchor` is not a valid anchor name. Without
+    /// it, such identifiers are accepted and may only fail later lookups. Disabled by default.
+    #[must_use]
+    pub fn strict_identifiers(mut self, strict: bool) -> Self {
+        self.policy.strict_identifiers = strict;
+        self
+    }
+    /// Rewrite URIs of referenced documents before retrieving them or looking them up in the
+    /// [`document_cache`](RegistryOptions::document_cache), e.g. to redirect a schema host to
+    /// another environment. See [`UriRewriter`].
+    #[must_use]
+    pub fn uri_rewriter(mut self, rewriter: impl UriRewriter + 'static) -> Self {
+        self.policy.rewriter = Some(Arc::new(rewriter));
+        self
+    }
+}
+
+/// How documents are retrieved and checked while processing resources.
+#[derive(Clone, Default)]
+struct RetrievalPolicy {
+    limits: Limits,
+    cache: Option<DocumentCache>,
+    strict_identifiers: bool,
+    rewriter: Option<Arc<dyn UriRewriter>>,
+}
+
+impl fmt::Debug for RetrievalPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetrievalPolicy")
+            .field("limits", &self.limits)
+            .field("cache", &self.cache.is_some())
+            .field("strict_identifiers", &self.strict_identifiers)
+            .field("rewriter", &self.rewriter.is_some())
+            .finish()
+    }
+}
+
+/// Bounds on the documents retrieved while processing resources.
+#[derive(Debug, Clone, Copy)]
+struct Limits {
+    documents: usize,
+    total_bytes: usize,
+    ref_depth: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            documents: usize::MAX,
+            total_bytes: usize::MAX,
+            ref_depth: usize::MAX,
+        }
+    }
 }
 
 impl RegistryOptions<Arc<dyn Retrieve>> {
@@ -209,6 +317,7 @@ impl RegistryOptions<Arc<dyn Retrieve>> {
         Self {
             retriever: Arc::new(DefaultRetriever),
             draft: Draft::default(),
+            policy: RetrievalPolicy::default(),
         }
     }
     /// Set a custom retriever for the [`Registry`].
@@ -227,6 +336,7 @@ impl RegistryOptions<Arc<dyn Retrieve>> {
         RegistryOptions {
             retriever: retriever.into_retriever(),
             draft: self.draft,
+            policy: self.policy,
         }
     }
     /// Create a [`Registry`] from multiple resources using these options.
@@ -236,11 +346,12 @@ impl RegistryOptions<Arc<dyn Retrieve>> {
     /// Returns an error if:
     /// - Any URI is invalid
     /// - Any referenced resources cannot be retrieved
+    /// - Any of the [`limits`](RegistryOptions::limits) is exceeded
     pub fn build(
         self,
         pairs: impl IntoIterator<Item = (impl AsRef<str>, Resource)>,
     ) -> Result<Registry, Error> {
-        Registry::try_from_resources_impl(pairs, &*self.retriever, self.draft)
+        Registry::try_from_resources_impl(pairs, &*self.retriever, self.draft, self.policy)
     }
 }
 
@@ -253,11 +364,13 @@ impl RegistryOptions<Arc<dyn crate::AsyncRetrieve>> {
     /// Returns an error if:
     /// - Any URI is invalid
     /// - Any referenced resources cannot be retrieved
+    /// - Any of the [`limits`](RegistryOptions::limits) is exceeded
     pub async fn build(
         self,
         pairs: impl IntoIterator<Item = (impl AsRef<str>, Resource)>,
     ) -> Result<Registry, Error> {
-        Registry::try_from_resources_async_impl(pairs, &*self.retriever, self.draft).await
+        Registry::try_from_resources_async_impl(pairs, &*self.retriever, self.draft, self.policy)
+            .await
     }
 }
 
@@ -333,7 +446,12 @@ impl Registry {
     pub fn try_from_resources(
         pairs: impl IntoIterator<Item = (impl AsRef<str>, Resource)>,
     ) -> Result<Self, Error> {
-        Self::try_from_resources_impl(pairs, &DefaultRetriever, Draft::default())
+        Self::try_from_resources_impl(
+            pairs,
+            &DefaultRetriever,
+            Draft::default(),
+            RetrievalPolicy::default(),
+        )
     }
     fn try_new_impl(
         uri: impl AsRef<str>,
@@ -341,12 +459,18 @@ impl Registry {
         retriever: &dyn Retrieve,
         draft: Draft,
     ) -> Result<Self, Error> {
-        Self::try_from_resources_impl([(uri, resource)], retriever, draft)
+        Self::try_from_resources_impl(
+            [(uri, resource)],
+            retriever,
+            draft,
+            RetrievalPolicy::default(),
+        )
     }
     fn try_from_resources_impl(
         pairs: impl IntoIterator<Item = (impl AsRef<str>, Resource)>,
         retriever: &dyn Retrieve,
         draft: Draft,
+        policy: RetrievalPolicy,
     ) -> Result<Self, Error> {
         let mut documents = AHashMap::new();
         let mut resources = ResourceMap::new();
@@ -360,12 +484,14 @@ impl Registry {
             &mut anchors,
             &mut resolution_cache,
             draft,
+            policy.clone(),
         )?;
         Ok(Registry {
             documents,
             resources,
             anchors,
             resolution_cache: resolution_cache.into_shared(),
+            policy,
         })
     }
     /// Create a new [`Registry`] from an iterator of (URI, Resource) pairs using an async retriever.
@@ -382,6 +508,7 @@ impl Registry {
         pairs: impl IntoIterator<Item = (impl AsRef<str>, Resource)>,
         retriever: &dyn crate::AsyncRetrieve,
         draft: Draft,
+        policy: RetrievalPolicy,
     ) -> Result<Self, Error> {
         let mut documents = AHashMap::new();
         let mut resources = ResourceMap::new();
@@ -396,6 +523,7 @@ impl Registry {
             &mut anchors,
             &mut resolution_cache,
             draft,
+            policy.clone(),
         )
         .await?;
 
@@ -404,6 +532,7 @@ impl Registry {
             resources,
             anchors,
             resolution_cache: resolution_cache.into_shared(),
+            policy,
         })
     }
     /// Create a new registry with a new resource.
@@ -421,6 +550,9 @@ impl Registry {
     }
     /// Create a new registry with new resources.
     ///
+    /// Documents are retrieved under the limits, document cache, identifier checks and URI
+    /// rewriter set in the [`RegistryOptions`] this registry was built with.
+    ///
     /// # Errors
     ///
     /// Returns an error if any URI is invalid or if there's an issue processing the resources.
@@ -433,6 +565,9 @@ impl Registry {
     }
     /// Create a new registry with new resources and using the given retriever.
     ///
+    /// Documents are retrieved under the limits, document cache, identifier checks and URI
+    /// rewriter set in the [`RegistryOptions`] this registry was built with.
+    ///
     /// # Errors
     ///
     /// Returns an error if any URI is invalid or if there's an issue processing the resources.
@@ -454,16 +589,21 @@ impl Registry {
             &mut anchors,
             &mut resolution_cache,
             draft,
+            self.policy.clone(),
         )?;
         Ok(Registry {
             documents,
             resources,
             anchors,
             resolution_cache: resolution_cache.into_shared(),
+            policy: self.policy,
         })
     }
     /// Create a new registry with new resources and using the given non-blocking retriever.
     ///
+    /// Documents are retrieved under the limits, document cache, identifier checks and URI
+    /// rewriter set in the [`RegistryOptions`] this registry was built with.
+    ///
     /// # Errors
     ///
     /// Returns an error if any URI is invalid or if there's an issue processing the resources.
@@ -486,6 +626,7 @@ impl Registry {
             &mut anchors,
             &mut resolution_cache,
             draft,
+            self.policy.clone(),
         )
         .await?;
         Ok(Registry {
@@ -493,6 +634,7 @@ impl Registry {
             resources,
             anchors,
             resolution_cache: resolution_cache.into_shared(),
+            policy: self.policy,
         })
     }
     /// Create a new [`Resolver`] for this registry with the given base URI.
@@ -517,6 +659,14 @@ impl Registry {
     ) -> Resolver {
         Resolver::from_parts(self, base_uri, scopes)
     }
+    /// Whether the registry holds a top-level document stored under the given URI.
+    ///
+    /// Documents are either provided explicitly or retrieved while resolving references, while
+    /// resources embedded in other documents or built-in meta-schemas are not counted.
+    #[must_use]
+    pub fn contains_document(&self, uri: &Uri<String>) -> bool {
+        self.documents.contains_key(uri)
+    }
     pub(crate) fn anchor<'a>(&self, uri: &'a Uri<String>, name: &'a str) -> Result<&Anchor, Error> {
         let key = AnchorKeyRef::new(uri, name);
         if let Some(value) = self.anchors.get(key.borrow_dyn()) {
@@ -536,6 +686,62 @@ impl Registry {
             Err(Error::no_such_anchor(name.to_string()))
         }
     }
+    /// Export resources of this registry and the links between them.
+    ///
+    /// Resources embedded in other resources via `$id` are separate nodes, and references are
+    /// attributed to the closest enclosing resource.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any reference is not a valid URI reference.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use referencing::{Draft, ReferenceKind, Registry};
+    /// use serde_json::json;
+    ///
+    /// let registry = Registry::try_new(
+    ///     "http://example.com/root",
+    ///     Draft::Draft202012.create_resource(json!({
+    ///         "$defs": {"item": {"$id": "item", "$anchor": "name"}},
+    ///         "items": {"$ref": "item#name"}
+    ///     })),
+    /// )?;
+    /// let graph = registry.reference_graph()?;
+    /// assert_eq!(graph.nodes, ["http://example.com/item", "http://example.com/root"]);
+    /// assert_eq!(graph.edges[1].source, "http://example.com/root");
+    /// assert_eq!(graph.edges[1].target, "http://example.com/item#name");
+    /// assert_eq!(graph.edges[1].kind, ReferenceKind::Ref);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn reference_graph(&self) -> Result<ReferenceGraph, Error> {
+        let mut nodes = Vec::new();
+        let mut edges = Vec::new();
+        for (base, resource) in &self.resources {
+            if let Some(id) = resource.id() {
+                // The same resource is also stored under its document URI
+                if uri::resolve_against(&base.borrow(), id)? != **base {
+                    continue;
+                }
+            }
+            nodes.push(base.as_str().to_string());
+            graph::collect_edges(base, resource.contents(), resource.draft(), &mut edges)?;
+        }
+        for key in self.anchors.keys() {
+            edges.push(ReferenceEdge {
+                source: key.uri().as_str().to_string(),
+                target: format!("{}#{}", key.uri().as_str(), key.name()),
+                kind: ReferenceKind::Anchor,
+            });
+        }
+        nodes.sort_unstable();
+        edges.sort_unstable();
+        edges.dedup();
+        Ok(ReferenceGraph { nodes, edges })
+    }
     /// Resolves a reference URI against a base URI using registry's cache.
     ///
     /// # Errors
@@ -544,23 +750,59 @@ impl Registry {
     pub fn resolve_against(&self, base: &Uri<&str>, uri: &str) -> Result<Arc<Uri<String>>, Error> {
         self.resolution_cache.resolve_against(base, uri)
     }
+    /// Resolves multiple reference URIs against the same base URI using registry's cache.
+    ///
+    /// Equivalent to calling [`Registry::resolve_against`] for each reference, but the cache is
+    /// locked once for all cached references and once for storing the new ones.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any reference can't be resolved against the base.
+    pub fn resolve_many(
+        &self,
+        base: &Uri<&str>,
+        uris: &[&str],
+    ) -> Result<Vec<Arc<Uri<String>>>, Error> {
+        self.resolution_cache.resolve_many(base, uris)
+    }
     /// Returns vocabulary set configured for given draft and contents.
+    ///
+    /// Unknown `$schema` values that are not present in the registry fall back to the default
+    /// vocabularies of `draft`. Use [`Registry::try_find_vocabularies`] to reject them instead.
     #[must_use]
     pub fn find_vocabularies(&self, draft: Draft, contents: &Value) -> VocabularySet {
+        self.try_find_vocabularies(draft, contents)
+            .unwrap_or_else(|_| draft.default_vocabularies())
+    }
+    /// Returns vocabulary set configured for given draft and contents, failing on unknown `$schema`.
+    ///
+    /// Custom meta-schemas are looked up in the registry, and their default vocabularies are used
+    /// if they don't declare `$vocabulary`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::UnknownSpecification`] if `$schema` is neither a known draft nor a
+    /// meta-schema present in the registry, or an error if that meta-schema declares a
+    /// vocabulary that is not a valid URI.
+    pub fn try_find_vocabularies(
+        &self,
+        draft: Draft,
+        contents: &Value,
+    ) -> Result<VocabularySet, Error> {
         match draft.detect(contents) {
-            Ok(draft) => draft.default_vocabularies(),
+            Ok(draft) => Ok(draft.default_vocabularies()),
             Err(Error::UnknownSpecification { specification }) => {
                 // Try to lookup the specification and find enabled vocabularies
-                if let Ok(Some(resource)) =
-                    uri::from_str(&specification).map(|uri| self.resources.get(&uri))
-                {
-                    if let Ok(Some(vocabularies)) = vocabularies::find(resource.contents()) {
-                        return vocabularies;
-                    }
-                }
-                draft.default_vocabularies()
+                let Some(resource) = uri::from_str(&specification)
+                    .ok()
+                    .and_then(|uri| self.resources.get(&uri))
+                else {
+                    return Err(Error::UnknownSpecification { specification });
+                };
+                Ok(vocabularies::find(resource.contents())?
+                    .unwrap_or_else(|| draft.default_vocabularies()))
             }
-            _ => unreachable!(),
+            Err(error) => Err(error),
         }
     }
 }
@@ -612,17 +854,171 @@ struct ProcessingState {
     external: AHashSet<(String, Uri<String>)>,
     scratch: String,
     refers_metaschemas: bool,
+    limits: Limits,
+    cache: Option<DocumentCache>,
+    strict_identifiers: bool,
+    rewriter: Option<Arc<dyn UriRewriter>>,
+    /// Retrieved documents by their digest, so identical ones are stored once.
+    retrieved: AHashMap<u64, Vec<Pin<Arc<ValueWrapper>>>>,
+    /// Number of retrieval rounds, i.e. the reference depth of documents retrieved last.
+    depth: usize,
+    retrieved_documents: usize,
+    retrieved_bytes: usize,
 }
 
 impl ProcessingState {
-    fn new() -> Self {
+    fn new(policy: RetrievalPolicy) -> Self {
         Self {
             queue: VecDeque::with_capacity(32),
             seen: HashSet::with_hasher(BuildNoHashHasher::default()),
             external: AHashSet::new(),
             scratch: String::new(),
             refers_metaschemas: false,
+            limits: policy.limits,
+            cache: policy.cache,
+            strict_identifiers: policy.strict_identifiers,
+            rewriter: policy.rewriter,
+            retrieved: AHashMap::new(),
+            depth: 0,
+            retrieved_documents: 0,
+            retrieved_bytes: 0,
+        }
+    }
+
+    /// Account for a document about to be retrieved in the current round.
+    fn reserve_retrieval(&mut self, uri: &Uri<String>) -> Result<(), Error> {
+        if self.depth > self.limits.ref_depth {
+            return Err(Error::limit_exceeded(
+                uri.as_str(),
+                LimitKind::ReferenceDepth,
+                self.limits.ref_depth,
+            ));
+        }
+        if self.retrieved_documents >= self.limits.documents {
+            return Err(Error::limit_exceeded(
+                uri.as_str(),
+                LimitKind::Documents,
+                self.limits.documents,
+            ));
         }
+        self.retrieved_documents += 1;
+        Ok(())
+    }
+
+    /// Where to retrieve a document from, after rewriting its URI.
+    fn location(&self, uri: &Uri<String>) -> Uri<String> {
+        self.rewriter
+            .as_ref()
+            .and_then(|rewriter| rewriter.rewrite(uri))
+            .unwrap_or_else(|| uri.clone())
+    }
+
+    /// A previously retrieved document, its size and digest, if shared via the cache.
+    fn cached(&self, uri: &Uri<String>) -> Option<(ValueWrapper, Option<usize>, u64)> {
+        self.cache
+            .as_ref()
+            .and_then(|cache| cache.get(uri))
+            .map(|(document, size, digest)| (ValueWrapper::Shared(document), size, digest))
+    }
+
+    /// Share a retrieved document via the cache, if any.
+    fn store(&self, uri: &Uri<String>, retrieved: Retrieved) -> (ValueWrapper, Option<usize>, u64) {
+        let (size, digest) = (retrieved.size, retrieved.digest);
+        let document = match &self.cache {
+            Some(cache) => ValueWrapper::Shared(cache.insert(uri, retrieved)),
+            None => ValueWrapper::Owned(retrieved.document),
+        };
+        (document, size, digest)
+    }
+
+    /// The size budget left for retrieved documents, if their total size is limited.
+    fn remaining_bytes(&self) -> Option<usize> {
+        (self.limits.total_bytes != usize::MAX)
+            .then(|| self.limits.total_bytes.saturating_sub(self.retrieved_bytes))
+    }
+
+    /// Convert a failed retrieval of `uri` into a registry error.
+    fn retrieval_error(
+        &self,
+        original: &str,
+        uri: &Uri<String>,
+        location: &Uri<String>,
+        error: Box<dyn std::error::Error + Send + Sync>,
+    ) -> Error {
+        if error.is::<DocumentTooLarge>() {
+            let mut fragmentless = uri.clone();
+            fragmentless.set_fragment(None);
+            Error::limit_exceeded(
+                fragmentless.as_str(),
+                LimitKind::TotalBytes,
+                self.limits.total_bytes,
+            )
+        } else if uri.scheme().as_str() == "json-schema" {
+            Error::unretrievable(original, "No base URI is available".into())
+        } else {
+            Error::unretrievable(location.as_str(), error)
+        }
+    }
+
+    /// Account for the size of a retrieved document before it is stored.
+    ///
+    /// Documents that were not read as text are measured by their compact JSON serialization.
+    fn record_retrieved(
+        &mut self,
+        uri: &Uri<String>,
+        retrieved: &Value,
+        size: Option<usize>,
+    ) -> Result<(), Error> {
+        if self.limits.total_bytes == usize::MAX {
+            return Ok(());
+        }
+        let size = size.unwrap_or_else(|| {
+            let mut counter = ByteCounter(0);
+            serde_json::to_writer(&mut counter, retrieved)
+                .expect("Writing to a counter never fails");
+            counter.0
+        });
+        self.retrieved_bytes = self.retrieved_bytes.saturating_add(size);
+        if self.retrieved_bytes > self.limits.total_bytes {
+            return Err(Error::limit_exceeded(
+                uri.as_str(),
+                LimitKind::TotalBytes,
+                self.limits.total_bytes,
+            ));
+        }
+        Ok(())
+    }
+
+    /// A previously retrieved document with the same contents, or `retrieved` itself, which is
+    /// then remembered for later retrievals.
+    ///
+    /// Mirrors of a document reachable via several URIs thus share their memory, along with the
+    /// resources pointing into it. Documents shared via the cache are deduplicated there, across
+    /// registries.
+    fn deduplicate(&mut self, retrieved: ValueWrapper, digest: u64) -> Pin<Arc<ValueWrapper>> {
+        let candidates = self.retrieved.entry(digest).or_default();
+        // Different contents may have the same digest
+        if let Some(existing) = candidates.iter().find(|existing| {
+            let existing = (***existing).as_ref();
+            std::ptr::eq(existing, retrieved.as_ref()) || existing == retrieved.as_ref()
+        }) {
+            return Pin::clone(existing);
+        }
+        let wrapped_value = Arc::pin(retrieved);
+        candidates.push(Pin::clone(&wrapped_value));
+        wrapped_value
+    }
+}
+
+struct ByteCounter(usize);
+
+impl io::Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
     }
 }
 
@@ -641,6 +1037,9 @@ fn process_input_resources(
                 let (draft, contents) = resource.into_inner();
                 let wrapped_value = Arc::pin(ValueWrapper::Owned(contents));
                 let resource = InnerResourcePtr::new((*wrapped_value).as_ref(), draft);
+                if state.strict_identifiers {
+                    identifiers::check(&key, &resource)?;
+                }
                 resources.insert(Arc::clone(&key), resource.clone());
                 state.queue.push_back((key, resource));
                 entry.insert(wrapped_value);
@@ -720,15 +1119,20 @@ fn handle_metaschemas(
 }
 
 fn create_resource(
-    retrieved: Value,
+    retrieved: ValueWrapper,
+    digest: u64,
     fragmentless: Uri<String>,
     default_draft: Draft,
+    state: &mut ProcessingState,
     documents: &mut DocumentStore,
     resources: &mut ResourceMap,
 ) -> Result<(Arc<Uri<String>>, InnerResourcePtr), Error> {
-    let draft = default_draft.detect(&retrieved)?;
-    let wrapped_value = Arc::pin(ValueWrapper::Owned(retrieved));
+    let draft = default_draft.detect(retrieved.as_ref())?;
+    let wrapped_value = state.deduplicate(retrieved, digest);
     let resource = InnerResourcePtr::new((*wrapped_value).as_ref(), draft);
+    if state.strict_identifiers {
+        identifiers::check(&fragmentless, &resource)?;
+    }
     let key = Arc::new(fragmentless);
     documents.insert(Arc::clone(&key), wrapped_value);
     resources.insert(Arc::clone(&key), resource.clone());
@@ -743,8 +1147,9 @@ fn process_resources(
     anchors: &mut AHashMap<AnchorKey, Anchor>,
     resolution_cache: &mut UriCache,
     default_draft: Draft,
+    policy: RetrievalPolicy,
 ) -> Result<(), Error> {
-    let mut state = ProcessingState::new();
+    let mut state = ProcessingState::new(policy);
     process_input_resources(pairs, documents, resources, &mut state)?;
 
     loop {
@@ -755,26 +1160,39 @@ fn process_resources(
         process_queue(&mut state, resources, anchors, resolution_cache)?;
 
         // Retrieve external resources
-        for (original, uri) in state.external.drain() {
+        state.depth += 1;
+        let external: Vec<_> = state.external.drain().collect();
+        for (original, uri) in external {
             let mut fragmentless = uri.clone();
             fragmentless.set_fragment(None);
             if !resources.contains_key(&fragmentless) {
-                let retrieved = match retriever.retrieve(&fragmentless) {
-                    Ok(retrieved) => retrieved,
-                    Err(error) => {
-                        return if uri.scheme().as_str() == "json-schema" {
-                            Err(Error::unretrievable(
-                                original,
-                                "No base URI is available".into(),
-                            ))
-                        } else {
-                            Err(Error::unretrievable(fragmentless.as_str(), error))
+                state.reserve_retrieval(&fragmentless)?;
+                let location = state.location(&fragmentless);
+                let (retrieved, size, digest) = if let Some(cached) = state.cached(&location) {
+                    cached
+                } else {
+                    let retrieved = match state.remaining_bytes() {
+                        Some(max_bytes) => retriever.retrieve_bounded(&location, max_bytes),
+                        None => retriever.retrieve(&location).map(Retrieved::from_value),
+                    };
+                    match retrieved {
+                        Ok(retrieved) => state.store(&location, retrieved),
+                        Err(error) => {
+                            return Err(state.retrieval_error(&original, &uri, &location, error))
                         }
                     }
                 };
+                state.record_retrieved(&fragmentless, retrieved.as_ref(), size)?;
 
-                let (key, resource) =
-                    create_resource(retrieved, fragmentless, default_draft, documents, resources)?;
+                let (key, resource) = create_resource(
+                    retrieved,
+                    digest,
+                    fragmentless,
+                    default_draft,
+                    &mut state,
+                    documents,
+                    resources,
+                )?;
 
                 handle_fragment(&uri, &resource, &key, default_draft, &mut state.queue)?;
 
@@ -797,8 +1215,9 @@ async fn process_resources_async(
     anchors: &mut AHashMap<AnchorKey, Anchor>,
     resolution_cache: &mut UriCache,
     default_draft: Draft,
+    policy: RetrievalPolicy,
 ) -> Result<(), Error> {
-    let mut state = ProcessingState::new();
+    let mut state = ProcessingState::new(policy);
     process_input_resources(pairs, documents, resources, &mut state)?;
 
     loop {
@@ -809,6 +1228,7 @@ async fn process_resources_async(
         process_queue(&mut state, resources, anchors, resolution_cache)?;
 
         if !state.external.is_empty() {
+            state.depth += 1;
             let data = state
                 .external
                 .drain()
@@ -822,33 +1242,62 @@ async fn process_resources_async(
                     }
                 })
                 .collect::<Vec<_>>();
+            for (_, _, fragmentless) in &data {
+                state.reserve_retrieval(fragmentless)?;
+            }
+            let locations = data
+                .iter()
+                .map(|(_, _, fragmentless)| state.location(fragmentless))
+                .collect::<Vec<_>>();
 
+            let cached = locations
+                .iter()
+                .map(|location| state.cached(location))
+                .collect::<Vec<_>>();
+
+            // Documents are retrieved concurrently, so each of them may use the whole budget
+            let remaining_bytes = state.remaining_bytes();
             let results = {
-                let futures = data
+                let futures = locations
                     .iter()
-                    .map(|(_, _, fragmentless)| retriever.retrieve(fragmentless));
+                    .zip(&cached)
+                    .filter(|(_, cached)| cached.is_none())
+                    .map(|(location, _)| async move {
+                        match remaining_bytes {
+                            Some(max_bytes) => {
+                                retriever.retrieve_bounded(location, max_bytes).await
+                            }
+                            None => retriever
+                                .retrieve(location)
+                                .await
+                                .map(Retrieved::from_value),
+                        }
+                    });
                 futures::future::join_all(futures).await
             };
+            let mut results = results.into_iter();
 
-            for ((original, uri, fragmentless), result) in data.iter().zip(results) {
-                let retrieved = match result {
-                    Ok(retrieved) => retrieved,
-                    Err(error) => {
-                        return if uri.scheme().as_str() == "json-schema" {
-                            Err(Error::unretrievable(
-                                original,
-                                "No base URI is available".into(),
-                            ))
-                        } else {
-                            Err(Error::unretrievable(fragmentless.as_str(), error))
+            for (((original, uri, fragmentless), location), cached) in
+                data.iter().zip(&locations).zip(cached)
+            {
+                let (retrieved, size, digest) = if let Some(cached) = cached {
+                    cached
+                } else {
+                    match results.next().expect("One result per uncached document") {
+                        Ok(retrieved) => state.store(location, retrieved),
+                        Err(error) => {
+                            return Err(state.retrieval_error(original, uri, location, error))
                         }
                     }
                 };
+                state.record_retrieved(fragmentless, retrieved.as_ref(), size)?;
 
                 let (key, resource) = create_resource(
                     retrieved,
+                    digest,
                     fragmentless.clone(),
                     default_draft,
+                    &mut state,
                     documents,
                     resources,
                 )?;
@@ -1004,14 +1453,23 @@ pub fn parse_index(s: &str) -> Option<usize> {
 
 #[cfg(test)]
 mod tests {
-    use std::error::Error as _;
+    use std::{
+        error::Error as _,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+    };
 
     use ahash::AHashMap;
     use fluent_uri::Uri;
     use serde_json::{json, Value};
     use test_case::test_case;
 
-    use crate::{uri::from_str, Draft, Registry, Resource, Retrieve};
+    use crate::{
+        uri::from_str, Breadcrumb, DocumentCache, DocumentTooLarge, Draft, Error, ReferenceKind,
+        Registry, Resource, Retrieve, Retrieved, Vocabulary,
+    };
 
     use super::{pointer, RegistryOptions, SPECIFICATIONS};
 
@@ -1068,6 +1526,99 @@ mod tests {
         assert_eq!(error.to_string(), "Resource './virtualNetwork.json' is not present in a registry and retrieving it failed: No base URI is available");
     }
 
+    #[test]
+    fn test_contains_document() {
+        let schema = Draft::Draft202012.create_resource(json!({
+            "$defs": {
+                "embedded": {"$id": "http://example.com/embedded"}
+            }
+        }));
+        let registry =
+            Registry::try_new("http://example.com/root", schema).expect("Invalid resources");
+        assert!(
+            registry.contains_document(&from_str("http://example.com/root").expect("Invalid URI"))
+        );
+        assert!(!registry
+            .contains_document(&from_str("http://example.com/embedded").expect("Invalid URI")));
+        assert!(!registry.contains_document(
+            &from_str("https://json-schema.org/draft/2020-12/schema").expect("Invalid URI")
+        ));
+    }
+
+    #[test]
+    fn test_reference_graph() {
+        let retriever = create_test_retriever(&[(
+            "http://example.com/remote",
+            json!({"$id": "http://example.com/canonical", "$dynamicAnchor": "node"}),
+        )]);
+        let registry = Registry::options()
+            .retriever(retriever)
+            .build([(
+                "http://example.com/root",
+                Draft::Draft202012.create_resource(json!({
+                    "$schema": "https://json-schema.org/draft/2020-12/schema",
+                    "properties": {
+                        "local": {"$ref": "#/$defs/local"},
+                        "remote": {"$ref": "remote"},
+                    },
+                    "$defs": {
+                        "local": {"items": {"$dynamicRef": "canonical#node"}},
+                        "embedded": {"$id": "embedded", "$ref": "root"}
+                    }
+                })),
+            )])
+            .expect("Invalid resources");
+        let graph = registry.reference_graph().expect("Invalid references");
+        assert_eq!(
+            graph.nodes,
+            [
+                "http://example.com/canonical",
+                "http://example.com/embedded",
+                "http://example.com/root"
+            ]
+        );
+        let edges: Vec<_> = graph
+            .edges
+            .iter()
+            .map(|edge| (edge.source.as_str(), edge.target.as_str(), edge.kind))
+            .collect();
+        assert_eq!(
+            edges,
+            [
+                (
+                    "http://example.com/canonical",
+                    "http://example.com/canonical#node",
+                    ReferenceKind::Anchor
+                ),
+                (
+                    "http://example.com/embedded",
+                    "http://example.com/root",
+                    ReferenceKind::Ref
+                ),
+                (
+                    "http://example.com/root",
+                    "http://example.com/canonical#node",
+                    ReferenceKind::DynamicRef
+                ),
+                (
+                    "http://example.com/root",
+                    "http://example.com/remote",
+                    ReferenceKind::Ref
+                ),
+                (
+                    "http://example.com/root",
+                    "http://example.com/root#/$defs/local",
+                    ReferenceKind::Ref
+                ),
+                (
+                    "http://example.com/root",
+                    "https://json-schema.org/draft/2020-12/schema",
+                    ReferenceKind::Schema
+                ),
+            ]
+        );
+    }
+
     struct TestRetriever {
         schemas: AHashMap<String, Value>,
     }
@@ -1311,6 +1862,459 @@ mod tests {
         assert!(error.source().is_some());
     }
 
+    #[test_case(usize::MAX, usize::MAX, usize::MAX, None; "unlimited")]
+    #[test_case(3, 1000, 3, None; "within limits")]
+    #[test_case(
+        2, usize::MAX, usize::MAX,
+        Some("Retrieving 'http://example.com/c' exceeds the limit of 2 retrieved documents");
+        "documents"
+    )]
+    #[test_case(
+        usize::MAX, 60, usize::MAX,
+        Some("Retrieving 'http://example.com/b' exceeds the limit of 60 bytes in retrieved documents");
+        "total bytes"
+    )]
+    #[test_case(
+        usize::MAX, usize::MAX, 1,
+        Some("Retrieving 'http://example.com/b' exceeds the reference depth limit of 1");
+        "reference depth"
+    )]
+    fn test_limits(
+        max_documents: usize,
+        max_total_bytes: usize,
+        max_ref_depth: usize,
+        expected: Option<&str>,
+    ) {
+        // Each document is 31 bytes long
+        let retriever = create_test_retriever(&[
+            (
+                "http://example.com/a",
+                json!({"$ref": "http://example.com/b"}),
+            ),
+            (
+                "http://example.com/b",
+                json!({"$ref": "http://example.com/c"}),
+            ),
+            (
+                "http://example.com/c",
+                json!({"$ref": "http://example.com/a"}),
+            ),
+        ]);
+        let result = Registry::options()
+            .retriever(retriever)
+            .limits(max_documents, max_total_bytes, max_ref_depth)
+            .build([(
+                "http://example.com/root",
+                Draft::Draft202012.create_resource(json!({"$ref": "http://example.com/a"})),
+            )]);
+        match expected {
+            None => {
+                result.expect("Limits should not be exceeded");
+            }
+            Some(expected) => {
+                let error = result.expect_err("Limits should be exceeded");
+                assert_eq!(error.to_string(), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_limits_with_resources() {
+        let retriever = create_test_retriever(&[
+            (
+                "http://example.com/a",
+                json!({"$ref": "http://example.com/b"}),
+            ),
+            ("http://example.com/b", json!({})),
+        ]);
+        let registry = Registry::options()
+            .limits(1, usize::MAX, usize::MAX)
+            .build([(
+                "http://example.com/root",
+                Draft::Draft202012.create_resource(json!({})),
+            )])
+            .expect("Invalid resources");
+        let error = registry
+            .try_with_resources_and_retriever(
+                [(
+                    "http://example.com/other",
+                    Draft::Draft202012.create_resource(json!({"$ref": "http://example.com/a"})),
+                )],
+                &retriever,
+                Draft::Draft202012,
+            )
+            .expect_err("Limits should be exceeded");
+        assert_eq!(
+            error.to_string(),
+            "Retrieving 'http://example.com/b' exceeds the limit of 1 retrieved documents"
+        );
+    }
+
+    /// Reads documents as pretty-printed JSON text, stopping at the size it is given.
+    struct BoundedRetriever {
+        inner: TestRetriever,
+        budgets: std::sync::Mutex<Vec<usize>>,
+    }
+
+    impl Retrieve for BoundedRetriever {
+        fn retrieve(
+            &self,
+            uri: &Uri<String>,
+        ) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+            self.inner.retrieve(uri)
+        }
+        fn retrieve_bounded(
+            &self,
+            uri: &Uri<String>,
+            max_bytes: usize,
+        ) -> Result<Retrieved, Box<dyn std::error::Error + Send + Sync>> {
+            self.budgets.lock().expect("Lock poisoned").push(max_bytes);
+            let text = serde_json::to_string_pretty(&self.inner.retrieve(uri)?)?;
+            if text.len() > max_bytes {
+                return Err(Box::new(DocumentTooLarge));
+            }
+            Ok(Retrieved::from_slice(text.as_bytes())?)
+        }
+    }
+
+    #[test]
+    fn test_bounded_retrieval() {
+        // Each document is 36 bytes long when pretty-printed, and 31 bytes as compact JSON
+        let retriever = Arc::new(BoundedRetriever {
+            inner: create_test_retriever(&[
+                (
+                    "http://example.com/a",
+                    json!({"$ref": "http://example.com/b"}),
+                ),
+                (
+                    "http://example.com/b",
+                    json!({"$ref": "http://example.com/c"}),
+                ),
+            ]),
+            budgets: std::sync::Mutex::default(),
+        });
+        let error = Registry::options()
+            .limits(usize::MAX, 50, usize::MAX)
+            .retriever(Arc::clone(&retriever) as Arc<dyn Retrieve>)
+            .build([(
+                "http://example.com/root",
+                Draft::Draft202012.create_resource(json!({"$ref": "http://example.com/a"})),
+            )])
+            .expect_err("Limits should be exceeded");
+        assert_eq!(
+            error.to_string(),
+            "Retrieving 'http://example.com/b' exceeds the limit of 50 bytes in retrieved documents"
+        );
+        // The second document only gets what is left after the bytes read for the first one
+        assert_eq!(*retriever.budgets.lock().expect("Lock poisoned"), [50, 14]);
+    }
+
+    #[derive(Clone, Default)]
+    struct CountingRetriever {
+        count: Arc<AtomicUsize>,
+    }
+
+    impl Retrieve for CountingRetriever {
+        fn retrieve(
+            &self,
+            uri: &Uri<String>,
+        ) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+            self.count.fetch_add(1, Ordering::Relaxed);
+            Ok(json!({"title": uri.as_str()}))
+        }
+    }
+
+    #[test]
+    fn test_document_cache() {
+        let retriever = CountingRetriever::default();
+        let cache = DocumentCache::new(1);
+        let build = |reference: &str| {
+            Registry::options()
+                .retriever(retriever.clone())
+                .document_cache(cache.clone())
+                .build([(
+                    "http://example.com/root",
+                    Draft::Draft202012.create_resource(json!({"$ref": reference})),
+                )])
+                .expect("Invalid resources")
+        };
+        let a = from_str("http://example.com/a").expect("Invalid URI");
+        let b = from_str("http://example.com/b").expect("Invalid URI");
+
+        let first = build("http://example.com/a");
+        let second = build("http://example.com/a");
+        assert_eq!(retriever.count.load(Ordering::Relaxed), 1);
+        let resolved = second
+            .try_resolver("http://example.com/root")
+            .expect("Invalid base URI")
+            .lookup("http://example.com/a")
+            .expect("Lookup failed");
+        assert_eq!(
+            resolved.contents(),
+            &json!({"title": "http://example.com/a"})
+        );
+
+        // `a` is no longer among the most recently used documents, but it is still in use
+        let third = build("http://example.com/b");
+        assert!(cache.contains(&a));
+        assert_eq!(cache.len(), 2);
+        drop((first, second));
+        assert!(!cache.contains(&a));
+        assert!(cache.contains(&b));
+
+        // Unused documents are only kept while recently used
+        drop(third);
+        assert!(cache.contains(&b));
+        cache.clear();
+        assert!(cache.is_empty());
+        build("http://example.com/b");
+        assert_eq!(retriever.count.load(Ordering::Relaxed), 3);
+    }
+
+    #[test]
+    fn test_deduplicate_retrieved() {
+        let mirrored = json!({"$defs": {"item": {"type": "string"}}});
+        let retriever = create_test_retriever(&[
+            ("http://example.com/a", mirrored.clone()),
+            ("http://mirror.example.com/a", mirrored),
+            (
+                "http://example.com/b",
+                json!({"$defs": {"item": {"type": "integer"}}}),
+            ),
+        ]);
+        let registry = Registry::options()
+            .retriever(retriever)
+            .build([(
+                "http://example.com/root",
+                Draft::Draft202012.create_resource(json!({"allOf": [
+                    {"$ref": "http://example.com/a#/$defs/item"},
+                    {"$ref": "http://mirror.example.com/a#/$defs/item"},
+                    {"$ref": "http://example.com/b#/$defs/item"},
+                ]})),
+            )])
+            .expect("Invalid resources");
+        let resolver = registry
+            .try_resolver("http://example.com/root")
+            .expect("Invalid base URI");
+        let lookup = |reference: &str| {
+            resolver
+                .lookup(reference)
+                .expect("Lookup failed")
+                .contents()
+        };
+        // Identical documents are stored once, while still resolved under both URIs
+        assert!(std::ptr::eq(
+            lookup("http://example.com/a#/$defs/item"),
+            lookup("http://mirror.example.com/a#/$defs/item")
+        ));
+        assert!(!std::ptr::eq(
+            lookup("http://example.com/a#/$defs/item"),
+            lookup("http://example.com/b#/$defs/item")
+        ));
+        assert_eq!(
+            lookup("http://example.com/b#/$defs/item"),
+            &json!({"type": "integer"})
+        );
+    }
+
+    #[test]
+    fn test_deduplicate_retrieved_across_registries() {
+        let mirrored = json!({"$defs": {"item": {"type": "string"}}});
+        let cache = DocumentCache::new(0);
+        let build = |reference: &str| {
+            Registry::options()
+                .retriever(create_test_retriever(&[
+                    ("http://example.com/a", mirrored.clone()),
+                    ("http://mirror.example.com/a", mirrored.clone()),
+                ]))
+                .document_cache(cache.clone())
+                .build([(
+                    "http://example.com/root",
+                    Draft::Draft202012.create_resource(json!({"$ref": reference})),
+                )])
+                .expect("Invalid resources")
+        };
+        let first = build("http://example.com/a#/$defs/item");
+        let second = build("http://mirror.example.com/a#/$defs/item");
+        let lookup = |registry: &Registry, reference: &str| {
+            std::ptr::addr_of!(*registry
+                .try_resolver("http://example.com/root")
+                .expect("Invalid base URI")
+                .lookup(reference)
+                .expect("Lookup failed")
+                .contents())
+        };
+        // Registries sharing a cache share identical documents retrieved from different URIs
+        assert_eq!(
+            lookup(&first, "http://example.com/a#/$defs/item"),
+            lookup(&second, "http://mirror.example.com/a#/$defs/item")
+        );
+    }
+
+    #[test]
+    fn test_document_cache_with_huge_capacity() {
+        // The capacity is a bound, not an allocation
+        let cache = DocumentCache::new(usize::MAX);
+        Registry::options()
+            .document_cache(cache.clone())
+            .build([(
+                "http://example.com/root",
+                Draft::Draft202012.create_resource(json!({"type": "string"})),
+            )])
+            .expect("Invalid resources");
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_uri_rewriter() {
+        let build = |reference: &str| {
+            Registry::options()
+                .retriever(create_test_retriever(&[(
+                    "https://schemas.staging/a.json",
+                    json!({"type": "string"}),
+                )]))
+                .uri_rewriter(|uri: &Uri<String>| {
+                    uri.as_str()
+                        .strip_prefix("https://schemas.prod/")
+                        .map(|path| {
+                            from_str(&format!("https://schemas.staging/{path}"))
+                                .expect("Invalid URI")
+                        })
+                })
+                .build([(
+                    "http://example.com/root",
+                    Draft::Draft202012.create_resource(json!({"$ref": reference})),
+                )])
+        };
+        let registry = build("https://schemas.prod/a.json").expect("Invalid resources");
+        // Documents are stored under their original URIs
+        let prod = from_str("https://schemas.prod/a.json").expect("Invalid URI");
+        assert!(registry.contains_document(&prod));
+        let resolved = registry
+            .try_resolver("http://example.com/root")
+            .expect("Invalid base URI")
+            .lookup("https://schemas.prod/a.json")
+            .expect("Lookup failed");
+        assert_eq!(resolved.contents(), &json!({"type": "string"}));
+
+        let error = build("https://schemas.prod/b.json").expect_err("Missing document");
+        assert_eq!(
+            error.to_string(),
+            "Resource 'https://schemas.staging/b.json' is not present in a registry and retrieving it failed: Failed to find https://schemas.staging/b.json"
+        );
+    }
+
+    #[test]
+    fn test_uri_rewriter_with_resources() {
+        let registry = Registry::options()
+            .uri_rewriter(|uri: &Uri<String>| {
+                uri.as_str()
+                    .strip_prefix("https://schemas.prod/")
+                    .map(|path| {
+                        from_str(&format!("https://schemas.staging/{path}")).expect("Invalid URI")
+                    })
+            })
+            .build([(
+                "http://example.com/root",
+                Draft::Draft202012.create_resource(json!({})),
+            )])
+            .expect("Invalid resources");
+        let retriever =
+            create_test_retriever(&[("https://schemas.staging/a.json", json!({"type": "string"}))]);
+        let registry = registry
+            .try_with_resources_and_retriever(
+                [(
+                    "http://example.com/other",
+                    Draft::Draft202012
+                        .create_resource(json!({"$ref": "https://schemas.prod/a.json"})),
+                )],
+                &retriever,
+                Draft::Draft202012,
+            )
+            .expect("Invalid resources");
+        let prod = from_str("https://schemas.prod/a.json").expect("Invalid URI");
+        assert!(registry.contains_document(&prod));
+    }
+
+    #[test]
+    fn test_resolve_many() {
+        let registry = Registry::try_new(
+            "http://example.com/root",
+            Draft::Draft202012.create_resource(json!({})),
+        )
+        .expect("Invalid resources");
+        let base = from_str("http://example.com/dir/root").expect("Invalid URI");
+        // Cached by a previous resolution
+        let cached = registry
+            .resolve_against(&base.borrow(), "a.json")
+            .expect("Invalid reference");
+        let references = ["a.json", "../b.json#/defs", "#anchor", "urn:c", "a.json"];
+        let resolved = registry
+            .resolve_many(&base.borrow(), &references)
+            .expect("Invalid references");
+        assert!(Arc::ptr_eq(&resolved[0], &cached));
+        assert!(Arc::ptr_eq(&resolved[4], &cached));
+        let resolved: Vec<_> = resolved.iter().map(|uri| uri.as_str()).collect();
+        assert_eq!(
+            resolved,
+            [
+                "http://example.com/dir/a.json",
+                "http://example.com/b.json#/defs",
+                "http://example.com/dir/root#anchor",
+                "urn:c",
+                "http://example.com/dir/a.json",
+            ]
+        );
+        for reference in references {
+            assert_eq!(
+                registry
+                    .resolve_against(&base.borrow(), reference)
+                    .expect("Invalid reference")
+                    .as_str(),
+                registry
+                    .resolve_many(&base.borrow(), &[reference])
+                    .expect("Invalid reference")[0]
+                    .as_str()
+            );
+        }
+        registry
+            .resolve_many(&base.borrow(), &["a.json", "http://[invalid"])
+            .expect_err("Invalid reference");
+    }
+
+    #[test]
+    fn test_try_find_vocabularies() {
+        let registry = Registry::try_new(
+            "http://example.com/meta",
+            Draft::Draft202012.create_resource(json!({
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "$id": "http://example.com/meta",
+                "$vocabulary": {
+                    "https://json-schema.org/draft/2020-12/vocab/core": true,
+                    "https://json-schema.org/draft/2020-12/vocab/applicator": true
+                }
+            })),
+        )
+        .expect("Invalid resources");
+        let custom = registry
+            .try_find_vocabularies(
+                Draft::Draft202012,
+                &json!({"$schema": "http://example.com/meta"}),
+            )
+            .expect("Known meta-schema");
+        assert!(custom.contains(&Vocabulary::Applicator));
+        assert!(!custom.contains(&Vocabulary::Validation));
+        let typo = json!({"$schema": "https://json-schema.org/draft/2020-12/shcema"});
+        let error = registry
+            .try_find_vocabularies(Draft::Draft202012, &typo)
+            .expect_err("Unknown meta-schema");
+        assert!(matches!(error, Error::UnknownSpecification { .. }));
+        // The lenient variant keeps falling back to the draft defaults
+        assert!(registry
+            .find_vocabularies(Draft::Draft202012, &typo)
+            .contains(&Vocabulary::Validation));
+    }
+
     #[test]
     fn test_options() {
         let _registry = RegistryOptions::default()
@@ -1394,6 +2398,57 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_resolver_breadcrumbs() {
+        let registry = Registry::try_from_resources([
+            (
+                "http://example.com/a.json",
+                Resource::from_contents(json!({"$ref": "b.json#/$defs/b"}))
+                    .expect("Invalid resource"),
+            ),
+            (
+                "http://example.com/b.json",
+                Resource::from_contents(json!({"$defs": {"b": {"$ref": "c.json"}}}))
+                    .expect("Invalid resource"),
+            ),
+            (
+                "http://example.com/c.json",
+                Resource::from_contents(json!({})).expect("Invalid resource"),
+            ),
+        ])
+        .expect("Invalid resources");
+        let resolver = registry
+            .try_resolver("http://example.com/a.json")
+            .expect("Invalid base URI");
+        // Not recorded by default
+        let c = resolver
+            .lookup("b.json#/$defs/b")
+            .and_then(|b| b.resolver().lookup("c.json"))
+            .expect("Lookup failed");
+        assert!(c.resolver().breadcrumbs().is_empty());
+
+        let resolver = resolver.with_breadcrumbs();
+        assert!(resolver.breadcrumbs().is_empty());
+        assert_eq!(resolver.breadcrumbs().to_string(), "");
+
+        let b = resolver.lookup("b.json#/$defs/b").expect("Lookup failed");
+        let c = b.resolver().lookup("c.json").expect("Lookup failed");
+        let breadcrumbs = c.resolver().breadcrumbs();
+        assert_eq!(breadcrumbs.len(), 2);
+        let references: Vec<_> = breadcrumbs.iter().map(Breadcrumb::reference).collect();
+        assert_eq!(references, ["b.json#/$defs/b", "c.json"]);
+        let first = breadcrumbs.iter().next().expect("Missing breadcrumb");
+        assert_eq!(first.base_uri().as_str(), "http://example.com/a.json");
+        assert_eq!(first.uri().as_str(), "http://example.com/b.json");
+        assert_eq!(first.fragment(), "/$defs/b");
+        assert_eq!(
+            breadcrumbs.to_string(),
+            "http://example.com/a.json → http://example.com/b.json#/$defs/b → http://example.com/c.json"
+        );
+        // Resolvers that followed fewer references are unaffected
+        assert_eq!(b.resolver().breadcrumbs().len(), 1);
+    }
+
     #[test]
     fn test_try_with_resource() {
         let registry = SPECIFICATIONS
@@ -1473,6 +2528,41 @@ mod async_tests {
         assert!(error.source().is_some());
     }
 
+    #[tokio::test]
+    async fn test_async_limits() {
+        let retriever = TestAsyncRetriever {
+            schemas: AHashMap::from_iter([
+                (
+                    "http://example.com/a".to_string(),
+                    json!({"$ref": "http://example.com/c"}),
+                ),
+                (
+                    "http://example.com/b".to_string(),
+                    json!({"$ref": "http://example.com/c"}),
+                ),
+                ("http://example.com/c".to_string(), json!({})),
+            ]),
+        };
+        let error = Registry::options()
+            .async_retriever(retriever)
+            .limits(2, usize::MAX, usize::MAX)
+            .build([(
+                "http://example.com/root",
+                Draft::Draft202012.create_resource(json!({
+                    "properties": {
+                        "a": {"$ref": "http://example.com/a"},
+                        "b": {"$ref": "http://example.com/b"}
+                    }
+                })),
+            )])
+            .await
+            .expect_err("Limits should be exceeded");
+        assert_eq!(
+            error.to_string(),
+            "Retrieving 'http://example.com/c' exceeds the limit of 2 retrieved documents"
+        );
+    }
+
     #[tokio::test]
     async fn test_async_options() {
         let _registry = Registry::options()
@@ -14,6 +14,8 @@ pub struct Resolver<'r> {
     pub(crate) registry: &'r Registry,
     base_uri: Arc<Uri<String>>,
     scopes: List<Uri<String>>,
+    /// Followed references, recorded only after [`Resolver::with_breadcrumbs`].
+    trail: Option<List<Breadcrumb>>,
 }
 
 impl PartialEq for Resolver<'_> {
@@ -51,6 +53,7 @@ impl<'r> Resolver<'r> {
             registry,
             base_uri,
             scopes: List::new(),
+            trail: None,
         }
     }
     pub(crate) fn from_parts(
@@ -62,6 +65,7 @@ impl<'r> Resolver<'r> {
             registry,
             base_uri,
             scopes,
+            trail: None,
         }
     }
     #[must_use]
@@ -97,17 +101,17 @@ impl<'r> Resolver<'r> {
         };
 
         if fragment.starts_with('/') {
-            let resolver = self.evolve(uri);
+            let resolver = self.evolve(uri, reference, fragment);
             return retrieved.pointer(fragment, resolver);
         }
 
         if !fragment.is_empty() {
             let retrieved = self.registry.anchor(&uri, fragment)?;
-            let resolver = self.evolve(uri);
+            let resolver = self.evolve(uri, reference, fragment);
             return retrieved.resolve(resolver);
         }
 
-        let resolver = self.evolve(uri);
+        let resolver = self.evolve(uri, reference, fragment);
         Ok(Resolved::new(
             retrieved.contents(),
             resolver,
@@ -176,6 +180,7 @@ impl<'r> Resolver<'r> {
                 registry: self.registry,
                 base_uri,
                 scopes: self.scopes.clone(),
+                trail: self.trail.clone(),
             })
         } else {
             Ok(self.clone())
@@ -185,7 +190,34 @@ impl<'r> Resolver<'r> {
     pub fn dynamic_scope(&self) -> List<Uri<String>> {
         self.scopes.clone()
     }
-    fn evolve(&self, base_uri: Arc<Uri<String>>) -> Resolver<'r> {
+    /// Record the references followed from now on by this resolver and the resolvers derived
+    /// from it, see [`Resolver::breadcrumbs`].
+    #[must_use]
+    pub fn with_breadcrumbs(mut self) -> Self {
+        self.trail.get_or_insert_with(List::new);
+        self
+    }
+    /// The references followed to reach this resolver, starting with the first one.
+    ///
+    /// Unlike [`Resolver::dynamic_scope`], which holds the base URIs of the visited resources,
+    /// breadcrumbs keep every reference as written together with the document and fragment it
+    /// pointed to, e.g. for telling where an error comes from. Empty unless recording is enabled
+    /// via [`Resolver::with_breadcrumbs`].
+    #[must_use]
+    pub fn breadcrumbs(&self) -> Breadcrumbs {
+        let mut crumbs: Vec<_> = self.trail.iter().flat_map(List::iter).cloned().collect();
+        crumbs.reverse();
+        Breadcrumbs(crumbs)
+    }
+    fn evolve(&self, base_uri: Arc<Uri<String>>, reference: &str, fragment: &str) -> Resolver<'r> {
+        let trail = self.trail.as_ref().map(|trail| {
+            trail.push_front(Arc::new(Breadcrumb {
+                reference: reference.to_string(),
+                base_uri: self.base_uri.clone(),
+                uri: base_uri.clone(),
+                fragment: fragment.to_string(),
+            }))
+        });
         if !self.base_uri.as_str().is_empty()
             && (self.scopes.is_empty() || base_uri != self.base_uri)
         {
@@ -193,12 +225,14 @@ impl<'r> Resolver<'r> {
                 registry: self.registry,
                 base_uri,
                 scopes: self.scopes.push_front(self.base_uri.clone()),
+                trail,
             }
         } else {
             Resolver {
                 registry: self.registry,
                 base_uri,
                 scopes: self.scopes.clone(),
+                trail,
             }
         }
     }
@@ -212,6 +246,94 @@ impl<'r> Resolver<'r> {
     }
 }
 
+/// A reference followed by a [`Resolver`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Breadcrumb {
+    reference: String,
+    base_uri: Arc<Uri<String>>,
+    uri: Arc<Uri<String>>,
+    fragment: String,
+}
+
+impl Breadcrumb {
+    /// The reference as written, e.g. `other.json#/$defs/item`.
+    #[must_use]
+    pub fn reference(&self) -> &str {
+        &self.reference
+    }
+    /// The base URI the reference was resolved against.
+    #[must_use]
+    pub fn base_uri(&self) -> &Uri<String> {
+        &self.base_uri
+    }
+    /// The URI of the document the reference points to, without the fragment.
+    #[must_use]
+    pub fn uri(&self) -> &Uri<String> {
+        &self.uri
+    }
+    /// The fragment of the reference, without `#`, which is empty for whole documents.
+    #[must_use]
+    pub fn fragment(&self) -> &str {
+        &self.fragment
+    }
+}
+
+impl fmt::Display for Breadcrumb {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.fragment.is_empty() {
+            f.write_str(self.uri.as_str())
+        } else {
+            write!(f, "{}#{}", self.uri.as_str(), self.fragment)
+        }
+    }
+}
+
+/// The references followed by a [`Resolver`], in order.
+///
+/// Displayed as the chain of visited locations, e.g.
+/// `http://example.com/a.json → http://example.com/b.json#/$defs/b → http://example.com/c.json`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Breadcrumbs(Vec<Breadcrumb>);
+
+impl Breadcrumbs {
+    /// Returns true if no references were followed.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+    /// The number of followed references.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+    /// Returns an iterator over the followed references, starting with the first one.
+    pub fn iter(&self) -> std::slice::Iter<'_, Breadcrumb> {
+        self.0.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Breadcrumbs {
+    type Item = &'a Breadcrumb;
+    type IntoIter = std::slice::Iter<'a, Breadcrumb>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl fmt::Display for Breadcrumbs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some(first) = self.0.first() else {
+            return Ok(());
+        };
+        f.write_str(first.base_uri.as_str())?;
+        for breadcrumb in &self.0 {
+            write!(f, " → {breadcrumb}")?;
+        }
+        Ok(())
+    }
+}
+
 /// A reference resolved to its contents by a [`Resolver`].
 #[derive(Debug)]
 pub struct Resolved<'r> {
@@ -1,4 +1,8 @@
-use core::fmt;
+use core::{
+    fmt,
+    hash::{Hash, Hasher},
+};
+use std::collections::hash_map::DefaultHasher;
 
 use fluent_uri::Uri;
 use serde_json::Value;
@@ -24,6 +28,148 @@ pub trait Retrieve: Send + Sync {
         &self,
         uri: &Uri<String>,
     ) -> Result<Value, Box<dyn std::error::Error + Send + Sync>>;
+    /// Attempt to retrieve a resource whose text may be at most `max_bytes` long.
+    ///
+    /// Called instead of [`Retrieve::retrieve`] while a total size limit is set via
+    /// [`RegistryOptions::limits`](crate::RegistryOptions::limits). Implementations reading the
+    /// document as text should stop once more than `max_bytes` are read and fail with
+    /// [`DocumentTooLarge`], and return [`Retrieved::from_slice`] so the bytes they read count
+    /// towards the limit. By default, the whole document is retrieved and measured afterwards.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`Retrieve::retrieve`], or [`DocumentTooLarge`] if the document is too large.
+    fn retrieve_bounded(
+        &self,
+        uri: &Uri<String>,
+        max_bytes: usize,
+    ) -> Result<Retrieved, Box<dyn std::error::Error + Send + Sync>> {
+        let _ = max_bytes;
+        self.retrieve(uri).map(Retrieved::from_value)
+    }
+}
+
+/// A document returned by [`Retrieve::retrieve_bounded`], along with its size if it was read as
+/// text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Retrieved {
+    pub(crate) document: Value,
+    /// Number of bytes read, counted towards the total size limit of retrieved documents.
+    pub(crate) size: Option<usize>,
+    /// Hash of the bytes read, or of the document itself, used to find identical documents.
+    pub(crate) digest: u64,
+}
+
+impl Retrieved {
+    /// Parse a document from the bytes read by a retriever.
+    ///
+    /// # Errors
+    ///
+    /// If the bytes are not valid JSON.
+    pub fn from_slice(bytes: &[u8]) -> Result<Retrieved, serde_json::Error> {
+        let mut hasher = DefaultHasher::new();
+        hasher.write(bytes);
+        Ok(Retrieved {
+            document: serde_json::from_slice(bytes)?,
+            size: Some(bytes.len()),
+            digest: hasher.finish(),
+        })
+    }
+    /// A document that was not read as text. It counts towards the total size limit of retrieved
+    /// documents with the size of its compact JSON serialization.
+    #[must_use]
+    pub fn from_value(document: Value) -> Retrieved {
+        let mut hasher = DefaultHasher::new();
+        hash_value(&document, &mut hasher);
+        Retrieved {
+            digest: hasher.finish(),
+            document,
+            size: None,
+        }
+    }
+    /// The retrieved document.
+    #[must_use]
+    pub fn into_document(self) -> Value {
+        self.document
+    }
+}
+
+/// Feed `value` to `hasher` without serializing it.
+fn hash_value(value: &Value, hasher: &mut DefaultHasher) {
+    match value {
+        Value::Null => hasher.write_u8(0),
+        Value::Bool(value) => {
+            hasher.write_u8(1);
+            value.hash(hasher);
+        }
+        Value::Number(value) => {
+            hasher.write_u8(2);
+            value.hash(hasher);
+        }
+        Value::String(value) => {
+            hasher.write_u8(3);
+            value.hash(hasher);
+        }
+        Value::Array(items) => {
+            hasher.write_u8(4);
+            hasher.write_usize(items.len());
+            for item in items {
+                hash_value(item, hasher);
+            }
+        }
+        Value::Object(object) => {
+            hasher.write_u8(5);
+            hasher.write_usize(object.len());
+            for (key, value) in object {
+                key.hash(hasher);
+                hash_value(value, hasher);
+            }
+        }
+    }
+}
+
+/// Returned by retrievers when a document exceeds the size passed to
+/// [`Retrieve::retrieve_bounded`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DocumentTooLarge;
+
+impl fmt::Display for DocumentTooLarge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Document exceeds the size limit")
+    }
+}
+
+impl std::error::Error for DocumentTooLarge {}
+
+/// Trait for redirecting references to other locations before retrieving them.
+///
+/// Rewritten URIs are used for retrieval and for [`crate::DocumentCache`] lookups, while
+/// documents are still stored under their original URIs, so references resolve as written.
+/// Closures taking a URI and returning the rewritten one, or `None` to keep it, implement it.
+///
+/// # Example
+///
+/// ```rust
+/// use referencing::{uri, Registry, Uri};
+///
+/// let options = Registry::options().uri_rewriter(|uri: &Uri<String>| {
+///     uri.as_str()
+///         .strip_prefix("https://schemas.prod/")
+///         .and_then(|path| uri::from_str(&format!("https://schemas.staging/{path}")).ok())
+/// });
+/// ```
+pub trait UriRewriter: Send + Sync {
+    /// The URI to retrieve instead of `uri`, if any.
+    fn rewrite(&self, uri: &Uri<String>) -> Option<Uri<String>>;
+}
+
+impl<F> UriRewriter for F
+where
+    F: Fn(&Uri<String>) -> Option<Uri<String>> + Send + Sync,
+{
+    fn rewrite(&self, uri: &Uri<String>) -> Option<Uri<String>> {
+        self(uri)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -68,6 +214,21 @@ pub trait AsyncRetrieve: Send + Sync {
         &self,
         uri: &Uri<String>,
     ) -> Result<Value, Box<dyn std::error::Error + Send + Sync>>;
+    /// Asynchronously retrieve a resource whose text may be at most `max_bytes` long.
+    ///
+    /// This is the non-blocking equivalent of [`Retrieve::retrieve_bounded`].
+    ///
+    /// # Errors
+    ///
+    /// Same as [`AsyncRetrieve::retrieve`], or [`DocumentTooLarge`] if the document is too large.
+    async fn retrieve_bounded(
+        &self,
+        uri: &Uri<String>,
+        max_bytes: usize,
+    ) -> Result<Retrieved, Box<dyn std::error::Error + Send + Sync>> {
+        let _ = max_bytes;
+        self.retrieve(uri).await.map(Retrieved::from_value)
+    }
 }
 
 #[cfg(feature = "retrieve-async")]
@@ -17,7 +17,7 @@ use crate::{
 
 /// JSON Schema specification versions.
 #[non_exhaustive]
-#[derive(Debug, Default, PartialEq, Copy, Clone, Hash, Eq, PartialOrd, Ord)]
+#[derive(Debug, PartialEq, Copy, Clone, Hash, Eq, PartialOrd, Ord)]
 pub enum Draft {
     /// JSON Schema Draft 4
     Draft4,
@@ -28,10 +28,26 @@ pub enum Draft {
     /// JSON Schema Draft 2019-09
     Draft201909,
     /// JSON Schema Draft 2020-12
-    #[default]
     Draft202012,
 }
 
+impl Default for Draft {
+    /// The newest draft whose support is enabled, i.e. Draft 2020-12 with the default features.
+    fn default() -> Self {
+        if cfg!(feature = "draft202012") {
+            Draft::Draft202012
+        } else if cfg!(feature = "draft201909") {
+            Draft::Draft201909
+        } else if cfg!(feature = "draft7") {
+            Draft::Draft7
+        } else if cfg!(feature = "draft6") {
+            Draft::Draft6
+        } else {
+            Draft::Draft4
+        }
+    }
+}
+
 impl Draft {
     #[must_use]
     pub fn create_resource(self, contents: Value) -> Resource {
@@ -45,26 +61,73 @@ impl Draft {
     ///
     /// # Errors
     ///
-    /// On unknown `$schema` value it returns [`Error::UnknownSpecification`]
+    /// On unknown `$schema` value it returns [`Error::UnknownSpecification`] and if the detected
+    /// draft is not enabled it returns [`Error::DisabledSpecification`].
     pub fn detect(self, contents: &Value) -> Result<Draft, Error> {
         if let Some(schema) = contents
             .as_object()
             .and_then(|contents| contents.get("$schema"))
             .and_then(|schema| schema.as_str())
         {
-            Ok(match schema.trim_end_matches('#') {
+            let draft = match schema.trim_end_matches('#') {
                 "https://json-schema.org/draft/2020-12/schema" => Draft::Draft202012,
                 "https://json-schema.org/draft/2019-09/schema" => Draft::Draft201909,
                 "http://json-schema.org/draft-07/schema" => Draft::Draft7,
                 "http://json-schema.org/draft-06/schema" => Draft::Draft6,
                 "http://json-schema.org/draft-04/schema" => Draft::Draft4,
                 value => return Err(Error::unknown_specification(value)),
-            })
+            };
+            if draft.is_enabled() {
+                Ok(draft)
+            } else {
+                Err(Error::disabled_specification(draft))
+            }
         } else {
             Ok(self)
         }
     }
-    pub(crate) fn id_of(self, contents: &Value) -> Option<&str> {
+    /// Whether support for this draft is compiled in via its cargo feature.
+    ///
+    /// ```rust
+    /// use referencing::Draft;
+    ///
+    /// assert!(Draft::Draft202012.is_enabled());
+    /// ```
+    #[must_use]
+    pub const fn is_enabled(self) -> bool {
+        match self {
+            Draft::Draft4 => cfg!(feature = "draft4"),
+            Draft::Draft6 => cfg!(feature = "draft6"),
+            Draft::Draft7 => cfg!(feature = "draft7"),
+            Draft::Draft201909 => cfg!(feature = "draft201909"),
+            Draft::Draft202012 => cfg!(feature = "draft202012"),
+        }
+    }
+    pub(crate) fn feature(self) -> &'static str {
+        match self {
+            Draft::Draft4 => "draft4",
+            Draft::Draft6 => "draft6",
+            Draft::Draft7 => "draft7",
+            Draft::Draft201909 => "draft201909",
+            Draft::Draft202012 => "draft202012",
+        }
+    }
+    /// The identifier a schema declares as a resource under this draft, i.e. its `$id`, or `id`
+    /// in Draft 4.
+    ///
+    /// Before Draft 2019-09, identifiers next to `$ref` are ignored, as are those only declaring
+    /// a plain-name fragment.
+    ///
+    /// ```rust
+    /// use referencing::Draft;
+    /// use serde_json::json;
+    ///
+    /// let schema = json!({"$id": "https://example.com/item.json"});
+    /// assert_eq!(Draft::Draft202012.id_of(&schema), Some("https://example.com/item.json"));
+    /// assert_eq!(Draft::Draft4.id_of(&schema), None);
+    /// ```
+    #[must_use]
+    pub fn id_of(self, contents: &Value) -> Option<&str> {
         match self {
             Draft::Draft4 => ids::legacy_id(contents),
             Draft::Draft6 | Draft::Draft7 => ids::legacy_dollar_id(contents),
@@ -209,6 +272,21 @@ mod tests {
         assert_eq!(result, expected);
     }
 
+    #[test]
+    fn test_default_is_newest_enabled() {
+        let default = Draft::default();
+        assert!(default.is_enabled());
+        assert!([
+            Draft::Draft4,
+            Draft::Draft6,
+            Draft::Draft7,
+            Draft::Draft201909,
+            Draft::Draft202012
+        ]
+        .into_iter()
+        .all(|draft| draft <= default || !draft.is_enabled()));
+    }
+
     #[test]
     fn test_unknown_specification() {
         let error = Draft::Draft7
@@ -0,0 +1,579 @@
+//! Static analysis of schemas.
+//!
+//! [`draft_compatibility`] lists the keywords of a schema whose meaning differs under another
+//! draft, so a schema can be checked before its `$schema` is changed, e.g. by
+//! `jsonschema migrate`.
+//!
+//! The keyword tables below classify keywords by their role across all drafts, e.g. to walk the
+//! subschemas of a schema without compiling it.
+//!
+//! ```rust
+//! use jsonschema::{analyze::IncompatibilityKind, Draft};
+//! use serde_json::json;
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let schema = json!({
+//!     "$schema": "http://json-schema.org/draft-07/schema#",
+//!     "properties": {
+//!         "point": {"items": [{"type": "number"}, {"type": "number"}]}
+//!     }
+//! });
+//! let incompatibilities = jsonschema::analyze::draft_compatibility(&schema, Draft::Draft202012)?;
+//! assert_eq!(incompatibilities.len(), 1);
+//! assert_eq!(incompatibilities[0].location().as_str(), "/properties/point");
+//! assert_eq!(incompatibilities[0].keyword(), "items");
+//! assert_eq!(incompatibilities[0].kind(), IncompatibilityKind::Invalid);
+//! # Ok(())
+//! # }
+//! ```
+use std::fmt;
+
+use referencing::{Draft, Error};
+use serde_json::{Map, Value};
+
+use crate::paths::Location;
+
+/// Keywords whose values are single schemas.
+pub const SCHEMA_KEYWORDS: &[&str] = &[
+    "additionalItems",
+    "additionalProperties",
+    "contains",
+    "contentSchema",
+    "else",
+    "if",
+    "items",
+    "not",
+    "propertyNames",
+    "then",
+    "unevaluatedItems",
+    "unevaluatedProperties",
+];
+/// Keywords whose values are arrays of schemas.
+pub const SCHEMA_ARRAY_KEYWORDS: &[&str] = &["allOf", "anyOf", "items", "oneOf", "prefixItems"];
+/// Keywords whose values map names to schemas, including [`DEFINITION_KEYWORDS`].
+pub const SCHEMA_MAP_KEYWORDS: &[&str] = &[
+    "$defs",
+    "definitions",
+    "dependencies",
+    "dependentSchemas",
+    "patternProperties",
+    "properties",
+];
+/// Keywords holding schemas that only apply where they are referenced.
+pub const DEFINITION_KEYWORDS: &[&str] = &["$defs", "definitions"];
+/// Keywords referencing other schemas.
+pub const REFERENCE_KEYWORDS: &[&str] = &["$ref", "$dynamicRef", "$recursiveRef"];
+/// Keywords next to `$ref` that do not affect validation in any draft.
+pub const REF_SIBLINGS: &[&str] = &[
+    "$comment",
+    "$defs",
+    "$id",
+    "$schema",
+    "default",
+    "definitions",
+    "description",
+    "examples",
+    "id",
+    "title",
+];
+/// Keywords without validation semantics.
+pub const ANNOTATION_KEYWORDS: &[&str] = &[
+    "$comment",
+    "contentEncoding",
+    "contentMediaType",
+    "contentSchema",
+    "default",
+    "deprecated",
+    "description",
+    "examples",
+    "readOnly",
+    "title",
+    "writeOnly",
+];
+/// Keywords whose values are instances rather than schemas.
+pub const INSTANCE_KEYWORDS: &[&str] = &["const", "default", "enum", "examples"];
+
+/// Whether the value of `keyword` holds subschemas in any draft.
+#[must_use]
+pub fn is_subschema_keyword(keyword: &str) -> bool {
+    SCHEMA_KEYWORDS.contains(&keyword)
+        || SCHEMA_ARRAY_KEYWORDS.contains(&keyword)
+        || SCHEMA_MAP_KEYWORDS.contains(&keyword)
+}
+
+/// The name of `draft` in messages, e.g. `Draft 2020-12`.
+#[must_use]
+pub fn draft_name(draft: Draft) -> &'static str {
+    match draft {
+        Draft::Draft4 => "Draft 4",
+        Draft::Draft6 => "Draft 6",
+        Draft::Draft7 => "Draft 7",
+        Draft::Draft201909 => "Draft 2019-09",
+        _ => "Draft 2020-12",
+    }
+}
+
+/// A keyword defined by the drafts from `since` until, but excluding, the draft that replaced it.
+struct Support {
+    keyword: &'static str,
+    since: Draft,
+    replaced: Option<(Draft, &'static str)>,
+}
+
+impl Support {
+    const fn new(keyword: &'static str, since: Draft) -> Support {
+        Support {
+            keyword,
+            since,
+            replaced: None,
+        }
+    }
+    const fn replaced(
+        keyword: &'static str,
+        since: Draft,
+        until: Draft,
+        replacement: &'static str,
+    ) -> Support {
+        Support {
+            keyword,
+            since,
+            replaced: Some((until, replacement)),
+        }
+    }
+    fn is_defined_in(&self, draft: Draft) -> bool {
+        draft >= self.since && self.replaced.map_or(true, |(until, _)| draft < until)
+    }
+}
+
+/// Keywords that are not defined by every draft.
+const SUPPORT: &[Support] = &[
+    Support::replaced("id", Draft::Draft4, Draft::Draft6, "`$id`"),
+    Support::new("$id", Draft::Draft6),
+    Support::new("const", Draft::Draft6),
+    Support::new("contains", Draft::Draft6),
+    Support::new("propertyNames", Draft::Draft6),
+    Support::new("if", Draft::Draft7),
+    Support::new("then", Draft::Draft7),
+    Support::new("else", Draft::Draft7),
+    Support::replaced(
+        "dependencies",
+        Draft::Draft4,
+        Draft::Draft201909,
+        "`dependentRequired` and `dependentSchemas`",
+    ),
+    Support::new("$anchor", Draft::Draft201909),
+    Support::new("dependentRequired", Draft::Draft201909),
+    Support::new("dependentSchemas", Draft::Draft201909),
+    Support::new("maxContains", Draft::Draft201909),
+    Support::new("minContains", Draft::Draft201909),
+    Support::new("unevaluatedItems", Draft::Draft201909),
+    Support::new("unevaluatedProperties", Draft::Draft201909),
+    Support::replaced(
+        "$recursiveAnchor",
+        Draft::Draft201909,
+        Draft::Draft202012,
+        "`$dynamicAnchor`",
+    ),
+    Support::replaced(
+        "$recursiveRef",
+        Draft::Draft201909,
+        Draft::Draft202012,
+        "`$dynamicRef`",
+    ),
+    Support::replaced(
+        "additionalItems",
+        Draft::Draft4,
+        Draft::Draft202012,
+        "`items`",
+    ),
+    Support::new("$dynamicAnchor", Draft::Draft202012),
+    Support::new("$dynamicRef", Draft::Draft202012),
+    Support::new("prefixItems", Draft::Draft202012),
+];
+
+/// How a keyword is affected by changing the draft of a schema, see [`Incompatibility`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IncompatibilityKind {
+    /// The keyword is not defined by the target draft and would be ignored.
+    Ignored,
+    /// The keyword validates differently in the target draft, including keywords that only the
+    /// target draft defines.
+    Changed,
+    /// The value of the keyword is not allowed by the target draft, so the schema would be
+    /// rejected.
+    Invalid,
+}
+
+/// A keyword whose meaning differs under another draft, see [`draft_compatibility`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Incompatibility {
+    location: Location,
+    keyword: String,
+    kind: IncompatibilityKind,
+    message: String,
+}
+
+impl Incompatibility {
+    /// The location of the schema containing the keyword.
+    #[must_use]
+    pub fn location(&self) -> &Location {
+        &self.location
+    }
+    /// The affected keyword.
+    #[must_use]
+    pub fn keyword(&self) -> &str {
+        &self.keyword
+    }
+    /// How the keyword is affected.
+    #[must_use]
+    pub fn kind(&self) -> IncompatibilityKind {
+        self.kind
+    }
+    /// A description of the difference between the drafts.
+    #[must_use]
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for Incompatibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{}: {}", self.location, self.message)
+    }
+}
+
+/// List the keywords of `schema` whose meaning would differ if it was interpreted as `target`.
+///
+/// The current draft of the schema and of its embedded resources is detected from `$schema`,
+/// defaulting to Draft 2020-12. Reported are keywords that only one of the drafts defines, e.g.
+/// `dependencies`, which 2019-09 replaces, the array form of `items` and the boolean
+/// `exclusiveMaximum` of Draft 4, keywords next to `$ref`, and `format` and the `content*`
+/// keywords, which are only annotations by default since 2019-09. References are not followed,
+/// so referenced external schemas should be checked separately.
+///
+/// # Errors
+///
+/// Returns an error if `$schema` refers to an unknown or disabled draft.
+pub fn draft_compatibility(schema: &Value, target: Draft) -> Result<Vec<Incompatibility>, Error> {
+    let mut incompatibilities = Vec::new();
+    check(
+        schema,
+        Draft::default(),
+        target,
+        &Location::new(),
+        &mut incompatibilities,
+    )?;
+    Ok(incompatibilities)
+}
+
+fn check(
+    schema: &Value,
+    draft: Draft,
+    target: Draft,
+    location: &Location,
+    incompatibilities: &mut Vec<Incompatibility>,
+) -> Result<(), Error> {
+    let Value::Object(object) = schema else {
+        return Ok(());
+    };
+    let draft = draft.detect(schema)?;
+    if draft != target {
+        for (keyword, value) in object {
+            if let Some((kind, message)) = compare(object, keyword, value, draft, target) {
+                incompatibilities.push(Incompatibility {
+                    location: location.clone(),
+                    keyword: keyword.clone(),
+                    kind,
+                    message,
+                });
+            }
+        }
+    }
+    for (keyword, value) in object {
+        let keyword = keyword.as_str();
+        match value {
+            Value::Object(_) | Value::Bool(_) if SCHEMA_KEYWORDS.contains(&keyword) => {
+                check(
+                    value,
+                    draft,
+                    target,
+                    &location.join(keyword),
+                    incompatibilities,
+                )?;
+            }
+            Value::Array(items) if SCHEMA_ARRAY_KEYWORDS.contains(&keyword) => {
+                let location = location.join(keyword);
+                for (idx, item) in items.iter().enumerate() {
+                    check(item, draft, target, &location.join(idx), incompatibilities)?;
+                }
+            }
+            Value::Object(schemas) if SCHEMA_MAP_KEYWORDS.contains(&keyword) => {
+                let location = location.join(keyword);
+                for (name, schema) in schemas {
+                    check(
+                        schema,
+                        draft,
+                        target,
+                        &location.join(name),
+                        incompatibilities,
+                    )?;
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// How `keyword` in `object` is affected by moving from `draft` to `target`, if at all.
+fn compare(
+    object: &Map<String, Value>,
+    keyword: &str,
+    value: &Value,
+    draft: Draft,
+    target: Draft,
+) -> Option<(IncompatibilityKind, String)> {
+    let (from, to) = (draft_name(draft), draft_name(target));
+    match keyword {
+        "exclusiveMaximum" | "exclusiveMinimum" => {
+            let bound = if keyword == "exclusiveMaximum" {
+                "maximum"
+            } else {
+                "minimum"
+            };
+            match value {
+                Value::Bool(_) if draft == Draft::Draft4 => Some((
+                    IncompatibilityKind::Invalid,
+                    format!(
+                        "`{keyword}` is a number in {to}, instead of a boolean modifying `{bound}`"
+                    ),
+                )),
+                Value::Number(_) if target == Draft::Draft4 && draft != Draft::Draft4 => Some((
+                    IncompatibilityKind::Invalid,
+                    format!("`{keyword}` is a boolean modifying `{bound}` in {to}"),
+                )),
+                _ => None,
+            }
+        }
+        "items" => {
+            if value.is_array() && draft < Draft::Draft202012 && target >= Draft::Draft202012 {
+                Some((
+                    IncompatibilityKind::Invalid,
+                    format!(
+                        "`items` with an array of schemas is replaced by `prefixItems` in {to}"
+                    ),
+                ))
+            } else if !value.is_array()
+                && object.contains_key("prefixItems")
+                && draft >= Draft::Draft202012
+                && target < Draft::Draft202012
+            {
+                Some((
+                    IncompatibilityKind::Changed,
+                    format!("`items` applies to all items in {to}, not only to those after `prefixItems`"),
+                ))
+            } else {
+                None
+            }
+        }
+        "$ref" => {
+            let applies_siblings = |draft: Draft| draft >= Draft::Draft201909;
+            if applies_siblings(draft) == applies_siblings(target)
+                || object
+                    .keys()
+                    .all(|keyword| keyword == "$ref" || REF_SIBLINGS.contains(&keyword.as_str()))
+            {
+                None
+            } else if applies_siblings(target) {
+                Some((
+                    IncompatibilityKind::Changed,
+                    format!("Keywords next to `$ref` are ignored in {from}, but apply in {to}"),
+                ))
+            } else {
+                Some((
+                    IncompatibilityKind::Changed,
+                    format!("Keywords next to `$ref` apply in {from}, but are ignored in {to}"),
+                ))
+            }
+        }
+        "format" => {
+            let asserts = |draft: Draft| draft <= Draft::Draft7;
+            if asserts(draft) == asserts(target) {
+                None
+            } else if asserts(target) {
+                Some((
+                    IncompatibilityKind::Changed,
+                    format!("`format` is validated by default in {to}"),
+                ))
+            } else {
+                Some((
+                    IncompatibilityKind::Changed,
+                    format!("`format` is only an annotation by default in {to}"),
+                ))
+            }
+        }
+        "contentEncoding" | "contentMediaType" => {
+            let asserts = |draft: Draft| matches!(draft, Draft::Draft6 | Draft::Draft7);
+            if asserts(draft) == asserts(target) {
+                None
+            } else if asserts(target) {
+                Some((
+                    IncompatibilityKind::Changed,
+                    format!("`{keyword}` is validated in {to}"),
+                ))
+            } else {
+                Some((
+                    IncompatibilityKind::Changed,
+                    format!("`{keyword}` is only an annotation in {to}"),
+                ))
+            }
+        }
+        _ => {
+            let support = SUPPORT.iter().find(|support| support.keyword == keyword)?;
+            if support.is_defined_in(draft) == support.is_defined_in(target) {
+                return None;
+            }
+            if support.is_defined_in(target) {
+                return Some((
+                    IncompatibilityKind::Changed,
+                    format!("`{keyword}` is ignored in {from}, but applies in {to}"),
+                ));
+            }
+            let message = match support.replaced {
+                Some((until, replacement)) if target >= until => {
+                    format!("`{keyword}` is replaced by {replacement} in {to}")
+                }
+                _ => format!(
+                    "`{keyword}` is not supported before {}",
+                    draft_name(support.since)
+                ),
+            };
+            Some((IncompatibilityKind::Ignored, message))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use test_case::test_case;
+
+    fn summary(schema: &Value, target: Draft) -> Vec<(String, String, IncompatibilityKind)> {
+        draft_compatibility(schema, target)
+            .expect("Analysis failed")
+            .into_iter()
+            .map(|incompatibility| {
+                (
+                    incompatibility.location().as_str().to_string(),
+                    incompatibility.keyword().to_string(),
+                    incompatibility.kind(),
+                )
+            })
+            .collect()
+    }
+
+    #[test_case(&json!({"$schema": "http://json-schema.org/draft-04/schema#", "maximum": 5, "exclusiveMaximum": true}), Draft::Draft7, "exclusiveMaximum", IncompatibilityKind::Invalid)]
+    #[test_case(&json!({"$schema": "http://json-schema.org/draft-07/schema#", "exclusiveMinimum": 5}), Draft::Draft4, "exclusiveMinimum", IncompatibilityKind::Invalid)]
+    #[test_case(&json!({"$schema": "http://json-schema.org/draft-07/schema#", "items": [{}]}), Draft::Draft202012, "items", IncompatibilityKind::Invalid)]
+    #[test_case(&json!({"prefixItems": [{}], "items": false}), Draft::Draft7, "items", IncompatibilityKind::Changed)]
+    #[test_case(&json!({"$schema": "http://json-schema.org/draft-07/schema#", "$ref": "#/definitions/a", "type": "object"}), Draft::Draft201909, "$ref", IncompatibilityKind::Changed)]
+    #[test_case(&json!({"$schema": "http://json-schema.org/draft-07/schema#", "format": "email"}), Draft::Draft202012, "format", IncompatibilityKind::Changed)]
+    #[test_case(&json!({"$schema": "http://json-schema.org/draft-07/schema#", "contentEncoding": "base64"}), Draft::Draft201909, "contentEncoding", IncompatibilityKind::Changed)]
+    #[test_case(&json!({"$schema": "http://json-schema.org/draft-07/schema#", "dependencies": {"a": ["b"]}}), Draft::Draft201909, "dependencies", IncompatibilityKind::Ignored)]
+    #[test_case(&json!({"$schema": "http://json-schema.org/draft-04/schema#", "id": "http://example.com"}), Draft::Draft6, "id", IncompatibilityKind::Ignored)]
+    #[test_case(&json!({"$schema": "https://json-schema.org/draft/2019-09/schema", "$recursiveRef": "#"}), Draft::Draft202012, "$recursiveRef", IncompatibilityKind::Ignored)]
+    #[test_case(&json!({"if": {}, "then": {}}), Draft::Draft6, "if", IncompatibilityKind::Ignored)]
+    #[test_case(&json!({"dependentRequired": {"a": ["b"]}}), Draft::Draft7, "dependentRequired", IncompatibilityKind::Ignored)]
+    #[test_case(&json!({"$schema": "http://json-schema.org/draft-06/schema#", "if": {}}), Draft::Draft7, "if", IncompatibilityKind::Changed)]
+    fn incompatible(schema: &Value, target: Draft, keyword: &str, kind: IncompatibilityKind) {
+        let incompatibilities = summary(schema, target);
+        assert!(
+            incompatibilities.contains(&(String::new(), keyword.to_string(), kind)),
+            "{incompatibilities:?}"
+        );
+    }
+
+    #[test_case(&json!({"$schema": "http://json-schema.org/draft-04/schema#", "maximum": 5, "exclusiveMaximum": true}), Draft::Draft4; "same draft")]
+    #[test_case(&json!({"$schema": "http://json-schema.org/draft-07/schema#", "items": {"type": "string"}, "exclusiveMaximum": 5}), Draft::Draft202012; "schema items")]
+    #[test_case(&json!({"$schema": "http://json-schema.org/draft-07/schema#", "$ref": "#/definitions/a", "description": "A"}), Draft::Draft202012; "annotations next to ref")]
+    #[test_case(&json!({"$schema": "https://json-schema.org/draft/2019-09/schema", "format": "email", "dependentRequired": {}}), Draft::Draft202012; "format annotation in both")]
+    #[test_case(&json!({"items": {"type": "string"}}), Draft::Draft7; "items without prefixItems")]
+    fn compatible(schema: &Value, target: Draft) {
+        assert_eq!(summary(schema, target), []);
+    }
+
+    #[test]
+    fn nested_locations() {
+        let schema = json!({
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "properties": {"a/b": {"items": [{"const": 1}]}},
+            "definitions": {"c": {"anyOf": [{"if": true}]}}
+        });
+        assert_eq!(
+            summary(&schema, Draft::Draft6),
+            [(
+                "/definitions/c/anyOf/0".to_string(),
+                "if".to_string(),
+                IncompatibilityKind::Ignored
+            ),]
+        );
+        assert_eq!(
+            summary(&schema, Draft::Draft202012),
+            [(
+                "/properties/a~1b".to_string(),
+                "items".to_string(),
+                IncompatibilityKind::Invalid
+            )]
+        );
+    }
+
+    #[test]
+    fn embedded_resources_keep_their_draft() {
+        let schema = json!({
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "properties": {
+                "a": {
+                    "$id": "http://example.com/a",
+                    "$schema": "https://json-schema.org/draft/2020-12/schema",
+                    "items": [{}]
+                }
+            }
+        });
+        assert_eq!(summary(&schema, Draft::Draft202012), []);
+    }
+
+    #[test]
+    fn messages() {
+        let schema = json!({
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "dependencies": {"a": ["b"]},
+            "properties": {"x": {"minContains": 1}}
+        });
+        let messages: Vec<_> = draft_compatibility(&schema, Draft::Draft201909)
+            .expect("Analysis failed")
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            messages,
+            [
+                "#: `dependencies` is replaced by `dependentRequired` and `dependentSchemas` in Draft 2019-09",
+                "#/properties/x: `minContains` is ignored in Draft 7, but applies in Draft 2019-09"
+            ]
+        );
+        let schema = json!({"minContains": 1});
+        let incompatibilities =
+            draft_compatibility(&schema, Draft::Draft7).expect("Analysis failed");
+        assert_eq!(
+            incompatibilities[0].message(),
+            "`minContains` is not supported before Draft 2019-09"
+        );
+    }
+
+    #[test]
+    fn unknown_draft() {
+        let schema = json!({"$schema": "http://example.com/unknown"});
+        assert!(draft_compatibility(&schema, Draft::Draft7).is_err());
+    }
+}
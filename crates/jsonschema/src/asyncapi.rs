@@ -0,0 +1,123 @@
+//! Validation of AsyncAPI 2.x message payloads.
+//!
+//! AsyncAPI 2.x schema objects are a superset of JSON Schema Draft 7, so `contentEncoding` and
+//! `contentMediaType` are assertions and binary payloads encoded as base64 strings are checked.
+//! The additional `discriminator`, `externalDocs` and `deprecated` keywords are annotations.
+//!
+//! ```rust
+//! use serde_json::json;
+//!
+//! let payload = json!({
+//!     "type": "object",
+//!     "properties": {
+//!         "checksum": {"type": "string", "format": "byte"},
+//!         "body": {"type": "string", "contentEncoding": "base64", "contentMediaType": "application/json"}
+//!     }
+//! });
+//! let validator = jsonschema::asyncapi::options()
+//!     .build(&payload)
+//!     .expect("Invalid schema");
+//!
+//! assert!(validator.is_valid(&json!({"checksum": "AAEC", "body": "eyJhIjogMX0="})));
+//! assert!(!validator.is_valid(&json!({"checksum": "not base64!"})));
+//! ```
+use crate::{content_encoding::is_base64, Draft, ValidationOptions};
+
+/// Options for payload schemas in the default AsyncAPI schema format.
+///
+/// Payloads are validated as Draft 7, with the `byte` format checking for base64 strings.
+/// The `binary` format accepts any string.
+#[must_use]
+pub fn options() -> ValidationOptions {
+    crate::options()
+        .with_draft(Draft::Draft7)
+        .with_format("byte", is_base64)
+        .with_format("binary", |_| true)
+}
+
+/// Options for payload schemas declared with the given AsyncAPI `schemaFormat`.
+///
+/// Both the AsyncAPI schema format, e.g. `application/vnd.aai.asyncapi+json;version=2.6.0`, and
+/// JSON Schema formats, e.g. `application/schema+json;version=draft-07`, are supported. Returns
+/// `None` for other formats, such as Avro or RAML.
+///
+/// ```rust
+/// use jsonschema::asyncapi;
+///
+/// assert!(asyncapi::options_for("application/schema+yaml;version=2020-12").is_some());
+/// assert!(asyncapi::options_for("application/vnd.apache.avro;version=1.9.0").is_none());
+/// ```
+#[must_use]
+pub fn options_for(schema_format: &str) -> Option<ValidationOptions> {
+    let mut parts = schema_format.split(';');
+    let media_type = parts.next().unwrap_or_default().trim().to_ascii_lowercase();
+    let version = parts.find_map(|parameter| {
+        let (name, value) = parameter.split_once('=')?;
+        name.trim()
+            .eq_ignore_ascii_case("version")
+            .then(|| value.trim())
+    });
+    match media_type.as_str() {
+        "application/vnd.aai.asyncapi"
+        | "application/vnd.aai.asyncapi+json"
+        | "application/vnd.aai.asyncapi+yaml" => version
+            .map_or(true, |version| version.starts_with("2."))
+            .then(options),
+        "application/schema+json" | "application/schema+yaml" => {
+            let draft = match version? {
+                "draft-04" => Draft::Draft4,
+                "draft-06" => Draft::Draft6,
+                "draft-07" => Draft::Draft7,
+                "2019-09" => Draft::Draft201909,
+                "2020-12" => Draft::Draft202012,
+                _ => return None,
+            };
+            Some(crate::options().with_draft(draft))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use test_case::test_case;
+
+    use super::options_for;
+    use crate::Draft;
+
+    #[test_case("application/vnd.aai.asyncapi;version=2.6.0", Some(Draft::Draft7))]
+    #[test_case(
+        "application/vnd.aai.asyncapi+json; version=2.0.0",
+        Some(Draft::Draft7)
+    )]
+    #[test_case("application/vnd.aai.asyncapi+yaml", Some(Draft::Draft7))]
+    #[test_case("application/vnd.aai.asyncapi;version=3.0.0", None)]
+    #[test_case("application/schema+json;version=draft-04", Some(Draft::Draft4))]
+    #[test_case("Application/Schema+YAML;Version=2019-09", Some(Draft::Draft201909))]
+    #[test_case("application/schema+json", None)]
+    #[test_case("application/schema+json;version=draft-03", None)]
+    #[test_case("application/vnd.oai.openapi;version=3.0.0", None)]
+    fn schema_formats(schema_format: &str, expected: Option<Draft>) {
+        assert_eq!(
+            options_for(schema_format).map(|options| options.draft()),
+            expected
+        );
+    }
+
+    #[test]
+    fn binary_content() {
+        let validator = super::options()
+            .build(&json!({
+                "type": "string",
+                "contentEncoding": "base64",
+                "contentMediaType": "application/json"
+            }))
+            .expect("Invalid schema");
+        assert!(validator.is_valid(&json!("eyJhIjogMX0=")));
+        // Not base64
+        assert!(!validator.is_valid(&json!("{\"a\": 1}")));
+        // Not JSON
+        assert!(!validator.is_valid(&json!("YWJj")));
+    }
+}
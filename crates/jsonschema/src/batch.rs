@@ -0,0 +1,750 @@
+//! Aggregated results of validating many instances.
+use std::{
+    fmt,
+    io::{self, Write},
+};
+
+use ahash::AHashMap;
+use serde::{ser::SerializeMap, Serialize, Serializer};
+
+use crate::{error::ValidationErrorKind, Transformation, ValidationError};
+
+/// Results of validating many instances, with statistics over all of them.
+///
+/// Serialized as an object with `valid`, `statistics` and `instances` members, or via
+/// [`BatchReport::write_ndjson`] as one line per instance followed by a summary line.
+///
+/// # Example
+///
+/// ```rust
+/// use serde_json::json;
+///
+/// let validator = jsonschema::validator_for(&json!({"type": "integer"})).expect("Invalid schema");
+/// let instances = [json!(1), json!("a"), json!(2)];
+/// let report = validator.validate_batch(
+///     instances
+///         .iter()
+///         .enumerate()
+///         .map(|(idx, instance)| (format!("#{idx}"), instance)),
+/// );
+///
+/// assert!(!report.is_valid());
+/// assert_eq!(report.statistics().invalid, 1);
+/// assert_eq!(report.instances()[1].errors()[0].error(), "\"a\" is not of type \"integer\"");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BatchReport {
+    instances: Vec<InstanceOutcome>,
+    statistics: BatchStatistics,
+}
+
+impl BatchReport {
+    /// Create an empty report.
+    #[must_use]
+    pub fn new() -> BatchReport {
+        BatchReport::default()
+    }
+    /// Add the outcome of an instance.
+    pub fn push(&mut self, outcome: InstanceOutcome) {
+        self.statistics.instances += 1;
+        self.statistics.errors += outcome.errors.len();
+        if outcome.is_valid() {
+            self.statistics.valid += 1;
+        } else {
+            self.statistics.invalid += 1;
+        }
+        if outcome.schema_error.is_some() {
+            self.statistics.schema_errors += 1;
+        }
+        self.instances.push(outcome);
+    }
+    /// Whether all instances are valid.
+    #[must_use]
+    pub fn is_valid(&self) -> bool {
+        self.statistics.invalid == 0
+    }
+    /// Outcomes of all instances, in the order they were added.
+    #[must_use]
+    pub fn instances(&self) -> &[InstanceOutcome] {
+        &self.instances
+    }
+    /// Statistics over all instances.
+    #[must_use]
+    pub fn statistics(&self) -> BatchStatistics {
+        self.statistics
+    }
+    /// Write one JSON line per instance, followed by a line with `valid` and `statistics`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing fails.
+    pub fn write_ndjson(&self, mut writer: impl Write) -> io::Result<()> {
+        for outcome in &self.instances {
+            serde_json::to_writer(&mut writer, outcome)?;
+            writer.write_all(b"\n")?;
+        }
+        serde_json::to_writer(
+            &mut writer,
+            &serde_json::json!({"valid": self.is_valid(), "statistics": self.statistics}),
+        )?;
+        writer.write_all(b"\n")
+    }
+    /// Compare the reports of two validation runs over the same instances, e.g. before and after
+    /// a schema change.
+    ///
+    /// Instances are matched by their instance and schema identifiers. Errors are matched by
+    /// their instance and keyword locations, so an error whose message changed, e.g. because a
+    /// bound was adjusted, is neither introduced nor fixed.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    ///
+    /// let instances = [json!(1), json!(5), json!(15)];
+    /// let run = |schema| {
+    ///     jsonschema::validator_for(&schema)
+    ///         .expect("Invalid schema")
+    ///         .validate_batch(["a.json", "b.json", "c.json"].into_iter().zip(instances.iter()))
+    /// };
+    /// let old = run(json!({"maximum": 10}));
+    /// let new = run(json!({"maximum": 10, "minimum": 3}));
+    ///
+    /// let diff = jsonschema::BatchReport::diff(&old, &new);
+    /// assert_eq!(diff.newly_invalid().map(|diff| diff.instance()).collect::<Vec<_>>(), ["a.json"]);
+    /// assert_eq!(diff.introduced_errors(), 1);
+    /// assert_eq!(diff.fixed_errors(), 0);
+    /// assert_eq!(
+    ///     diff.to_string(),
+    ///     "1 previously valid instance is now invalid, 0 previously invalid instances are now valid; 1 error introduced, 0 errors fixed"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn diff(old: &BatchReport, new: &BatchReport) -> BatchDiff {
+        let mut previous: AHashMap<(&str, Option<&str>), &InstanceOutcome> = old
+            .instances
+            .iter()
+            .map(|outcome| ((outcome.instance(), outcome.schema()), outcome))
+            .collect();
+        let mut diff = BatchDiff::default();
+        for outcome in &new.instances {
+            let Some(before) = previous.remove(&(outcome.instance(), outcome.schema())) else {
+                diff.added.push(outcome.instance.clone());
+                continue;
+            };
+            let instance = InstanceDiff {
+                instance: outcome.instance.clone(),
+                schema: outcome.schema.clone(),
+                was_valid: before.valid,
+                is_valid: outcome.valid,
+                introduced: difference(&outcome.errors, &before.errors),
+                fixed: difference(&before.errors, &outcome.errors),
+            };
+            if instance.was_valid != instance.is_valid
+                || !instance.introduced.is_empty()
+                || !instance.fixed.is_empty()
+            {
+                diff.instances.push(instance);
+            }
+        }
+        diff.removed = old
+            .instances
+            .iter()
+            .filter(|outcome| previous.contains_key(&(outcome.instance(), outcome.schema())))
+            .map(|outcome| outcome.instance.clone())
+            .collect();
+        diff
+    }
+}
+
+/// Errors in `left` that have no counterpart in `right`.
+fn difference(left: &[BatchError], right: &[BatchError]) -> Vec<BatchError> {
+    let mut remaining: AHashMap<_, usize> = AHashMap::new();
+    for error in right {
+        *remaining.entry(error.diff_key()).or_default() += 1;
+    }
+    left.iter()
+        .filter(|error| match remaining.get_mut(&error.diff_key()) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                false
+            }
+            _ => true,
+        })
+        .cloned()
+        .collect()
+}
+
+impl Extend<InstanceOutcome> for BatchReport {
+    fn extend<T: IntoIterator<Item = InstanceOutcome>>(&mut self, iter: T) {
+        for outcome in iter {
+            self.push(outcome);
+        }
+    }
+}
+
+impl FromIterator<InstanceOutcome> for BatchReport {
+    fn from_iter<T: IntoIterator<Item = InstanceOutcome>>(iter: T) -> BatchReport {
+        let mut report = BatchReport::new();
+        report.extend(iter);
+        report
+    }
+}
+
+impl Serialize for BatchReport {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(3))?;
+        map.serialize_entry("valid", &self.is_valid())?;
+        map.serialize_entry("statistics", &self.statistics)?;
+        map.serialize_entry("instances", &self.instances)?;
+        map.end()
+    }
+}
+
+/// Counts over all instances of a [`BatchReport`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchStatistics {
+    /// Number of instances.
+    pub instances: usize,
+    /// Number of valid instances.
+    pub valid: usize,
+    /// Number of invalid instances, including those whose schema is invalid.
+    pub invalid: usize,
+    /// Number of instances whose schema is invalid.
+    pub schema_errors: usize,
+    /// Number of errors over all instances.
+    pub errors: usize,
+}
+
+/// The validation outcome of a single instance within a [`BatchReport`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstanceOutcome {
+    instance: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    schema: Option<String>,
+    valid: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    errors: Vec<BatchError>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    schema_error: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    schema_errors: Vec<BatchError>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    transformations: Vec<Transformation>,
+}
+
+impl InstanceOutcome {
+    /// The outcome of an instance identified by `instance`, e.g. its file name, with the given
+    /// errors. The instance is valid if there are none.
+    pub fn new(
+        instance: impl Into<String>,
+        errors: impl IntoIterator<Item = BatchError>,
+    ) -> InstanceOutcome {
+        let errors: Vec<_> = errors.into_iter().collect();
+        InstanceOutcome {
+            instance: instance.into(),
+            schema: None,
+            valid: errors.is_empty(),
+            errors,
+            schema_error: None,
+            schema_errors: Vec::new(),
+            transformations: Vec::new(),
+        }
+    }
+    /// The outcome of an instance that could not be validated because its schema is invalid.
+    pub fn invalid_schema(
+        instance: impl Into<String>,
+        error: impl Into<String>,
+    ) -> InstanceOutcome {
+        InstanceOutcome {
+            instance: instance.into(),
+            schema: None,
+            valid: false,
+            errors: Vec::new(),
+            schema_error: Some(error.into()),
+            schema_errors: Vec::new(),
+            transformations: Vec::new(),
+        }
+    }
+    /// Set the schema the instance was validated against, e.g. its file name.
+    #[must_use]
+    pub fn with_schema(mut self, schema: impl Into<String>) -> InstanceOutcome {
+        self.schema = Some(schema.into());
+        self
+    }
+    /// Record every error of an invalid schema, located within the schema, e.g. all errors of
+    /// the schema against its meta-schema.
+    #[must_use]
+    pub fn with_schema_errors(
+        mut self,
+        errors: impl IntoIterator<Item = BatchError>,
+    ) -> InstanceOutcome {
+        self.schema_errors.extend(errors);
+        self
+    }
+    /// Record the transformations applied to the instance before validation, see
+    /// [`ValidationOptions::with_preprocessor`](crate::ValidationOptions::with_preprocessor).
+    #[must_use]
+    pub fn with_transformations(
+        mut self,
+        transformations: impl IntoIterator<Item = Transformation>,
+    ) -> InstanceOutcome {
+        self.transformations.extend(transformations);
+        self
+    }
+    /// The instance identifier.
+    #[must_use]
+    pub fn instance(&self) -> &str {
+        &self.instance
+    }
+    /// The schema identifier, if set.
+    #[must_use]
+    pub fn schema(&self) -> Option<&str> {
+        self.schema.as_deref()
+    }
+    /// Whether the instance is valid.
+    #[must_use]
+    pub fn is_valid(&self) -> bool {
+        self.valid
+    }
+    /// Errors of the instance.
+    #[must_use]
+    pub fn errors(&self) -> &[BatchError] {
+        &self.errors
+    }
+    /// The error of the schema, if it is invalid.
+    #[must_use]
+    pub fn schema_error(&self) -> Option<&str> {
+        self.schema_error.as_deref()
+    }
+    /// Every error of the schema, if it is invalid and they were recorded.
+    #[must_use]
+    pub fn schema_errors(&self) -> &[BatchError] {
+        &self.schema_errors
+    }
+    /// Transformations applied to the instance before validation.
+    #[must_use]
+    pub fn transformations(&self) -> &[Transformation] {
+        &self.transformations
+    }
+}
+
+/// Changes between two [`BatchReport`]s, see [`BatchReport::diff`].
+///
+/// Displayed as a one-line summary of the changes.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct BatchDiff {
+    instances: Vec<InstanceDiff>,
+    added: Vec<String>,
+    removed: Vec<String>,
+}
+
+impl BatchDiff {
+    /// Instances present in both reports whose validity or errors changed, in the order of the
+    /// new report.
+    #[must_use]
+    pub fn instances(&self) -> &[InstanceDiff] {
+        &self.instances
+    }
+    /// Instances that were valid and no longer are.
+    pub fn newly_invalid(&self) -> impl Iterator<Item = &InstanceDiff> {
+        self.instances
+            .iter()
+            .filter(|instance| instance.was_valid && !instance.is_valid)
+    }
+    /// Instances that were invalid and now are valid.
+    pub fn newly_valid(&self) -> impl Iterator<Item = &InstanceDiff> {
+        self.instances
+            .iter()
+            .filter(|instance| !instance.was_valid && instance.is_valid)
+    }
+    /// Number of errors only in the new report.
+    #[must_use]
+    pub fn introduced_errors(&self) -> usize {
+        self.instances
+            .iter()
+            .map(|instance| instance.introduced.len())
+            .sum()
+    }
+    /// Number of errors only in the old report.
+    #[must_use]
+    pub fn fixed_errors(&self) -> usize {
+        self.instances
+            .iter()
+            .map(|instance| instance.fixed.len())
+            .sum()
+    }
+    /// Instances only in the new report.
+    #[must_use]
+    pub fn added(&self) -> &[String] {
+        &self.added
+    }
+    /// Instances only in the old report.
+    #[must_use]
+    pub fn removed(&self) -> &[String] {
+        &self.removed
+    }
+    /// Whether both reports have the same outcomes for the same instances.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.instances.is_empty() && self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+impl fmt::Display for BatchDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let plural = |count: usize, singular: &'static str, plural: &'static str| {
+            if count == 1 {
+                singular
+            } else {
+                plural
+            }
+        };
+        let invalid = self.newly_invalid().count();
+        let valid = self.newly_valid().count();
+        let introduced = self.introduced_errors();
+        let fixed = self.fixed_errors();
+        write!(
+            f,
+            "{invalid} previously valid {} now invalid, {valid} previously invalid {} now valid; \
+             {introduced} {} introduced, {fixed} {} fixed",
+            plural(invalid, "instance is", "instances are"),
+            plural(valid, "instance is", "instances are"),
+            plural(introduced, "error", "errors"),
+            plural(fixed, "error", "errors"),
+        )
+    }
+}
+
+/// Changes of a single instance within a [`BatchDiff`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstanceDiff {
+    instance: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    schema: Option<String>,
+    was_valid: bool,
+    is_valid: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    introduced: Vec<BatchError>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    fixed: Vec<BatchError>,
+}
+
+impl InstanceDiff {
+    /// The instance identifier.
+    #[must_use]
+    pub fn instance(&self) -> &str {
+        &self.instance
+    }
+    /// The schema identifier, if set.
+    #[must_use]
+    pub fn schema(&self) -> Option<&str> {
+        self.schema.as_deref()
+    }
+    /// Whether the instance was valid in the old report.
+    #[must_use]
+    pub fn was_valid(&self) -> bool {
+        self.was_valid
+    }
+    /// Whether the instance is valid in the new report.
+    #[must_use]
+    pub fn is_valid(&self) -> bool {
+        self.is_valid
+    }
+    /// Errors only in the new report.
+    #[must_use]
+    pub fn introduced(&self) -> &[BatchError] {
+        &self.introduced
+    }
+    /// Errors only in the old report.
+    #[must_use]
+    pub fn fixed(&self) -> &[BatchError] {
+        &self.fixed
+    }
+}
+
+/// A single error within an [`InstanceOutcome`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchError {
+    instance_location: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    keyword_location: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    keyword: Option<String>,
+    error: String,
+}
+
+impl BatchError {
+    /// An error at `instance_location`, optionally caused by the schema keyword at
+    /// `keyword_location`.
+    pub fn new(
+        instance_location: impl Into<String>,
+        keyword_location: Option<String>,
+        error: impl Into<String>,
+    ) -> BatchError {
+        BatchError {
+            instance_location: instance_location.into(),
+            keyword_location,
+            keyword: None,
+            error: error.into(),
+        }
+    }
+    /// Set the name of the schema keyword that caused the error, e.g. `type`.
+    #[must_use]
+    pub fn with_keyword(mut self, keyword: impl Into<String>) -> BatchError {
+        self.keyword = Some(keyword.into());
+        self
+    }
+    /// Replace the error message, e.g. with a translated one.
+    #[must_use]
+    pub fn with_error(mut self, error: impl Into<String>) -> BatchError {
+        self.error = error.into();
+        self
+    }
+    /// JSON Pointer to the invalid part of the instance.
+    #[must_use]
+    pub fn instance_location(&self) -> &str {
+        &self.instance_location
+    }
+    /// JSON Pointer to the schema keyword that caused the error, if any.
+    #[must_use]
+    pub fn keyword_location(&self) -> Option<&str> {
+        self.keyword_location.as_deref()
+    }
+    /// The name of the schema keyword that caused the error, if any.
+    ///
+    /// For validation errors, this is [`ValidationErrorKind::name`], which differs from the
+    /// keyword where it produces several kinds of errors, e.g. `oneOfNotValid`.
+    #[must_use]
+    pub fn keyword(&self) -> Option<&str> {
+        self.keyword.as_deref()
+    }
+    /// The error message.
+    #[must_use]
+    pub fn error(&self) -> &str {
+        &self.error
+    }
+    /// What identifies the error across validation runs. Errors without a keyword location, e.g.
+    /// parse errors, are identified by their message.
+    fn diff_key(&self) -> (&str, Option<&str>, Option<&str>) {
+        (
+            &self.instance_location,
+            self.keyword_location.as_deref(),
+            self.keyword_location
+                .is_none()
+                .then_some(self.error.as_str()),
+        )
+    }
+}
+
+impl From<&ValidationError<'_>> for BatchError {
+    fn from(error: &ValidationError<'_>) -> BatchError {
+        let batch_error = BatchError::new(
+            error.instance_path.as_str(),
+            Some(error.schema_path.as_str().to_string()),
+            error.to_string(),
+        );
+        // `false` subschemas have no keyword
+        if matches!(error.kind, ValidationErrorKind::FalseSchema) {
+            return batch_error;
+        }
+        batch_error.with_keyword(error.kind.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    use super::{BatchError, BatchReport, BatchStatistics, InstanceOutcome};
+
+    fn outcome(instance: &str, errors: &[(&str, &str)]) -> InstanceOutcome {
+        InstanceOutcome::new(
+            instance,
+            errors.iter().map(|(location, message)| {
+                BatchError::new("", Some((*location).to_string()), *message)
+            }),
+        )
+    }
+
+    #[test]
+    fn statistics_and_serialization() {
+        let validator = crate::validator_for(&json!({"type": "object", "required": ["a", "b"]}))
+            .expect("Invalid schema");
+        let instances = [json!({"a": 1, "b": 2}), json!({}), json!(1)];
+        let mut report = validator.validate_batch(
+            ["valid.json", "missing.json", "type.json"]
+                .into_iter()
+                .zip(instances.iter()),
+        );
+        report.push(
+            InstanceOutcome::invalid_schema("other.json", "Invalid schema").with_schema("s.json"),
+        );
+        assert!(!report.is_valid());
+        assert_eq!(
+            report.statistics(),
+            BatchStatistics {
+                instances: 4,
+                valid: 1,
+                invalid: 3,
+                schema_errors: 1,
+                errors: 3,
+            }
+        );
+        assert_eq!(
+            serde_json::to_value(&report).expect("Serializable"),
+            json!({
+                "valid": false,
+                "statistics": {"instances": 4, "valid": 1, "invalid": 3, "schemaErrors": 1, "errors": 3},
+                "instances": [
+                    {"instance": "valid.json", "valid": true},
+                    {"instance": "missing.json", "valid": false, "errors": [
+                        {"instanceLocation": "", "keywordLocation": "/required", "keyword": "required", "error": "\"a\" is a required property"},
+                        {"instanceLocation": "", "keywordLocation": "/required", "keyword": "required", "error": "\"b\" is a required property"}
+                    ]},
+                    {"instance": "type.json", "valid": false, "errors": [
+                        {"instanceLocation": "", "keywordLocation": "/type", "keyword": "type", "error": "1 is not of type \"object\""}
+                    ]},
+                    {"instance": "other.json", "schema": "s.json", "valid": false, "schemaError": "Invalid schema"}
+                ]
+            })
+        );
+    }
+
+    #[test_case(&json!({"minLength": 2}), &json!("a"), Some("minLength"); "keyword")]
+    #[test_case(&json!({"$ref": "#/$defs/a", "$defs": {"a": {"minLength": 2}}}), &json!("a"), Some("minLength"); "reference")]
+    #[test_case(&json!({"properties": {"a": false}}), &json!({"a": 1}), None; "false schema")]
+    #[test_case(&json!({"dependentRequired": {"a": ["b"]}}), &json!({"a": 1}), Some("dependentRequired"); "dependent required")]
+    #[test_case(&json!({"properties": {"type": {"const": 1}}}), &json!({"type": 2}), Some("const"); "property named like a keyword")]
+    fn keyword(schema: &Value, instance: &Value, expected: Option<&str>) {
+        let validator = crate::validator_for(schema).expect("Invalid schema");
+        let error = validator.validate(instance).expect_err("Should fail");
+        assert_eq!(BatchError::from(&error).keyword(), expected);
+    }
+
+    #[test]
+    fn ndjson() {
+        let report: BatchReport = [
+            InstanceOutcome::new("a", []),
+            InstanceOutcome::new("b", [BatchError::new("/x", None, "Duplicate key")]),
+        ]
+        .into_iter()
+        .collect();
+        let mut output = Vec::new();
+        report.write_ndjson(&mut output).expect("Writable");
+        assert_eq!(
+            String::from_utf8(output).expect("UTF-8"),
+            concat!(
+                "{\"instance\":\"a\",\"valid\":true}\n",
+                "{\"instance\":\"b\",\"valid\":false,\"errors\":[{\"instanceLocation\":\"/x\",\"error\":\"Duplicate key\"}]}\n",
+                "{\"statistics\":{\"errors\":1,\"instances\":2,\"invalid\":1,\"schemaErrors\":0,\"valid\":1},\"valid\":false}\n"
+            )
+        );
+    }
+
+    #[test]
+    fn transformations() {
+        let validator = crate::options()
+            .with_preprocessor(crate::TrimStrings)
+            .build(&json!({"items": {"maxLength": 1}}))
+            .expect("Invalid schema");
+        let instance = json!([" a", "b"]);
+        let report = validator.validate_batch([("a.json", &instance)]);
+        assert!(report.is_valid());
+        assert_eq!(
+            serde_json::to_value(&report.instances()[0]).expect("Serializable"),
+            json!({
+                "instance": "a.json",
+                "valid": true,
+                "transformations": [
+                    {"path": "/0", "preprocessor": "trimStrings", "original": " a", "processed": "a"}
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn empty() {
+        let report = BatchReport::new();
+        assert!(report.is_valid());
+        assert_eq!(report.statistics(), BatchStatistics::default());
+    }
+
+    #[test]
+    fn diff() {
+        let old: BatchReport = [
+            outcome(
+                "same.json",
+                &[("/maximum", "11 is greater than the maximum of 10")],
+            ),
+            outcome("broken.json", &[]),
+            outcome("fixed.json", &[("/type", "1 is not of type \"string\"")]),
+            outcome("changed.json", &[("/minLength", "a"), ("/minLength", "b")]),
+            outcome("removed.json", &[]),
+        ]
+        .into_iter()
+        .collect();
+        let new: BatchReport = [
+            outcome(
+                "same.json",
+                &[("/maximum", "11 is greater than the maximum of 9")],
+            ),
+            outcome(
+                "broken.json",
+                &[("/required", "\"a\" is a required property")],
+            ),
+            outcome("fixed.json", &[]),
+            outcome("changed.json", &[("/minLength", "a"), ("/pattern", "c")]),
+            outcome("added.json", &[]),
+            InstanceOutcome::invalid_schema("removed.json", "Invalid schema").with_schema("s.json"),
+        ]
+        .into_iter()
+        .collect();
+        let diff = BatchReport::diff(&old, &new);
+        let instances: Vec<_> = diff
+            .instances()
+            .iter()
+            .map(|instance| {
+                (
+                    instance.instance(),
+                    instance.introduced().len(),
+                    instance.fixed().len(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            instances,
+            [
+                ("broken.json", 1, 0),
+                ("fixed.json", 0, 1),
+                ("changed.json", 1, 1)
+            ]
+        );
+        assert_eq!(diff.newly_invalid().count(), 1);
+        assert_eq!(diff.newly_valid().count(), 1);
+        assert_eq!(diff.added(), ["added.json", "removed.json"]);
+        assert_eq!(diff.removed(), ["removed.json"]);
+        assert_eq!(
+            diff.to_string(),
+            "1 previously valid instance is now invalid, 1 previously invalid instance is now valid; 2 errors introduced, 2 errors fixed"
+        );
+        assert_eq!(
+            serde_json::to_value(&diff).expect("Serializable")["instances"][0],
+            json!({
+                "instance": "broken.json",
+                "wasValid": true,
+                "isValid": false,
+                "introduced": [{"instanceLocation": "", "keywordLocation": "/required", "error": "\"a\" is a required property"}]
+            })
+        );
+        assert!(BatchReport::diff(&new, &new).is_empty());
+    }
+}
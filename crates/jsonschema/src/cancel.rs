@@ -0,0 +1,64 @@
+//! Cooperative cancellation of a running validation.
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+/// A handle for aborting validation that is in progress.
+///
+/// The token is checked before evaluating each subschema by [`Validator::validate_with_cancel`]
+/// and can be triggered either explicitly via [`CancellationToken::cancel`] from any thread,
+/// or implicitly once its deadline passes.
+///
+/// Cloned tokens share the same state and compare equal.
+///
+/// [`Validator::validate_with_cancel`]: crate::Validator::validate_with_cancel
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+    deadline: Option<Instant>,
+}
+
+impl CancellationToken {
+    /// Create a token that is cancelled only explicitly.
+    #[must_use]
+    pub fn new() -> CancellationToken {
+        CancellationToken::default()
+    }
+    /// Create a token that is cancelled once `deadline` passes.
+    #[must_use]
+    pub fn with_deadline(deadline: Instant) -> CancellationToken {
+        CancellationToken {
+            cancelled: Arc::default(),
+            deadline: Some(deadline),
+        }
+    }
+    /// Create a token that is cancelled once `timeout` elapses from now.
+    #[must_use]
+    pub fn with_timeout(timeout: Duration) -> CancellationToken {
+        CancellationToken::with_deadline(Instant::now() + timeout)
+    }
+    /// Request cancellation of all validations using this token.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+    /// Whether cancellation was requested or the deadline has passed.
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+            || self
+                .deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+    }
+}
+
+impl PartialEq for CancellationToken {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.cancelled, &other.cancelled) && self.deadline == other.deadline
+    }
+}
+
+impl Eq for CancellationToken {}
@@ -0,0 +1,357 @@
+//! Schema-aware canonical forms of instances.
+//!
+//! Instances that mean the same under a schema, e.g. `{"id": "0A1B…", "n": 1.0}` and
+//! `{"n": 1, "id": "0a1b…"}` for a schema declaring `id` as a UUID, serialize to identical text
+//! once canonicalized, so they can be hashed or signed.
+use std::{
+    cell::RefCell,
+    net::{Ipv4Addr, Ipv6Addr},
+    str::FromStr,
+};
+
+use ahash::AHashMap;
+use serde_json::{Map, Number, Value};
+
+use crate::{
+    ecma,
+    keywords::format::{is_valid_datetime, is_valid_hostname, is_valid_uuid},
+    regex::build_fancy_regex,
+};
+
+/// Bring `instance` into a canonical form according to `schema`, in place.
+///
+/// - Object keys are sorted.
+/// - Integral numbers are integers, e.g. `1.0` becomes `1` and `-0.0` becomes `0`.
+/// - Strings with a `uuid` or `hostname` format are lowercased, strings with a `date-time` format
+///   use uppercase `T` and `Z`, and `ipv4` / `ipv6` addresses are written in their shortest form.
+///   Strings that are not valid for their format are left as is.
+/// - Strings with a JSON `contentMediaType` hold their content in canonical form, without
+///   insignificant whitespace. `contentSchema` applies to that content.
+///
+/// Subschemas are found via `properties`, `patternProperties`, `additionalProperties`, `items`,
+/// `prefixItems`, `additionalItems`, `allOf` and references within the schema document. Branches
+/// of `anyOf`, `oneOf` and conditionals are not followed, as they may not apply to the instance.
+/// The instance is not validated.
+///
+/// # Example
+///
+/// ```rust
+/// use serde_json::json;
+///
+/// let schema = json!({
+///     "properties": {
+///         "id": {"type": "string", "format": "uuid"},
+///         "payload": {"type": "string", "contentMediaType": "application/json"}
+///     }
+/// });
+/// let mut left = json!({
+///     "id": "67E55044-10B1-426F-9247-BB680E5FE0C8",
+///     "count": 2.0,
+///     "payload": "{ \"b\": 1, \"a\": [ 1.0 ] }"
+/// });
+/// let mut right = json!({
+///     "payload": "{\"a\":[1],\"b\":1}",
+///     "count": 2,
+///     "id": "67e55044-10b1-426f-9247-bb680e5fe0c8"
+/// });
+/// jsonschema::canonicalize(&schema, &mut left);
+/// jsonschema::canonicalize(&schema, &mut right);
+/// assert_eq!(left.to_string(), right.to_string());
+/// ```
+pub fn canonicalize(schema: &Value, instance: &mut Value) {
+    let walker = Walker {
+        root: schema,
+        regexes: RefCell::new(AHashMap::new()),
+    };
+    let schemas = walker.applicable([schema]);
+    walker.canonicalize(&schemas, instance);
+}
+
+/// Finds subschemas within a single schema document.
+struct Walker<'s> {
+    root: &'s Value,
+    /// Compiled `patternProperties` patterns, `None` for unsupported ones
+    regexes: RefCell<AHashMap<&'s str, Option<fancy_regex::Regex>>>,
+}
+
+impl<'s> Walker<'s> {
+    /// Object subschemas applying to the same instance as `schemas`, including `schemas`.
+    fn applicable(
+        &self,
+        schemas: impl IntoIterator<Item = &'s Value>,
+    ) -> Vec<&'s Map<String, Value>> {
+        let mut applicable = Vec::new();
+        for schema in schemas {
+            self.collect(schema, &mut applicable);
+        }
+        applicable
+    }
+
+    fn collect(&self, schema: &'s Value, applicable: &mut Vec<&'s Map<String, Value>>) {
+        let Value::Object(object) = schema else {
+            return;
+        };
+        // Also stops reference cycles
+        if applicable.iter().any(|seen| std::ptr::eq(*seen, object)) {
+            return;
+        }
+        applicable.push(object);
+        if let Some(Value::Array(schemas)) = object.get("allOf") {
+            for schema in schemas {
+                self.collect(schema, applicable);
+            }
+        }
+        if let Some(target) = object
+            .get("$ref")
+            .and_then(Value::as_str)
+            .and_then(|reference| self.resolve(reference))
+        {
+            self.collect(target, applicable);
+        }
+    }
+
+    /// Resolve a reference to a JSON Pointer within the schema document.
+    fn resolve(&self, reference: &str) -> Option<&'s Value> {
+        let pointer = reference.strip_prefix('#')?;
+        let pointer = percent_encoding::percent_decode_str(pointer)
+            .decode_utf8()
+            .ok()?;
+        self.root.pointer(&pointer)
+    }
+
+    fn property_schemas(
+        &self,
+        schemas: &[&'s Map<String, Value>],
+        property: &str,
+    ) -> Vec<&'s Map<String, Value>> {
+        let mut subschemas = Vec::new();
+        for schema in schemas {
+            let mut matched = false;
+            if let Some(subschema) = schema
+                .get("properties")
+                .and_then(|properties| properties.get(property))
+            {
+                matched = true;
+                self.collect(subschema, &mut subschemas);
+            }
+            if let Some(Value::Object(patterns)) = schema.get("patternProperties") {
+                for (pattern, subschema) in patterns {
+                    if self.is_match(pattern, property) {
+                        matched = true;
+                        self.collect(subschema, &mut subschemas);
+                    }
+                }
+            }
+            if let (false, Some(subschema)) = (matched, schema.get("additionalProperties")) {
+                self.collect(subschema, &mut subschemas);
+            }
+        }
+        subschemas
+    }
+
+    fn is_match(&self, pattern: &'s str, property: &str) -> bool {
+        self.regexes
+            .borrow_mut()
+            .entry(pattern)
+            .or_insert_with(|| {
+                ecma::to_rust_regex(pattern)
+                    .ok()
+                    .and_then(|pattern| build_fancy_regex(&pattern, None, None, None, true).ok())
+            })
+            .as_ref()
+            .is_some_and(|regex| regex.is_match(property).unwrap_or(false))
+    }
+
+    fn item_schemas(
+        &self,
+        schemas: &[&'s Map<String, Value>],
+        index: usize,
+    ) -> Vec<&'s Map<String, Value>> {
+        let mut subschemas = Vec::new();
+        for schema in schemas {
+            let (prefix, rest) = match (schema.get("prefixItems"), schema.get("items")) {
+                (Some(Value::Array(prefix)), rest) => (prefix.as_slice(), rest),
+                // Before Draft 2020-12, an array of schemas in `items` is a tuple
+                (_, Some(Value::Array(prefix))) => {
+                    (prefix.as_slice(), schema.get("additionalItems"))
+                }
+                (_, rest) => (&[][..], rest),
+            };
+            if let Some(subschema) = prefix.get(index).or(rest) {
+                self.collect(subschema, &mut subschemas);
+            }
+        }
+        subschemas
+    }
+
+    fn canonicalize(&self, schemas: &[&'s Map<String, Value>], instance: &mut Value) {
+        match instance {
+            Value::Object(object) => {
+                let mut entries: Vec<_> = std::mem::take(object).into_iter().collect();
+                entries.sort_by(|(left, _), (right, _)| left.cmp(right));
+                for (property, value) in &mut entries {
+                    self.canonicalize(&self.property_schemas(schemas, property), value);
+                }
+                object.extend(entries);
+            }
+            Value::Array(items) => {
+                for (index, item) in items.iter_mut().enumerate() {
+                    self.canonicalize(&self.item_schemas(schemas, index), item);
+                }
+            }
+            Value::Number(number) => {
+                if let Some(integer) = as_integer(number) {
+                    *number = integer;
+                }
+            }
+            Value::String(string) => {
+                for schema in schemas {
+                    if let Some(format) = schema.get("format").and_then(Value::as_str) {
+                        if let Some(normalized) = normalize_format(format, string) {
+                            *string = normalized;
+                        }
+                    }
+                    if let Some(content) = self.canonicalize_content(schema, string) {
+                        *string = content;
+                    }
+                }
+            }
+            Value::Null | Value::Bool(_) => {}
+        }
+    }
+
+    /// The canonical form of JSON content embedded in a string.
+    fn canonicalize_content(&self, schema: &'s Map<String, Value>, string: &str) -> Option<String> {
+        let media_type = schema.get("contentMediaType")?.as_str()?;
+        // Encoded content is compared as is
+        if schema.contains_key("contentEncoding") || !is_json(media_type) {
+            return None;
+        }
+        let mut content: Value = serde_json::from_str(string).ok()?;
+        let schemas = self.applicable(schema.get("contentSchema"));
+        self.canonicalize(&schemas, &mut content);
+        Some(content.to_string())
+    }
+}
+
+/// Whether the media type is JSON, e.g. `application/json` or `application/geo+json`.
+fn is_json(media_type: &str) -> bool {
+    let media_type = media_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    media_type == "application/json" || media_type.ends_with("+json")
+}
+
+/// The integer with the same value as a float, if there is one.
+fn as_integer(number: &Number) -> Option<Number> {
+    if !number.is_f64() {
+        return None;
+    }
+    let value = number.as_f64()?;
+    if value.fract() != 0.0 {
+        return None;
+    }
+    // Bounds are powers of two, so they are exact as floats
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    if (-9_223_372_036_854_775_808.0..9_223_372_036_854_775_808.0).contains(&value) {
+        Some(Number::from(value as i64))
+    } else if (0.0..18_446_744_073_709_551_616.0).contains(&value) {
+        Some(Number::from(value as u64))
+    } else {
+        None
+    }
+}
+
+fn normalize_format(format: &str, value: &str) -> Option<String> {
+    match format {
+        "uuid" if is_valid_uuid(value) => Some(value.to_ascii_lowercase()),
+        "hostname" if is_valid_hostname(value) => Some(value.to_ascii_lowercase()),
+        // Only the separator and the UTC designator may be letters
+        "date-time" if is_valid_datetime(value) => Some(value.to_ascii_uppercase()),
+        "ipv4" => Ipv4Addr::from_str(value).ok().map(|ip| ip.to_string()),
+        "ipv6" => Ipv6Addr::from_str(value).ok().map(|ip| ip.to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    use super::{canonicalize, Walker};
+    use ahash::AHashMap;
+    use std::cell::RefCell;
+
+    #[test_case(&json!({}), json!({"b": 1.0, "a": [-0.0, 2.5, 1e20]}), json!({"a": [0, 2.5, 1e20], "b": 1}); "numbers")]
+    #[test_case(&json!({"format": "uuid"}), json!("67E55044-10B1-426F-9247-BB680E5FE0C8"), json!("67e55044-10b1-426f-9247-bb680e5fe0c8"); "uuid")]
+    #[test_case(&json!({"format": "uuid"}), json!("NOT-A-UUID"), json!("NOT-A-UUID"); "invalid uuid")]
+    #[test_case(&json!({"format": "hostname"}), json!("Example.COM"), json!("example.com"); "hostname")]
+    #[test_case(&json!({"format": "date-time"}), json!("2024-01-02t03:04:05z"), json!("2024-01-02T03:04:05Z"); "date-time")]
+    #[test_case(&json!({"format": "ipv6"}), json!("2001:0DB8:0000:0000:0000:0000:0000:0001"), json!("2001:db8::1"); "ipv6")]
+    #[test_case(&json!({"format": "email"}), json!("User@Example.com"), json!("User@Example.com"); "unnormalized format")]
+    #[test_case(&json!({"format": "uuid"}), json!(["A0000000-0000-0000-0000-000000000000"]), json!(["A0000000-0000-0000-0000-000000000000"]); "format of another location")]
+    fn values(schema: &Value, mut instance: Value, expected: Value) {
+        canonicalize(schema, &mut instance);
+        assert_eq!(instance, expected);
+    }
+
+    #[test_case(&json!({"properties": {"a": {"format": "hostname"}}}), json!({"a": "A.com", "b": "B.com"}), json!({"a": "a.com", "b": "B.com"}); "properties")]
+    #[test_case(&json!({"patternProperties": {"^h-": {"format": "hostname"}}}), json!({"h-1": "A.com", "x": "B.com"}), json!({"h-1": "a.com", "x": "B.com"}); "pattern properties")]
+    #[test_case(&json!({"properties": {"a": true}, "additionalProperties": {"format": "hostname"}}), json!({"a": "A.com", "b": "B.com"}), json!({"a": "A.com", "b": "b.com"}); "additional properties")]
+    #[test_case(&json!({"prefixItems": [true], "items": {"format": "hostname"}}), json!(["A.com", "B.com"]), json!(["A.com", "b.com"]); "prefix items")]
+    #[test_case(&json!({"items": [{"format": "hostname"}], "additionalItems": true}), json!(["A.com", "B.com"]), json!(["a.com", "B.com"]); "tuple items")]
+    #[test_case(&json!({"allOf": [{"$ref": "#/$defs/host"}], "$defs": {"host": {"format": "hostname"}}}), json!("A.com"), json!("a.com"); "all of and references")]
+    #[test_case(&json!({"$ref": "#", "items": {"$ref": "#"}, "format": "hostname"}), json!(["A.com"]), json!(["a.com"]); "recursive reference")]
+    #[test_case(&json!({"anyOf": [{"format": "hostname"}]}), json!("A.com"), json!("A.com"); "any of")]
+    fn subschemas(schema: &Value, mut instance: Value, expected: Value) {
+        canonicalize(schema, &mut instance);
+        assert_eq!(instance, expected);
+    }
+
+    #[test]
+    fn content() {
+        let schema = json!({
+            "contentMediaType": "application/json",
+            "contentSchema": {"properties": {"id": {"format": "uuid"}}}
+        });
+        let mut instance =
+            json!("{ \"n\": 1.0, \"id\": \"A0000000-0000-0000-0000-000000000000\" }");
+        canonicalize(&schema, &mut instance);
+        assert_eq!(
+            instance,
+            json!(r#"{"id":"a0000000-0000-0000-0000-000000000000","n":1}"#)
+        );
+        let mut invalid = json!("{ not json");
+        canonicalize(&schema, &mut invalid);
+        assert_eq!(invalid, json!("{ not json"));
+        let schema = json!({"contentMediaType": "application/json", "contentEncoding": "base64"});
+        let mut encoded = json!("eyAiYSI6IDEgfQ==");
+        canonicalize(&schema, &mut encoded);
+        assert_eq!(encoded, json!("eyAiYSI6IDEgfQ=="));
+    }
+
+    #[test]
+    fn patterns_are_compiled_once() {
+        let schema = json!({"patternProperties": {"^h-": {"format": "hostname"}, "(": {}}});
+        let walker = Walker {
+            root: &schema,
+            regexes: RefCell::new(AHashMap::new()),
+        };
+        let schemas = walker.applicable([&schema]);
+        let mut instance = json!({"h-1": "A.com", "h-2": "B.com", "x": "C.com"});
+        walker.canonicalize(&schemas, &mut instance);
+        assert_eq!(
+            instance,
+            json!({"h-1": "a.com", "h-2": "b.com", "x": "C.com"})
+        );
+        let regexes = walker.regexes.borrow();
+        assert_eq!(regexes.len(), 2);
+        assert!(regexes["^h-"].is_some());
+        // Invalid patterns are not retried either
+        assert!(regexes["("].is_none());
+    }
+}
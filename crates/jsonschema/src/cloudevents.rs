@@ -0,0 +1,155 @@
+//! Validation of CloudEvents data against their data schemas.
+//!
+//! Data schemas, referenced by the `dataschema` attribute, are usually written without `$schema`
+//! and are interpreted as Draft 7 then, like the schema of the CloudEvents JSON format itself.
+//! Events in the JSON format carry their data either in `data` or, for binary data, as a base64
+//! string in `data_base64`. [`data`] extracts it either way.
+//!
+//! ```rust
+//! use serde_json::json;
+//!
+//! let schema = json!({"type": "object", "required": ["id"]});
+//! let validator = jsonschema::cloudevents::validator_for(&schema).expect("Invalid schema");
+//!
+//! let event = json!({
+//!     "specversion": "1.0",
+//!     "type": "com.example.created",
+//!     "source": "/example",
+//!     "id": "1",
+//!     "datacontenttype": "application/json",
+//!     "data_base64": "eyJpZCI6IDF9"
+//! });
+//! let data = jsonschema::cloudevents::data(&event)
+//!     .expect("Invalid data")
+//!     .expect("Missing data");
+//! assert!(validator.is_valid(&data));
+//! ```
+use std::{borrow::Cow, error, fmt};
+
+use base64::{engine::general_purpose, Engine as _};
+use serde_json::Value;
+
+use crate::{Draft, ValidationError, Validator};
+
+/// An error extracting the data of an event.
+#[derive(Debug)]
+pub enum DataError {
+    /// `data_base64` is not a base64 string.
+    Base64,
+    /// The decoded data is declared as JSON but can't be parsed.
+    Json(serde_json::Error),
+}
+
+impl fmt::Display for DataError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DataError::Base64 => f.write_str("`data_base64` is not a valid base64 string"),
+            DataError::Json(error) => write!(f, "Invalid JSON data: {error}"),
+        }
+    }
+}
+
+impl error::Error for DataError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            DataError::Base64 => None,
+            DataError::Json(error) => Some(error),
+        }
+    }
+}
+
+/// Create a validator for a data schema, using Draft 7 unless the schema declares `$schema`.
+///
+/// # Errors
+///
+/// Returns an error if the schema is invalid.
+pub fn validator_for(schema: &Value) -> Result<Validator, ValidationError<'static>> {
+    let mut options = crate::options();
+    // Unknown `$schema` values are left to the usual detection, which may retrieve them
+    if let Ok(draft) = Draft::Draft7.detect(schema) {
+        options = options.with_draft(draft);
+    }
+    options.build(schema)
+}
+
+/// The data of an event in the JSON format, or `None` if it has no data.
+///
+/// Binary data in `data_base64` is decoded and parsed if `datacontenttype` is JSON, which is the
+/// default. Otherwise it is returned as is, i.e. as a base64 string.
+///
+/// # Errors
+///
+/// Returns an error if binary JSON data can't be decoded or parsed.
+pub fn data(event: &Value) -> Result<Option<Cow<'_, Value>>, DataError> {
+    if let Some(data) = event.get("data") {
+        return Ok(Some(Cow::Borrowed(data)));
+    }
+    let Some(encoded) = event.get("data_base64") else {
+        return Ok(None);
+    };
+    let content_type = event
+        .get("datacontenttype")
+        .and_then(Value::as_str)
+        .unwrap_or("application/json");
+    if !is_json(content_type) {
+        return Ok(Some(Cow::Borrowed(encoded)));
+    }
+    let decoded = encoded
+        .as_str()
+        .and_then(|encoded| general_purpose::STANDARD.decode(encoded).ok())
+        .ok_or(DataError::Base64)?;
+    serde_json::from_slice(&decoded)
+        .map(|data| Some(Cow::Owned(data)))
+        .map_err(DataError::Json)
+}
+
+/// Whether the media type is JSON, e.g. `application/json` or `application/cloudevents+json`.
+fn is_json(content_type: &str) -> bool {
+    let media_type = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    media_type == "application/json" || media_type == "text/json" || media_type.ends_with("+json")
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    use super::{data, validator_for, DataError};
+
+    #[test_case(&json!({"data": {"a": 1}}), Some(json!({"a": 1})); "data")]
+    #[test_case(&json!({"data_base64": "eyJhIjogMX0="}), Some(json!({"a": 1})); "default content type")]
+    #[test_case(&json!({"datacontenttype": "application/vnd.example+json; charset=utf-8", "data_base64": "WzFd"}), Some(json!([1])); "structured suffix")]
+    #[test_case(&json!({"datacontenttype": "image/png", "data_base64": "iVBORw=="}), Some(json!("iVBORw==")); "binary")]
+    #[test_case(&json!({"id": "1"}), None; "no data")]
+    fn extract(event: &Value, expected: Option<Value>) {
+        let data = data(event).expect("Invalid data");
+        assert_eq!(data.map(std::borrow::Cow::into_owned), expected);
+    }
+
+    #[test]
+    fn invalid_data() {
+        assert!(matches!(
+            data(&json!({"data_base64": "not base64!"})),
+            Err(DataError::Base64)
+        ));
+        assert!(matches!(
+            data(&json!({"data_base64": "YWJj"})),
+            Err(DataError::Json(_))
+        ));
+    }
+
+    #[test_case(&json!({"type": "string", "contentEncoding": "base64"}), false; "draft 7 by default")]
+    #[test_case(&json!({"$schema": "https://json-schema.org/draft/2020-12/schema", "type": "string", "contentEncoding": "base64"}), true; "declared draft")]
+    fn drafts(schema: &Value, accepts_invalid_encoding: bool) {
+        let validator = validator_for(schema).expect("Invalid schema");
+        assert_eq!(
+            validator.is_valid(&json!("not base64!")),
+            accepts_invalid_encoding
+        );
+    }
+}
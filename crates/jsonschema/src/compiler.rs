@@ -1,15 +1,20 @@
 use crate::{
     content_encoding::{ContentEncodingCheckType, ContentEncodingConverterType},
     content_media_type::ContentMediaTypeCheckType,
+    diagnostics::{self, Diagnostic, DiagnosticKind},
+    error::CompilationLimitKind,
+    keywords::helpers::InPlaceGuard,
     keywords::{
         self,
         custom::{CustomKeyword, KeywordFactory},
-        format::Format,
         BoxedValidator, BuiltinKeyword, Keyword,
     },
-    node::SchemaNode,
+    meta::validators::{shared, SharedValidator},
+    node::{NodeInfo, SchemaNode},
     options::ValidationOptions,
     paths::{Location, LocationSegment},
+    profile::{NodeProfile, Profiler},
+    sync::Shared,
     types::{JsonType, JsonTypeSet},
     ValidationError, Validator,
 };
@@ -18,32 +23,62 @@ use referencing::{
     uri, Draft, List, Registry, Resolved, Resolver, Resource, ResourceRef, Uri, Vocabulary,
     VocabularySet,
 };
-use serde_json::Value;
+use serde_json::{Map, Value};
 use std::{borrow::Cow, cell::RefCell, iter::once, rc::Rc, sync::Arc};
 
 const DEFAULT_SCHEME: &str = "json-schema";
 pub(crate) const DEFAULT_BASE_URI: &str = "json-schema:///";
 type BaseUri = Uri<String>;
-type ResolverComponents = (Arc<BaseUri>, List<BaseUri>, Resource);
+/// Base URI, dynamic scope, the resolved resource and its key for [`InPlaceGuard`].
+type ResolverComponents = (Arc<BaseUri>, List<BaseUri>, Resource, usize);
+
+/// State of a single validator build, shared by all contexts compiling its schema, including the
+/// ones of lazily compiled references.
+#[derive(Debug)]
+pub(crate) struct BuildState {
+    /// The base URI of the root schema.
+    root_base_uri: Uri<String>,
+    /// Index of the dialect declared by the root schema among the configured dialects, if any.
+    dialect: Option<usize>,
+    /// Collects the profiles of the built validator, if profiling.
+    pub(crate) profiler: Option<Arc<Profiler>>,
+}
 
 /// Container for information required to build a tree.
 ///
 /// Tracks the path to the current keyword, and a resolver for the current resource.
 #[derive(Debug, Clone)]
 pub(crate) struct Context<'a> {
-    config: Arc<ValidationOptions>,
-    pub(crate) registry: Arc<Registry>,
+    config: Shared<ValidationOptions>,
+    build: Shared<BuildState>,
+    pub(crate) registry: Shared<Registry>,
     resolver: Rc<Resolver<'a>>,
     vocabularies: VocabularySet,
     location: Location,
+    /// The target of the innermost reference compiled in place, and the length of `location`
+    /// at the target.
+    reference: Option<(Arc<Uri<String>>, usize)>,
     pub(crate) draft: Draft,
     seen: Rc<RefCell<AHashSet<Arc<Uri<String>>>>>,
+    /// Targets of the references compiled in place around the current location, outermost first.
+    references: Rc<RefCell<Vec<Arc<Uri<String>>>>>,
+    external_references: Rc<RefCell<AHashSet<Uri<String>>>>,
+    diagnostics: Rc<RefCell<Diagnostics>>,
+    nodes: Rc<RefCell<Vec<NodeInfo>>>,
+}
+
+/// Problems found in the schema, in the order they were found and without duplicates.
+#[derive(Debug, Default)]
+struct Diagnostics {
+    found: Vec<Diagnostic>,
+    seen: AHashSet<Diagnostic>,
 }
 
 impl<'a> Context<'a> {
     pub(crate) fn new(
-        config: Arc<ValidationOptions>,
-        registry: Arc<Registry>,
+        config: Shared<ValidationOptions>,
+        build: Shared<BuildState>,
+        registry: Shared<Registry>,
         resolver: Rc<Resolver<'a>>,
         vocabularies: VocabularySet,
         draft: Draft,
@@ -51,20 +86,29 @@ impl<'a> Context<'a> {
     ) -> Self {
         Context {
             config,
+            build,
             registry,
             resolver,
             location,
+            reference: None,
             vocabularies,
             draft,
             seen: Rc::new(RefCell::new(AHashSet::new())),
+            references: Rc::new(RefCell::new(Vec::new())),
+            external_references: Rc::new(RefCell::new(AHashSet::new())),
+            diagnostics: Rc::new(RefCell::new(Diagnostics::default())),
+            nodes: Rc::new(RefCell::new(Vec::new())),
         }
     }
     pub(crate) fn draft(&self) -> Draft {
         self.draft
     }
-    pub(crate) fn config(&self) -> &Arc<ValidationOptions> {
+    pub(crate) fn config(&self) -> &Shared<ValidationOptions> {
         &self.config
     }
+    pub(crate) fn build(&self) -> &Shared<BuildState> {
+        &self.build
+    }
 
     /// Create a context for this schema.
     pub(crate) fn in_subresource(
@@ -73,13 +117,19 @@ impl<'a> Context<'a> {
     ) -> Result<Context<'a>, referencing::Error> {
         let resolver = self.resolver.in_subresource(resource)?;
         Ok(Context {
-            config: Arc::clone(&self.config),
-            registry: Arc::clone(&self.registry),
+            config: Shared::clone(&self.config),
+            build: Shared::clone(&self.build),
+            registry: Shared::clone(&self.registry),
             resolver: Rc::new(resolver),
             vocabularies: self.vocabularies.clone(),
             draft: resource.draft(),
             location: self.location.clone(),
+            reference: self.reference.clone(),
             seen: Rc::clone(&self.seen),
+            references: Rc::clone(&self.references),
+            external_references: Rc::clone(&self.external_references),
+            diagnostics: Rc::clone(&self.diagnostics),
+            nodes: Rc::clone(&self.nodes),
         })
     }
     pub(crate) fn as_resource_ref<'r>(&'a self, contents: &'r Value) -> ResourceRef<'r> {
@@ -93,18 +143,35 @@ impl<'a> Context<'a> {
     pub(crate) fn new_at_location(&'a self, chunk: impl Into<LocationSegment<'a>>) -> Self {
         let location = self.location.join(chunk);
         Context {
-            config: Arc::clone(&self.config),
-            registry: Arc::clone(&self.registry),
+            config: Shared::clone(&self.config),
+            build: Shared::clone(&self.build),
+            registry: Shared::clone(&self.registry),
             resolver: Rc::clone(&self.resolver),
             vocabularies: self.vocabularies.clone(),
             location,
+            reference: self.reference.clone(),
             draft: self.draft,
             seen: Rc::clone(&self.seen),
+            references: Rc::clone(&self.references),
+            external_references: Rc::clone(&self.external_references),
+            diagnostics: Rc::clone(&self.diagnostics),
+            nodes: Rc::clone(&self.nodes),
         }
     }
 
-    pub(crate) fn lookup(&'a self, reference: &str) -> Result<Resolved<'a>, referencing::Error> {
-        self.resolver.lookup(reference)
+    /// Resolve a reference, naming the references that led here if it fails.
+    pub(crate) fn lookup(
+        &'a self,
+        reference: &str,
+    ) -> Result<Resolved<'a>, ValidationError<'static>> {
+        self.resolver.lookup(reference).map_err(|error| {
+            let breadcrumbs = self.resolver.breadcrumbs();
+            if breadcrumbs.is_empty() {
+                error.into()
+            } else {
+                ValidationError::referencing_via(error, breadcrumbs)
+            }
+        })
     }
 
     pub(crate) fn scopes(&self) -> List<Uri<String>> {
@@ -134,9 +201,6 @@ impl<'a> Context<'a> {
             Draft::Draft4 | Draft::Draft6 | Draft::Draft7
         ))
     }
-    pub(crate) fn are_unknown_formats_ignored(&self) -> bool {
-        self.config.are_unknown_formats_ignored()
-    }
     pub(crate) fn with_resolver_and_draft(
         &'a self,
         resolver: Resolver<'a>,
@@ -145,13 +209,19 @@ impl<'a> Context<'a> {
         location: Location,
     ) -> Context<'a> {
         Context {
-            config: Arc::clone(&self.config),
-            registry: Arc::clone(&self.registry),
+            config: Shared::clone(&self.config),
+            build: Shared::clone(&self.build),
+            registry: Shared::clone(&self.registry),
             resolver: Rc::new(resolver),
             draft,
             vocabularies,
             location,
+            reference: self.reference.clone(),
             seen: Rc::clone(&self.seen),
+            references: Rc::clone(&self.references),
+            external_references: Rc::clone(&self.external_references),
+            diagnostics: Rc::clone(&self.diagnostics),
+            nodes: Rc::clone(&self.nodes),
         }
     }
     pub(crate) fn get_content_media_type_check(
@@ -174,27 +244,107 @@ impl<'a> Context<'a> {
         self.config.get_content_encoding_convert(content_encoding)
     }
     pub(crate) fn get_keyword_factory(&self, name: &str) -> Option<&Arc<dyn KeywordFactory>> {
-        self.config.get_keyword_factory(name)
+        self.config.get_keyword_factory(name, self.build.dialect)
     }
-    pub(crate) fn get_format(&self, format: &str) -> Option<(&String, &Arc<dyn Format>)> {
-        self.config.get_format(format)
+    /// The absolute URI of a reference.
+    pub(crate) fn resolve_reference(
+        &self,
+        reference: &str,
+    ) -> Result<Arc<Uri<String>>, referencing::Error> {
+        self.resolver
+            .resolve_against(&self.resolver.base_uri().borrow(), reference)
     }
     pub(crate) fn is_circular_reference(
         &self,
         reference: &str,
     ) -> Result<bool, referencing::Error> {
-        let uri = self
-            .resolver
-            .resolve_against(&self.resolver.base_uri().borrow(), reference)?;
+        let uri = self.resolve_reference(reference)?;
         Ok(self.seen.borrow().contains(&*uri))
     }
     pub(crate) fn mark_seen(&self, reference: &str) -> Result<(), referencing::Error> {
+        let uri = self.resolve_reference(reference)?;
+        self.seen.borrow_mut().insert(uri);
+        Ok(())
+    }
+
+    /// Enter a reference compiled in place, until the returned guard is dropped.
+    ///
+    /// Fails if references are nested deeper than the configured limit, with the URIs of the
+    /// references forming a cycle with `target`, if any.
+    pub(crate) fn enter_reference(
+        &self,
+        target: Arc<Uri<String>>,
+    ) -> Result<ReferenceGuard, Vec<String>> {
+        let limit = self.config.compilation_limits().reference_depth();
+        let mut references = self.references.borrow_mut();
+        if references.len() >= limit {
+            return Err(references
+                .iter()
+                .rposition(|reference| *reference == target)
+                .map(|start| {
+                    references[start..]
+                        .iter()
+                        .chain(once(&target))
+                        .map(|reference| reference.as_str().to_string())
+                        .collect()
+                })
+                .unwrap_or_default());
+        }
+        references.push(target);
+        Ok(ReferenceGuard(Rc::clone(&self.references)))
+    }
+
+    /// Record the document a reference points to if it lives outside of the schema being compiled.
+    pub(crate) fn mark_external_reference(
+        &self,
+        reference: &str,
+    ) -> Result<(), referencing::Error> {
         let uri = self
             .resolver
             .resolve_against(&self.resolver.base_uri().borrow(), reference)?;
-        self.seen.borrow_mut().insert(uri);
+        let mut uri = (*uri).clone();
+        uri.set_fragment(None);
+        if self.registry.contains_document(&uri) {
+            self.external_references.borrow_mut().insert(uri);
+        }
         Ok(())
     }
+    pub(crate) fn external_references(&self) -> Vec<Uri<String>> {
+        let mut references: Vec<_> = self.external_references.borrow().iter().cloned().collect();
+        references.sort_unstable_by(|left, right| left.as_str().cmp(right.as_str()));
+        references
+    }
+
+    /// Record problems found in this subschema.
+    /// The same subschema may be compiled more than once, e.g. via multiple references.
+    fn analyze(&self, schema: &Map<String, Value>) {
+        let mut diagnostics = self.diagnostics.borrow_mut();
+        for diagnostic in diagnostics::analyze(schema, &self.location, self.draft()) {
+            // Custom keywords may reuse names from newer drafts
+            if let DiagnosticKind::UnsupportedKeyword { keyword, .. } = diagnostic.kind() {
+                if self.get_keyword_factory(keyword).is_some() {
+                    continue;
+                }
+            }
+            if diagnostics.seen.insert(diagnostic.clone()) {
+                diagnostics.found.push(diagnostic);
+            }
+        }
+    }
+    pub(crate) fn diagnostics(&self) -> Vec<Diagnostic> {
+        self.diagnostics.borrow().found.clone()
+    }
+    /// Compiled subschemas, in compilation order and without duplicate locations.
+    pub(crate) fn nodes(&self) -> Vec<NodeInfo> {
+        let mut seen = AHashSet::new();
+        self.nodes
+            .borrow()
+            .iter()
+            .filter(|node| seen.insert(node.location().as_str()))
+            .enumerate()
+            .map(|(index, node)| node.clone().with_id(index))
+            .collect()
+    }
 
     pub(crate) fn lookup_recursive_reference(&self) -> Result<Resolved<'_>, referencing::Error> {
         self.resolver.lookup_recursive_ref()
@@ -208,7 +358,7 @@ impl<'a> Context<'a> {
     ) -> Result<Option<ResolverComponents>, ValidationError<'static>> {
         let resolved = if self.is_circular_reference(reference)? {
             // Otherwise we need to manually check whether this location has already been explored
-            self.resolver.lookup(reference)?
+            self.lookup(reference)?
         } else {
             // This is potentially recursive, but it is unknown yet
             if !is_recursive {
@@ -222,13 +372,81 @@ impl<'a> Context<'a> {
         if let Some(id) = resource.id() {
             base_uri = self.registry.resolve_against(&base_uri.borrow(), id)?;
         };
-        Ok(Some((base_uri, scopes, resource)))
+        let origin = InPlaceGuard::key(resolved.contents());
+        Ok(Some((base_uri, scopes, resource, origin)))
     }
 
     pub(crate) fn location(&self) -> &Location {
         &self.location
     }
 
+    /// Mark the current location as the target of a reference, identifying its subschemas by
+    /// `target` in profiles.
+    pub(crate) fn with_reference(mut self, target: Arc<Uri<String>>) -> Self {
+        self.reference = Some((target, self.location.as_str().len()));
+        self
+    }
+
+    /// Whether `target` is the absolute URI of the subschema at the current location, i.e. a
+    /// reference to it is contained in the subschema it refers to.
+    pub(crate) fn is_current_subschema(&self, target: &Uri<String>) -> bool {
+        let location = self.location.as_str();
+        let (base, pointer) = match &self.reference {
+            Some((reference, length)) => (Arc::clone(reference), &location[*length..]),
+            None => (self.resolver.base_uri(), location),
+        };
+        let split = |uri: &Uri<String>| {
+            let mut document = uri.clone();
+            document.set_fragment(None);
+            let fragment = uri.fragment().map_or(Cow::Borrowed(""), |fragment| {
+                percent_encoding::percent_decode_str(fragment.as_str()).decode_utf8_lossy()
+            });
+            (document, fragment.into_owned())
+        };
+        let (document, fragment) = split(target);
+        let (base_document, base_fragment) = split(&base);
+        document == base_document && fragment.strip_prefix(base_fragment.as_str()) == Some(pointer)
+    }
+
+    /// The location of the current subschema within the resource defining it, if it differs
+    /// from its keyword location because the subschema is reached via a reference.
+    ///
+    /// A JSON Pointer within the root schema, e.g. `/$defs/item`, or an absolute URI for
+    /// subschemas of other documents, e.g. `https://example.com/item.json#/properties/id`.
+    pub(crate) fn canonical_location(&self) -> Option<String> {
+        let (target, length) = self.reference.as_ref()?;
+        let suffix = &self.location.as_str()[*length..];
+        let root = Some(&self.build.root_base_uri);
+        let mut document = (**target).clone();
+        document.set_fragment(None);
+        Some(match target.fragment() {
+            Some(fragment) if Some(&document) == root => {
+                let fragment = fragment.as_str();
+                if fragment.is_empty() || fragment.starts_with('/') {
+                    let pointer =
+                        percent_encoding::percent_decode_str(fragment).decode_utf8_lossy();
+                    format!("{pointer}{suffix}")
+                } else {
+                    // Anchors have no pointer
+                    format!("#{fragment}{suffix}")
+                }
+            }
+            None if Some(&document) == root => suffix.to_string(),
+            _ => format!("{}{suffix}", target.as_str()),
+        })
+    }
+
+    /// The profile counters of the subschema at the current location, if profiling.
+    pub(crate) fn profile(&self) -> Option<Arc<NodeProfile>> {
+        let profiler = self.build.profiler.as_ref()?;
+        Some(
+            profiler.node(
+                self.canonical_location()
+                    .unwrap_or_else(|| self.location.as_str().to_string()),
+            ),
+        )
+    }
+
     pub(crate) fn vocabularies(&self) -> &VocabularySet {
         &self.vocabularies
     }
@@ -246,7 +464,13 @@ pub(crate) fn build_validator(
     mut config: ValidationOptions,
     schema: &Value,
 ) -> Result<Validator, ValidationError<'static>> {
+    let substituted = config.substitute_variables(schema)?;
+    let schema = substituted.as_ref().unwrap_or(schema);
     let draft = config.draft_for(schema)?;
+    let dialect = config.dialect_index(schema);
+    let mut resources = std::mem::take(&mut config.resources);
+    config.add_dialect_resources(&mut resources)?;
+    config.add_ref_overrides(draft, &mut resources)?;
     let resource_ref = draft.create_resource_ref(schema);
     let resource = draft.create_resource(schema.clone());
     let base_uri = if let Some(base_uri) = config.base_uri.as_ref() {
@@ -256,25 +480,41 @@ pub(crate) fn build_validator(
     };
 
     // Build a registry & resolver needed for validator compilation
-    let pairs = collect_resource_pairs(base_uri.as_str(), resource, &mut config.resources);
+    let pairs = collect_resource_pairs(base_uri.as_str(), resource, &mut resources);
 
     let registry = if let Some(registry) = config.registry.take() {
-        Arc::new(registry.try_with_resources_and_retriever(pairs, &*config.retriever, draft)?)
+        Shared::new(registry.try_with_resources_and_retriever(pairs, &*config.retriever, draft)?)
     } else {
-        Arc::new(
-            Registry::options()
-                .draft(draft)
-                .retriever(Arc::clone(&config.retriever))
-                .build(pairs)?,
-        )
+        let mut options = Registry::options()
+            .draft(draft)
+            .retriever(Arc::clone(&config.retriever));
+        if let Some(cache) = &config.document_cache {
+            options = options.document_cache(cache.clone());
+        }
+        if let Some((max_documents, max_total_bytes, max_ref_depth)) = config.retrieval_limits {
+            options = options.limits(max_documents, max_total_bytes, max_ref_depth);
+        }
+        options = options.strict_identifiers(config.strict_identifiers);
+        if let Some(rewriter) = &config.uri_rewriter {
+            let rewriter = Arc::clone(rewriter);
+            options = options.uri_rewriter(move |uri: &Uri<String>| rewriter.rewrite(uri));
+        }
+        Shared::new(options.build(pairs)?)
     };
-    let vocabularies = registry.find_vocabularies(draft, schema);
-    let resolver = Rc::new(registry.resolver(base_uri));
+    let vocabularies = config.vocabularies_for(&registry, draft, schema)?;
+    // Compile errors name the references leading to unresolvable ones
+    let resolver = Rc::new(registry.resolver(base_uri.clone()).with_breadcrumbs());
+    let build = Shared::new(BuildState {
+        root_base_uri: base_uri.clone(),
+        dialect,
+        profiler: config.profiles().then(|| Arc::new(Profiler::default())),
+    });
 
-    let config = Arc::new(config);
+    let config = Shared::new(config);
     let ctx = Context::new(
-        Arc::clone(&config),
-        Arc::clone(&registry),
+        Shared::clone(&config),
+        Shared::clone(&build),
+        Shared::clone(&registry),
         resolver,
         vocabularies,
         draft,
@@ -288,7 +528,18 @@ pub(crate) fn build_validator(
 
     // Finally, compile the validator
     let root = compile(&ctx, resource_ref).map_err(|err| err.to_owned())?;
-    Ok(Validator { root, config })
+    let mut external_references = ctx.external_references();
+    external_references.retain(|uri| *uri != base_uri);
+    Ok(Validator {
+        root,
+        config,
+        build,
+        external_references,
+        diagnostics: ctx.diagnostics(),
+        nodes: ctx.nodes(),
+        registry,
+        base_uri,
+    })
 }
 
 #[cfg(feature = "resolve-async")]
@@ -296,7 +547,13 @@ pub(crate) async fn build_validator_async(
     mut config: ValidationOptions<Arc<dyn referencing::AsyncRetrieve>>,
     schema: &Value,
 ) -> Result<Validator, ValidationError<'static>> {
+    let substituted = config.substitute_variables(schema)?;
+    let schema = substituted.as_ref().unwrap_or(schema);
     let draft = config.draft_for(schema).await?;
+    let dialect = config.dialect_index(schema);
+    let mut resources = std::mem::take(&mut config.resources);
+    config.add_dialect_resources(&mut resources)?;
+    config.add_ref_overrides(draft, &mut resources)?;
     let resource_ref = draft.create_resource_ref(schema);
     let resource = draft.create_resource(schema.clone());
     let base_uri = if let Some(base_uri) = config.base_uri.as_ref() {
@@ -305,35 +562,50 @@ pub(crate) async fn build_validator_async(
         uri::from_str(resource_ref.id().unwrap_or(DEFAULT_BASE_URI))?
     };
 
-    let pairs = collect_resource_pairs(base_uri.as_str(), resource, &mut config.resources);
+    let pairs = collect_resource_pairs(base_uri.as_str(), resource, &mut resources);
 
     let registry = if let Some(registry) = config.registry.take() {
-        Arc::new(
+        Shared::new(
             registry
                 .try_with_resources_and_retriever_async(pairs, &*config.retriever, draft)
                 .await?,
         )
     } else {
-        Arc::new(
-            Registry::options()
-                .async_retriever(Arc::clone(&config.retriever))
-                .draft(draft)
-                .build(pairs)
-                .await?,
-        )
+        let mut options = Registry::options()
+            .async_retriever(Arc::clone(&config.retriever))
+            .draft(draft);
+        if let Some(cache) = &config.document_cache {
+            options = options.document_cache(cache.clone());
+        }
+        if let Some((max_documents, max_total_bytes, max_ref_depth)) = config.retrieval_limits {
+            options = options.limits(max_documents, max_total_bytes, max_ref_depth);
+        }
+        options = options.strict_identifiers(config.strict_identifiers);
+        if let Some(rewriter) = &config.uri_rewriter {
+            let rewriter = Arc::clone(rewriter);
+            options = options.uri_rewriter(move |uri: &Uri<String>| rewriter.rewrite(uri));
+        }
+        Shared::new(options.build(pairs).await?)
     };
 
-    let vocabularies = registry.find_vocabularies(draft, schema);
-    let resolver = Rc::new(registry.resolver(base_uri));
+    let vocabularies = config.vocabularies_for(&registry, draft, schema)?;
+    // Compile errors name the references leading to unresolvable ones
+    let resolver = Rc::new(registry.resolver(base_uri.clone()).with_breadcrumbs());
+    let build = Shared::new(BuildState {
+        root_base_uri: base_uri.clone(),
+        dialect,
+        profiler: config.profiles().then(|| Arc::new(Profiler::default())),
+    });
     // HACK: As we store the config and it has a type parameter we need to apply a small hack here.
     //       `ValidationOptions` struct has a default type parameter as `Arc<dyn Retrieve>` and to
     //       avoid propagating types everywhere in `Context`, it is easier to just replace the
     //       retriever to one that implements `Retrieve`, as it is not used anymore anyway.
     //       In the future it might be better to avoid storing the context anyway.
-    let config = Arc::new(config.with_blocking_retriever(crate::retriever::DefaultRetriever));
+    let config = Shared::new(config.with_blocking_retriever(crate::retriever::DefaultRetriever));
     let ctx = Context::new(
-        Arc::clone(&config),
-        Arc::clone(&registry),
+        Shared::clone(&config),
+        Shared::clone(&build),
+        Shared::clone(&registry),
         resolver,
         vocabularies,
         draft,
@@ -345,7 +617,18 @@ pub(crate) async fn build_validator_async(
     }
 
     let root = compile(&ctx, resource_ref).map_err(|err| err.to_owned())?;
-    Ok(Validator { root, config })
+    let mut external_references = ctx.external_references();
+    external_references.retain(|uri| *uri != base_uri);
+    Ok(Validator {
+        root,
+        config,
+        build,
+        external_references,
+        diagnostics: ctx.diagnostics(),
+        nodes: ctx.nodes(),
+        registry,
+        base_uri,
+    })
 }
 
 fn collect_resource_pairs<'a>(
@@ -360,14 +643,27 @@ fn collect_resource_pairs<'a>(
     )
 }
 
+/// The validator for the meta-schema of `draft`, if support for it is enabled.
+fn meta_validator(draft: Draft) -> Option<SharedValidator> {
+    match draft {
+        #[cfg(feature = "draft4")]
+        Draft::Draft4 => Some(shared(&crate::draft4::meta::VALIDATOR)),
+        #[cfg(feature = "draft6")]
+        Draft::Draft6 => Some(shared(&crate::draft6::meta::VALIDATOR)),
+        #[cfg(feature = "draft7")]
+        Draft::Draft7 => Some(shared(&crate::draft7::meta::VALIDATOR)),
+        #[cfg(feature = "draft201909")]
+        Draft::Draft201909 => Some(shared(&crate::draft201909::meta::VALIDATOR)),
+        #[cfg(feature = "draft202012")]
+        Draft::Draft202012 => Some(shared(&crate::draft202012::meta::VALIDATOR)),
+        _ => None,
+    }
+}
+
 fn validate_schema(draft: Draft, schema: &Value) -> Result<(), ValidationError<'static>> {
-    let validator = match draft {
-        Draft::Draft4 => &crate::draft4::meta::VALIDATOR,
-        Draft::Draft6 => &crate::draft6::meta::VALIDATOR,
-        Draft::Draft7 => &crate::draft7::meta::VALIDATOR,
-        Draft::Draft201909 => &crate::draft201909::meta::VALIDATOR,
-        Draft::Draft202012 => &crate::draft202012::meta::VALIDATOR,
-        _ => unreachable!("Unknown draft"),
+    // Disabled drafts are usually rejected earlier, by `draft_for`
+    let Some(validator) = meta_validator(draft) else {
+        return Err(referencing::Error::DisabledSpecification { draft }.into());
     };
     if let Err(error) = validator.validate(schema) {
         return Err(error.to_owned());
@@ -375,6 +671,15 @@ fn validate_schema(draft: Draft, schema: &Value) -> Result<(), ValidationError<'
     Ok(())
 }
 
+/// Leaves a reference compiled in place when dropped, see [`Context::enter_reference`].
+pub(crate) struct ReferenceGuard(Rc<RefCell<Vec<Arc<Uri<String>>>>>);
+
+impl Drop for ReferenceGuard {
+    fn drop(&mut self) {
+        self.0.borrow_mut().pop();
+    }
+}
+
 /// Compile a JSON Schema instance to a tree of nodes.
 pub(crate) fn compile<'a>(
     ctx: &Context,
@@ -389,17 +694,30 @@ pub(crate) fn compile_with<'a>(
     resource: ResourceRef<'a>,
 ) -> Result<SchemaNode, ValidationError<'a>> {
     let location = ctx.location().clone();
+    if let Some(limit) = ctx.config.compilation_limits().subschemas() {
+        if ctx.nodes.borrow().len() >= limit {
+            return Err(ValidationError::compilation_limit_exceeded(
+                Location::new(),
+                location,
+                resource.contents(),
+                CompilationLimitKind::Subschemas,
+                limit,
+                Vec::new(),
+            ));
+        }
+    }
+    ctx.nodes.borrow_mut().push(NodeInfo::new(
+        location.clone(),
+        ctx.canonical_location(),
+        ctx.base_uri(),
+        resource.contents(),
+    ));
     match resource.contents() {
-        Value::Bool(value) => match value {
-            true => Ok(SchemaNode::from_boolean(ctx, None)),
-            false => Ok(SchemaNode::from_boolean(
-                ctx,
-                Some(
-                    keywords::boolean::FalseValidator::compile(location)
-                        .expect("Should always compile"),
-                ),
-            )),
-        },
+        Value::Bool(value) => Ok(SchemaNode::from_boolean(ctx, *value)),
+        // Empty schemas are as common as `true` in generated schemas, skip the keyword lookups
+        Value::Object(schema) if schema.is_empty() => {
+            Ok(SchemaNode::from_keywords(ctx, Vec::new(), None))
+        }
         Value::Object(schema) => {
             // A schema could contain validation keywords along with annotations and we need to
             // collect annotations separately
@@ -427,12 +745,20 @@ pub(crate) fn compile_with<'a>(
                             Some(annotations),
                         ))
                     } else {
-                        // Infinite reference to the same location
-                        Ok(SchemaNode::from_boolean(ctx, None))
+                        // Infinite reference to the same location, only the annotations apply
+                        let annotations = if annotations.is_empty() {
+                            None
+                        } else {
+                            Some(annotations)
+                        };
+                        Ok(SchemaNode::from_keywords(ctx, Vec::new(), annotations))
                     };
                 }
             }
 
+            if ctx.config().reports_diagnostics() {
+                ctx.analyze(schema);
+            }
             let mut validators = Vec::with_capacity(schema.len());
             let mut annotations = AHashMap::new();
             for (keyword, value) in schema {
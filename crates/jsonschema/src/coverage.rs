@@ -0,0 +1,147 @@
+//! Tracking of subschemas exercised by a set of instances.
+use ahash::{AHashMap, AHashSet};
+
+use crate::{node::NodeInfo, paths::Location};
+
+/// Subschemas that accepted at least one location in the instances recorded so far.
+///
+/// Created via [`Validator::coverage`](crate::Validator::coverage) and filled via
+/// [`Validator::record_coverage`](crate::Validator::record_coverage), which allows evaluating
+/// whether a corpus of test fixtures exercises every branch of a schema.
+///
+/// Subschemas are identified by their canonical locations, i.e. a subschema reached via `$ref`
+/// is located where it is defined, e.g. `/$defs/item`, and is covered once any reference to it
+/// accepted an instance location. Subschemas of other documents are identified by their
+/// absolute URIs. Subschemas of keywords that do not produce annotations, e.g. `not`, are never
+/// covered.
+#[derive(Debug, Clone)]
+pub struct Coverage {
+    /// Canonical locations of all subschemas, in the order they were compiled.
+    subschemas: Vec<String>,
+    /// Indexes into `subschemas` by keyword location of the compiled nodes.
+    nodes: AHashMap<Location, usize>,
+    /// Indexes into `subschemas` by canonical location.
+    positions: AHashMap<String, usize>,
+    covered: AHashSet<usize>,
+}
+
+impl Coverage {
+    pub(crate) fn new(nodes: &[NodeInfo]) -> Coverage {
+        let mut coverage = Coverage {
+            subschemas: Vec::new(),
+            nodes: AHashMap::with_capacity(nodes.len()),
+            positions: AHashMap::new(),
+            covered: AHashSet::new(),
+        };
+        for node in nodes {
+            let canonical = node.canonical_location();
+            let position = match coverage.positions.get(canonical) {
+                Some(&position) => position,
+                None => {
+                    let position = coverage.subschemas.len();
+                    coverage.subschemas.push(canonical.to_string());
+                    coverage.positions.insert(canonical.to_string(), position);
+                    position
+                }
+            };
+            coverage.nodes.insert(node.location().clone(), position);
+        }
+        coverage
+    }
+    /// Mark the subschema compiled at `keyword_location` as covered.
+    pub(crate) fn insert(&mut self, keyword_location: &Location) {
+        if let Some(&position) = self.nodes.get(keyword_location) {
+            self.covered.insert(position);
+        }
+    }
+    /// Canonical locations of all subschemas of the schema, in the order they were compiled.
+    #[must_use]
+    pub fn subschemas(&self) -> &[String] {
+        &self.subschemas
+    }
+    /// Subschemas that accepted at least one instance location.
+    pub fn covered(&self) -> impl Iterator<Item = &str> {
+        self.subschemas
+            .iter()
+            .enumerate()
+            .filter(|(position, _)| self.covered.contains(position))
+            .map(|(_, location)| location.as_str())
+    }
+    /// Subschemas that did not accept any instance location.
+    pub fn uncovered(&self) -> impl Iterator<Item = &str> {
+        self.subschemas
+            .iter()
+            .enumerate()
+            .filter(|(position, _)| !self.covered.contains(position))
+            .map(|(_, location)| location.as_str())
+    }
+    /// Whether the subschema at the canonical `location` accepted at least one instance location.
+    #[must_use]
+    pub fn is_covered(&self, location: &str) -> bool {
+        self.positions
+            .get(location)
+            .is_some_and(|position| self.covered.contains(position))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    #[test_case(
+        &json!({"properties": {"a": {"type": "integer"}, "b": {"type": "string"}}}),
+        &[json!({"a": 1})],
+        &["/properties/b"];
+        "properties"
+    )]
+    #[test_case(
+        &json!({"if": {"type": "integer"}, "then": {"minimum": 0}, "else": {"type": "string"}}),
+        &[json!(1), json!(2)],
+        &["/else"];
+        "conditional"
+    )]
+    #[test_case(
+        &json!({"$defs": {"a": {"type": "integer"}}, "items": {"$ref": "#/$defs/a"}}),
+        &[json!([])],
+        &["/items", "/$defs/a"];
+        "reference"
+    )]
+    #[test_case(
+        &json!({
+            "$defs": {"a": {"type": "integer"}},
+            "properties": {"a": {"$ref": "#/$defs/a"}, "b": {"$ref": "#/$defs/a"}}
+        }),
+        &[json!({"a": 1})],
+        &["/properties/b"];
+        "subschema referenced twice"
+    )]
+    #[test_case(
+        &json!({"oneOf": [{"type": "integer"}, {"type": "string"}]}),
+        &[json!(1), json!("a")],
+        &[];
+        "fully covered"
+    )]
+    #[test_case(
+        &json!({"type": "object"}),
+        &[json!(1)],
+        &[""];
+        "invalid instance"
+    )]
+    fn uncovered(schema: &Value, instances: &[Value], expected: &[&str]) {
+        let validator = crate::validator_for(schema).expect("Invalid schema");
+        let mut coverage = validator.coverage();
+        for instance in instances {
+            validator.record_coverage(instance, &mut coverage);
+        }
+        let uncovered: Vec<_> = coverage.uncovered().collect();
+        assert_eq!(uncovered, expected);
+        for location in expected {
+            assert!(!coverage.is_covered(location));
+        }
+        assert_eq!(
+            coverage.covered().count() + expected.len(),
+            coverage.subschemas().len()
+        );
+    }
+}
@@ -0,0 +1,285 @@
+//! Detection of schema constraints that can never be satisfied.
+//!
+//! Such constraints are valid according to the specification, but almost always indicate an
+//! authoring bug, e.g. `{"type": "string", "minimum": 3}` where `minimum` never applies.
+//! Keywords from drafts newer than the one in use are reported as well, as they are silently
+//! treated as annotations.
+use std::{fmt, str::FromStr};
+
+use referencing::Draft;
+use serde_json::{Map, Value};
+
+use crate::{analyze::draft_name, ext::cmp::equal, paths::Location, JsonType, JsonTypeSet};
+
+/// A potential problem found in a schema during compilation.
+///
+/// See [`Validator::diagnostics`](crate::Validator::diagnostics).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Diagnostic {
+    location: Location,
+    kind: DiagnosticKind,
+}
+
+/// Kinds of problems reported by [`Diagnostic`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum DiagnosticKind {
+    /// The keyword applies only to types that are not allowed by `type`.
+    IneffectiveKeyword { keyword: String },
+    /// The lower bound keyword is greater than the upper bound one.
+    ContradictoryBounds {
+        lower: &'static str,
+        upper: &'static str,
+    },
+    /// The value of `const` is not allowed by `type`.
+    ConstTypeMismatch,
+    /// The value of `const` is not listed in `enum`.
+    ConstNotInEnum,
+    /// None of the `enum` values are allowed by `type`.
+    EnumTypeMismatch,
+    /// `enum` has no values.
+    EmptyEnum,
+    /// The keyword was introduced in a newer draft than the one in use and is ignored.
+    UnsupportedKeyword {
+        keyword: String,
+        draft: Draft,
+        introduced: Draft,
+    },
+}
+
+impl Diagnostic {
+    /// Location of the subschema containing the problem.
+    #[must_use]
+    pub const fn location(&self) -> &Location {
+        &self.location
+    }
+    /// The kind of the problem.
+    #[must_use]
+    pub const fn kind(&self) -> &DiagnosticKind {
+        &self.kind
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let location = if self.location.as_str().is_empty() {
+            "/"
+        } else {
+            self.location.as_str()
+        };
+        write!(f, "{location}: ")?;
+        match &self.kind {
+            DiagnosticKind::IneffectiveKeyword { keyword } => write!(
+                f,
+                "'{keyword}' never applies to the types allowed by 'type'"
+            ),
+            DiagnosticKind::ContradictoryBounds { lower, upper } => {
+                write!(f, "'{lower}' is greater than '{upper}'")
+            }
+            DiagnosticKind::ConstTypeMismatch => {
+                f.write_str("'const' value is not allowed by 'type'")
+            }
+            DiagnosticKind::ConstNotInEnum => f.write_str("'const' value is not listed in 'enum'"),
+            DiagnosticKind::EnumTypeMismatch => {
+                f.write_str("None of the 'enum' values are allowed by 'type'")
+            }
+            DiagnosticKind::EmptyEnum => f.write_str("'enum' has no values"),
+            DiagnosticKind::UnsupportedKeyword {
+                keyword,
+                draft,
+                introduced,
+            } => write!(
+                f,
+                "'{keyword}' is ignored under {}, as it was introduced in {}",
+                draft_name(*draft),
+                draft_name(*introduced)
+            ),
+        }
+    }
+}
+
+const NUMERIC: JsonTypeSet = JsonTypeSet::empty()
+    .insert(JsonType::Integer)
+    .insert(JsonType::Number);
+const STRING: JsonTypeSet = JsonTypeSet::empty().insert(JsonType::String);
+const ARRAY: JsonTypeSet = JsonTypeSet::empty().insert(JsonType::Array);
+const OBJECT: JsonTypeSet = JsonTypeSet::empty().insert(JsonType::Object);
+
+/// Keywords that only constrain instances of specific types.
+const TYPE_SPECIFIC_KEYWORDS: &[(&str, JsonTypeSet)] = &[
+    ("minimum", NUMERIC),
+    ("maximum", NUMERIC),
+    ("exclusiveMinimum", NUMERIC),
+    ("exclusiveMaximum", NUMERIC),
+    ("multipleOf", NUMERIC),
+    ("minLength", STRING),
+    ("maxLength", STRING),
+    ("pattern", STRING),
+    ("minItems", ARRAY),
+    ("maxItems", ARRAY),
+    ("uniqueItems", ARRAY),
+    ("items", ARRAY),
+    ("prefixItems", ARRAY),
+    ("additionalItems", ARRAY),
+    ("contains", ARRAY),
+    ("minContains", ARRAY),
+    ("maxContains", ARRAY),
+    ("minProperties", OBJECT),
+    ("maxProperties", OBJECT),
+    ("required", OBJECT),
+    ("properties", OBJECT),
+    ("patternProperties", OBJECT),
+    ("additionalProperties", OBJECT),
+    ("propertyNames", OBJECT),
+    ("dependentRequired", OBJECT),
+    ("dependentSchemas", OBJECT),
+];
+
+/// Keywords with the draft introducing them.
+const NEWER_KEYWORDS: &[(&str, Draft)] = &[
+    ("const", Draft::Draft6),
+    ("contains", Draft::Draft6),
+    ("propertyNames", Draft::Draft6),
+    ("if", Draft::Draft7),
+    ("then", Draft::Draft7),
+    ("else", Draft::Draft7),
+    ("$anchor", Draft::Draft201909),
+    ("$recursiveRef", Draft::Draft201909),
+    ("dependentRequired", Draft::Draft201909),
+    ("dependentSchemas", Draft::Draft201909),
+    ("minContains", Draft::Draft201909),
+    ("maxContains", Draft::Draft201909),
+    ("unevaluatedItems", Draft::Draft201909),
+    ("unevaluatedProperties", Draft::Draft201909),
+    ("prefixItems", Draft::Draft202012),
+    ("$dynamicRef", Draft::Draft202012),
+    ("$dynamicAnchor", Draft::Draft202012),
+];
+
+const BOUNDS: &[(&str, &str)] = &[
+    ("minimum", "maximum"),
+    ("minLength", "maxLength"),
+    ("minItems", "maxItems"),
+    ("minProperties", "maxProperties"),
+    ("minContains", "maxContains"),
+];
+
+fn allowed_types(schema: &Map<String, Value>) -> Option<JsonTypeSet> {
+    let parse = |value: &Value| value.as_str().and_then(|ty| JsonType::from_str(ty).ok());
+    match schema.get("type")? {
+        Value::String(_) => parse(&schema["type"]).map(|ty| JsonTypeSet::empty().insert(ty)),
+        Value::Array(items) => items.iter().try_fold(JsonTypeSet::empty(), |types, item| {
+            parse(item).map(|ty| types.insert(ty))
+        }),
+        _ => None,
+    }
+}
+
+fn intersects(left: JsonTypeSet, right: JsonTypeSet) -> bool {
+    left.iter().any(|ty| right.contains(ty))
+}
+
+/// Check a single subschema, without descending into its subschemas.
+pub(crate) fn analyze(
+    schema: &Map<String, Value>,
+    location: &Location,
+    draft: Draft,
+) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let mut report = |kind| {
+        diagnostics.push(Diagnostic {
+            location: location.clone(),
+            kind,
+        });
+    };
+    for (keyword, introduced) in NEWER_KEYWORDS {
+        if *introduced > draft && schema.contains_key(*keyword) {
+            report(DiagnosticKind::UnsupportedKeyword {
+                keyword: (*keyword).to_string(),
+                draft,
+                introduced: *introduced,
+            });
+        }
+    }
+    let types = allowed_types(schema);
+    if let Some(types) = types {
+        for (keyword, applicable) in TYPE_SPECIFIC_KEYWORDS {
+            if schema.contains_key(*keyword) && !intersects(types, *applicable) {
+                report(DiagnosticKind::IneffectiveKeyword {
+                    keyword: (*keyword).to_string(),
+                });
+            }
+        }
+    }
+    for (lower, upper) in BOUNDS {
+        if let (Some(min), Some(max)) = (
+            schema.get(*lower).and_then(Value::as_f64),
+            schema.get(*upper).and_then(Value::as_f64),
+        ) {
+            if min > max {
+                report(DiagnosticKind::ContradictoryBounds { lower, upper });
+            }
+        }
+    }
+    let variants = schema.get("enum").and_then(Value::as_array);
+    if let Some(value) = schema.get("const") {
+        if types.is_some_and(|types| !types.contains_value_type(value)) {
+            report(DiagnosticKind::ConstTypeMismatch);
+        }
+        if variants.is_some_and(|variants| !variants.iter().any(|variant| equal(variant, value))) {
+            report(DiagnosticKind::ConstNotInEnum);
+        }
+    }
+    if let Some(variants) = variants {
+        if variants.is_empty() {
+            report(DiagnosticKind::EmptyEnum);
+        } else if types.is_some_and(|types| {
+            !variants
+                .iter()
+                .any(|variant| types.contains_value_type(variant))
+        }) {
+            report(DiagnosticKind::EnumTypeMismatch);
+        }
+    }
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    #[test_case(&json!({"type": "string", "minimum": 3}), &["/: 'minimum' never applies to the types allowed by 'type'"]; "ineffective keyword")]
+    #[test_case(&json!({"items": {"type": ["null", "boolean"], "required": ["a"]}}), &["/items: 'required' never applies to the types allowed by 'type'"]; "ineffective keyword in subschema")]
+    #[test_case(&json!({"minLength": 5, "maxLength": 2}), &["/: 'minLength' is greater than 'maxLength'"]; "contradictory bounds")]
+    #[test_case(&json!({"type": "integer", "const": "a"}), &["/: 'const' value is not allowed by 'type'"]; "const type mismatch")]
+    #[test_case(&json!({"const": 1, "enum": [2, 3]}), &["/: 'const' value is not listed in 'enum'"]; "const not in enum")]
+    #[test_case(&json!({"type": "object", "enum": [1, "a"]}), &["/: None of the 'enum' values are allowed by 'type'"]; "enum type mismatch")]
+    #[test_case(&json!({"enum": []}), &["/: 'enum' has no values"]; "empty enum")]
+    #[test_case(
+        &json!({
+            "properties": {
+                "a": {"$ref": "#/$defs/bad"}
+            },
+            "$defs": {"bad": {"type": "array", "pattern": "^a"}}
+        }),
+        &["/properties/a/$ref: 'pattern' never applies to the types allowed by 'type'"];
+        "referenced subschema"
+    )]
+    #[test_case(&json!({"type": ["integer", "string"], "minimum": 1, "maxLength": 3, "const": 1, "enum": [1.0, 2]}), &[]; "satisfiable")]
+    #[test_case(&json!({"type": "integer", "minimum": 1.5, "maximum": 1.5}), &[]; "equal bounds")]
+    #[test_case(&json!({"$schema": "http://json-schema.org/draft-07/schema#", "prefixItems": [{"type": "integer"}]}), &["/: 'prefixItems' is ignored under Draft 7, as it was introduced in Draft 2020-12"]; "newer keyword")]
+    #[test_case(&json!({"$schema": "http://json-schema.org/draft-04/schema#", "properties": {"a": {"const": 1}}}), &["/properties/a: 'const' is ignored under Draft 4, as it was introduced in Draft 6"]; "newer keyword in subschema")]
+    #[test_case(&json!({"$schema": "https://json-schema.org/draft/2019-09/schema", "dependentRequired": {"a": ["b"]}}), &[]; "supported keyword")]
+    fn diagnostics(schema: &Value, expected: &[&str]) {
+        let validator = crate::options()
+            .should_report_diagnostics(true)
+            .build(schema)
+            .expect("Invalid schema");
+        let diagnostics: Vec<_> = validator
+            .diagnostics()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(diagnostics, expected);
+    }
+}
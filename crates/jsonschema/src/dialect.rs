@@ -0,0 +1,158 @@
+//! Custom JSON Schema dialects.
+use std::{fmt, sync::Arc};
+
+use ahash::AHashMap;
+use referencing::{Draft, Resource};
+use serde_json::{json, Map, Value};
+
+use crate::{keywords::custom::KeywordFactory, paths::Location, Keyword, ValidationError};
+
+/// A JSON Schema dialect identified by its meta-schema URI, e.g. the OpenAPI 3.1 one.
+///
+/// Schemas declaring the dialect URI in `$schema` are compiled with the semantics of the base
+/// [`Draft`], extended with the dialect keywords. Registered via
+/// [`ValidationOptions::with_dialect`](crate::ValidationOptions::with_dialect).
+///
+/// # Example
+///
+/// ```rust
+/// use jsonschema::{paths::{LazyLocation, Location}, Dialect, Draft, Keyword, ValidationError};
+/// use serde_json::{json, Value};
+///
+/// struct Even(Location);
+///
+/// impl Keyword for Even {
+///     fn validate<'i>(
+///         &self,
+///         instance: &'i Value,
+///         location: &LazyLocation,
+///     ) -> Result<(), ValidationError<'i>> {
+///         if self.is_valid(instance) {
+///             Ok(())
+///         } else {
+///             Err(ValidationError::custom(
+///                 self.0.clone(),
+///                 location.into(),
+///                 instance,
+///                 "Value is not even",
+///             ))
+///         }
+///     }
+///     fn is_valid(&self, instance: &Value) -> bool {
+///         instance.as_u64().map_or(true, |value| value % 2 == 0)
+///     }
+/// }
+///
+/// let dialect = Dialect::new("https://example.com/dialect", Draft::Draft202012)
+///     .with_keyword("x-even", |_, _, location| Ok(Box::new(Even(location))));
+/// let validator = jsonschema::options()
+///     .with_dialect(dialect)
+///     .build(&json!({"$schema": "https://example.com/dialect", "x-even": true}))
+///     .expect("Invalid schema");
+///
+/// assert!(validator.is_valid(&json!(2)));
+/// assert!(!validator.is_valid(&json!(3)));
+/// ```
+#[derive(Clone)]
+pub struct Dialect {
+    id: String,
+    draft: Draft,
+    meta_schema: Option<Value>,
+    pub(crate) keywords: AHashMap<String, Arc<dyn KeywordFactory>>,
+}
+
+impl Dialect {
+    /// Create a dialect identified by `id` and following the semantics of `draft`.
+    pub fn new(id: impl Into<String>, draft: Draft) -> Dialect {
+        let mut id = id.into();
+        if id.ends_with('#') {
+            id.pop();
+        }
+        Dialect {
+            id,
+            draft,
+            meta_schema: None,
+            keywords: AHashMap::new(),
+        }
+    }
+    /// Set the meta-schema of this dialect, instead of the meta-schema of its draft.
+    ///
+    /// It becomes addressable by the dialect URI, and its `$vocabulary` (if any) defines the
+    /// vocabularies enabled for schemas using this dialect.
+    #[must_use]
+    pub fn with_meta_schema(mut self, meta_schema: Value) -> Dialect {
+        self.meta_schema = Some(meta_schema);
+        self
+    }
+    /// Add a keyword that is available only in schemas using this dialect.
+    ///
+    /// See [`ValidationOptions::with_keyword`](crate::ValidationOptions::with_keyword).
+    #[must_use]
+    pub fn with_keyword<N, F>(mut self, name: N, factory: F) -> Dialect
+    where
+        N: Into<String>,
+        F: for<'a> Fn(
+                &'a Map<String, Value>,
+                &'a Value,
+                Location,
+            ) -> Result<Box<dyn Keyword>, ValidationError<'a>>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.keywords.insert(name.into(), Arc::new(factory));
+        self
+    }
+    /// The URI identifying this dialect.
+    #[must_use]
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+    /// The draft defining the semantics of this dialect.
+    #[must_use]
+    pub fn draft(&self) -> Draft {
+        self.draft
+    }
+    /// The meta-schema as a resource addressable by the dialect URI.
+    /// Defaults to the meta-schema of the dialect draft.
+    pub(crate) fn meta_schema(&self) -> Result<Resource, referencing::Error> {
+        if let Some(contents) = &self.meta_schema {
+            // Dialect meta-schemas often declare themselves in `$schema`
+            Ok(Resource::from_contents(contents.clone())
+                .unwrap_or_else(|_| self.draft.create_resource(contents.clone())))
+        } else {
+            let uri = match self.draft {
+                Draft::Draft4 => "http://json-schema.org/draft-04/schema#",
+                Draft::Draft6 => "http://json-schema.org/draft-06/schema#",
+                Draft::Draft7 => "http://json-schema.org/draft-07/schema#",
+                Draft::Draft201909 => "https://json-schema.org/draft/2019-09/schema",
+                Draft::Draft202012 => "https://json-schema.org/draft/2020-12/schema",
+                _ => {
+                    return Err(referencing::Error::UnknownSpecification {
+                        specification: self.id.clone(),
+                    })
+                }
+            };
+            Ok(self
+                .draft
+                .create_resource(json!({"$schema": uri, "$ref": uri})))
+        }
+    }
+    /// Whether `contents` declare this dialect in `$schema`.
+    pub(crate) fn is_declared_by(&self, contents: &Value) -> bool {
+        contents
+            .get("$schema")
+            .and_then(Value::as_str)
+            .is_some_and(|schema| schema.trim_end_matches('#') == self.id)
+    }
+}
+
+impl fmt::Debug for Dialect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Dialect")
+            .field("id", &self.id)
+            .field("draft", &self.draft)
+            .field("keywords", &self.keywords.keys())
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,490 @@
+//! Documentation extracted from schemas.
+//!
+//! [`extract`] walks a schema once, following references, and collects the annotations and
+//! constraints of every reachable subschema by its canonical location, so documentation
+//! generators do not need to interpret keywords and resolve references themselves.
+//!
+//! ```rust
+//! use jsonschema::{Registry, Resource};
+//! use serde_json::json;
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let port = json!({"type": "integer", "maximum": 65535, "examples": [8080]});
+//! let registry = Registry::try_new(
+//!     "https://example.com/port.json",
+//!     Resource::from_contents(port)?,
+//! )?;
+//! let schema = json!({
+//!     "$id": "https://example.com/server.json",
+//!     "title": "Server",
+//!     "properties": {"port": {"$ref": "port.json"}}
+//! });
+//! let docs = jsonschema::docs::extract(&schema, &registry)?;
+//! assert_eq!(docs.root(), "https://example.com/server.json#");
+//! let root = docs.get(docs.root()).expect("Root is documented");
+//! assert_eq!(root.title(), Some("Server"));
+//!
+//! let child = &root.children()[0];
+//! assert_eq!(child.path(), "properties/port");
+//! let property = docs.get(child.location()).expect("Property is documented");
+//! assert_eq!(property.reference(), Some("https://example.com/port.json#"));
+//! let port = docs.get("https://example.com/port.json#").expect("Target is documented");
+//! assert_eq!(port.types(), ["integer"]);
+//! assert_eq!(port.constraints()["maximum"], json!(65535));
+//! assert_eq!(port.examples(), [json!(8080)]);
+//! # Ok(())
+//! # }
+//! ```
+use std::collections::BTreeMap;
+
+use ahash::AHashMap;
+use referencing::{uri, Draft, Error, Registry, Resolver, Resource};
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::{
+    analyze::{
+        DEFINITION_KEYWORDS, REFERENCE_KEYWORDS, SCHEMA_ARRAY_KEYWORDS, SCHEMA_KEYWORDS,
+        SCHEMA_MAP_KEYWORDS,
+    },
+    compiler::DEFAULT_BASE_URI,
+};
+
+/// Keywords that constrain instances, apart from `type` and those with subschemas.
+const CONSTRAINT_KEYWORDS: &[&str] = &[
+    "const",
+    "contentEncoding",
+    "contentMediaType",
+    "dependentRequired",
+    "enum",
+    "exclusiveMaximum",
+    "exclusiveMinimum",
+    "format",
+    "maxContains",
+    "maxItems",
+    "maxLength",
+    "maxProperties",
+    "maximum",
+    "minContains",
+    "minItems",
+    "minLength",
+    "minProperties",
+    "minimum",
+    "multipleOf",
+    "pattern",
+    "required",
+    "uniqueItems",
+];
+
+/// Documentation of a schema and of the subschemas reachable from it, see [`extract`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Documentation {
+    root: String,
+    schemas: BTreeMap<String, SchemaDoc>,
+}
+
+impl Documentation {
+    /// The canonical location of the root schema.
+    #[must_use]
+    pub fn root(&self) -> &str {
+        &self.root
+    }
+    /// The documentation of the schema at this canonical location.
+    #[must_use]
+    pub fn get(&self, location: &str) -> Option<&SchemaDoc> {
+        self.schemas.get(location)
+    }
+    /// All documented schemas, ordered by their canonical locations.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &SchemaDoc)> {
+        self.schemas
+            .iter()
+            .map(|(location, schema)| (location.as_str(), schema))
+    }
+}
+
+/// Documentation of a single schema, see [`Documentation`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SchemaDoc {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    types: Vec<String>,
+    #[serde(skip_serializing_if = "Map::is_empty")]
+    constraints: Map<String, Value>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    examples: Vec<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    default: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reference: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    children: Vec<ChildDoc>,
+}
+
+impl SchemaDoc {
+    /// The `title` annotation.
+    #[must_use]
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+    /// The `description` annotation.
+    #[must_use]
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+    /// The types allowed by `type`, empty if it is absent.
+    #[must_use]
+    pub fn types(&self) -> &[String] {
+        &self.types
+    }
+    /// Keywords constraining instances, e.g. `minimum` or `pattern`, with their values.
+    #[must_use]
+    pub fn constraints(&self) -> &Map<String, Value> {
+        &self.constraints
+    }
+    /// The `examples` annotation.
+    #[must_use]
+    pub fn examples(&self) -> &[Value] {
+        &self.examples
+    }
+    /// The `default` annotation.
+    #[must_use]
+    pub fn default_value(&self) -> Option<&Value> {
+        self.default.as_ref()
+    }
+    /// The canonical location of the schema referenced by `$ref`, `$dynamicRef` or
+    /// `$recursiveRef`, resolved statically.
+    #[must_use]
+    pub fn reference(&self) -> Option<&str> {
+        self.reference.as_deref()
+    }
+    /// Subschemas of this schema, e.g. its `properties`, in the order of the schema.
+    #[must_use]
+    pub fn children(&self) -> &[ChildDoc] {
+        &self.children
+    }
+}
+
+/// A subschema of a [`SchemaDoc`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ChildDoc {
+    path: String,
+    location: String,
+}
+
+impl ChildDoc {
+    /// The JSON Pointer of the subschema relative to its parent, e.g. `properties/name`.
+    #[must_use]
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+    /// The canonical location of the subschema.
+    #[must_use]
+    pub fn location(&self) -> &str {
+        &self.location
+    }
+}
+
+fn escape(segment: &str) -> String {
+    segment.replace('~', "~0").replace('/', "~1")
+}
+
+/// Subschemas of a schema with their paths relative to it.
+fn subschemas(object: &Map<String, Value>, definitions: bool) -> Vec<(String, &Value)> {
+    let mut subschemas = Vec::new();
+    for (keyword, value) in object {
+        let keyword = keyword.as_str();
+        match value {
+            Value::Object(_) | Value::Bool(_) if SCHEMA_KEYWORDS.contains(&keyword) => {
+                subschemas.push((escape(keyword), value));
+            }
+            Value::Array(items) if SCHEMA_ARRAY_KEYWORDS.contains(&keyword) => {
+                for (idx, item) in items.iter().enumerate() {
+                    subschemas.push((format!("{}/{idx}", escape(keyword)), item));
+                }
+            }
+            // Definitions are documented only when they are referenced
+            Value::Object(schemas)
+                if SCHEMA_MAP_KEYWORDS.contains(&keyword)
+                    && (definitions || !DEFINITION_KEYWORDS.contains(&keyword)) =>
+            {
+                for (name, schema) in schemas {
+                    // `dependencies` also holds arrays of property names
+                    if schema.is_object() || schema.is_boolean() {
+                        subschemas.push((format!("{}/{}", escape(keyword), escape(name)), schema));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    subschemas
+}
+
+struct Extractor<'r> {
+    registry: &'r Registry,
+    /// Canonical locations of the subschemas of indexed documents, by their addresses
+    locations: AHashMap<*const Value, String>,
+    schemas: BTreeMap<String, SchemaDoc>,
+}
+
+impl<'r> Extractor<'r> {
+    /// Record the canonical locations of a schema and its subschemas.
+    fn index(&mut self, location: String, value: &'r Value) {
+        if let Value::Object(object) = value {
+            for (path, subschema) in subschemas(object, true) {
+                self.index(format!("{location}/{path}"), subschema);
+            }
+        }
+        self.locations.insert(value, location);
+    }
+
+    /// The canonical location of `target`, which `reference` resolves to.
+    ///
+    /// Targets in documents that are not indexed yet are located relative to the resource
+    /// containing them.
+    fn locate(
+        &mut self,
+        resolver: &Resolver<'r>,
+        reference: &str,
+        target: &'r Value,
+    ) -> Result<String, Error> {
+        let address: *const Value = target;
+        if !self.locations.contains_key(&address) {
+            let uri = resolver.resolve_against(&resolver.base_uri().borrow(), reference)?;
+            let mut document = (*uri).clone();
+            document.set_fragment(None);
+            let (contents, _, _) = self
+                .registry
+                .resolver(document.clone())
+                .lookup("")?
+                .into_inner();
+            self.index(format!("{document}#"), contents);
+        }
+        Ok(self.locations[&address].clone())
+    }
+
+    /// Document a schema and everything reachable from it.
+    fn document(
+        &mut self,
+        location: String,
+        value: &'r Value,
+        resolver: &Resolver<'r>,
+        draft: Draft,
+    ) -> Result<(), Error> {
+        if self.schemas.contains_key(&location) {
+            return Ok(());
+        }
+        // Placeholder for schemas that reference themselves
+        self.schemas.insert(location.clone(), SchemaDoc::default());
+        let mut schema = SchemaDoc::default();
+        if let Value::Object(object) = value {
+            let draft = draft.detect(value).unwrap_or(draft);
+            let resolver = resolver.in_subresource(draft.create_resource_ref(value))?;
+            let text = |keyword| {
+                object
+                    .get(keyword)
+                    .and_then(Value::as_str)
+                    .map(String::from)
+            };
+            schema.title = text("title");
+            schema.description = text("description");
+            schema.types = match object.get("type") {
+                Some(Value::String(ty)) => vec![ty.clone()],
+                Some(Value::Array(types)) => types
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .map(String::from)
+                    .collect(),
+                _ => Vec::new(),
+            };
+            schema.constraints = CONSTRAINT_KEYWORDS
+                .iter()
+                .filter_map(|keyword| Some((keyword.to_string(), object.get(*keyword)?.clone())))
+                .collect();
+            if let Some(Value::Array(examples)) = object.get("examples") {
+                schema.examples.clone_from(examples);
+            }
+            schema.default = object.get("default").cloned();
+            for keyword in REFERENCE_KEYWORDS {
+                if let Some(Value::String(reference)) = object.get(*keyword) {
+                    let (contents, target_resolver, draft) =
+                        resolver.lookup(reference)?.into_inner();
+                    let target = self.locate(&resolver, reference, contents)?;
+                    self.document(target.clone(), contents, &target_resolver, draft)?;
+                    schema.reference = Some(target);
+                    break;
+                }
+            }
+            for (path, subschema) in subschemas(object, false) {
+                let child = format!("{location}/{path}");
+                self.document(child.clone(), subschema, &resolver, draft)?;
+                schema.children.push(ChildDoc {
+                    path,
+                    location: child,
+                });
+            }
+        }
+        self.schemas.insert(location, schema);
+        Ok(())
+    }
+}
+
+/// Extract the documentation of a schema and of every subschema reachable from it, including
+/// through references.
+///
+/// Schemas are identified by their canonical location: the URI of the document they are in,
+/// followed by a JSON Pointer within it, e.g. `https://example.com/root.json#/properties/name`.
+/// A schema without `$id` is identified by `json-schema:///`. References to other documents are
+/// resolved against `registry`. Definitions are documented only when they are referenced.
+///
+/// # Errors
+///
+/// Returns an error if a URI is invalid or if a reference can not be resolved.
+pub fn extract(schema: &Value, registry: &Registry) -> Result<Documentation, Error> {
+    let resource = Resource::from_contents(schema.clone())?;
+    let uri = resource.id().unwrap_or(DEFAULT_BASE_URI).to_string();
+    let registry = registry.clone().try_with_resource(&uri, resource)?;
+    extract_at(&uri, &registry)
+}
+
+/// Extract the documentation of the schema that `registry` holds at `uri`.
+pub(crate) fn extract_at(uri: &str, registry: &Registry) -> Result<Documentation, Error> {
+    let mut document = uri::from_str(uri)?;
+    document.set_fragment(None);
+    let (schema, resolver, draft) = registry.resolver(document.clone()).lookup("")?.into_inner();
+    let mut extractor = Extractor {
+        registry,
+        locations: AHashMap::new(),
+        schemas: BTreeMap::new(),
+    };
+    let root = format!("{document}#");
+    extractor.index(root.clone(), schema);
+    extractor.document(root.clone(), schema, &resolver, draft)?;
+    Ok(Documentation {
+        root,
+        schemas: extractor.schemas,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use referencing::Resource;
+    use serde_json::json;
+
+    fn empty() -> Registry {
+        Registry::try_from_resources(std::iter::empty::<(&str, Resource)>())
+            .expect("Invalid registry")
+    }
+
+    #[test]
+    fn annotations_and_constraints() {
+        let schema = json!({
+            "description": "A name",
+            "type": ["string", "null"],
+            "minLength": 1,
+            "pattern": "^[a-z]+$",
+            "default": "x",
+            "$comment": "not documented"
+        });
+        let docs = extract(&schema, &empty()).expect("Extraction failed");
+        assert_eq!(docs.root(), "json-schema:///#");
+        let root = docs.get(docs.root()).expect("Missing root");
+        assert_eq!(root.description(), Some("A name"));
+        assert_eq!(root.types(), ["string", "null"]);
+        assert_eq!(
+            Value::Object(root.constraints().clone()),
+            json!({"minLength": 1, "pattern": "^[a-z]+$"})
+        );
+        assert_eq!(root.default_value(), Some(&json!("x")));
+        assert_eq!(docs.iter().count(), 1);
+    }
+
+    #[test]
+    fn children_and_definitions() {
+        let schema = json!({
+            "properties": {"a/b": {"type": "string"}},
+            "items": [{"type": "integer"}],
+            "$defs": {"unused": {"type": "null"}}
+        });
+        let docs = extract(&schema, &empty()).expect("Extraction failed");
+        let locations: Vec<_> = docs.iter().map(|(location, _)| location).collect();
+        assert_eq!(
+            locations,
+            [
+                "json-schema:///#",
+                "json-schema:///#/items/0",
+                "json-schema:///#/properties/a~1b"
+            ]
+        );
+        let paths: Vec<_> = docs
+            .get(docs.root())
+            .expect("Missing root")
+            .children()
+            .iter()
+            .map(ChildDoc::path)
+            .collect();
+        assert_eq!(paths, ["items/0", "properties/a~1b"]);
+    }
+
+    #[test]
+    fn recursive_references() {
+        let schema = json!({
+            "$id": "https://example.com/tree.json",
+            "$defs": {
+                "node": {
+                    "$anchor": "node",
+                    "title": "Node",
+                    "properties": {"children": {"items": {"$ref": "#node"}}}
+                }
+            },
+            "$ref": "#/$defs/node"
+        });
+        let docs = extract(&schema, &empty()).expect("Extraction failed");
+        let root = docs.get(docs.root()).expect("Missing root");
+        let node = "https://example.com/tree.json#/$defs/node";
+        assert_eq!(root.reference(), Some(node));
+        assert_eq!(docs.get(node).and_then(SchemaDoc::title), Some("Node"));
+        let items = docs
+            .get(&format!("{node}/properties/children/items"))
+            .expect("Missing items");
+        assert_eq!(items.reference(), Some(node));
+    }
+
+    #[test]
+    fn embedded_resources() {
+        let schema = json!({
+            "$id": "https://example.com/root.json",
+            "properties": {
+                "a": {"$ref": "item.json"},
+                "b": {"$id": "item.json", "$ref": "#/$defs/leaf", "$defs": {"leaf": {"type": "boolean"}}}
+            }
+        });
+        let docs = extract(&schema, &empty()).expect("Extraction failed");
+        let b = "https://example.com/root.json#/properties/b";
+        assert_eq!(
+            docs.get("https://example.com/root.json#/properties/a")
+                .and_then(SchemaDoc::reference),
+            Some(b)
+        );
+        let leaf = docs
+            .get(b)
+            .and_then(SchemaDoc::reference)
+            .expect("Missing reference");
+        assert_eq!(leaf, format!("{b}/$defs/leaf"));
+        assert_eq!(docs.get(leaf).expect("Missing leaf").types(), ["boolean"]);
+    }
+
+    #[test]
+    fn unresolvable_reference() {
+        let schema = json!({"$ref": "#/$defs/missing"});
+        let error = extract(&schema, &empty()).expect_err("Should fail");
+        assert!(matches!(error, Error::PointerToNowhere { .. }));
+        let schema = json!({"$ref": "#missing"});
+        let error = extract(&schema, &empty()).expect_err("Should fail");
+        assert!(matches!(error, Error::NoSuchAnchor { .. }));
+    }
+}
@@ -59,6 +59,10 @@ pub struct ValidationError<'a> {
     pub instance_path: Location,
     /// Path to the JSON Schema keyword that failed validation.
     pub schema_path: Location,
+    /// The property whose presence applied the dependent schema the error comes from, i.e. the
+    /// key in `dependentSchemas` or `dependencies`. `None` for errors outside of dependent
+    /// schemas.
+    pub trigger: Option<String>,
 }
 
 /// An iterator over instances of [`ValidationError`] that represent validation error for the
@@ -78,7 +82,13 @@ pub struct ValidationError<'a> {
 ///     }
 /// }
 /// ```
+#[cfg(not(jsonschema_single_threaded))]
 pub type ErrorIterator<'a> = Box<dyn Iterator<Item = ValidationError<'a>> + Sync + Send + 'a>;
+/// An iterator over instances of [`ValidationError`] that represent validation error for the
+/// input instance, which is neither `Send` nor `Sync` when built with
+/// `--cfg jsonschema_single_threaded`.
+#[cfg(jsonschema_single_threaded)]
+pub type ErrorIterator<'a> = Box<dyn Iterator<Item = ValidationError<'a>> + 'a>;
 
 // Empty iterator means no error happened
 pub(crate) fn no_error<'a>() -> ErrorIterator<'a> {
@@ -95,10 +105,19 @@ pub(crate) fn error(instance: ValidationError) -> ErrorIterator {
 pub enum ValidationErrorKind {
     /// The input array contain more items than expected.
     AdditionalItems { limit: usize },
-    /// Unexpected properties.
-    AdditionalProperties { unexpected: Vec<String> },
+    /// Unexpected properties, with declared property names they are likely misspellings of, as
+    /// `(unexpected, declared)` pairs.
+    AdditionalProperties {
+        unexpected: Vec<String>,
+        suggestions: Vec<(String, String)>,
+    },
     /// The input value is not valid under any of the schemas listed in the 'anyOf' keyword.
-    AnyOf,
+    AnyOf {
+        /// Errors of each subschema.
+        context: Vec<Vec<ValidationError<'static>>>,
+    },
+    /// Validation was aborted via a cancellation token or its deadline.
+    Cancelled,
     /// Results from a [`fancy_regex::RuntimeError::BacktrackLimitExceeded`] variant when matching
     BacktrackLimitExceeded { error: fancy_regex::Error },
     /// The input value doesn't match expected constant.
@@ -111,6 +130,15 @@ pub enum ValidationErrorKind {
     ContentMediaType { content_media_type: String },
     /// Custom error message for user-defined validation.
     Custom { message: String },
+    /// A `date` or `date-time` value is outside of the configured
+    /// [`DateBounds`](crate::DateBounds).
+    DateOutOfBounds { format: String, bound: DateBound },
+    /// Properties required by `dependentRequired`, or an array in `dependencies`, are missing
+    /// from an object that contains the triggering `property`.
+    DependentRequired {
+        property: String,
+        missing: Vec<String>,
+    },
     /// The input value doesn't match any of specified options.
     Enum { options: Value },
     /// Value is too large.
@@ -139,24 +167,55 @@ pub enum ValidationErrorKind {
     MinLength { limit: u64 },
     /// Not enough properties in an object.
     MinProperties { limit: u64 },
+    /// All required properties missing from an object, reported in addition to their `Required`
+    /// errors if enabled via [`ValidationOptions::should_report_missing_properties`].
+    ///
+    /// [`ValidationOptions::should_report_missing_properties`]: crate::ValidationOptions::should_report_missing_properties
+    MissingProperties { properties: Vec<String> },
     /// When some number is not a multiple of another number.
     MultipleOf { multiple_of: f64 },
     /// Negated schema failed validation.
-    Not { schema: Value },
+    Not {
+        schema: Value,
+        /// Schema locations of the assertions within the negated schema that accept the
+        /// instance, i.e. why the negation fails. Applicators such as `anyOf` are replaced with
+        /// the assertions of their matching subschemas.
+        matched: Vec<Location>,
+    },
     /// The given schema is valid under more than one of the schemas listed in the 'oneOf' keyword.
     OneOfMultipleValid,
     /// The given schema is not valid under any of the schemas listed in the 'oneOf' keyword.
-    OneOfNotValid,
+    OneOfNotValid {
+        /// Errors of each subschema.
+        context: Vec<Vec<ValidationError<'static>>>,
+    },
     /// When the input doesn't match to a pattern.
     Pattern { pattern: String },
     /// Object property names are invalid.
     PropertyNames {
+        /// The key that failed validation.
+        property: String,
         error: Box<ValidationError<'static>>,
     },
     /// When a required property is missing.
     Required { property: Value },
+    /// The input value exceeds one of the configured size limits.
+    SizeLimitExceeded { kind: SizeLimitKind, limit: usize },
+    /// Compiling the schema exceeds one of the configured
+    /// [`CompilationLimits`](crate::CompilationLimits).
+    CompilationLimitExceeded {
+        kind: CompilationLimitKind,
+        limit: usize,
+        /// For [`CompilationLimitKind::ReferenceDepth`], the URIs of the references forming the
+        /// cycle that exceeds the limit, with the first one repeated at the end. Empty if the
+        /// nested references do not form a cycle.
+        cycle: Vec<String>,
+    },
     /// When the input value doesn't match one or multiple required types.
     Type { kind: TypeKind },
+    /// A schema variable placeholder refers to a variable missing from the context given to
+    /// [`ValidationOptions::with_variables`](crate::ValidationOptions::with_variables).
+    UndefinedVariable { name: String },
     /// Unexpected items.
     UnevaluatedItems { unexpected: Vec<String> },
     /// Unexpected properties.
@@ -165,6 +224,12 @@ pub enum ValidationErrorKind {
     UniqueItems,
     /// Error during schema ref resolution.
     Referencing(referencing::Error),
+    /// A reference cannot be resolved in a subschema that other references lead to.
+    ReferencingVia {
+        error: referencing::Error,
+        /// The references followed from the root schema, starting with the first one.
+        breadcrumbs: referencing::Breadcrumbs,
+    },
 }
 
 #[derive(Debug)]
@@ -174,6 +239,260 @@ pub enum TypeKind {
     Multiple(JsonTypeSet),
 }
 
+/// The instance characteristic guarded by a size limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SizeLimitKind {
+    /// Length of a string (in bytes) matched against `pattern`.
+    StringLength,
+    /// Number of properties in an object.
+    Properties,
+    /// Number of items in an array.
+    Items,
+}
+
+impl SizeLimitKind {
+    /// Returns the name of the limited characteristic.
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            SizeLimitKind::StringLength => "string length",
+            SizeLimitKind::Properties => "properties",
+            SizeLimitKind::Items => "items",
+        }
+    }
+}
+
+/// The bound of [`DateBounds`](crate::DateBounds) that a value violates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateBound {
+    /// The value is before the earliest allowed time.
+    Earliest,
+    /// The value is after the latest allowed time.
+    Latest,
+}
+
+impl DateBound {
+    /// Returns the name of the bound.
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            DateBound::Earliest => "earliest",
+            DateBound::Latest => "latest",
+        }
+    }
+}
+
+/// The compilation characteristic guarded by a limit, see
+/// [`CompilationLimits`](crate::CompilationLimits).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompilationLimitKind {
+    /// Number of references compiled in place inside each other.
+    ReferenceDepth,
+    /// Number of compiled subschemas.
+    Subschemas,
+}
+
+impl CompilationLimitKind {
+    /// Returns the name of the limited characteristic.
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            CompilationLimitKind::ReferenceDepth => "reference depth",
+            CompilationLimitKind::Subschemas => "subschemas",
+        }
+    }
+}
+
+impl ValidationErrorKind {
+    /// Returns a stable identifier of this error kind, e.g. `minLength` or `oneOfNotValid`.
+    ///
+    /// Identifiers match the keyword names wherever a keyword produces a single kind of error
+    /// and are suitable as keys of a message catalog.
+    #[must_use]
+    pub const fn name(&self) -> &'static str {
+        match self {
+            ValidationErrorKind::AdditionalItems { .. } => "additionalItems",
+            ValidationErrorKind::AdditionalProperties { .. } => "additionalProperties",
+            ValidationErrorKind::AnyOf { .. } => "anyOf",
+            ValidationErrorKind::Cancelled => "cancelled",
+            ValidationErrorKind::CompilationLimitExceeded { .. } => "compilationLimitExceeded",
+            ValidationErrorKind::BacktrackLimitExceeded { .. } => "backtrackLimitExceeded",
+            ValidationErrorKind::Constant { .. } => "const",
+            ValidationErrorKind::Contains => "contains",
+            ValidationErrorKind::ContentEncoding { .. } => "contentEncoding",
+            ValidationErrorKind::ContentMediaType { .. } => "contentMediaType",
+            ValidationErrorKind::Custom { .. } => "custom",
+            ValidationErrorKind::DateOutOfBounds { .. } => "dateOutOfBounds",
+            ValidationErrorKind::DependentRequired { .. } => "dependentRequired",
+            ValidationErrorKind::Enum { .. } => "enum",
+            ValidationErrorKind::ExclusiveMaximum { .. } => "exclusiveMaximum",
+            ValidationErrorKind::ExclusiveMinimum { .. } => "exclusiveMinimum",
+            ValidationErrorKind::FalseSchema => "falseSchema",
+            ValidationErrorKind::Format { .. } => "format",
+            ValidationErrorKind::FromUtf8 { .. } => "fromUtf8",
+            ValidationErrorKind::MaxItems { .. } => "maxItems",
+            ValidationErrorKind::Maximum { .. } => "maximum",
+            ValidationErrorKind::MaxLength { .. } => "maxLength",
+            ValidationErrorKind::MaxProperties { .. } => "maxProperties",
+            ValidationErrorKind::MinItems { .. } => "minItems",
+            ValidationErrorKind::Minimum { .. } => "minimum",
+            ValidationErrorKind::MinLength { .. } => "minLength",
+            ValidationErrorKind::MinProperties { .. } => "minProperties",
+            ValidationErrorKind::MissingProperties { .. } => "missingProperties",
+            ValidationErrorKind::MultipleOf { .. } => "multipleOf",
+            ValidationErrorKind::Not { .. } => "not",
+            ValidationErrorKind::OneOfMultipleValid => "oneOfMultipleValid",
+            ValidationErrorKind::OneOfNotValid { .. } => "oneOfNotValid",
+            ValidationErrorKind::Pattern { .. } => "pattern",
+            ValidationErrorKind::PropertyNames { .. } => "propertyNames",
+            ValidationErrorKind::Required { .. } => "required",
+            ValidationErrorKind::SizeLimitExceeded { .. } => "sizeLimitExceeded",
+            ValidationErrorKind::Type { .. } => "type",
+            ValidationErrorKind::UndefinedVariable { .. } => "undefinedVariable",
+            ValidationErrorKind::UnevaluatedItems { .. } => "unevaluatedItems",
+            ValidationErrorKind::UnevaluatedProperties { .. } => "unevaluatedProperties",
+            ValidationErrorKind::UniqueItems => "uniqueItems",
+            ValidationErrorKind::Referencing(_) | ValidationErrorKind::ReferencingVia { .. } => {
+                "referencing"
+            }
+        }
+    }
+
+    /// Returns the values the error message is built from, keyed by parameter name.
+    ///
+    /// For example, `maxLength` errors have a `limit` parameter and `required` errors have a
+    /// `property` parameter. Kinds without any details have no parameters.
+    #[must_use]
+    pub fn parameters(&self) -> Map<String, Value> {
+        let mut parameters = Map::new();
+        let mut insert = |name: &str, value: Value| {
+            parameters.insert(name.to_string(), value);
+        };
+        match self {
+            ValidationErrorKind::AdditionalItems { limit } => insert("limit", (*limit).into()),
+            ValidationErrorKind::AdditionalProperties {
+                unexpected,
+                suggestions,
+            } => {
+                insert("unexpected", unexpected.clone().into());
+                if !suggestions.is_empty() {
+                    insert(
+                        "suggestions",
+                        suggestions
+                            .iter()
+                            .map(|(unexpected, declared)| {
+                                (unexpected.clone(), Value::from(declared.as_str()))
+                            })
+                            .collect::<Map<String, Value>>()
+                            .into(),
+                    );
+                }
+            }
+            ValidationErrorKind::UnevaluatedItems { unexpected }
+            | ValidationErrorKind::UnevaluatedProperties { unexpected } => {
+                insert("unexpected", unexpected.clone().into());
+            }
+            ValidationErrorKind::BacktrackLimitExceeded { error } => {
+                insert("error", error.to_string().into());
+            }
+            ValidationErrorKind::Constant { expected_value } => {
+                insert("expected", expected_value.clone());
+            }
+            ValidationErrorKind::ContentEncoding { content_encoding } => {
+                insert("contentEncoding", content_encoding.clone().into());
+            }
+            ValidationErrorKind::ContentMediaType { content_media_type } => {
+                insert("contentMediaType", content_media_type.clone().into());
+            }
+            ValidationErrorKind::CompilationLimitExceeded { kind, limit, cycle } => {
+                insert("kind", kind.as_str().into());
+                insert("limit", (*limit).into());
+                insert("cycle", cycle.clone().into());
+            }
+            ValidationErrorKind::Custom { message } => insert("message", message.clone().into()),
+            ValidationErrorKind::DateOutOfBounds { format, bound } => {
+                insert("format", format.clone().into());
+                insert("bound", bound.as_str().into());
+            }
+            ValidationErrorKind::DependentRequired { property, missing } => {
+                insert("property", property.clone().into());
+                insert("missing", missing.clone().into());
+            }
+            ValidationErrorKind::Enum { options } => insert("options", options.clone()),
+            ValidationErrorKind::ExclusiveMaximum { limit }
+            | ValidationErrorKind::ExclusiveMinimum { limit }
+            | ValidationErrorKind::Maximum { limit }
+            | ValidationErrorKind::Minimum { limit } => insert("limit", limit.clone()),
+            ValidationErrorKind::Format { format } => insert("format", format.clone().into()),
+            ValidationErrorKind::FromUtf8 { error } => insert("error", error.to_string().into()),
+            ValidationErrorKind::MaxItems { limit }
+            | ValidationErrorKind::MaxLength { limit }
+            | ValidationErrorKind::MaxProperties { limit }
+            | ValidationErrorKind::MinItems { limit }
+            | ValidationErrorKind::MinLength { limit }
+            | ValidationErrorKind::MinProperties { limit } => insert("limit", (*limit).into()),
+            ValidationErrorKind::MissingProperties { properties } => {
+                insert("properties", properties.clone().into());
+            }
+            ValidationErrorKind::MultipleOf { multiple_of } => {
+                insert("multipleOf", (*multiple_of).into());
+            }
+            ValidationErrorKind::Not { schema, matched } => {
+                insert("schema", schema.clone());
+                insert(
+                    "matched",
+                    matched
+                        .iter()
+                        .map(Location::as_str)
+                        .collect::<Vec<_>>()
+                        .into(),
+                );
+            }
+            ValidationErrorKind::Pattern { pattern } => insert("pattern", pattern.clone().into()),
+            ValidationErrorKind::PropertyNames { property, error } => {
+                insert("property", property.clone().into());
+                insert("error", error.to_string().into());
+            }
+            ValidationErrorKind::Required { property } => insert("property", property.clone()),
+            ValidationErrorKind::SizeLimitExceeded { kind, limit } => {
+                insert("kind", kind.as_str().into());
+                insert("limit", (*limit).into());
+            }
+            ValidationErrorKind::UndefinedVariable { name } => insert("name", name.clone().into()),
+            ValidationErrorKind::Type { kind } => {
+                let types = match kind {
+                    TypeKind::Single(type_) => vec![type_.to_string()],
+                    TypeKind::Multiple(types) => types.iter().map(|t| t.to_string()).collect(),
+                };
+                insert("types", types.into());
+            }
+            ValidationErrorKind::Referencing(error) => insert("error", error.to_string().into()),
+            ValidationErrorKind::ReferencingVia { error, breadcrumbs } => {
+                insert("error", error.to_string().into());
+                insert(
+                    "via",
+                    breadcrumbs
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect::<Vec<_>>()
+                        .into(),
+                );
+            }
+            ValidationErrorKind::AnyOf { context }
+            | ValidationErrorKind::OneOfNotValid { context } => {
+                let context: Vec<Vec<String>> = context
+                    .iter()
+                    .map(|errors| errors.iter().map(ToString::to_string).collect())
+                    .collect();
+                insert("context", context.into());
+            }
+            ValidationErrorKind::Cancelled
+            | ValidationErrorKind::Contains
+            | ValidationErrorKind::FalseSchema
+            | ValidationErrorKind::OneOfMultipleValid
+            | ValidationErrorKind::UniqueItems => {}
+        }
+        parameters
+    }
+}
+
 /// Shortcuts for creation of specific error kinds.
 impl<'a> ValidationError<'a> {
     /// Returns a wrapper that masks instance values in error messages.
@@ -192,6 +511,74 @@ impl<'a> ValidationError<'a> {
             placeholder: placeholder.into(),
         }
     }
+    /// Renders the error using a message template instead of the default text.
+    ///
+    /// Placeholders in braces are replaced with the matching [`ValidationErrorKind::parameters`],
+    /// or with `instance`, `instance_path`, `schema_path` and, within dependent schemas, `trigger`. String parameters are inserted
+    /// verbatim, array parameters as comma-separated items and other values as JSON, while
+    /// `instance` is always inserted as JSON, just like in the default messages. Unknown placeholders are kept as is and `{{` / `}}` produce
+    /// literal braces.
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use serde_json::json;
+    ///
+    /// let validator = jsonschema::validator_for(&json!({"maxLength": 5}))?;
+    /// let instance = json!("sensitive data");
+    /// let error = validator.validate(&instance).expect_err("Too long");
+    /// assert_eq!(error.kind.name(), "maxLength");
+    /// assert_eq!(
+    ///     error.format_with("{instance} est plus long que {limit} caractères"),
+    ///     r#""sensitive data" est plus long que 5 caractères"#
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn format_with(&self, template: &str) -> String {
+        let parameters = self.kind.parameters();
+        let mut output = String::with_capacity(template.len());
+        let mut rest = template;
+        while let Some(position) = rest.find(['{', '}']) {
+            output.push_str(&rest[..position]);
+            rest = &rest[position..];
+            if rest.starts_with("{{") || rest.starts_with("}}") {
+                output.push_str(&rest[..1]);
+                rest = &rest[2..];
+                continue;
+            }
+            let placeholder = rest[1..]
+                .find('}')
+                .filter(|_| rest.starts_with('{'))
+                .map(|end| &rest[1..=end]);
+            let value = placeholder.and_then(|name| match name {
+                "instance" => Some(self.instance.to_string()),
+                "instance_path" => Some(self.instance_path.to_string()),
+                "schema_path" => Some(self.schema_path.to_string()),
+                "trigger" => self.trigger.clone(),
+                _ => parameters.get(name).map(|value| match value {
+                    Value::Array(items) => items
+                        .iter()
+                        .map(parameter_to_string)
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    value => parameter_to_string(value),
+                }),
+            });
+            match (placeholder, value) {
+                (Some(name), Some(value)) => {
+                    output.push_str(&value);
+                    rest = &rest[name.len() + 2..];
+                }
+                _ => {
+                    output.push_str(&rest[..1]);
+                    rest = &rest[1..];
+                }
+            }
+        }
+        output.push_str(rest);
+        output
+    }
     /// Converts the `ValidationError` into an owned version with `'static` lifetime.
     pub fn to_owned(self) -> ValidationError<'static> {
         ValidationError {
@@ -199,9 +586,50 @@ impl<'a> ValidationError<'a> {
             instance: Cow::Owned(self.instance.into_owned()),
             kind: self.kind,
             schema_path: self.schema_path,
+            trigger: self.trigger,
         }
     }
 
+    /// The keyword that every `anyOf` / `oneOf` branch failed on, if there are several branches
+    /// and each of them failed with a single error at the instance location of the combinator.
+    pub(crate) fn common_branch_failure(&self) -> Option<&str> {
+        let (ValidationErrorKind::AnyOf { context }
+        | ValidationErrorKind::OneOfNotValid { context }) = &self.kind
+        else {
+            return None;
+        };
+        if context.len() < 2 {
+            return None;
+        }
+        let mut keyword = None;
+        for errors in context {
+            let [error] = errors.as_slice() else {
+                return None;
+            };
+            if error.instance_path != self.instance_path {
+                return None;
+            }
+            let current = error.schema_path.as_str().rsplit('/').next()?;
+            if *keyword.get_or_insert(current) != current {
+                return None;
+            }
+        }
+        keyword
+    }
+
+    /// The message of an `anyOf` / `oneOf` error without branch errors in its `context`, followed
+    /// by the messages of branches that all failed on `keyword`.
+    pub(crate) fn with_branch_messages(
+        &self,
+        keyword: &str,
+        messages: impl Iterator<Item = impl fmt::Display>,
+    ) -> String {
+        let mut message = self.to_string();
+        write_branch_messages(&mut message, keyword, messages)
+            .expect("Writing to a string never fails");
+        message
+    }
+
     pub(crate) const fn additional_items(
         location: Location,
         instance_path: Location,
@@ -213,6 +641,7 @@ impl<'a> ValidationError<'a> {
             instance: Cow::Borrowed(instance),
             kind: ValidationErrorKind::AdditionalItems { limit },
             schema_path: location,
+            trigger: None,
         }
     }
     pub(crate) const fn additional_properties(
@@ -220,24 +649,33 @@ impl<'a> ValidationError<'a> {
         instance_path: Location,
         instance: &'a Value,
         unexpected: Vec<String>,
+        suggestions: Vec<(String, String)>,
     ) -> ValidationError<'a> {
         ValidationError {
             instance_path,
             instance: Cow::Borrowed(instance),
-            kind: ValidationErrorKind::AdditionalProperties { unexpected },
+            kind: ValidationErrorKind::AdditionalProperties {
+                unexpected,
+                suggestions,
+            },
             schema_path: location,
+            trigger: None,
         }
     }
-    pub(crate) const fn any_of(
+    pub(crate) fn any_of(
         location: Location,
         instance_path: Location,
         instance: &'a Value,
+        context: Vec<Vec<ValidationError<'a>>>,
     ) -> ValidationError<'a> {
         ValidationError {
             instance_path,
             instance: Cow::Borrowed(instance),
-            kind: ValidationErrorKind::AnyOf,
+            kind: ValidationErrorKind::AnyOf {
+                context: into_owned_context(context),
+            },
             schema_path: location,
+            trigger: None,
         }
     }
     pub(crate) const fn backtrack_limit(
@@ -251,6 +689,23 @@ impl<'a> ValidationError<'a> {
             instance: Cow::Borrowed(instance),
             kind: ValidationErrorKind::BacktrackLimitExceeded { error },
             schema_path: location,
+            trigger: None,
+        }
+    }
+    pub(crate) fn constant(
+        location: Location,
+        instance_path: Location,
+        instance: &'a Value,
+        expected_value: &Value,
+    ) -> ValidationError<'a> {
+        ValidationError {
+            instance_path,
+            instance: Cow::Borrowed(instance),
+            kind: ValidationErrorKind::Constant {
+                expected_value: expected_value.clone(),
+            },
+            schema_path: location,
+            trigger: None,
         }
     }
     pub(crate) fn constant_array(
@@ -266,6 +721,7 @@ impl<'a> ValidationError<'a> {
                 expected_value: Value::Array(expected_value.to_vec()),
             },
             schema_path: location,
+            trigger: None,
         }
     }
     pub(crate) const fn constant_boolean(
@@ -281,6 +737,7 @@ impl<'a> ValidationError<'a> {
                 expected_value: Value::Bool(expected_value),
             },
             schema_path: location,
+            trigger: None,
         }
     }
     pub(crate) const fn constant_null(
@@ -295,6 +752,7 @@ impl<'a> ValidationError<'a> {
                 expected_value: Value::Null,
             },
             schema_path: location,
+            trigger: None,
         }
     }
     pub(crate) fn constant_number(
@@ -310,6 +768,7 @@ impl<'a> ValidationError<'a> {
                 expected_value: Value::Number(expected_value.clone()),
             },
             schema_path: location,
+            trigger: None,
         }
     }
     pub(crate) fn constant_object(
@@ -325,6 +784,7 @@ impl<'a> ValidationError<'a> {
                 expected_value: Value::Object(expected_value.clone()),
             },
             schema_path: location,
+            trigger: None,
         }
     }
     pub(crate) fn constant_string(
@@ -340,6 +800,7 @@ impl<'a> ValidationError<'a> {
                 expected_value: Value::String(expected_value.to_string()),
             },
             schema_path: location,
+            trigger: None,
         }
     }
     pub(crate) const fn contains(
@@ -352,6 +813,7 @@ impl<'a> ValidationError<'a> {
             instance: Cow::Borrowed(instance),
             kind: ValidationErrorKind::Contains,
             schema_path: location,
+            trigger: None,
         }
     }
     pub(crate) fn content_encoding(
@@ -367,6 +829,7 @@ impl<'a> ValidationError<'a> {
                 content_encoding: encoding.to_string(),
             },
             schema_path: location,
+            trigger: None,
         }
     }
     pub(crate) fn content_media_type(
@@ -382,6 +845,22 @@ impl<'a> ValidationError<'a> {
                 content_media_type: media_type.to_string(),
             },
             schema_path: location,
+            trigger: None,
+        }
+    }
+    pub(crate) const fn dependent_required(
+        location: Location,
+        instance_path: Location,
+        instance: &'a Value,
+        property: String,
+        missing: Vec<String>,
+    ) -> ValidationError<'a> {
+        ValidationError {
+            instance_path,
+            instance: Cow::Borrowed(instance),
+            kind: ValidationErrorKind::DependentRequired { property, missing },
+            schema_path: location,
+            trigger: None,
         }
     }
     pub(crate) fn enumeration(
@@ -397,6 +876,7 @@ impl<'a> ValidationError<'a> {
                 options: options.clone(),
             },
             schema_path: location,
+            trigger: None,
         }
     }
     pub(crate) const fn exclusive_maximum(
@@ -410,6 +890,7 @@ impl<'a> ValidationError<'a> {
             instance: Cow::Borrowed(instance),
             kind: ValidationErrorKind::ExclusiveMaximum { limit },
             schema_path: location,
+            trigger: None,
         }
     }
     pub(crate) const fn exclusive_minimum(
@@ -423,6 +904,20 @@ impl<'a> ValidationError<'a> {
             instance: Cow::Borrowed(instance),
             kind: ValidationErrorKind::ExclusiveMinimum { limit },
             schema_path: location,
+            trigger: None,
+        }
+    }
+    pub(crate) const fn cancelled(
+        location: Location,
+        instance_path: Location,
+        instance: &'a Value,
+    ) -> ValidationError<'a> {
+        ValidationError {
+            instance_path,
+            instance: Cow::Borrowed(instance),
+            kind: ValidationErrorKind::Cancelled,
+            schema_path: location,
+            trigger: None,
         }
     }
     pub(crate) const fn false_schema(
@@ -435,6 +930,7 @@ impl<'a> ValidationError<'a> {
             instance: Cow::Borrowed(instance),
             kind: ValidationErrorKind::FalseSchema,
             schema_path: location,
+            trigger: None,
         }
     }
     pub(crate) fn format(
@@ -450,6 +946,7 @@ impl<'a> ValidationError<'a> {
                 format: format.into(),
             },
             schema_path: location,
+            trigger: None,
         }
     }
     pub(crate) fn from_utf8(error: FromUtf8Error) -> ValidationError<'a> {
@@ -458,6 +955,7 @@ impl<'a> ValidationError<'a> {
             instance: Cow::Owned(Value::Null),
             kind: ValidationErrorKind::FromUtf8 { error },
             schema_path: Location::new(),
+            trigger: None,
         }
     }
     pub(crate) const fn max_items(
@@ -471,6 +969,7 @@ impl<'a> ValidationError<'a> {
             instance: Cow::Borrowed(instance),
             kind: ValidationErrorKind::MaxItems { limit },
             schema_path: location,
+            trigger: None,
         }
     }
     pub(crate) const fn maximum(
@@ -484,6 +983,7 @@ impl<'a> ValidationError<'a> {
             instance: Cow::Borrowed(instance),
             kind: ValidationErrorKind::Maximum { limit },
             schema_path: location,
+            trigger: None,
         }
     }
     pub(crate) const fn max_length(
@@ -497,6 +997,7 @@ impl<'a> ValidationError<'a> {
             instance: Cow::Borrowed(instance),
             kind: ValidationErrorKind::MaxLength { limit },
             schema_path: location,
+            trigger: None,
         }
     }
     pub(crate) const fn max_properties(
@@ -510,6 +1011,7 @@ impl<'a> ValidationError<'a> {
             instance: Cow::Borrowed(instance),
             kind: ValidationErrorKind::MaxProperties { limit },
             schema_path: location,
+            trigger: None,
         }
     }
     pub(crate) const fn min_items(
@@ -523,6 +1025,7 @@ impl<'a> ValidationError<'a> {
             instance: Cow::Borrowed(instance),
             kind: ValidationErrorKind::MinItems { limit },
             schema_path: location,
+            trigger: None,
         }
     }
     pub(crate) const fn minimum(
@@ -536,6 +1039,7 @@ impl<'a> ValidationError<'a> {
             instance: Cow::Borrowed(instance),
             kind: ValidationErrorKind::Minimum { limit },
             schema_path: location,
+            trigger: None,
         }
     }
     pub(crate) const fn min_length(
@@ -549,6 +1053,7 @@ impl<'a> ValidationError<'a> {
             instance: Cow::Borrowed(instance),
             kind: ValidationErrorKind::MinLength { limit },
             schema_path: location,
+            trigger: None,
         }
     }
     pub(crate) const fn min_properties(
@@ -562,6 +1067,21 @@ impl<'a> ValidationError<'a> {
             instance: Cow::Borrowed(instance),
             kind: ValidationErrorKind::MinProperties { limit },
             schema_path: location,
+            trigger: None,
+        }
+    }
+    pub(crate) const fn missing_properties(
+        location: Location,
+        instance_path: Location,
+        instance: &'a Value,
+        properties: Vec<String>,
+    ) -> ValidationError<'a> {
+        ValidationError {
+            instance_path,
+            instance: Cow::Borrowed(instance),
+            kind: ValidationErrorKind::MissingProperties { properties },
+            schema_path: location,
+            trigger: None,
         }
     }
     pub(crate) const fn multiple_of(
@@ -575,6 +1095,7 @@ impl<'a> ValidationError<'a> {
             instance: Cow::Borrowed(instance),
             kind: ValidationErrorKind::MultipleOf { multiple_of },
             schema_path: location,
+            trigger: None,
         }
     }
     pub(crate) const fn not(
@@ -582,12 +1103,14 @@ impl<'a> ValidationError<'a> {
         instance_path: Location,
         instance: &'a Value,
         schema: Value,
+        matched: Vec<Location>,
     ) -> ValidationError<'a> {
         ValidationError {
             instance_path,
             instance: Cow::Borrowed(instance),
-            kind: ValidationErrorKind::Not { schema },
+            kind: ValidationErrorKind::Not { schema, matched },
             schema_path: location,
+            trigger: None,
         }
     }
     pub(crate) const fn one_of_multiple_valid(
@@ -600,18 +1123,23 @@ impl<'a> ValidationError<'a> {
             instance: Cow::Borrowed(instance),
             kind: ValidationErrorKind::OneOfMultipleValid,
             schema_path: location,
+            trigger: None,
         }
     }
-    pub(crate) const fn one_of_not_valid(
+    pub(crate) fn one_of_not_valid(
         location: Location,
         instance_path: Location,
         instance: &'a Value,
+        context: Vec<Vec<ValidationError<'a>>>,
     ) -> ValidationError<'a> {
         ValidationError {
             instance_path,
             instance: Cow::Borrowed(instance),
-            kind: ValidationErrorKind::OneOfNotValid,
+            kind: ValidationErrorKind::OneOfNotValid {
+                context: into_owned_context(context),
+            },
             schema_path: location,
+            trigger: None,
         }
     }
     pub(crate) const fn pattern(
@@ -625,21 +1153,74 @@ impl<'a> ValidationError<'a> {
             instance: Cow::Borrowed(instance),
             kind: ValidationErrorKind::Pattern { pattern },
             schema_path: location,
+            trigger: None,
+        }
+    }
+    pub(crate) const fn size_limit_exceeded(
+        location: Location,
+        instance_path: Location,
+        instance: &'a Value,
+        kind: SizeLimitKind,
+        limit: usize,
+    ) -> ValidationError<'a> {
+        ValidationError {
+            instance_path,
+            instance: Cow::Borrowed(instance),
+            kind: ValidationErrorKind::SizeLimitExceeded { kind, limit },
+            schema_path: location,
+            trigger: None,
+        }
+    }
+    pub(crate) fn date_out_of_bounds(
+        location: Location,
+        instance_path: Location,
+        instance: &'a Value,
+        format: &str,
+        bound: DateBound,
+    ) -> ValidationError<'a> {
+        ValidationError {
+            instance_path,
+            instance: Cow::Borrowed(instance),
+            kind: ValidationErrorKind::DateOutOfBounds {
+                format: format.to_string(),
+                bound,
+            },
+            schema_path: location,
+            trigger: None,
+        }
+    }
+    pub(crate) fn compilation_limit_exceeded(
+        location: Location,
+        instance_path: Location,
+        instance: &'a Value,
+        kind: CompilationLimitKind,
+        limit: usize,
+        cycle: Vec<String>,
+    ) -> ValidationError<'a> {
+        ValidationError {
+            instance_path,
+            instance: Cow::Borrowed(instance),
+            kind: ValidationErrorKind::CompilationLimitExceeded { kind, limit, cycle },
+            schema_path: location,
+            trigger: None,
         }
     }
     pub(crate) fn property_names(
         location: Location,
         instance_path: Location,
         instance: &'a Value,
+        property: &str,
         error: ValidationError<'a>,
     ) -> ValidationError<'a> {
         ValidationError {
             instance_path,
             instance: Cow::Borrowed(instance),
             kind: ValidationErrorKind::PropertyNames {
+                property: property.to_string(),
                 error: Box::new(error.to_owned()),
             },
             schema_path: location,
+            trigger: None,
         }
     }
     pub(crate) const fn required(
@@ -653,6 +1234,7 @@ impl<'a> ValidationError<'a> {
             instance: Cow::Borrowed(instance),
             kind: ValidationErrorKind::Required { property },
             schema_path: location,
+            trigger: None,
         }
     }
 
@@ -669,6 +1251,7 @@ impl<'a> ValidationError<'a> {
                 kind: TypeKind::Single(type_name),
             },
             schema_path: location,
+            trigger: None,
         }
     }
     pub(crate) const fn multiple_type_error(
@@ -684,6 +1267,7 @@ impl<'a> ValidationError<'a> {
                 kind: TypeKind::Multiple(types),
             },
             schema_path: location,
+            trigger: None,
         }
     }
     pub(crate) const fn unevaluated_items(
@@ -697,6 +1281,7 @@ impl<'a> ValidationError<'a> {
             instance: Cow::Borrowed(instance),
             kind: ValidationErrorKind::UnevaluatedItems { unexpected },
             schema_path: location,
+            trigger: None,
         }
     }
     pub(crate) const fn unevaluated_properties(
@@ -710,6 +1295,7 @@ impl<'a> ValidationError<'a> {
             instance: Cow::Borrowed(instance),
             kind: ValidationErrorKind::UnevaluatedProperties { unexpected },
             schema_path: location,
+            trigger: None,
         }
     }
     pub(crate) const fn unique_items(
@@ -722,6 +1308,7 @@ impl<'a> ValidationError<'a> {
             instance: Cow::Borrowed(instance),
             kind: ValidationErrorKind::UniqueItems,
             schema_path: location,
+            trigger: None,
         }
     }
     /// Create a new custom validation error.
@@ -738,6 +1325,7 @@ impl<'a> ValidationError<'a> {
                 message: message.into(),
             },
             schema_path: location,
+            trigger: None,
         }
     }
 }
@@ -751,6 +1339,35 @@ impl From<referencing::Error> for ValidationError<'_> {
             instance: Cow::Owned(Value::Null),
             kind: ValidationErrorKind::Referencing(err),
             schema_path: Location::new(),
+            trigger: None,
+        }
+    }
+}
+impl ValidationError<'_> {
+    pub(crate) fn undefined_variable(
+        schema_path: Location,
+        name: &str,
+    ) -> ValidationError<'static> {
+        ValidationError {
+            instance_path: Location::new(),
+            instance: Cow::Owned(Value::Null),
+            kind: ValidationErrorKind::UndefinedVariable {
+                name: name.to_string(),
+            },
+            schema_path,
+            trigger: None,
+        }
+    }
+    pub(crate) fn referencing_via(
+        error: referencing::Error,
+        breadcrumbs: referencing::Breadcrumbs,
+    ) -> ValidationError<'static> {
+        ValidationError {
+            instance_path: Location::new(),
+            instance: Cow::Owned(Value::Null),
+            kind: ValidationErrorKind::ReferencingVia { error, breadcrumbs },
+            schema_path: Location::new(),
+            trigger: None,
         }
     }
 }
@@ -761,6 +1378,13 @@ impl From<FromUtf8Error> for ValidationError<'_> {
     }
 }
 
+fn parameter_to_string(value: &Value) -> String {
+    match value {
+        Value::String(value) => value.clone(),
+        value => value.to_string(),
+    }
+}
+
 fn write_quoted_list(f: &mut Formatter<'_>, items: &[impl fmt::Display]) -> fmt::Result {
     let mut iter = items.iter();
     if let Some(item) = iter.next() {
@@ -785,12 +1409,165 @@ fn write_unexpected_suffix(f: &mut Formatter<'_>, len: usize) -> fmt::Result {
     })
 }
 
+fn write_additional_properties(
+    f: &mut Formatter<'_>,
+    unexpected: &[String],
+    suggestions: &[(String, String)],
+) -> fmt::Result {
+    f.write_str("Additional properties are not allowed (")?;
+    write_quoted_list(f, unexpected)?;
+    if suggestions.is_empty() {
+        return write_unexpected_suffix(f, unexpected.len());
+    }
+    f.write_str(if unexpected.len() == 1 {
+        " was unexpected; did you mean "
+    } else {
+        " were unexpected; did you mean "
+    })?;
+    for (idx, (property, declared)) in suggestions.iter().enumerate() {
+        if idx > 0 {
+            f.write_str(", ")?;
+        }
+        write!(f, "'{declared}'")?;
+        if unexpected.len() > 1 {
+            write!(f, " instead of '{property}'")?;
+        }
+    }
+    f.write_str("?)")
+}
+
+fn write_missing_properties(f: &mut Formatter<'_>, properties: &[String]) -> fmt::Result {
+    f.write_str(if properties.len() == 1 {
+        "Missing required property: "
+    } else {
+        "Missing required properties: "
+    })?;
+    for (idx, property) in properties.iter().enumerate() {
+        if idx > 0 {
+            f.write_str(", ")?;
+        }
+        // Value enum is needed for proper string escaping
+        write!(f, "{}", Value::from(property.as_str()))?;
+    }
+    Ok(())
+}
+
+fn write_dependent_required(
+    f: &mut Formatter<'_>,
+    property: &str,
+    missing: &[String],
+) -> fmt::Result {
+    write!(
+        f,
+        "Missing propert{} required by {}: ",
+        if missing.len() == 1 { "y" } else { "ies" },
+        Value::from(property)
+    )?;
+    for (idx, name) in missing.iter().enumerate() {
+        if idx > 0 {
+            f.write_str(", ")?;
+        }
+        write!(f, "{}", Value::from(name.as_str()))?;
+    }
+    Ok(())
+}
+
+fn into_owned_context(
+    context: Vec<Vec<ValidationError<'_>>>,
+) -> Vec<Vec<ValidationError<'static>>> {
+    context
+        .into_iter()
+        .map(|errors| errors.into_iter().map(ValidationError::to_owned).collect())
+        .collect()
+}
+
+/// Details of the failed `anyOf` / `oneOf` branches, if all of them failed for the same reason.
+fn write_branch_failures(
+    f: &mut Formatter<'_>,
+    error: &ValidationError<'_>,
+    context: &[Vec<ValidationError<'static>>],
+    message: impl Fn(&ValidationError<'static>) -> String,
+) -> fmt::Result {
+    let Some(keyword) = error.common_branch_failure() else {
+        return Ok(());
+    };
+    write_branch_messages(f, keyword, context.iter().map(|errors| message(&errors[0])))
+}
+
+fn write_branch_messages(
+    f: &mut impl Write,
+    keyword: &str,
+    messages: impl Iterator<Item = impl fmt::Display>,
+) -> fmt::Result {
+    write!(f, ", '{keyword}' failed in every branch:")?;
+    for (idx, message) in messages.enumerate() {
+        let separator = if idx == 0 { "" } else { ";" };
+        write!(f, "{separator} [{idx}] {message}")?;
+    }
+    Ok(())
+}
+
+fn write_matched(f: &mut Formatter<'_>, matched: &[Location]) -> fmt::Result {
+    if matched.is_empty() {
+        return Ok(());
+    }
+    f.write_str(" (it matches ")?;
+    write_quoted_list(f, matched)?;
+    f.write_char(')')
+}
+
+fn write_size_limit(f: &mut Formatter<'_>, kind: SizeLimitKind, limit: usize) -> fmt::Result {
+    match kind {
+        SizeLimitKind::StringLength => write!(
+            f,
+            "String is longer than the configured limit of {limit} byte{}",
+            if limit == 1 { "" } else { "s" }
+        ),
+        SizeLimitKind::Properties => write!(
+            f,
+            "Object has more than the configured limit of {limit} propert{}",
+            if limit == 1 { "y" } else { "ies" }
+        ),
+        SizeLimitKind::Items => write!(
+            f,
+            "Array has more than the configured limit of {limit} item{}",
+            if limit == 1 { "" } else { "s" }
+        ),
+    }
+}
+
+fn write_compilation_limit(
+    f: &mut Formatter<'_>,
+    kind: CompilationLimitKind,
+    limit: usize,
+    cycle: &[String],
+) -> fmt::Result {
+    match kind {
+        CompilationLimitKind::ReferenceDepth if cycle.is_empty() => write!(
+            f,
+            "References are nested deeper than the compilation depth limit of {limit}"
+        ),
+        CompilationLimitKind::ReferenceDepth => write!(
+            f,
+            "Reference cycle exceeds the compilation depth limit of {limit}: {}",
+            cycle.join(" -> ")
+        ),
+        CompilationLimitKind::Subschemas => write!(
+            f,
+            "Schema compiles to more than the limit of {limit} subschemas"
+        ),
+    }
+}
+
 /// Textual representation of various validation errors.
 impl fmt::Display for ValidationError<'_> {
     #[allow(clippy::too_many_lines)] // The function is long but it does formatting only
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match &self.kind {
             ValidationErrorKind::Referencing(error) => error.fmt(f),
+            ValidationErrorKind::ReferencingVia { error, breadcrumbs } => {
+                write!(f, "{error} (via {breadcrumbs})")
+            }
             ValidationErrorKind::BacktrackLimitExceeded { error } => error.fmt(f),
             ValidationErrorKind::Format { format } => {
                 write!(f, r#"{} is not a "{}""#, self.instance, format)
@@ -810,21 +1587,27 @@ impl fmt::Display for ValidationError<'_> {
 
                 write_unexpected_suffix(f, array.len() - limit)
             }
-            ValidationErrorKind::AdditionalProperties { unexpected } => {
-                f.write_str("Additional properties are not allowed (")?;
-                write_quoted_list(f, unexpected)?;
-                write_unexpected_suffix(f, unexpected.len())
+            ValidationErrorKind::AdditionalProperties {
+                unexpected,
+                suggestions,
+            } => write_additional_properties(f, unexpected, suggestions),
+            ValidationErrorKind::AnyOf { context } => {
+                write!(
+                    f,
+                    "{} is not valid under any of the schemas listed in the 'anyOf' keyword",
+                    self.instance
+                )?;
+                write_branch_failures(f, self, context, ToString::to_string)
+            }
+            ValidationErrorKind::Cancelled => f.write_str("Validation was cancelled"),
+            ValidationErrorKind::OneOfNotValid { context } => {
+                write!(
+                    f,
+                    "{} is not valid under any of the schemas listed in the 'oneOf' keyword",
+                    self.instance
+                )?;
+                write_branch_failures(f, self, context, ToString::to_string)
             }
-            ValidationErrorKind::AnyOf => write!(
-                f,
-                "{} is not valid under any of the schemas listed in the 'anyOf' keyword",
-                self.instance
-            ),
-            ValidationErrorKind::OneOfNotValid => write!(
-                f,
-                "{} is not valid under any of the schemas listed in the 'oneOf' keyword",
-                self.instance
-            ),
             ValidationErrorKind::Contains => write!(
                 f,
                 "None of {} are valid under the given schema",
@@ -914,8 +1697,9 @@ impl fmt::Display for ValidationError<'_> {
                 limit,
                 if *limit == 1 { "y" } else { "ies" }
             ),
-            ValidationErrorKind::Not { schema } => {
-                write!(f, "{} is not allowed for {}", schema, self.instance)
+            ValidationErrorKind::Not { schema, matched } => {
+                write!(f, "{} is not allowed for {}", schema, self.instance)?;
+                write_matched(f, matched)
             }
             ValidationErrorKind::OneOfMultipleValid => write!(
                 f,
@@ -925,10 +1709,19 @@ impl fmt::Display for ValidationError<'_> {
             ValidationErrorKind::Pattern { pattern } => {
                 write!(f, r#"{} does not match "{}""#, self.instance, pattern)
             }
-            ValidationErrorKind::PropertyNames { error } => error.fmt(f),
+            ValidationErrorKind::PropertyNames { error, .. } => error.fmt(f),
+            ValidationErrorKind::SizeLimitExceeded { kind, limit } => {
+                write_size_limit(f, *kind, *limit)
+            }
+            ValidationErrorKind::CompilationLimitExceeded { kind, limit, cycle } => {
+                write_compilation_limit(f, *kind, *limit, cycle)
+            }
             ValidationErrorKind::Required { property } => {
                 write!(f, "{} is a required property", property)
             }
+            ValidationErrorKind::MissingProperties { properties } => {
+                write_missing_properties(f, properties)
+            }
             ValidationErrorKind::MultipleOf { multiple_of } => {
                 write!(f, "{} is not a multiple of {}", self.instance, multiple_of)
             }
@@ -945,6 +1738,9 @@ impl fmt::Display for ValidationError<'_> {
             ValidationErrorKind::UniqueItems => {
                 write!(f, "{} has non-unique elements", self.instance)
             }
+            ValidationErrorKind::UndefinedVariable { name } => {
+                write!(f, "Undefined schema variable '{name}'")
+            }
             ValidationErrorKind::Type {
                 kind: TypeKind::Single(type_),
             } => write!(f, r#"{} is not of type "{}""#, self.instance, type_),
@@ -967,6 +1763,21 @@ impl fmt::Display for ValidationError<'_> {
                 Ok(())
             }
             ValidationErrorKind::Custom { message } => f.write_str(message),
+            ValidationErrorKind::DateOutOfBounds { format, bound } => {
+                let relation = match bound {
+                    DateBound::Earliest => "before",
+                    DateBound::Latest => "after",
+                };
+                write!(
+                    f,
+                    "{} is {relation} the {} allowed {format}",
+                    self.instance,
+                    bound.as_str()
+                )
+            }
+            ValidationErrorKind::DependentRequired { property, missing } => {
+                write_dependent_required(f, property, missing)
+            }
         }
     }
 }
@@ -982,6 +1793,9 @@ impl fmt::Display for MaskedValidationError<'_, '_, '_> {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match &self.error.kind {
             ValidationErrorKind::Referencing(error) => error.fmt(f),
+            ValidationErrorKind::ReferencingVia { error, breadcrumbs } => {
+                write!(f, "{error} (via {breadcrumbs})")
+            }
             ValidationErrorKind::BacktrackLimitExceeded { error } => error.fmt(f),
             ValidationErrorKind::Format { format } => {
                 write!(f, r#"{} is not a "{format}""#, self.placeholder)
@@ -989,21 +1803,31 @@ impl fmt::Display for MaskedValidationError<'_, '_, '_> {
             ValidationErrorKind::AdditionalItems { limit } => {
                 write!(f, "Additional items are not allowed ({limit} items)")
             }
-            ValidationErrorKind::AdditionalProperties { unexpected } => {
-                f.write_str("Additional properties are not allowed (")?;
-                write_quoted_list(f, unexpected)?;
-                write_unexpected_suffix(f, unexpected.len())
+            ValidationErrorKind::AdditionalProperties {
+                unexpected,
+                suggestions,
+            } => write_additional_properties(f, unexpected, suggestions),
+            ValidationErrorKind::AnyOf { context } => {
+                write!(
+                    f,
+                    "{} is not valid under any of the schemas listed in the 'anyOf' keyword",
+                    self.placeholder
+                )?;
+                write_branch_failures(f, self.error, context, |error| {
+                    error.masked_with(&*self.placeholder).to_string()
+                })
+            }
+            ValidationErrorKind::Cancelled => f.write_str("Validation was cancelled"),
+            ValidationErrorKind::OneOfNotValid { context } => {
+                write!(
+                    f,
+                    "{} is not valid under any of the schemas listed in the 'oneOf' keyword",
+                    self.placeholder
+                )?;
+                write_branch_failures(f, self.error, context, |error| {
+                    error.masked_with(&*self.placeholder).to_string()
+                })
             }
-            ValidationErrorKind::AnyOf => write!(
-                f,
-                "{} is not valid under any of the schemas listed in the 'anyOf' keyword",
-                self.placeholder
-            ),
-            ValidationErrorKind::OneOfNotValid => write!(
-                f,
-                "{} is not valid under any of the schemas listed in the 'oneOf' keyword",
-                self.placeholder
-            ),
             ValidationErrorKind::Contains => write!(
                 f,
                 "None of {} are valid under the given schema",
@@ -1097,8 +1921,9 @@ impl fmt::Display for MaskedValidationError<'_, '_, '_> {
                 limit,
                 if *limit == 1 { "y" } else { "ies" }
             ),
-            ValidationErrorKind::Not { schema } => {
-                write!(f, "{} is not allowed for {}", schema, self.placeholder)
+            ValidationErrorKind::Not { schema, matched } => {
+                write!(f, "{} is not allowed for {}", schema, self.placeholder)?;
+                write_matched(f, matched)
             }
             ValidationErrorKind::OneOfMultipleValid => write!(
                 f,
@@ -1108,10 +1933,19 @@ impl fmt::Display for MaskedValidationError<'_, '_, '_> {
             ValidationErrorKind::Pattern { pattern } => {
                 write!(f, r#"{} does not match "{}""#, self.placeholder, pattern)
             }
-            ValidationErrorKind::PropertyNames { error } => error.fmt(f),
+            ValidationErrorKind::PropertyNames { error, .. } => error.fmt(f),
+            ValidationErrorKind::SizeLimitExceeded { kind, limit } => {
+                write_size_limit(f, *kind, *limit)
+            }
+            ValidationErrorKind::CompilationLimitExceeded { kind, limit, cycle } => {
+                write_compilation_limit(f, *kind, *limit, cycle)
+            }
             ValidationErrorKind::Required { property } => {
                 write!(f, "{} is a required property", property)
             }
+            ValidationErrorKind::MissingProperties { properties } => {
+                write_missing_properties(f, properties)
+            }
             ValidationErrorKind::MultipleOf { multiple_of } => {
                 write!(
                     f,
@@ -1134,6 +1968,9 @@ impl fmt::Display for MaskedValidationError<'_, '_, '_> {
             ValidationErrorKind::UniqueItems => {
                 write!(f, "{} has non-unique elements", self.placeholder)
             }
+            ValidationErrorKind::UndefinedVariable { name } => {
+                write!(f, "Undefined schema variable '{name}'")
+            }
             ValidationErrorKind::Type {
                 kind: TypeKind::Single(type_),
             } => write!(f, r#"{} is not of type "{}""#, self.placeholder, type_),
@@ -1156,6 +1993,21 @@ impl fmt::Display for MaskedValidationError<'_, '_, '_> {
                 Ok(())
             }
             ValidationErrorKind::Custom { message } => f.write_str(message),
+            ValidationErrorKind::DateOutOfBounds { format, bound } => {
+                let relation = match bound {
+                    DateBound::Earliest => "before",
+                    DateBound::Latest => "after",
+                };
+                write!(
+                    f,
+                    "{} is {relation} the {} allowed {format}",
+                    self.placeholder,
+                    bound.as_str()
+                )
+            }
+            ValidationErrorKind::DependentRequired { property, missing } => {
+                write_dependent_required(f, property, missing)
+            }
         }
     }
 }
@@ -1323,7 +2175,8 @@ mod tests {
     #[test_case(
         json!({"secret": "data", "key": "value"}),
         ValidationErrorKind::AdditionalProperties {
-            unexpected: vec!["secret".to_string(), "key".to_string()] 
+            unexpected: vec!["secret".to_string(), "key".to_string()],
+            suggestions: vec![],
         },
         "Additional properties are not allowed ('secret', 'key' were unexpected)"
     )]
@@ -1353,6 +2206,7 @@ mod tests {
             kind,
             instance_path: Location::new(),
             schema_path: Location::new(),
+            trigger: None,
         };
         assert_eq!(error.masked().to_string(), expected);
     }
@@ -1382,7 +2236,50 @@ mod tests {
             kind,
             instance_path: Location::new(),
             schema_path: Location::new(),
+            trigger: None,
         };
         assert_eq!(error.masked_with(placeholder).to_string(), expected);
     }
+
+    #[test_case(
+        json!("foo"),
+        ValidationErrorKind::Format { format: "email".to_string() },
+        "{instance} n'est pas un \"{format}\"",
+        "\"foo\" n'est pas un \"email\""
+    )]
+    #[test_case(
+        json!({}),
+        ValidationErrorKind::Required { property: json!("name") },
+        "{property} fehlt in {instance_path}",
+        "name fehlt in /a/0"
+    )]
+    #[test_case(
+        json!(1),
+        ValidationErrorKind::Type {
+            kind: TypeKind::Multiple(JsonTypeSet::empty().insert(JsonType::String).insert(JsonType::Null))
+        },
+        "{types} ({schema_path})",
+        "null, string (/type)"
+    )]
+    #[test_case(
+        json!(1),
+        ValidationErrorKind::Minimum { limit: json!(2) },
+        "{{{limit}}} {unknown} {limit",
+        "{2} {unknown} {limit"
+    )]
+    fn test_format_with(
+        instance: Value,
+        kind: ValidationErrorKind,
+        template: &str,
+        expected: &str,
+    ) {
+        let error = ValidationError {
+            instance: Cow::Owned(instance),
+            kind,
+            instance_path: Location::new().join("a").join(0),
+            schema_path: Location::new().join("type"),
+            trigger: None,
+        };
+        assert_eq!(error.format_with(template), expected);
+    }
 }
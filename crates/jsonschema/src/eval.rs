@@ -0,0 +1,340 @@
+//! Per-call settings for running a compiled validator.
+use std::{any::Any, cell::RefCell, fmt};
+
+use crate::{
+    evaluation::{EvaluatedSubschema, Recording},
+    CancellationToken, SizeLimits,
+};
+
+/// Settings for a single validation call that override how the validator was built.
+///
+/// The same compiled schema can then serve both strict and lenient paths, e.g. asserting formats
+/// only for data from untrusted sources. See [`Validator::validate_with`],
+/// [`Validator::is_valid_with`] and [`Validator::apply_with`].
+///
+/// # Example
+///
+/// ```rust
+/// use jsonschema::EvalOptions;
+/// use serde_json::json;
+///
+/// let validator = jsonschema::options()
+///     .should_validate_formats(false)
+///     .build(&json!({"format": "email"}))
+///     .expect("Invalid schema");
+/// let instance = json!("not an email");
+///
+/// assert!(validator.is_valid(&instance));
+/// let mut strict = EvalOptions::new().should_validate_formats(true);
+/// assert!(!validator.is_valid_with(&instance, &mut strict));
+/// ```
+///
+/// [`Validator::validate_with`]: crate::Validator::validate_with
+/// [`Validator::is_valid_with`]: crate::Validator::is_valid_with
+/// [`Validator::apply_with`]: crate::Validator::apply_with
+#[derive(Default)]
+pub struct EvalOptions {
+    validate_formats: Option<bool>,
+    size_limits: Option<SizeLimits>,
+    context: Option<Box<dyn Any + Send>>,
+}
+
+impl EvalOptions {
+    /// Create options that keep all settings of the validator.
+    #[must_use]
+    pub fn new() -> EvalOptions {
+        EvalOptions::default()
+    }
+    /// Override whether `format` keywords are assertions.
+    #[must_use]
+    pub fn should_validate_formats(mut self, yes: bool) -> EvalOptions {
+        self.validate_formats = Some(yes);
+        self
+    }
+    /// Replace the limits on the number of object properties and array items of the validator.
+    ///
+    /// The length limit of strings matched against `pattern` is fixed when the validator is built.
+    #[must_use]
+    pub fn with_size_limits(mut self, limits: SizeLimits) -> EvalOptions {
+        self.size_limits = Some(limits);
+        self
+    }
+    /// Set a value that custom keywords can read and modify during validation via
+    /// [`LazyLocation::with_eval_context`].
+    ///
+    /// [`LazyLocation::with_eval_context`]: crate::paths::LazyLocation::with_eval_context
+    #[must_use]
+    pub fn with_context<T: Any + Send>(mut self, context: T) -> EvalOptions {
+        self.context = Some(Box::new(context));
+        self
+    }
+    /// The context, if it is set and of type `T`.
+    #[must_use]
+    pub fn context<T: Any>(&self) -> Option<&T> {
+        self.context.as_ref()?.downcast_ref()
+    }
+    /// Remove the context, if it is set and of type `T`.
+    pub fn take_context<T: Any>(&mut self) -> Option<T> {
+        match self.context.take()?.downcast() {
+            Ok(context) => Some(*context),
+            Err(context) => {
+                self.context = Some(context);
+                None
+            }
+        }
+    }
+}
+
+impl fmt::Debug for EvalOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EvalOptions")
+            .field("validate_formats", &self.validate_formats)
+            .field("size_limits", &self.size_limits)
+            .field("context", &self.context.is_some())
+            .finish()
+    }
+}
+
+/// Per-call state of a validation, shared by all locations of the instance via
+/// [`LazyLocation`](crate::paths::LazyLocation).
+#[derive(Default)]
+pub(crate) struct Evaluation {
+    cancellation: Option<CancellationToken>,
+    validate_formats: Option<bool>,
+    size_limits: Option<SizeLimits>,
+    context: RefCell<Option<Box<dyn Any + Send>>>,
+    recording: Option<Recording>,
+}
+
+impl Evaluation {
+    /// An evaluation with the settings of `options`, which takes their context until
+    /// [`Evaluation::finish`].
+    pub(crate) fn new(options: &mut EvalOptions) -> Evaluation {
+        Evaluation {
+            cancellation: None,
+            validate_formats: options.validate_formats,
+            size_limits: options.size_limits,
+            context: RefCell::new(options.context.take()),
+            recording: None,
+        }
+    }
+    pub(crate) fn cancellable(token: &CancellationToken) -> Evaluation {
+        Evaluation {
+            cancellation: Some(token.clone()),
+            ..Evaluation::default()
+        }
+    }
+    /// An evaluation recording the subschemas that accept `pointer`, or any location if `None`.
+    pub(crate) fn recording(pointer: Option<&str>) -> Evaluation {
+        Evaluation {
+            recording: Some(Recording::new(pointer)),
+            ..Evaluation::default()
+        }
+    }
+    /// Hand the context back to `options`.
+    pub(crate) fn finish(self, options: &mut EvalOptions) {
+        options.context = self.context.into_inner();
+    }
+    #[inline]
+    pub(crate) fn is_cancelled(&self) -> bool {
+        self.cancellation
+            .as_ref()
+            .is_some_and(CancellationToken::is_cancelled)
+    }
+    /// Whether subschemas evaluated only for their validity, e.g. `anyOf` branches, depend on
+    /// this evaluation, so they have to be validated with their location.
+    #[inline]
+    pub(crate) fn is_overriding(&self) -> bool {
+        self.validate_formats.is_some() || self.context.borrow().is_some()
+    }
+    /// Whether `format` keywords are assertions.
+    #[inline]
+    pub(crate) fn validates_formats(&self, by_default: bool) -> bool {
+        self.validate_formats.unwrap_or(by_default)
+    }
+    #[inline]
+    pub(crate) fn recorder(&self) -> Option<&Recording> {
+        self.recording.as_ref()
+    }
+    /// Subschemas recorded during this evaluation.
+    pub(crate) fn recorded(self) -> Vec<EvaluatedSubschema> {
+        self.recording.map(Recording::finish).unwrap_or_default()
+    }
+    pub(crate) fn size_limits(&self) -> Option<SizeLimits> {
+        self.size_limits
+    }
+    pub(crate) fn with_context<T: Any, R>(&self, f: impl FnOnce(&mut T) -> R) -> Option<R> {
+        // Taken out while `f` runs, so it may validate other instances
+        let mut context = self.context.borrow_mut().take()?;
+        let result = context.downcast_mut().map(f);
+        *self.context.borrow_mut() = Some(context);
+        result
+    }
+}
+
+impl fmt::Debug for Evaluation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Evaluation")
+            .field("cancellation", &self.cancellation)
+            .field("validate_formats", &self.validate_formats)
+            .field("size_limits", &self.size_limits)
+            .field("recording", &self.recording.is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+impl PartialEq for Evaluation {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl Eq for Evaluation {}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    use super::EvalOptions;
+    use crate::SizeLimits;
+
+    #[test_case(None, Some(true), false; "enabled per call")]
+    #[test_case(Some(true), Some(false), true; "disabled per call")]
+    #[test_case(Some(true), None, false; "enabled by default")]
+    #[test_case(Some(false), None, true; "disabled by default")]
+    fn formats(by_default: Option<bool>, per_call: Option<bool>, expected: bool) {
+        let mut options = crate::options().with_draft(crate::Draft::Draft202012);
+        if let Some(yes) = by_default {
+            options = options.should_validate_formats(yes);
+        }
+        let validator = options
+            .build(&json!({"properties": {"ip": {"format": "ipv4"}}}))
+            .expect("Invalid schema");
+        let mut eval_options = EvalOptions::new();
+        if let Some(yes) = per_call {
+            eval_options = eval_options.should_validate_formats(yes);
+        }
+        let instance = json!({"ip": "localhost"});
+        assert_eq!(
+            validator.is_valid_with(&instance, &mut eval_options),
+            expected
+        );
+        assert_eq!(
+            validator
+                .validate_with(&instance, &mut eval_options)
+                .is_ok(),
+            expected
+        );
+        assert_eq!(
+            validator
+                .apply_with(&instance, &mut eval_options)
+                .is_valid(),
+            expected
+        );
+        // Overrides apply only to their call
+        assert_eq!(validator.is_valid(&instance), !by_default.unwrap_or(false));
+    }
+
+    #[test]
+    fn unknown_formats_are_reported_when_enabled_per_call() {
+        let validator = crate::options()
+            .should_validate_formats(false)
+            .should_ignore_unknown_formats(false)
+            .build(&json!({"format": "unknown"}))
+            .expect("Invalid schema");
+        assert!(validator.is_valid(&json!("a")));
+        let mut options = EvalOptions::new().should_validate_formats(true);
+        assert!(!validator.is_valid_with(&json!("a"), &mut options));
+        let instance = json!("a");
+        let error = validator
+            .validate_with(&instance, &mut options)
+            .expect_err("Should fail");
+        assert_eq!(
+            error.to_string(),
+            "Unknown format: 'unknown'. Adjust configuration to ignore unrecognized formats"
+        );
+    }
+
+    #[test]
+    fn formats_in_applicators() {
+        let validator = crate::options()
+            .with_draft(crate::Draft::Draft202012)
+            .build(&json!({
+                "anyOf": [{"format": "ipv4"}, {"type": "integer"}],
+                "if": {"format": "ipv4"},
+                "then": {"maxLength": 100},
+                "else": {"const": "localhost"}
+            }))
+            .expect("Invalid schema");
+        let instance = json!("localhost");
+        assert!(validator.is_valid(&instance));
+        let mut options = EvalOptions::new().should_validate_formats(true);
+        assert!(!validator.is_valid_with(&instance, &mut options));
+        assert!(validator
+            .validate_with(&json!("127.0.0.1"), &mut options)
+            .is_ok());
+    }
+
+    #[test]
+    fn size_limits() {
+        let validator = crate::options()
+            .with_size_limits(SizeLimits::new().max_properties(1))
+            .build(&json!({"type": "object"}))
+            .expect("Invalid schema");
+        let instance = json!({"a": 1, "b": 2});
+        assert!(!validator.is_valid(&instance));
+        let mut options = EvalOptions::new().with_size_limits(SizeLimits::new());
+        assert!(validator.is_valid_with(&instance, &mut options));
+        let mut options = EvalOptions::new().with_size_limits(SizeLimits::new().max_properties(0));
+        assert!(!validator
+            .apply_with(&json!({"a": 1}), &mut options)
+            .is_valid());
+    }
+
+    #[test]
+    fn context() {
+        let validator = crate::options()
+            .with_keyword("x-log", |_, value, _| {
+                let label = value.as_str().unwrap_or_default().to_string();
+                Ok(Box::new(Log(label)))
+            })
+            .build(&json!({"properties": {"a": {"x-log": "a"}, "b": {"x-log": "b"}}}))
+            .expect("Invalid schema");
+        let mut options = EvalOptions::new().with_context(Vec::<String>::new());
+        assert!(validator
+            .validate_with(&json!({"a": 1, "b": 2}), &mut options)
+            .is_ok());
+        assert_eq!(
+            options.context::<Vec<String>>().map(Vec::as_slice),
+            Some(&["a".to_string(), "b".to_string()][..])
+        );
+        assert_eq!(options.take_context::<String>(), None);
+        assert_eq!(
+            options.take_context::<Vec<String>>().map(|log| log.len()),
+            Some(2)
+        );
+        assert!(options.context::<Vec<String>>().is_none());
+        // No context outside of validation with options
+        assert_eq!(
+            crate::paths::LazyLocation::new().with_eval_context(|log: &mut Vec<String>| log.len()),
+            None
+        );
+    }
+
+    struct Log(String);
+
+    impl crate::Keyword for Log {
+        fn validate<'i>(
+            &self,
+            _: &'i Value,
+            location: &crate::paths::LazyLocation,
+        ) -> Result<(), crate::ValidationError<'i>> {
+            location.with_eval_context(|log: &mut Vec<String>| log.push(self.0.clone()));
+            Ok(())
+        }
+        fn is_valid(&self, _: &Value) -> bool {
+            true
+        }
+    }
+}
@@ -0,0 +1,80 @@
+//! Recording of subschemas that accepted a specific location in an instance, or any location.
+use std::cell::RefCell;
+
+use referencing::Uri;
+
+use crate::paths::{LazyLocation, Location};
+
+/// A subschema that accepted an instance location.
+///
+/// See [`Validator::evaluation_path`](crate::Validator::evaluation_path).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EvaluatedSubschema {
+    keyword_location: Location,
+    absolute_keyword_location: Option<Uri<String>>,
+}
+
+impl EvaluatedSubschema {
+    /// The location of the subschema, relative to the root of the schema.
+    #[must_use]
+    pub const fn keyword_location(&self) -> &Location {
+        &self.keyword_location
+    }
+    /// The absolute location of the subschema. This will be different to `keyword_location` if
+    /// the subschema is a resolved reference.
+    #[must_use]
+    pub fn absolute_keyword_location(&self) -> Option<Uri<&str>> {
+        self.absolute_keyword_location
+            .as_ref()
+            .map(|uri| uri.borrow())
+    }
+}
+
+/// Subschemas that accepted the recorded instance location during one evaluation.
+pub(crate) struct Recording {
+    /// `None` records subschemas accepting any location.
+    pointer: Option<String>,
+    accepted: RefCell<Vec<EvaluatedSubschema>>,
+}
+
+impl Recording {
+    pub(crate) fn new(pointer: Option<&str>) -> Recording {
+        Recording {
+            pointer: pointer.map(str::to_string),
+            accepted: RefCell::new(Vec::new()),
+        }
+    }
+    /// Subschemas in the order they were entered.
+    pub(crate) fn finish(self) -> Vec<EvaluatedSubschema> {
+        self.accepted.into_inner()
+    }
+    /// Run `apply` for a subschema and record it if it accepted the recorded instance location.
+    pub(crate) fn record<T>(
+        &self,
+        keyword_location: &Location,
+        absolute_keyword_location: Option<&Uri<String>>,
+        location: &LazyLocation,
+        apply: impl FnOnce() -> T,
+        is_valid: impl FnOnce(&T) -> bool,
+    ) -> T {
+        let matches = self
+            .pointer
+            .as_deref()
+            .map_or(true, |pointer| Location::from(location).as_str() == pointer);
+        let position = matches.then(|| self.accepted.borrow().len());
+        let result = apply();
+        if let Some(position) = position {
+            if is_valid(&result) {
+                // Parents are inserted before the subschemas they contain
+                self.accepted.borrow_mut().insert(
+                    position,
+                    EvaluatedSubschema {
+                        keyword_location: keyword_location.clone(),
+                        absolute_keyword_location: absolute_keyword_location.cloned(),
+                    },
+                );
+            }
+        }
+        result
+    }
+}
@@ -0,0 +1,254 @@
+//! Verification of `examples` and `default` values against the schemas declaring them.
+use std::{fmt, rc::Rc};
+
+use referencing::{uri, Registry};
+use serde_json::Value;
+
+use crate::{
+    compiler, docs,
+    paths::{LazyLocation, Location},
+    sync::Shared,
+    validator::Validate as _,
+    ValidationError, Validator,
+};
+
+/// An `examples` item or a `default` value rejected by the schema declaring it, see
+/// [`Validator::check_examples`].
+#[derive(Debug)]
+pub struct ExampleFailure {
+    schema_location: String,
+    keyword: &'static str,
+    index: Option<usize>,
+    value: Value,
+    errors: Vec<ValidationError<'static>>,
+}
+
+impl ExampleFailure {
+    /// The canonical location of the schema declaring the value, i.e. the URI of its document
+    /// followed by a JSON Pointer within it, e.g. `https://example.com/root.json#/properties/age`.
+    #[must_use]
+    pub fn schema_location(&self) -> &str {
+        &self.schema_location
+    }
+    /// The keyword holding the value, either `examples` or `default`.
+    #[must_use]
+    pub fn keyword(&self) -> &str {
+        self.keyword
+    }
+    /// The index of the value within `examples`, or `None` for `default`.
+    #[must_use]
+    pub fn index(&self) -> Option<usize> {
+        self.index
+    }
+    /// The rejected value.
+    #[must_use]
+    pub fn value(&self) -> &Value {
+        &self.value
+    }
+    /// Why the value is rejected, with schema paths relative to the schema declaring it.
+    #[must_use]
+    pub fn errors(&self) -> &[ValidationError<'static>] {
+        &self.errors
+    }
+}
+
+impl fmt::Display for ExampleFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.keyword)?;
+        if let Some(index) = self.index {
+            write!(f, "/{index}")?;
+        }
+        write!(f, " of {} is invalid: ", self.schema_location)?;
+        for (idx, error) in self.errors.iter().enumerate() {
+            if idx > 0 {
+                f.write_str("; ")?;
+            }
+            error.fmt(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Validate the `examples` and `default` values of every schema reachable from the root schema
+/// of `validator`, including through references.
+pub(crate) fn check(
+    validator: &Validator,
+) -> Result<Vec<ExampleFailure>, ValidationError<'static>> {
+    let registry = &validator.registry;
+    let base_uri = validator.base_uri.as_str();
+    let (schema, _, _) = registry.try_resolver(base_uri)?.lookup("")?.into_inner();
+    let documentation = docs::extract_at(base_uri, registry)?;
+    let vocabularies = validator
+        .config
+        .vocabularies_for(registry, validator.draft(), schema)?;
+    let mut failures = Vec::new();
+    let mut fragment = String::new();
+    for (location, doc) in documentation.iter() {
+        let values: Vec<_> = doc
+            .examples()
+            .iter()
+            .enumerate()
+            .map(|(idx, value)| ("examples", Some(idx), value))
+            .chain(doc.default_value().map(|value| ("default", None, value)))
+            .collect();
+        if values.is_empty() {
+            continue;
+        }
+        // Compile the schema on its own, as lazily compiled references do
+        let (document, pointer) = location.split_once('#').unwrap_or((location, ""));
+        fragment.clear();
+        fragment.push('#');
+        uri::encode_to(pointer, &mut fragment);
+        let (contents, resolver, draft) = registry
+            .try_resolver(document)?
+            .lookup(&fragment)?
+            .into_inner();
+        let ctx = compiler::Context::new(
+            Shared::clone(&validator.config),
+            Shared::clone(&validator.build),
+            Shared::clone(registry),
+            Rc::new(resolver),
+            vocabularies.clone(),
+            draft,
+            Location::new(),
+        );
+        let node = compiler::compile(&ctx, draft.create_resource_ref(contents))
+            .map_err(|error| error.to_owned())?;
+        for (keyword, index, value) in values {
+            let errors: Vec<_> = node
+                .iter_errors(value, &LazyLocation::new())
+                .map(ValidationError::to_owned)
+                .collect();
+            if !errors.is_empty() {
+                failures.push(ExampleFailure {
+                    schema_location: canonical_location(registry, document, pointer)?,
+                    keyword,
+                    index,
+                    value: value.clone(),
+                    errors,
+                });
+            }
+        }
+    }
+    Ok(failures)
+}
+
+/// The URI of the innermost resource containing the subschema at `pointer` within `document`,
+/// followed by the JSON Pointer of the subschema relative to that resource.
+fn canonical_location(
+    registry: &Registry,
+    document: &str,
+    pointer: &str,
+) -> Result<String, referencing::Error> {
+    let resolver = registry.try_resolver(document)?;
+    let mut base = resolver.lookup("")?.resolver().base_uri();
+    let mut relative = 0;
+    let mut fragment = String::new();
+    let ends = pointer
+        .match_indices('/')
+        .skip(1)
+        .map(|(end, _)| end)
+        .chain((!pointer.is_empty()).then_some(pointer.len()));
+    for end in ends {
+        fragment.clear();
+        fragment.push('#');
+        uri::encode_to(&pointer[..end], &mut fragment);
+        let current = resolver.lookup(&fragment)?.resolver().base_uri();
+        if current != base {
+            base = current;
+            relative = end;
+        }
+    }
+    Ok(format!("{}#{}", base.as_str(), &pointer[relative..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use referencing::Draft;
+    use serde_json::json;
+
+    fn failures(validator: &crate::Validator) -> Vec<(String, String, Option<usize>)> {
+        validator
+            .check_examples()
+            .expect("Resolvable schema")
+            .iter()
+            .map(|failure| {
+                (
+                    failure.schema_location().to_string(),
+                    failure.keyword().to_string(),
+                    failure.index(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn valid_examples() {
+        let validator = crate::validator_for(&json!({
+            "type": "object",
+            "examples": [{}],
+            "properties": {"name": {"type": "string", "examples": ["a", "b"], "default": ""}}
+        }))
+        .expect("Invalid schema");
+        assert!(failures(&validator).is_empty());
+    }
+
+    #[test]
+    fn references() {
+        let validator = crate::options()
+            .with_resource(
+                "https://example.com/age.json",
+                Draft::Draft202012.create_resource(json!({"minimum": 0, "examples": [-1]})),
+            )
+            .build(&json!({
+                "$defs": {"positive": {"exclusiveMinimum": 0, "default": 0}},
+                "properties": {
+                    "a b/c~": {"$ref": "#/$defs/positive", "examples": [1, 2]},
+                    "age": {"$ref": "https://example.com/age.json"},
+                    "never": {"$defs": {"unused": {"type": "string", "default": 1}}},
+                    "nothing": false
+                },
+                "default": {"nothing": 1}
+            }))
+            .expect("Invalid schema");
+        assert_eq!(
+            failures(&validator),
+            [
+                (
+                    "https://example.com/age.json#".to_string(),
+                    "examples".to_string(),
+                    Some(0)
+                ),
+                ("json-schema:///#".to_string(), "default".to_string(), None),
+                (
+                    "json-schema:///#/$defs/positive".to_string(),
+                    "default".to_string(),
+                    None
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn embedded_resource_and_base_uri() {
+        let validator = crate::options()
+            .with_base_uri("https://example.com/root.json")
+            .build(&json!({
+                "properties": {
+                    "item": {
+                        "$id": "item.json",
+                        "$defs": {"id": {"type": "integer"}},
+                        "properties": {"id": {"$ref": "#/$defs/id", "examples": ["x"]}}
+                    }
+                }
+            }))
+            .expect("Invalid schema");
+        let failures = validator.check_examples().expect("Resolvable schema");
+        assert_eq!(
+            failures[0].schema_location(),
+            "https://example.com/item.json#/properties/id"
+        );
+        assert_eq!(failures[0].value(), &json!("x"));
+        assert_eq!(failures[0].errors()[0].schema_path.as_str(), "/$ref/type");
+    }
+}
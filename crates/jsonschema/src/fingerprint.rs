@@ -0,0 +1,379 @@
+//! Structural fingerprints of schemas.
+//!
+//! A fingerprint stays the same for schemas that differ only in ways that do not affect their
+//! meaning, e.g. the order of object keys or of values in `required`. It is stable across
+//! processes and platforms, so it can be stored and compared later.
+use std::fmt;
+
+use referencing::{Draft, Registry, Resolver};
+use serde_json::{Map, Value};
+
+use crate::analyze::{
+    DEFINITION_KEYWORDS, INSTANCE_KEYWORDS, REFERENCE_KEYWORDS, SCHEMA_MAP_KEYWORDS,
+};
+
+/// A structural hash of a schema, see [`fingerprint`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Fingerprint(u64);
+
+impl Fingerprint {
+    /// The fingerprint as an integer.
+    #[must_use]
+    pub const fn as_u64(self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for Fingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+/// Keywords whose array values are sets, i.e. the order of their items is irrelevant.
+const SET_KEYWORDS: &[&str] = &["required", "type", "enum"];
+
+/// 64-bit FNV-1a, which is simple and has a stable output unlike `std` hashers.
+struct Hasher(u64);
+
+impl Hasher {
+    const fn new(tag: u8) -> Hasher {
+        Hasher(0xcbf2_9ce4_8422_2325).byte(tag)
+    }
+    const fn byte(mut self, byte: u8) -> Hasher {
+        self.0 ^= byte as u64;
+        self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        self
+    }
+    fn bytes(mut self, bytes: &[u8]) -> Hasher {
+        for byte in bytes {
+            self = self.byte(*byte);
+        }
+        self
+    }
+    fn u64(self, value: u64) -> Hasher {
+        self.bytes(&value.to_le_bytes())
+    }
+    fn str(self, value: &str) -> Hasher {
+        self.u64(value.len() as u64).bytes(value.as_bytes())
+    }
+}
+
+fn hash_number(number: &serde_json::Number) -> u64 {
+    // Numbers are compared by value, so `1` and `1.0` are the same
+    if let Some(value) = number.as_i64() {
+        return Hasher::new(b'i').bytes(&i128::from(value).to_le_bytes()).0;
+    }
+    if let Some(value) = number.as_u64() {
+        return Hasher::new(b'i').bytes(&i128::from(value).to_le_bytes()).0;
+    }
+    let value = number.as_f64().expect("Always valid");
+    #[allow(clippy::cast_possible_truncation)]
+    if value.fract() == 0.0 && value.abs() < 1e38 {
+        return Hasher::new(b'i').bytes(&(value as i128).to_le_bytes()).0;
+    }
+    Hasher::new(b'f').u64(value.to_bits()).0
+}
+
+fn hash_items(items: impl Iterator<Item = u64>, is_set: bool) -> u64 {
+    let mut hashes: Vec<_> = items.collect();
+    if is_set {
+        hashes.sort_unstable();
+    }
+    let hasher = Hasher::new(b'a').u64(hashes.len() as u64);
+    hashes
+        .into_iter()
+        .fold(hasher, |hasher, hash| hasher.u64(hash))
+        .0
+}
+
+fn hash_entries<'a>(
+    object: &'a Map<String, Value>,
+    mut hash_value: impl FnMut(&'a str, &'a Value) -> Option<u64>,
+) -> u64 {
+    let mut entries: Vec<_> = object
+        .iter()
+        .filter_map(|(key, value)| Some((key.as_str(), hash_value(key, value)?)))
+        .collect();
+    entries.sort_unstable_by(|left, right| left.0.cmp(right.0));
+    let hasher = Hasher::new(b'o').u64(entries.len() as u64);
+    entries
+        .into_iter()
+        .fold(hasher, |hasher, (key, hash)| hasher.str(key).u64(hash))
+        .0
+}
+
+fn hash_instance(value: &Value) -> u64 {
+    match value {
+        Value::Null => Hasher::new(b'n').0,
+        Value::Bool(value) => Hasher::new(b'b').byte(u8::from(*value)).0,
+        Value::Number(number) => hash_number(number),
+        Value::String(value) => Hasher::new(b's').str(value).0,
+        Value::Array(items) => hash_items(items.iter().map(hash_instance), false),
+        Value::Object(object) => hash_entries(object, |_, value| Some(hash_instance(value))),
+    }
+}
+
+/// State for replacing references with the hashes of their targets.
+#[derive(Default)]
+struct References {
+    /// Schemas that are being hashed, to detect cycles
+    stack: Vec<*const Value>,
+    error: Option<referencing::Error>,
+}
+
+impl References {
+    /// Hash the target of a reference.
+    /// References to a schema that is already being hashed are hashed by its depth in the stack,
+    /// so the result does not depend on how definitions are named.
+    fn resolve(&mut self, resolver: &Resolver<'_>, reference: &str) -> u64 {
+        let resolved = match resolver.lookup(reference) {
+            Ok(resolved) => resolved,
+            Err(error) => {
+                self.error.get_or_insert(error);
+                return 0;
+            }
+        };
+        let target = resolved.contents() as *const Value;
+        if let Some(depth) = self.stack.iter().position(|seen| *seen == target) {
+            return Hasher::new(b'r').u64(depth as u64).0;
+        }
+        let (contents, resolver, draft) = resolved.into_inner();
+        self.stack.push(target);
+        let hash = hash_schema(contents, Some((&resolver, draft)), self);
+        self.stack.pop();
+        Hasher::new(b'$').u64(hash).0
+    }
+}
+
+/// Hash a value at a schema position, where keywords have their usual meaning.
+///
+/// With a resolver, references are replaced with the hashes of their targets and definitions are
+/// skipped, as they only matter through references.
+fn hash_schema(
+    value: &Value,
+    resolver: Option<(&Resolver<'_>, Draft)>,
+    references: &mut References,
+) -> u64 {
+    let Value::Object(object) = value else {
+        return hash_instance(value);
+    };
+    let scope = match resolver.map(|(resolver, draft)| {
+        let draft = draft.detect(value).unwrap_or(draft);
+        resolver
+            .in_subresource(draft.create_resource_ref(value))
+            .map(|resolver| (resolver, draft))
+    }) {
+        Some(Ok(scope)) => Some(scope),
+        Some(Err(error)) => {
+            references.error.get_or_insert(error);
+            return 0;
+        }
+        None => None,
+    };
+    let resolver = scope.as_ref().map(|(resolver, draft)| (resolver, *draft));
+    hash_entries(object, |key, value| {
+        if let Some((resolver, _)) = resolver {
+            if DEFINITION_KEYWORDS.contains(&key) {
+                return None;
+            }
+            if let Value::String(reference) = value {
+                if REFERENCE_KEYWORDS.contains(&key) {
+                    return Some(references.resolve(resolver, reference));
+                }
+            }
+        }
+        Some(match value {
+            Value::Array(items) if INSTANCE_KEYWORDS.contains(&key) => {
+                hash_items(items.iter().map(hash_instance), SET_KEYWORDS.contains(&key))
+            }
+            Value::Array(items) if SET_KEYWORDS.contains(&key) => {
+                hash_items(items.iter().map(hash_instance), true)
+            }
+            _ if INSTANCE_KEYWORDS.contains(&key) => hash_instance(value),
+            Value::Object(schemas)
+                if SCHEMA_MAP_KEYWORDS.contains(&key) && !DEFINITION_KEYWORDS.contains(&key) =>
+            {
+                hash_entries(schemas, |_, schema| {
+                    Some(hash_schema(schema, resolver, references))
+                })
+            }
+            Value::Array(items) => hash_items(
+                items
+                    .iter()
+                    .map(|item| hash_schema(item, resolver, references)),
+                false,
+            ),
+            _ => hash_schema(value, resolver, references),
+        })
+    })
+}
+
+/// Compute a structural fingerprint of a schema.
+///
+/// Schemas that differ only in the order of object keys, the order of values in `required`,
+/// `type` or `enum`, or in the representation of numbers (`1` vs `1.0`) have the same fingerprint.
+/// References are hashed as is, see [`fingerprint_resolved`] to hash their targets instead.
+///
+/// # Example
+///
+/// ```rust
+/// use serde_json::json;
+///
+/// let left = json!({"type": "object", "required": ["a", "b"], "maximum": 1});
+/// let right = json!({"required": ["b", "a"], "maximum": 1.0, "type": "object"});
+/// assert_eq!(jsonschema::fingerprint(&left), jsonschema::fingerprint(&right));
+/// assert_ne!(
+///     jsonschema::fingerprint(&left),
+///     jsonschema::fingerprint(&json!({"type": "array"}))
+/// );
+/// ```
+#[must_use]
+pub fn fingerprint(schema: &Value) -> Fingerprint {
+    Fingerprint(hash_schema(schema, None, &mut References::default()))
+}
+
+/// Compute a structural fingerprint of the schema stored in `registry` under `uri`, replacing
+/// every `$ref` with the fingerprint of its target.
+///
+/// Two schemas that are split into documents differently, or that name their definitions
+/// differently, have the same fingerprint as long as they are structurally the same.
+///
+/// # Errors
+///
+/// Returns an error if `uri` is invalid or if a reference can not be resolved.
+///
+/// # Example
+///
+/// ```rust
+/// use jsonschema::{Registry, Resource};
+/// use serde_json::json;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let left = json!({"$ref": "#/$defs/a", "$defs": {"a": {"type": "string"}}});
+/// let right = json!({"$ref": "#/$defs/b", "$defs": {"b": {"type": "string"}}});
+/// let left = Registry::try_new("urn:left", Resource::from_contents(left)?)?;
+/// let right = Registry::try_new("urn:right", Resource::from_contents(right)?)?;
+/// assert_eq!(
+///     jsonschema::fingerprint_resolved(&left, "urn:left")?,
+///     jsonschema::fingerprint_resolved(&right, "urn:right")?
+/// );
+/// # Ok(())
+/// # }
+/// ```
+pub fn fingerprint_resolved(
+    registry: &Registry,
+    uri: &str,
+) -> Result<Fingerprint, referencing::Error> {
+    let resolver = registry.try_resolver(uri)?;
+    let (contents, resolver, draft) = resolver.lookup("")?.into_inner();
+    let mut references = References {
+        stack: vec![contents as *const Value],
+        error: None,
+    };
+    let hash = hash_schema(contents, Some((&resolver, draft)), &mut references);
+    match references.error {
+        Some(error) => Err(error),
+        None => Ok(Fingerprint(hash)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use referencing::Resource;
+    use serde_json::json;
+    use test_case::test_case;
+
+    fn resolved(schema: Value) -> Result<Fingerprint, referencing::Error> {
+        let registry = Registry::try_new(
+            "https://example.com/root.json",
+            Resource::from_contents(schema).expect("Invalid resource"),
+        )
+        .expect("Invalid registry");
+        fingerprint_resolved(&registry, "https://example.com/root.json")
+    }
+
+    #[test_case(&json!({"type": ["string", "null"]}), &json!({"type": ["null", "string"]}); "type set")]
+    #[test_case(&json!({"enum": [1, "a"]}), &json!({"enum": ["a", 1.0]}); "enum set")]
+    #[test_case(&json!({"minimum": -0.0}), &json!({"minimum": 0}); "negative zero")]
+    #[test_case(&json!({"properties": {"a": {"required": ["x", "y"]}}}), &json!({"properties": {"a": {"required": ["y", "x"]}}}); "nested")]
+    fn same(left: &Value, right: &Value) {
+        assert_eq!(fingerprint(left), fingerprint(right));
+    }
+
+    #[test_case(&json!({"items": [{"type": "string"}, {"type": "null"}]}), &json!({"items": [{"type": "null"}, {"type": "string"}]}); "tuple order")]
+    #[test_case(&json!({"const": ["a", "b"]}), &json!({"const": ["b", "a"]}); "const array")]
+    #[test_case(&json!({"const": {"required": ["a", "b"]}}), &json!({"const": {"required": ["b", "a"]}}); "instance values are not schemas")]
+    #[test_case(&json!({"minimum": 1}), &json!({"minimum": 1.5}); "number")]
+    #[test_case(&json!({"type": "string"}), &json!("string"); "shape")]
+    fn different(left: &Value, right: &Value) {
+        assert_ne!(fingerprint(left), fingerprint(right));
+    }
+
+    #[test]
+    fn display() {
+        let fingerprint = fingerprint(&json!({}));
+        assert_eq!(fingerprint.to_string().len(), 16);
+        assert_eq!(
+            u64::from_str_radix(&fingerprint.to_string(), 16),
+            Ok(fingerprint.as_u64())
+        );
+    }
+
+    #[test]
+    fn resolved_ignores_definition_names() {
+        let referenced = resolved(json!({
+            "properties": {"a": {"$ref": "#/$defs/name"}},
+            "$defs": {"name": {"type": "string"}, "unused": {"type": "null"}}
+        }));
+        assert_eq!(
+            referenced.expect("Resolvable"),
+            resolved(json!({
+                "properties": {"a": {"$ref": "#/definitions/other"}},
+                "definitions": {"other": {"type": "string"}}
+            }))
+            .expect("Resolvable")
+        );
+    }
+
+    #[test]
+    fn resolved_keeps_properties_named_like_keywords() {
+        let left = resolved(json!({"properties": {"definitions": {"type": "string"}}}));
+        let right = resolved(json!({"properties": {"definitions": {"type": "integer"}}}));
+        assert_ne!(left.expect("Resolvable"), right.expect("Resolvable"));
+    }
+
+    #[test]
+    fn resolved_cycles() {
+        let left = resolved(json!({
+            "properties": {"child": {"$ref": "#/$defs/node"}},
+            "$defs": {"node": {"properties": {"child": {"$ref": "#/$defs/node"}}}}
+        }));
+        let right = resolved(json!({
+            "properties": {"child": {"$ref": "#/$defs/tree"}},
+            "$defs": {"tree": {"properties": {"child": {"$ref": "#/$defs/tree"}}}}
+        }));
+        assert_eq!(left.expect("Resolvable"), right.expect("Resolvable"));
+    }
+
+    #[test]
+    fn resolved_nested_base_uri() {
+        let schema = json!({
+            "$id": "https://example.com/root.json",
+            "properties": {
+                "a": {
+                    "$id": "nested/",
+                    "$ref": "item.json"
+                }
+            },
+            "$defs": {"item": {"$id": "nested/item.json", "type": "string"}}
+        });
+        assert!(resolved(schema).is_ok());
+    }
+
+    #[test]
+    fn resolved_unresolvable() {
+        assert!(resolved(json!({"$ref": "#/$defs/missing"})).is_err());
+    }
+}
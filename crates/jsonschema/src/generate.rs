@@ -0,0 +1,914 @@
+//! Random instances of schemas.
+//!
+//! A [`Generator`] produces instances that satisfy a schema, e.g. to seed tests, demo fixtures,
+//! or benchmarks, and near misses that violate it in a single place:
+//!
+//! ```rust
+//! use jsonschema::{generate::Generator, Registry, Resource};
+//! use serde_json::json;
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let schema = json!({
+//!     "type": "object",
+//!     "properties": {"port": {"type": "integer", "minimum": 1, "maximum": 65535}},
+//!     "required": ["port"]
+//! });
+//! let validator = jsonschema::validator_for(&schema)?;
+//! let registry = Registry::try_new("urn:server", Resource::from_contents(schema)?)?;
+//! let mut generator = Generator::new(&registry, "urn:server", 42)?;
+//!
+//! let instance = generator.generate(&validator)?;
+//! assert!(validator.is_valid(&instance));
+//! let port = instance["port"].as_u64().expect("Port is an integer");
+//! assert!((1..=65535).contains(&port));
+//!
+//! let near_miss = generator.near_miss(&instance);
+//! assert!(!validator.is_valid(&near_miss));
+//! # Ok(())
+//! # }
+//! ```
+use std::{collections::BTreeMap, error, fmt};
+
+use referencing::{Draft, Error, Registry, Resolver};
+use regex_syntax::hir::{Class, Hir, HirKind, Literal};
+use serde_json::{Map, Number, Value};
+
+use crate::{ecma::to_rust_regex, Validator};
+
+/// How often generating an instance is attempted before giving up.
+const MAX_ATTEMPTS: usize = 100;
+/// Beyond this depth only required properties and the fewest array items are generated, so that
+/// recursive schemas produce finite instances.
+const SHALLOW_DEPTH: usize = 4;
+const MAX_DEPTH: usize = 64;
+/// Bounds for numbers, strings and arrays that the schema leaves open.
+const NUMBER_SPAN: f64 = 100.0;
+const EXTRA_LENGTH: u64 = 8;
+const EXTRA_ITEMS: u64 = 3;
+
+/// An error generating an instance with a [`Generator`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GenerateError {
+    message: String,
+}
+
+impl fmt::Display for GenerateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl error::Error for GenerateError {}
+
+/// A schema that a generated value has to satisfy, with the context to resolve its references.
+#[derive(Clone)]
+struct Part<'a> {
+    schema: &'a Value,
+    resolver: Resolver<'a>,
+    draft: Draft,
+}
+
+impl<'a> Part<'a> {
+    fn new(schema: &'a Value, resolver: Resolver<'a>, draft: Draft) -> Part<'a> {
+        Part {
+            schema,
+            resolver,
+            draft,
+        }
+    }
+
+    fn get(&self, keyword: &str) -> Option<&'a Value> {
+        self.schema.get(keyword)
+    }
+
+    fn subschema(&self, schema: &'a Value) -> Part<'a> {
+        Part::new(schema, self.resolver.clone(), self.draft)
+    }
+}
+
+/// A small, seedable pseudo-random number generator (`SplitMix64`).
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `low..=high`.
+    fn between(&mut self, low: u64, high: u64) -> u64 {
+        if high <= low {
+            return low;
+        }
+        match (high - low).checked_add(1) {
+            Some(span) => low + self.next() % span,
+            None => self.next(),
+        }
+    }
+
+    fn chance(&mut self) -> bool {
+        self.next() & 1 == 0
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn fraction(&mut self) -> f64 {
+        (self.next() >> 11) as f64 / (1_u64 << 53) as f64
+    }
+
+    fn pick<'v, T>(&mut self, items: &'v [T]) -> Option<&'v T> {
+        if items.is_empty() {
+            None
+        } else {
+            let index = self.between(0, items.len() as u64 - 1);
+            items.get(usize::try_from(index).ok()?)
+        }
+    }
+}
+
+/// A seedable generator of random instances of a schema.
+///
+/// Instances are built from types, `const`, `enum`, `format`, `pattern`, numeric and length
+/// bounds, and required properties, following references and picking random `anyOf` / `oneOf`
+/// branches.
+/// Constraints it does not model, e.g. `not`, are satisfied by [`Generator::generate`] retrying
+/// with other random choices. All choices derive from the seed, which thereby reproduces the
+/// instances.
+pub struct Generator<'r> {
+    root: Part<'r>,
+    rng: Rng,
+    depth: usize,
+}
+
+impl fmt::Debug for Generator<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Generator")
+            .field("schema", self.root.schema)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'r> Generator<'r> {
+    /// Create a generator for the schema that `registry` holds at `uri`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `uri` is invalid or `registry` does not hold it.
+    pub fn new(registry: &'r Registry, uri: &str, seed: u64) -> Result<Generator<'r>, Error> {
+        let (schema, resolver, draft) = registry.try_resolver(uri)?.lookup("#")?.into_inner();
+        Ok(Generator {
+            root: Part::new(schema, resolver, draft),
+            rng: Rng(seed),
+            depth: 0,
+        })
+    }
+
+    /// Generate an instance that `validator` accepts.
+    ///
+    /// `validator` is expected to be built from the same schema. It rejects candidates that miss
+    /// constraints the generator does not model, and another one is tried then.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no accepted instance is found after a number of attempts, e.g. because
+    /// the schema accepts no instances at all.
+    pub fn generate(&mut self, validator: &Validator) -> Result<Value, GenerateError> {
+        let mut failure = String::new();
+        for _ in 0..MAX_ATTEMPTS {
+            match self.candidate() {
+                Ok(instance) => match validator.validate(&instance) {
+                    Ok(()) => return Ok(instance),
+                    Err(error) if error.instance_path.as_str().is_empty() => {
+                        failure = error.to_string();
+                    }
+                    Err(error) => {
+                        failure = format!("{error} at `{}`", error.instance_path);
+                    }
+                },
+                Err(error) => failure = error.message,
+            }
+        }
+        Err(GenerateError {
+            message: format!(
+                "Could not generate a valid instance in {MAX_ATTEMPTS} attempts, the last one failed with: {failure}"
+            ),
+        })
+    }
+
+    /// Generate a candidate instance without checking it against the schema.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the schema evidently accepts no instances, e.g. `false`, or a
+    /// reference can not be resolved.
+    pub fn candidate(&mut self) -> Result<Value, GenerateError> {
+        self.depth = 0;
+        let root = self.root.clone();
+        self.instance(&[root])
+            .map_err(|message| GenerateError { message })
+    }
+
+    /// Copy `instance` with a single random change: a member dropped, or a value replaced with
+    /// one of another type.
+    ///
+    /// The copy is usually invalid when `instance` is valid, which makes near misses useful to
+    /// exercise the error paths of validators.
+    pub fn near_miss(&mut self, instance: &Value) -> Value {
+        let mut result = instance.clone();
+        self.change(&mut result);
+        result
+    }
+
+    fn change(&mut self, value: &mut Value) {
+        let descend = self.rng.chance();
+        match value {
+            Value::Object(object) if !object.is_empty() => {
+                let index = self.rng.between(0, object.len() as u64 - 1);
+                let key = usize::try_from(index)
+                    .ok()
+                    .and_then(|index| object.keys().nth(index))
+                    .cloned()
+                    .expect("Index is in bounds");
+                if descend {
+                    self.change(object.get_mut(&key).expect("Key exists"));
+                } else {
+                    object.remove(&key);
+                }
+            }
+            Value::Array(items) if !items.is_empty() && descend => {
+                let index = self.rng.between(0, items.len() as u64 - 1);
+                let index = usize::try_from(index).expect("Index is in bounds");
+                self.change(&mut items[index]);
+            }
+            Value::String(_) => *value = Value::from(1),
+            Value::Number(_) => *value = Value::from("1"),
+            Value::Bool(_) | Value::Null => *value = Value::Array(Vec::new()),
+            Value::Array(_) | Value::Object(_) => *value = Value::Bool(false),
+        }
+    }
+
+    /// A value satisfying all `parts`.
+    fn instance(&mut self, parts: &[Part<'_>]) -> Result<Value, String> {
+        if self.depth >= MAX_DEPTH {
+            return Err(format!("Schemas are nested deeper than {MAX_DEPTH} levels"));
+        }
+        self.depth += 1;
+        let mut flattened = Vec::new();
+        for part in parts {
+            self.flatten(part, &mut flattened, 0)?;
+        }
+        let result = self.value(&flattened);
+        self.depth -= 1;
+        result
+    }
+
+    /// Add the object schemas that `part` consists of to `parts`, following references and
+    /// `allOf`, and picking one `anyOf` / `oneOf` branch.
+    fn flatten<'a>(
+        &mut self,
+        part: &Part<'a>,
+        parts: &mut Vec<Part<'a>>,
+        references: usize,
+    ) -> Result<(), String> {
+        let object = match part.schema {
+            Value::Bool(true) => return Ok(()),
+            Value::Bool(false) => return Err("The `false` schema accepts no instances".into()),
+            Value::Object(object) => object,
+            _ => return Err("Schemas must be objects or booleans".into()),
+        };
+        if references >= MAX_DEPTH {
+            return Err(format!(
+                "References are nested deeper than {MAX_DEPTH} levels"
+            ));
+        }
+        let resolver = part
+            .resolver
+            .in_subresource(part.draft.create_resource_ref(part.schema))
+            .map_err(|error| error.to_string())?;
+        let part = Part::new(part.schema, resolver, part.draft);
+        for keyword in ["$ref", "$dynamicRef", "$recursiveRef"] {
+            if let Some(reference) = object.get(keyword).and_then(Value::as_str) {
+                let resolved = part
+                    .resolver
+                    .lookup(reference)
+                    .map_err(|error| error.to_string())?;
+                let target = Part::new(
+                    resolved.contents(),
+                    resolved.resolver().clone(),
+                    resolved.draft(),
+                );
+                self.flatten(&target, parts, references + 1)?;
+                if part.draft <= Draft::Draft7 {
+                    // Other keywords are ignored next to `$ref`
+                    return Ok(());
+                }
+            }
+        }
+        if let Some(Value::Array(all_of)) = object.get("allOf") {
+            for subschema in all_of {
+                self.flatten(&part.subschema(subschema), parts, references)?;
+            }
+        }
+        for keyword in ["anyOf", "oneOf"] {
+            if let Some(Value::Array(branches)) = object.get(keyword) {
+                if let Some(branch) = self.rng.pick(branches) {
+                    self.flatten(&part.subschema(branch), parts, references)?;
+                }
+            }
+        }
+        parts.push(part);
+        Ok(())
+    }
+
+    fn value(&mut self, parts: &[Part<'_>]) -> Result<Value, String> {
+        if let Some(value) = parts.iter().find_map(|part| part.get("const")) {
+            return Ok(value.clone());
+        }
+        if let Some(Value::Array(values)) = parts.iter().find_map(|part| part.get("enum")) {
+            return self
+                .rng
+                .pick(values)
+                .cloned()
+                .ok_or_else(|| "An empty `enum` accepts no instances".to_string());
+        }
+        let types = allowed_types(parts);
+        let Some(kind) = self.rng.pick(&types).copied() else {
+            return Err("No type satisfies all of the schemas".into());
+        };
+        Ok(match kind {
+            "null" => Value::Null,
+            "boolean" => Value::Bool(self.rng.chance()),
+            "integer" => self.number(parts, true),
+            "number" => self.number(parts, false),
+            "string" => Value::String(self.string(parts)?),
+            "array" => self.array(parts)?,
+            _ => self.object(parts)?,
+        })
+    }
+
+    fn number(&mut self, parts: &[Part<'_>], integer: bool) -> Value {
+        let mut lower: Option<(f64, bool)> = None;
+        let mut upper: Option<(f64, bool)> = None;
+        let mut multiple_of = None;
+        for part in parts {
+            let legacy = part.draft == Draft::Draft4;
+            let exclusive = |keyword| part.get(keyword).and_then(Value::as_bool) == Some(true);
+            let mut bounds = Vec::new();
+            if let Some(minimum) = part.get("minimum").and_then(Value::as_f64) {
+                bounds.push((true, minimum, legacy && exclusive("exclusiveMinimum")));
+            }
+            if let Some(maximum) = part.get("maximum").and_then(Value::as_f64) {
+                bounds.push((false, maximum, legacy && exclusive("exclusiveMaximum")));
+            }
+            if let Some(minimum) = part.get("exclusiveMinimum").and_then(Value::as_f64) {
+                bounds.push((true, minimum, true));
+            }
+            if let Some(maximum) = part.get("exclusiveMaximum").and_then(Value::as_f64) {
+                bounds.push((false, maximum, true));
+            }
+            for (is_lower, bound, exclusive) in bounds {
+                if is_lower {
+                    if lower.map_or(true, |(current, _)| bound >= current) {
+                        lower = Some((bound, exclusive));
+                    }
+                } else if upper.map_or(true, |(current, _)| bound <= current) {
+                    upper = Some((bound, exclusive));
+                }
+            }
+            if multiple_of.is_none() {
+                multiple_of = part.get("multipleOf").and_then(Value::as_f64);
+            }
+        }
+        let step = match multiple_of {
+            Some(multiple_of) if multiple_of > 0.0 => Some(multiple_of),
+            _ if integer => Some(1.0),
+            _ => None,
+        };
+        let (low, high) = match (lower, upper) {
+            (Some((low, _)), Some((high, _))) => (low, high),
+            (Some((low, _)), None) => (low, low + NUMBER_SPAN),
+            (None, Some((high, _))) => (high - NUMBER_SPAN, high),
+            (None, None) => (0.0, NUMBER_SPAN),
+        };
+        let excludes = |value: f64| {
+            lower.is_some_and(|(bound, exclusive)| exclusive && value <= bound)
+                || upper.is_some_and(|(bound, exclusive)| exclusive && value >= bound)
+        };
+        let value = if let Some(step) = step {
+            let mut first = (low / step).ceil();
+            let mut last = (high / step).floor();
+            if excludes(first * step) {
+                first += 1.0;
+            }
+            if excludes(last * step) {
+                last -= 1.0;
+            }
+            let multiple = if last > first {
+                first
+                    + (self.rng.fraction() * (last - first + 1.0))
+                        .floor()
+                        .min(last - first)
+            } else {
+                first
+            };
+            // Rounding hides representation errors, e.g. in multiples of `0.01`
+            (multiple * step * 1e10).round() / 1e10
+        } else {
+            // Two decimals keep generated numbers readable
+            let value = ((low + self.rng.fraction() * (high - low)) * 100.0).round() / 100.0;
+            if excludes(value) {
+                (low + high) / 2.0
+            } else {
+                value
+            }
+        };
+        number_value(value)
+    }
+
+    fn string(&mut self, parts: &[Part<'_>]) -> Result<String, String> {
+        if let Some(format) = parts
+            .iter()
+            .find_map(|part| part.get("format").and_then(Value::as_str))
+        {
+            if let Some(value) = self.formatted(format) {
+                return Ok(value);
+            }
+        }
+        if let Some(pattern) = parts
+            .iter()
+            .find_map(|part| part.get("pattern").and_then(Value::as_str))
+        {
+            return self.matching(pattern);
+        }
+        let min_length = max_of(parts, "minLength").unwrap_or(0);
+        let max_length = min_of(parts, "maxLength").unwrap_or(min_length + EXTRA_LENGTH);
+        let length = self
+            .rng
+            .between(min_length, max_length.min(min_length + EXTRA_LENGTH));
+        Ok((0..length)
+            .map(|_| char::from(b'a' + u8::try_from(self.rng.between(0, 25)).unwrap_or(0)))
+            .collect())
+    }
+
+    /// A random string matching `pattern`.
+    fn matching(&mut self, pattern: &str) -> Result<String, String> {
+        let hir = to_rust_regex(pattern)
+            .ok()
+            .and_then(|pattern| regex_syntax::Parser::new().parse(&pattern).ok())
+            .ok_or_else(|| {
+                format!("Generating strings matching the pattern \"{pattern}\" is not supported")
+            })?;
+        let mut result = String::new();
+        self.push_match(&hir, &mut result);
+        Ok(result)
+    }
+
+    /// Append a random match of `hir` to `result`.
+    ///
+    /// Assertions, e.g. anchors or word boundaries, are not enforced, validation rejects the rare
+    /// strings that miss them.
+    fn push_match(&mut self, hir: &Hir, result: &mut String) {
+        match hir.kind() {
+            HirKind::Empty | HirKind::Look(_) => {}
+            HirKind::Literal(Literal(bytes)) => result.push_str(&String::from_utf8_lossy(bytes)),
+            HirKind::Class(Class::Unicode(class)) => {
+                if let Some(range) = self.rng.pick(class.ranges()) {
+                    let code = self
+                        .rng
+                        .between(u64::from(range.start()), u64::from(range.end()));
+                    result.extend(u32::try_from(code).ok().and_then(char::from_u32));
+                }
+            }
+            HirKind::Class(Class::Bytes(class)) => {
+                if let Some(range) = self.rng.pick(class.ranges()) {
+                    let byte = self
+                        .rng
+                        .between(u64::from(range.start()), u64::from(range.end()));
+                    result.extend(u8::try_from(byte).ok().map(char::from));
+                }
+            }
+            HirKind::Repetition(repetition) => {
+                let min = u64::from(repetition.min);
+                let max = repetition.max.map_or(min + EXTRA_LENGTH, |max| {
+                    u64::from(max).min(min + EXTRA_LENGTH)
+                });
+                for _ in 0..self.rng.between(min, max) {
+                    self.push_match(&repetition.sub, result);
+                }
+            }
+            HirKind::Capture(capture) => self.push_match(&capture.sub, result),
+            HirKind::Concat(items) => {
+                for item in items {
+                    self.push_match(item, result);
+                }
+            }
+            HirKind::Alternation(branches) => {
+                if let Some(branch) = self.rng.pick(branches) {
+                    self.push_match(branch, result);
+                }
+            }
+        }
+    }
+
+    /// A random string in `format`, if it is known.
+    fn formatted(&mut self, format: &str) -> Option<String> {
+        let n = self.rng.between(1, 9999);
+        let date = format!(
+            "{:04}-{:02}-{:02}",
+            self.rng.between(1970, 2069),
+            self.rng.between(1, 12),
+            self.rng.between(1, 28)
+        );
+        let time = format!(
+            "{:02}:{:02}:{:02}Z",
+            self.rng.between(0, 23),
+            self.rng.between(0, 59),
+            self.rng.between(0, 59)
+        );
+        Some(match format {
+            "date-time" => format!("{date}T{time}"),
+            "date" => date,
+            "time" => time,
+            "duration" => format!("P{}DT{}H", self.rng.between(0, 30), self.rng.between(0, 23)),
+            "email" | "idn-email" => format!("user{n}@example.com"),
+            "hostname" | "idn-hostname" => format!("host{n}.example.com"),
+            "ipv4" => format!(
+                "{}.{}.{}.{}",
+                self.rng.between(1, 254),
+                self.rng.between(0, 255),
+                self.rng.between(0, 255),
+                self.rng.between(1, 254)
+            ),
+            "ipv6" => format!("2001:db8::{:x}", self.rng.between(1, 0xffff)),
+            "uri" | "iri" => format!("https://example.com/{n}"),
+            "uri-reference" | "iri-reference" | "json-pointer" => format!("/items/{n}"),
+            "uri-template" => "https://example.com/{id}".to_string(),
+            "uuid" => {
+                let high = self.rng.next();
+                let low = self.rng.next();
+                format!(
+                    "{:08x}-{:04x}-4{:03x}-{:x}{:03x}-{:012x}",
+                    high >> 32,
+                    (high >> 16) & 0xffff,
+                    high & 0xfff,
+                    8 + (low >> 62),
+                    (low >> 48) & 0xfff,
+                    low & 0xffff_ffff_ffff
+                )
+            }
+            "relative-json-pointer" => format!("0/items/{n}"),
+            "regex" => "^[a-z]+$".to_string(),
+            _ => return None,
+        })
+    }
+
+    fn array(&mut self, parts: &[Part<'_>]) -> Result<Value, String> {
+        let mut prefix = Vec::new();
+        let mut rest = Vec::new();
+        let mut closed = false;
+        let mut contains = None;
+        let mut unique = false;
+        for part in parts {
+            let (prefix_items, items) = if part.draft >= Draft::Draft202012 {
+                (part.get("prefixItems"), part.get("items"))
+            } else {
+                match part.get("items") {
+                    items @ Some(Value::Array(_)) => (items, part.get("additionalItems")),
+                    items => (None, items),
+                }
+            };
+            if let Some(Value::Array(items)) = prefix_items {
+                if prefix.is_empty() {
+                    prefix = items.iter().map(|item| part.subschema(item)).collect();
+                }
+            }
+            match items {
+                Some(Value::Bool(false)) => closed = true,
+                Some(items) => rest.push(part.subschema(items)),
+                None => {}
+            }
+            if contains.is_none() {
+                contains = part.get("contains").map(|schema| part.subschema(schema));
+            }
+            unique |= part.get("uniqueItems") == Some(&Value::Bool(true));
+        }
+        let min_items = max_of(parts, "minItems").unwrap_or(0);
+        let mut max_items = min_of(parts, "maxItems").unwrap_or(u64::MAX);
+        if closed {
+            max_items = max_items.min(prefix.len() as u64);
+        }
+        let extra = if self.depth > SHALLOW_DEPTH {
+            0
+        } else {
+            EXTRA_ITEMS
+        };
+        let wanted = min_items.max(u64::from(contains.is_some()));
+        let length = self
+            .rng
+            .between(wanted, max_items.min(wanted + extra))
+            .max(wanted.min(max_items));
+        let length = usize::try_from(length).map_err(|error| error.to_string())?;
+        let mut items: Vec<Value> = Vec::with_capacity(length);
+        for index in 0..length {
+            let mut item_parts = match prefix.get(index) {
+                Some(part) => vec![part.clone()],
+                None => rest.clone(),
+            };
+            if index == prefix.len().min(length - 1) {
+                if let Some(contains) = &contains {
+                    item_parts.push(contains.clone());
+                }
+            }
+            let mut item = self.instance(&item_parts)?;
+            // A few attempts at a distinct item, validation rejects duplicates left over
+            for _ in 0..8 {
+                if !unique || !items.contains(&item) {
+                    break;
+                }
+                item = self.instance(&item_parts)?;
+            }
+            items.push(item);
+        }
+        Ok(Value::Array(items))
+    }
+
+    fn object(&mut self, parts: &[Part<'_>]) -> Result<Value, String> {
+        let mut properties: BTreeMap<&str, Vec<Part<'_>>> = BTreeMap::new();
+        let mut required = Vec::new();
+        let mut additional = Vec::new();
+        let mut closed = false;
+        let mut dependencies: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        for part in parts {
+            if let Some(Value::Object(members)) = part.get("properties") {
+                for (name, schema) in members {
+                    properties
+                        .entry(name.as_str())
+                        .or_default()
+                        .push(part.subschema(schema));
+                }
+            }
+            if let Some(Value::Array(names)) = part.get("required") {
+                required.extend(names.iter().filter_map(Value::as_str));
+            }
+            match part.get("additionalProperties") {
+                Some(Value::Bool(false)) => closed = true,
+                Some(schema) => additional.push(part.subschema(schema)),
+                None => {}
+            }
+            for keyword in ["dependentRequired", "dependencies"] {
+                if let Some(Value::Object(members)) = part.get(keyword) {
+                    for (name, dependents) in members {
+                        if let Value::Array(dependents) = dependents {
+                            dependencies
+                                .entry(name.as_str())
+                                .or_default()
+                                .extend(dependents.iter().filter_map(Value::as_str));
+                        }
+                    }
+                }
+            }
+        }
+        let min_properties = max_of(parts, "minProperties").unwrap_or(0);
+        let max_properties = min_of(parts, "maxProperties").unwrap_or(u64::MAX);
+        let shallow = self.depth <= SHALLOW_DEPTH;
+        let mut names: Vec<String> = Vec::new();
+        for name in &required {
+            if !names.iter().any(|known| known == name) {
+                names.push((*name).to_string());
+            }
+        }
+        for (name, schemas) in &properties {
+            let accepts = schemas
+                .iter()
+                .all(|part| part.schema != &Value::Bool(false));
+            if accepts
+                && !names.iter().any(|known| known == name)
+                && ((shallow && self.rng.chance()) || (names.len() as u64) < min_properties)
+                && (names.len() as u64) < max_properties
+            {
+                names.push((*name).to_string());
+            }
+        }
+        // Names of dependencies are added until nothing else is required
+        let mut index = 0;
+        while index < names.len() {
+            if let Some(dependents) = dependencies.get(names[index].as_str()) {
+                for dependent in dependents {
+                    if !names.iter().any(|known| known == dependent) {
+                        names.push((*dependent).to_string());
+                    }
+                }
+            }
+            index += 1;
+        }
+        let mut extra = 0;
+        while (names.len() as u64) < min_properties && !closed {
+            extra += 1;
+            let name = format!("property{extra}");
+            if !names.contains(&name) && !properties.contains_key(name.as_str()) {
+                names.push(name);
+            }
+        }
+        let mut object = Map::new();
+        for name in names {
+            let schemas = properties
+                .get(name.as_str())
+                .cloned()
+                .unwrap_or_else(|| additional.clone());
+            let value = self.instance(&schemas)?;
+            object.insert(name, value);
+        }
+        Ok(Value::Object(object))
+    }
+}
+
+/// Types that satisfy all `parts`, inferred from their keywords if none declares any.
+fn allowed_types(parts: &[Part<'_>]) -> Vec<&'static str> {
+    const ALL: [&str; 7] = [
+        "null", "boolean", "integer", "number", "string", "array", "object",
+    ];
+    let mut allowed: Vec<&'static str> = ALL.to_vec();
+    let mut declared = false;
+    for part in parts {
+        let types: Vec<&str> = match part.get("type") {
+            Some(Value::String(name)) => vec![name.as_str()],
+            Some(Value::Array(names)) => names.iter().filter_map(Value::as_str).collect(),
+            _ => continue,
+        };
+        declared = true;
+        allowed.retain(|kind| {
+            types.contains(kind) || (*kind == "integer" && types.contains(&"number"))
+        });
+    }
+    if declared {
+        return allowed;
+    }
+    let has = |keywords: &[&str]| {
+        parts
+            .iter()
+            .any(|part| keywords.iter().any(|keyword| part.get(keyword).is_some()))
+    };
+    if has(&[
+        "properties",
+        "required",
+        "additionalProperties",
+        "minProperties",
+        "maxProperties",
+        "dependentRequired",
+    ]) {
+        vec!["object"]
+    } else if has(&[
+        "items",
+        "prefixItems",
+        "minItems",
+        "maxItems",
+        "contains",
+        "uniqueItems",
+    ]) {
+        vec!["array"]
+    } else if has(&["minLength", "maxLength", "pattern", "format"]) {
+        vec!["string"]
+    } else if has(&[
+        "minimum",
+        "maximum",
+        "exclusiveMinimum",
+        "exclusiveMaximum",
+        "multipleOf",
+    ]) {
+        vec!["number"]
+    } else {
+        vec!["null", "boolean", "integer", "string"]
+    }
+}
+
+/// The largest value of a non-negative integer `keyword` among `parts`.
+fn max_of(parts: &[Part<'_>], keyword: &str) -> Option<u64> {
+    parts
+        .iter()
+        .filter_map(|part| part.get(keyword).and_then(Value::as_u64))
+        .max()
+}
+
+/// The smallest value of a non-negative integer `keyword` among `parts`.
+fn min_of(parts: &[Part<'_>], keyword: &str) -> Option<u64> {
+    parts
+        .iter()
+        .filter_map(|part| part.get(keyword).and_then(Value::as_u64))
+        .min()
+}
+
+/// A JSON number, written as an integer if it has no fractional part.
+#[allow(clippy::cast_possible_truncation, clippy::float_cmp)]
+fn number_value(value: f64) -> Value {
+    if value.fract() == 0.0 && value.abs() < 9_007_199_254_740_992.0 {
+        Value::from(value as i64)
+    } else {
+        Number::from_f64(value).map_or(Value::Null, Value::Number)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use referencing::Resource;
+    use serde_json::json;
+    use test_case::test_case;
+
+    fn registry(schema: &Value) -> Registry {
+        Registry::try_new(
+            "urn:root",
+            Resource::from_contents(schema.clone()).expect("Invalid schema"),
+        )
+        .expect("Invalid registry")
+    }
+
+    #[test_case(&json!({"type": "integer", "minimum": 3, "maximum": 5}))]
+    #[test_case(&json!({"type": "number", "exclusiveMinimum": 0, "multipleOf": 0.25}))]
+    #[test_case(&json!({"type": "string", "format": "date-time", "minLength": 1}))]
+    #[test_case(&json!({"type": "array", "items": {"enum": [1, 2, 3]}, "minItems": 2, "uniqueItems": true}))]
+    #[test_case(&json!({"required": ["a"], "properties": {"a": {"$ref": "#/$defs/a"}}, "additionalProperties": false, "$defs": {"a": {"const": 1}}}))]
+    #[test_case(&json!({"anyOf": [{"type": "null"}, {"type": "boolean"}], "not": {"const": false}}))]
+    #[test_case(&json!({"$schema": "http://json-schema.org/draft-04/schema#", "type": "integer", "minimum": 0, "exclusiveMinimum": true}))]
+    #[test_case(&json!({"type": "string", "pattern": "^[A-Z]{2}-\\d{3,5}$"}))]
+    #[test_case(&json!({"pattern": "^(red|green|blue)(-[a-f0-9]+)?$"}))]
+    #[test_case(&json!({"type": "string", "pattern": "^\\p{Lu}[^\\s]*\\.$", "maxLength": 12}))]
+    fn generates_valid_instances(schema: &Value) {
+        let validator = crate::validator_for(schema).expect("Invalid schema");
+        let registry = registry(schema);
+        let mut generator = Generator::new(&registry, "urn:root", 7).expect("Invalid URI");
+        for _ in 0..20 {
+            let instance = generator.generate(&validator).expect("Should generate");
+            assert!(validator.is_valid(&instance), "{instance}");
+        }
+    }
+
+    #[test]
+    fn same_seed_same_instances() {
+        let schema = json!({"type": "array", "items": {"type": ["string", "integer"]}});
+        let validator = crate::validator_for(&schema).expect("Invalid schema");
+        let registry = registry(&schema);
+        let generate = |seed| {
+            let mut generator = Generator::new(&registry, "urn:root", seed).expect("Invalid URI");
+            (0..10)
+                .map(|_| generator.generate(&validator).expect("Should generate"))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(generate(1), generate(1));
+        assert_ne!(generate(1), generate(2));
+    }
+
+    #[test]
+    fn external_references() {
+        let registry = Registry::try_from_resources([
+            (
+                "urn:root",
+                Resource::from_contents(json!({"$ref": "urn:port"})).expect("Invalid schema"),
+            ),
+            (
+                "urn:port",
+                Resource::from_contents(json!({"type": "integer", "minimum": 1024}))
+                    .expect("Invalid schema"),
+            ),
+        ])
+        .expect("Invalid registry");
+        let mut generator = Generator::new(&registry, "urn:root", 0).expect("Invalid URI");
+        let instance = generator.candidate().expect("Should generate");
+        assert!(instance.as_u64().is_some_and(|port| port >= 1024));
+    }
+
+    #[test_case(&json!(false), "The `false` schema accepts no instances")]
+    #[test_case(&json!({"allOf": [{"type": "integer"}, {"type": "string"}]}), "No type satisfies all of the schemas")]
+    #[test_case(&json!({"type": "integer", "not": {"type": "integer"}}), "is not allowed")]
+    #[test_case(&json!({"type": "string", "pattern": "^(?=a)"}), "Generating strings matching the pattern \"^(?=a)\" is not supported")]
+    fn unsatisfiable(schema: &Value, expected: &str) {
+        let validator = crate::validator_for(schema).expect("Invalid schema");
+        let registry = registry(schema);
+        let mut generator = Generator::new(&registry, "urn:root", 0).expect("Invalid URI");
+        let error = generator.generate(&validator).expect_err("Should fail");
+        let message = error.to_string();
+        assert!(
+            message.starts_with("Could not generate a valid instance in 100 attempts"),
+            "{message}"
+        );
+        assert!(message.contains(expected), "{message}");
+    }
+
+    #[test]
+    fn near_misses() {
+        let schema = json!({
+            "type": "object",
+            "properties": {"a": {"type": "string"}, "b": {"type": "array", "items": {"type": "integer"}}},
+            "required": ["a", "b"]
+        });
+        let validator = crate::validator_for(&schema).expect("Invalid schema");
+        let registry = registry(&schema);
+        let mut generator = Generator::new(&registry, "urn:root", 3).expect("Invalid URI");
+        let instance = generator.generate(&validator).expect("Should generate");
+        for _ in 0..20 {
+            let near_miss = generator.near_miss(&instance);
+            assert_ne!(near_miss, instance);
+        }
+    }
+}
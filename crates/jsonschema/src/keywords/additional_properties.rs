@@ -13,7 +13,7 @@ use crate::{
     node::SchemaNode,
     options::PatternEngineOptions,
     output::{Annotations, BasicOutput, OutputUnit},
-    paths::{LazyLocation, Location},
+    paths::{LazyLocation, Location, LocationSegment},
     properties::*,
     regex::RegexEngine,
     types::JsonType,
@@ -92,6 +92,16 @@ impl AdditionalPropertiesValidator {
     }
 }
 impl Validate for AdditionalPropertiesValidator {
+    fn subschemas_at<'s>(
+        &'s self,
+        segment: LocationSegment<'_>,
+        visit: &mut dyn FnMut(&'s SchemaNode),
+    ) {
+        if let LocationSegment::Property(_) = segment {
+            visit(&self.node);
+        }
+    }
+
     #[allow(clippy::needless_collect)]
     fn iter_errors<'i>(&self, instance: &'i Value, location: &LazyLocation) -> ErrorIterator<'i> {
         if let Value::Object(item) = instance {
@@ -240,6 +250,18 @@ impl AdditionalPropertiesNotEmptyFalseValidator<BigValidatorsMap> {
     }
 }
 impl<M: PropertiesValidatorsMap> Validate for AdditionalPropertiesNotEmptyFalseValidator<M> {
+    fn subschemas_at<'s>(
+        &'s self,
+        segment: LocationSegment<'_>,
+        visit: &mut dyn FnMut(&'s SchemaNode),
+    ) {
+        if let LocationSegment::Property(property) = segment {
+            if let Some(node) = self.properties.get_validator(property) {
+                visit(node);
+            }
+        }
+    }
+
     fn iter_errors<'i>(&self, instance: &'i Value, location: &LazyLocation) -> ErrorIterator<'i> {
         if let Value::Object(item) = instance {
             let mut errors = vec![];
@@ -254,11 +276,12 @@ impl<M: PropertiesValidatorsMap> Validate for AdditionalPropertiesNotEmptyFalseV
                 }
             }
             if !unexpected.is_empty() {
-                errors.push(ValidationError::additional_properties(
+                errors.push(additional_properties_error(
                     self.location.clone(),
                     location.into(),
                     instance,
                     unexpected,
+                    self.properties.property_names(),
                 ))
             }
             Box::new(errors.into_iter())
@@ -285,11 +308,12 @@ impl<M: PropertiesValidatorsMap> Validate for AdditionalPropertiesNotEmptyFalseV
                 if let Some((name, node)) = self.properties.get_key_validator(property) {
                     node.validate(value, &location.push(name))?;
                 } else {
-                    return Err(ValidationError::additional_properties(
+                    return Err(additional_properties_error(
                         self.location.clone(),
                         location.into(),
                         instance,
                         vec![property.clone()],
+                        self.properties.property_names(),
                     ));
                 }
             }
@@ -312,11 +336,12 @@ impl<M: PropertiesValidatorsMap> Validate for AdditionalPropertiesNotEmptyFalseV
             let mut result: PartialApplication = output.into();
             if !unexpected.is_empty() {
                 result.mark_errored(
-                    ValidationError::additional_properties(
+                    additional_properties_error(
                         self.location.clone(),
                         location.into(),
                         instance,
                         unexpected,
+                        self.properties.property_names(),
                     )
                     .into(),
                 );
@@ -380,6 +405,20 @@ impl AdditionalPropertiesNotEmptyValidator<BigValidatorsMap> {
     }
 }
 impl<M: PropertiesValidatorsMap> Validate for AdditionalPropertiesNotEmptyValidator<M> {
+    fn subschemas_at<'s>(
+        &'s self,
+        segment: LocationSegment<'_>,
+        visit: &mut dyn FnMut(&'s SchemaNode),
+    ) {
+        if let LocationSegment::Property(property) = segment {
+            visit(
+                self.properties
+                    .get_validator(property)
+                    .unwrap_or(&self.node),
+            );
+        }
+    }
+
     fn iter_errors<'i>(&self, instance: &'i Value, location: &LazyLocation) -> ErrorIterator<'i> {
         if let Value::Object(map) = instance {
             let mut errors = vec![];
@@ -637,11 +676,12 @@ impl<R: RegexEngine> Validate for AdditionalPropertiesWithPatternsFalseValidator
                 }
             }
             if !unexpected.is_empty() {
-                errors.push(ValidationError::additional_properties(
+                errors.push(additional_properties_error(
                     self.location.clone(),
                     location.into(),
                     instance,
                     unexpected,
+                    literal_patterns(&self.patterns),
                 ))
             }
             Box::new(errors.into_iter())
@@ -675,11 +715,12 @@ impl<R: RegexEngine> Validate for AdditionalPropertiesWithPatternsFalseValidator
                     }
                 }
                 if !has_match {
-                    return Err(ValidationError::additional_properties(
+                    return Err(additional_properties_error(
                         self.location.clone(),
                         location.into(),
                         instance,
                         vec![property.clone()],
+                        literal_patterns(&self.patterns),
                     ));
                 }
             }
@@ -718,11 +759,12 @@ impl<R: RegexEngine> Validate for AdditionalPropertiesWithPatternsFalseValidator
             let mut result: PartialApplication = output.into();
             if !unexpected.is_empty() {
                 result.mark_errored(
-                    ValidationError::additional_properties(
+                    additional_properties_error(
                         self.location.clone(),
                         location.into(),
                         instance,
                         unexpected,
+                        literal_patterns(&self.patterns),
                     )
                     .into(),
                 );
@@ -975,11 +1017,15 @@ impl<M: PropertiesValidatorsMap, R: RegexEngine> Validate
                 }
             }
             if !unexpected.is_empty() {
-                errors.push(ValidationError::additional_properties(
+                errors.push(additional_properties_error(
                     self.location.clone(),
                     location.into(),
                     instance,
                     unexpected,
+                    self.properties
+                        .property_names()
+                        .into_iter()
+                        .chain(literal_patterns(&self.patterns)),
                 ))
             }
             Box::new(errors.into_iter())
@@ -1037,11 +1083,15 @@ impl<M: PropertiesValidatorsMap, R: RegexEngine> Validate
                         }
                     }
                     if !has_match {
-                        return Err(ValidationError::additional_properties(
+                        return Err(additional_properties_error(
                             self.location.clone(),
                             location.into(),
                             instance,
                             vec![property.clone()],
+                            self.properties
+                                .property_names()
+                                .into_iter()
+                                .chain(literal_patterns(&self.patterns)),
                         ));
                     }
                 }
@@ -1080,11 +1130,15 @@ impl<M: PropertiesValidatorsMap, R: RegexEngine> Validate
             let mut result: PartialApplication = output.into();
             if !unexpected.is_empty() {
                 result.mark_errored(
-                    ValidationError::additional_properties(
+                    additional_properties_error(
                         self.location.clone(),
                         location.into(),
                         instance,
                         unexpected,
+                        self.properties
+                            .property_names()
+                            .into_iter()
+                            .chain(literal_patterns(&self.patterns)),
                     )
                     .into(),
                 )
@@ -1361,6 +1415,81 @@ pub(crate) fn compile<'a>(
     }
 }
 
+/// An `additionalProperties` error, suggesting declared property names for likely misspellings.
+fn additional_properties_error<'i, 'd>(
+    location: Location,
+    instance_path: Location,
+    instance: &'i Value,
+    unexpected: Vec<String>,
+    declared: impl IntoIterator<Item = &'d str>,
+) -> ValidationError<'i> {
+    let declared: Vec<&str> = declared.into_iter().collect();
+    let suggestions = unexpected
+        .iter()
+        .filter_map(|property| {
+            closest_name(property, &declared).map(|name| (property.clone(), name.to_string()))
+        })
+        .collect();
+    ValidationError::additional_properties(
+        location,
+        instance_path,
+        instance,
+        unexpected,
+        suggestions,
+    )
+}
+
+/// Patterns in `patternProperties` that only match a single property name, e.g. `^timeout$`.
+fn literal_patterns<R: RegexEngine>(patterns: &[(R, SchemaNode)]) -> impl Iterator<Item = &str> {
+    patterns.iter().filter_map(|(pattern, _)| {
+        let literal = pattern.pattern().strip_prefix('^')?.strip_suffix('$')?;
+        (!literal.contains(|ch: char| "\\.^$|?*+()[]{}".contains(ch))).then_some(literal)
+    })
+}
+
+/// The declared name closest to `property` by edit distance, ignoring ASCII case, if it is
+/// close enough to be a likely misspelling.
+fn closest_name<'d>(property: &str, declared: &[&'d str]) -> Option<&'d str> {
+    let property = property.to_ascii_lowercase();
+    declared
+        .iter()
+        .filter_map(|name| {
+            let distance = edit_distance(&property, &name.to_ascii_lowercase());
+            // About one edit per three characters, as in `rustc` suggestions
+            let threshold = (property.chars().count().max(name.chars().count()) / 3).max(1);
+            (distance <= threshold).then_some((distance, *name))
+        })
+        // Ties are broken by name, as big property maps have no stable order
+        .min()
+        .map(|(_, name)| name)
+}
+
+/// Optimal string alignment distance, i.e. Levenshtein distance that also counts swapping two
+/// adjacent characters as a single edit.
+fn edit_distance(left: &str, right: &str) -> usize {
+    let left: Vec<char> = left.chars().collect();
+    let right: Vec<char> = right.chars().collect();
+    let width = right.len() + 1;
+    let mut distances: Vec<usize> = vec![0; (left.len() + 1) * width];
+    for (idx, distance) in distances.iter_mut().take(width).enumerate() {
+        *distance = idx;
+    }
+    for i in 1..=left.len() {
+        distances[i * width] = i;
+        for j in 1..=right.len() {
+            let cost = usize::from(left[i - 1] != right[j - 1]);
+            let mut distance = (distances[(i - 1) * width + j] + 1)
+                .min(distances[i * width + j - 1] + 1)
+                .min(distances[(i - 1) * width + j - 1] + cost);
+            if i > 1 && j > 1 && left[i - 1] == right[j - 2] && left[i - 2] == right[j - 1] {
+                distance = distance.min(distances[(i - 2) * width + j - 2] + 1);
+            }
+            distances[i * width + j] = distance;
+        }
+    }
+    distances[left.len() * width + right.len()]
+}
+
 #[cfg(test)]
 mod tests {
     use crate::tests_util;
@@ -1769,4 +1898,61 @@ mod tests {
         tests_util::expect_errors(&schema, instance, expected);
         tests_util::assert_locations(&schema, instance, locations)
     }
+
+    #[test_case(
+        &json!({"properties": {"timeout_ms": {}, "retries": {}}, "additionalProperties": false}),
+        &json!({"timeoutms": 1}),
+        "Additional properties are not allowed ('timeoutms' was unexpected; did you mean 'timeout_ms'?)",
+        &json!({"timeoutms": "timeout_ms"});
+        "properties"
+    )]
+    #[test_case(
+        &json!({"properties": {"retries": {}}, "additionalProperties": false}),
+        &json!({"Retires": 1, "color": 2}),
+        "Additional properties are not allowed ('Retires', 'color' were unexpected; did you mean 'retries' instead of 'Retires'?)",
+        &json!({"Retires": "retries"});
+        "case and transposition"
+    )]
+    #[test_case(
+        &json!({"patternProperties": {"^name$": {}, "^x-": {}}, "additionalProperties": false}),
+        &json!({"nmae": 1}),
+        "Additional properties are not allowed ('nmae' was unexpected; did you mean 'name'?)",
+        &json!({"nmae": "name"});
+        "literal patterns"
+    )]
+    #[test_case(
+        &json!({"properties": {"id": {}}, "patternProperties": {"^name$": {}}, "additionalProperties": false}),
+        &json!({"ids": 1, "nam": 2}),
+        "Additional properties are not allowed ('ids', 'nam' were unexpected; did you mean 'id' instead of 'ids', 'name' instead of 'nam'?)",
+        &json!({"ids": "id", "nam": "name"});
+        "properties and patterns"
+    )]
+    fn suggestions(schema: &Value, instance: &Value, message: &str, suggestions: &Value) {
+        let validator = crate::validator_for(schema).expect("Invalid schema");
+        let errors: Vec<_> = validator.iter_errors(instance).collect();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].to_string(), message);
+        assert_eq!(&errors[0].kind.parameters()["suggestions"], suggestions);
+        let error = validator.validate(instance).expect_err("Should fail");
+        assert!(error.to_string().contains("did you mean"));
+    }
+
+    #[test_case(&json!({"properties": {"foo": {}}, "additionalProperties": false}), &json!({"faz": 1}); "too far")]
+    #[test_case(&json!({"patternProperties": {"^na.e$": {}}, "additionalProperties": false}), &json!({"nmae": 1}); "non-literal pattern")]
+    fn no_suggestions(schema: &Value, instance: &Value) {
+        let validator = crate::validator_for(schema).expect("Invalid schema");
+        let error = validator.validate(instance).expect_err("Should fail");
+        assert!(!error.to_string().contains("did you mean"));
+        assert!(!error.kind.parameters().contains_key("suggestions"));
+    }
+
+    #[test_case("timeout", "timeout", 0)]
+    #[test_case("timeout", "timeuot", 1)]
+    #[test_case("timeout", "timeout_ms", 3)]
+    #[test_case("", "abc", 3)]
+    #[test_case("ñame", "name", 1)]
+    fn edit_distance(left: &str, right: &str, expected: usize) {
+        assert_eq!(super::edit_distance(left, right), expected);
+        assert_eq!(super::edit_distance(right, left), expected);
+    }
 }
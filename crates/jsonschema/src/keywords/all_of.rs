@@ -3,7 +3,7 @@ use crate::{
     error::{ErrorIterator, ValidationError},
     node::SchemaNode,
     output::BasicOutput,
-    paths::{LazyLocation, Location},
+    paths::{LazyLocation, Location, LocationSegment},
     types::JsonType,
     validator::{PartialApplication, Validate},
 };
@@ -33,6 +33,22 @@ impl AllOfValidator {
 }
 
 impl Validate for AllOfValidator {
+    fn subschemas_at<'s>(
+        &'s self,
+        segment: LocationSegment<'_>,
+        visit: &mut dyn FnMut(&'s SchemaNode),
+    ) {
+        for node in &self.schemas {
+            node.subschemas_at(segment, visit);
+        }
+    }
+
+    fn matching_subschemas<'s>(&'s self, instance: &Value, visit: &mut dyn FnMut(&'s SchemaNode)) {
+        if self.is_valid(instance) {
+            self.schemas.iter().for_each(visit);
+        }
+    }
+
     #[allow(clippy::needless_collect)]
     fn iter_errors<'i>(&self, instance: &'i Value, location: &LazyLocation) -> ErrorIterator<'i> {
         let errors: Vec<_> = self
@@ -82,6 +98,20 @@ impl SingleValueAllOfValidator {
 }
 
 impl Validate for SingleValueAllOfValidator {
+    fn subschemas_at<'s>(
+        &'s self,
+        segment: LocationSegment<'_>,
+        visit: &mut dyn FnMut(&'s SchemaNode),
+    ) {
+        self.node.subschemas_at(segment, visit);
+    }
+
+    fn matching_subschemas<'s>(&'s self, instance: &Value, visit: &mut dyn FnMut(&'s SchemaNode)) {
+        if self.node.is_valid(instance) {
+            visit(&self.node);
+        }
+    }
+
     fn iter_errors<'i>(&self, instance: &'i Value, location: &LazyLocation) -> ErrorIterator<'i> {
         self.node.iter_errors(instance, location)
     }
@@ -1,6 +1,7 @@
 use crate::{
     compiler,
     error::{error, no_error, ErrorIterator, ValidationError},
+    keywords::helpers,
     node::SchemaNode,
     paths::{LazyLocation, Location},
     types::JsonType,
@@ -42,15 +43,23 @@ impl AnyOfValidator {
 }
 
 impl Validate for AnyOfValidator {
+    fn matching_subschemas<'s>(&'s self, instance: &Value, visit: &mut dyn FnMut(&'s SchemaNode)) {
+        for node in &self.schemas {
+            if node.is_valid(instance) {
+                visit(node);
+            }
+        }
+    }
+
     fn iter_errors<'i>(&self, instance: &'i Value, location: &LazyLocation) -> ErrorIterator<'i> {
-        if self.is_valid(instance) {
-            no_error()
-        } else {
-            error(ValidationError::any_of(
+        match helpers::first_valid_branch(&self.schemas, instance, location) {
+            Ok(_) => no_error(),
+            Err(context) => error(ValidationError::any_of(
                 self.location.clone(),
                 location.into(),
                 instance,
-            ))
+                context,
+            )),
         }
     }
 
@@ -63,14 +72,14 @@ impl Validate for AnyOfValidator {
         instance: &'i Value,
         location: &LazyLocation,
     ) -> Result<(), ValidationError<'i>> {
-        if self.is_valid(instance) {
-            Ok(())
-        } else {
-            Err(ValidationError::any_of(
+        match helpers::first_valid_branch(&self.schemas, instance, location) {
+            Ok(_) => Ok(()),
+            Err(context) => Err(ValidationError::any_of(
                 self.location.clone(),
                 location.into(),
                 instance,
-            ))
+                context,
+            )),
         }
     }
 
@@ -86,7 +95,15 @@ impl Validate for AnyOfValidator {
             }
         }
         if successes.is_empty() {
-            failures.into_iter().collect()
+            helpers::merge_branch_failures(&failures, location, || {
+                ValidationError::any_of(
+                    self.location.clone(),
+                    location.into(),
+                    instance,
+                    Vec::new(),
+                )
+            })
+            .unwrap_or_else(|| failures.into_iter().collect())
         } else {
             successes.into_iter().collect()
         }
@@ -104,7 +121,7 @@ pub(crate) fn compile<'a>(
 
 #[cfg(test)]
 mod tests {
-    use crate::tests_util;
+    use crate::{error::ValidationErrorKind, tests_util};
     use serde_json::{json, Value};
     use test_case::test_case;
 
@@ -113,4 +130,54 @@ mod tests {
     fn location(schema: &Value, instance: &Value, expected: &str) {
         tests_util::assert_schema_location(schema, instance, expected)
     }
+
+    #[test_case(&json!({"anyOf": [{"type": "object"}, {"type": "array"}]}), "anyOf")]
+    #[test_case(&json!({"oneOf": [{"type": "object"}, {"type": "array"}]}), "oneOf")]
+    fn branch_errors(schema: &Value, keyword: &str) {
+        let validator = crate::validator_for(schema).expect("Invalid schema");
+        let instance = json!(1);
+        let error = validator.validate(&instance).expect_err("Should fail");
+        let (ValidationErrorKind::AnyOf { context }
+        | ValidationErrorKind::OneOfNotValid { context }) = &error.kind
+        else {
+            panic!("Unexpected error kind: {:?}", error.kind);
+        };
+        let context: Vec<Vec<_>> = context
+            .iter()
+            .map(|errors| {
+                errors
+                    .iter()
+                    .map(|error| error.schema_path.to_string())
+                    .collect()
+            })
+            .collect();
+        assert_eq!(
+            context,
+            [
+                [format!("/{keyword}/0/type")],
+                [format!("/{keyword}/1/type")]
+            ]
+        );
+        assert_eq!(
+            error.to_string(),
+            format!("1 is not valid under any of the schemas listed in the '{keyword}' keyword, 'type' failed in every branch: [0] 1 is not of type \"object\"; [1] 1 is not of type \"array\"")
+        );
+        assert_eq!(
+            error.masked().to_string(),
+            format!("value is not valid under any of the schemas listed in the '{keyword}' keyword, 'type' failed in every branch: [0] value is not of type \"object\"; [1] value is not of type \"array\"")
+        );
+    }
+
+    #[test]
+    fn different_branch_failures() {
+        let validator =
+            crate::validator_for(&json!({"anyOf": [{"type": "object"}, {"minimum": 2}]}))
+                .expect("Invalid schema");
+        let instance = json!(1);
+        let error = validator.validate(&instance).expect_err("Should fail");
+        assert_eq!(
+            error.to_string(),
+            "1 is not valid under any of the schemas listed in the 'anyOf' keyword"
+        );
+    }
 }
@@ -30,6 +30,31 @@ impl Validate for FalseValidator {
     }
 }
 
+/// The validator of every `false` subschema.
+///
+/// It has no location, so nodes holding it produce errors themselves.
+pub(crate) struct FalseSchemaValidator;
+
+pub(crate) static FALSE_SCHEMA: FalseSchemaValidator = FalseSchemaValidator;
+
+impl Validate for FalseSchemaValidator {
+    fn is_valid(&self, _: &Value) -> bool {
+        false
+    }
+
+    fn validate<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+    ) -> Result<(), ValidationError<'i>> {
+        Err(ValidationError::false_schema(
+            Location::new(),
+            location.into(),
+            instance,
+        ))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::tests_util;
@@ -1,6 +1,6 @@
 use crate::{
     compiler, error::ValidationError, ext::cmp, keywords::CompilationResult, paths::Location,
-    validator::Validate,
+    validator::Validate, ValueComparison,
 };
 use serde_json::{Map, Number, Value};
 
@@ -245,6 +245,50 @@ impl Validate for ConstStringValidator {
     }
 }
 
+/// `const` with a non-default [`ValueComparison`].
+struct ConstComparingValidator {
+    value: Value,
+    comparison: ValueComparison,
+    location: Location,
+}
+
+impl ConstComparingValidator {
+    #[inline]
+    pub(crate) fn compile(
+        value: &Value,
+        comparison: ValueComparison,
+        location: Location,
+    ) -> CompilationResult {
+        Ok(Box::new(ConstComparingValidator {
+            value: value.clone(),
+            comparison,
+            location,
+        }))
+    }
+}
+
+impl Validate for ConstComparingValidator {
+    fn validate<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+    ) -> Result<(), ValidationError<'i>> {
+        if self.is_valid(instance) {
+            Ok(())
+        } else {
+            Err(ValidationError::constant(
+                self.location.clone(),
+                location.into(),
+                instance,
+                &self.value,
+            ))
+        }
+    }
+    fn is_valid(&self, instance: &Value) -> bool {
+        self.comparison.equal(&self.value, instance)
+    }
+}
+
 #[inline]
 pub(crate) fn compile<'a>(
     ctx: &compiler::Context,
@@ -252,6 +296,14 @@ pub(crate) fn compile<'a>(
     schema: &'a Value,
 ) -> Option<CompilationResult<'a>> {
     let location = ctx.location().join("const");
+    let comparison = ctx.config().value_comparison();
+    if !comparison.is_default() {
+        return Some(ConstComparingValidator::compile(
+            schema,
+            *comparison,
+            location,
+        ));
+    }
     match schema {
         Value::Array(items) => Some(ConstArrayValidator::compile(items, location)),
         Value::Bool(item) => Some(ConstBooleanValidator::compile(*item, location)),
@@ -264,7 +316,7 @@ pub(crate) fn compile<'a>(
 
 #[cfg(test)]
 mod tests {
-    use crate::tests_util;
+    use crate::{tests_util, ValueComparison};
     use serde_json::{json, Value};
     use test_case::test_case;
 
@@ -277,4 +329,29 @@ mod tests {
     fn location(schema: &Value, instance: &Value, expected: &str) {
         tests_util::assert_schema_location(schema, instance, expected)
     }
+
+    #[test_case(ValueComparison::new(), &json!(1.0), true)]
+    #[test_case(ValueComparison::new().strict_numbers(true), &json!(1.0), false)]
+    #[test_case(ValueComparison::new().strict_numbers(true), &json!(1), true)]
+    #[test_case(ValueComparison::new().strict_numbers(true), &json!([1.0, "a"]), false; "strict numbers nested")]
+    #[test_case(ValueComparison::new().case_insensitive_strings(true), &json!("A"), true)]
+    #[test_case(ValueComparison::new().case_insensitive_strings(true), &json!([1.0, "A"]), true; "case insensitive nested")]
+    fn value_comparison(comparison: ValueComparison, instance: &Value, expected: bool) {
+        let schema = if instance.is_array() {
+            json!({"const": [1, "a"]})
+        } else if instance.is_string() {
+            json!({"const": "a"})
+        } else {
+            json!({"const": 1})
+        };
+        let validator = crate::options()
+            .with_value_comparison(comparison)
+            .build(&schema)
+            .expect("Invalid schema");
+        assert_eq!(validator.is_valid(instance), expected);
+        if !expected {
+            let error = validator.validate(instance).expect_err("Should fail");
+            assert_eq!(error.schema_path.as_str(), "/const");
+        }
+    }
 }
@@ -40,7 +40,7 @@ impl Validate for ContainsValidator {
         location: &LazyLocation,
     ) -> Result<(), ValidationError<'i>> {
         if let Value::Array(items) = instance {
-            if items.iter().any(|i| self.node.is_valid(i)) {
+            if items.iter().any(|i| self.node.is_valid_at(i, location)) {
                 return Ok(());
             }
             Err(ValidationError::contains(
@@ -119,11 +119,7 @@ impl Validate for MinContainsValidator {
         if let Value::Array(items) = instance {
             let mut matches = 0;
             for item in items {
-                if self
-                    .node
-                    .validators()
-                    .all(|validator| validator.is_valid(item))
-                {
+                if self.node.is_valid_at(item, location) {
                     matches += 1;
                     if matches >= self.min_contains {
                         return Ok(());
@@ -198,11 +194,7 @@ impl Validate for MaxContainsValidator {
         if let Value::Array(items) = instance {
             let mut matches = 0;
             for item in items {
-                if self
-                    .node
-                    .validators()
-                    .all(|validator| validator.is_valid(item))
-                {
+                if self.node.is_valid_at(item, location) {
                     matches += 1;
                     if matches > self.max_contains {
                         return Err(ValidationError::contains(
@@ -285,11 +277,7 @@ impl Validate for MinMaxContainsValidator {
         if let Value::Array(items) = instance {
             let mut matches = 0;
             for item in items {
-                if self
-                    .node
-                    .validators()
-                    .all(|validator| validator.is_valid(item))
-                {
+                if self.node.is_valid_at(item, location) {
                     matches += 1;
                     if matches > self.max_contains {
                         return Err(ValidationError::contains(
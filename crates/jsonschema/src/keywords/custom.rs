@@ -30,6 +30,9 @@ impl Validate for CustomKeyword {
 }
 
 /// Trait that allows implementing custom validation for keywords.
+///
+/// Implementations do not need to be `Send + Sync` when built with `--cfg jsonschema_single_threaded`.
+#[cfg(not(jsonschema_single_threaded))]
 pub trait Keyword: Send + Sync {
     /// Validate instance according to a custom specification.
     ///
@@ -48,6 +51,26 @@ pub trait Keyword: Send + Sync {
     fn is_valid(&self, instance: &Value) -> bool;
 }
 
+/// Trait that allows implementing custom validation for keywords.
+#[cfg(jsonschema_single_threaded)]
+pub trait Keyword {
+    /// Validate instance according to a custom specification.
+    ///
+    /// A custom keyword validator may be used when a validation that cannot be
+    /// easily or efficiently expressed in JSON schema.
+    ///
+    /// The custom validation is applied in addition to the JSON schema validation.
+    fn validate<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+    ) -> Result<(), ValidationError<'i>>;
+    /// Validate instance and return a boolean result.
+    ///
+    /// Could be potentilly faster than [`Keyword::validate`] method.
+    fn is_valid(&self, instance: &Value) -> bool;
+}
+
 pub(crate) trait KeywordFactory: Send + Sync {
     fn init<'a>(
         &self,
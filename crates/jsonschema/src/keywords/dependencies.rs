@@ -1,16 +1,186 @@
 use crate::{
     compiler,
     error::{no_error, ErrorIterator, ValidationError},
-    keywords::{required, unique_items, CompilationResult},
+    keywords::{unique_items, CompilationResult},
     node::SchemaNode,
+    output::{Annotations, BasicOutput},
     paths::{LazyLocation, Location},
     types::JsonType,
-    validator::Validate,
+    validator::{PartialApplication, Validate},
 };
 use serde_json::{Map, Value};
 
+/// Properties an object must contain when it contains the triggering `property`.
+struct RequiredDependency {
+    property: String,
+    required: Vec<String>,
+    location: Location,
+}
+
+impl RequiredDependency {
+    fn compile<'a>(
+        ctx: &compiler::Context,
+        property: &str,
+        schema: &'a Value,
+    ) -> Result<Self, ValidationError<'a>> {
+        let Value::Array(items) = schema else {
+            return Err(ValidationError::single_type_error(
+                Location::new(),
+                ctx.location().clone(),
+                schema,
+                JsonType::Array,
+            ));
+        };
+        let mut required = Vec::with_capacity(items.len());
+        for item in items {
+            match item {
+                Value::String(string) => required.push(string.clone()),
+                _ => {
+                    return Err(ValidationError::single_type_error(
+                        Location::new(),
+                        ctx.location().clone(),
+                        item,
+                        JsonType::String,
+                    ))
+                }
+            }
+        }
+        Ok(RequiredDependency {
+            property: property.to_string(),
+            required,
+            location: ctx.location().clone(),
+        })
+    }
+
+    fn is_valid(&self, item: &Map<String, Value>) -> bool {
+        self.required.iter().all(|name| item.contains_key(name))
+    }
+
+    /// A single error listing all missing properties, if any.
+    fn error<'i>(
+        &self,
+        instance: &'i Value,
+        item: &Map<String, Value>,
+        location: &LazyLocation,
+    ) -> Option<ValidationError<'i>> {
+        let missing: Vec<_> = self
+            .required
+            .iter()
+            .filter(|name| !item.contains_key(*name))
+            .cloned()
+            .collect();
+        if missing.is_empty() {
+            None
+        } else {
+            Some(ValidationError::dependent_required(
+                self.location.clone(),
+                location.into(),
+                instance,
+                self.property.clone(),
+                missing,
+            ))
+        }
+    }
+}
+
+/// A schema an object must be valid under when it contains the triggering `property`.
+struct SchemaDependency {
+    property: String,
+    node: SchemaNode,
+}
+
+impl SchemaDependency {
+    fn compile<'a>(
+        ctx: &compiler::Context,
+        property: &str,
+        schema: &'a Value,
+    ) -> Result<Self, ValidationError<'a>> {
+        Ok(SchemaDependency {
+            property: property.to_string(),
+            node: compiler::compile(ctx, ctx.as_resource_ref(schema))?,
+        })
+    }
+
+    /// Errors of the dependent schema at their own locations, naming the triggering property.
+    fn iter_errors<'i>(&self, instance: &'i Value, location: &LazyLocation) -> ErrorIterator<'i> {
+        let errors: Vec<_> = self
+            .node
+            .iter_errors(instance, location)
+            .map(|error| self.triggered(error))
+            .collect();
+        Box::new(errors.into_iter())
+    }
+
+    fn validate<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+    ) -> Result<(), ValidationError<'i>> {
+        self.node
+            .validate(instance, location)
+            .map_err(|error| self.triggered(error))
+    }
+
+    /// Errors of nested dependent schemas keep their own, innermost trigger.
+    fn triggered<'i>(&self, mut error: ValidationError<'i>) -> ValidationError<'i> {
+        if error.trigger.is_none() {
+            error.trigger = Some(self.property.clone());
+        }
+        error
+    }
+}
+
+enum Dependency {
+    Required(RequiredDependency),
+    Schema(SchemaDependency),
+}
+
+impl Dependency {
+    fn property(&self) -> &str {
+        match self {
+            Dependency::Required(dependency) => &dependency.property,
+            Dependency::Schema(dependency) => &dependency.property,
+        }
+    }
+
+    fn is_valid(&self, instance: &Value, item: &Map<String, Value>) -> bool {
+        match self {
+            Dependency::Required(dependency) => dependency.is_valid(item),
+            Dependency::Schema(dependency) => dependency.node.is_valid(instance),
+        }
+    }
+
+    fn iter_errors<'i>(
+        &self,
+        instance: &'i Value,
+        item: &Map<String, Value>,
+        location: &LazyLocation,
+    ) -> ErrorIterator<'i> {
+        match self {
+            Dependency::Required(dependency) => {
+                Box::new(dependency.error(instance, item, location).into_iter())
+            }
+            Dependency::Schema(dependency) => dependency.iter_errors(instance, location),
+        }
+    }
+
+    fn validate<'i>(
+        &self,
+        instance: &'i Value,
+        item: &Map<String, Value>,
+        location: &LazyLocation,
+    ) -> Result<(), ValidationError<'i>> {
+        match self {
+            Dependency::Required(dependency) => dependency
+                .error(instance, item, location)
+                .map_or(Ok(()), Err),
+            Dependency::Schema(dependency) => dependency.validate(instance, location),
+        }
+    }
+}
+
 pub(crate) struct DependenciesValidator {
-    dependencies: Vec<(String, SchemaNode)>,
+    dependencies: Vec<Dependency>,
 }
 
 impl DependenciesValidator {
@@ -21,19 +191,13 @@ impl DependenciesValidator {
             let mut dependencies = Vec::with_capacity(map.len());
             for (key, subschema) in map {
                 let ctx = kctx.new_at_location(key.as_str());
-                let s =
-                    match subschema {
-                        Value::Array(_) => {
-                            let validators = vec![required::compile_with_path(
-                                subschema,
-                                kctx.location().clone(),
-                            )
-                            .expect("The required validator compilation does not return None")?];
-                            SchemaNode::from_array(&kctx, validators)
-                        }
-                        _ => compiler::compile(&ctx, ctx.as_resource_ref(subschema))?,
-                    };
-                dependencies.push((key.clone(), s))
+                let dependency = match subschema {
+                    Value::Array(_) => {
+                        Dependency::Required(RequiredDependency::compile(&ctx, key, subschema)?)
+                    }
+                    _ => Dependency::Schema(SchemaDependency::compile(&ctx, key, subschema)?),
+                };
+                dependencies.push(dependency);
             }
             Ok(Box::new(DependenciesValidator { dependencies }))
         } else {
@@ -52,23 +216,21 @@ impl Validate for DependenciesValidator {
         if let Value::Object(item) = instance {
             self.dependencies
                 .iter()
-                .filter(|(property, _)| item.contains_key(property))
-                .all(move |(_, node)| node.is_valid(instance))
+                .filter(|dependency| item.contains_key(dependency.property()))
+                .all(|dependency| dependency.is_valid(instance, item))
         } else {
             true
         }
     }
 
-    #[allow(clippy::needless_collect)]
     fn iter_errors<'i>(&self, instance: &'i Value, location: &LazyLocation) -> ErrorIterator<'i> {
         if let Value::Object(item) = instance {
             let errors: Vec<_> = self
                 .dependencies
                 .iter()
-                .filter(|(property, _)| item.contains_key(property))
-                .flat_map(move |(_, node)| node.iter_errors(instance, location))
+                .filter(|dependency| item.contains_key(dependency.property()))
+                .flat_map(|dependency| dependency.iter_errors(instance, item, location))
                 .collect();
-            // TODO. custom error message for "required" case
             Box::new(errors.into_iter())
         } else {
             no_error()
@@ -81,9 +243,9 @@ impl Validate for DependenciesValidator {
         location: &LazyLocation,
     ) -> Result<(), ValidationError<'i>> {
         if let Value::Object(item) = instance {
-            for (property, dependency) in &self.dependencies {
-                if item.contains_key(property) {
-                    dependency.validate(instance, location)?;
+            for dependency in &self.dependencies {
+                if item.contains_key(dependency.property()) {
+                    dependency.validate(instance, item, location)?;
                 }
             }
         }
@@ -92,7 +254,7 @@ impl Validate for DependenciesValidator {
 }
 
 pub(crate) struct DependentRequiredValidator {
-    dependencies: Vec<(String, SchemaNode)>,
+    dependencies: Vec<RequiredDependency>,
 }
 
 impl DependentRequiredValidator {
@@ -111,22 +273,8 @@ impl DependentRequiredValidator {
                             subschema,
                         ));
                     }
-                    let validators =
-                        vec![
-                            required::compile_with_path(subschema, kctx.location().clone())
-                                .expect(
-                                    "The required validator compilation does not return None",
-                                )?,
-                        ];
-                    dependencies.push((key.clone(), SchemaNode::from_array(&kctx, validators)));
-                } else {
-                    return Err(ValidationError::single_type_error(
-                        Location::new(),
-                        ictx.location().clone(),
-                        subschema,
-                        JsonType::Array,
-                    ));
                 }
+                dependencies.push(RequiredDependency::compile(&ictx, key, subschema)?);
             }
             Ok(Box::new(DependentRequiredValidator { dependencies }))
         } else {
@@ -145,8 +293,8 @@ impl Validate for DependentRequiredValidator {
             let errors: Vec<_> = self
                 .dependencies
                 .iter()
-                .filter(|(property, _)| item.contains_key(property))
-                .flat_map(move |(_, node)| node.iter_errors(instance, location))
+                .filter(|dependency| item.contains_key(&dependency.property))
+                .filter_map(|dependency| dependency.error(instance, item, location))
                 .collect();
             Box::new(errors.into_iter())
         } else {
@@ -157,8 +305,8 @@ impl Validate for DependentRequiredValidator {
         if let Value::Object(item) = instance {
             self.dependencies
                 .iter()
-                .filter(|(property, _)| item.contains_key(property))
-                .all(move |(_, node)| node.is_valid(instance))
+                .filter(|dependency| item.contains_key(&dependency.property))
+                .all(|dependency| dependency.is_valid(item))
         } else {
             true
         }
@@ -170,9 +318,11 @@ impl Validate for DependentRequiredValidator {
         location: &LazyLocation,
     ) -> Result<(), ValidationError<'i>> {
         if let Value::Object(item) = instance {
-            for (property, dependency) in &self.dependencies {
-                if item.contains_key(property) {
-                    dependency.validate(instance, location)?;
+            for dependency in &self.dependencies {
+                if item.contains_key(&dependency.property) {
+                    if let Some(error) = dependency.error(instance, item, location) {
+                        return Err(error);
+                    }
                 }
             }
             Ok(())
@@ -183,7 +333,7 @@ impl Validate for DependentRequiredValidator {
 }
 
 pub(crate) struct DependentSchemasValidator {
-    dependencies: Vec<(String, SchemaNode)>,
+    dependencies: Vec<SchemaDependency>,
 }
 impl DependentSchemasValidator {
     #[inline]
@@ -193,8 +343,7 @@ impl DependentSchemasValidator {
             let mut dependencies = Vec::with_capacity(map.len());
             for (key, subschema) in map {
                 let ctx = ctx.new_at_location(key.as_str());
-                let schema_nodes = compiler::compile(&ctx, ctx.as_resource_ref(subschema))?;
-                dependencies.push((key.clone(), schema_nodes));
+                dependencies.push(SchemaDependency::compile(&ctx, key, subschema)?);
             }
             Ok(Box::new(DependentSchemasValidator { dependencies }))
         } else {
@@ -213,8 +362,8 @@ impl Validate for DependentSchemasValidator {
             let errors: Vec<_> = self
                 .dependencies
                 .iter()
-                .filter(|(property, _)| item.contains_key(property))
-                .flat_map(move |(_, node)| node.iter_errors(instance, location))
+                .filter(|dependency| item.contains_key(&dependency.property))
+                .flat_map(|dependency| dependency.iter_errors(instance, location))
                 .collect();
             Box::new(errors.into_iter())
         } else {
@@ -225,8 +374,8 @@ impl Validate for DependentSchemasValidator {
         if let Value::Object(item) = instance {
             self.dependencies
                 .iter()
-                .filter(|(property, _)| item.contains_key(property))
-                .all(move |(_, node)| node.is_valid(instance))
+                .filter(|dependency| item.contains_key(&dependency.property))
+                .all(|dependency| dependency.node.is_valid(instance))
         } else {
             true
         }
@@ -238,8 +387,8 @@ impl Validate for DependentSchemasValidator {
         location: &LazyLocation,
     ) -> Result<(), ValidationError<'i>> {
         if let Value::Object(item) = instance {
-            for (property, dependency) in &self.dependencies {
-                if item.contains_key(property) {
+            for dependency in &self.dependencies {
+                if item.contains_key(&dependency.property) {
                     dependency.validate(instance, location)?;
                 }
             }
@@ -248,6 +397,25 @@ impl Validate for DependentSchemasValidator {
             Ok(())
         }
     }
+
+    fn apply<'a>(&'a self, instance: &Value, location: &LazyLocation) -> PartialApplication<'a> {
+        if let Value::Object(item) = instance {
+            let mut activated = Vec::new();
+            let mut result = BasicOutput::default();
+            for dependency in &self.dependencies {
+                if item.contains_key(&dependency.property) {
+                    activated.push(Value::String(dependency.property.clone()));
+                    result += dependency.node.apply_rooted(instance, location);
+                }
+            }
+            let mut result: PartialApplication<'a> = result.into();
+            // Report which dependent schemas were activated by the instance
+            result.annotate(Annotations::from(Value::Array(activated)));
+            result
+        } else {
+            PartialApplication::valid_empty()
+        }
+    }
 }
 
 #[inline]
@@ -280,9 +448,91 @@ mod tests {
     use serde_json::{json, Value};
     use test_case::test_case;
 
-    #[test_case(&json!({"dependencies": {"bar": ["foo"]}}), &json!({"bar": 1}), "/dependencies")]
+    #[test_case(&json!({"dependencies": {"bar": ["foo"]}}), &json!({"bar": 1}), "/dependencies/bar")]
     #[test_case(&json!({"dependencies": {"bar": {"type": "string"}}}), &json!({"bar": 1}), "/dependencies/bar/type")]
+    #[test_case(&json!({"dependentRequired": {"bar": ["foo"]}}), &json!({"bar": 1}), "/dependentRequired/bar")]
+    #[test_case(&json!({"dependentSchemas": {"bar": {"type": "string"}}}), &json!({"bar": 1}), "/dependentSchemas/bar/type")]
     fn location(schema: &Value, instance: &Value, expected: &str) {
         tests_util::assert_schema_location(schema, instance, expected)
     }
+
+    #[test_case(&json!({"dependentRequired": {"a": ["b", "c", "d"], "b": ["e"], "x": ["y"]}}); "dependentRequired")]
+    #[test_case(&json!({"dependencies": {"a": ["b", "c", "d"], "b": ["e"], "x": ["y"]}}); "dependencies")]
+    fn required_grouped_per_property(schema: &Value) {
+        let validator = crate::validator_for(schema).expect("Invalid schema");
+        let instance = json!({"a": 1, "b": 2, "c": 3});
+        let errors: Vec<_> = validator.iter_errors(&instance).collect();
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].kind.name(), "dependentRequired");
+        assert_eq!(
+            Value::from(errors[0].kind.parameters()),
+            json!({"property": "a", "missing": ["d"]})
+        );
+        assert_eq!(
+            Value::from(errors[1].kind.parameters()),
+            json!({"property": "b", "missing": ["e"]})
+        );
+        assert_eq!(
+            errors[0].to_string(),
+            r#"Missing property required by "a": "d""#
+        );
+        let validator = crate::validator_for(&json!({"dependentRequired": {"a\"": ["b", "c"]}}))
+            .expect("Invalid schema");
+        let instance = json!({"a\"": 1});
+        let error = validator.validate(&instance).expect_err("Should fail");
+        assert_eq!(
+            error.to_string(),
+            r#"Missing properties required by "a\"": "b", "c""#
+        );
+    }
+
+    #[test_case(&json!({"dependentSchemas": {"a": {"properties": {"b": {"type": "string"}}, "required": ["c"]}, "x": false}}), "/dependentSchemas"; "dependentSchemas")]
+    #[test_case(&json!({"dependencies": {"a": {"properties": {"b": {"type": "string"}}, "required": ["c"]}, "x": false}}), "/dependencies"; "dependencies")]
+    fn schema_errors_at_own_locations(schema: &Value, keyword: &str) {
+        let validator = crate::validator_for(schema).expect("Invalid schema");
+        let instance = json!({"a": 1, "b": 2});
+        let errors: Vec<_> = validator
+            .iter_errors(&instance)
+            .map(|error| {
+                (
+                    error.instance_path.to_string(),
+                    error.schema_path.to_string(),
+                    error.trigger,
+                )
+            })
+            .collect();
+        let trigger = Some("a".to_string());
+        assert_eq!(
+            errors,
+            [
+                (
+                    "/b".to_string(),
+                    format!("{keyword}/a/properties/b/type"),
+                    trigger.clone()
+                ),
+                (String::new(), format!("{keyword}/a/required"), trigger),
+            ]
+        );
+        let error = validator.validate(&instance).expect_err("Should fail");
+        assert_eq!(error.instance_path.as_str(), "/b");
+        assert_eq!(error.trigger.as_deref(), Some("a"));
+        assert_eq!(
+            error.format_with("{trigger}: {schema_path}"),
+            format!("a: {keyword}/a/properties/b/type")
+        );
+    }
+
+    #[test]
+    fn nested_schema_errors_keep_innermost_trigger() {
+        let validator = crate::validator_for(&json!({
+            "dependentSchemas": {"a": {"dependentSchemas": {"b": {"required": ["c"]}}}},
+            "required": ["d"]
+        }))
+        .expect("Invalid schema");
+        let triggers: Vec<_> = validator
+            .iter_errors(&json!({"a": 1, "b": 2}))
+            .map(|error| error.trigger)
+            .collect();
+        assert_eq!(triggers, [Some("b".to_string()), None]);
+    }
 }
@@ -6,6 +6,7 @@ use crate::{
     paths::{LazyLocation, Location},
     types::{JsonType, JsonTypeSet},
     validator::Validate,
+    ValueComparison,
 };
 use serde_json::{Map, Value};
 
@@ -113,6 +114,57 @@ impl Validate for SingleValueEnumValidator {
     }
 }
 
+/// `enum` with a non-default [`ValueComparison`].
+#[derive(Debug)]
+pub(crate) struct EnumComparingValidator {
+    options: Value,
+    items: Vec<Value>,
+    comparison: ValueComparison,
+    location: Location,
+}
+
+impl EnumComparingValidator {
+    #[inline]
+    pub(crate) fn compile<'a>(
+        schema: &'a Value,
+        items: &'a [Value],
+        comparison: ValueComparison,
+        location: Location,
+    ) -> CompilationResult<'a> {
+        Ok(Box::new(EnumComparingValidator {
+            options: schema.clone(),
+            items: items.to_vec(),
+            comparison,
+            location,
+        }))
+    }
+}
+
+impl Validate for EnumComparingValidator {
+    fn validate<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+    ) -> Result<(), ValidationError<'i>> {
+        if self.is_valid(instance) {
+            Ok(())
+        } else {
+            Err(ValidationError::enumeration(
+                self.location.clone(),
+                location.into(),
+                instance,
+                &self.options,
+            ))
+        }
+    }
+
+    fn is_valid(&self, instance: &Value) -> bool {
+        self.items
+            .iter()
+            .any(|item| self.comparison.equal(instance, item))
+    }
+}
+
 #[inline]
 pub(crate) fn compile<'a>(
     ctx: &compiler::Context,
@@ -121,7 +173,15 @@ pub(crate) fn compile<'a>(
 ) -> Option<CompilationResult<'a>> {
     if let Value::Array(items) = schema {
         let location = ctx.location().join("enum");
-        if items.len() == 1 {
+        let comparison = ctx.config().value_comparison();
+        if !comparison.is_default() {
+            Some(EnumComparingValidator::compile(
+                schema,
+                items,
+                *comparison,
+                location,
+            ))
+        } else if items.len() == 1 {
             let value = items.iter().next().expect("Vec is not empty");
             Some(SingleValueEnumValidator::compile(schema, value, location))
         } else {
@@ -139,7 +199,7 @@ pub(crate) fn compile<'a>(
 
 #[cfg(test)]
 mod tests {
-    use crate::tests_util;
+    use crate::{tests_util, ValueComparison};
     use serde_json::{json, Value};
     use test_case::test_case;
 
@@ -148,4 +208,23 @@ mod tests {
     fn location(schema: &Value, instance: &Value, expected: &str) {
         tests_util::assert_schema_location(schema, instance, expected)
     }
+
+    #[test_case(ValueComparison::new(), &json!(-0.0), true)]
+    #[test_case(ValueComparison::new().strict_numbers(true), &json!(-0.0), false)]
+    #[test_case(ValueComparison::new().strict_numbers(true), &json!(0), true)]
+    #[test_case(ValueComparison::new().strict_numbers(true), &json!(2.5), true)]
+    #[test_case(ValueComparison::new(), &json!("ÉTÉ"), false; "case sensitive by default")]
+    #[test_case(ValueComparison::new().case_insensitive_strings(true), &json!("ÉTÉ"), true; "case insensitive")]
+    #[test_case(ValueComparison::new().case_insensitive_strings(true), &json!("hiver"), false)]
+    fn value_comparison(comparison: ValueComparison, instance: &Value, expected: bool) {
+        let validator = crate::options()
+            .with_value_comparison(comparison)
+            .build(&json!({"enum": [0, 2.5, "été"]}))
+            .expect("Invalid schema");
+        assert_eq!(validator.is_valid(instance), expected);
+        if !expected {
+            let error = validator.validate(instance).expect_err("Should fail");
+            assert_eq!(error.schema_path.as_str(), "/enum");
+        }
+    }
 }
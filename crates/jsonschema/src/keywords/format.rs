@@ -7,18 +7,19 @@ use std::{
 
 use email_address::EmailAddress;
 use fancy_regex::Regex;
-use once_cell::sync::Lazy;
+use once_cell::sync::{Lazy, OnceCell};
 use serde_json::{Map, Value};
 use uuid_simd::{parse_hyphenated, Out};
 
 use crate::{
     compiler, ecma,
-    error::ValidationError,
-    keywords::CompilationResult,
+    error::{DateBound, ValidationError},
+    keywords::{BoxedValidator, CompilationResult},
     paths::{LazyLocation, Location},
+    sync::Shared,
     types::JsonType,
     validator::Validate,
-    Draft,
+    Draft, ValidationOptions,
 };
 
 static URI_TEMPLATE_RE: Lazy<Regex> = Lazy::new(|| {
@@ -274,7 +275,7 @@ fn is_valid_time(time: &str) -> bool {
     }
 }
 
-fn is_valid_datetime(datetime: &str) -> bool {
+pub(crate) fn is_valid_datetime(datetime: &str) -> bool {
     // Find the position of 'T' or 't' separator
     let t_pos = match datetime.bytes().position(|b| b == b'T' || b == b't') {
         Some(pos) => pos,
@@ -315,7 +316,7 @@ fn is_valid_idn_email(email: &str) -> bool {
     is_valid_email_impl(email, is_valid_idn_hostname)
 }
 
-fn is_valid_hostname(hostname: &str) -> bool {
+pub(crate) fn is_valid_hostname(hostname: &str) -> bool {
     const VALID_CHARS: [bool; 256] = {
         let mut table = [false; 256];
         let mut i = 0;
@@ -608,7 +609,7 @@ fn is_valid_uri_template(uri_template: &str) -> bool {
         .expect("Simple URI_TEMPLATE_RE pattern")
 }
 
-fn is_valid_uuid(uuid: &str) -> bool {
+pub(crate) fn is_valid_uuid(uuid: &str) -> bool {
     let mut out = [0; 16];
     parse_hyphenated(uuid.as_bytes(), Out::from_mut(&mut out)).is_ok()
 }
@@ -621,7 +622,7 @@ macro_rules! format_validators {
             }
 
             impl $validator {
-                pub(crate) fn compile<'a>(ctx: &compiler::Context) -> CompilationResult<'a> {
+                pub(crate) fn compile<'a>(ctx: &FormatContext) -> CompilationResult<'a> {
                     let location = ctx.location().join("format");
                     Ok(Box::new($validator { location }))
                 }
@@ -691,6 +692,128 @@ format_validators!(
     (UuidValidator, "uuid", is_valid_uuid),
 );
 
+/// Days since the Unix epoch of a proleptic Gregorian date.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Nanoseconds since the Unix epoch of a valid `date` or `date-time` value.
+fn timestamp(value: &str) -> Option<i128> {
+    let number = |range: std::ops::Range<usize>| value.get(range)?.parse::<i64>().ok();
+    let days = days_from_civil(number(0..4)?, number(5..7)?, number(8..10)?);
+    let mut seconds = days * 86_400;
+    let mut nanos = 0;
+    if value.len() > 10 {
+        // `T`, then `HH:MM:SS`, an optional fraction, and the offset
+        seconds += number(11..13)? * 3600 + number(14..16)? * 60 + number(17..19)?;
+        let rest = &value[19..];
+        let offset_start = rest.find(['Z', 'z', '+', '-'])?;
+        if let Some(fraction) = rest[..offset_start].strip_prefix('.') {
+            let digits: String = fraction
+                .chars()
+                .chain(std::iter::repeat('0'))
+                .take(9)
+                .collect();
+            nanos = digits.parse::<i128>().ok()?;
+        }
+        let offset = &rest[offset_start..];
+        if offset.len() == 6 {
+            let minutes =
+                offset[1..3].parse::<i64>().ok()? * 60 + offset[4..6].parse::<i64>().ok()?;
+            // The local time is ahead of UTC for positive offsets
+            if offset.starts_with('+') {
+                seconds -= minutes * 60;
+            } else {
+                seconds += minutes * 60;
+            }
+        }
+    }
+    Some(i128::from(seconds) * 1_000_000_000 + nanos)
+}
+
+/// A `date` or `date-time` format restricted by [`DateBounds`].
+///
+/// [`DateBounds`]: crate::DateBounds
+struct BoundedDateValidator {
+    location: Location,
+    format: &'static str,
+    bounds: crate::DateBounds,
+}
+
+impl BoundedDateValidator {
+    fn compile<'a>(ctx: &FormatContext, format: &'static str) -> CompilationResult<'a> {
+        let location = ctx.location().join("format");
+        Ok(Box::new(BoundedDateValidator {
+            location,
+            format,
+            bounds: ctx.config().date_bounds().clone(),
+        }))
+    }
+
+    fn is_valid_format(&self, value: &str) -> bool {
+        if self.format == "date" {
+            is_valid_date(value)
+        } else {
+            is_valid_datetime(value)
+        }
+    }
+
+    /// Which bound the value violates, if any.
+    fn out_of_bounds(&self, value: &str) -> Option<DateBound> {
+        let time = timestamp(value)?;
+        let (earliest, latest) = self.bounds.resolve();
+        if earliest.is_some_and(|earliest| time < earliest) {
+            Some(DateBound::Earliest)
+        } else if latest.is_some_and(|latest| time > latest) {
+            Some(DateBound::Latest)
+        } else {
+            None
+        }
+    }
+}
+
+impl Validate for BoundedDateValidator {
+    fn is_valid(&self, instance: &Value) -> bool {
+        if let Value::String(item) = instance {
+            self.is_valid_format(item) && self.out_of_bounds(item).is_none()
+        } else {
+            true
+        }
+    }
+
+    fn validate<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+    ) -> Result<(), ValidationError<'i>> {
+        if let Value::String(item) = instance {
+            if !self.is_valid_format(item) {
+                return Err(ValidationError::format(
+                    self.location.clone(),
+                    location.into(),
+                    instance,
+                    self.format,
+                ));
+            }
+            if let Some(bound) = self.out_of_bounds(item) {
+                return Err(ValidationError::date_out_of_bounds(
+                    self.location.clone(),
+                    location.into(),
+                    instance,
+                    self.format,
+                    bound,
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
 struct CustomFormatValidator {
     location: Location,
     format_name: String,
@@ -698,7 +821,7 @@ struct CustomFormatValidator {
 }
 impl CustomFormatValidator {
     pub(crate) fn compile<'a>(
-        ctx: &compiler::Context,
+        ctx: &FormatContext,
         format_name: String,
         check: Arc<dyn Format>,
     ) -> CompilationResult<'a> {
@@ -752,16 +875,138 @@ where
     }
 }
 
+/// What compiling a `format` keyword depends on, kept by validators that compile it on first
+/// use.
+struct FormatContext {
+    config: Shared<ValidationOptions>,
+    draft: Draft,
+    location: Location,
+}
+
+impl FormatContext {
+    fn new(ctx: &compiler::Context) -> FormatContext {
+        FormatContext {
+            config: Shared::clone(ctx.config()),
+            draft: ctx.draft(),
+            location: ctx.location().clone(),
+        }
+    }
+    fn config(&self) -> &ValidationOptions {
+        &self.config
+    }
+    fn draft(&self) -> Draft {
+        self.draft
+    }
+    fn location(&self) -> &Location {
+        &self.location
+    }
+    fn get_format(&self, format: &str) -> Option<(&String, &Arc<dyn Format>)> {
+        self.config.get_format(format)
+    }
+    fn are_unknown_formats_ignored(&self) -> bool {
+        self.config.are_unknown_formats_ignored()
+    }
+}
+
+/// Applies a format validator only if formats are asserted, which [`EvalOptions`] may override
+/// per call.
+///
+/// [`EvalOptions`]: crate::EvalOptions
+enum FormatToggle {
+    /// Asserted by default, compiled with the schema.
+    Enabled(BoxedValidator),
+    /// Compiled once asserted per call, so that errors in the keyword, e.g. unknown formats, are
+    /// reported only then.
+    Disabled(Box<LazyFormat>),
+}
+
+struct LazyFormat {
+    ctx: FormatContext,
+    format: Value,
+    inner: OnceCell<Result<Option<BoxedValidator>, ValidationError<'static>>>,
+}
+
+impl FormatToggle {
+    /// The validator to apply in the evaluation at `location`, if any.
+    fn asserted<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+    ) -> Result<Option<&BoxedValidator>, ValidationError<'i>> {
+        match self {
+            FormatToggle::Enabled(inner) => Ok(location
+                .evaluation
+                .map_or(true, |evaluation| evaluation.validates_formats(true))
+                .then_some(inner)),
+            FormatToggle::Disabled(lazy) => {
+                if !location
+                    .evaluation
+                    .is_some_and(|evaluation| evaluation.validates_formats(false))
+                {
+                    return Ok(None);
+                }
+                let compiled = lazy.inner.get_or_init(|| {
+                    compile_format(&lazy.ctx, &lazy.format)
+                        .transpose()
+                        .map_err(ValidationError::to_owned)
+                });
+                match compiled {
+                    Ok(inner) => Ok(inner.as_ref()),
+                    Err(error) => Err(ValidationError::custom(
+                        error.schema_path.clone(),
+                        location.into(),
+                        instance,
+                        error.to_string(),
+                    )),
+                }
+            }
+        }
+    }
+}
+
+impl Validate for FormatToggle {
+    fn is_valid(&self, instance: &Value) -> bool {
+        match self {
+            FormatToggle::Enabled(inner) => inner.is_valid(instance),
+            FormatToggle::Disabled(_) => true,
+        }
+    }
+
+    fn validate<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+    ) -> Result<(), ValidationError<'i>> {
+        match self.asserted(instance, location)? {
+            Some(inner) => inner.validate(instance, location),
+            None => Ok(()),
+        }
+    }
+}
+
 #[inline]
 pub(crate) fn compile<'a>(
     ctx: &compiler::Context,
     _: &'a Map<String, Value>,
     schema: &'a Value,
 ) -> Option<CompilationResult<'a>> {
-    if !ctx.validates_formats_by_default() {
-        return None;
+    let format = FormatContext::new(ctx);
+    if ctx.validates_formats_by_default() {
+        let inner = match compile_format(&format, schema)? {
+            Ok(inner) => inner,
+            Err(error) => return Some(Err(error)),
+        };
+        Some(Ok(Box::new(FormatToggle::Enabled(inner))))
+    } else {
+        Some(Ok(Box::new(FormatToggle::Disabled(Box::new(LazyFormat {
+            ctx: format,
+            format: schema.clone(),
+            inner: OnceCell::new(),
+        })))))
     }
+}
 
+fn compile_format<'a>(ctx: &FormatContext, schema: &'a Value) -> Option<CompilationResult<'a>> {
     if let Value::String(format) = schema {
         if let Some((name, func)) = ctx.get_format(format) {
             return Some(CustomFormatValidator::compile(
@@ -772,6 +1017,14 @@ pub(crate) fn compile<'a>(
         }
         let draft = ctx.draft();
         match format.as_str() {
+            "date" | "date-time" if ctx.config().date_bounds().is_enabled() => {
+                let format = if format == "date" {
+                    "date"
+                } else {
+                    "date-time"
+                };
+                Some(BoundedDateValidator::compile(ctx, format))
+            }
             "date" => Some(DateValidator::compile(ctx)),
             "date-time" => Some(DateTimeValidator::compile(ctx)),
             "duration" if draft >= Draft::Draft201909 => Some(DurationValidator::compile(ctx)),
@@ -822,7 +1075,7 @@ mod tests {
     use serde_json::json;
     use test_case::test_case;
 
-    use crate::tests_util;
+    use crate::{error::ValidationErrorKind, tests_util};
 
     use super::*;
 
@@ -855,6 +1108,82 @@ mod tests {
         assert!(without_validation.is_valid(&not_email_instance));
     }
 
+    #[test_case("1970-01-01", 0; "epoch date")]
+    #[test_case("2000-02-29", 951_782_400; "leap day")]
+    #[test_case("1969-12-31T23:59:59Z", -1; "before epoch")]
+    #[test_case("2024-03-01T12:00:00+02:00", 1_709_287_200; "positive offset")]
+    #[test_case("2024-03-01T12:00:00.5-01:30", 1_709_299_800; "negative offset with fraction")]
+    fn timestamps(value: &str, seconds: i64) {
+        assert_eq!(
+            timestamp(value).map(|nanos| nanos.div_euclid(1_000_000_000)),
+            Some(i128::from(seconds))
+        );
+    }
+
+    #[test_case("date-time", "2023-11-14T22:13:20Z", None; "now is allowed")]
+    #[test_case("date-time", "2023-11-14T23:13:20+01:00", None; "now with offset")]
+    #[test_case("date-time", "2023-11-14T22:13:20.001Z", Some("\"2023-11-14T22:13:20.001Z\" is after the latest allowed date-time"); "future")]
+    #[test_case("date-time", "1999-12-31T23:59:59Z", Some("\"1999-12-31T23:59:59Z\" is before the earliest allowed date-time"); "too early")]
+    #[test_case("date", "2000-01-01", None; "earliest date")]
+    #[test_case("date", "2023-11-15", Some("\"2023-11-15\" is after the latest allowed date"); "future date")]
+    #[test_case("date", "2023-02-29", Some("\"2023-02-29\" is not a \"date\""); "invalid date")]
+    fn date_bounds(format: &str, value: &str, expected: Option<&str>) {
+        use std::time::{Duration, UNIX_EPOCH};
+
+        let now = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let validator = crate::options()
+            .should_validate_formats(true)
+            .with_date_bounds(
+                crate::DateBounds::new()
+                    .earliest(crate::TimeBound::At(
+                        UNIX_EPOCH + Duration::from_secs(946_684_800),
+                    ))
+                    .latest(crate::TimeBound::FromNow(0))
+                    .clock(move || now),
+            )
+            .build(&json!({"format": format}))
+            .expect("Invalid schema");
+        let instance = json!(value);
+        assert_eq!(validator.is_valid(&instance), expected.is_none());
+        let error = validator.validate(&instance).err();
+        if let Some(error) = &error {
+            if !matches!(error.kind, ValidationErrorKind::Format { .. }) {
+                assert!(matches!(
+                    &error.kind,
+                    ValidationErrorKind::DateOutOfBounds { format: kind, .. } if kind == format
+                ));
+            }
+        }
+        assert_eq!(error.map(|error| error.to_string()).as_deref(), expected);
+    }
+
+    #[test]
+    fn date_bounds_relative_to_now() {
+        use std::time::{Duration, UNIX_EPOCH};
+
+        let validator = crate::options()
+            .should_validate_formats(true)
+            .with_date_bounds(
+                crate::DateBounds::new()
+                    .earliest(crate::TimeBound::FromNow(-86_400))
+                    .clock(|| UNIX_EPOCH + Duration::from_secs(86_400 * 10)),
+            )
+            .build(&json!({"format": "date"}))
+            .expect("Invalid schema");
+        assert!(validator.is_valid(&json!("1970-01-10")));
+        assert!(!validator.is_valid(&json!("1970-01-09")));
+    }
+
+    #[test]
+    fn date_bounds_without_format_validation() {
+        let validator = crate::options()
+            .should_validate_formats(false)
+            .with_date_bounds(crate::DateBounds::new().latest(crate::TimeBound::FromNow(0)))
+            .build(&json!({"format": "date"}))
+            .expect("Invalid schema");
+        assert!(validator.is_valid(&json!("9999-12-31")));
+    }
+
     #[test]
     fn ecma_regex() {
         // See GH-230
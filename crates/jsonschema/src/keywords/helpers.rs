@@ -1,6 +1,93 @@
+use std::cell::RefCell;
+
 use serde_json::{Map, Value};
 
-use crate::{compiler, paths::Location, types::JsonType, ValidationError};
+use crate::{
+    compiler,
+    node::SchemaNode,
+    output::BasicOutput,
+    paths::{LazyLocation, Location},
+    types::JsonType,
+    validator::{PartialApplication, Validate},
+    ValidationError,
+};
+
+thread_local! {
+    /// Subschemas expanded in place on the current thread, innermost last, along with the address
+    /// of the instance they are evaluated against, or zero during compilation.
+    static IN_PLACE: RefCell<Vec<(usize, usize)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Marks a subschema as being expanded in place, i.e. against the same instance location, for as
+/// long as the guard is alive.
+///
+/// `unevaluatedProperties` and `unevaluatedItems` follow references in place while compiling, and
+/// recursive references are followed in place while validating, so a reference that leads back
+/// to a subschema being expanded would otherwise recurse indefinitely.
+pub(crate) struct InPlaceGuard {
+    instance: usize,
+    subschema: usize,
+}
+
+impl InPlaceGuard {
+    pub(crate) fn new(subschema: &Map<String, Value>) -> InPlaceGuard {
+        InPlaceGuard::at(InPlaceGuard::key(subschema))
+    }
+    pub(crate) fn at(subschema: usize) -> InPlaceGuard {
+        IN_PLACE.with(|stack| stack.borrow_mut().push((0, subschema)));
+        InPlaceGuard {
+            instance: 0,
+            subschema,
+        }
+    }
+    /// Identifies a subschema by its address in the registry.
+    pub(crate) fn key<T>(subschema: &T) -> usize {
+        subschema as *const T as usize
+    }
+    /// Whether the subschema is being expanded in place during compilation.
+    pub(crate) fn is_expanding(subschema: &Map<String, Value>) -> bool {
+        let key = (0, InPlaceGuard::key(subschema));
+        IN_PLACE.with(|stack| stack.borrow().contains(&key))
+    }
+    /// Marks `subschema` as being evaluated against `instance`.
+    ///
+    /// Returns `None` if it is already evaluated against the same instance location, i.e. a
+    /// reference cycles without descending into the instance, e.g. `a -> b -> a`. Such cycles
+    /// can't reject anything the enclosing evaluation does not reject already.
+    pub(crate) fn enter(instance: &Value, subschema: usize) -> Option<InPlaceGuard> {
+        let instance = InPlaceGuard::key(instance);
+        IN_PLACE.with(|stack| {
+            let mut stack = stack.borrow_mut();
+            // Evaluations on the same location are always on top of the stack
+            if stack
+                .iter()
+                .rev()
+                .filter(|(key, _)| *key != 0)
+                .take_while(|(key, _)| *key == instance)
+                .any(|(_, active)| *active == subschema)
+            {
+                return None;
+            }
+            stack.push((instance, subschema));
+            Some(InPlaceGuard {
+                instance,
+                subschema,
+            })
+        })
+    }
+}
+
+impl Drop for InPlaceGuard {
+    fn drop(&mut self) {
+        IN_PLACE.with(|stack| {
+            let mut stack = stack.borrow_mut();
+            let entry = (self.instance, self.subschema);
+            if let Some(position) = stack.iter().rposition(|active| *active == entry) {
+                stack.remove(position);
+            }
+        });
+    }
+}
 
 #[inline]
 pub(crate) fn map_get_u64<'a>(
@@ -46,3 +133,101 @@ pub(crate) fn fail_on_non_positive_integer(
         ValidationError::single_type_error(Location::new(), instance_path, value, JsonType::Integer)
     }
 }
+
+/// Bytes counted at once before checking whether a character count reached its cap.
+const CHAR_COUNT_CHUNK: usize = 8 * 1024;
+
+/// The number of characters in `string`, counting no further than `cap`.
+///
+/// Characters take one to four bytes in UTF-8, so long strings are often decided by their byte
+/// length alone, and are otherwise counted in chunks to stop as soon as `cap` is reached.
+fn count_chars_up_to(string: &str, cap: u64) -> u64 {
+    let bytes = string.as_bytes();
+    if u64::try_from(bytes.len() / 4).map_or(true, |min_chars| min_chars >= cap) {
+        return cap;
+    }
+    let mut count: u64 = 0;
+    for chunk in bytes.chunks(CHAR_COUNT_CHUNK) {
+        count = count.saturating_add(bytecount::num_chars(chunk) as u64);
+        if count >= cap {
+            return cap;
+        }
+    }
+    count
+}
+
+/// Whether `string` has more than `limit` characters.
+#[inline]
+pub(crate) fn has_more_chars_than(string: &str, limit: u64) -> bool {
+    // Characters take at least a byte each
+    if u64::try_from(string.len()).is_ok_and(|len| len <= limit) {
+        return false;
+    }
+    limit < u64::MAX && count_chars_up_to(string, limit + 1) > limit
+}
+
+/// Whether `string` has fewer than `limit` characters.
+#[inline]
+pub(crate) fn has_fewer_chars_than(string: &str, limit: u64) -> bool {
+    if u64::try_from(string.len()).is_ok_and(|len| len < limit) {
+        return true;
+    }
+    count_chars_up_to(string, limit) < limit
+}
+
+/// The index of the first valid subschema, or the errors of every subschema if none is valid.
+pub(crate) fn first_valid_branch<'i>(
+    schemas: &[SchemaNode],
+    instance: &'i Value,
+    location: &LazyLocation,
+) -> Result<usize, Vec<Vec<ValidationError<'i>>>> {
+    if let Some(idx) = schemas
+        .iter()
+        .position(|node| node.is_valid_at(instance, location))
+    {
+        return Ok(idx);
+    }
+    Err(schemas
+        .iter()
+        .map(|node| node.iter_errors(instance, location).collect())
+        .collect())
+}
+
+/// Report failures of all `anyOf` / `oneOf` branches as the single error of the combinator if
+/// every branch failed for the same reason, see [`ValidationError::common_branch_failure`].
+///
+/// `error` builds the error of the combinator without branch errors, its message is extended with
+/// the ones already collected in `failures`. Returns `None` if branches failed differently, so their
+/// errors should be reported as is.
+pub(crate) fn merge_branch_failures<'a, 'i>(
+    failures: &[BasicOutput<'a>],
+    location: &LazyLocation,
+    error: impl FnOnce() -> ValidationError<'i>,
+) -> Option<PartialApplication<'a>> {
+    if failures.len() < 2 {
+        return None;
+    }
+    let current = Location::from(location);
+    let mut keyword = None;
+    let mut messages = Vec::with_capacity(failures.len());
+    for failure in failures {
+        let BasicOutput::Invalid(units) = failure else {
+            return None;
+        };
+        let (1, Some(unit)) = (units.len(), units.front()) else {
+            return None;
+        };
+        if unit.instance_location() != &current {
+            return None;
+        }
+        let failed = unit.keyword_location().as_str().rsplit('/').next()?;
+        if *keyword.get_or_insert(failed) != failed {
+            return None;
+        }
+        messages.push(unit.error_description());
+    }
+    let message = error().with_branch_messages(keyword?, messages.into_iter());
+    Some(PartialApplication::invalid_empty(vec![message
+        .as_str()
+        .into()]))
+}
@@ -3,11 +3,12 @@ use crate::{
     error::{no_error, ErrorIterator},
     keywords::CompilationResult,
     node::SchemaNode,
+    output::Annotations,
     paths::LazyLocation,
     validator::{PartialApplication, Validate},
     ValidationError,
 };
-use serde_json::{Map, Value};
+use serde_json::{json, Map, Value};
 
 pub(crate) struct IfThenValidator {
     schema: SchemaNode,
@@ -37,7 +38,7 @@ impl IfThenValidator {
 impl Validate for IfThenValidator {
     #[allow(clippy::needless_collect)]
     fn iter_errors<'i>(&self, instance: &'i Value, location: &LazyLocation) -> ErrorIterator<'i> {
-        if self.schema.is_valid(instance) {
+        if self.schema.is_valid_at(instance, location) {
             let errors: Vec<_> = self.then_schema.iter_errors(instance, location).collect();
             Box::new(errors.into_iter())
         } else {
@@ -58,7 +59,7 @@ impl Validate for IfThenValidator {
         instance: &'i Value,
         location: &LazyLocation,
     ) -> Result<(), ValidationError<'i>> {
-        if self.schema.is_valid(instance) {
+        if self.schema.is_valid_at(instance, location) {
             self.then_schema.validate(instance, location)
         } else {
             Ok(())
@@ -70,9 +71,13 @@ impl Validate for IfThenValidator {
         if if_result.is_valid() {
             let then_result = self.then_schema.apply_rooted(instance, location);
             if_result += then_result;
-            if_result.into()
+            let mut result: PartialApplication<'a> = if_result.into();
+            result.annotate(branch_annotation(true, Some("then")));
+            result
         } else {
-            PartialApplication::valid_empty()
+            let mut result = PartialApplication::valid_empty();
+            result.annotate(branch_annotation(false, None));
+            result
         }
     }
 }
@@ -105,7 +110,7 @@ impl IfElseValidator {
 impl Validate for IfElseValidator {
     #[allow(clippy::needless_collect)]
     fn iter_errors<'i>(&self, instance: &'i Value, location: &LazyLocation) -> ErrorIterator<'i> {
-        if self.schema.is_valid(instance) {
+        if self.schema.is_valid_at(instance, location) {
             no_error()
         } else {
             let errors: Vec<_> = self.else_schema.iter_errors(instance, location).collect();
@@ -126,7 +131,7 @@ impl Validate for IfElseValidator {
         instance: &'i Value,
         location: &LazyLocation,
     ) -> Result<(), ValidationError<'i>> {
-        if self.schema.is_valid(instance) {
+        if self.schema.is_valid_at(instance, location) {
             Ok(())
         } else {
             self.else_schema.validate(instance, location)
@@ -135,11 +140,16 @@ impl Validate for IfElseValidator {
 
     fn apply<'a>(&'a self, instance: &Value, location: &LazyLocation) -> PartialApplication<'a> {
         let if_result = self.schema.apply_rooted(instance, location);
-        if if_result.is_valid() {
-            if_result.into()
+        let (mut result, matched): (PartialApplication<'a>, _) = if if_result.is_valid() {
+            (if_result.into(), true)
         } else {
-            self.else_schema.apply_rooted(instance, location).into()
-        }
+            (
+                self.else_schema.apply_rooted(instance, location).into(),
+                false,
+            )
+        };
+        result.annotate(branch_annotation(matched, (!matched).then_some("else")));
+        result
     }
 }
 
@@ -177,7 +187,7 @@ impl IfThenElseValidator {
 impl Validate for IfThenElseValidator {
     #[allow(clippy::needless_collect)]
     fn iter_errors<'i>(&self, instance: &'i Value, location: &LazyLocation) -> ErrorIterator<'i> {
-        if self.schema.is_valid(instance) {
+        if self.schema.is_valid_at(instance, location) {
             let errors: Vec<_> = self.then_schema.iter_errors(instance, location).collect();
             Box::new(errors.into_iter())
         } else {
@@ -199,7 +209,7 @@ impl Validate for IfThenElseValidator {
         instance: &'i Value,
         location: &LazyLocation,
     ) -> Result<(), ValidationError<'i>> {
-        if self.schema.is_valid(instance) {
+        if self.schema.is_valid_at(instance, location) {
             self.then_schema.validate(instance, location)
         } else {
             self.else_schema.validate(instance, location)
@@ -208,15 +218,28 @@ impl Validate for IfThenElseValidator {
 
     fn apply<'a>(&'a self, instance: &Value, location: &LazyLocation) -> PartialApplication<'a> {
         let mut if_result = self.schema.apply_rooted(instance, location);
-        if if_result.is_valid() {
+        let (mut result, matched): (PartialApplication<'a>, _) = if if_result.is_valid() {
             if_result += self.then_schema.apply_rooted(instance, location);
-            if_result.into()
+            (if_result.into(), true)
         } else {
-            self.else_schema.apply_rooted(instance, location).into()
-        }
+            (
+                self.else_schema.apply_rooted(instance, location).into(),
+                false,
+            )
+        };
+        result.annotate(branch_annotation(
+            matched,
+            Some(if matched { "then" } else { "else" }),
+        ));
+        result
     }
 }
 
+/// Records whether `if` matched and which branch was taken, if any.
+fn branch_annotation(matched: bool, branch: Option<&str>) -> Annotations<'static> {
+    Annotations::from(json!({"matched": matched, "branch": branch}))
+}
+
 #[inline]
 pub(crate) fn compile<'a>(
     ctx: &compiler::Context,
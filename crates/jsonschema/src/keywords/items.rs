@@ -3,7 +3,7 @@ use crate::{
     error::{no_error, ErrorIterator},
     keywords::CompilationResult,
     node::SchemaNode,
-    paths::LazyLocation,
+    paths::{LazyLocation, LocationSegment},
     validator::{PartialApplication, Validate},
     ValidationError,
 };
@@ -29,6 +29,18 @@ impl ItemsArrayValidator {
     }
 }
 impl Validate for ItemsArrayValidator {
+    fn subschemas_at<'s>(
+        &'s self,
+        segment: LocationSegment<'_>,
+        visit: &mut dyn FnMut(&'s SchemaNode),
+    ) {
+        if let LocationSegment::Index(idx) = segment {
+            if let Some(node) = self.items.get(idx) {
+                visit(node);
+            }
+        }
+    }
+
     #[allow(clippy::needless_collect)]
     fn iter_errors<'i>(&self, instance: &'i Value, location: &LazyLocation) -> ErrorIterator<'i> {
         if let Value::Array(items) = instance {
@@ -82,6 +94,16 @@ impl ItemsObjectValidator {
     }
 }
 impl Validate for ItemsObjectValidator {
+    fn subschemas_at<'s>(
+        &'s self,
+        segment: LocationSegment<'_>,
+        visit: &mut dyn FnMut(&'s SchemaNode),
+    ) {
+        if let LocationSegment::Index(_) = segment {
+            visit(&self.node);
+        }
+    }
+
     #[allow(clippy::needless_collect)]
     fn iter_errors<'i>(&self, instance: &'i Value, location: &LazyLocation) -> ErrorIterator<'i> {
         if let Value::Array(items) = instance {
@@ -161,6 +183,18 @@ impl ItemsObjectSkipPrefixValidator {
 }
 
 impl Validate for ItemsObjectSkipPrefixValidator {
+    fn subschemas_at<'s>(
+        &'s self,
+        segment: LocationSegment<'_>,
+        visit: &mut dyn FnMut(&'s SchemaNode),
+    ) {
+        if let LocationSegment::Index(idx) = segment {
+            if idx >= self.skip_prefix {
+                visit(&self.node);
+            }
+        }
+    }
+
     #[allow(clippy::needless_collect)]
     fn iter_errors<'i>(&self, instance: &'i Value, location: &LazyLocation) -> ErrorIterator<'i> {
         if let Value::Array(items) = instance {
@@ -1,7 +1,10 @@
 use crate::{
     compiler,
     error::ValidationError,
-    keywords::{helpers::fail_on_non_positive_integer, CompilationResult},
+    keywords::{
+        helpers::{fail_on_non_positive_integer, has_more_chars_than},
+        CompilationResult,
+    },
     paths::{LazyLocation, Location},
     validator::Validate,
 };
@@ -41,7 +44,7 @@ impl MaxLengthValidator {
 impl Validate for MaxLengthValidator {
     fn is_valid(&self, instance: &Value) -> bool {
         if let Value::String(item) = instance {
-            if (bytecount::num_chars(item.as_bytes()) as u64) > self.limit {
+            if has_more_chars_than(item, self.limit) {
                 return false;
             }
         }
@@ -54,7 +57,7 @@ impl Validate for MaxLengthValidator {
         location: &LazyLocation,
     ) -> Result<(), ValidationError<'i>> {
         if let Value::String(item) = instance {
-            if (bytecount::num_chars(item.as_bytes()) as u64) > self.limit {
+            if has_more_chars_than(item, self.limit) {
                 return Err(ValidationError::max_length(
                     self.location.clone(),
                     location.into(),
@@ -81,9 +84,37 @@ pub(crate) fn compile<'a>(
 mod tests {
     use crate::tests_util;
     use serde_json::json;
+    use test_case::test_case;
 
     #[test]
     fn location() {
         tests_util::assert_schema_location(&json!({"maxLength": 1}), &json!("ab"), "/maxLength")
     }
+
+    #[test_case("日本", 2, true)]
+    #[test_case("日本", 1, false)]
+    #[test_case("😀", 1, true)]
+    #[test_case("a", u64::MAX, true)]
+    fn multibyte(instance: &str, limit: u64, valid: bool) {
+        let schema = json!({"maxLength": limit});
+        if valid {
+            tests_util::is_valid(&schema, &json!(instance));
+        } else {
+            tests_util::is_not_valid(&schema, &json!(instance));
+        }
+    }
+
+    #[test_case(20_000, true)]
+    #[test_case(19_999, false)]
+    #[test_case(4_999, false)]
+    fn long_strings(limit: u64, valid: bool) {
+        // Counted across several chunks, with characters split between them
+        let instance = "日".repeat(20_000);
+        let schema = json!({"maxLength": limit});
+        if valid {
+            tests_util::is_valid(&schema, &json!(instance));
+        } else {
+            tests_util::is_not_valid(&schema, &json!(instance));
+        }
+    }
 }
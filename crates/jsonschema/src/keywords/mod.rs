@@ -13,6 +13,7 @@ pub(crate) mod format;
 pub(crate) mod helpers;
 pub(crate) mod if_;
 pub(crate) mod items;
+#[cfg(feature = "draft4")]
 pub(crate) mod legacy;
 pub(crate) mod max_items;
 pub(crate) mod max_length;
@@ -28,6 +29,7 @@ pub(crate) mod pattern;
 pub(crate) mod pattern_properties;
 pub(crate) mod prefix_items;
 pub(crate) mod properties;
+pub(crate) mod property_groups;
 pub(crate) mod property_names;
 pub(crate) mod ref_;
 pub(crate) mod required;
@@ -43,7 +45,7 @@ use serde_json::{Map, Value};
 use crate::{compiler, error, validator::Validate};
 
 pub(crate) type CompilationResult<'a> = Result<BoxedValidator, error::ValidationError<'a>>;
-pub(crate) type BoxedValidator = Box<dyn Validate + Send + Sync>;
+pub(crate) type BoxedValidator = Box<dyn Validate>;
 
 type CompileFunc<'a> =
     fn(&'a compiler::Context, &'a Map<String, Value>, &'a Value) -> Option<CompilationResult<'a>>;
@@ -254,14 +256,17 @@ pub(crate) fn get_for_draft<'a>(
             Some((BuiltinKeyword::UniqueItems.into(), unique_items::compile))
         }
         // Draft 4 specific
+        #[cfg(feature = "draft4")]
         (Draft::Draft4, "maximum") => Some((
             BuiltinKeyword::Maximum.into(),
             legacy::maximum_draft_4::compile,
         )),
+        #[cfg(feature = "draft4")]
         (Draft::Draft4, "minimum") => Some((
             BuiltinKeyword::Minimum.into(),
             legacy::minimum_draft_4::compile,
         )),
+        #[cfg(feature = "draft4")]
         (Draft::Draft4, "type") => {
             Some((BuiltinKeyword::Type.into(), legacy::type_draft_4::compile))
         }
@@ -418,7 +423,7 @@ mod tests {
     #[test_case(&json!({"minLength": 2}), &json!("f"), r#""f" is shorter than 2 characters"#)]
     #[test_case(&json!({"minProperties": 1}), &json!({}), r#"{} has less than 1 property"#)]
     #[test_case(&json!({"multipleOf": 2}), &json!(7), r#"7 is not a multiple of 2"#)]
-    #[test_case(&json!({"not": {"type": "integer"}}), &json!(1), r#"{"type":"integer"} is not allowed for 1"#)]
+    #[test_case(&json!({"not": {"type": "integer"}}), &json!(1), r#"{"type":"integer"} is not allowed for 1 (it matches '/not/type')"#)]
     #[test_case(&json!({"oneOf": [{"type": "integer"}, {"minimum": 2}]}), &json!(1.1), r#"1.1 is not valid under any of the schemas listed in the 'oneOf' keyword"#)]
     #[test_case(&json!({"oneOf": [{"type": "integer"}, {"minimum": 2}]}), &json!(3), r#"3 is valid under more than one of the schemas listed in the 'oneOf' keyword"#)]
     #[test_case(&json!({"pattern": "^a*$"}), &json!("abc"), r#""abc" does not match "^a*$""#)]
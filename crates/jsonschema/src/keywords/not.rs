@@ -31,14 +31,17 @@ impl Validate for NotValidator {
         instance: &'i Value,
         location: &LazyLocation,
     ) -> Result<(), ValidationError<'i>> {
-        if self.is_valid(instance) {
+        if !self.node.is_valid_at(instance, location) {
             Ok(())
         } else {
+            let mut matched = Vec::new();
+            self.node.matched_assertions(instance, &mut matched);
             Err(ValidationError::not(
                 self.node.location().clone(),
                 location.into(),
                 instance,
                 self.original.clone(),
+                matched,
             ))
         }
     }
@@ -55,8 +58,9 @@ pub(crate) fn compile<'a>(
 
 #[cfg(test)]
 mod tests {
-    use crate::tests_util;
-    use serde_json::json;
+    use crate::{error::ValidationErrorKind, tests_util};
+    use serde_json::{json, Value};
+    use test_case::test_case;
 
     #[test]
     fn location() {
@@ -66,4 +70,25 @@ mod tests {
             "/not",
         )
     }
+
+    #[test_case(&json!({"not": {"type": "string", "minLength": 2}}), &json!("foo"), &["/not/minLength", "/not/type"])]
+    #[test_case(&json!({"not": {"anyOf": [{"type": "string"}, {"maxLength": 3}, {"type": "integer"}]}}), &json!("foo"), &["/not/anyOf/0/type", "/not/anyOf/1/maxLength"])]
+    #[test_case(&json!({"not": {"oneOf": [{"type": "integer"}, {"minimum": 2}]}}), &json!(1), &["/not/oneOf/0/type"])]
+    #[test_case(&json!({"not": {"allOf": [{"$ref": "#/$defs/positive"}, {"multipleOf": 2}]}, "$defs": {"positive": {"minimum": 1}}}), &json!(4), &["/not/allOf/0/$ref/minimum", "/not/allOf/1/multipleOf"])]
+    #[test_case(&json!({"not": {}}), &json!(1), &[]; "empty schema")]
+    #[test_case(&json!({"not": true}), &json!(1), &["/not"])]
+    fn matched(schema: &Value, instance: &Value, expected: &[&str]) {
+        let validator = crate::validator_for(schema).expect("Invalid schema");
+        let error = validator.validate(instance).expect_err("Should fail");
+        let ValidationErrorKind::Not { matched, .. } = &error.kind else {
+            panic!("Unexpected error: {error:?}");
+        };
+        assert_eq!(
+            matched
+                .iter()
+                .map(|location| location.as_str())
+                .collect::<Vec<_>>(),
+            expected
+        );
+    }
 }
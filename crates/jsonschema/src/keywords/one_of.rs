@@ -1,7 +1,7 @@
 use crate::{
     compiler,
     error::ValidationError,
-    keywords::CompilationResult,
+    keywords::{helpers, CompilationResult},
     node::SchemaNode,
     output::BasicOutput,
     paths::{LazyLocation, Location},
@@ -40,10 +40,10 @@ impl OneOfValidator {
         }
     }
 
-    fn get_first_valid(&self, instance: &Value) -> Option<usize> {
+    fn get_first_valid(&self, instance: &Value, location: &LazyLocation) -> Option<usize> {
         let mut first_valid_idx = None;
         for (idx, node) in self.schemas.iter().enumerate() {
-            if node.is_valid(instance) {
+            if node.is_valid_at(instance, location) {
                 first_valid_idx = Some(idx);
                 break;
             }
@@ -52,43 +52,54 @@ impl OneOfValidator {
     }
 
     #[allow(clippy::arithmetic_side_effects)]
-    fn are_others_valid(&self, instance: &Value, idx: usize) -> bool {
+    fn are_others_valid(&self, instance: &Value, location: &LazyLocation, idx: usize) -> bool {
         // `idx + 1` will not overflow, because the maximum possible value there is `usize::MAX - 1`
         // For example we have `usize::MAX` schemas and only the last one is valid, then
         // in `get_first_valid` we enumerate from `0`, and on the last index will be `usize::MAX - 1`
         self.schemas
             .iter()
             .skip(idx + 1)
-            .any(|n| n.is_valid(instance))
+            .any(|n| n.is_valid_at(instance, location))
     }
 }
 
 impl Validate for OneOfValidator {
+    fn matching_subschemas<'s>(&'s self, instance: &Value, visit: &mut dyn FnMut(&'s SchemaNode)) {
+        let location = LazyLocation::new();
+        if let Some(idx) = self.get_first_valid(instance, &location) {
+            if !self.are_others_valid(instance, &location, idx) {
+                visit(&self.schemas[idx]);
+            }
+        }
+    }
+
     fn is_valid(&self, instance: &Value) -> bool {
-        let first_valid_idx = self.get_first_valid(instance);
-        first_valid_idx.is_some_and(|idx| !self.are_others_valid(instance, idx))
+        let location = LazyLocation::new();
+        let first_valid_idx = self.get_first_valid(instance, &location);
+        first_valid_idx.is_some_and(|idx| !self.are_others_valid(instance, &location, idx))
     }
     fn validate<'i>(
         &self,
         instance: &'i Value,
         location: &LazyLocation,
     ) -> Result<(), ValidationError<'i>> {
-        let first_valid_idx = self.get_first_valid(instance);
-        if let Some(idx) = first_valid_idx {
-            if self.are_others_valid(instance, idx) {
-                return Err(ValidationError::one_of_multiple_valid(
-                    self.location.clone(),
-                    location.into(),
-                    instance,
-                ));
+        match helpers::first_valid_branch(&self.schemas, instance, location) {
+            Ok(idx) => {
+                if self.are_others_valid(instance, location, idx) {
+                    return Err(ValidationError::one_of_multiple_valid(
+                        self.location.clone(),
+                        location.into(),
+                        instance,
+                    ));
+                }
+                Ok(())
             }
-            Ok(())
-        } else {
-            Err(ValidationError::one_of_not_valid(
+            Err(context) => Err(ValidationError::one_of_not_valid(
                 self.location.clone(),
                 location.into(),
                 instance,
-            ))
+                context,
+            )),
         }
     }
     fn apply<'a>(&'a self, instance: &Value, location: &LazyLocation) -> PartialApplication<'a> {
@@ -106,9 +117,24 @@ impl Validate for OneOfValidator {
         } else if successes.len() > 1 {
             PartialApplication::invalid_empty(vec!["more than one subschema succeeded".into()])
         } else if !failures.is_empty() {
-            failures.into_iter().sum::<BasicOutput<'_>>().into()
+            helpers::merge_branch_failures(&failures, location, || {
+                ValidationError::one_of_not_valid(
+                    self.location.clone(),
+                    location.into(),
+                    instance,
+                    Vec::new(),
+                )
+            })
+            .unwrap_or_else(|| failures.into_iter().sum::<BasicOutput<'_>>().into())
         } else {
-            unreachable!("compilation should fail for oneOf with no subschemas")
+            // An empty `oneOf` is rejected by the meta-schema, unless its validation is disabled
+            PartialApplication::invalid_empty(vec![ValidationError::one_of_not_valid(
+                self.location.clone(),
+                location.into(),
+                instance,
+                Vec::new(),
+            )
+            .into()])
         }
     }
 }
@@ -133,4 +159,14 @@ mod tests {
     fn location(schema: &Value, instance: &Value, expected: &str) {
         tests_util::assert_schema_location(schema, instance, expected)
     }
+
+    #[test]
+    fn empty_without_schema_validation() {
+        let validator = crate::options()
+            .without_schema_validation()
+            .build(&json!({"oneOf": []}))
+            .expect("Invalid schema");
+        assert!(!validator.is_valid(&json!(1)));
+        assert!(!validator.apply(&json!(1)).basic().is_valid());
+    }
 }
@@ -1,6 +1,6 @@
 use crate::{
     compiler, ecma,
-    error::ValidationError,
+    error::{SizeLimitKind, ValidationError},
     keywords::CompilationResult,
     options::PatternEngineOptions,
     paths::{LazyLocation, Location},
@@ -12,6 +12,7 @@ use serde_json::{Map, Value};
 
 pub(crate) struct PatternValidator<R> {
     regex: R,
+    max_length: Option<usize>,
     location: Location,
 }
 
@@ -22,6 +23,17 @@ impl<R: RegexEngine> Validate for PatternValidator<R> {
         location: &LazyLocation,
     ) -> Result<(), ValidationError<'i>> {
         if let Value::String(item) = instance {
+            if let Some(limit) = self.max_length {
+                if item.len() > limit {
+                    return Err(ValidationError::size_limit_exceeded(
+                        self.location.clone(),
+                        location.into(),
+                        instance,
+                        SizeLimitKind::StringLength,
+                        limit,
+                    ));
+                }
+            }
             match self.regex.is_match(item) {
                 Ok(is_match) => {
                     if !is_match {
@@ -49,6 +61,9 @@ impl<R: RegexEngine> Validate for PatternValidator<R> {
 
     fn is_valid(&self, instance: &Value) -> bool {
         if let Value::String(item) = instance {
+            if self.max_length.is_some_and(|limit| item.len() > limit) {
+                return false;
+            }
             return self.regex.is_match(item).unwrap_or(false);
         }
         true
@@ -72,13 +87,18 @@ pub(crate) fn compile<'a>(
                     size_limit,
                     dfa_size_limit,
                 } => {
-                    let Ok(regex) =
-                        build_fancy_regex(&pattern, backtrack_limit, size_limit, dfa_size_limit)
-                    else {
+                    let Ok(regex) = build_fancy_regex(
+                        &pattern,
+                        backtrack_limit,
+                        size_limit,
+                        dfa_size_limit,
+                        ctx.config().shares_regexes(),
+                    ) else {
                         return Some(Err(invalid_regex(ctx, schema)));
                     };
                     Some(Ok(Box::new(PatternValidator {
                         regex,
+                        max_length: ctx.config().size_limits().string_length(),
                         location: ctx.location().join("pattern"),
                     })))
                 }
@@ -86,11 +106,17 @@ pub(crate) fn compile<'a>(
                     size_limit,
                     dfa_size_limit,
                 } => {
-                    let Ok(regex) = build_regex(&pattern, size_limit, dfa_size_limit) else {
+                    let Ok(regex) = build_regex(
+                        &pattern,
+                        size_limit,
+                        dfa_size_limit,
+                        ctx.config().shares_regexes(),
+                    ) else {
                         return Some(Err(invalid_regex(ctx, schema)));
                     };
                     Some(Ok(Box::new(PatternValidator {
                         regex,
+                        max_length: ctx.config().size_limits().string_length(),
                         location: ctx.location().join("pattern"),
                     })))
                 }
@@ -111,7 +137,7 @@ fn invalid_regex<'a>(ctx: &compiler::Context, schema: &'a Value) -> ValidationEr
 
 #[cfg(test)]
 mod tests {
-    use crate::{tests_util, PatternOptions};
+    use crate::{tests_util, PatternOptions, SizeLimits};
     use serde_json::json;
     use test_case::test_case;
 
@@ -159,4 +185,23 @@ mod tests {
         let invalid = json!("Hello123");
         assert!(!validator.is_valid(&invalid));
     }
+
+    #[test]
+    fn test_max_string_length() {
+        let schema = json!({"pattern": "^a+$"});
+        let validator = crate::options()
+            .with_size_limits(SizeLimits::new().max_string_length(3))
+            .build(&schema)
+            .expect("Schema should be valid");
+
+        assert!(validator.is_valid(&json!("aaa")));
+        let instance = json!("aaaa");
+        assert!(!validator.is_valid(&instance));
+        let error = validator.validate(&instance).expect_err("Should fail");
+        assert_eq!(
+            error.to_string(),
+            "String is longer than the configured limit of 3 bytes"
+        );
+        assert_eq!(error.schema_path.as_str(), "/pattern");
+    }
 }
@@ -185,6 +185,7 @@ pub(crate) fn compile<'a>(
                                         backtrack_limit,
                                         size_limit,
                                         dfa_size_limit,
+                                        ctx.config().shares_regexes(),
                                     )
                                 }) {
                                     Ok(Ok(r)) => r,
@@ -204,7 +205,12 @@ pub(crate) fn compile<'a>(
                         } => Some(Ok(Box::new(SingleValuePatternPropertiesValidator {
                             regex: {
                                 match ecma::to_rust_regex(key).map(|pattern| {
-                                    build_regex(&pattern, size_limit, dfa_size_limit)
+                                    build_regex(
+                                        &pattern,
+                                        size_limit,
+                                        dfa_size_limit,
+                                        ctx.config().shares_regexes(),
+                                    )
                                 }) {
                                     Ok(Ok(r)) => r,
                                     _ => {
@@ -236,6 +242,7 @@ pub(crate) fn compile<'a>(
                                             backtrack_limit,
                                             size_limit,
                                             dfa_size_limit,
+                                            ctx.config().shares_regexes(),
                                         )
                                     }) {
                                         Ok(Ok(r)) => r,
@@ -259,7 +266,12 @@ pub(crate) fn compile<'a>(
                                 let pctx = ctx.new_at_location(pattern.as_str());
                                 patterns.push((
                                     match ecma::to_rust_regex(pattern).map(|pattern| {
-                                        build_regex(&pattern, size_limit, dfa_size_limit)
+                                        build_regex(
+                                            &pattern,
+                                            size_limit,
+                                            dfa_size_limit,
+                                            ctx.config().shares_regexes(),
+                                        )
                                     }) {
                                         Ok(Ok(r)) => r,
                                         _ => return Some(Err(invalid_regex(&ctx, subschema))),
@@ -2,7 +2,7 @@ use crate::{
     compiler,
     error::{no_error, ErrorIterator, ValidationError},
     node::SchemaNode,
-    paths::{LazyLocation, Location},
+    paths::{LazyLocation, Location, LocationSegment},
     types::JsonType,
     validator::{PartialApplication, Validate},
 };
@@ -32,6 +32,18 @@ impl PrefixItemsValidator {
 }
 
 impl Validate for PrefixItemsValidator {
+    fn subschemas_at<'s>(
+        &'s self,
+        segment: LocationSegment<'_>,
+        visit: &mut dyn FnMut(&'s SchemaNode),
+    ) {
+        if let LocationSegment::Index(idx) = segment {
+            if let Some(node) = self.schemas.get(idx) {
+                visit(node);
+            }
+        }
+    }
+
     #[allow(clippy::needless_collect)]
     fn iter_errors<'i>(&self, instance: &'i Value, location: &LazyLocation) -> ErrorIterator<'i> {
         if let Value::Array(items) = instance {
@@ -4,7 +4,7 @@ use crate::{
     keywords::CompilationResult,
     node::SchemaNode,
     output::BasicOutput,
-    paths::{LazyLocation, Location},
+    paths::{LazyLocation, Location, LocationSegment},
     types::JsonType,
     validator::{PartialApplication, Validate},
 };
@@ -41,6 +41,20 @@ impl PropertiesValidator {
 }
 
 impl Validate for PropertiesValidator {
+    fn subschemas_at<'s>(
+        &'s self,
+        segment: LocationSegment<'_>,
+        visit: &mut dyn FnMut(&'s SchemaNode),
+    ) {
+        if let LocationSegment::Property(property) = segment {
+            for (name, node) in &self.properties {
+                if name == property {
+                    visit(node);
+                }
+            }
+        }
+    }
+
     #[allow(clippy::needless_collect)]
     fn iter_errors<'i>(&self, instance: &'i Value, location: &LazyLocation) -> ErrorIterator<'i> {
         if let Value::Object(item) = instance {
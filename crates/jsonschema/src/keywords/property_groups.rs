@@ -0,0 +1,228 @@
+//! Non-standard keywords constraining which properties of a group may appear together.
+//!
+//! Expressing these constraints with `oneOf` / `not` + `required` leads to large schemas and
+//! hard-to-read error messages, therefore they are implemented natively. They are disabled by
+//! default, see [`ValidationOptions::with_property_group_keywords`].
+//!
+//! [`ValidationOptions::with_property_group_keywords`]: crate::ValidationOptions::with_property_group_keywords
+use crate::{
+    keywords::custom::Keyword,
+    paths::{LazyLocation, Location},
+    types::JsonType,
+    ValidationError,
+};
+use serde_json::{Map, Value};
+
+/// Exactly one property of the group must be present.
+pub(crate) const REQUIRES_ONE_OF: &str = "x-requiresOneOf";
+/// At least one property of the group must be present.
+pub(crate) const REQUIRES_ANY_OF: &str = "x-requiresAnyOf";
+/// At most one property of the group may be present.
+pub(crate) const MUTUALLY_EXCLUSIVE: &str = "x-mutuallyExclusive";
+
+#[derive(Debug, Clone, Copy)]
+enum GroupRule {
+    RequiresOneOf,
+    RequiresAnyOf,
+    MutuallyExclusive,
+}
+
+impl GroupRule {
+    fn is_satisfied(self, present: usize) -> bool {
+        match self {
+            GroupRule::RequiresOneOf => present == 1,
+            GroupRule::RequiresAnyOf => present >= 1,
+            GroupRule::MutuallyExclusive => present <= 1,
+        }
+    }
+}
+
+struct PropertyGroupValidator {
+    properties: Vec<String>,
+    rule: GroupRule,
+    location: Location,
+}
+
+impl PropertyGroupValidator {
+    fn present<'a>(&'a self, object: &'a Map<String, Value>) -> impl Iterator<Item = &'a str> {
+        self.properties
+            .iter()
+            .filter(|property| object.contains_key(property.as_str()))
+            .map(String::as_str)
+    }
+}
+
+fn quoted(properties: &[impl AsRef<str>]) -> String {
+    let mut result = String::new();
+    for (idx, property) in properties.iter().enumerate() {
+        if idx > 0 {
+            result.push_str(", ");
+        }
+        result.push('\'');
+        result.push_str(property.as_ref());
+        result.push('\'');
+    }
+    result
+}
+
+impl Keyword for PropertyGroupValidator {
+    fn validate<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+    ) -> Result<(), ValidationError<'i>> {
+        let Value::Object(object) = instance else {
+            return Ok(());
+        };
+        let present: Vec<_> = self.present(object).collect();
+        if self.rule.is_satisfied(present.len()) {
+            return Ok(());
+        }
+        let group = quoted(&self.properties);
+        let message = match (self.rule, present.is_empty()) {
+            (GroupRule::RequiresOneOf | GroupRule::RequiresAnyOf, true) => {
+                let qualifier = if matches!(self.rule, GroupRule::RequiresOneOf) {
+                    "Exactly"
+                } else {
+                    "At least"
+                };
+                format!("{qualifier} one of {group} is required, but none are present")
+            }
+            (GroupRule::RequiresOneOf, false) => format!(
+                "Exactly one of {group} is required, but {} are present",
+                quoted(&present)
+            ),
+            _ => format!("Properties {} are mutually exclusive", quoted(&present)),
+        };
+        Err(ValidationError::custom(
+            self.location.clone(),
+            location.into(),
+            instance,
+            message,
+        ))
+    }
+
+    fn is_valid(&self, instance: &Value) -> bool {
+        if let Value::Object(object) = instance {
+            self.rule.is_satisfied(self.present(object).count())
+        } else {
+            true
+        }
+    }
+}
+
+fn compile<'a>(
+    schema: &'a Value,
+    location: Location,
+    rule: GroupRule,
+) -> Result<Box<dyn Keyword>, ValidationError<'a>> {
+    let invalid = || {
+        ValidationError::single_type_error(
+            Location::new(),
+            location.clone(),
+            schema,
+            JsonType::Array,
+        )
+    };
+    let Value::Array(items) = schema else {
+        return Err(invalid());
+    };
+    let mut properties = Vec::with_capacity(items.len());
+    for item in items {
+        let Value::String(property) = item else {
+            return Err(ValidationError::single_type_error(
+                Location::new(),
+                location,
+                item,
+                JsonType::String,
+            ));
+        };
+        properties.push(property.clone());
+    }
+    Ok(Box::new(PropertyGroupValidator {
+        properties,
+        rule,
+        location,
+    }))
+}
+
+pub(crate) fn compile_requires_one_of<'a>(
+    _: &'a Map<String, Value>,
+    schema: &'a Value,
+    location: Location,
+) -> Result<Box<dyn Keyword>, ValidationError<'a>> {
+    compile(schema, location, GroupRule::RequiresOneOf)
+}
+
+pub(crate) fn compile_requires_any_of<'a>(
+    _: &'a Map<String, Value>,
+    schema: &'a Value,
+    location: Location,
+) -> Result<Box<dyn Keyword>, ValidationError<'a>> {
+    compile(schema, location, GroupRule::RequiresAnyOf)
+}
+
+pub(crate) fn compile_mutually_exclusive<'a>(
+    _: &'a Map<String, Value>,
+    schema: &'a Value,
+    location: Location,
+) -> Result<Box<dyn Keyword>, ValidationError<'a>> {
+    compile(schema, location, GroupRule::MutuallyExclusive)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    fn validator(schema: &Value) -> crate::Validator {
+        crate::options()
+            .with_property_group_keywords()
+            .build(schema)
+            .expect("Invalid schema")
+    }
+
+    #[test_case(&json!({"x-requiresOneOf": ["a", "b"]}), &json!({"a": 1}))]
+    #[test_case(&json!({"x-requiresOneOf": ["a", "b"]}), &json!(42))]
+    #[test_case(&json!({"x-requiresAnyOf": ["a", "b"]}), &json!({"a": 1, "b": 2}))]
+    #[test_case(&json!({"x-mutuallyExclusive": ["a", "b"]}), &json!({}))]
+    #[test_case(&json!({"x-mutuallyExclusive": ["a", "b"]}), &json!({"b": 1, "c": 2}))]
+    fn valid(schema: &Value, instance: &Value) {
+        let validator = validator(schema);
+        assert!(validator.is_valid(instance));
+        assert!(validator.validate(instance).is_ok());
+    }
+
+    #[test_case(&json!({"x-requiresOneOf": ["a", "b"]}), &json!({}), "Exactly one of 'a', 'b' is required, but none are present")]
+    #[test_case(&json!({"x-requiresOneOf": ["a", "b", "c"]}), &json!({"a": 1, "c": 2}), "Exactly one of 'a', 'b', 'c' is required, but 'a', 'c' are present")]
+    #[test_case(&json!({"x-requiresAnyOf": ["a", "b"]}), &json!({"c": 1}), "At least one of 'a', 'b' is required, but none are present")]
+    #[test_case(&json!({"x-mutuallyExclusive": ["a", "b"]}), &json!({"a": 1, "b": 2}), "Properties 'a', 'b' are mutually exclusive")]
+    fn invalid(schema: &Value, instance: &Value, expected: &str) {
+        let validator = validator(schema);
+        assert!(!validator.is_valid(instance));
+        let error = validator.validate(instance).expect_err("Should fail");
+        assert_eq!(error.to_string(), expected);
+        let keyword = schema
+            .as_object()
+            .and_then(|schema| schema.keys().next())
+            .expect("Keyword is present");
+        assert_eq!(error.schema_path.as_str(), format!("/{keyword}"));
+    }
+
+    #[test_case(&json!({"x-requiresOneOf": "a"}), "\"a\" is not of type \"array\"")]
+    #[test_case(&json!({"x-mutuallyExclusive": ["a", 1]}), "1 is not of type \"string\"")]
+    fn invalid_schema(schema: &Value, expected: &str) {
+        let error = crate::options()
+            .with_property_group_keywords()
+            .build(schema)
+            .expect_err("Should fail");
+        assert_eq!(error.to_string(), expected);
+    }
+
+    #[test]
+    fn disabled_by_default() {
+        let validator =
+            crate::validator_for(&json!({"x-requiresOneOf": ["a", "b"]})).expect("Invalid schema");
+        assert!(validator.is_valid(&json!({})));
+    }
+}
@@ -3,13 +3,16 @@ use crate::{
     error::{no_error, ErrorIterator, ValidationError},
     keywords::CompilationResult,
     node::SchemaNode,
+    options::KeyDecoder,
     paths::{LazyLocation, Location},
     validator::{PartialApplication, Validate},
 };
 use serde_json::{Map, Value};
+use std::sync::Arc;
 
 pub(crate) struct PropertyNamesObjectValidator {
     node: SchemaNode,
+    decoder: Option<Arc<dyn KeyDecoder>>,
 }
 
 impl PropertyNamesObjectValidator {
@@ -18,8 +21,16 @@ impl PropertyNamesObjectValidator {
         let ctx = ctx.new_at_location("propertyNames");
         Ok(Box::new(PropertyNamesObjectValidator {
             node: compiler::compile(&ctx, ctx.as_resource_ref(schema))?,
+            decoder: ctx.config().key_decoder().cloned(),
         }))
     }
+
+    fn decode(&self, key: &str) -> Value {
+        match &self.decoder {
+            Some(decoder) => decoder.decode(key),
+            None => Value::String(key.to_string()),
+        }
+    }
 }
 
 impl Validate for PropertyNamesObjectValidator {
@@ -29,7 +40,7 @@ impl Validate for PropertyNamesObjectValidator {
             let errors: Vec<_> = item
                 .keys()
                 .flat_map(move |key| {
-                    let wrapper = Value::String(key.to_string());
+                    let wrapper = self.decode(key);
                     let errors: Vec<_> = self
                         .node
                         .iter_errors(&wrapper, location)
@@ -38,6 +49,7 @@ impl Validate for PropertyNamesObjectValidator {
                                 error.schema_path.clone(),
                                 location.into(),
                                 instance,
+                                key,
                                 error.to_owned(),
                             )
                         })
@@ -54,7 +66,7 @@ impl Validate for PropertyNamesObjectValidator {
     fn is_valid(&self, instance: &Value) -> bool {
         if let Value::Object(item) = &instance {
             item.keys().all(move |key| {
-                let wrapper = Value::String(key.to_string());
+                let wrapper = self.decode(key);
                 self.node.is_valid(&wrapper)
             })
         } else {
@@ -69,7 +81,7 @@ impl Validate for PropertyNamesObjectValidator {
     ) -> Result<(), ValidationError<'i>> {
         if let Value::Object(item) = &instance {
             for key in item.keys() {
-                let wrapper = Value::String(key.to_string());
+                let wrapper = self.decode(key);
                 match self.node.validate(&wrapper, location) {
                     Ok(_) => {}
                     Err(error) => {
@@ -77,6 +89,7 @@ impl Validate for PropertyNamesObjectValidator {
                             error.schema_path.clone(),
                             location.into(),
                             instance,
+                            key,
                             error.to_owned(),
                         ))
                     }
@@ -90,7 +103,7 @@ impl Validate for PropertyNamesObjectValidator {
         if let Value::Object(item) = instance {
             item.keys()
                 .map(|key| {
-                    let wrapper = Value::String(key.to_string());
+                    let wrapper = self.decode(key);
                     self.node.apply_rooted(&wrapper, location)
                 })
                 .collect()
@@ -154,7 +167,7 @@ pub(crate) fn compile<'a>(
 
 #[cfg(test)]
 mod tests {
-    use crate::tests_util;
+    use crate::{error::ValidationErrorKind, tests_util, KeyDecoder, TypedKeys};
     use serde_json::{json, Value};
     use test_case::test_case;
 
@@ -163,4 +176,73 @@ mod tests {
     fn location(schema: &Value, instance: &Value, expected: &str) {
         tests_util::assert_schema_location(schema, instance, expected)
     }
+
+    #[test]
+    fn error_keeps_property() {
+        let schema = json!({"propertyNames": {"maxLength": 3}});
+        let validator = crate::validator_for(&schema).expect("Invalid schema");
+        let instance = json!({"foo": 1, "long": 2});
+        let error = validator.validate(&instance).expect_err("Should fail");
+        let ValidationErrorKind::PropertyNames { property, error } = &error.kind else {
+            panic!("Unexpected error: {error:?}");
+        };
+        assert_eq!(property, "long");
+        assert_eq!(error.schema_path.as_str(), "/propertyNames/maxLength");
+    }
+
+    #[test_case("42", &json!(42))]
+    #[test_case("-7", &json!(-7))]
+    #[test_case("18446744073709551615", &json!(18_446_744_073_709_551_615_u64))]
+    #[test_case("true", &json!(true))]
+    #[test_case("042", &json!("042"))]
+    #[test_case("+1", &json!("+1"))]
+    #[test_case("1.5", &json!("1.5"))]
+    #[test_case("key", &json!("key"))]
+    fn typed_keys(key: &str, expected: &Value) {
+        assert_eq!(&TypedKeys.decode(key), expected);
+    }
+
+    #[test]
+    fn key_decoder() {
+        let schema = json!({"propertyNames": {"type": "integer", "maximum": 10}});
+        let validator = crate::options()
+            .with_key_decoder(TypedKeys)
+            .build(&schema)
+            .expect("Invalid schema");
+        assert!(validator.is_valid(&json!({"1": true, "10": false})));
+        let instance = json!({"1": true, "11": false, "x": null});
+        let errors: Vec<_> = validator
+            .iter_errors(&instance)
+            .map(|error| match &error.kind {
+                ValidationErrorKind::PropertyNames { property, .. } => {
+                    (property.clone(), error.to_string())
+                }
+                kind => panic!("Unexpected error: {kind:?}"),
+            })
+            .collect();
+        assert_eq!(
+            errors,
+            vec![
+                (
+                    "11".to_string(),
+                    "11 is greater than the maximum of 10".to_string()
+                ),
+                (
+                    "x".to_string(),
+                    r#""x" is not of type "integer""#.to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn key_decoder_closure() {
+        let schema = json!({"propertyNames": {"enum": ["A", "B"]}});
+        let validator = crate::options()
+            .with_key_decoder(|key: &str| Value::String(key.to_uppercase()))
+            .build(&schema)
+            .expect("Invalid schema");
+        assert!(validator.is_valid(&json!({"a": 1, "B": 2})));
+        assert!(!validator.is_valid(&json!({"c": 1})));
+    }
 }
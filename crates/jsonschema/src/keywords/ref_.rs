@@ -2,10 +2,11 @@ use std::{rc::Rc, sync::Arc};
 
 use crate::{
     compiler,
-    error::ErrorIterator,
-    keywords::CompilationResult,
+    error::{no_error, CompilationLimitKind, ErrorIterator},
+    keywords::{helpers::InPlaceGuard, CompilationResult},
     node::SchemaNode,
-    paths::{LazyLocation, Location},
+    paths::{LazyLocation, Location, LocationSegment},
+    sync::Shared,
     types::JsonType,
     validator::{PartialApplication, Validate},
     ValidationError, ValidationOptions,
@@ -29,7 +30,7 @@ impl RefValidator {
     ) -> Option<CompilationResult<'a>> {
         let location = ctx.location().join(keyword);
         Some(
-            if let Some((base_uri, scopes, resource)) = {
+            if let Some((base_uri, scopes, resource, origin)) = {
                 match ctx.lookup_maybe_recursive(reference, is_recursive) {
                     Ok(resolved) => resolved,
                     Err(error) => return Some(Err(error)),
@@ -43,10 +44,17 @@ impl RefValidator {
                         }
                     }
                 }
+                let target = match ctx.resolve_reference(reference) {
+                    Ok(target) => target,
+                    Err(error) => return Some(Err(error.into())),
+                };
                 Ok(Box::new(RefValidator::Lazy(LazyRefValidator {
                     resource,
-                    config: Arc::clone(ctx.config()),
-                    registry: Arc::clone(&ctx.registry),
+                    origin,
+                    target,
+                    config: Shared::clone(ctx.config()),
+                    build: Shared::clone(ctx.build()),
+                    registry: Shared::clone(&ctx.registry),
                     base_uri,
                     scopes,
                     location,
@@ -55,18 +63,45 @@ impl RefValidator {
                     inner: OnceCell::default(),
                 })))
             } else {
+                let target = match ctx.resolve_reference(reference) {
+                    Ok(target) => target,
+                    Err(error) => return Some(Err(error.into())),
+                };
+                // A reference to the subschema containing it adds nothing to its other keywords
+                if ctx.is_current_subschema(&target) {
+                    return None;
+                }
+                let _guard = match ctx.enter_reference(Arc::clone(&target)) {
+                    Ok(guard) => guard,
+                    Err(cycle) => {
+                        let reference = Value::String(reference.to_string());
+                        return Some(Err(ValidationError::compilation_limit_exceeded(
+                            Location::new().join(keyword),
+                            ctx.location().clone(),
+                            &reference,
+                            CompilationLimitKind::ReferenceDepth,
+                            ctx.config().compilation_limits().reference_depth(),
+                            cycle,
+                        )
+                        .to_owned()));
+                    }
+                };
                 let (contents, resolver, draft) = match ctx.lookup(reference) {
                     Ok(resolved) => resolved.into_inner(),
-                    Err(error) => return Some(Err(error.into())),
+                    Err(error) => return Some(Err(error)),
                 };
-                let vocabularies = ctx.registry.find_vocabularies(draft, contents);
+                let vocabularies =
+                    match ctx
+                        .config()
+                        .vocabularies_for(&ctx.registry, draft, contents)
+                    {
+                        Ok(vocabularies) => vocabularies,
+                        Err(error) => return Some(Err(error.into())),
+                    };
                 let resource_ref = draft.create_resource_ref(contents);
-                let ctx = ctx.with_resolver_and_draft(
-                    resolver,
-                    resource_ref.draft(),
-                    vocabularies,
-                    location,
-                );
+                let ctx = ctx
+                    .with_resolver_and_draft(resolver, resource_ref.draft(), vocabularies, location)
+                    .with_reference(target);
                 let inner = match compiler::compile_with(&ctx, resource_ref)
                     .map_err(|err| err.to_owned())
                 {
@@ -88,10 +123,15 @@ impl RefValidator {
 /// lazy evaluation won't be needed.
 pub(crate) struct LazyRefValidator {
     resource: Resource,
-    config: Arc<ValidationOptions>,
-    registry: Arc<Registry>,
+    config: Shared<ValidationOptions>,
+    build: Shared<compiler::BuildState>,
+    registry: Shared<Registry>,
     scopes: List<Uri<String>>,
     base_uri: Arc<Uri<String>>,
+    /// The absolute URI of the reference, identifying its subschemas in profiles.
+    target: Arc<Uri<String>>,
+    /// The referenced subschema, see [`InPlaceGuard::key`].
+    origin: usize,
     vocabularies: VocabularySet,
     location: Location,
     draft: Draft,
@@ -111,8 +151,11 @@ impl LazyRefValidator {
         };
         Ok(Box::new(LazyRefValidator {
             resource,
-            config: Arc::clone(ctx.config()),
-            registry: Arc::clone(&ctx.registry),
+            origin: InPlaceGuard::key(resolved.contents()),
+            config: Shared::clone(ctx.config()),
+            build: Shared::clone(ctx.build()),
+            registry: Shared::clone(&ctx.registry),
+            target: Arc::clone(&base_uri),
             base_uri,
             scopes,
             vocabularies: ctx.vocabularies().clone(),
@@ -121,47 +164,90 @@ impl LazyRefValidator {
             inner: OnceCell::default(),
         }))
     }
-    fn lazy_compile(&self) -> &SchemaNode {
-        self.inner.get_or_init(|| {
+    fn lazy_compile(&self) -> Result<&SchemaNode, ValidationError<'static>> {
+        self.inner.get_or_try_init(|| {
             let resolver = self
                 .registry
                 .resolver_from_raw_parts(self.base_uri.clone(), self.scopes.clone());
 
             let ctx = compiler::Context::new(
-                Arc::clone(&self.config),
-                Arc::clone(&self.registry),
+                Shared::clone(&self.config),
+                Shared::clone(&self.build),
+                Shared::clone(&self.registry),
                 Rc::new(resolver),
                 self.vocabularies.clone(),
                 self.draft,
                 self.location.clone(),
-            );
-            // INVARIANT: This schema was already used during compilation before detecting a
-            // reference cycle that lead to building this validator.
-            compiler::compile(&ctx, self.resource.as_ref()).expect("Invalid schema")
+            )
+            .with_reference(Arc::clone(&self.target));
+            compiler::compile(&ctx, self.resource.as_ref()).map_err(ValidationError::to_owned)
         })
     }
 }
 
 impl Validate for LazyRefValidator {
     fn is_valid(&self, instance: &Value) -> bool {
-        self.lazy_compile().is_valid(instance)
+        let Some(_guard) = InPlaceGuard::enter(instance, self.origin) else {
+            return true;
+        };
+        self.lazy_compile()
+            .is_ok_and(|node| node.is_valid(instance))
     }
     fn validate<'i>(
         &self,
         instance: &'i Value,
         location: &LazyLocation,
     ) -> Result<(), ValidationError<'i>> {
-        self.lazy_compile().validate(instance, location)
+        let Some(_guard) = InPlaceGuard::enter(instance, self.origin) else {
+            return Ok(());
+        };
+        self.lazy_compile()?.validate(instance, location)
     }
     fn iter_errors<'i>(&self, instance: &'i Value, location: &LazyLocation) -> ErrorIterator<'i> {
-        self.lazy_compile().iter_errors(instance, location)
+        let Some(_guard) = InPlaceGuard::enter(instance, self.origin) else {
+            return no_error();
+        };
+        match self.lazy_compile() {
+            Ok(node) => {
+                // Errors are collected while the guard is alive
+                let errors: Vec<_> = node.iter_errors(instance, location).collect();
+                Box::new(errors.into_iter())
+            }
+            Err(error) => Box::new(std::iter::once(error)),
+        }
     }
     fn apply<'a>(&'a self, instance: &Value, location: &LazyLocation) -> PartialApplication<'a> {
-        self.lazy_compile().apply(instance, location)
+        let Some(_guard) = InPlaceGuard::enter(instance, self.origin) else {
+            return PartialApplication::valid_empty();
+        };
+        match self.lazy_compile() {
+            Ok(node) => node.apply(instance, location),
+            Err(error) => PartialApplication::invalid_empty(vec![error.into()]),
+        }
     }
 }
 
 impl Validate for RefValidator {
+    fn subschemas_at<'s>(
+        &'s self,
+        segment: LocationSegment<'_>,
+        visit: &mut dyn FnMut(&'s SchemaNode),
+    ) {
+        match self {
+            RefValidator::Default { inner } => inner.subschemas_at(segment, visit),
+            // Recursive references may cycle on the same instance location
+            RefValidator::Lazy(_) => {}
+        }
+    }
+
+    fn matching_subschemas<'s>(&'s self, instance: &Value, visit: &mut dyn FnMut(&'s SchemaNode)) {
+        match self {
+            RefValidator::Default { inner } if inner.is_valid(instance) => visit(inner),
+            // Recursive references may cycle on the same instance location
+            _ => {}
+        }
+    }
+
     fn is_valid(&self, instance: &Value) -> bool {
         match self {
             RefValidator::Default { inner } => inner.is_valid(instance),
@@ -213,6 +299,9 @@ pub(crate) fn compile_impl<'a>(
         .and_then(Value::as_bool)
         .unwrap_or_default();
     if let Some(reference) = schema.as_str() {
+        if let Err(error) = ctx.mark_external_reference(reference) {
+            return Some(Err(error.into()));
+        }
         RefValidator::compile(ctx, reference, is_recursive, keyword)
     } else {
         Some(Err(invalid_reference(ctx, schema)))
@@ -253,7 +342,10 @@ pub(crate) fn compile_recursive_ref<'a>(
 
 #[cfg(test)]
 mod tests {
-    use crate::tests_util;
+    use crate::{
+        error::{CompilationLimitKind, ValidationErrorKind},
+        tests_util,
+    };
     use ahash::HashMap;
     use referencing::{Retrieve, Uri};
     use serde_json::{json, Value};
@@ -608,6 +700,19 @@ mod tests {
         assert!(validator.is_valid(&json!(42)));
     }
 
+    #[test]
+    fn test_indirect_infinite_loop() {
+        let schema = json!({
+            "$defs": {
+                "a": {"$ref": "#/$defs/b"},
+                "b": {"anyOf": [{"$ref": "#/$defs/a"}], "type": "integer"}
+            },
+            "$ref": "#/$defs/a"
+        });
+        tests_util::is_valid(&schema, &json!(42));
+        tests_util::is_not_valid(&schema, &json!("a"));
+    }
+
     #[test]
     fn test_nested_external_reference() {
         let schema = json!({
@@ -704,4 +809,94 @@ mod tests {
         let error = crate::validator_for(&schema).expect_err("Should fail");
         assert_eq!(error.to_string(), "Resource './virtualNetwork.json' is not present in a registry and retrieving it failed: No base URI is available");
     }
+
+    #[test]
+    fn unresolvable_reference_names_followed_references() {
+        let error = crate::options()
+            .with_resource(
+                "https://example.com/a.json",
+                crate::Resource::from_contents(
+                    json!({"$defs": {"b": {"items": {"$ref": "#/$defs/missing"}}}}),
+                )
+                .expect("Invalid resource"),
+            )
+            .build(&json!({"$ref": "https://example.com/a.json#/$defs/b"}))
+            .expect_err("Should fail");
+        assert_eq!(error.kind.name(), "referencing");
+        assert_eq!(
+            error.to_string(),
+            "Pointer '/$defs/missing' does not exist (via json-schema:/// → https://example.com/a.json#/$defs/b)"
+        );
+    }
+
+    #[test]
+    fn in_place_reference_cycle() {
+        // References next to `$recursiveAnchor` are not compiled lazily
+        let schema = json!({
+            "$schema": "https://json-schema.org/draft/2019-09/schema",
+            "$recursiveAnchor": true,
+            "$ref": "#/$defs/a",
+            "$defs": {
+                "a": {"$recursiveAnchor": true, "$ref": "#"}
+            }
+        });
+        let error = crate::validator_for(&schema).expect_err("Should fail");
+        assert_eq!(
+            error.to_string(),
+            "Reference cycle exceeds the compilation depth limit of 64: json-schema:///#/$defs/a -> json-schema:///# -> json-schema:///#/$defs/a"
+        );
+        let ValidationErrorKind::CompilationLimitExceeded { kind, limit, cycle } = &error.kind
+        else {
+            panic!("Unexpected error: {error:?}");
+        };
+        assert_eq!(*kind, CompilationLimitKind::ReferenceDepth);
+        assert_eq!(*limit, 64);
+        assert_eq!(
+            cycle,
+            &[
+                "json-schema:///#/$defs/a",
+                "json-schema:///#",
+                "json-schema:///#/$defs/a"
+            ]
+        );
+
+        let error = crate::options()
+            .with_compilation_limits(crate::CompilationLimits::new().max_reference_depth(3))
+            .build(&schema)
+            .expect_err("Should fail");
+        assert!(error
+            .to_string()
+            .starts_with("Reference cycle exceeds the compilation depth limit of 3"));
+    }
+
+    #[test]
+    fn reference_depth_without_cycle() {
+        let schema = json!({
+            "$ref": "#/$defs/a",
+            "$defs": {
+                "a": {"$ref": "#/$defs/b"},
+                "b": {"$ref": "#/$defs/c"},
+                "c": {"type": "integer"}
+            }
+        });
+        let limits = crate::CompilationLimits::new().max_reference_depth(2);
+        let error = crate::options()
+            .with_compilation_limits(limits)
+            .build(&schema)
+            .expect_err("Should fail");
+        assert_eq!(
+            error.to_string(),
+            "References are nested deeper than the compilation depth limit of 2"
+        );
+        assert!(matches!(
+            &error.kind,
+            ValidationErrorKind::CompilationLimitExceeded { cycle, .. } if cycle.is_empty()
+        ));
+        let limits = crate::CompilationLimits::new().max_reference_depth(3);
+        let validator = crate::options()
+            .with_compilation_limits(limits)
+            .build(&schema)
+            .expect("Valid schema");
+        assert!(!validator.is_valid(&json!("a")));
+    }
 }
@@ -11,11 +11,16 @@ use serde_json::{Map, Value};
 pub(crate) struct RequiredValidator {
     required: Vec<String>,
     location: Location,
+    report_missing: bool,
 }
 
 impl RequiredValidator {
     #[inline]
-    pub(crate) fn compile(items: &[Value], location: Location) -> CompilationResult {
+    pub(crate) fn compile(
+        items: &[Value],
+        location: Location,
+        report_missing: bool,
+    ) -> CompilationResult {
         let mut required = Vec::with_capacity(items.len());
         for item in items {
             match item {
@@ -30,7 +35,11 @@ impl RequiredValidator {
                 }
             }
         }
-        Ok(Box::new(RequiredValidator { required, location }))
+        Ok(Box::new(RequiredValidator {
+            required,
+            location,
+            report_missing,
+        }))
     }
 }
 
@@ -68,6 +77,7 @@ impl Validate for RequiredValidator {
     fn iter_errors<'i>(&self, instance: &'i Value, location: &LazyLocation) -> ErrorIterator<'i> {
         if let Value::Object(item) = instance {
             let mut errors = vec![];
+            let mut missing = vec![];
             for property_name in &self.required {
                 if !item.contains_key(property_name) {
                     errors.push(ValidationError::required(
@@ -77,8 +87,17 @@ impl Validate for RequiredValidator {
                         // Value enum is needed for proper string escaping
                         Value::String(property_name.clone()),
                     ));
+                    missing.push(property_name.clone());
                 }
             }
+            if self.report_missing && !missing.is_empty() {
+                errors.push(ValidationError::missing_properties(
+                    self.location.clone(),
+                    location.into(),
+                    instance,
+                    missing,
+                ));
+            }
             if !errors.is_empty() {
                 return Box::new(errors.into_iter());
             }
@@ -90,14 +109,20 @@ impl Validate for RequiredValidator {
 pub(crate) struct SingleItemRequiredValidator {
     value: String,
     location: Location,
+    report_missing: bool,
 }
 
 impl SingleItemRequiredValidator {
     #[inline]
-    pub(crate) fn compile(value: &str, location: Location) -> CompilationResult {
+    pub(crate) fn compile(
+        value: &str,
+        location: Location,
+        report_missing: bool,
+    ) -> CompilationResult {
         Ok(Box::new(SingleItemRequiredValidator {
             value: value.to_string(),
             location,
+            report_missing,
         }))
     }
 }
@@ -120,6 +145,25 @@ impl Validate for SingleItemRequiredValidator {
         Ok(())
     }
 
+    fn iter_errors<'i>(&self, instance: &'i Value, location: &LazyLocation) -> ErrorIterator<'i> {
+        match self.validate(instance, location) {
+            Ok(()) => no_error(),
+            Err(error) if self.report_missing => Box::new(
+                [
+                    error,
+                    ValidationError::missing_properties(
+                        self.location.clone(),
+                        location.into(),
+                        instance,
+                        vec![self.value.clone()],
+                    ),
+                ]
+                .into_iter(),
+            ),
+            Err(error) => Box::new(std::iter::once(error)),
+        }
+    }
+
     fn is_valid(&self, instance: &Value) -> bool {
         if let Value::Object(item) = instance {
             item.contains_key(&self.value)
@@ -136,18 +180,24 @@ pub(crate) fn compile<'a>(
     schema: &'a Value,
 ) -> Option<CompilationResult<'a>> {
     let location = ctx.location().join("required");
-    compile_with_path(schema, location)
+    compile_required(schema, location, ctx.config().reports_missing_properties())
 }
 
-#[inline]
-pub(crate) fn compile_with_path(schema: &Value, location: Location) -> Option<CompilationResult> {
-    // IMPORTANT: If this function will ever return `None`, adjust `dependencies.rs` accordingly
+fn compile_required(
+    schema: &Value,
+    location: Location,
+    report_missing: bool,
+) -> Option<CompilationResult> {
     match schema {
         Value::Array(items) => {
             if items.len() == 1 {
                 let item = &items[0];
                 if let Value::String(item) = item {
-                    Some(SingleItemRequiredValidator::compile(item, location))
+                    Some(SingleItemRequiredValidator::compile(
+                        item,
+                        location,
+                        report_missing,
+                    ))
                 } else {
                     Some(Err(ValidationError::single_type_error(
                         Location::new(),
@@ -157,7 +207,7 @@ pub(crate) fn compile_with_path(schema: &Value, location: Location) -> Option<Co
                     )))
                 }
             } else {
-                Some(RequiredValidator::compile(items, location))
+                Some(RequiredValidator::compile(items, location, report_missing))
             }
         }
         _ => Some(Err(ValidationError::single_type_error(
@@ -180,4 +230,61 @@ mod tests {
     fn location(schema: &Value, instance: &Value, expected: &str) {
         tests_util::assert_schema_location(schema, instance, expected)
     }
+
+    #[test_case(&json!({"required": ["a", "b", "c"]}), &json!({"b": 1}), &["a", "c"]; "multiple")]
+    #[test_case(&json!({"required": ["a"]}), &json!({}), &["a"]; "single")]
+    #[test_case(&json!({"properties": {"x": {"required": ["a\"b"]}}}), &json!({"x": {}}), &["a\"b"]; "nested")]
+    fn missing_properties(schema: &Value, instance: &Value, expected: &[&str]) {
+        let validator = crate::options()
+            .should_report_missing_properties(true)
+            .build(schema)
+            .expect("Invalid schema");
+        let errors: Vec<_> = validator.iter_errors(instance).collect();
+        assert_eq!(errors.len(), expected.len() + 1);
+        let summary = errors.last().expect("Summary error");
+        assert_eq!(summary.kind.name(), "missingProperties");
+        assert_eq!(summary.kind.parameters()["properties"], json!(expected));
+        assert_eq!(summary.schema_path.as_str(), errors[0].schema_path.as_str());
+        assert_eq!(
+            summary.instance_path.as_str(),
+            errors[0].instance_path.as_str()
+        );
+        let output = validator.apply(instance).basic();
+        assert!(!output.is_valid());
+        // Fail-fast validation reports the first missing property only
+        let error = validator.validate(instance).expect_err("Should fail");
+        assert_eq!(error.kind.name(), "required");
+    }
+
+    #[test]
+    fn missing_properties_message() {
+        let validator = crate::options()
+            .should_report_missing_properties(true)
+            .build(&json!({"required": ["a", "b"]}))
+            .expect("Invalid schema");
+        let messages: Vec<_> = validator
+            .iter_errors(&json!({}))
+            .map(|error| error.to_string())
+            .collect();
+        assert_eq!(messages[2], r#"Missing required properties: "a", "b""#);
+        let validator = crate::options()
+            .should_report_missing_properties(true)
+            .build(&json!({"required": ["a"]}))
+            .expect("Invalid schema");
+        let messages: Vec<_> = validator
+            .iter_errors(&json!({}))
+            .map(|error| error.to_string())
+            .collect();
+        assert_eq!(messages[1], r#"Missing required property: "a""#);
+    }
+
+    #[test_case(&json!({"required": ["a", "b"]}), "required"; "multiple")]
+    #[test_case(&json!({"required": ["a"]}), "required"; "single")]
+    #[test_case(&json!({"dependentRequired": {"x": ["a", "b"]}}), "dependentRequired"; "dependent required")]
+    fn no_summary_by_default(schema: &Value, expected: &str) {
+        let validator = crate::validator_for(schema).expect("Invalid schema");
+        assert!(validator
+            .iter_errors(&json!({"x": 1}))
+            .all(|error| error.kind.name() == expected));
+    }
 }
@@ -5,27 +5,33 @@ use crate::{
     compiler,
     node::SchemaNode,
     paths::{LazyLocation, Location},
+    sync::MaybeSendSync,
     validator::Validate,
     ValidationError,
 };
 
-use super::CompilationResult;
+use super::{helpers::InPlaceGuard, CompilationResult};
 
-pub(crate) trait ItemsFilter: Send + Sync + Sized + 'static {
+pub(crate) trait ItemsFilter: MaybeSendSync + Sized + 'static {
     fn new<'a>(
         ctx: &'a compiler::Context<'a>,
         parent: &'a Map<String, Value>,
     ) -> Result<Self, ValidationError<'a>>;
     fn unevaluated(&self) -> Option<&SchemaNode>;
 
-    fn is_valid(&self, instance: &Value) -> bool {
+    fn is_valid(&self, instance: &Value, location: &LazyLocation) -> bool {
         self.unevaluated()
             .as_ref()
-            .map(|u| u.is_valid(instance))
+            .map(|u| u.is_valid_at(instance, location))
             .unwrap_or(false)
     }
 
-    fn mark_evaluated_indexes(&self, instance: &Value, indexes: &mut Vec<bool>);
+    fn mark_evaluated_indexes(
+        &self,
+        instance: &Value,
+        indexes: &mut Vec<bool>,
+        location: &LazyLocation,
+    );
 }
 
 pub(crate) struct UnevaluatedItemsValidator<F: ItemsFilter> {
@@ -50,11 +56,13 @@ impl<F: ItemsFilter> Validate for UnevaluatedItemsValidator<F> {
     fn is_valid(&self, instance: &Value) -> bool {
         if let Value::Array(items) = instance {
             // NOTE: It could be a packed bitset instead
+            let location = LazyLocation::new();
             let mut indexes = vec![false; items.len()];
-            self.filter.mark_evaluated_indexes(instance, &mut indexes);
+            self.filter
+                .mark_evaluated_indexes(instance, &mut indexes, &location);
 
             for (item, is_evaluated) in items.iter().zip(indexes) {
-                if !is_evaluated && !self.filter.is_valid(item) {
+                if !is_evaluated && !self.filter.is_valid(item, &location) {
                     return false;
                 }
             }
@@ -70,10 +78,11 @@ impl<F: ItemsFilter> Validate for UnevaluatedItemsValidator<F> {
         if let Value::Array(items) = instance {
             // NOTE: It could be a packed bitset instead
             let mut indexes = vec![false; items.len()];
-            self.filter.mark_evaluated_indexes(instance, &mut indexes);
+            self.filter
+                .mark_evaluated_indexes(instance, &mut indexes, location);
             let mut unevaluated = vec![];
             for (item, is_evaluated) in items.iter().zip(indexes) {
-                if !is_evaluated && !self.filter.is_valid(item) {
+                if !is_evaluated && !self.filter.is_valid(item, location) {
                     unevaluated.push(item.to_string());
                 }
             }
@@ -107,12 +116,15 @@ impl ItemsFilter for Draft2019ItemsFilter {
         ctx: &'a compiler::Context<'_>,
         parent: &'a Map<String, Value>,
     ) -> Result<Self, ValidationError<'a>> {
+        let _guard = InPlaceGuard::new(parent);
         let mut ref_ = None;
 
         if let Some(Value::String(reference)) = parent.get("$ref") {
             let resolved = ctx.lookup(reference)?;
             if let Value::Object(subschema) = resolved.contents() {
-                ref_ = Some(Box::new(Self::new(ctx, subschema)?));
+                if !InPlaceGuard::is_expanding(subschema) {
+                    ref_ = Some(Box::new(Self::new(ctx, subschema)?));
+                }
             }
         }
         let mut recursive_ref = None;
@@ -120,7 +132,9 @@ impl ItemsFilter for Draft2019ItemsFilter {
         if parent.contains_key("$recursiveRef") {
             let resolved = ctx.lookup_recursive_reference()?;
             if let Value::Object(subschema) = resolved.contents() {
-                recursive_ref = Some(Box::new(Self::new(ctx, subschema)?));
+                if !InPlaceGuard::is_expanding(subschema) {
+                    recursive_ref = Some(Box::new(Self::new(ctx, subschema)?));
+                }
             }
         }
 
@@ -167,13 +181,12 @@ impl ItemsFilter for Draft2019ItemsFilter {
         };
         let mut items = None;
         if let Some(subschema) = parent.get("items") {
-            let limit = if parent.contains_key("additionalItems") || subschema.is_object() {
-                usize::MAX
-            } else {
-                subschema
-                    .as_array()
-                    .expect("Items are either an object or an array")
-                    .len()
+            let limit = match subschema {
+                Value::Array(subschemas) if !parent.contains_key("additionalItems") => {
+                    subschemas.len()
+                }
+                // `items` is a single schema that applies to all items, including boolean ones
+                _ => usize::MAX,
             };
             items = Some(limit);
         };
@@ -193,7 +206,12 @@ impl ItemsFilter for Draft2019ItemsFilter {
     fn unevaluated(&self) -> Option<&SchemaNode> {
         self.unevaluated.as_ref()
     }
-    fn mark_evaluated_indexes(&self, instance: &Value, indexes: &mut Vec<bool>) {
+    fn mark_evaluated_indexes(
+        &self,
+        instance: &Value,
+        indexes: &mut Vec<bool>,
+        location: &LazyLocation,
+    ) {
         if let Some(limit) = self.items {
             for idx in indexes.iter_mut().take(limit) {
                 *idx = true;
@@ -201,15 +219,15 @@ impl ItemsFilter for Draft2019ItemsFilter {
         }
 
         if let Some(ref_) = &self.ref_ {
-            ref_.mark_evaluated_indexes(instance, indexes);
+            ref_.mark_evaluated_indexes(instance, indexes, location);
         }
 
         if let Some(recursive_ref) = &self.recursive_ref {
-            recursive_ref.mark_evaluated_indexes(instance, indexes);
+            recursive_ref.mark_evaluated_indexes(instance, indexes, location);
         }
 
         if let Some(conditional) = &self.conditional {
-            conditional.mark_evaluated_indexes(instance, indexes);
+            conditional.mark_evaluated_indexes(instance, indexes, location);
         }
         if let Value::Array(items) = instance {
             for (item, is_evaluated) in items.iter().zip(indexes.iter_mut()) {
@@ -217,13 +235,13 @@ impl ItemsFilter for Draft2019ItemsFilter {
                     continue;
                 }
                 if let Some(validator) = &self.contains {
-                    if validator.is_valid(item) {
+                    if validator.is_valid_at(item, location) {
                         *is_evaluated = true;
                         continue;
                     }
                 }
                 if let Some(validator) = &self.unevaluated {
-                    if validator.is_valid(item) {
+                    if validator.is_valid_at(item, location) {
                         *is_evaluated = true;
                     }
                 }
@@ -234,9 +252,9 @@ impl ItemsFilter for Draft2019ItemsFilter {
             if combinator
                 .subschemas
                 .iter()
-                .all(|(v, _)| v.is_valid(instance))
+                .all(|(v, _)| v.is_valid_at(instance, location))
             {
-                combinator.mark_evaluated_indexes(instance, indexes);
+                combinator.mark_evaluated_indexes(instance, indexes, location);
             }
         }
 
@@ -244,9 +262,9 @@ impl ItemsFilter for Draft2019ItemsFilter {
             if combinator
                 .subschemas
                 .iter()
-                .all(|(v, _)| v.is_valid(instance))
+                .all(|(v, _)| v.is_valid_at(instance, location))
             {
-                combinator.mark_evaluated_indexes(instance, indexes);
+                combinator.mark_evaluated_indexes(instance, indexes, location);
             }
         }
 
@@ -254,11 +272,11 @@ impl ItemsFilter for Draft2019ItemsFilter {
             if combinator
                 .subschemas
                 .iter()
-                .filter(|(v, _)| v.is_valid(instance))
+                .filter(|(v, _)| v.is_valid_at(instance, location))
                 .count()
                 == 1
             {
-                combinator.mark_evaluated_indexes(instance, indexes);
+                combinator.mark_evaluated_indexes(instance, indexes, location);
             }
         }
     }
@@ -282,12 +300,15 @@ impl ItemsFilter for DefaultItemsFilter {
         ctx: &'a compiler::Context<'a>,
         parent: &'a Map<String, Value>,
     ) -> Result<DefaultItemsFilter, ValidationError<'a>> {
+        let _guard = InPlaceGuard::new(parent);
         let mut ref_ = None;
 
         if let Some(Value::String(reference)) = parent.get("$ref") {
             let resolved = ctx.lookup(reference)?;
             if let Value::Object(subschema) = resolved.contents() {
-                ref_ = Some(Box::new(Self::new(ctx, subschema)?));
+                if !InPlaceGuard::is_expanding(subschema) {
+                    ref_ = Some(Box::new(Self::new(ctx, subschema)?));
+                }
             }
         }
 
@@ -296,7 +317,9 @@ impl ItemsFilter for DefaultItemsFilter {
         if let Some(Value::String(reference)) = parent.get("$dynamicRef") {
             let resolved = ctx.lookup(reference)?;
             if let Value::Object(subschema) = resolved.contents() {
-                dynamic_ref = Some(Box::new(Self::new(ctx, subschema)?));
+                if !InPlaceGuard::is_expanding(subschema) {
+                    dynamic_ref = Some(Box::new(Self::new(ctx, subschema)?));
+                }
             }
         }
 
@@ -365,7 +388,12 @@ impl ItemsFilter for DefaultItemsFilter {
         self.unevaluated.as_ref()
     }
 
-    fn mark_evaluated_indexes(&self, instance: &Value, indexes: &mut Vec<bool>) {
+    fn mark_evaluated_indexes(
+        &self,
+        instance: &Value,
+        indexes: &mut Vec<bool>,
+        location: &LazyLocation,
+    ) {
         if self.items {
             for idx in indexes {
                 *idx = true;
@@ -374,11 +402,11 @@ impl ItemsFilter for DefaultItemsFilter {
         }
 
         if let Some(ref_) = &self.ref_ {
-            ref_.mark_evaluated_indexes(instance, indexes);
+            ref_.mark_evaluated_indexes(instance, indexes, location);
         }
 
         if let Some(dynamic_ref) = &self.dynamic_ref {
-            dynamic_ref.mark_evaluated_indexes(instance, indexes);
+            dynamic_ref.mark_evaluated_indexes(instance, indexes, location);
         }
 
         if let Some(limit) = self.prefix_items {
@@ -387,7 +415,7 @@ impl ItemsFilter for DefaultItemsFilter {
             }
         }
         if let Some(conditional) = &self.conditional {
-            conditional.mark_evaluated_indexes(instance, indexes);
+            conditional.mark_evaluated_indexes(instance, indexes, location);
         }
         if let Value::Array(items) = instance {
             for (item, is_evaluated) in items.iter().zip(indexes.iter_mut()) {
@@ -395,13 +423,13 @@ impl ItemsFilter for DefaultItemsFilter {
                     continue;
                 }
                 if let Some(validator) = &self.contains {
-                    if validator.is_valid(item) {
+                    if validator.is_valid_at(item, location) {
                         *is_evaluated = true;
                         continue;
                     }
                 }
                 if let Some(validator) = &self.unevaluated {
-                    if validator.is_valid(item) {
+                    if validator.is_valid_at(item, location) {
                         *is_evaluated = true;
                     }
                 }
@@ -412,9 +440,9 @@ impl ItemsFilter for DefaultItemsFilter {
             if combinator
                 .subschemas
                 .iter()
-                .all(|(v, _)| v.is_valid(instance))
+                .all(|(v, _)| v.is_valid_at(instance, location))
             {
-                combinator.mark_evaluated_indexes(instance, indexes);
+                combinator.mark_evaluated_indexes(instance, indexes, location);
             }
         }
 
@@ -422,9 +450,9 @@ impl ItemsFilter for DefaultItemsFilter {
             if combinator
                 .subschemas
                 .iter()
-                .all(|(v, _)| v.is_valid(instance))
+                .all(|(v, _)| v.is_valid_at(instance, location))
             {
-                combinator.mark_evaluated_indexes(instance, indexes);
+                combinator.mark_evaluated_indexes(instance, indexes, location);
             }
         }
 
@@ -432,11 +460,11 @@ impl ItemsFilter for DefaultItemsFilter {
             if combinator
                 .subschemas
                 .iter()
-                .filter(|(v, _)| v.is_valid(instance))
+                .filter(|(v, _)| v.is_valid_at(instance, location))
                 .count()
                 == 1
             {
-                combinator.mark_evaluated_indexes(instance, indexes);
+                combinator.mark_evaluated_indexes(instance, indexes, location);
             }
         }
     }
@@ -447,9 +475,14 @@ struct CombinatorFilter<F> {
 }
 
 impl<F: ItemsFilter> CombinatorFilter<F> {
-    fn mark_evaluated_indexes(&self, instance: &Value, indexes: &mut Vec<bool>) {
+    fn mark_evaluated_indexes(
+        &self,
+        instance: &Value,
+        indexes: &mut Vec<bool>,
+        location: &LazyLocation,
+    ) {
         for (_, subschema) in &self.subschemas {
-            subschema.mark_evaluated_indexes(instance, indexes);
+            subschema.mark_evaluated_indexes(instance, indexes, location);
         }
     }
 }
@@ -480,14 +513,19 @@ struct ConditionalFilter<F> {
 }
 
 impl<F: ItemsFilter> ConditionalFilter<F> {
-    fn mark_evaluated_indexes(&self, instance: &Value, indexes: &mut Vec<bool>) {
-        if self.condition.is_valid(instance) {
-            self.if_.mark_evaluated_indexes(instance, indexes);
+    fn mark_evaluated_indexes(
+        &self,
+        instance: &Value,
+        indexes: &mut Vec<bool>,
+        location: &LazyLocation,
+    ) {
+        if self.condition.is_valid_at(instance, location) {
+            self.if_.mark_evaluated_indexes(instance, indexes, location);
             if let Some(then_) = &self.then_ {
-                then_.mark_evaluated_indexes(instance, indexes);
+                then_.mark_evaluated_indexes(instance, indexes, location);
             }
         } else if let Some(else_) = &self.else_ {
-            else_.mark_evaluated_indexes(instance, indexes);
+            else_.mark_evaluated_indexes(instance, indexes, location);
         }
     }
 }
@@ -515,7 +553,9 @@ pub(crate) fn compile<'a>(
 
 #[cfg(test)]
 mod tests {
-    use serde_json::json;
+    use crate::{tests_util, Draft};
+    use serde_json::{json, Value};
+    use test_case::test_case;
 
     #[test]
     fn test_unevaluated_items_with_recursion() {
@@ -591,4 +631,19 @@ mod tests {
         assert!(validator.is_valid(&valid_instance));
         assert!(validator.validate(&valid_instance).is_ok());
     }
+
+    #[test_case(Draft::Draft201909, &json!({"$ref": "#", "unevaluatedItems": false}))]
+    #[test_case(Draft::Draft202012, &json!({"$ref": "#", "unevaluatedItems": false}))]
+    #[test_case(Draft::Draft201909, &json!({"$recursiveRef": "#", "unevaluatedItems": false}))]
+    #[test_case(Draft::Draft202012, &json!({"$dynamicRef": "#", "unevaluatedItems": false}))]
+    fn in_place_reference_cycle(draft: Draft, schema: &Value) {
+        tests_util::is_valid_with_draft(draft, schema, &json!([]));
+        tests_util::is_not_valid_with_draft(draft, schema, &json!([1]));
+    }
+
+    #[test]
+    fn boolean_items() {
+        let schema = json!({"items": true, "unevaluatedItems": false});
+        tests_util::is_valid_with_draft(Draft::Draft201909, &schema, &json!([1]));
+    }
 }
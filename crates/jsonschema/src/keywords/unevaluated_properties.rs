@@ -1,7 +1,6 @@
 use std::{rc::Rc, sync::Arc};
 
 use ahash::AHashSet;
-use fancy_regex::Regex;
 use once_cell::sync::OnceCell;
 use referencing::{Draft, List, Registry, Resource, Uri, VocabularySet};
 use serde_json::{Map, Value};
@@ -10,23 +9,25 @@ use crate::{
     compiler, ecma,
     node::SchemaNode,
     paths::{LazyLocation, Location},
+    regex::build_fancy_regex,
+    sync::{MaybeSendSync, Shared},
     validator::Validate,
     ValidationError, ValidationOptions,
 };
 
-use super::CompilationResult;
+use super::{helpers::InPlaceGuard, CompilationResult};
 
-pub(crate) trait PropertiesFilter: Send + Sync + Sized + 'static {
+pub(crate) trait PropertiesFilter: MaybeSendSync + Sized + 'static {
     fn new<'a>(
         ctx: &'a compiler::Context<'a>,
         parent: &'a Map<String, Value>,
     ) -> Result<Self, ValidationError<'a>>;
     fn unevaluated(&self) -> Option<&SchemaNode>;
 
-    fn is_valid(&self, instance: &Value) -> bool {
+    fn is_valid(&self, instance: &Value, location: &LazyLocation) -> bool {
         self.unevaluated()
             .as_ref()
-            .map(|u| u.is_valid(instance))
+            .map(|u| u.is_valid_at(instance, location))
             .unwrap_or(false)
     }
 
@@ -34,7 +35,8 @@ pub(crate) trait PropertiesFilter: Send + Sync + Sized + 'static {
         &self,
         instance: &'i Value,
         properties: &mut AHashSet<&'i String>,
-    );
+        location: &LazyLocation,
+    ) -> Result<(), ValidationError<'static>>;
 }
 
 pub(crate) struct UnevaluatedPropertiesValidator<F: PropertiesFilter> {
@@ -64,11 +66,11 @@ impl<F: PropertiesFilter> Validate for UnevaluatedPropertiesValidator<F> {
         if let Value::Object(properties) = instance {
             let mut evaluated = AHashSet::new();
             self.filter
-                .mark_evaluated_properties(instance, &mut evaluated);
+                .mark_evaluated_properties(instance, &mut evaluated, location)?;
 
             let mut unevaluated = vec![];
             for (property, value) in properties {
-                if !evaluated.contains(property) && !self.filter.is_valid(value) {
+                if !evaluated.contains(property) && !self.filter.is_valid(value, location) {
                     unevaluated.push(property.clone());
                 }
             }
@@ -86,12 +88,18 @@ impl<F: PropertiesFilter> Validate for UnevaluatedPropertiesValidator<F> {
 
     fn is_valid(&self, instance: &Value) -> bool {
         if let Value::Object(properties) = instance {
+            let location = LazyLocation::new();
             let mut evaluated = AHashSet::new();
-            self.filter
-                .mark_evaluated_properties(instance, &mut evaluated);
+            if self
+                .filter
+                .mark_evaluated_properties(instance, &mut evaluated, &location)
+                .is_err()
+            {
+                return false;
+            }
 
             for (property, value) in properties {
-                if !evaluated.contains(property) && !self.filter.is_valid(value) {
+                if !evaluated.contains(property) && !self.filter.is_valid(value, &location) {
                     return false;
                 }
             }
@@ -124,13 +132,17 @@ impl<F: PropertiesFilter> ReferenceFilter<F> {
         &self,
         instance: &'i Value,
         properties: &mut AHashSet<&'i String>,
-    ) {
+        location: &LazyLocation,
+    ) -> Result<(), ValidationError<'static>> {
         match self {
-            ReferenceFilter::Recursive(filter) => filter
-                .get_or_init()
-                .mark_evaluated_properties(instance, properties),
+            ReferenceFilter::Recursive(filter) => {
+                if let Some(filter) = filter.get_or_init()? {
+                    filter.mark_evaluated_properties(instance, properties, location)?;
+                }
+                Ok(())
+            }
             ReferenceFilter::Default(filter) => {
-                filter.mark_evaluated_properties(instance, properties)
+                filter.mark_evaluated_properties(instance, properties, location)
             }
         }
     }
@@ -138,14 +150,17 @@ impl<F: PropertiesFilter> ReferenceFilter<F> {
 
 struct LazyReference<T> {
     resource: Resource,
-    config: Arc<ValidationOptions>,
-    registry: Arc<Registry>,
+    config: Shared<ValidationOptions>,
+    build: Shared<compiler::BuildState>,
+    registry: Shared<Registry>,
     scopes: List<Uri<String>>,
     base_uri: Arc<Uri<String>>,
     vocabularies: VocabularySet,
     location: Location,
     draft: Draft,
-    inner: OnceCell<Box<T>>,
+    /// The referenced subschema, see [`InPlaceGuard::key`].
+    origin: usize,
+    inner: OnceCell<Option<Box<T>>>,
 }
 
 impl<T: PropertiesFilter> LazyReference<T> {
@@ -160,9 +175,11 @@ impl<T: PropertiesFilter> LazyReference<T> {
         }
 
         Ok(LazyReference {
+            origin: resolved.contents().as_object().map_or(0, InPlaceGuard::key),
             resource,
-            config: Arc::clone(ctx.config()),
-            registry: Arc::clone(&ctx.registry),
+            config: Shared::clone(ctx.config()),
+            build: Shared::clone(ctx.build()),
+            registry: Shared::clone(&ctx.registry),
             base_uri,
             scopes,
             vocabularies: ctx.vocabularies().clone(),
@@ -172,32 +189,33 @@ impl<T: PropertiesFilter> LazyReference<T> {
         })
     }
 
-    fn get_or_init(&self) -> &T {
-        self.inner.get_or_init(|| {
-            let resolver = self
-                .registry
-                .resolver_from_raw_parts(self.base_uri.clone(), self.scopes.clone());
-
-            let ctx = compiler::Context::new(
-                Arc::clone(&self.config),
-                Arc::clone(&self.registry),
-                Rc::new(resolver),
-                self.vocabularies.clone(),
-                self.draft,
-                self.location.clone(),
-            );
-
-            Box::new(
-                T::new(
-                    &ctx,
-                    self.resource
-                        .contents()
-                        .as_object()
-                        .expect("Invalid schema"),
-                )
-                .expect("Invalid schema during lazy compilation"),
-            )
-        })
+    /// The filter for the referenced subschema, if it is an object.
+    fn get_or_init(&self) -> Result<Option<&T>, ValidationError<'static>> {
+        self.inner
+            .get_or_try_init(|| {
+                let Some(contents) = self.resource.contents().as_object() else {
+                    return Ok(None);
+                };
+                let resolver = self
+                    .registry
+                    .resolver_from_raw_parts(self.base_uri.clone(), self.scopes.clone());
+
+                let ctx = compiler::Context::new(
+                    Shared::clone(&self.config),
+                    Shared::clone(&self.build),
+                    Shared::clone(&self.registry),
+                    Rc::new(resolver),
+                    self.vocabularies.clone(),
+                    self.draft,
+                    self.location.clone(),
+                );
+
+                let _guard = InPlaceGuard::at(self.origin);
+                T::new(&ctx, contents)
+                    .map(|filter| Some(Box::new(filter)))
+                    .map_err(ValidationError::to_owned)
+            })
+            .map(Option::as_deref)
     }
 }
 
@@ -206,18 +224,28 @@ impl PropertiesFilter for Draft2019PropertiesFilter {
         ctx: &'a compiler::Context<'_>,
         parent: &'a Map<String, Value>,
     ) -> Result<Self, ValidationError<'a>> {
+        let _guard = InPlaceGuard::new(parent);
         let mut ref_ = None;
 
         if let Some(Value::String(reference)) = parent.get("$ref") {
             let resolved = ctx.lookup(reference)?;
             if let Value::Object(subschema) = resolved.contents() {
-                ref_ = Some(Box::new(Self::new(ctx, subschema)?));
+                if !InPlaceGuard::is_expanding(subschema) {
+                    ref_ = Some(Box::new(Self::new(ctx, subschema)?));
+                }
             }
         }
 
         let mut recursive_ref = None;
         if parent.contains_key("$recursiveRef") {
-            recursive_ref = Some(LazyReference::new(ctx)?);
+            let resolved = ctx.lookup_recursive_reference()?;
+            if !resolved
+                .contents()
+                .as_object()
+                .is_some_and(InPlaceGuard::is_expanding)
+            {
+                recursive_ref = Some(LazyReference::new(ctx)?);
+            }
         }
 
         let mut conditional = None;
@@ -269,7 +297,9 @@ impl PropertiesFilter for Draft2019PropertiesFilter {
         if let Some(Value::Object(patterns)) = parent.get("patternProperties") {
             for (pattern, schema) in patterns {
                 pattern_properties.push((
-                    match ecma::to_rust_regex(pattern).map(|pattern| Regex::new(&pattern)) {
+                    match ecma::to_rust_regex(pattern).map(|pattern| {
+                        build_fancy_regex(&pattern, None, None, None, ctx.config().shares_regexes())
+                    }) {
                         Ok(Ok(r)) => r,
                         _ => {
                             return Err(ValidationError::format(
@@ -323,39 +353,40 @@ impl PropertiesFilter for Draft2019PropertiesFilter {
         &self,
         instance: &'i Value,
         properties: &mut AHashSet<&'i String>,
-    ) {
+        location: &LazyLocation,
+    ) -> Result<(), ValidationError<'static>> {
         if let Some(ref_) = &self.ref_ {
-            ref_.mark_evaluated_properties(instance, properties);
+            ref_.mark_evaluated_properties(instance, properties, location)?;
         }
 
         if let Some(recursive_ref) = &self.recursive_ref {
-            recursive_ref
-                .get_or_init()
-                .mark_evaluated_properties(instance, properties);
+            if let Some(recursive_ref) = recursive_ref.get_or_init()? {
+                recursive_ref.mark_evaluated_properties(instance, properties, location)?;
+            }
         }
 
         if let Value::Object(obj) = instance {
             for (property, value) in obj {
                 for (p, node) in &self.properties {
-                    if property == p && node.is_valid(value) {
+                    if property == p && node.is_valid_at(value, location) {
                         properties.insert(property);
                         continue;
                     }
                 }
                 if let Some(additional) = self.additional.as_ref() {
-                    if additional.is_valid(value) {
+                    if additional.is_valid_at(value, location) {
                         properties.insert(property);
                         continue;
                     }
                 }
                 if let Some(unevaluated) = self.unevaluated.as_ref() {
-                    if unevaluated.is_valid(value) {
+                    if unevaluated.is_valid_at(value, location) {
                         properties.insert(property);
                         continue;
                     }
                 }
                 for (pattern, _) in &self.pattern_properties {
-                    if pattern.is_match(property).unwrap() {
+                    if pattern.is_match(property).unwrap_or(false) {
                         properties.insert(property);
                     }
                 }
@@ -364,21 +395,21 @@ impl PropertiesFilter for Draft2019PropertiesFilter {
                 if !obj.contains_key(property) {
                     continue;
                 }
-                subschema.mark_evaluated_properties(instance, properties);
+                subschema.mark_evaluated_properties(instance, properties, location)?;
             }
         }
 
         if let Some(conditional) = &self.conditional {
-            conditional.mark_evaluated_properties(instance, properties);
+            conditional.mark_evaluated_properties(instance, properties, location)?;
         }
 
         if let Some(combinator) = &self.all_of {
             if combinator
                 .subschemas
                 .iter()
-                .all(|(v, _)| v.is_valid(instance))
+                .all(|(v, _)| v.is_valid_at(instance, location))
             {
-                combinator.mark_evaluated_properties(instance, properties);
+                combinator.mark_evaluated_properties(instance, properties, location)?;
             }
         }
 
@@ -386,9 +417,9 @@ impl PropertiesFilter for Draft2019PropertiesFilter {
             if combinator
                 .subschemas
                 .iter()
-                .any(|(v, _)| v.is_valid(instance))
+                .any(|(v, _)| v.is_valid_at(instance, location))
             {
-                combinator.mark_evaluated_properties(instance, properties);
+                combinator.mark_evaluated_properties(instance, properties, location)?;
             }
         }
 
@@ -396,17 +427,18 @@ impl PropertiesFilter for Draft2019PropertiesFilter {
             let result = combinator
                 .subschemas
                 .iter()
-                .map(|(v, _)| v.is_valid(instance))
+                .map(|(v, _)| v.is_valid_at(instance, location))
                 .collect::<Vec<_>>();
             if result.iter().filter(|v| **v).count() == 1 {
                 for ((_, subschema), is_valid) in combinator.subschemas.iter().zip(result) {
                     if is_valid {
-                        subschema.mark_evaluated_properties(instance, properties);
+                        subschema.mark_evaluated_properties(instance, properties, location)?;
                         break;
                     }
                 }
             }
         }
+        Ok(())
     }
 
     fn unevaluated(&self) -> Option<&SchemaNode> {
@@ -433,39 +465,46 @@ impl PropertiesFilter for DefaultPropertiesFilter {
         ctx: &'a compiler::Context<'_>,
         parent: &'a Map<String, Value>,
     ) -> Result<Self, ValidationError<'a>> {
+        let _guard = InPlaceGuard::new(parent);
         let mut ref_ = None;
 
         if let Some(Value::String(reference)) = parent.get("$ref") {
-            if ctx.is_circular_reference(reference)? {
-                let scopes = ctx.scopes();
-                let resolved = ctx.lookup(reference)?;
-                let resource = ctx.draft().create_resource(resolved.contents().clone());
-                let resolver = resolved.resolver();
-                let mut base_uri = resolver.base_uri();
-                if let Some(id) = resource.id() {
-                    base_uri = resolver.resolve_against(&base_uri.borrow(), id)?;
-                }
+            let resolved = ctx.lookup(reference)?;
+            match resolved.contents() {
+                // Already applied to this instance location
+                Value::Object(subschema) if InPlaceGuard::is_expanding(subschema) => {}
+                _ if ctx.is_circular_reference(reference)? => {
+                    let scopes = ctx.scopes();
+                    let resource = ctx.draft().create_resource(resolved.contents().clone());
+                    let resolver = resolved.resolver();
+                    let mut base_uri = resolver.base_uri();
+                    if let Some(id) = resource.id() {
+                        base_uri = resolver.resolve_against(&base_uri.borrow(), id)?;
+                    }
 
-                ref_ = Some(ReferenceFilter::Recursive(LazyReference {
-                    resource,
-                    config: Arc::clone(ctx.config()),
-                    registry: Arc::clone(&ctx.registry),
-                    base_uri,
-                    scopes,
-                    vocabularies: ctx.vocabularies().clone(),
-                    location: ctx.location().clone(),
-                    draft: ctx.draft(),
-                    inner: OnceCell::default(),
-                }));
-            } else {
-                ctx.mark_seen(reference)?;
-                let resolved = ctx.lookup(reference)?;
-                if let Value::Object(subschema) = resolved.contents() {
-                    ref_ = Some(ReferenceFilter::Default(Box::new(Self::new(
-                        ctx, subschema,
-                    )?)));
+                    ref_ = Some(ReferenceFilter::Recursive(LazyReference {
+                        resource,
+                        config: Shared::clone(ctx.config()),
+                        build: Shared::clone(ctx.build()),
+                        registry: Shared::clone(&ctx.registry),
+                        base_uri,
+                        scopes,
+                        vocabularies: ctx.vocabularies().clone(),
+                        location: ctx.location().clone(),
+                        draft: ctx.draft(),
+                        origin: resolved.contents().as_object().map_or(0, InPlaceGuard::key),
+                        inner: OnceCell::default(),
+                    }));
                 }
-            };
+                contents => {
+                    ctx.mark_seen(reference)?;
+                    if let Value::Object(subschema) = contents {
+                        ref_ = Some(ReferenceFilter::Default(Box::new(Self::new(
+                            ctx, subschema,
+                        )?)));
+                    }
+                }
+            }
         }
 
         let mut dynamic_ref = None;
@@ -473,7 +512,9 @@ impl PropertiesFilter for DefaultPropertiesFilter {
         if let Some(Value::String(reference)) = parent.get("$dynamicRef") {
             let resolved = ctx.lookup(reference)?;
             if let Value::Object(subschema) = resolved.contents() {
-                dynamic_ref = Some(Box::new(Self::new(ctx, subschema)?));
+                if !InPlaceGuard::is_expanding(subschema) {
+                    dynamic_ref = Some(Box::new(Self::new(ctx, subschema)?));
+                }
             }
         }
 
@@ -526,7 +567,9 @@ impl PropertiesFilter for DefaultPropertiesFilter {
         if let Some(Value::Object(patterns)) = parent.get("patternProperties") {
             for (pattern, schema) in patterns {
                 pattern_properties.push((
-                    match ecma::to_rust_regex(pattern).map(|pattern| Regex::new(&pattern)) {
+                    match ecma::to_rust_regex(pattern).map(|pattern| {
+                        build_fancy_regex(&pattern, None, None, None, ctx.config().shares_regexes())
+                    }) {
                         Ok(Ok(r)) => r,
                         _ => {
                             return Err(ValidationError::format(
@@ -580,37 +623,38 @@ impl PropertiesFilter for DefaultPropertiesFilter {
         &self,
         instance: &'i Value,
         properties: &mut AHashSet<&'i String>,
-    ) {
+        location: &LazyLocation,
+    ) -> Result<(), ValidationError<'static>> {
         if let Some(ref_) = &self.ref_ {
-            ref_.mark_evaluated_properties(instance, properties);
+            ref_.mark_evaluated_properties(instance, properties, location)?;
         }
 
         if let Some(recursive_ref) = &self.dynamic_ref {
-            recursive_ref.mark_evaluated_properties(instance, properties);
+            recursive_ref.mark_evaluated_properties(instance, properties, location)?;
         }
 
         if let Value::Object(obj) = instance {
             for (property, value) in obj {
                 for (p, node) in &self.properties {
-                    if property == p && node.is_valid(value) {
+                    if property == p && node.is_valid_at(value, location) {
                         properties.insert(property);
                         continue;
                     }
                 }
                 if let Some(additional) = self.additional.as_ref() {
-                    if additional.is_valid(value) {
+                    if additional.is_valid_at(value, location) {
                         properties.insert(property);
                         continue;
                     }
                 }
                 if let Some(unevaluated) = self.unevaluated.as_ref() {
-                    if unevaluated.is_valid(value) {
+                    if unevaluated.is_valid_at(value, location) {
                         properties.insert(property);
                         continue;
                     }
                 }
                 for (pattern, _) in &self.pattern_properties {
-                    if pattern.is_match(property).unwrap() {
+                    if pattern.is_match(property).unwrap_or(false) {
                         properties.insert(property);
                     }
                 }
@@ -619,21 +663,21 @@ impl PropertiesFilter for DefaultPropertiesFilter {
                 if !obj.contains_key(property) {
                     continue;
                 }
-                subschema.mark_evaluated_properties(instance, properties);
+                subschema.mark_evaluated_properties(instance, properties, location)?;
             }
         }
 
         if let Some(conditional) = &self.conditional {
-            conditional.mark_evaluated_properties(instance, properties);
+            conditional.mark_evaluated_properties(instance, properties, location)?;
         }
 
         if let Some(combinator) = &self.all_of {
             if combinator
                 .subschemas
                 .iter()
-                .all(|(v, _)| v.is_valid(instance))
+                .all(|(v, _)| v.is_valid_at(instance, location))
             {
-                combinator.mark_evaluated_properties(instance, properties);
+                combinator.mark_evaluated_properties(instance, properties, location)?;
             }
         }
 
@@ -641,9 +685,9 @@ impl PropertiesFilter for DefaultPropertiesFilter {
             if combinator
                 .subschemas
                 .iter()
-                .any(|(v, _)| v.is_valid(instance))
+                .any(|(v, _)| v.is_valid_at(instance, location))
             {
-                combinator.mark_evaluated_properties(instance, properties);
+                combinator.mark_evaluated_properties(instance, properties, location)?;
             }
         }
 
@@ -651,17 +695,18 @@ impl PropertiesFilter for DefaultPropertiesFilter {
             let result = combinator
                 .subschemas
                 .iter()
-                .map(|(v, _)| v.is_valid(instance))
+                .map(|(v, _)| v.is_valid_at(instance, location))
                 .collect::<Vec<_>>();
             if result.iter().filter(|v| **v).count() == 1 {
                 for ((_, subschema), is_valid) in combinator.subschemas.iter().zip(result) {
                     if is_valid {
-                        subschema.mark_evaluated_properties(instance, properties);
+                        subschema.mark_evaluated_properties(instance, properties, location)?;
                         break;
                     }
                 }
             }
         }
+        Ok(())
     }
 
     fn unevaluated(&self) -> Option<&SchemaNode> {
@@ -678,10 +723,12 @@ impl<F: PropertiesFilter> CombinatorFilter<F> {
         &self,
         instance: &'i Value,
         properties: &mut AHashSet<&'i String>,
-    ) {
+        location: &LazyLocation,
+    ) -> Result<(), ValidationError<'static>> {
         for (_, subschema) in &self.subschemas {
-            subschema.mark_evaluated_properties(instance, properties);
+            subschema.mark_evaluated_properties(instance, properties, location)?;
         }
+        Ok(())
     }
 }
 
@@ -715,15 +762,18 @@ impl<F: PropertiesFilter> ConditionalFilter<F> {
         &self,
         instance: &'i Value,
         properties: &mut AHashSet<&'i String>,
-    ) {
-        if self.condition.is_valid(instance) {
-            self.if_.mark_evaluated_properties(instance, properties);
+        location: &LazyLocation,
+    ) -> Result<(), ValidationError<'static>> {
+        if self.condition.is_valid_at(instance, location) {
+            self.if_
+                .mark_evaluated_properties(instance, properties, location)?;
             if let Some(then_) = &self.then_ {
-                then_.mark_evaluated_properties(instance, properties);
+                then_.mark_evaluated_properties(instance, properties, location)?;
             }
         } else if let Some(else_) = &self.else_ {
-            else_.mark_evaluated_properties(instance, properties);
+            else_.mark_evaluated_properties(instance, properties, location)?;
         }
+        Ok(())
     }
 }
 
@@ -752,8 +802,9 @@ pub(crate) fn compile<'a>(
 
 #[cfg(test)]
 mod tests {
-    use crate::{tests_util, Draft};
-    use serde_json::json;
+    use crate::{tests_util, Draft, PatternOptions};
+    use serde_json::{json, Value};
+    use test_case::test_case;
 
     #[test]
     fn one_of() {
@@ -926,4 +977,34 @@ mod tests {
         assert!(!validator.is_valid(&instance));
         assert!(validator.validate(&instance).is_err());
     }
+
+    #[test_case(Draft::Draft201909, &json!({"$ref": "#", "unevaluatedProperties": false}))]
+    #[test_case(Draft::Draft202012, &json!({"$ref": "#", "unevaluatedProperties": false}))]
+    #[test_case(Draft::Draft201909, &json!({"$recursiveRef": "#", "unevaluatedProperties": false}))]
+    #[test_case(Draft::Draft202012, &json!({"$dynamicRef": "#", "unevaluatedProperties": false}))]
+    #[test_case(
+        Draft::Draft202012,
+        &json!({
+            "$defs": {"a": {"$ref": "#/$defs/b"}, "b": {"$ref": "#/$defs/a"}},
+            "$ref": "#/$defs/a",
+            "unevaluatedProperties": false
+        });
+        "indirect cycle"
+    )]
+    fn in_place_reference_cycle(draft: Draft, schema: &Value) {
+        tests_util::is_valid_with_draft(draft, schema, &json!({}));
+        tests_util::is_not_valid_with_draft(draft, schema, &json!({"a": 1}));
+    }
+
+    #[test]
+    fn backtrack_limit_in_pattern_properties() {
+        let validator = crate::options()
+            .with_pattern_options(PatternOptions::fancy_regex().backtrack_limit(1))
+            .build(&json!({
+                "patternProperties": {"(?=a)a+b": true},
+                "unevaluatedProperties": false
+            }))
+            .expect("Invalid schema");
+        assert!(!validator.is_valid(&json!({"aaaaaaaaaaaa": 1})));
+    }
 }
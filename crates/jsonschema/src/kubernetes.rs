@@ -0,0 +1,390 @@
+//! Validation of Kubernetes custom resources against the structural schemas of their CRDs.
+//!
+//! The `openAPIV3Schema` of a `CustomResourceDefinition` is an OpenAPI 3.0 schema object, i.e. a
+//! Draft 4 schema with a few Kubernetes extensions. The API server treats it differently from a
+//! plain JSON Schema validator:
+//!
+//! - Unknown fields are dropped instead of being accepted or rejected, unless
+//!   `x-kubernetes-preserve-unknown-fields` is set. The same applies to `null` values of fields
+//!   that are not `nullable`. See [`prune`].
+//! - `nullable: true` allows `null` in addition to the declared `type` and `enum`.
+//! - `x-kubernetes-int-or-string: true` accepts integers and strings.
+//! - `x-kubernetes-embedded-resource: true` requires `apiVersion` and `kind`.
+//! - `x-kubernetes-validations` holds CEL rules. They are checked for a valid shape but not
+//!   evaluated.
+//!
+//! Custom resources are expected to be pruned before being validated, as the API server does:
+//!
+//! ```rust
+//! use serde_json::json;
+//!
+//! let schema = json!({
+//!     "type": "object",
+//!     "properties": {
+//!         "spec": {
+//!             "type": "object",
+//!             "properties": {
+//!                 "port": {"x-kubernetes-int-or-string": true},
+//!                 "image": {"type": "string", "nullable": true}
+//!             }
+//!         }
+//!     }
+//! });
+//! let mut resource = json!({
+//!     "apiVersion": "example.com/v1",
+//!     "kind": "App",
+//!     "spec": {"port": "http", "image": null, "replicas": 3}
+//! });
+//! jsonschema::kubernetes::prune(&schema, &mut resource);
+//! assert_eq!(resource["spec"], json!({"port": "http", "image": null}));
+//!
+//! let validator = jsonschema::kubernetes::validator_for(&schema).expect("Invalid schema");
+//! assert!(validator.is_valid(&resource));
+//! ```
+use serde_json::{Map, Value};
+
+use crate::{
+    keywords::custom::Keyword,
+    paths::{LazyLocation, Location},
+    types::JsonType,
+    Draft, ValidationError, ValidationOptions, Validator,
+};
+
+const INT_OR_STRING: &str = "x-kubernetes-int-or-string";
+const EMBEDDED_RESOURCE: &str = "x-kubernetes-embedded-resource";
+const PRESERVE_UNKNOWN_FIELDS: &str = "x-kubernetes-preserve-unknown-fields";
+const VALIDATIONS: &str = "x-kubernetes-validations";
+
+/// Fields that are kept in every resource regardless of its schema.
+const RESOURCE_FIELDS: [&str; 3] = ["apiVersion", "kind", "metadata"];
+
+/// Create a validator for the structural schema of a custom resource definition.
+///
+/// The schema is interpreted as Draft 4 with the Kubernetes extensions described in the
+/// [module documentation](self). Instances are expected to be [pruned](prune) already.
+///
+/// # Errors
+///
+/// Returns an error if the schema is invalid, e.g. if `x-kubernetes-validations` is not an array
+/// of rules.
+pub fn validator_for(schema: &Value) -> Result<Validator, ValidationError<'static>> {
+    options().build(&with_nullable(schema))
+}
+
+/// Drop the fields of a custom resource that the API server would not store.
+///
+/// A field is dropped if it is not described by `properties` or `additionalProperties` of the
+/// enclosing object schema, unless that schema sets `x-kubernetes-preserve-unknown-fields`.
+/// `null` values of fields that are not `nullable` are dropped too. `apiVersion`, `kind` and
+/// `metadata` are always kept at the root and in embedded resources.
+pub fn prune(schema: &Value, instance: &mut Value) {
+    prune_value(schema, instance, true);
+}
+
+fn options() -> ValidationOptions {
+    crate::options()
+        .with_draft(Draft::Draft4)
+        .with_keyword(INT_OR_STRING, compile_int_or_string)
+        .with_keyword(EMBEDDED_RESOURCE, compile_embedded_resource)
+        .with_keyword(VALIDATIONS, compile_validations)
+}
+
+fn is_enabled(schema: &Value, keyword: &str) -> bool {
+    schema.get(keyword).and_then(Value::as_bool) == Some(true)
+}
+
+fn prune_value(schema: &Value, instance: &mut Value, is_resource: bool) {
+    match instance {
+        Value::Object(object) => {
+            let is_resource = is_resource || is_enabled(schema, EMBEDDED_RESOURCE);
+            let preserve = is_enabled(schema, PRESERVE_UNKNOWN_FIELDS);
+            let properties = schema.get("properties").and_then(Value::as_object);
+            let additional = schema
+                .get("additionalProperties")
+                .filter(|additional| additional.is_object());
+            object.retain(|name, value| {
+                let Some(subschema) = properties
+                    .and_then(|properties| properties.get(name))
+                    .or(additional)
+                else {
+                    return preserve || (is_resource && RESOURCE_FIELDS.contains(&name.as_str()));
+                };
+                if value.is_null() {
+                    return is_enabled(subschema, "nullable");
+                }
+                prune_value(subschema, value, false);
+                true
+            });
+        }
+        Value::Array(items) => {
+            if let Some(subschema) = schema.get("items").filter(|items| items.is_object()) {
+                for item in items {
+                    prune_value(subschema, item, false);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Make `null` valid for schemas with `nullable: true` by adding it to `type` and `enum`.
+fn with_nullable(schema: &Value) -> Value {
+    let Value::Object(object) = schema else {
+        return schema.clone();
+    };
+    let mut result = Map::with_capacity(object.len());
+    for (keyword, value) in object {
+        let value = match (keyword.as_str(), value) {
+            ("properties", Value::Object(properties)) => Value::Object(
+                properties
+                    .iter()
+                    .map(|(name, subschema)| (name.clone(), with_nullable(subschema)))
+                    .collect(),
+            ),
+            ("allOf" | "anyOf" | "oneOf" | "items", Value::Array(subschemas)) => {
+                Value::Array(subschemas.iter().map(with_nullable).collect())
+            }
+            ("additionalProperties" | "items" | "not", Value::Object(_)) => with_nullable(value),
+            _ => value.clone(),
+        };
+        result.insert(keyword.clone(), value);
+    }
+    if is_enabled(schema, "nullable") {
+        if let Some(Value::String(ty)) = result.get("type") {
+            let ty = Value::String(ty.clone());
+            result.insert(
+                "type".to_string(),
+                Value::Array(vec![ty, Value::String("null".to_string())]),
+            );
+        }
+        if let Some(Value::Array(values)) = result.get_mut("enum") {
+            if !values.contains(&Value::Null) {
+                values.push(Value::Null);
+            }
+        }
+    }
+    Value::Object(result)
+}
+
+struct IntOrStringValidator {
+    location: Location,
+}
+
+impl Keyword for IntOrStringValidator {
+    fn validate<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+    ) -> Result<(), ValidationError<'i>> {
+        if self.is_valid(instance) {
+            Ok(())
+        } else {
+            Err(ValidationError::custom(
+                self.location.clone(),
+                location.into(),
+                instance,
+                format!("{instance} is not an integer or a string"),
+            ))
+        }
+    }
+
+    fn is_valid(&self, instance: &Value) -> bool {
+        instance.is_string() || instance.as_f64().is_some_and(|number| number.fract() == 0.)
+    }
+}
+
+struct EmbeddedResourceValidator {
+    location: Location,
+}
+
+impl EmbeddedResourceValidator {
+    fn missing(object: &Map<String, Value>) -> Option<&'static str> {
+        ["apiVersion", "kind"].into_iter().find(|field| {
+            object
+                .get(*field)
+                .and_then(Value::as_str)
+                .map_or(true, str::is_empty)
+        })
+    }
+}
+
+impl Keyword for EmbeddedResourceValidator {
+    fn validate<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+    ) -> Result<(), ValidationError<'i>> {
+        let Value::Object(object) = instance else {
+            return Ok(());
+        };
+        match Self::missing(object) {
+            None => Ok(()),
+            Some(field) => Err(ValidationError::custom(
+                self.location.clone(),
+                location.into(),
+                instance,
+                format!("Embedded resource must have a non-empty '{field}'"),
+            )),
+        }
+    }
+
+    fn is_valid(&self, instance: &Value) -> bool {
+        instance
+            .as_object()
+            .map_or(true, |object| Self::missing(object).is_none())
+    }
+}
+
+/// Rules are not evaluated, so every instance is valid.
+struct PassthroughValidator;
+
+impl Keyword for PassthroughValidator {
+    fn validate<'i>(&self, _: &'i Value, _: &LazyLocation) -> Result<(), ValidationError<'i>> {
+        Ok(())
+    }
+
+    fn is_valid(&self, _: &Value) -> bool {
+        true
+    }
+}
+
+fn compile_flag<'a>(schema: &'a Value, location: &Location) -> Result<bool, ValidationError<'a>> {
+    schema.as_bool().ok_or_else(|| {
+        ValidationError::single_type_error(
+            Location::new(),
+            location.clone(),
+            schema,
+            JsonType::Boolean,
+        )
+    })
+}
+
+fn compile_int_or_string<'a>(
+    _: &'a Map<String, Value>,
+    schema: &'a Value,
+    location: Location,
+) -> Result<Box<dyn Keyword>, ValidationError<'a>> {
+    if compile_flag(schema, &location)? {
+        Ok(Box::new(IntOrStringValidator { location }))
+    } else {
+        Ok(Box::new(PassthroughValidator))
+    }
+}
+
+fn compile_embedded_resource<'a>(
+    _: &'a Map<String, Value>,
+    schema: &'a Value,
+    location: Location,
+) -> Result<Box<dyn Keyword>, ValidationError<'a>> {
+    if compile_flag(schema, &location)? {
+        Ok(Box::new(EmbeddedResourceValidator { location }))
+    } else {
+        Ok(Box::new(PassthroughValidator))
+    }
+}
+
+fn compile_validations<'a>(
+    _: &'a Map<String, Value>,
+    schema: &'a Value,
+    location: Location,
+) -> Result<Box<dyn Keyword>, ValidationError<'a>> {
+    let Value::Array(rules) = schema else {
+        return Err(ValidationError::single_type_error(
+            Location::new(),
+            location,
+            schema,
+            JsonType::Array,
+        ));
+    };
+    for rule in rules {
+        if !rule.get("rule").is_some_and(Value::is_string) {
+            return Err(ValidationError::custom(
+                Location::new(),
+                location,
+                rule,
+                "Validation rules must be objects with a 'rule' string",
+            ));
+        }
+    }
+    Ok(Box::new(PassthroughValidator))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    use super::{prune, validator_for};
+
+    #[test_case(
+        &json!({"type": "object", "properties": {"spec": {"type": "object", "properties": {"a": {"type": "integer"}}}}}),
+        json!({"apiVersion": "v1", "kind": "A", "metadata": {"name": "a"}, "spec": {"a": 1, "b": 2}, "status": {}}),
+        &json!({"apiVersion": "v1", "kind": "A", "metadata": {"name": "a"}, "spec": {"a": 1}});
+        "unknown fields"
+    )]
+    #[test_case(
+        &json!({"properties": {"a": {"type": "string"}, "b": {"type": "string", "nullable": true}}}),
+        json!({"a": null, "b": null}),
+        &json!({"b": null});
+        "null values"
+    )]
+    #[test_case(
+        &json!({"properties": {"spec": {"x-kubernetes-preserve-unknown-fields": true, "properties": {"a": {"type": "object"}}}}}),
+        json!({"spec": {"a": {"b": 1}, "c": 2}}),
+        &json!({"spec": {"a": {}, "c": 2}});
+        "preserved unknown fields"
+    )]
+    #[test_case(
+        &json!({"properties": {"labels": {"additionalProperties": {"type": "string"}}}}),
+        json!({"labels": {"a": "b", "c": null}}),
+        &json!({"labels": {"a": "b"}});
+        "maps"
+    )]
+    #[test_case(
+        &json!({"properties": {"items": {"items": {"properties": {"a": {}}}}}}),
+        json!({"items": [{"a": 1, "b": 2}, {"c": 3}]}),
+        &json!({"items": [{"a": 1}, {}]});
+        "arrays"
+    )]
+    #[test_case(
+        &json!({"properties": {"template": {"x-kubernetes-embedded-resource": true, "properties": {"spec": {}}}}}),
+        json!({"template": {"apiVersion": "v1", "kind": "Pod", "metadata": {}, "spec": {}, "other": 1}}),
+        &json!({"template": {"apiVersion": "v1", "kind": "Pod", "metadata": {}, "spec": {}}});
+        "embedded resources"
+    )]
+    fn pruning(schema: &Value, mut instance: Value, expected: &Value) {
+        prune(schema, &mut instance);
+        assert_eq!(&instance, expected);
+    }
+
+    #[test_case(&json!({"x-kubernetes-int-or-string": true}), &json!(1))]
+    #[test_case(&json!({"x-kubernetes-int-or-string": true}), &json!("50%"))]
+    #[test_case(&json!({"x-kubernetes-int-or-string": false}), &json!(1.5))]
+    #[test_case(&json!({"type": "string", "nullable": true}), &json!(null))]
+    #[test_case(&json!({"properties": {"a": {"type": "string", "enum": ["b"], "nullable": true}}}), &json!({"a": null}))]
+    #[test_case(&json!({"x-kubernetes-embedded-resource": true}), &json!({"apiVersion": "v1", "kind": "Pod"}))]
+    #[test_case(&json!({"x-kubernetes-validations": [{"rule": "self.a > 1", "message": "a"}]}), &json!({"a": 0}))]
+    #[test_case(&json!({"type": "integer", "minimum": 1, "exclusiveMinimum": true}), &json!(2); "draft 4")]
+    fn valid(schema: &Value, instance: &Value) {
+        let validator = validator_for(schema).expect("Invalid schema");
+        assert!(validator.is_valid(instance));
+        assert!(validator.validate(instance).is_ok());
+    }
+
+    #[test_case(&json!({"x-kubernetes-int-or-string": true}), &json!(1.5), "1.5 is not an integer or a string")]
+    #[test_case(&json!({"type": "string"}), &json!(null), "null is not of type \"string\"")]
+    #[test_case(&json!({"x-kubernetes-embedded-resource": true}), &json!({"apiVersion": "v1"}), "Embedded resource must have a non-empty 'kind'")]
+    fn invalid(schema: &Value, instance: &Value, expected: &str) {
+        let validator = validator_for(schema).expect("Invalid schema");
+        assert!(!validator.is_valid(instance));
+        let error = validator.validate(instance).expect_err("Should fail");
+        assert_eq!(error.to_string(), expected);
+    }
+
+    #[test_case(&json!({"x-kubernetes-validations": {"rule": "true"}}))]
+    #[test_case(&json!({"x-kubernetes-validations": [{"message": "a"}]}))]
+    #[test_case(&json!({"x-kubernetes-int-or-string": "yes"}))]
+    fn invalid_schema(schema: &Value) {
+        assert!(validator_for(schema).is_err());
+    }
+}
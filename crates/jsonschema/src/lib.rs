@@ -223,6 +223,22 @@
 //! # }
 //! ```
 //!
+//! ## Global Defaults
+//!
+//! Applications enforcing the same policy everywhere, e.g. asserting formats, can set process-wide
+//! defaults once with [`set_global_defaults`]. They are the starting point of [`options`], and
+//! therefore apply to [`validator_for`], [`is_valid`], [`validate`] and the draft-specific modules:
+//!
+//! ```rust
+//! use serde_json::json;
+//!
+//! jsonschema::set_global_defaults(jsonschema::options().should_validate_formats(true))
+//!     .expect("Global defaults are already set");
+//!
+//! let schema = json!({"format": "email"});
+//! assert!(!jsonschema::is_valid(&schema, &json!("not an email")));
+//! ```
+//!
 //! # External References
 //!
 //! By default, `jsonschema` resolves HTTP references using `reqwest` and file references from the local file system.
@@ -254,12 +270,16 @@
 //! reqwest = { version = "*", features = ["rustls-tls"] }
 //! ```
 //!
-//! You can disable the default behavior using crate features:
+//! You can disable the default behavior using crate features. Drafts are default features as
+//! well, so list the ones you need next to the resolving features:
 //!
-//! - Disable HTTP resolving: `default-features = false, features = ["resolve-file"]`
-//! - Disable file resolving: `default-features = false, features = ["resolve-http"]`
+//! - Disable HTTP resolving: `default-features = false, features = ["resolve-file", "draft202012"]`
+//! - Disable file resolving: `default-features = false, features = ["resolve-http", "draft202012"]`
 //! - Enable async resolution: `features = ["resolve-async"]`
-//! - Disable all resolving: `default-features = false`
+//! - Disable all resolving: `default-features = false, features = ["draft202012"]`
+//!
+//! The `unicode-normalization` feature, disabled by default, adds the `NormalizeNfc`
+//! preprocessor together with the Unicode data tables it needs.
 //!
 //! ## Custom retrievers
 //!
@@ -633,44 +653,116 @@
 //!    - No filesystem access (`resolve-file` feature)
 //!    - No direct HTTP requests, at least right now (`resolve-http` feature)
 //!
-//! To use `jsonschema` in WASM, disable default features:
+//! To use `jsonschema` in WASM, disable default features and enable the drafts you need:
 //!
 //! ```toml
-//! jsonschema = { version = "x.y.z", default-features = false }
+//! jsonschema = { version = "x.y.z", default-features = false, features = ["draft202012"] }
 //! ```
 //!
 //! For external references in WASM you may want to implement a custom retriever.
 //! See the [External References](#external-references) section for implementation details.
-
+//!
+//! Single-threaded embedders can also build with `RUSTFLAGS="--cfg jsonschema_single_threaded"`.
+//! Compiled validators then use `Rc` instead of `Arc` for shared locations, options and
+//! registries, avoiding atomic reference counting while errors and annotations are produced. In
+//! exchange, validators, error iterators and custom keywords are neither `Send` nor `Sync`, and
+//! shared validators, i.e. meta-schema validators and [`Validator::always_valid`] /
+//! [`Validator::always_invalid`], are built once per thread on first use and handed out as
+//! `Rc<Validator>`, e.g. via `jsonschema::draft7::meta::VALIDATOR.get()`. They are freed when
+//! their thread exits. This is a `cfg` flag rather than a crate feature so that it can not be
+//! enabled by accident for other dependents in the same build.
+
+#[cfg(not(any(
+    feature = "draft4",
+    feature = "draft6",
+    feature = "draft7",
+    feature = "draft201909",
+    feature = "draft202012"
+)))]
+compile_error!(
+    "at least one of the `draft4`, `draft6`, `draft7`, `draft201909` or `draft202012` features \
+     must be enabled"
+);
+
+pub mod analyze;
+pub mod asyncapi;
+mod batch;
+mod cancel;
+mod canonical;
+pub mod cloudevents;
 pub(crate) mod compiler;
 mod content_encoding;
 mod content_media_type;
+mod coverage;
+mod diagnostics;
+mod dialect;
+pub mod docs;
 mod ecma;
 pub mod error;
+mod eval;
+mod evaluation;
+mod examples;
 pub mod ext;
+mod fingerprint;
+pub mod generate;
 mod keywords;
+pub mod kubernetes;
 mod node;
+pub mod non_finite;
 mod options;
 pub mod output;
+pub mod patch;
 pub mod paths;
+mod preprocess;
+mod profile;
 pub(crate) mod properties;
 pub(crate) mod regex;
+mod remediation;
 mod retriever;
+mod stream;
+mod sync;
 pub mod types;
 mod validator;
+mod variables;
 
 #[deprecated(since = "0.30.0", note = "Use `jsonschema::types` instead.")]
 pub mod primitive_type {
     pub use super::types::*;
 }
 
+pub use batch::{
+    BatchDiff, BatchError, BatchReport, BatchStatistics, InstanceDiff, InstanceOutcome,
+};
+pub use cancel::CancellationToken;
+pub use canonical::canonicalize;
+pub use coverage::Coverage;
+pub use diagnostics::{Diagnostic, DiagnosticKind};
+pub use dialect::Dialect;
 pub use error::{ErrorIterator, MaskedValidationError, ValidationError};
+pub use eval::EvalOptions;
+pub use evaluation::EvaluatedSubschema;
+pub use examples::ExampleFailure;
+pub use fingerprint::{fingerprint, fingerprint_resolved, Fingerprint};
 pub use keywords::custom::Keyword;
-pub use options::{FancyRegex, PatternOptions, Regex, ValidationOptions};
+pub use node::{NodeId, NodeInfo};
+pub use options::{
+    Clock, CompilationLimits, DateBounds, FancyRegex, KeyDecoder, PatternOptions, Regex,
+    SizeLimits, TimeBound, TypedKeys, ValidationOptions, ValueComparison,
+};
 pub use output::BasicOutput;
+#[cfg(feature = "unicode-normalization")]
+pub use preprocess::NormalizeNfc;
+pub use preprocess::{Preprocessed, Preprocessor, Transformation, TrimStrings};
+pub use profile::ProfileEntry;
 pub use referencing::{
-    Draft, Error as ReferencingError, Registry, RegistryOptions, Resource, Retrieve, Uri,
+    DocumentCache, Draft, Error as ReferencingError, Registry, RegistryOptions, Resource, Retrieve,
+    Uri, UriRewriter,
 };
+pub use remediation::{PatchOperation, Remediation};
+pub use retriever::DefaultRetriever;
+#[cfg(all(feature = "resolve-http", not(target_arch = "wasm32")))]
+pub use retriever::RetrievalPolicy;
+pub use stream::{Event, StreamError, StreamValidator};
 pub use types::{JsonType, JsonTypeSet, JsonTypeSetIterator};
 pub use validator::Validator;
 
@@ -751,6 +843,36 @@ pub fn validator_for(schema: &Value) -> Result<Validator, ValidationError<'stati
     Validator::new(schema)
 }
 
+/// Create a validator for the schema published at `url`, retrieving it and the documents it
+/// references within the bounds of `policy`.
+///
+/// The draft is detected automatically and other options are the defaults.
+///
+/// # Errors
+///
+/// Returns an error if a document can not be retrieved or is not allowed by the policy, or if
+/// the schema is invalid.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use jsonschema::RetrievalPolicy;
+/// use serde_json::json;
+///
+/// let validator = jsonschema::from_url(
+///     "https://json.schemastore.org/package.json",
+///     RetrievalPolicy::new().allow("https://json.schemastore.org/"),
+/// )?;
+/// assert!(validator.is_valid(&json!({"name": "jsonschema"})));
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(all(feature = "resolve-http", not(target_arch = "wasm32")))]
+pub fn from_url(url: &str, policy: RetrievalPolicy) -> Result<Validator, ValidationError<'static>> {
+    retriever::from_url(url, &policy)
+}
+
 /// Create a validator for the input schema with automatic draft detection and default options,
 /// using non-blocking retrieval for external references.
 ///
@@ -828,6 +950,38 @@ pub fn options() -> ValidationOptions {
     Validator::options()
 }
 
+/// Set process-wide default options, which [`options`] and everything built on it start from.
+///
+/// Defaults can be set only once, typically at startup, and can still be overridden per
+/// validator. They don't apply to [`async_options`], which uses a different retriever type.
+///
+/// # Examples
+///
+/// ```rust
+/// use serde_json::json;
+///
+/// let defaults = jsonschema::options().should_validate_formats(true);
+/// assert!(jsonschema::set_global_defaults(defaults).is_ok());
+/// // Defaults can't be replaced
+/// assert!(jsonschema::set_global_defaults(jsonschema::options()).is_err());
+///
+/// let schema = json!({"format": "ipv4"});
+/// assert!(!jsonschema::is_valid(&schema, &json!("localhost")));
+///
+/// let validator = jsonschema::options()
+///     .should_validate_formats(false)
+///     .build(&schema)
+///     .expect("Invalid schema");
+/// assert!(validator.is_valid(&json!("localhost")));
+/// ```
+///
+/// # Errors
+///
+/// Returns the given options back if defaults are already set.
+pub fn set_global_defaults(options: ValidationOptions) -> Result<(), ValidationOptions> {
+    options::set_global_defaults(options)
+}
+
 /// Create a builder for configuring JSON Schema validation options.
 ///
 /// This function returns a [`ValidationOptions`] struct which allows you to set various options for JSON Schema validation.
@@ -895,46 +1049,99 @@ pub mod meta {
     use crate::{error::ValidationError, Draft, ReferencingError};
     use serde_json::Value;
 
-    use crate::Validator;
-
     pub(crate) mod validators {
-        use crate::Validator;
-        use once_cell::sync::Lazy;
+        use crate::{ValidationOptions, Validator};
+
+        macro_rules! shared_validator {
+            ($name:ident, $options:expr, $schema:expr, $message:expr) => {
+                #[cfg(not(jsonschema_single_threaded))]
+                pub static $name: once_cell::sync::Lazy<Validator> =
+                    once_cell::sync::Lazy::new(|| $options.build($schema).expect($message));
+
+                // Validators are not `Sync` there, so each thread builds its own once
+                #[cfg(jsonschema_single_threaded)]
+                pub static $name: PerThread = PerThread({
+                    thread_local! {
+                        static VALIDATOR: std::rc::Rc<Validator> =
+                            std::rc::Rc::new($options.build($schema).expect($message));
+                    }
+                    &VALIDATOR
+                });
+            };
+        }
 
-        pub static DRAFT4_META_VALIDATOR: Lazy<Validator> = Lazy::new(|| {
-            crate::options()
-                .without_schema_validation()
-                .build(&referencing::meta::DRAFT4)
-                .expect("Draft 4 meta-schema should be valid")
-        });
+        macro_rules! meta_validator {
+            ($name:ident, $draft:ident, $title:literal) => {
+                // Meta-schemas are validated the same way regardless of global defaults
+                shared_validator!(
+                    $name,
+                    <ValidationOptions>::default().without_schema_validation(),
+                    &referencing::meta::$draft,
+                    concat!($title, " meta-schema should be valid")
+                );
+            };
+        }
 
-        pub static DRAFT6_META_VALIDATOR: Lazy<Validator> = Lazy::new(|| {
-            crate::options()
-                .without_schema_validation()
-                .build(&referencing::meta::DRAFT6)
-                .expect("Draft 6 meta-schema should be valid")
-        });
+        /// A shared validator built on first use in each thread and freed when the thread exits.
+        #[cfg(jsonschema_single_threaded)]
+        pub struct PerThread(&'static std::thread::LocalKey<std::rc::Rc<Validator>>);
 
-        pub static DRAFT7_META_VALIDATOR: Lazy<Validator> = Lazy::new(|| {
-            crate::options()
-                .without_schema_validation()
-                .build(&referencing::meta::DRAFT7)
-                .expect("Draft 7 meta-schema should be valid")
-        });
+        #[cfg(jsonschema_single_threaded)]
+        impl PerThread {
+            /// The instance of the validator for the current thread.
+            #[must_use]
+            pub fn get(&self) -> std::rc::Rc<Validator> {
+                self.0.with(std::rc::Rc::clone)
+            }
+        }
 
-        pub static DRAFT201909_META_VALIDATOR: Lazy<Validator> = Lazy::new(|| {
-            crate::options()
-                .without_schema_validation()
-                .build(&referencing::meta::DRAFT201909)
-                .expect("Draft 2019-09 meta-schema should be valid")
-        });
+        /// A handle to a shared validator, see [`shared`].
+        #[cfg(not(jsonschema_single_threaded))]
+        pub(crate) type SharedValidator = &'static Validator;
+        #[cfg(jsonschema_single_threaded)]
+        pub(crate) type SharedValidator = std::rc::Rc<Validator>;
+
+        #[cfg(not(jsonschema_single_threaded))]
+        pub(crate) fn shared(
+            validator: &'static once_cell::sync::Lazy<Validator>,
+        ) -> SharedValidator {
+            validator
+        }
+        #[cfg(jsonschema_single_threaded)]
+        pub(crate) fn shared(validator: &PerThread) -> SharedValidator {
+            validator.get()
+        }
+
+        #[cfg(feature = "draft4")]
+        meta_validator!(DRAFT4_META_VALIDATOR, DRAFT4, "Draft 4");
+        #[cfg(feature = "draft6")]
+        meta_validator!(DRAFT6_META_VALIDATOR, DRAFT6, "Draft 6");
+        #[cfg(feature = "draft7")]
+        meta_validator!(DRAFT7_META_VALIDATOR, DRAFT7, "Draft 7");
+        #[cfg(feature = "draft201909")]
+        meta_validator!(DRAFT201909_META_VALIDATOR, DRAFT201909, "Draft 2019-09");
+        #[cfg(feature = "draft202012")]
+        meta_validator!(DRAFT202012_META_VALIDATOR, DRAFT202012, "Draft 2020-12");
+
+        shared_validator!(
+            ALWAYS_VALID,
+            boolean_schema_options(),
+            &serde_json::Value::Bool(true),
+            "`true` schema should compile"
+        );
+        shared_validator!(
+            ALWAYS_INVALID,
+            boolean_schema_options(),
+            &serde_json::Value::Bool(false),
+            "`false` schema should compile"
+        );
 
-        pub static DRAFT202012_META_VALIDATOR: Lazy<Validator> = Lazy::new(|| {
-            crate::options()
+        /// Boolean schemas mean the same in every draft, so the default one is used.
+        fn boolean_schema_options() -> ValidationOptions {
+            <ValidationOptions>::default()
+                .with_draft(crate::Draft::default())
                 .without_schema_validation()
-                .build(&referencing::meta::DRAFT202012)
-                .expect("Draft 2020-12 meta-schema should be valid")
-        });
+        }
     }
 
     /// Validate a JSON Schema document against its meta-schema and get a `true` if the schema is valid
@@ -986,7 +1193,7 @@ pub mod meta {
         meta_validator_for(schema).validate(schema)
     }
 
-    fn meta_validator_for(schema: &Value) -> &'static Validator {
+    fn meta_validator_for(schema: &Value) -> validators::SharedValidator {
         try_meta_validator_for(schema).expect("Failed to detect meta schema")
     }
 
@@ -1056,15 +1263,23 @@ pub mod meta {
         Ok(try_meta_validator_for(schema)?.validate(schema))
     }
 
-    fn try_meta_validator_for(schema: &Value) -> Result<&'static Validator, ReferencingError> {
-        Ok(match Draft::default().detect(schema)? {
-            Draft::Draft4 => &validators::DRAFT4_META_VALIDATOR,
-            Draft::Draft6 => &validators::DRAFT6_META_VALIDATOR,
-            Draft::Draft7 => &validators::DRAFT7_META_VALIDATOR,
-            Draft::Draft201909 => &validators::DRAFT201909_META_VALIDATOR,
-            Draft::Draft202012 => &validators::DRAFT202012_META_VALIDATOR,
-            _ => unreachable!("Unknown draft"),
-        })
+    fn try_meta_validator_for(
+        schema: &Value,
+    ) -> Result<validators::SharedValidator, ReferencingError> {
+        match Draft::default().detect(schema)? {
+            #[cfg(feature = "draft4")]
+            Draft::Draft4 => Ok(validators::shared(&validators::DRAFT4_META_VALIDATOR)),
+            #[cfg(feature = "draft6")]
+            Draft::Draft6 => Ok(validators::shared(&validators::DRAFT6_META_VALIDATOR)),
+            #[cfg(feature = "draft7")]
+            Draft::Draft7 => Ok(validators::shared(&validators::DRAFT7_META_VALIDATOR)),
+            #[cfg(feature = "draft201909")]
+            Draft::Draft201909 => Ok(validators::shared(&validators::DRAFT201909_META_VALIDATOR)),
+            #[cfg(feature = "draft202012")]
+            Draft::Draft202012 => Ok(validators::shared(&validators::DRAFT202012_META_VALIDATOR)),
+            // Disabled drafts are rejected by `detect`, the default draft is always enabled
+            draft => Err(ReferencingError::DisabledSpecification { draft }),
+        }
     }
 }
 
@@ -1085,6 +1300,7 @@ pub mod meta {
 ///
 /// assert!(jsonschema::draft4::is_valid(&schema, &instance));
 /// ```
+#[cfg(feature = "draft4")]
 pub mod draft4 {
     use super::*;
 
@@ -1194,7 +1410,7 @@ pub mod draft4 {
         #[must_use]
         #[inline]
         pub fn is_valid(schema: &Value) -> bool {
-            VALIDATOR.is_valid(schema)
+            crate::meta::validators::shared(&VALIDATOR).is_valid(schema)
         }
 
         /// Validate a JSON Schema document against Draft 4 meta-schema and return the first error if any.
@@ -1218,7 +1434,7 @@ pub mod draft4 {
         /// ```
         #[inline]
         pub fn validate(schema: &Value) -> Result<(), ValidationError> {
-            VALIDATOR.validate(schema)
+            crate::meta::validators::shared(&VALIDATOR).validate(schema)
         }
     }
 }
@@ -1240,6 +1456,7 @@ pub mod draft4 {
 ///
 /// assert!(jsonschema::draft6::is_valid(&schema, &instance));
 /// ```
+#[cfg(feature = "draft6")]
 pub mod draft6 {
     use super::*;
 
@@ -1349,7 +1566,7 @@ pub mod draft6 {
         #[must_use]
         #[inline]
         pub fn is_valid(schema: &Value) -> bool {
-            VALIDATOR.is_valid(schema)
+            crate::meta::validators::shared(&VALIDATOR).is_valid(schema)
         }
 
         /// Validate a JSON Schema document against Draft 6 meta-schema and return the first error if any.
@@ -1373,7 +1590,7 @@ pub mod draft6 {
         /// ```
         #[inline]
         pub fn validate(schema: &Value) -> Result<(), ValidationError> {
-            VALIDATOR.validate(schema)
+            crate::meta::validators::shared(&VALIDATOR).validate(schema)
         }
     }
 }
@@ -1395,6 +1612,7 @@ pub mod draft6 {
 ///
 /// assert!(jsonschema::draft7::is_valid(&schema, &instance));
 /// ```
+#[cfg(feature = "draft7")]
 pub mod draft7 {
     use super::*;
 
@@ -1504,7 +1722,7 @@ pub mod draft7 {
         #[must_use]
         #[inline]
         pub fn is_valid(schema: &Value) -> bool {
-            VALIDATOR.is_valid(schema)
+            crate::meta::validators::shared(&VALIDATOR).is_valid(schema)
         }
 
         /// Validate a JSON Schema document against Draft 7 meta-schema and return the first error if any.
@@ -1528,7 +1746,7 @@ pub mod draft7 {
         /// ```
         #[inline]
         pub fn validate(schema: &Value) -> Result<(), ValidationError> {
-            VALIDATOR.validate(schema)
+            crate::meta::validators::shared(&VALIDATOR).validate(schema)
         }
     }
 }
@@ -1550,6 +1768,7 @@ pub mod draft7 {
 ///
 /// assert!(jsonschema::draft201909::is_valid(&schema, &instance));
 /// ```
+#[cfg(feature = "draft201909")]
 pub mod draft201909 {
     use super::*;
 
@@ -1658,7 +1877,7 @@ pub mod draft201909 {
         #[must_use]
         #[inline]
         pub fn is_valid(schema: &Value) -> bool {
-            VALIDATOR.is_valid(schema)
+            crate::meta::validators::shared(&VALIDATOR).is_valid(schema)
         }
 
         /// Validate a JSON Schema document against Draft 2019-09 meta-schema and return the first error if any.
@@ -1682,7 +1901,7 @@ pub mod draft201909 {
         /// ```
         #[inline]
         pub fn validate(schema: &Value) -> Result<(), ValidationError> {
-            VALIDATOR.validate(schema)
+            crate::meta::validators::shared(&VALIDATOR).validate(schema)
         }
     }
 }
@@ -1707,6 +1926,7 @@ pub mod draft201909 {
 /// # Ok(())
 /// # }
 /// ```
+#[cfg(feature = "draft202012")]
 pub mod draft202012 {
     use super::*;
 
@@ -1816,7 +2036,7 @@ pub mod draft202012 {
         #[must_use]
         #[inline]
         pub fn is_valid(schema: &Value) -> bool {
-            VALIDATOR.is_valid(schema)
+            crate::meta::validators::shared(&VALIDATOR).is_valid(schema)
         }
 
         /// Validate a JSON Schema document against Draft 2020-12 meta-schema and return the first error if any.
@@ -1840,7 +2060,7 @@ pub mod draft202012 {
         /// ```
         #[inline]
         pub fn validate(schema: &Value) -> Result<(), ValidationError> {
-            VALIDATOR.validate(schema)
+            crate::meta::validators::shared(&VALIDATOR).validate(schema)
         }
     }
 }
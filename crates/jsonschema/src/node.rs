@@ -1,16 +1,97 @@
 use crate::{
     compiler::Context,
     error::ErrorIterator,
-    keywords::{BoxedValidator, Keyword},
+    eval::Evaluation,
+    keywords::{boolean::FALSE_SCHEMA, BoxedValidator, Keyword},
     output::{Annotations, BasicOutput, ErrorDescription, OutputUnit},
     paths::{LazyLocation, Location, LocationSegment},
+    profile::NodeProfile,
     validator::{PartialApplication, Validate},
     ValidationError,
 };
 use ahash::AHashMap;
 use referencing::{uri, Uri};
 use serde_json::Value;
-use std::{cell::OnceCell, collections::VecDeque, fmt};
+use std::{cell::OnceCell, collections::VecDeque, fmt, sync::Arc};
+
+/// A stable identifier of a compiled subschema within a [`Validator`](crate::Validator).
+///
+/// IDs are assigned in compilation order, so compiling the same schema with the same options
+/// yields the same IDs. They can be used as indices into side tables of
+/// [`Validator::nodes`](crate::Validator::nodes).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NodeId(usize);
+
+impl NodeId {
+    /// The index of the node in [`Validator::nodes`](crate::Validator::nodes).
+    #[must_use]
+    pub const fn index(self) -> usize {
+        self.0
+    }
+}
+
+/// Metadata of a compiled subschema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeInfo {
+    id: NodeId,
+    location: Location,
+    /// The location within the resource defining this node, if reached via a reference.
+    canonical_location: Option<String>,
+    base_uri: Option<Uri<String>>,
+    keywords: Vec<String>,
+}
+
+impl NodeInfo {
+    pub(crate) fn new(
+        location: Location,
+        canonical_location: Option<String>,
+        base_uri: Option<Uri<String>>,
+        schema: &Value,
+    ) -> NodeInfo {
+        NodeInfo {
+            id: NodeId(0),
+            location,
+            canonical_location,
+            base_uri,
+            keywords: schema
+                .as_object()
+                .map(|schema| schema.keys().cloned().collect())
+                .unwrap_or_default(),
+        }
+    }
+    pub(crate) fn with_id(mut self, index: usize) -> NodeInfo {
+        self.id = NodeId(index);
+        self
+    }
+    /// The ID of this node.
+    #[must_use]
+    pub const fn id(&self) -> NodeId {
+        self.id
+    }
+    /// The keyword location of this node, i.e. the path from the root schema through `$ref`s.
+    #[must_use]
+    pub const fn location(&self) -> &Location {
+        &self.location
+    }
+    /// The location of this node within the resource defining it, e.g. `/$defs/item` for a
+    /// subschema reached via `"$ref": "#/$defs/item"`. Nodes reached via different references to
+    /// the same subschema share it.
+    pub(crate) fn canonical_location(&self) -> &str {
+        self.canonical_location
+            .as_deref()
+            .unwrap_or_else(|| self.location.as_str())
+    }
+    /// The base URI of the resource containing this node, if it is not the default one.
+    #[must_use]
+    pub const fn base_uri(&self) -> Option<&Uri<String>> {
+        self.base_uri.as_ref()
+    }
+    /// Keywords of this node, as ordered in the schema object. Empty for boolean schemas.
+    #[must_use]
+    pub fn keywords(&self) -> &[String] {
+        &self.keywords
+    }
+}
 
 /// A node in the schema tree, returned by [`compiler::compile`]
 #[derive(Debug)]
@@ -18,6 +99,7 @@ pub(crate) struct SchemaNode {
     validators: NodeValidators,
     location: Location,
     absolute_path: Option<Uri<String>>,
+    profile: Option<Arc<NodeProfile>>,
 }
 
 enum NodeValidators {
@@ -30,13 +112,13 @@ enum NodeValidators {
     /// ```
     ///
     /// Here the result of `compiler::compile` called with the `false` value will return a
-    /// `SchemaNode` with a single `BooleanValidator` as it's `validators`.
-    Boolean { validator: Option<BoxedValidator> },
+    /// `SchemaNode` with the shared `FalseSchemaValidator` as it's `validators`. Nodes for `true`
+    /// have no validators at all.
+    Boolean {
+        validator: Option<&'static dyn Validate>,
+    },
     /// The result of compiling a schema which is composed of keywords (almost all schemas)
     Keyword(KeywordValidators),
-    /// The result of compiling a schema which is "array valued", e.g the "dependencies" keyword of
-    /// draft 7 which can take values which are an array of other property names
-    Array { validators: Vec<BoxedValidator> },
 }
 
 impl fmt::Debug for NodeValidators {
@@ -44,7 +126,6 @@ impl fmt::Debug for NodeValidators {
         match self {
             Self::Boolean { .. } => f.debug_struct("Boolean").finish(),
             Self::Keyword(_) => f.debug_tuple("Keyword").finish(),
-            Self::Array { .. } => f.debug_struct("Array").finish(),
         }
     }
 }
@@ -59,11 +140,17 @@ struct KeywordValidators {
 }
 
 impl SchemaNode {
-    pub(crate) fn from_boolean(ctx: &Context<'_>, validator: Option<BoxedValidator>) -> SchemaNode {
+    /// A node for the `true` or `false` schema.
+    ///
+    /// All `false` nodes share a single validator, so neither kind allocates one.
+    pub(crate) fn from_boolean(ctx: &Context<'_>, valid: bool) -> SchemaNode {
         SchemaNode {
             location: ctx.location().clone(),
             absolute_path: ctx.base_uri(),
-            validators: NodeValidators::Boolean { validator },
+            profile: ctx.profile(),
+            validators: NodeValidators::Boolean {
+                validator: if valid { None } else { Some(&FALSE_SCHEMA) },
+            },
         }
     }
 
@@ -75,6 +162,7 @@ impl SchemaNode {
         SchemaNode {
             location: ctx.location().clone(),
             absolute_path: ctx.base_uri(),
+            profile: ctx.profile(),
             validators: NodeValidators::Keyword(KeywordValidators {
                 unmatched_keywords,
                 validators,
@@ -82,19 +170,11 @@ impl SchemaNode {
         }
     }
 
-    pub(crate) fn from_array(ctx: &Context<'_>, validators: Vec<BoxedValidator>) -> SchemaNode {
-        SchemaNode {
-            location: ctx.location().clone(),
-            absolute_path: ctx.base_uri(),
-            validators: NodeValidators::Array { validators },
-        }
-    }
-
-    pub(crate) fn validators(&self) -> impl ExactSizeIterator<Item = &BoxedValidator> {
+    pub(crate) fn validators(&self) -> impl ExactSizeIterator<Item = &dyn Validate> {
         match &self.validators {
             NodeValidators::Boolean { validator } => {
                 if let Some(v) = validator {
-                    NodeValidatorsIter::BooleanValidators(std::iter::once(v))
+                    NodeValidatorsIter::BooleanValidators(std::iter::once(*v))
                 } else {
                     NodeValidatorsIter::NoValidator
                 }
@@ -102,8 +182,40 @@ impl SchemaNode {
             NodeValidators::Keyword(kvals) => {
                 NodeValidatorsIter::KeywordValidators(kvals.validators.iter())
             }
-            NodeValidators::Array { validators } => {
-                NodeValidatorsIter::ArrayValidators(validators.iter())
+        }
+    }
+
+    fn apply_validators<'a>(
+        &'a self,
+        instance: &Value,
+        location: &LazyLocation,
+    ) -> PartialApplication<'a> {
+        match self.validators {
+            NodeValidators::Boolean { ref validator } => {
+                if validator.is_some() {
+                    PartialApplication::invalid_empty(vec![ErrorDescription::from(
+                        self.false_schema_error(instance, location),
+                    )])
+                } else {
+                    PartialApplication::Valid {
+                        annotations: None,
+                        child_results: VecDeque::new(),
+                    }
+                }
+            }
+            NodeValidators::Keyword(ref kvals) => {
+                let KeywordValidators {
+                    ref unmatched_keywords,
+                    ref validators,
+                } = *kvals;
+                let annotations: Option<Annotations<'a>> =
+                    unmatched_keywords.as_ref().map(Annotations::from);
+                self.apply_subschemas(
+                    instance,
+                    location,
+                    validators.iter().map(|(p, v)| (p, v)),
+                    annotations,
+                )
             }
         }
     }
@@ -135,6 +247,15 @@ impl SchemaNode {
         }
     }
 
+    /// The error of a `false` node, located at the node as its validator is shared
+    fn false_schema_error<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+    ) -> ValidationError<'i> {
+        ValidationError::false_schema(self.location.clone(), location.into(), instance)
+    }
+
     /// Create an error output which is marked as occurring at this schema node
     pub(crate) fn error_at(
         &self,
@@ -175,7 +296,7 @@ impl SchemaNode {
         annotations: Option<Annotations<'a>>,
     ) -> PartialApplication<'a>
     where
-        I: Iterator<Item = (P, &'a Box<dyn Validate + Send + Sync + 'a>)> + 'a,
+        I: Iterator<Item = (P, &'a Box<dyn Validate + 'a>)> + 'a,
         P: Into<LocationSegment<'a>> + fmt::Display,
     {
         let mut success_results: VecDeque<OutputUnit<Annotations>> = VecDeque::new();
@@ -251,13 +372,51 @@ impl SchemaNode {
         }
     }
 
+    /// Collect the schema locations of the assertions accepting `instance`, descending into the
+    /// subschemas that make applicators accept it.
+    pub(crate) fn matched_assertions(&self, instance: &Value, matched: &mut Vec<Location>) {
+        let NodeValidators::Keyword(kvals) = &self.validators else {
+            // A `true` schema, or an array-valued one
+            matched.push(self.location.clone());
+            return;
+        };
+        for (keyword, validator) in &kvals.validators {
+            let mut subschemas = Vec::new();
+            validator.matching_subschemas(instance, &mut |node| subschemas.push(node));
+            if subschemas.is_empty() {
+                matched.push(self.location.join(keyword));
+            }
+            for node in subschemas {
+                node.matched_assertions(instance, matched);
+            }
+        }
+    }
+
     pub(crate) fn location(&self) -> &Location {
         &self.location
     }
+
+    /// Stop a cancellable validation, see [`crate::Validator::validate_with_cancel`].
+    #[inline]
+    fn check_cancelled<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+    ) -> Result<(), ValidationError<'i>> {
+        if location.evaluation.is_some_and(Evaluation::is_cancelled) {
+            Err(ValidationError::cancelled(
+                self.location.clone(),
+                location.into(),
+                instance,
+            ))
+        } else {
+            Ok(())
+        }
+    }
 }
 
-impl Validate for SchemaNode {
-    fn iter_errors<'i>(&self, instance: &'i Value, location: &LazyLocation) -> ErrorIterator<'i> {
+impl SchemaNode {
+    fn node_errors<'i>(&self, instance: &'i Value, location: &LazyLocation) -> ErrorIterator<'i> {
         match &self.validators {
             NodeValidators::Keyword(kvs) if kvs.validators.len() == 1 => {
                 kvs.validators[0].1.iter_errors(instance, location)
@@ -270,22 +429,15 @@ impl Validate for SchemaNode {
                     .into_iter(),
             ),
             NodeValidators::Boolean {
-                validator: Some(v), ..
-            } => v.iter_errors(instance, location),
+                validator: Some(_), ..
+            } => Box::new(std::iter::once(self.false_schema_error(instance, location))),
             NodeValidators::Boolean {
                 validator: None, ..
             } => Box::new(std::iter::empty()),
-            NodeValidators::Array { validators } => Box::new(
-                validators
-                    .iter()
-                    .flat_map(move |v| v.iter_errors(instance, location))
-                    .collect::<Vec<_>>()
-                    .into_iter(),
-            ),
         }
     }
 
-    fn validate<'i>(
+    fn validate_node<'i>(
         &self,
         instance: &'i Value,
         location: &LazyLocation,
@@ -296,24 +448,15 @@ impl Validate for SchemaNode {
                     validator.validate(instance, location)?;
                 }
             }
-            NodeValidators::Array { validators } => {
-                for validator in validators {
-                    validator.validate(instance, location)?;
-                }
-            }
             NodeValidators::Boolean { validator: Some(_) } => {
-                return Err(ValidationError::false_schema(
-                    self.location.clone(),
-                    location.into(),
-                    instance,
-                ))
+                return Err(self.false_schema_error(instance, location))
             }
             NodeValidators::Boolean { validator: None } => return Ok(()),
         }
         Ok(())
     }
 
-    fn is_valid(&self, instance: &Value) -> bool {
+    fn is_valid_node(&self, instance: &Value) -> bool {
         match &self.validators {
             // If we only have one validator then calling it's `is_valid` directly does
             // actually save the 20 or so instructions required to call the `slice::Iter::all`
@@ -330,61 +473,93 @@ impl Validate for SchemaNode {
                 }
                 true
             }
-            NodeValidators::Array { validators } => validators.iter().all(|v| v.is_valid(instance)),
             NodeValidators::Boolean { validator: Some(_) } => false,
             NodeValidators::Boolean { validator: None } => true,
         }
     }
 
-    fn apply<'a>(&'a self, instance: &Value, location: &LazyLocation) -> PartialApplication<'a> {
-        match self.validators {
-            NodeValidators::Array { ref validators } => {
-                self.apply_subschemas(instance, location, validators.iter().enumerate(), None)
-            }
-            NodeValidators::Boolean { ref validator } => {
-                if let Some(validator) = validator {
-                    validator.apply(instance, location)
-                } else {
-                    PartialApplication::Valid {
-                        annotations: None,
-                        child_results: VecDeque::new(),
-                    }
-                }
-            }
-            NodeValidators::Keyword(ref kvals) => {
-                let KeywordValidators {
-                    ref unmatched_keywords,
-                    ref validators,
-                } = *kvals;
-                let annotations: Option<Annotations<'a>> =
-                    unmatched_keywords.as_ref().map(Annotations::from);
-                self.apply_subschemas(
-                    instance,
-                    location,
-                    validators.iter().map(|(p, v)| (p, v)),
-                    annotations,
-                )
+    fn apply_node<'a>(
+        &'a self,
+        instance: &Value,
+        location: &LazyLocation,
+    ) -> PartialApplication<'a> {
+        match location.evaluation.and_then(Evaluation::recorder) {
+            Some(recording) => recording.record(
+                &self.location,
+                self.absolute_path.as_ref(),
+                location,
+                || self.apply_validators(instance, location),
+                |result| matches!(result, PartialApplication::Valid { .. }),
+            ),
+            None => self.apply_validators(instance, location),
+        }
+    }
+}
+
+impl Validate for SchemaNode {
+    fn iter_errors<'i>(&self, instance: &'i Value, location: &LazyLocation) -> ErrorIterator<'i> {
+        match &self.profile {
+            // Errors are collected, so the time includes evaluating all of them
+            Some(profile) => {
+                let errors: Vec<_> =
+                    profile.record(|| self.node_errors(instance, location).collect());
+                Box::new(errors.into_iter())
             }
+            None => self.node_errors(instance, location),
+        }
+    }
+
+    fn validate<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+    ) -> Result<(), ValidationError<'i>> {
+        self.check_cancelled(instance, location)?;
+        match &self.profile {
+            Some(profile) => profile.record(|| self.validate_node(instance, location)),
+            None => self.validate_node(instance, location),
+        }
+    }
+
+    fn is_valid(&self, instance: &Value) -> bool {
+        match &self.profile {
+            Some(profile) => profile.record(|| self.is_valid_node(instance)),
+            None => self.is_valid_node(instance),
+        }
+    }
+
+    fn apply<'a>(&'a self, instance: &Value, location: &LazyLocation) -> PartialApplication<'a> {
+        match &self.profile {
+            Some(profile) => profile.record(|| self.apply_node(instance, location)),
+            None => self.apply_node(instance, location),
+        }
+    }
+
+    fn subschemas_at<'s>(
+        &'s self,
+        segment: LocationSegment<'_>,
+        visit: &mut dyn FnMut(&'s SchemaNode),
+    ) {
+        for validator in self.validators() {
+            validator.subschemas_at(segment, visit);
         }
     }
 }
 
 enum NodeValidatorsIter<'a> {
     NoValidator,
-    BooleanValidators(std::iter::Once<&'a BoxedValidator>),
+    BooleanValidators(std::iter::Once<&'a dyn Validate>),
     KeywordValidators(std::slice::Iter<'a, (Keyword, BoxedValidator)>),
-    ArrayValidators(std::slice::Iter<'a, BoxedValidator>),
 }
 
 impl<'a> Iterator for NodeValidatorsIter<'a> {
-    type Item = &'a BoxedValidator;
+    type Item = &'a dyn Validate;
 
     fn next(&mut self) -> Option<Self::Item> {
         match self {
             Self::NoValidator => None,
             Self::BooleanValidators(i) => i.next(),
-            Self::KeywordValidators(v) => v.next().map(|(_, v)| v),
-            Self::ArrayValidators(v) => v.next(),
+            Self::KeywordValidators(v) => v.next().map(|(_, v)| &**v),
         }
     }
 
@@ -396,8 +571,7 @@ impl<'a> Iterator for NodeValidatorsIter<'a> {
         match self {
             Self::NoValidator => true,
             Self::BooleanValidators(i) => i.all(f),
-            Self::KeywordValidators(v) => v.all(|(_, v)| f(v)),
-            Self::ArrayValidators(v) => v.all(f),
+            Self::KeywordValidators(v) => v.all(|(_, v)| f(&**v)),
         }
     }
 }
@@ -408,7 +582,6 @@ impl ExactSizeIterator for NodeValidatorsIter<'_> {
             Self::NoValidator => 0,
             Self::BooleanValidators(..) => 1,
             Self::KeywordValidators(v) => v.len(),
-            Self::ArrayValidators(v) => v.len(),
         }
     }
 }
@@ -0,0 +1,346 @@
+//! Parsing of JSON documents with non-finite numbers.
+//!
+//! Lenient producers, e.g. Python's `json` module, emit `NaN`, `Infinity` and `-Infinity`, which
+//! are not valid JSON. `serde_json` rejects them with a syntax error that does not say which part
+//! of the document is affected. The helpers in this module accept such documents and handle each
+//! non-finite number according to a [`NonFinitePolicy`]:
+//!
+//! ```rust
+//! use jsonschema::non_finite::{self, NonFinitePolicy, ParseError};
+//! use serde_json::json;
+//!
+//! let text = r#"{"readings": [1.5, NaN, -Infinity]}"#;
+//! assert_eq!(
+//!     non_finite::from_str(text, NonFinitePolicy::Null).expect("Valid document"),
+//!     json!({"readings": [1.5, null, null]})
+//! );
+//!
+//! let Err(ParseError::NonFinite(error)) = non_finite::from_str(text, NonFinitePolicy::Reject) else {
+//!     panic!("Should fail");
+//! };
+//! assert_eq!(error.location().as_str(), "/readings/1");
+//! assert_eq!(
+//!     error.to_string(),
+//!     "Non-finite number NaN at /readings/1 (line 1, column 20)"
+//! );
+//! ```
+use std::{
+    error,
+    fmt::{self, Write},
+};
+
+use serde_json::Value;
+
+use crate::{paths::Location, ValidationError, Validator};
+
+/// Literals that stand for non-finite numbers, longest first.
+const LITERALS: [&str; 4] = ["-Infinity", "+Infinity", "Infinity", "NaN"];
+
+/// How non-finite numbers are handled, see the [module documentation](self).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NonFinitePolicy {
+    /// Fail with [`ParseError::NonFinite`] if there are non-finite numbers.
+    #[default]
+    Reject,
+    /// Replace non-finite numbers with `null`.
+    Null,
+    /// Replace non-finite numbers with strings holding their literals, e.g. `"NaN"`.
+    String,
+}
+
+/// A non-finite number rejected by [`NonFinitePolicy::Reject`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NonFiniteError {
+    literal: &'static str,
+    location: Location,
+    line: usize,
+    column: usize,
+}
+
+impl NonFiniteError {
+    /// The literal of the number, e.g. `-Infinity`.
+    #[must_use]
+    pub fn literal(&self) -> &str {
+        self.literal
+    }
+    /// JSON Pointer to the number within the document.
+    #[must_use]
+    pub fn location(&self) -> &Location {
+        &self.location
+    }
+    /// One-based line of the number.
+    #[must_use]
+    pub fn line(&self) -> usize {
+        self.line
+    }
+    /// One-based column of the number, in characters.
+    #[must_use]
+    pub fn column(&self) -> usize {
+        self.column
+    }
+}
+
+impl fmt::Display for NonFiniteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Non-finite number {} at {} (line {}, column {})",
+            self.literal,
+            if self.location.as_str().is_empty() {
+                "the document root"
+            } else {
+                self.location.as_str()
+            },
+            self.line,
+            self.column
+        )
+    }
+}
+
+impl error::Error for NonFiniteError {}
+
+/// An error parsing a document with [`from_str`] or [`validate`].
+#[derive(Debug)]
+pub enum ParseError {
+    /// The document contains a non-finite number and the policy is [`NonFinitePolicy::Reject`].
+    NonFinite(NonFiniteError),
+    /// The document is not valid JSON, apart from non-finite numbers.
+    Json(serde_json::Error),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::NonFinite(error) => error.fmt(f),
+            ParseError::Json(error) => error.fmt(f),
+        }
+    }
+}
+
+impl error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            ParseError::NonFinite(error) => Some(error),
+            ParseError::Json(error) => Some(error),
+        }
+    }
+}
+
+impl From<serde_json::Error> for ParseError {
+    fn from(error: serde_json::Error) -> ParseError {
+        ParseError::Json(error)
+    }
+}
+
+/// A non-finite literal found in the document text.
+struct Occurrence {
+    literal: &'static str,
+    line: usize,
+    column: usize,
+}
+
+/// Replace non-finite literals outside of strings with placeholder strings made of `marker` and
+/// the index of the occurrence.
+fn substitute(text: &str, marker: &str) -> (String, Vec<Occurrence>) {
+    let mut output = String::with_capacity(text.len());
+    let mut occurrences = Vec::new();
+    let (mut line, mut column) = (1, 1);
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut rest = text;
+    while let Some(ch) = rest.chars().next() {
+        if !in_string {
+            if let Some(literal) = LITERALS.iter().find(|literal| rest.starts_with(**literal)) {
+                let _ = write!(output, "\"{marker}{}\"", occurrences.len());
+                occurrences.push(Occurrence {
+                    literal,
+                    line,
+                    column,
+                });
+                column += literal.len();
+                rest = &rest[literal.len()..];
+                continue;
+            }
+        }
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if ch == '\\' {
+                escaped = true;
+            } else if ch == '"' {
+                in_string = false;
+            }
+        } else if ch == '"' {
+            in_string = true;
+        }
+        if ch == '\n' {
+            line += 1;
+            column = 1;
+        } else {
+            column += 1;
+        }
+        output.push(ch);
+        rest = &rest[ch.len_utf8()..];
+    }
+    (output, occurrences)
+}
+
+/// Apply the policy to the placeholders in `value`.
+fn restore(
+    value: &mut Value,
+    marker: &str,
+    occurrences: &[Occurrence],
+    policy: NonFinitePolicy,
+    location: &Location,
+) -> Result<(), NonFiniteError> {
+    match value {
+        Value::String(string) => {
+            let Some(occurrence) = string
+                .strip_prefix(marker)
+                .and_then(|idx| idx.parse::<usize>().ok())
+                .and_then(|idx| occurrences.get(idx))
+            else {
+                return Ok(());
+            };
+            *value = match policy {
+                NonFinitePolicy::Reject => {
+                    return Err(NonFiniteError {
+                        literal: occurrence.literal,
+                        location: location.clone(),
+                        line: occurrence.line,
+                        column: occurrence.column,
+                    })
+                }
+                NonFinitePolicy::Null => Value::Null,
+                NonFinitePolicy::String => {
+                    Value::String(occurrence.literal.trim_start_matches('+').to_string())
+                }
+            };
+        }
+        Value::Array(items) => {
+            for (idx, item) in items.iter_mut().enumerate() {
+                restore(item, marker, occurrences, policy, &location.join(idx))?;
+            }
+        }
+        Value::Object(object) => {
+            for (key, item) in object.iter_mut() {
+                restore(item, marker, occurrences, policy, &location.join(key))?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Parse a JSON document that may contain `NaN`, `Infinity`, `+Infinity` or `-Infinity`,
+/// handling them according to `policy`.
+///
+/// # Errors
+///
+/// Returns an error if the document is not valid JSON apart from non-finite numbers, or if it
+/// contains a non-finite number and the policy is [`NonFinitePolicy::Reject`].
+pub fn from_str(text: &str, policy: NonFinitePolicy) -> Result<Value, ParseError> {
+    // Extend the marker until it does not occur in the document, so strings there are kept
+    let mut marker = String::from("\\u0000non-finite:");
+    while text.contains(marker.as_str()) {
+        marker.push('~');
+    }
+    let (substituted, occurrences) = substitute(text, &marker);
+    let mut value: Value = serde_json::from_str(&substituted)?;
+    if !occurrences.is_empty() {
+        let marker = marker.replacen("\\u0000", "\0", 1);
+        restore(&mut value, &marker, &occurrences, policy, &Location::new())
+            .map_err(ParseError::NonFinite)?;
+    }
+    Ok(value)
+}
+
+/// Parse a JSON document as [`from_str`] does, and validate it.
+///
+/// Returns the parsed instance together with its validation errors, which are empty if it is
+/// valid.
+///
+/// # Errors
+///
+/// Returns an error if the document can not be parsed.
+///
+/// # Example
+///
+/// ```rust
+/// use jsonschema::non_finite::{self, NonFinitePolicy};
+/// use serde_json::json;
+///
+/// let validator = jsonschema::validator_for(&json!({"items": {"type": "number"}}))
+///     .expect("Invalid schema");
+/// let (instance, errors) = non_finite::validate(&validator, "[1, Infinity]", NonFinitePolicy::String)
+///     .expect("Valid document");
+/// assert_eq!(instance, json!([1, "Infinity"]));
+/// assert_eq!(errors[0].instance_path.as_str(), "/1");
+/// ```
+pub fn validate(
+    validator: &Validator,
+    text: &str,
+    policy: NonFinitePolicy,
+) -> Result<(Value, Vec<ValidationError<'static>>), ParseError> {
+    let instance = from_str(text, policy)?;
+    let errors = validator
+        .iter_errors(&instance)
+        .map(ValidationError::to_owned)
+        .collect();
+    Ok((instance, errors))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use test_case::test_case;
+
+    #[test_case("NaN", NonFinitePolicy::Null, &json!(null))]
+    #[test_case("[Infinity, -Infinity, +Infinity]", NonFinitePolicy::String, &json!(["Infinity", "-Infinity", "Infinity"]))]
+    #[test_case(r#"{"a": {"b": NaN}, "c": 1e3}"#, NonFinitePolicy::Null, &json!({"a": {"b": null}, "c": 1000.0}))]
+    #[test_case(r#"["NaN", "say \"Infinity\"", NaN]"#, NonFinitePolicy::String, &json!(["NaN", "say \"Infinity\"", "NaN"]); "strings are kept")]
+    #[test_case(r#"["\u0000non-finite:0", NaN]"#, NonFinitePolicy::Null, &json!(["\u{0}non-finite:0", null]); "placeholder lookalikes")]
+    #[test_case("[1, 2]", NonFinitePolicy::Reject, &json!([1, 2]); "finite")]
+    fn parse(text: &str, policy: NonFinitePolicy, expected: &Value) {
+        assert_eq!(&from_str(text, policy).expect("Valid document"), expected);
+    }
+
+    #[test_case(
+        "NaN",
+        "",
+        1,
+        1,
+        "Non-finite number NaN at the document root (line 1, column 1)"
+    )]
+    #[test_case(
+        "{\n  \"a/b\": [0,\n    -Infinity]\n}",
+        "/a~1b/1",
+        3,
+        5,
+        "Non-finite number -Infinity at /a~1b/1 (line 3, column 5)"
+    )]
+    #[test_case(
+        "{\"é\": NaN}",
+        "/é",
+        1,
+        7,
+        "Non-finite number NaN at /é (line 1, column 7)"
+    )]
+    fn reject(text: &str, location: &str, line: usize, column: usize, message: &str) {
+        let Err(ParseError::NonFinite(error)) = from_str(text, NonFinitePolicy::Reject) else {
+            panic!("Should fail");
+        };
+        assert_eq!(error.location().as_str(), location);
+        assert_eq!((error.line(), error.column()), (line, column));
+        assert_eq!(error.to_string(), message);
+    }
+
+    #[test]
+    fn invalid_json() {
+        assert!(matches!(
+            from_str("[NaN,", NonFinitePolicy::Null),
+            Err(ParseError::Json(_))
+        ));
+    }
+}
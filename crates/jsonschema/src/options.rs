@@ -5,15 +5,24 @@ use crate::{
         DEFAULT_CONTENT_ENCODING_CHECKS_AND_CONVERTERS,
     },
     content_media_type::{ContentMediaTypeCheckType, DEFAULT_CONTENT_MEDIA_TYPE_CHECKS},
-    keywords::{custom::KeywordFactory, format::Format},
-    paths::Location,
+    dialect::Dialect,
+    error::SizeLimitKind,
+    keywords::{custom::KeywordFactory, format::Format, property_groups},
+    paths::{LazyLocation, Location},
+    preprocess::Preprocessor,
     retriever::DefaultRetriever,
-    Keyword, ValidationError, Validator,
+    variables, Keyword, ValidationError, Validator,
 };
 use ahash::AHashMap;
-use referencing::{uri, Draft, Resource, Retrieve};
+use once_cell::sync::OnceCell;
+use referencing::{uri, Draft, Resource, Retrieve, UriRewriter, VocabularySet};
 use serde_json::Value;
-use std::{fmt, marker::PhantomData, sync::Arc};
+use std::{
+    fmt,
+    marker::PhantomData,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
 /// Configuration options for JSON Schema validation.
 #[derive(Clone)]
@@ -27,13 +36,31 @@ pub struct ValidationOptions<R = Arc<dyn Retrieve>> {
     pub(crate) retriever: R,
     /// Additional resources that should be addressable during validation.
     pub(crate) resources: AHashMap<String, Resource>,
+    ref_overrides: AHashMap<String, Value>,
     pub(crate) registry: Option<referencing::Registry>,
+    pub(crate) document_cache: Option<referencing::DocumentCache>,
+    pub(crate) retrieval_limits: Option<(usize, usize, usize)>,
+    pub(crate) strict_identifiers: bool,
+    pub(crate) uri_rewriter: Option<Arc<dyn UriRewriter>>,
     formats: AHashMap<String, Arc<dyn Format>>,
     validate_formats: Option<bool>,
     pub(crate) validate_schema: bool,
     ignore_unknown_formats: bool,
+    reject_unknown_specifications: bool,
+    report_missing_properties: bool,
     keywords: AHashMap<String, Arc<dyn KeywordFactory>>,
     pattern_options: PatternEngineOptions,
+    share_regexes: bool,
+    size_limits: SizeLimits,
+    date_bounds: DateBounds,
+    key_decoder: Option<Arc<dyn KeyDecoder>>,
+    preprocessors: Vec<Arc<dyn Preprocessor>>,
+    profile: bool,
+    diagnostics: bool,
+    compilation_limits: CompilationLimits,
+    dialects: Vec<Dialect>,
+    value_comparison: ValueComparison,
+    variables: Option<Value>,
 }
 
 impl Default for ValidationOptions<Arc<dyn Retrieve>> {
@@ -45,13 +72,31 @@ impl Default for ValidationOptions<Arc<dyn Retrieve>> {
             base_uri: None,
             retriever: Arc::new(DefaultRetriever),
             resources: AHashMap::default(),
+            ref_overrides: AHashMap::default(),
             registry: None,
+            document_cache: None,
+            retrieval_limits: None,
+            strict_identifiers: false,
+            uri_rewriter: None,
             formats: AHashMap::default(),
             validate_formats: None,
             validate_schema: true,
             ignore_unknown_formats: true,
+            reject_unknown_specifications: false,
+            report_missing_properties: false,
             keywords: AHashMap::default(),
             pattern_options: PatternEngineOptions::default(),
+            share_regexes: true,
+            size_limits: SizeLimits::default(),
+            date_bounds: DateBounds::default(),
+            key_decoder: None,
+            preprocessors: Vec::new(),
+            profile: false,
+            diagnostics: false,
+            compilation_limits: CompilationLimits::default(),
+            dialects: Vec::new(),
+            value_comparison: ValueComparison::default(),
+            variables: None,
         }
     }
 }
@@ -66,13 +111,31 @@ impl Default for ValidationOptions<Arc<dyn referencing::AsyncRetrieve>> {
             base_uri: None,
             retriever: Arc::new(DefaultRetriever),
             resources: AHashMap::default(),
+            ref_overrides: AHashMap::default(),
             registry: None,
+            document_cache: None,
+            retrieval_limits: None,
+            strict_identifiers: false,
+            uri_rewriter: None,
             formats: AHashMap::default(),
             validate_formats: None,
             validate_schema: true,
             ignore_unknown_formats: true,
+            reject_unknown_specifications: false,
+            report_missing_properties: false,
             keywords: AHashMap::default(),
             pattern_options: PatternEngineOptions::default(),
+            share_regexes: true,
+            size_limits: SizeLimits::default(),
+            date_bounds: DateBounds::default(),
+            key_decoder: None,
+            preprocessors: Vec::new(),
+            profile: false,
+            diagnostics: false,
+            compilation_limits: CompilationLimits::default(),
+            dialects: Vec::new(),
+            value_comparison: ValueComparison::default(),
+            variables: None,
         }
     }
 }
@@ -315,6 +378,37 @@ impl<R> ValidationOptions<R> {
         }
         self
     }
+    /// Resolve references to these absolute URIs to the given in-memory schemas, e.g. to stub out
+    /// remote dependencies in tests.
+    ///
+    /// Overrides take precedence over resources added via [`Self::with_resource`] and are never
+    /// retrieved, but documents already in a registry set via [`Self::with_registry`] are kept.
+    /// Their drafts are detected when the validator is built, as for the main schema.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use serde_json::json;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let schema = json!({"$ref": "https://example.com/remote.json"});
+    /// let validator = jsonschema::options()
+    ///     .with_ref_overrides([("https://example.com/remote.json", json!({"type": "integer"}))])
+    ///     .build(&schema)?;
+    /// assert!(validator.is_valid(&json!(5)));
+    /// assert!(!validator.is_valid(&json!("5")));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_ref_overrides<I, U>(mut self, overrides: I) -> Self
+    where
+        I: IntoIterator<Item = (U, Value)>,
+        U: Into<String>,
+    {
+        for (uri, schema) in overrides {
+            self.ref_overrides.insert(uri.into(), schema);
+        }
+        self
+    }
     /// Use external schema resources from the registry, making them accessible via references
     /// during validation.
     ///
@@ -346,6 +440,108 @@ impl<R> ValidationOptions<R> {
         self.registry = Some(registry);
         self
     }
+    /// Share retrieved documents between validators built with the same [`DocumentCache`](crate::DocumentCache).
+    ///
+    /// Validators keep alive only the retrieved documents they still need, e.g. for recursive
+    /// references, while the cache keeps a bounded number of recently used documents on top of
+    /// that. This bounds memory usage in long-running processes compiling many transient schemas
+    /// with common remote references.
+    /// The cache is not used for validators built with a registry via [`Self::with_registry`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use serde_json::json;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use jsonschema::DocumentCache;
+    ///
+    /// let cache = DocumentCache::new(64);
+    /// let validator = jsonschema::options()
+    ///     .with_document_cache(cache.clone())
+    ///     .build(&json!({"type": "string"}))?;
+    /// assert!(validator.is_valid(&json!("foo")));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_document_cache(mut self, cache: referencing::DocumentCache) -> Self {
+        self.document_cache = Some(cache);
+        self
+    }
+    /// Limit the documents retrieved while building a validator.
+    ///
+    /// * `max_documents` - The number of retrieved documents.
+    /// * `max_total_bytes` - The total size of retrieved documents, in bytes read by the retriever.
+    /// * `max_ref_depth` - The length of reference chains between retrieved documents.
+    ///
+    /// Building fails with [`referencing::Error::LimitExceeded`] as soon as any limit is
+    /// exceeded. See [`RegistryOptions::limits`](referencing::RegistryOptions::limits) for
+    /// details. Validators built with a registry via [`Self::with_registry`] use the limits that
+    /// registry was built with instead.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use serde_json::json;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let validator = jsonschema::options()
+    ///     .with_retrieval_limits(16, 1024 * 1024, 4)
+    ///     .build(&json!({"type": "string"}))?;
+    /// assert!(validator.is_valid(&json!("foo")));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_retrieval_limits(
+        mut self,
+        max_documents: usize,
+        max_total_bytes: usize,
+        max_ref_depth: usize,
+    ) -> Self {
+        self.retrieval_limits = Some((max_documents, max_total_bytes, max_ref_depth));
+        self
+    }
+    /// Reject schemas and retrieved documents declaring identifiers that violate the
+    /// specification, e.g. non-normalized `$id` URIs or invalid `$anchor` names.
+    ///
+    /// Building fails with [`referencing::Error::InvalidIdentifier`], see
+    /// [`RegistryOptions::strict_identifiers`](referencing::RegistryOptions::strict_identifiers)
+    /// for details. Validators built with a registry via [`Self::with_registry`] check
+    /// identifiers as that registry was built to instead. Disabled by default.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use serde_json::json;
+    /// let result = jsonschema::options()
+    ///     .with_strict_identifiers(true)
+    ///     .build(&json!({"$defs": {"a": {"$anchor": "1st"}}}));
+    /// assert!(result.is_err());
+    /// ```
+    pub fn with_strict_identifiers(mut self, strict: bool) -> Self {
+        self.strict_identifiers = strict;
+        self
+    }
+    /// Rewrite URIs of referenced documents before retrieving them, e.g. to redirect a schema
+    /// host to another environment.
+    ///
+    /// References still resolve as written, see [`UriRewriter`] for details. Validators built
+    /// with a registry via [`Self::with_registry`] use the rewriter that registry was built with
+    /// instead.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use jsonschema::Uri;
+    ///
+    /// let options = jsonschema::options().with_uri_rewriter(|uri: &Uri<String>| {
+    ///     uri.as_str()
+    ///         .strip_prefix("https://schemas.prod/")
+    ///         .and_then(|path| Uri::parse(format!("https://schemas.staging/{path}")).ok())
+    /// });
+    /// ```
+    pub fn with_uri_rewriter(mut self, rewriter: impl UriRewriter + 'static) -> Self {
+        self.uri_rewriter = Some(Arc::new(rewriter));
+        self
+    }
     /// Register a custom format validator.
     ///
     /// # Example
@@ -411,6 +607,74 @@ impl<R> ValidationOptions<R> {
     pub(crate) const fn are_unknown_formats_ignored(&self) -> bool {
         self.ignore_unknown_formats
     }
+    /// Set whether to reject schemas with an unknown `$schema`.
+    ///
+    /// By default, a `$schema` that is neither a known draft nor a meta-schema available via
+    /// resources or the retriever enables the default vocabularies of the draft in use. Set to
+    /// `true` to fail compilation instead, so typos in `$schema` don't silently change the
+    /// validation semantics.
+    ///
+    /// ```rust
+    /// # use serde_json::json;
+    /// let schema = json!({"$schema": "https://json-schema.org/draft/2020-12/shcema"});
+    /// let result = jsonschema::options()
+    ///     .with_draft(jsonschema::Draft::Draft202012)
+    ///     .should_reject_unknown_specifications(true)
+    ///     .build(&schema);
+    /// assert!(result.is_err());
+    /// ```
+    pub fn should_reject_unknown_specifications(mut self, yes: bool) -> Self {
+        self.reject_unknown_specifications = yes;
+        self
+    }
+    /// Vocabularies enabled by the `$schema` of `contents`.
+    pub(crate) fn vocabularies_for(
+        &self,
+        registry: &referencing::Registry,
+        draft: Draft,
+        contents: &Value,
+    ) -> Result<VocabularySet, referencing::Error> {
+        if self.reject_unknown_specifications {
+            registry.try_find_vocabularies(draft, contents)
+        } else {
+            Ok(registry.find_vocabularies(draft, contents))
+        }
+    }
+    /// Set whether to report all required properties missing from an object as a single error.
+    ///
+    /// The error has the [`ValidationErrorKind::MissingProperties`] kind and lists the properties
+    /// in its `properties` parameter. It is reported after the individual `required` errors, e.g.
+    /// for forms that show one summary per object. Fail-fast validation still stops at the first
+    /// missing property.
+    ///
+    /// ```rust
+    /// # use serde_json::json;
+    /// let validator = jsonschema::options()
+    ///     .should_report_missing_properties(true)
+    ///     .build(&json!({"required": ["a", "b", "c"]}))
+    ///     .expect("A valid schema");
+    /// let messages: Vec<_> = validator
+    ///     .iter_errors(&json!({"b": 1}))
+    ///     .map(|error| error.to_string())
+    ///     .collect();
+    /// assert_eq!(
+    ///     messages,
+    ///     [
+    ///         r#""a" is a required property"#,
+    ///         r#""c" is a required property"#,
+    ///         r#"Missing required properties: "a", "c""#,
+    ///     ]
+    /// );
+    /// ```
+    ///
+    /// [`ValidationErrorKind::MissingProperties`]: crate::error::ValidationErrorKind::MissingProperties
+    pub fn should_report_missing_properties(mut self, yes: bool) -> Self {
+        self.report_missing_properties = yes;
+        self
+    }
+    pub(crate) const fn reports_missing_properties(&self) -> bool {
+        self.report_missing_properties
+    }
     /// Register a custom keyword validator.
     ///
     /// ## Example
@@ -482,8 +746,386 @@ impl<R> ValidationOptions<R> {
         self
     }
 
-    pub(crate) fn get_keyword_factory(&self, name: &str) -> Option<&Arc<dyn KeywordFactory>> {
-        self.keywords.get(name)
+    /// The factory of a custom keyword, falling back to the keywords of the dialect at index
+    /// `dialect` (see `dialect_index`).
+    pub(crate) fn get_keyword_factory(
+        &self,
+        name: &str,
+        dialect: Option<usize>,
+    ) -> Option<&Arc<dyn KeywordFactory>> {
+        self.keywords
+            .get(name)
+            .or_else(|| dialect.and_then(|idx| self.dialects[idx].keywords.get(name)))
+    }
+    /// Enable non-standard keywords that constrain which properties of a group may be present:
+    ///
+    /// - `x-requiresOneOf`: exactly one of the listed properties must be present;
+    /// - `x-requiresAnyOf`: at least one of the listed properties must be present;
+    /// - `x-mutuallyExclusive`: at most one of the listed properties may be present.
+    ///
+    /// Each keyword takes an array of property names and only applies to objects.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    ///
+    /// let schema = json!({"x-requiresOneOf": ["email", "phone"]});
+    /// let validator = jsonschema::options()
+    ///     .with_property_group_keywords()
+    ///     .build(&schema)
+    ///     .expect("A valid schema");
+    ///
+    /// assert!(validator.is_valid(&json!({"email": "user@example.com"})));
+    /// assert!(!validator.is_valid(&json!({"email": "user@example.com", "phone": "123"})));
+    /// ```
+    pub fn with_property_group_keywords(self) -> Self {
+        self.with_keyword(
+            property_groups::REQUIRES_ONE_OF,
+            property_groups::compile_requires_one_of,
+        )
+        .with_keyword(
+            property_groups::REQUIRES_ANY_OF,
+            property_groups::compile_requires_any_of,
+        )
+        .with_keyword(
+            property_groups::MUTUALLY_EXCLUSIVE,
+            property_groups::compile_mutually_exclusive,
+        )
+    }
+    /// Set limits on instance size characteristics that are enforced during validation.
+    ///
+    /// Instances exceeding any of these limits are rejected with
+    /// [`ValidationErrorKind::SizeLimitExceeded`](crate::error::ValidationErrorKind::SizeLimitExceeded)
+    /// before the expensive parts of validation run. This is useful when validating
+    /// untrusted documents.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    /// use jsonschema::SizeLimits;
+    ///
+    /// let schema = json!({"type": "array"});
+    /// let validator = jsonschema::options()
+    ///     .with_size_limits(SizeLimits::new().max_items(2))
+    ///     .build(&schema)
+    ///     .expect("A valid schema");
+    ///
+    /// assert!(validator.is_valid(&json!([1, 2])));
+    /// assert!(!validator.is_valid(&json!([1, 2, 3])));
+    /// ```
+    pub fn with_size_limits(mut self, limits: SizeLimits) -> Self {
+        self.size_limits = limits;
+        self
+    }
+    pub(crate) fn size_limits(&self) -> &SizeLimits {
+        &self.size_limits
+    }
+    /// Set limits on schema compilation, so schemas with reference cycles that can not be
+    /// compiled lazily, or with huge numbers of subschemas, fail to build with an error instead
+    /// of exhausting the stack or memory.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    /// use jsonschema::{
+    ///     error::{CompilationLimitKind, ValidationErrorKind},
+    ///     CompilationLimits,
+    /// };
+    ///
+    /// let schema = json!({"properties": {"a": true, "b": true}});
+    /// let error = jsonschema::options()
+    ///     .with_compilation_limits(CompilationLimits::new().max_subschemas(2))
+    ///     .build(&schema)
+    ///     .expect_err("Too many subschemas");
+    /// assert_eq!(
+    ///     error.to_string(),
+    ///     "Schema compiles to more than the limit of 2 subschemas"
+    /// );
+    /// assert!(matches!(
+    ///     error.kind,
+    ///     ValidationErrorKind::CompilationLimitExceeded {
+    ///         kind: CompilationLimitKind::Subschemas,
+    ///         limit: 2,
+    ///         ..
+    ///     }
+    /// ));
+    /// ```
+    pub fn with_compilation_limits(mut self, limits: CompilationLimits) -> Self {
+        self.compilation_limits = limits;
+        self
+    }
+    pub(crate) fn compilation_limits(&self) -> &CompilationLimits {
+        &self.compilation_limits
+    }
+    /// Decode object keys before validating them against `propertyNames`, so the key schema
+    /// can describe typed keys, e.g. the integer keys of JSON-encoded protobuf maps.
+    ///
+    /// Keys are validated as strings by default. Errors keep the original key in
+    /// [`ValidationErrorKind::PropertyNames`](crate::error::ValidationErrorKind::PropertyNames).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    /// use jsonschema::TypedKeys;
+    ///
+    /// let schema = json!({"propertyNames": {"type": "integer", "minimum": 0}});
+    /// let validator = jsonschema::options()
+    ///     .with_key_decoder(TypedKeys)
+    ///     .build(&schema)
+    ///     .expect("Invalid schema");
+    /// assert!(validator.is_valid(&json!({"1": "a", "42": "b"})));
+    /// assert!(!validator.is_valid(&json!({"-1": "a"})));
+    /// assert!(!validator.is_valid(&json!({"one": "a"})));
+    /// ```
+    pub fn with_key_decoder(mut self, decoder: impl KeyDecoder) -> Self {
+        self.key_decoder = Some(Arc::new(decoder));
+        self
+    }
+    pub(crate) fn key_decoder(&self) -> Option<&Arc<dyn KeyDecoder>> {
+        self.key_decoder.as_ref()
+    }
+    /// Add a transformation of instances, applied before validation by
+    /// [`Validator::validate_batch`] and [`Validator::preprocess`].
+    ///
+    /// Preprocessors are applied in the order they are added and each transformation is recorded,
+    /// so reports show which values were changed. Other validation methods take instances as they
+    /// are, pass them through [`Validator::preprocess`] first to apply preprocessors.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    /// use jsonschema::TrimStrings;
+    ///
+    /// let schema = json!({"enum": ["Cafe"]});
+    /// let validator = jsonschema::options()
+    ///     .with_preprocessor(TrimStrings)
+    ///     .build(&schema)
+    ///     .expect("Invalid schema");
+    /// let instance = json!(" Cafe");
+    /// assert!(!validator.is_valid(&instance));
+    ///
+    /// let preprocessed = validator.preprocess(&instance);
+    /// assert!(validator.is_valid(preprocessed.instance()));
+    /// assert_eq!(preprocessed.transformations().len(), 1);
+    ///
+    /// let report = validator.validate_batch([("instance.json", &instance)]);
+    /// assert!(report.is_valid());
+    /// assert_eq!(report.instances()[0].transformations()[0].preprocessor(), "trimStrings");
+    /// ```
+    ///
+    /// [`Validator::validate_batch`]: crate::Validator::validate_batch
+    /// [`Validator::preprocess`]: crate::Validator::preprocess
+    pub fn with_preprocessor(mut self, preprocessor: impl Preprocessor) -> Self {
+        self.preprocessors.push(Arc::new(preprocessor));
+        self
+    }
+    pub(crate) fn preprocessors(&self) -> &[Arc<dyn Preprocessor>] {
+        &self.preprocessors
+    }
+    /// Count evaluations of each subschema and measure the time spent in them, to find the
+    /// subschemas responsible for slow validation. Disabled by default, as it slows down
+    /// validation.
+    ///
+    /// Statistics accumulate across validations until [`Validator::reset_profile`] and are
+    /// grouped by the canonical location of subschemas, so a definition referenced from several
+    /// places has a single entry.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    ///
+    /// let schema = json!({
+    ///     "properties": {"a": {"$ref": "#/$defs/name"}, "b": {"$ref": "#/$defs/name"}},
+    ///     "$defs": {"name": {"pattern": "^[a-z]+$"}}
+    /// });
+    /// let validator = jsonschema::options()
+    ///     .should_profile(true)
+    ///     .build(&schema)
+    ///     .expect("Invalid schema");
+    /// assert!(validator.is_valid(&json!({"a": "x", "b": "y"})));
+    ///
+    /// let profile = validator.profile();
+    /// let entry = profile
+    ///     .iter()
+    ///     .find(|entry| entry.location() == "/$defs/name")
+    ///     .expect("Evaluated");
+    /// assert_eq!(entry.evaluations(), 2);
+    /// ```
+    ///
+    /// [`Validator::reset_profile`]: crate::Validator::reset_profile
+    pub fn should_profile(mut self, yes: bool) -> Self {
+        self.profile = yes;
+        self
+    }
+    pub(crate) const fn profiles(&self) -> bool {
+        self.profile
+    }
+    /// Look for likely authoring bugs, e.g. subschemas that can never be satisfied, while
+    /// compiling, and list them in [`Validator::diagnostics`]. Disabled by default, as it adds
+    /// work to every compilation.
+    ///
+    /// [`Validator::diagnostics`]: crate::Validator::diagnostics
+    pub fn should_report_diagnostics(mut self, yes: bool) -> Self {
+        self.diagnostics = yes;
+        self
+    }
+    pub(crate) const fn reports_diagnostics(&self) -> bool {
+        self.diagnostics
+    }
+    /// Reject `date` and `date-time` values outside of these bounds when formats are validated.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::time::{Duration, UNIX_EPOCH};
+    /// use serde_json::json;
+    /// use jsonschema::{DateBounds, TimeBound};
+    ///
+    /// let schema = json!({"format": "date-time"});
+    /// let now = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+    /// let validator = jsonschema::options()
+    ///     .should_validate_formats(true)
+    ///     .with_date_bounds(
+    ///         DateBounds::new()
+    ///             .earliest(TimeBound::At(UNIX_EPOCH))
+    ///             .latest(TimeBound::FromNow(0))
+    ///             .clock(move || now),
+    ///     )
+    ///     .build(&schema)
+    ///     .expect("A valid schema");
+    ///
+    /// assert!(validator.is_valid(&json!("2023-01-01T00:00:00Z")));
+    /// assert!(!validator.is_valid(&json!("1969-12-31T23:59:59Z")));
+    /// assert!(!validator.is_valid(&json!("2024-01-01T00:00:00Z")));
+    /// ```
+    pub fn with_date_bounds(mut self, bounds: DateBounds) -> Self {
+        self.date_bounds = bounds;
+        self
+    }
+    pub(crate) fn date_bounds(&self) -> &DateBounds {
+        &self.date_bounds
+    }
+    /// Set how `enum` and `const` compare instances with the expected values.
+    ///
+    /// By default, values are compared as the JSON Schema specification requires, e.g. `1` and
+    /// `1.0` are equal, and strings are compared exactly.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use serde_json::json;
+    /// # use jsonschema::ValueComparison;
+    /// let validator = jsonschema::options()
+    ///     .with_value_comparison(ValueComparison::new().case_insensitive_strings(true))
+    ///     .build(&json!({"enum": ["red", "green"]}))
+    ///     .expect("A valid schema");
+    ///
+    /// assert!(validator.is_valid(&json!("RED")));
+    /// assert!(!validator.is_valid(&json!("blue")));
+    /// ```
+    pub fn with_value_comparison(mut self, comparison: ValueComparison) -> Self {
+        self.value_comparison = comparison;
+        self
+    }
+    pub(crate) fn value_comparison(&self) -> &ValueComparison {
+        &self.value_comparison
+    }
+    /// Substitute schema variables from `context` before compilation.
+    ///
+    /// Objects consisting of a single `$var` member with a dot-separated path, e.g.
+    /// `{"$var": "limits.batch"}`, are replaced with the value at this path in `context`, where
+    /// numeric segments index arrays. This applies to the schema and to the schemas set via
+    /// [`Self::with_ref_overrides`], so one schema source can be specialized, e.g. per tenant.
+    /// Substituted values are used as they are, without substituting variables within them.
+    /// Values of `const`, `enum`, `default` and `examples` are instance data and are kept as
+    /// they are. Documents added via [`Self::with_resource`], [`Self::with_registry`] or
+    /// retrieved while building are not substituted either.
+    ///
+    /// Building the validator fails with [`ValidationErrorKind::UndefinedVariable`] if a variable
+    /// is not defined in `context`, pointing to the placeholder with its `schema_path`.
+    ///
+    /// [`ValidationErrorKind::UndefinedVariable`]: crate::error::ValidationErrorKind::UndefinedVariable
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use serde_json::json;
+    /// let schema = json!({"type": "array", "maxItems": {"$var": "limits.batch"}});
+    /// let validator = jsonschema::options()
+    ///     .with_variables(json!({"limits": {"batch": 2}}))
+    ///     .build(&schema)
+    ///     .expect("A valid schema");
+    ///
+    /// assert!(validator.is_valid(&json!([1, 2])));
+    /// assert!(!validator.is_valid(&json!([1, 2, 3])));
+    /// ```
+    pub fn with_variables(mut self, context: Value) -> Self {
+        self.variables = Some(context);
+        self
+    }
+    /// The schema with its variables substituted, or `None` if it has none.
+    pub(crate) fn substitute_variables(
+        &self,
+        schema: &Value,
+    ) -> Result<Option<Value>, ValidationError<'static>> {
+        match &self.variables {
+            Some(context) => variables::substitute(schema, context),
+            None => Ok(None),
+        }
+    }
+    /// Register a custom [`Dialect`].
+    ///
+    /// Schemas declaring the dialect URI in `$schema` are compiled with the dialect draft and
+    /// keywords, and its meta-schema becomes addressable by the dialect URI.
+    /// Registering a dialect with the same URI again replaces the previous one.
+    pub fn with_dialect(mut self, dialect: Dialect) -> Self {
+        self.dialects
+            .retain(|existing| existing.id() != dialect.id());
+        self.dialects.push(dialect);
+        self
+    }
+    fn dialect_for(&self, contents: &Value) -> Option<&Dialect> {
+        self.dialects
+            .iter()
+            .find(|dialect| dialect.is_declared_by(contents))
+    }
+    /// Turn reference overrides into resources, replacing resources with the same URI.
+    pub(crate) fn add_ref_overrides(
+        &self,
+        draft: Draft,
+        resources: &mut AHashMap<String, Resource>,
+    ) -> Result<(), ValidationError<'static>> {
+        for (uri, schema) in &self.ref_overrides {
+            let schema = self
+                .substitute_variables(schema)?
+                .unwrap_or_else(|| schema.clone());
+            let resource = draft.detect(&schema)?.create_resource(schema);
+            resources.insert(uri.clone(), resource);
+        }
+        Ok(())
+    }
+    /// Make the meta-schemas of all dialects addressable, replacing resources with the same URI.
+    pub(crate) fn add_dialect_resources(
+        &self,
+        resources: &mut AHashMap<String, Resource>,
+    ) -> Result<(), referencing::Error> {
+        for dialect in &self.dialects {
+            resources.insert(dialect.id().to_string(), dialect.meta_schema()?);
+        }
+        Ok(())
+    }
+    /// Index of the dialect declared by `contents`, whose keywords are enabled for the validator
+    /// being built. Keywords set via `with_keyword` take precedence.
+    pub(crate) fn dialect_index(&self, contents: &Value) -> Option<usize> {
+        self.dialects
+            .iter()
+            .position(|dialect| dialect.is_declared_by(contents))
     }
 }
 
@@ -509,14 +1151,17 @@ impl ValidationOptions<Arc<dyn referencing::Retrieve>> {
     pub(crate) fn draft_for(&self, contents: &Value) -> Result<Draft, ValidationError<'static>> {
         // Preference:
         //  - Explicitly set
+        //  - Registered dialect
         //  - Autodetected
         //  - Default
         if let Some(draft) = self.draft {
-            Ok(draft)
+            enabled(draft)
+        } else if let Some(dialect) = self.dialect_for(contents) {
+            enabled(dialect.draft())
         } else {
             let default = Draft::default();
             match default.detect(contents) {
-                Ok(draft) => Ok(draft),
+                Ok(draft) => enabled(draft),
                 Err(referencing::Error::UnknownSpecification { specification }) => {
                     // Try to retrieve the specification and detect its draft
                     if let Ok(Ok(retrieved)) =
@@ -568,6 +1213,32 @@ impl ValidationOptions<Arc<dyn referencing::Retrieve>> {
     pub(crate) fn pattern_options(&self) -> PatternEngineOptions {
         self.pattern_options
     }
+    /// Reuse regexes compiled for other validators in the process, and let them reuse the ones
+    /// compiled for this validator. Enabled by default.
+    ///
+    /// The shared cache is bounded and evicts the least recently used regexes. Disable it when
+    /// building validators from untrusted schemas, so their patterns do not evict the ones of
+    /// other validators.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    ///
+    /// let schema = json!({"pattern": "^[a-z]+$"});
+    /// let validator = jsonschema::options()
+    ///     .should_share_regexes(false)
+    ///     .build(&schema)
+    ///     .expect("A valid schema");
+    /// assert!(validator.is_valid(&json!("abc")));
+    /// ```
+    pub fn should_share_regexes(mut self, yes: bool) -> Self {
+        self.share_regexes = yes;
+        self
+    }
+    pub(crate) const fn shares_regexes(&self) -> bool {
+        self.share_regexes
+    }
 }
 
 #[cfg(feature = "resolve-async")]
@@ -586,13 +1257,31 @@ impl ValidationOptions<Arc<dyn referencing::AsyncRetrieve>> {
             content_encoding_checks_and_converters: self.content_encoding_checks_and_converters,
             base_uri: None,
             resources: self.resources,
+            ref_overrides: self.ref_overrides,
             registry: self.registry,
+            document_cache: self.document_cache,
+            retrieval_limits: self.retrieval_limits,
+            strict_identifiers: self.strict_identifiers,
+            uri_rewriter: self.uri_rewriter,
             formats: self.formats,
             validate_formats: self.validate_formats,
             validate_schema: self.validate_schema,
             ignore_unknown_formats: self.ignore_unknown_formats,
+            reject_unknown_specifications: self.reject_unknown_specifications,
+            report_missing_properties: self.report_missing_properties,
             keywords: self.keywords,
             pattern_options: self.pattern_options,
+            share_regexes: self.share_regexes,
+            size_limits: self.size_limits,
+            date_bounds: self.date_bounds,
+            key_decoder: self.key_decoder,
+            preprocessors: self.preprocessors,
+            profile: self.profile,
+            diagnostics: self.diagnostics,
+            compilation_limits: self.compilation_limits,
+            dialects: self.dialects,
+            value_comparison: self.value_comparison,
+            variables: self.variables,
         }
     }
     pub(crate) async fn draft_for(
@@ -601,14 +1290,17 @@ impl ValidationOptions<Arc<dyn referencing::AsyncRetrieve>> {
     ) -> Result<Draft, ValidationError<'static>> {
         // Preference:
         //  - Explicitly set
+        //  - Registered dialect
         //  - Autodetected
         //  - Default
         if let Some(draft) = self.draft {
-            Ok(draft)
+            enabled(draft)
+        } else if let Some(dialect) = self.dialect_for(contents) {
+            enabled(dialect.draft())
         } else {
             let default = Draft::default();
             match default.detect(contents) {
-                Ok(draft) => Ok(draft),
+                Ok(draft) => enabled(draft),
                 Err(referencing::Error::UnknownSpecification { specification }) => {
                     // Try to retrieve the specification and detect its draft
                     if let Ok(uri) = uri::from_str(&specification) {
@@ -634,17 +1326,57 @@ impl ValidationOptions<Arc<dyn referencing::AsyncRetrieve>> {
             content_encoding_checks_and_converters: self.content_encoding_checks_and_converters,
             base_uri: None,
             resources: self.resources,
+            ref_overrides: self.ref_overrides,
             registry: self.registry,
+            document_cache: self.document_cache,
+            retrieval_limits: self.retrieval_limits,
+            strict_identifiers: self.strict_identifiers,
+            uri_rewriter: self.uri_rewriter,
             formats: self.formats,
             validate_formats: self.validate_formats,
             validate_schema: self.validate_schema,
             ignore_unknown_formats: self.ignore_unknown_formats,
+            reject_unknown_specifications: self.reject_unknown_specifications,
+            report_missing_properties: self.report_missing_properties,
             keywords: self.keywords,
             pattern_options: self.pattern_options,
+            share_regexes: self.share_regexes,
+            size_limits: self.size_limits,
+            date_bounds: self.date_bounds,
+            key_decoder: self.key_decoder,
+            preprocessors: self.preprocessors,
+            profile: self.profile,
+            diagnostics: self.diagnostics,
+            compilation_limits: self.compilation_limits,
+            dialects: self.dialects,
+            value_comparison: self.value_comparison,
+            variables: self.variables,
         }
     }
 }
 
+/// Reject drafts whose support is compiled out via cargo features.
+fn enabled(draft: Draft) -> Result<Draft, ValidationError<'static>> {
+    if draft.is_enabled() {
+        Ok(draft)
+    } else {
+        Err(referencing::Error::DisabledSpecification { draft }.into())
+    }
+}
+
+static GLOBAL_DEFAULTS: OnceCell<ValidationOptions> = OnceCell::new();
+
+pub(crate) fn set_global_defaults(options: ValidationOptions) -> Result<(), ValidationOptions> {
+    GLOBAL_DEFAULTS.set(options)
+}
+
+impl ValidationOptions {
+    /// Options set via [`crate::set_global_defaults`], or the built-in defaults.
+    pub(crate) fn from_global_defaults() -> ValidationOptions {
+        GLOBAL_DEFAULTS.get().cloned().unwrap_or_default()
+    }
+}
+
 impl fmt::Debug for ValidationOptions {
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
         fmt.debug_struct("CompilationConfig")
@@ -768,6 +1500,331 @@ impl PatternOptions<Regex> {
     }
 }
 
+/// How `enum` and `const` compare values, see [`ValidationOptions::with_value_comparison`].
+///
+/// The default follows the JSON Schema specification.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ValueComparison {
+    strict_numbers: bool,
+    case_insensitive_strings: bool,
+}
+
+impl ValueComparison {
+    /// Create a configuration following the JSON Schema specification.
+    pub fn new() -> ValueComparison {
+        ValueComparison::default()
+    }
+    /// Compare numbers by their representation, so `1` and `1.0`, as well as `0` and `-0.0`
+    /// are different.
+    pub fn strict_numbers(mut self, yes: bool) -> Self {
+        self.strict_numbers = yes;
+        self
+    }
+    /// Compare strings ignoring their case. Object keys are still compared exactly.
+    pub fn case_insensitive_strings(mut self, yes: bool) -> Self {
+        self.case_insensitive_strings = yes;
+        self
+    }
+    pub(crate) fn is_default(&self) -> bool {
+        *self == ValueComparison::default()
+    }
+    /// Whether two values are equal under this configuration.
+    pub(crate) fn equal(&self, left: &Value, right: &Value) -> bool {
+        match (left, right) {
+            (Value::String(left), Value::String(right)) if self.case_insensitive_strings => left
+                .chars()
+                .flat_map(char::to_lowercase)
+                .eq(right.chars().flat_map(char::to_lowercase)),
+            (Value::Number(left), Value::Number(right)) if self.strict_numbers => {
+                match (left.as_f64(), right.as_f64()) {
+                    (Some(l), Some(r)) if left.is_f64() && right.is_f64() => {
+                        l.to_bits() == r.to_bits()
+                    }
+                    _ => !left.is_f64() && !right.is_f64() && left == right,
+                }
+            }
+            (Value::Array(left), Value::Array(right)) => {
+                left.len() == right.len()
+                    && left
+                        .iter()
+                        .zip(right)
+                        .all(|(left, right)| self.equal(left, right))
+            }
+            (Value::Object(left), Value::Object(right)) => {
+                left.len() == right.len()
+                    && left.iter().all(|(key, left)| {
+                        right.get(key).is_some_and(|right| self.equal(left, right))
+                    })
+            }
+            _ => crate::ext::cmp::equal(left, right),
+        }
+    }
+}
+
+/// Limits on instance size characteristics, see [`ValidationOptions::with_size_limits`].
+///
+/// All limits are disabled by default.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SizeLimits {
+    max_string_length: Option<usize>,
+    max_properties: Option<usize>,
+    max_items: Option<usize>,
+}
+
+impl SizeLimits {
+    /// Create a configuration with all limits disabled.
+    pub fn new() -> SizeLimits {
+        SizeLimits::default()
+    }
+    /// Maximum length, in bytes, of a string matched against `pattern`.
+    pub fn max_string_length(mut self, limit: usize) -> Self {
+        self.max_string_length = Some(limit);
+        self
+    }
+    /// Maximum number of properties in any object of the instance.
+    pub fn max_properties(mut self, limit: usize) -> Self {
+        self.max_properties = Some(limit);
+        self
+    }
+    /// Maximum number of items in any array of the instance.
+    pub fn max_items(mut self, limit: usize) -> Self {
+        self.max_items = Some(limit);
+        self
+    }
+    pub(crate) fn string_length(&self) -> Option<usize> {
+        self.max_string_length
+    }
+    pub(crate) fn is_traversal_limited(&self) -> bool {
+        self.max_properties.is_some() || self.max_items.is_some()
+    }
+    /// Find the first object or array in `instance` that exceeds the configured limits.
+    pub(crate) fn check<'i>(
+        &self,
+        instance: &'i Value,
+        location: &LazyLocation,
+    ) -> Result<(), ValidationError<'i>> {
+        match instance {
+            Value::Object(object) => {
+                if let Some(limit) = self.max_properties {
+                    if object.len() > limit {
+                        return Err(ValidationError::size_limit_exceeded(
+                            Location::new(),
+                            location.into(),
+                            instance,
+                            SizeLimitKind::Properties,
+                            limit,
+                        ));
+                    }
+                }
+                for (name, value) in object {
+                    self.check(value, &location.push(name))?;
+                }
+            }
+            Value::Array(items) => {
+                if let Some(limit) = self.max_items {
+                    if items.len() > limit {
+                        return Err(ValidationError::size_limit_exceeded(
+                            Location::new(),
+                            location.into(),
+                            instance,
+                            SizeLimitKind::Items,
+                            limit,
+                        ));
+                    }
+                }
+                for (idx, item) in items.iter().enumerate() {
+                    self.check(item, &location.push(idx))?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// Limits on schema compilation, see [`ValidationOptions::with_compilation_limits`].
+///
+/// By default, references compiled in place may be nested 64 levels deep, and the number of
+/// subschemas is unlimited.
+#[derive(Debug, Clone, Copy)]
+pub struct CompilationLimits {
+    max_reference_depth: usize,
+    max_subschemas: Option<usize>,
+}
+
+impl Default for CompilationLimits {
+    fn default() -> Self {
+        CompilationLimits {
+            max_reference_depth: 64,
+            max_subschemas: None,
+        }
+    }
+}
+
+impl CompilationLimits {
+    /// Create a configuration with the default limits.
+    pub fn new() -> CompilationLimits {
+        CompilationLimits::default()
+    }
+    /// Maximum number of references compiled in place inside each other.
+    ///
+    /// Most reference cycles are compiled lazily and don't count towards this limit, but e.g.
+    /// `$ref`s next to `$recursiveAnchor` are always compiled in place.
+    pub fn max_reference_depth(mut self, limit: usize) -> Self {
+        self.max_reference_depth = limit;
+        self
+    }
+    /// Maximum number of subschemas compiled, including those reached via references.
+    pub fn max_subschemas(mut self, limit: usize) -> Self {
+        self.max_subschemas = Some(limit);
+        self
+    }
+    pub(crate) fn reference_depth(&self) -> usize {
+        self.max_reference_depth
+    }
+    pub(crate) fn subschemas(&self) -> Option<usize> {
+        self.max_subschemas
+    }
+}
+
+/// A source of the current time, see [`DateBounds::clock`].
+pub trait Clock: Send + Sync + 'static {
+    /// The current time.
+    fn now(&self) -> SystemTime;
+}
+
+impl<F> Clock for F
+where
+    F: Fn() -> SystemTime + Send + Sync + 'static,
+{
+    fn now(&self) -> SystemTime {
+        self()
+    }
+}
+
+/// Turns an object key into the value validated by `propertyNames`, see
+/// [`ValidationOptions::with_key_decoder`].
+///
+/// Custom keywords may use decoders directly to validate keys as typed values.
+pub trait KeyDecoder: Send + Sync + 'static {
+    /// The value this key stands for.
+    fn decode(&self, key: &str) -> Value;
+}
+
+impl<F> KeyDecoder for F
+where
+    F: Fn(&str) -> Value + Send + Sync + 'static,
+{
+    fn decode(&self, key: &str) -> Value {
+        self(key)
+    }
+}
+
+/// Decodes keys in their canonical integer or boolean form into numbers and booleans, as
+/// used for non-string map keys by the protobuf JSON mapping. Other keys stay strings.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TypedKeys;
+
+impl KeyDecoder for TypedKeys {
+    fn decode(&self, key: &str) -> Value {
+        match key {
+            "true" => return Value::Bool(true),
+            "false" => return Value::Bool(false),
+            _ => {}
+        }
+        let canonical = |value: &dyn fmt::Display| value.to_string() == key;
+        if let Ok(value) = key.parse::<i64>() {
+            if canonical(&value) {
+                return Value::from(value);
+            }
+        } else if let Ok(value) = key.parse::<u64>() {
+            if canonical(&value) {
+                return Value::from(value);
+            }
+        }
+        Value::String(key.to_string())
+    }
+}
+
+/// A bound of [`DateBounds`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeBound {
+    /// A fixed point in time.
+    At(SystemTime),
+    /// The current time shifted by this many seconds, negative for the past.
+    FromNow(i64),
+}
+
+/// Bounds for `date` and `date-time` values, see [`ValidationOptions::with_date_bounds`].
+///
+/// Both bounds are inclusive and disabled by default. `date` values are compared as midnight UTC
+/// of that day.
+#[derive(Clone, Default)]
+pub struct DateBounds {
+    earliest: Option<TimeBound>,
+    latest: Option<TimeBound>,
+    clock: Option<Arc<dyn Clock>>,
+}
+
+impl fmt::Debug for DateBounds {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DateBounds")
+            .field("earliest", &self.earliest)
+            .field("latest", &self.latest)
+            .finish_non_exhaustive()
+    }
+}
+
+impl DateBounds {
+    /// Create a configuration with both bounds disabled.
+    pub fn new() -> DateBounds {
+        DateBounds::default()
+    }
+    /// Reject values before this point in time.
+    pub fn earliest(mut self, bound: TimeBound) -> Self {
+        self.earliest = Some(bound);
+        self
+    }
+    /// Reject values after this point in time.
+    pub fn latest(mut self, bound: TimeBound) -> Self {
+        self.latest = Some(bound);
+        self
+    }
+    /// Use this clock instead of the system one for [`TimeBound::FromNow`], e.g. in tests.
+    pub fn clock(mut self, clock: impl Clock) -> Self {
+        self.clock = Some(Arc::new(clock));
+        self
+    }
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.earliest.is_some() || self.latest.is_some()
+    }
+    /// The earliest and latest allowed times, in nanoseconds since the Unix epoch.
+    pub(crate) fn resolve(&self) -> (Option<i128>, Option<i128>) {
+        let now = || {
+            self.clock
+                .as_ref()
+                .map_or_else(SystemTime::now, |clock| clock.now())
+        };
+        let resolve = |bound: &TimeBound| match bound {
+            TimeBound::At(time) => nanos_since_epoch(*time),
+            TimeBound::FromNow(seconds) => {
+                nanos_since_epoch(now()) + i128::from(*seconds) * 1_000_000_000
+            }
+        };
+        (
+            self.earliest.as_ref().map(resolve),
+            self.latest.as_ref().map(resolve),
+        )
+    }
+}
+
+fn nanos_since_epoch(time: SystemTime) -> i128 {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(elapsed) => i128::try_from(elapsed.as_nanos()).unwrap_or(i128::MAX),
+        Err(error) => -i128::try_from(error.duration().as_nanos()).unwrap_or(i128::MAX),
+    }
+}
+
 impl Default for PatternEngineOptions {
     fn default() -> Self {
         PatternEngineOptions::FancyRegex {
@@ -781,8 +1838,10 @@ impl Default for PatternEngineOptions {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::Dialect;
     use referencing::{Registry, Resource};
     use serde_json::json;
+    use test_case::test_case;
 
     fn custom(s: &str) -> bool {
         s.ends_with("42!")
@@ -800,6 +1859,91 @@ mod tests {
         assert!(validator.is_valid(&json!("foo42!")));
     }
 
+    const DIALECT: &str = "https://example.com/dialect";
+
+    fn dialect() -> Dialect {
+        Dialect::new(format!("{DIALECT}#"), Draft::Draft7)
+            .with_keyword("exclusive", property_groups::compile_mutually_exclusive)
+    }
+
+    #[test_case(&json!({"$schema": DIALECT, "exclusive": ["a", "b"]}), false; "declared")]
+    #[test_case(&json!({"$schema": format!("{DIALECT}#"), "exclusive": ["a", "b"]}), false; "declared with empty fragment")]
+    #[test_case(&json!({"exclusive": ["a", "b"]}), true; "not declared")]
+    fn dialect_keywords(schema: &Value, expected: bool) {
+        let validator = crate::options()
+            .with_dialect(dialect())
+            .build(schema)
+            .expect("Invalid schema");
+        assert_eq!(validator.is_valid(&json!({"a": 1, "b": 2})), expected);
+    }
+
+    #[test]
+    fn dialect_keywords_are_scoped_to_validator() {
+        let validator = crate::options()
+            .with_dialect(dialect())
+            .build(&json!({"$schema": DIALECT, "exclusive": ["a", "b"]}))
+            .expect("Invalid schema");
+        assert!(!validator.is_valid(&json!({"a": 1, "b": 2})));
+        // The dialect declared by the schema is not recorded in the options
+        assert!(validator
+            .config()
+            .get_keyword_factory("exclusive", None)
+            .is_none());
+        // Options of the built validator do not enable the keyword for schemas without the dialect
+        let other = ValidationOptions::clone(&validator.config())
+            .build(&json!({"exclusive": ["a", "b"]}))
+            .expect("Invalid schema");
+        assert!(other.is_valid(&json!({"a": 1, "b": 2})));
+        assert!(!other.config().keywords.contains_key("exclusive"));
+    }
+
+    #[test]
+    fn dialect_draft() {
+        // Draft 7 ignores keywords adjacent to `$ref`
+        let schema = json!({
+            "$schema": DIALECT,
+            "$ref": "#/definitions/any",
+            "type": "string",
+            "definitions": {"any": true}
+        });
+        let validator = crate::options()
+            .with_dialect(dialect())
+            .build(&schema)
+            .expect("Invalid schema");
+        assert!(validator.is_valid(&json!(1)));
+    }
+
+    #[test]
+    fn dialect_meta_schema_vocabularies() {
+        let meta_schema = json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "$id": DIALECT,
+            "$vocabulary": {
+                "https://json-schema.org/draft/2020-12/vocab/core": true,
+                "https://json-schema.org/draft/2020-12/vocab/applicator": true
+            }
+        });
+        let dialect = Dialect::new(DIALECT, Draft::Draft202012).with_meta_schema(meta_schema);
+        let validator = crate::options()
+            .with_dialect(dialect)
+            .build(&json!({"$schema": DIALECT, "type": "string"}))
+            .expect("Invalid schema");
+        // The validation vocabulary is not enabled
+        assert!(validator.is_valid(&json!(1)));
+    }
+
+    #[test]
+    fn unknown_dialect() {
+        let error = crate::options()
+            .with_dialect(dialect())
+            .build(&json!({"$schema": "https://example.com/unknown"}))
+            .expect_err("Should fail");
+        assert_eq!(
+            error.to_string(),
+            "Unknown specification: https://example.com/unknown"
+        );
+    }
+
     #[test]
     fn with_registry() {
         let registry = Registry::try_new(
@@ -820,6 +1964,125 @@ mod tests {
         assert!(!validator.is_valid(&json!({ "name": 123 })));
     }
 
+    #[test]
+    fn with_document_cache() {
+        struct CountingRetriever(Arc<std::sync::atomic::AtomicUsize>);
+
+        impl Retrieve for CountingRetriever {
+            fn retrieve(
+                &self,
+                _: &referencing::Uri<String>,
+            ) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+                self.0.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                Ok(json!({"type": "string"}))
+            }
+        }
+
+        let count = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let cache = referencing::DocumentCache::new(1);
+        let schema = json!({"$ref": "http://example.com/string"});
+        let build = || {
+            crate::options()
+                .with_retriever(CountingRetriever(Arc::clone(&count)))
+                .with_document_cache(cache.clone())
+                .build(&schema)
+                .expect("Invalid schema")
+        };
+        build();
+        let validator = build();
+        assert_eq!(count.load(std::sync::atomic::Ordering::Relaxed), 1);
+        assert!(validator.is_valid(&json!("foo")));
+        assert!(!validator.is_valid(&json!(42)));
+        // Validators keep the documents they use alive, e.g. for `Validator::check_examples`
+        cache.clear();
+        assert!(!cache.is_empty());
+        drop(validator);
+        cache.clear();
+        assert!(cache.is_empty());
+        build();
+        assert_eq!(count.load(std::sync::atomic::Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn with_uri_rewriter() {
+        struct StagingRetriever;
+
+        impl Retrieve for StagingRetriever {
+            fn retrieve(
+                &self,
+                uri: &referencing::Uri<String>,
+            ) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+                if uri.as_str() == "https://schemas.staging/string.json" {
+                    Ok(json!({"type": "string"}))
+                } else {
+                    Err(format!("Unexpected URI {uri}").into())
+                }
+            }
+        }
+
+        let validator = crate::options()
+            .with_retriever(StagingRetriever)
+            .with_uri_rewriter(|uri: &referencing::Uri<String>| {
+                uri.as_str()
+                    .strip_prefix("https://schemas.prod/")
+                    .and_then(|path| {
+                        referencing::Uri::parse(format!("https://schemas.staging/{path}")).ok()
+                    })
+            })
+            .build(&json!({"$ref": "https://schemas.prod/string.json"}))
+            .expect("Invalid schema");
+        assert!(validator.is_valid(&json!("foo")));
+        assert!(!validator.is_valid(&json!(42)));
+    }
+
+    #[test]
+    fn with_variables() {
+        let build = |variables: Value| {
+            crate::options()
+                .with_variables(variables)
+                .with_ref_overrides([(
+                    "https://example.com/name.json",
+                    json!({"maxLength": {"$var": "limits.name"}}),
+                )])
+                .build(&json!({
+                    "properties": {"name": {"$ref": "https://example.com/name.json"}},
+                    "maxProperties": {"$var": "limits.properties"}
+                }))
+        };
+        let validator =
+            build(json!({"limits": {"name": 3, "properties": 1}})).expect("Invalid schema");
+        assert!(validator.is_valid(&json!({"name": "abc"})));
+        assert!(!validator.is_valid(&json!({"name": "abcd"})));
+        assert!(!validator.is_valid(&json!({"name": "abc", "other": 1})));
+        let error = build(json!({"limits": {"name": 3}})).expect_err("Undefined variable");
+        assert_eq!(
+            error.to_string(),
+            "Undefined schema variable 'limits.properties'"
+        );
+    }
+
+    #[test_case(&json!({"$schema": "https://json-schema.org/draft/2020-12/shcema"}), false; "typo")]
+    #[test_case(&json!({"$schema": "http://example.com/meta"}), true; "registered meta-schema")]
+    #[test_case(&json!({"$schema": "https://json-schema.org/draft/2020-12/schema"}), true; "known draft")]
+    fn should_reject_unknown_specifications(schema: &Value, accepted: bool) {
+        let build = |yes| {
+            crate::options()
+                .with_draft(Draft::Draft202012)
+                .with_resource(
+                    "http://example.com/meta",
+                    Resource::from_contents(json!({
+                        "$schema": "https://json-schema.org/draft/2020-12/schema",
+                        "$id": "http://example.com/meta"
+                    }))
+                    .expect("Invalid resource"),
+                )
+                .should_reject_unknown_specifications(yes)
+                .build(schema)
+        };
+        assert!(build(false).is_ok());
+        assert_eq!(build(true).is_ok(), accepted);
+    }
+
     #[test]
     fn test_fancy_regex_options_builder() {
         let options = PatternOptions::fancy_regex()
@@ -858,4 +2121,92 @@ mod tests {
             panic!("Expected Regex variant");
         }
     }
+
+    #[test_case(&json!({"a": 1, "b": 2, "c": 3}), "", "Object has more than the configured limit of 2 properties")]
+    #[test_case(&json!({"a": [1, 2, 3]}), "/a", "Array has more than the configured limit of 2 items")]
+    #[test_case(&json!([[1], {"a": {"b": 1, "c": 2, "d": 3}}]), "/1/a", "Object has more than the configured limit of 2 properties")]
+    fn size_limits(instance: &Value, instance_path: &str, expected: &str) {
+        let validator = crate::options()
+            .with_size_limits(SizeLimits::new().max_properties(2).max_items(2))
+            .build(&json!({}))
+            .expect("Invalid schema");
+        assert!(!validator.is_valid(instance));
+        let errors: Vec<_> = validator.iter_errors(instance).collect();
+        assert_eq!(errors.len(), 1);
+        let error = validator.validate(instance).expect_err("Should fail");
+        assert_eq!(error.instance_path.as_str(), instance_path);
+        assert_eq!(error.to_string(), expected);
+        assert!(!validator.apply(instance).basic().is_valid());
+    }
+
+    #[test]
+    fn size_limits_within_bounds() {
+        let validator = crate::options()
+            .with_size_limits(SizeLimits::new().max_properties(2).max_items(2))
+            .build(&json!({}))
+            .expect("Invalid schema");
+        assert!(validator.is_valid(&json!({"a": [1, 2], "b": {"c": 1}})));
+    }
+
+    struct FailingRetriever;
+
+    impl Retrieve for FailingRetriever {
+        fn retrieve(
+            &self,
+            uri: &referencing::Uri<String>,
+        ) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+            Err(format!("Unexpected retrieval of {uri}").into())
+        }
+    }
+
+    #[test]
+    fn ref_overrides() {
+        let schema = json!({
+            "properties": {
+                "a": {"$ref": "https://example.com/a.json"},
+                "b": {"$ref": "https://example.com/b.json#/$defs/b"}
+            }
+        });
+        let validator = crate::options()
+            .with_retriever(FailingRetriever)
+            .with_resource(
+                "https://example.com/a.json",
+                Resource::from_contents(json!({"type": "boolean"})).expect("Invalid resource"),
+            )
+            .with_ref_overrides([
+                ("https://example.com/a.json", json!({"type": "integer"})),
+                (
+                    "https://example.com/b.json",
+                    json!({"$defs": {"b": {"type": "null"}}}),
+                ),
+            ])
+            .build(&schema)
+            .expect("Invalid schema");
+        assert!(validator.is_valid(&json!({"a": 1, "b": null})));
+        assert!(!validator.is_valid(&json!({"a": "1"})));
+        assert!(!validator.is_valid(&json!({"a": true})));
+        assert!(!validator.is_valid(&json!({"b": 1})));
+    }
+
+    #[test]
+    fn ref_overrides_detect_drafts() {
+        let validator = crate::options()
+            .with_ref_overrides([(
+                "https://example.com/old.json",
+                json!({"$schema": "http://json-schema.org/draft-04/schema#", "maximum": 5, "exclusiveMaximum": true}),
+            )])
+            .build(&json!({"$ref": "https://example.com/old.json"}))
+            .expect("Invalid schema");
+        assert!(validator.is_valid(&json!(4)));
+        assert!(!validator.is_valid(&json!(5)));
+
+        let error = crate::options()
+            .with_ref_overrides([(
+                "https://example.com/unknown.json",
+                json!({"$schema": "https://example.com/meta"}),
+            )])
+            .build(&json!({"$ref": "https://example.com/unknown.json"}))
+            .expect_err("Unknown specification");
+        assert!(error.to_string().contains("https://example.com/meta"));
+    }
 }
@@ -16,7 +16,7 @@ use ahash::AHashMap;
 use referencing::Uri;
 use serde::ser::SerializeMap;
 
-use crate::{node::SchemaNode, paths::LazyLocation, Validator};
+use crate::{eval::Evaluation, node::SchemaNode, paths::LazyLocation, Validator};
 
 /// The output format resulting from the application of a schema.
 ///
@@ -103,8 +103,27 @@ impl<'a> Output<'a, '_> {
     /// ```
     #[must_use]
     pub fn basic(&self) -> BasicOutput<'a> {
-        self.root_node
-            .apply_rooted(self.instance, &LazyLocation::new())
+        self.basic_at(None)
+    }
+
+    /// The "basic" output of a validation with per-call settings.
+    pub(crate) fn basic_in(&self, evaluation: &Evaluation) -> BasicOutput<'a> {
+        self.basic_at(Some(evaluation))
+    }
+
+    fn basic_at(&self, evaluation: Option<&Evaluation>) -> BasicOutput<'a> {
+        if let Err(error) = self.schema.check_size_limits(self.instance, evaluation) {
+            let mut errors = VecDeque::new();
+            errors.push_back(OutputUnit::<ErrorDescription>::error(
+                error.schema_path.clone(),
+                error.instance_path.clone(),
+                None,
+                error.into(),
+            ));
+            return BasicOutput::Invalid(errors);
+        }
+        let location = evaluation.map_or_else(LazyLocation::new, LazyLocation::evaluating);
+        self.root_node.apply_rooted(self.instance, &location)
     }
 }
 
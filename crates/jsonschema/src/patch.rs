@@ -0,0 +1,448 @@
+//! Validation of JSON Merge Patch (RFC 7396) and JSON Patch (RFC 6902) documents.
+//!
+//! Validating a patch against the schema of the documents it changes rejects valid patches, e.g.
+//! ones that omit required properties, and accepts invalid ones, e.g. removals of required
+//! properties. The helpers in this module validate the result of applying a patch to its base
+//! document instead, as a PATCH endpoint would store it:
+//!
+//! ```rust
+//! use jsonschema::patch;
+//! use serde_json::json;
+//!
+//! let validator = jsonschema::validator_for(&json!({
+//!     "properties": {"age": {"type": "integer"}},
+//!     "required": ["name"]
+//! }))
+//! .expect("Invalid schema");
+//! let base = json!({"name": "Alice", "age": 41});
+//!
+//! let (patched, errors) = patch::validate_merge_patch(&validator, &base, &json!({"age": 42}));
+//! assert_eq!(patched, json!({"name": "Alice", "age": 42}));
+//! assert!(errors.is_empty());
+//!
+//! let (_, errors) = patch::validate_json_patch(
+//!     &validator,
+//!     &base,
+//!     &json!([{"op": "remove", "path": "/name"}]),
+//! )
+//! .expect("Applicable patch");
+//! assert_eq!(errors[0].to_string(), r#""name" is a required property"#);
+//! ```
+//!
+//! If the base document is not at hand, [`validate_merge_patch_shape`] checks what a merge patch
+//! determines about the result on its own.
+use std::{error, fmt};
+
+use serde_json::{Map, Value};
+
+use crate::{error::ValidationErrorKind, ext::cmp, ValidationError, Validator};
+
+/// Apply a JSON Merge Patch to `target`.
+///
+/// Objects in `patch` are merged into `target` recursively, with `null` members removing the
+/// corresponding properties. Any other value replaces the target as a whole.
+pub fn merge(target: &mut Value, patch: &Value) {
+    let Value::Object(members) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+    let Value::Object(object) = target else {
+        unreachable!("Replaced with an object above")
+    };
+    for (key, value) in members {
+        if value.is_null() {
+            object.remove(key);
+        } else {
+            merge(object.entry(key.as_str()).or_insert(Value::Null), value);
+        }
+    }
+}
+
+/// An error applying a JSON Patch with [`apply`] or [`validate_json_patch`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatchError {
+    /// The patch is not an array of operations, or an operation is not an object with a known
+    /// `op` and the members it requires.
+    Malformed {
+        /// The index of the operation, or `None` if the patch is not an array.
+        index: Option<usize>,
+        /// What is wrong with it.
+        message: String,
+    },
+    /// A path does not point to an existing value, or, for `add`, to a member of an existing
+    /// object or array.
+    NotFound {
+        /// The index of the operation.
+        index: usize,
+        /// The JSON Pointer that does not exist.
+        path: String,
+    },
+    /// The value at the path of a `test` operation differs from its `value`.
+    TestFailed {
+        /// The index of the operation.
+        index: usize,
+        /// JSON Pointer to the tested value.
+        path: String,
+    },
+}
+
+impl fmt::Display for PatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PatchError::Malformed {
+                index: Some(index),
+                message,
+            } => write!(f, "Operation {index} is malformed: {message}"),
+            PatchError::Malformed {
+                index: None,
+                message,
+            } => write!(f, "Patch is malformed: {message}"),
+            PatchError::NotFound { index, path } => {
+                write!(
+                    f,
+                    "Operation {index} refers to a missing location: '{path}'"
+                )
+            }
+            PatchError::TestFailed { index, path } => {
+                write!(f, "Operation {index} failed its test of '{path}'")
+            }
+        }
+    }
+}
+
+impl error::Error for PatchError {}
+
+/// Apply a JSON Patch to `target`.
+///
+/// Operations are applied in order, and `target` is left unchanged if any of them fails.
+///
+/// # Errors
+///
+/// Returns an error if the patch is malformed, if an operation refers to a missing location, or
+/// if a `test` operation fails.
+pub fn apply(target: &mut Value, patch: &Value) -> Result<(), PatchError> {
+    let Value::Array(operations) = patch else {
+        return Err(PatchError::Malformed {
+            index: None,
+            message: "expected an array of operations".to_string(),
+        });
+    };
+    let mut patched = target.clone();
+    for (index, operation) in operations.iter().enumerate() {
+        apply_operation(&mut patched, operation, index)?;
+    }
+    *target = patched;
+    Ok(())
+}
+
+fn apply_operation(target: &mut Value, operation: &Value, index: usize) -> Result<(), PatchError> {
+    let malformed = |message: &str| PatchError::Malformed {
+        index: Some(index),
+        message: message.to_string(),
+    };
+    let member = |name: &str| {
+        operation
+            .get(name)
+            .ok_or_else(|| malformed(&format!("missing '{name}'")))
+    };
+    let pointer = |name: &str| {
+        member(name)?
+            .as_str()
+            .filter(|pointer| pointer.is_empty() || pointer.starts_with('/'))
+            .ok_or_else(|| malformed(&format!("'{name}' is not a JSON Pointer")))
+    };
+    let not_found = |path: &str| PatchError::NotFound {
+        index,
+        path: path.to_string(),
+    };
+    let op = member("op")?
+        .as_str()
+        .ok_or_else(|| malformed("'op' is not a string"))?;
+    let path = pointer("path")?;
+    match op {
+        "add" => add(target, path, member("value")?.clone()).ok_or_else(|| not_found(path)),
+        "remove" => remove(target, path)
+            .map(drop)
+            .ok_or_else(|| not_found(path)),
+        "replace" => {
+            let value = member("value")?.clone();
+            let current = target.pointer_mut(path).ok_or_else(|| not_found(path))?;
+            *current = value;
+            Ok(())
+        }
+        "move" => {
+            let from = pointer("from")?;
+            if path.starts_with(from) && path[from.len()..].starts_with('/') {
+                return Err(malformed("'from' is a parent of 'path'"));
+            }
+            let value = remove(target, from).ok_or_else(|| not_found(from))?;
+            add(target, path, value).ok_or_else(|| not_found(path))
+        }
+        "copy" => {
+            let from = pointer("from")?;
+            let value = target.pointer(from).ok_or_else(|| not_found(from))?.clone();
+            add(target, path, value).ok_or_else(|| not_found(path))
+        }
+        "test" => {
+            let expected = member("value")?;
+            let actual = target.pointer(path).ok_or_else(|| not_found(path))?;
+            if cmp::equal(actual, expected) {
+                Ok(())
+            } else {
+                Err(PatchError::TestFailed {
+                    index,
+                    path: path.to_string(),
+                })
+            }
+        }
+        _ => Err(malformed(&format!("unknown 'op': '{op}'"))),
+    }
+}
+
+/// Split a non-empty JSON Pointer into the pointer to its parent and its unescaped last token.
+fn split(path: &str) -> Option<(&str, String)> {
+    let (parent, token) = path.rsplit_once('/')?;
+    Some((parent, token.replace("~1", "/").replace("~0", "~")))
+}
+
+/// Parse an array index token, which has no leading zeros.
+fn array_index(token: &str) -> Option<usize> {
+    if token.starts_with('+') || (token.starts_with('0') && token.len() > 1) {
+        return None;
+    }
+    token.parse().ok()
+}
+
+fn add(target: &mut Value, path: &str, value: Value) -> Option<()> {
+    let Some((parent, token)) = split(path) else {
+        *target = value;
+        return Some(());
+    };
+    match target.pointer_mut(parent)? {
+        Value::Object(object) => {
+            object.insert(token, value);
+        }
+        Value::Array(items) => {
+            let index = if token == "-" {
+                items.len()
+            } else {
+                array_index(&token).filter(|index| *index <= items.len())?
+            };
+            items.insert(index, value);
+        }
+        _ => return None,
+    }
+    Some(())
+}
+
+fn remove(target: &mut Value, path: &str) -> Option<Value> {
+    let (parent, token) = split(path)?;
+    match target.pointer_mut(parent)? {
+        Value::Object(object) => object.remove(&token),
+        Value::Array(items) => {
+            let index = array_index(&token).filter(|index| *index < items.len())?;
+            Some(items.remove(index))
+        }
+        _ => None,
+    }
+}
+
+fn validate_patched(validator: &Validator, patched: &Value) -> Vec<ValidationError<'static>> {
+    validator
+        .iter_errors(patched)
+        .map(ValidationError::to_owned)
+        .collect()
+}
+
+/// Apply a JSON Merge Patch to a copy of `base`, and validate the result.
+///
+/// Returns the patched document together with its validation errors, which are empty if it is
+/// valid.
+pub fn validate_merge_patch(
+    validator: &Validator,
+    base: &Value,
+    patch: &Value,
+) -> (Value, Vec<ValidationError<'static>>) {
+    let mut patched = base.clone();
+    merge(&mut patched, patch);
+    let errors = validate_patched(validator, &patched);
+    (patched, errors)
+}
+
+/// Apply a JSON Patch to a copy of `base`, and validate the result.
+///
+/// Returns the patched document together with its validation errors, which are empty if it is
+/// valid.
+///
+/// # Errors
+///
+/// Returns an error if the patch can not be applied to `base`, see [`apply`].
+pub fn validate_json_patch(
+    validator: &Validator,
+    base: &Value,
+    patch: &Value,
+) -> Result<(Value, Vec<ValidationError<'static>>), PatchError> {
+    let mut patched = base.clone();
+    apply(&mut patched, patch)?;
+    let errors = validate_patched(validator, &patched);
+    Ok((patched, errors))
+}
+
+/// Validate a JSON Merge Patch without its base document.
+///
+/// The patch is validated as the set of properties it sets, with `null` members removed.
+/// Properties the patch does not mention may exist in the base document, so only errors that hold
+/// regardless of the base are reported: missing `required` and `dependentRequired` properties are
+/// reported only if the patch removes them, and `minProperties` is not checked. Keywords that depend on other
+/// properties, e.g. within `if` or `oneOf`, only see the properties of the patch, so use
+/// [`validate_merge_patch`] for a precise result if the base document is available.
+///
+/// ```rust
+/// use jsonschema::patch;
+/// use serde_json::json;
+///
+/// let validator = jsonschema::validator_for(&json!({
+///     "properties": {"name": {"type": "string"}, "age": {"type": "integer"}},
+///     "required": ["name", "age"]
+/// }))
+/// .expect("Invalid schema");
+/// assert!(patch::validate_merge_patch_shape(&validator, &json!({"age": 42})).is_empty());
+///
+/// let errors = patch::validate_merge_patch_shape(&validator, &json!({"name": null, "age": "42"}));
+/// let messages: Vec<_> = errors.iter().map(ToString::to_string).collect();
+/// assert_eq!(
+///     messages,
+///     [r#""42" is not of type "integer""#, r#""name" is a required property"#]
+/// );
+/// ```
+#[must_use]
+pub fn validate_merge_patch_shape(
+    validator: &Validator,
+    patch: &Value,
+) -> Vec<ValidationError<'static>> {
+    let shape = without_removals(patch);
+    let removes = |error: &ValidationError<'_>, name: &str| {
+        patch
+            .pointer(error.instance_path.as_str())
+            .and_then(|object| object.get(name))
+            .is_some_and(Value::is_null)
+    };
+    validator
+        .iter_errors(&shape)
+        .filter(|error| match &error.kind {
+            ValidationErrorKind::Required { property } => {
+                property.as_str().is_some_and(|name| removes(error, name))
+            }
+            ValidationErrorKind::DependentRequired { missing, .. } => {
+                missing.iter().any(|name| removes(error, name))
+            }
+            ValidationErrorKind::MissingProperties { .. }
+            | ValidationErrorKind::MinProperties { .. } => false,
+            _ => true,
+        })
+        .map(ValidationError::to_owned)
+        .collect()
+}
+
+/// The properties set by a merge patch, i.e. the patch without its `null` members.
+fn without_removals(patch: &Value) -> Value {
+    match patch {
+        Value::Object(members) => Value::Object(
+            members
+                .iter()
+                .filter(|(_, value)| !value.is_null())
+                .map(|(key, value)| (key.clone(), without_removals(value)))
+                .collect(),
+        ),
+        // Arrays are replaced as a whole, including their `null` items
+        _ => patch.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use test_case::test_case;
+
+    // Examples from RFC 7396, Appendix A
+    #[test_case(&json!({"a": "b"}), &json!({"a": "c"}), &json!({"a": "c"}))]
+    #[test_case(&json!({"a": "b"}), &json!({"b": "c"}), &json!({"a": "b", "b": "c"}))]
+    #[test_case(&json!({"a": "b"}), &json!({"a": null}), &json!({}))]
+    #[test_case(&json!({"a": {"b": "c"}}), &json!({"a": {"b": "d", "c": null}}), &json!({"a": {"b": "d"}}))]
+    #[test_case(&json!({"a": [{"b": "c"}]}), &json!({"a": [1]}), &json!({"a": [1]}))]
+    #[test_case(&json!(["a", "b"]), &json!({"a": "c"}), &json!({"a": "c"}))]
+    #[test_case(&json!({"a": "foo"}), &json!("bar"), &json!("bar"))]
+    #[test_case(&json!({"e": null}), &json!({"a": 1}), &json!({"e": null, "a": 1}))]
+    #[test_case(&json!({}), &json!({"a": {"bb": {"ccc": null}}}), &json!({"a": {"bb": {}}}))]
+    fn merge_patch(base: &Value, patch: &Value, expected: &Value) {
+        let mut target = base.clone();
+        merge(&mut target, patch);
+        assert_eq!(&target, expected);
+    }
+
+    #[test_case(&json!({"foo": "bar"}), &json!([{"op": "add", "path": "/baz", "value": "qux"}]), &json!({"foo": "bar", "baz": "qux"}))]
+    #[test_case(&json!({"foo": ["bar", "baz"]}), &json!([{"op": "add", "path": "/foo/1", "value": "qux"}]), &json!({"foo": ["bar", "qux", "baz"]}))]
+    #[test_case(&json!([1]), &json!([{"op": "add", "path": "/-", "value": 2}]), &json!([1, 2]))]
+    #[test_case(&json!({"a": 1}), &json!([{"op": "add", "path": "", "value": [1]}]), &json!([1]); "whole document")]
+    #[test_case(&json!({"a/b": 1, "c": [1, 2]}), &json!([{"op": "remove", "path": "/a~1b"}, {"op": "remove", "path": "/c/0"}]), &json!({"c": [2]}))]
+    #[test_case(&json!({"a": 1}), &json!([{"op": "replace", "path": "/a", "value": 2}]), &json!({"a": 2}))]
+    #[test_case(&json!({"a": {"b": 1}, "c": {}}), &json!([{"op": "move", "from": "/a/b", "path": "/c/d"}]), &json!({"a": {}, "c": {"d": 1}}))]
+    #[test_case(&json!({"a": [1]}), &json!([{"op": "copy", "from": "/a", "path": "/b"}]), &json!({"a": [1], "b": [1]}))]
+    #[test_case(&json!({"a": 1}), &json!([{"op": "test", "path": "/a", "value": 1.0}]), &json!({"a": 1}))]
+    fn json_patch(base: &Value, patch: &Value, expected: &Value) {
+        let mut target = base.clone();
+        apply(&mut target, patch).expect("Applicable patch");
+        assert_eq!(&target, expected);
+    }
+
+    #[test_case(&json!({"op": "add"}), "Patch is malformed: expected an array of operations")]
+    #[test_case(&json!([{"op": "add", "path": "/a"}]), "Operation 0 is malformed: missing 'value'")]
+    #[test_case(&json!([{"op": "delete", "path": "/a"}]), "Operation 0 is malformed: unknown 'op': 'delete'")]
+    #[test_case(&json!([{"op": "remove", "path": "a"}]), "Operation 0 is malformed: 'path' is not a JSON Pointer")]
+    #[test_case(&json!([{"op": "move", "from": "/a", "path": "/a/b"}]), "Operation 0 is malformed: 'from' is a parent of 'path'")]
+    #[test_case(&json!([{"op": "remove", "path": "/a"}, {"op": "remove", "path": "/a"}]), "Operation 1 refers to a missing location: '/a'")]
+    #[test_case(&json!([{"op": "add", "path": "/b/01", "value": 1}]), "Operation 0 refers to a missing location: '/b/01'")]
+    #[test_case(&json!([{"op": "add", "path": "/b/2", "value": 1}]), "Operation 0 refers to a missing location: '/b/2'")]
+    #[test_case(&json!([{"op": "test", "path": "/a", "value": 2}]), "Operation 0 failed its test of '/a'")]
+    fn json_patch_errors(patch: &Value, message: &str) {
+        let base = json!({"a": {"c": 1}, "b": [1]});
+        let mut target = base.clone();
+        let error = apply(&mut target, patch).expect_err("Should fail");
+        assert_eq!(error.to_string(), message);
+        // Failed patches are not applied partially
+        assert_eq!(target, base);
+    }
+
+    #[test_case(&json!({"name": "Bob"}), &[])]
+    #[test_case(&json!({"tags": ["a", null]}), &["/tags/1"])]
+    #[test_case(&json!({"nested": {"id": null}}), &["/nested"]; "removed required property")]
+    #[test_case(&json!({"nested": {"extra": 1}}), &[]; "required properties may be in the base")]
+    #[test_case(&json!({"name": null, "other": null}), &[""])]
+    #[test_case(&json!({"card": 1}), &[]; "dependent properties may be in the base")]
+    #[test_case(&json!({"card": 1, "billing": null}), &[""]; "removed dependent property")]
+    fn merge_patch_shape(patch: &Value, expected: &[&str]) {
+        let validator = crate::validator_for(&json!({
+            "properties": {
+                "name": {"type": "string"},
+                "tags": {"items": {"type": "string"}},
+                "nested": {"required": ["id"], "minProperties": 2}
+            },
+            "required": ["name"],
+            "dependentRequired": {"card": ["billing"]},
+            "minProperties": 2
+        }))
+        .expect("Invalid schema");
+        let errors = validate_merge_patch_shape(&validator, patch);
+        assert_eq!(
+            errors
+                .iter()
+                .map(|error| error.instance_path.as_str())
+                .collect::<Vec<_>>(),
+            expected
+        );
+    }
+}
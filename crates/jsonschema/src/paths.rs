@@ -1,7 +1,7 @@
 //! Facilities for working with paths within schemas or validated instances.
-use std::{fmt, sync::Arc};
+use std::{any::Any, fmt};
 
-use crate::keywords::Keyword;
+use crate::{eval::Evaluation, keywords::Keyword, sync::Shared};
 
 /// A location segment.
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
@@ -29,6 +29,8 @@ impl fmt::Display for LocationSegment<'_> {
 pub struct LazyLocation<'a, 'b> {
     pub(crate) segment: LocationSegment<'a>,
     pub(crate) parent: Option<&'b LazyLocation<'b, 'a>>,
+    /// Per-call state of the validation, shared by all segments.
+    pub(crate) evaluation: Option<&'b Evaluation>,
 }
 
 impl Default for LazyLocation<'_, '_> {
@@ -44,6 +46,7 @@ impl<'a> LazyLocation<'a, '_> {
             // The value does not matter, it will never be used
             segment: LocationSegment::Index(0),
             parent: None,
+            evaluation: None,
         }
     }
 
@@ -53,10 +56,68 @@ impl<'a> LazyLocation<'a, '_> {
         LazyLocation {
             segment: segment.into(),
             parent: Some(self),
+            evaluation: self.evaluation,
         }
     }
 }
 
+impl<'b> LazyLocation<'_, 'b> {
+    /// Create a root node for a validation with per-call state.
+    pub(crate) const fn evaluating(evaluation: &'b Evaluation) -> Self {
+        LazyLocation {
+            segment: LocationSegment::Index(0),
+            parent: None,
+            evaluation: Some(evaluation),
+        }
+    }
+
+    /// Access the context set via [`EvalOptions::with_context`] from within a custom keyword.
+    ///
+    /// Returns `None` outside of validation with [`EvalOptions`], or if the context is not of
+    /// type `T`. Validations with [`EvalOptions`] call [`Keyword::validate`], which receives the
+    /// location, even for subschemas evaluated only for their validity.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use jsonschema::{paths::LazyLocation, EvalOptions, Keyword, ValidationError};
+    /// use serde_json::{json, Value};
+    ///
+    /// // Counts the values it is applied to
+    /// struct Counter;
+    ///
+    /// impl Keyword for Counter {
+    ///     fn validate<'i>(
+    ///         &self,
+    ///         _: &'i Value,
+    ///         location: &LazyLocation,
+    ///     ) -> Result<(), ValidationError<'i>> {
+    ///         location.with_eval_context(|count: &mut usize| *count += 1);
+    ///         Ok(())
+    ///     }
+    ///     fn is_valid(&self, _: &Value) -> bool {
+    ///         true
+    ///     }
+    /// }
+    ///
+    /// let validator = jsonschema::options()
+    ///     .with_keyword("x-count", |_, _, _| Ok(Box::new(Counter)))
+    ///     .build(&json!({"items": {"x-count": true}}))
+    ///     .expect("Invalid schema");
+    ///
+    /// let mut options = EvalOptions::new().with_context(0_usize);
+    /// assert!(validator.is_valid_with(&json!([1, 2, 3]), &mut options));
+    /// assert_eq!(options.context::<usize>(), Some(&3));
+    /// ```
+    ///
+    /// [`EvalOptions::with_context`]: crate::EvalOptions::with_context
+    /// [`EvalOptions`]: crate::EvalOptions
+    /// [`Keyword::validate`]: crate::Keyword::validate
+    pub fn with_eval_context<T: Any, R>(&self, f: impl FnOnce(&mut T) -> R) -> Option<R> {
+        self.evaluation?.with_context(f)
+    }
+}
+
 impl<'a> From<&'a LazyLocation<'_, '_>> for Location {
     fn from(value: &'a LazyLocation<'_, '_>) -> Self {
         let mut capacity = 0;
@@ -101,7 +162,7 @@ impl<'a> From<&'a LazyLocation<'_, '_>> for Location {
             }
         }
 
-        Location(Arc::new(buffer))
+        Location(Shared::new(buffer))
     }
 }
 
@@ -137,7 +198,7 @@ impl From<usize> for LocationSegment<'_> {
 
 /// A cheap to clone JSON pointer that represents location with a JSON value.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
-pub struct Location(Arc<String>);
+pub struct Location(Shared<String>);
 
 impl serde::Serialize for Location {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
@@ -151,7 +212,7 @@ impl serde::Serialize for Location {
 impl Location {
     /// Create a new, empty `Location`.
     pub fn new() -> Self {
-        Self(Arc::new(String::new()))
+        Self(Shared::new(String::new()))
     }
     pub fn join<'a>(&self, segment: impl Into<LocationSegment<'a>>) -> Self {
         let parent = self.0.as_str();
@@ -161,12 +222,12 @@ impl Location {
                 buffer.push_str(parent);
                 buffer.push('/');
                 write_escaped_str(&mut buffer, property);
-                Self(Arc::new(buffer))
+                Self(Shared::new(buffer))
             }
             LocationSegment::Index(idx) => {
                 let mut buffer = itoa::Buffer::new();
                 let segment = buffer.format(idx);
-                Self(Arc::new(format!("{parent}/{segment}")))
+                Self(Shared::new(format!("{parent}/{segment}")))
             }
         }
     }
@@ -337,7 +398,7 @@ mod tests {
         LocationSegment::Index(2)
     ]; "mixed properties and indices")]
     fn test_into_iter(location: &str, expected_segments: Vec<LocationSegment>) {
-        let loc = Location(Arc::new(location.to_string()));
+        let loc = Location(Shared::new(location.to_string()));
         assert_eq!(loc.into_iter().collect::<Vec<_>>(), expected_segments);
     }
 
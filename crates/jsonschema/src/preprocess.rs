@@ -0,0 +1,246 @@
+//! Transformations of instances applied before validation.
+#[cfg(feature = "unicode-normalization")]
+use std::borrow::Cow;
+use std::sync::Arc;
+
+#[cfg(feature = "unicode-normalization")]
+use icu_normalizer::ComposingNormalizerBorrowed;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::paths::write_escaped_str;
+
+/// Transforms the values of instances before validation, see
+/// [`ValidationOptions::with_preprocessor`].
+///
+/// Useful for normalization that producers of data disagree on and schemas can not express,
+/// e.g. surrounding whitespace or Unicode normalization forms.
+///
+/// [`ValidationOptions::with_preprocessor`]: crate::ValidationOptions::with_preprocessor
+pub trait Preprocessor: Send + Sync + 'static {
+    /// The name of the transformation, recorded in [`Transformation::preprocessor`].
+    fn name(&self) -> &str;
+    /// The replacement for `value`, or `None` to keep it.
+    ///
+    /// Called for every value of an instance, with arrays and objects after their members.
+    fn process(&self, value: &Value) -> Option<Value>;
+}
+
+/// Removes leading and trailing whitespace from strings.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TrimStrings;
+
+impl Preprocessor for TrimStrings {
+    fn name(&self) -> &str {
+        "trimStrings"
+    }
+    fn process(&self, value: &Value) -> Option<Value> {
+        let string = value.as_str()?;
+        let trimmed = string.trim();
+        (trimmed.len() != string.len()).then(|| Value::String(trimmed.to_string()))
+    }
+}
+
+/// Normalizes strings to the Unicode Normalization Form C (NFC), i.e. composed characters.
+///
+/// Requires the `unicode-normalization` feature.
+#[cfg(feature = "unicode-normalization")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NormalizeNfc;
+
+#[cfg(feature = "unicode-normalization")]
+impl Preprocessor for NormalizeNfc {
+    fn name(&self) -> &str {
+        "normalizeNfc"
+    }
+    fn process(&self, value: &Value) -> Option<Value> {
+        let string = value.as_str()?;
+        match ComposingNormalizerBorrowed::new_nfc().normalize(string) {
+            Cow::Borrowed(_) => None,
+            Cow::Owned(normalized) => Some(Value::String(normalized)),
+        }
+    }
+}
+
+/// A value replaced by a [`Preprocessor`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Transformation {
+    path: String,
+    preprocessor: String,
+    original: Value,
+    processed: Value,
+}
+
+impl Transformation {
+    /// JSON Pointer to the value within the instance.
+    #[must_use]
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+    /// The name of the preprocessor.
+    #[must_use]
+    pub fn preprocessor(&self) -> &str {
+        &self.preprocessor
+    }
+    /// The value before the transformation.
+    #[must_use]
+    pub fn original(&self) -> &Value {
+        &self.original
+    }
+    /// The value after the transformation.
+    #[must_use]
+    pub fn processed(&self) -> &Value {
+        &self.processed
+    }
+}
+
+/// An instance transformed by the preprocessors of a validator, see
+/// [`Validator::preprocess`](crate::Validator::preprocess).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Preprocessed {
+    instance: Value,
+    transformations: Vec<Transformation>,
+}
+
+impl Preprocessed {
+    /// The transformed instance.
+    #[must_use]
+    pub fn instance(&self) -> &Value {
+        &self.instance
+    }
+    /// The applied transformations, in the order they were applied.
+    #[must_use]
+    pub fn transformations(&self) -> &[Transformation] {
+        &self.transformations
+    }
+    /// The transformed instance and the applied transformations.
+    #[must_use]
+    pub fn into_parts(self) -> (Value, Vec<Transformation>) {
+        (self.instance, self.transformations)
+    }
+}
+
+pub(crate) fn preprocess(
+    preprocessors: &[Arc<dyn Preprocessor>],
+    instance: &Value,
+) -> Preprocessed {
+    let mut instance = instance.clone();
+    let mut transformations = Vec::new();
+    visit(
+        preprocessors,
+        &mut instance,
+        &mut String::new(),
+        &mut transformations,
+    );
+    Preprocessed {
+        instance,
+        transformations,
+    }
+}
+
+fn visit(
+    preprocessors: &[Arc<dyn Preprocessor>],
+    value: &mut Value,
+    path: &mut String,
+    transformations: &mut Vec<Transformation>,
+) {
+    let length = path.len();
+    match value {
+        Value::Array(items) => {
+            for (idx, item) in items.iter_mut().enumerate() {
+                path.push('/');
+                path.push_str(itoa::Buffer::new().format(idx));
+                visit(preprocessors, item, path, transformations);
+                path.truncate(length);
+            }
+        }
+        Value::Object(members) => {
+            for (name, member) in members.iter_mut() {
+                path.push('/');
+                write_escaped_str(path, name);
+                visit(preprocessors, member, path, transformations);
+                path.truncate(length);
+            }
+        }
+        _ => {}
+    }
+    for preprocessor in preprocessors {
+        match preprocessor.process(value) {
+            Some(processed) if processed != *value => {
+                transformations.push(Transformation {
+                    path: path.clone(),
+                    preprocessor: preprocessor.name().to_string(),
+                    original: std::mem::replace(value, processed.clone()),
+                    processed,
+                });
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use test_case::test_case;
+
+    #[test_case(&json!("  a b "), Some(json!("a b")))]
+    #[test_case(&json!("a b"), None)]
+    #[test_case(&json!(1), None)]
+    fn trim_strings(value: &Value, expected: Option<Value>) {
+        assert_eq!(TrimStrings.process(value), expected);
+    }
+
+    #[cfg(feature = "unicode-normalization")]
+    #[test_case(&json!("Cafe\u{301}"), Some(json!("Caf\u{e9}")); "decomposed")]
+    #[test_case(&json!("Caf\u{e9}"), None; "composed")]
+    #[test_case(&json!(true), None; "not a string")]
+    fn normalize_nfc(value: &Value, expected: Option<Value>) {
+        assert_eq!(NormalizeNfc.process(value), expected);
+    }
+
+    struct Lowercase;
+
+    impl Preprocessor for Lowercase {
+        fn name(&self) -> &str {
+            "lowercase"
+        }
+        fn process(&self, value: &Value) -> Option<Value> {
+            Some(Value::String(value.as_str()?.to_lowercase()))
+        }
+    }
+
+    #[test]
+    fn records_transformations() {
+        let preprocessors: [Arc<dyn Preprocessor>; 2] =
+            [Arc::new(TrimStrings), Arc::new(Lowercase)];
+        let instance = json!({"a/b": [" x ", "y"], "c": "Cafe "});
+        let preprocessed = preprocess(&preprocessors, &instance);
+        assert_eq!(
+            preprocessed.instance(),
+            &json!({"a/b": ["x", "y"], "c": "cafe"})
+        );
+        let transformations: Vec<_> = preprocessed
+            .transformations()
+            .iter()
+            .map(|transformation| {
+                (
+                    transformation.path(),
+                    transformation.preprocessor(),
+                    transformation.original(),
+                    transformation.processed(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            transformations,
+            [
+                ("/a~1b/0", "trimStrings", &json!(" x "), &json!("x")),
+                ("/c", "trimStrings", &json!("Cafe "), &json!("Cafe")),
+                ("/c", "lowercase", &json!("Cafe"), &json!("cafe")),
+            ]
+        );
+    }
+}
@@ -0,0 +1,227 @@
+//! Evaluation counts and times of subschemas, see [`ValidationOptions::should_profile`].
+//!
+//! [`ValidationOptions::should_profile`]: crate::ValidationOptions::should_profile
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, PoisonError,
+    },
+    time::{Duration, Instant},
+};
+
+use ahash::AHashMap;
+
+/// Evaluation statistics of a subschema, see [`Validator::profile`](crate::Validator::profile).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProfileEntry {
+    location: String,
+    evaluations: u64,
+    total_time: Duration,
+}
+
+impl ProfileEntry {
+    /// The canonical location of the subschema.
+    ///
+    /// A JSON Pointer within the root schema, e.g. `/$defs/item` for a subschema reached via
+    /// `"$ref": "#/$defs/item"` from several places. Subschemas of other documents are
+    /// identified by their absolute URI, e.g. `https://example.com/item.json#/properties/id`.
+    #[must_use]
+    pub fn location(&self) -> &str {
+        &self.location
+    }
+    /// How many times the subschema was evaluated.
+    #[must_use]
+    pub const fn evaluations(&self) -> u64 {
+        self.evaluations
+    }
+    /// The cumulative time spent evaluating the subschema, including its own subschemas.
+    ///
+    /// Time spent in recursive evaluations of the same subschema is counted at every level.
+    #[must_use]
+    pub const fn total_time(&self) -> Duration {
+        self.total_time
+    }
+}
+
+/// Counters shared by all compiled nodes with the same canonical location.
+#[derive(Debug, Default)]
+pub(crate) struct NodeProfile {
+    evaluations: AtomicU64,
+    nanos: AtomicU64,
+}
+
+impl NodeProfile {
+    #[inline]
+    pub(crate) fn record<T>(&self, evaluate: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = evaluate();
+        let elapsed = u64::try_from(start.elapsed().as_nanos()).unwrap_or(u64::MAX);
+        self.evaluations.fetch_add(1, Ordering::Relaxed);
+        self.nanos.fetch_add(elapsed, Ordering::Relaxed);
+        result
+    }
+}
+
+/// Collects the counters of all nodes of a validator, including lazily compiled ones.
+#[derive(Debug, Default)]
+pub(crate) struct Profiler {
+    nodes: Mutex<AHashMap<String, Arc<NodeProfile>>>,
+}
+
+impl Profiler {
+    /// The counters of the node at the canonical `location`.
+    pub(crate) fn node(&self, location: String) -> Arc<NodeProfile> {
+        let mut nodes = self.nodes.lock().unwrap_or_else(PoisonError::into_inner);
+        Arc::clone(nodes.entry(location).or_default())
+    }
+
+    /// Entries of evaluated subschemas, by decreasing total time.
+    pub(crate) fn entries(&self) -> Vec<ProfileEntry> {
+        let nodes = self.nodes.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut entries: Vec<_> = nodes
+            .iter()
+            .filter_map(|(location, node)| {
+                let evaluations = node.evaluations.load(Ordering::Relaxed);
+                (evaluations > 0).then(|| ProfileEntry {
+                    location: location.clone(),
+                    evaluations,
+                    total_time: Duration::from_nanos(node.nanos.load(Ordering::Relaxed)),
+                })
+            })
+            .collect();
+        entries.sort_unstable_by(|left, right| {
+            right
+                .total_time
+                .cmp(&left.total_time)
+                .then_with(|| left.location.cmp(&right.location))
+        });
+        entries
+    }
+
+    pub(crate) fn reset(&self) {
+        let nodes = self.nodes.lock().unwrap_or_else(PoisonError::into_inner);
+        for node in nodes.values() {
+            node.evaluations.store(0, Ordering::Relaxed);
+            node.nanos.store(0, Ordering::Relaxed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    use crate::Resource;
+
+    fn evaluations(validator: &crate::Validator) -> Vec<(String, u64)> {
+        let mut evaluations: Vec<_> = validator
+            .profile()
+            .into_iter()
+            .map(|entry| (entry.location().to_string(), entry.evaluations()))
+            .collect();
+        evaluations.sort();
+        evaluations
+    }
+
+    #[test]
+    fn references_share_entries() {
+        let schema = json!({
+            "properties": {
+                "a": {"$ref": "#/$defs/name"},
+                "b": {"items": {"$ref": "#/$defs/name"}}
+            },
+            "$defs": {"name": {"type": "string"}}
+        });
+        let validator = crate::options()
+            .should_profile(true)
+            .build(&schema)
+            .expect("Invalid schema");
+        assert!(validator.is_valid(&json!({"a": "x", "b": ["y", "z"]})));
+        assert_eq!(
+            evaluations(&validator),
+            [
+                (String::new(), 1),
+                ("/$defs/name".to_string(), 3),
+                ("/properties/a".to_string(), 1),
+                ("/properties/b".to_string(), 1),
+                ("/properties/b/items".to_string(), 2),
+            ]
+        );
+        let root = &validator.profile()[0];
+        assert_eq!(root.location(), "");
+        assert!(validator
+            .profile()
+            .iter()
+            .all(|entry| entry.total_time() <= root.total_time()));
+    }
+
+    #[test]
+    fn other_documents() {
+        let schema = json!({"$ref": "https://example.com/item.json#/$defs/id"});
+        let item = json!({"$defs": {"id": {"anyOf": [{"type": "integer"}]}}});
+        let validator = crate::options()
+            .should_profile(true)
+            .with_resource(
+                "https://example.com/item.json",
+                Resource::from_contents(item).expect("Invalid resource"),
+            )
+            .build(&schema)
+            .expect("Invalid schema");
+        assert!(validator.validate(&json!(1)).is_ok());
+        assert_eq!(
+            evaluations(&validator),
+            [
+                (String::new(), 1),
+                ("https://example.com/item.json#/$defs/id".to_string(), 1),
+                (
+                    "https://example.com/item.json#/$defs/id/anyOf/0".to_string(),
+                    1
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn recursive_references() {
+        let schema = json!({
+            "$id": "https://example.com/tree.json",
+            "properties": {"children": {"items": {"$ref": "#"}}}
+        });
+        let validator = crate::options()
+            .should_profile(true)
+            .build(&schema)
+            .expect("Invalid schema");
+        let instance = json!({"children": [{"children": [{}]}, {}]});
+        assert!(validator.is_valid(&instance));
+        let profile = evaluations(&validator);
+        assert!(profile.contains(&(String::new(), 4)), "{profile:?}");
+    }
+
+    #[test_case(&json!({"a": 1}); "valid")]
+    #[test_case(&json!({"a": "x"}); "invalid")]
+    fn all_methods_record(instance: &Value) {
+        let schema = json!({"properties": {"a": {"type": "integer"}}});
+        let validator = crate::options()
+            .should_profile(true)
+            .build(&schema)
+            .expect("Invalid schema");
+        let _ = validator.is_valid(instance);
+        let _ = validator.validate(instance);
+        let _ = validator.iter_errors(instance).count();
+        let _ = validator.apply(instance).basic();
+        assert_eq!(
+            evaluations(&validator),
+            [(String::new(), 4), ("/properties/a".to_string(), 4)]
+        );
+        validator.reset_profile();
+        assert!(validator.profile().is_empty());
+    }
+
+    #[test]
+    fn disabled_by_default() {
+        let validator = crate::validator_for(&json!({"type": "integer"})).expect("Invalid schema");
+        assert!(validator.is_valid(&json!(1)));
+        assert!(validator.profile().is_empty());
+    }
+}
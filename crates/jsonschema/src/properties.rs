@@ -3,6 +3,7 @@ use crate::{
     node::SchemaNode,
     paths::Location,
     regex::{build_fancy_regex, build_regex},
+    sync::MaybeSendSync,
     validator::Validate as _,
 };
 use ahash::AHashMap;
@@ -14,9 +15,10 @@ pub(crate) type FancyRegexValidators = Vec<(fancy_regex::Regex, SchemaNode)>;
 pub(crate) type RegexValidators = Vec<(regex::Regex, SchemaNode)>;
 
 /// A value that can look up property validators by name.
-pub(crate) trait PropertiesValidatorsMap: Send + Sync {
+pub(crate) trait PropertiesValidatorsMap: MaybeSendSync {
     fn get_validator(&self, property: &str) -> Option<&SchemaNode>;
     fn get_key_validator(&self, property: &str) -> Option<(&String, &SchemaNode)>;
+    fn property_names(&self) -> Vec<&str>;
 }
 
 // We're defining two different property validator map implementations, one for small map sizes and
@@ -48,6 +50,10 @@ impl PropertiesValidatorsMap for SmallValidatorsMap {
         }
         None
     }
+
+    fn property_names(&self) -> Vec<&str> {
+        self.iter().map(|(prop, _)| prop.as_str()).collect()
+    }
 }
 
 impl PropertiesValidatorsMap for BigValidatorsMap {
@@ -60,6 +66,10 @@ impl PropertiesValidatorsMap for BigValidatorsMap {
     fn get_key_validator(&self, property: &str) -> Option<(&String, &SchemaNode)> {
         self.get_key_value(property)
     }
+
+    fn property_names(&self) -> Vec<&str> {
+        self.keys().map(String::as_str).collect()
+    }
 }
 
 pub(crate) fn compile_small_map<'a>(
@@ -121,9 +131,15 @@ pub(crate) fn compile_fancy_regex_patterns<'a>(
     let mut compiled_patterns = Vec::with_capacity(obj.len());
     for (pattern, subschema) in obj {
         let pctx = kctx.new_at_location(pattern.as_str());
-        if let Ok(Ok(compiled_pattern)) = ecma::to_rust_regex(pattern)
-            .map(|pattern| build_fancy_regex(&pattern, backtrack_limit, size_limit, dfa_size_limit))
-        {
+        if let Ok(Ok(compiled_pattern)) = ecma::to_rust_regex(pattern).map(|pattern| {
+            build_fancy_regex(
+                &pattern,
+                backtrack_limit,
+                size_limit,
+                dfa_size_limit,
+                ctx.config().shares_regexes(),
+            )
+        }) {
             let node = compiler::compile(&pctx, pctx.as_resource_ref(subschema))?;
             compiled_patterns.push((compiled_pattern, node));
         } else {
@@ -149,9 +165,14 @@ pub(crate) fn compile_regex_patterns<'a>(
     let mut compiled_patterns = Vec::with_capacity(obj.len());
     for (pattern, subschema) in obj {
         let pctx = kctx.new_at_location(pattern.as_str());
-        if let Ok(Ok(compiled_pattern)) = ecma::to_rust_regex(pattern)
-            .map(|pattern| build_regex(&pattern, size_limit, dfa_size_limit))
-        {
+        if let Ok(Ok(compiled_pattern)) = ecma::to_rust_regex(pattern).map(|pattern| {
+            build_regex(
+                &pattern,
+                size_limit,
+                dfa_size_limit,
+                ctx.config().shares_regexes(),
+            )
+        }) {
             let node = compiler::compile(&pctx, pctx.as_resource_ref(subschema))?;
             compiled_patterns.push((compiled_pattern, node));
         } else {
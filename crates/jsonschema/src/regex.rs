@@ -1,3 +1,8 @@
+use std::sync::{Mutex, PoisonError};
+
+use ahash::AHashMap;
+use once_cell::sync::Lazy;
+
 pub(crate) trait RegexEngine: Sized + Send + Sync {
     type Error: RegexError;
     fn is_match(&self, text: &str) -> Result<bool, Self::Error>;
@@ -45,37 +50,211 @@ impl RegexError for regex::Error {
     }
 }
 
+/// Pattern text along with the engine limits it was compiled with.
+type CacheKey = (String, [Option<usize>; 3]);
+
+/// Maximum number of regexes kept per engine, so the cache can not grow without bounds.
+const CACHE_CAPACITY: usize = 1024;
+
+/// Compiled regexes shared by all validators in the process.
+///
+/// Schemas often repeat the same patterns (UUIDs, e-mails, etc.), and building many validators
+/// would otherwise compile them again each time. Clones of a compiled regex share its automata.
+///
+/// Once full, the least recently used regex is evicted, so patterns from untrusted schemas can
+/// not occupy the cache for good.
+struct RegexCache<R>(Lazy<Mutex<Entries<R>>>);
+
+struct Entries<R> {
+    /// Regexes along with the time they were last used.
+    regexes: AHashMap<CacheKey, (R, u64)>,
+    /// Incremented on each use.
+    clock: u64,
+}
+
+impl<R: Clone> RegexCache<R> {
+    const fn new() -> Self {
+        RegexCache(Lazy::new(|| {
+            Mutex::new(Entries {
+                regexes: AHashMap::new(),
+                clock: 0,
+            })
+        }))
+    }
+
+    fn get_or_try_insert<E>(
+        &self,
+        key: CacheKey,
+        build: impl FnOnce(&str) -> Result<R, E>,
+    ) -> Result<R, E> {
+        {
+            let mut entries = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+            entries.clock += 1;
+            let clock = entries.clock;
+            if let Some((regex, used)) = entries.regexes.get_mut(&key) {
+                *used = clock;
+                return Ok(regex.clone());
+            }
+        }
+        // Build outside of the lock, as compiling a regex may take a while
+        let regex = build(&key.0)?;
+        let mut entries = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        if entries.regexes.len() >= CACHE_CAPACITY && !entries.regexes.contains_key(&key) {
+            let oldest = entries
+                .regexes
+                .iter()
+                .min_by_key(|(_, (_, used))| *used)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                entries.regexes.remove(&oldest);
+            }
+        }
+        entries.clock += 1;
+        let clock = entries.clock;
+        entries.regexes.insert(key, (regex.clone(), clock));
+        Ok(regex)
+    }
+}
+
+static FANCY_REGEX_CACHE: RegexCache<fancy_regex::Regex> = RegexCache::new();
+static REGEX_CACHE: RegexCache<regex::Regex> = RegexCache::new();
+
+/// Build a `fancy-regex` regex, reusing a previously compiled one if `shared` is set.
 #[allow(clippy::result_large_err)]
 pub(crate) fn build_fancy_regex(
     pattern: &str,
     backtrack_limit: Option<usize>,
     size_limit: Option<usize>,
     dfa_size_limit: Option<usize>,
+    shared: bool,
 ) -> Result<fancy_regex::Regex, fancy_regex::Error> {
-    let mut builder = fancy_regex::RegexBuilder::new(pattern);
-    if let Some(limit) = backtrack_limit {
-        builder.backtrack_limit(limit);
-    }
-    if let Some(limit) = size_limit {
-        builder.delegate_size_limit(limit);
+    let build = |pattern: &str| {
+        let mut builder = fancy_regex::RegexBuilder::new(pattern);
+        if let Some(limit) = backtrack_limit {
+            builder.backtrack_limit(limit);
+        }
+        if let Some(limit) = size_limit {
+            builder.delegate_size_limit(limit);
+        }
+        if let Some(limit) = dfa_size_limit {
+            builder.delegate_dfa_size_limit(limit);
+        }
+        builder.build()
+    };
+    if !shared {
+        return build(pattern);
     }
-    if let Some(limit) = dfa_size_limit {
-        builder.delegate_dfa_size_limit(limit);
-    }
-    builder.build()
+    let key = (
+        pattern.to_string(),
+        [backtrack_limit, size_limit, dfa_size_limit],
+    );
+    FANCY_REGEX_CACHE.get_or_try_insert(key, build)
 }
 
+/// Build a `regex` regex, reusing a previously compiled one if `shared` is set.
 pub(crate) fn build_regex(
     pattern: &str,
     size_limit: Option<usize>,
     dfa_size_limit: Option<usize>,
+    shared: bool,
 ) -> Result<regex::Regex, regex::Error> {
-    let mut builder = regex::RegexBuilder::new(pattern);
-    if let Some(limit) = size_limit {
-        builder.size_limit(limit);
+    let build = |pattern: &str| {
+        let mut builder = regex::RegexBuilder::new(pattern);
+        if let Some(limit) = size_limit {
+            builder.size_limit(limit);
+        }
+        if let Some(limit) = dfa_size_limit {
+            builder.dfa_size_limit(limit);
+        }
+        builder.build()
+    };
+    if !shared {
+        return build(pattern);
+    }
+    let key = (pattern.to_string(), [None, size_limit, dfa_size_limit]);
+    REGEX_CACHE.get_or_try_insert(key, build)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_cached<R: Clone>(
+        cache: &RegexCache<R>,
+        pattern: &str,
+        limits: [Option<usize>; 3],
+    ) -> bool {
+        cache
+            .0
+            .lock()
+            .expect("Lock is poisoned")
+            .regexes
+            .contains_key(&(pattern.to_string(), limits))
+    }
+
+    #[test]
+    fn shared_across_validators() {
+        let pattern = "^shared-[a-z]+$";
+        let schema = serde_json::json!({"pattern": pattern, "patternProperties": {pattern: true}});
+        let first = crate::validator_for(&schema).expect("Invalid schema");
+        let second = crate::validator_for(&schema).expect("Invalid schema");
+        assert!(is_cached(&FANCY_REGEX_CACHE, pattern, [None; 3]));
+        assert!(first.is_valid(&serde_json::json!("shared-a")));
+        assert!(!second.is_valid(&serde_json::json!("shared-1")));
     }
-    if let Some(limit) = dfa_size_limit {
-        builder.dfa_size_limit(limit);
+
+    #[test]
+    fn keyed_by_limits() {
+        let pattern = "^limited-[a-z]+$";
+        build_regex(pattern, Some(1_000_000), None, true).expect("Invalid regex");
+        assert!(is_cached(
+            &REGEX_CACHE,
+            pattern,
+            [None, Some(1_000_000), None]
+        ));
+        assert!(!is_cached(&REGEX_CACHE, pattern, [None; 3]));
+    }
+
+    #[test]
+    fn errors_are_not_cached() {
+        let pattern = "^invalid-(";
+        assert!(build_regex(pattern, None, None, true).is_err());
+        assert!(!is_cached(&REGEX_CACHE, pattern, [None; 3]));
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let cache = RegexCache::new();
+        let key = |idx: usize| (format!("^{idx}$"), [None; 3]);
+        for idx in 0..CACHE_CAPACITY {
+            cache
+                .get_or_try_insert(key(idx), |_| Ok::<_, ()>(idx))
+                .expect("Not failing");
+        }
+        // Use the first regex again, so the second one is the least recently used
+        assert_eq!(
+            cache.get_or_try_insert(key(0), |_| Err(())),
+            Ok(0),
+            "Cached"
+        );
+        cache
+            .get_or_try_insert(key(CACHE_CAPACITY), |_| Ok::<_, ()>(CACHE_CAPACITY))
+            .expect("Not failing");
+        let entries = cache.0.lock().expect("Lock is poisoned");
+        assert_eq!(entries.regexes.len(), CACHE_CAPACITY);
+        assert!(entries.regexes.contains_key(&key(0)));
+        assert!(!entries.regexes.contains_key(&key(1)));
+        assert!(entries.regexes.contains_key(&key(CACHE_CAPACITY)));
+    }
+
+    #[test]
+    fn not_shared_if_disabled() {
+        let pattern = "^private-[a-z]+$";
+        let validator = crate::options()
+            .should_share_regexes(false)
+            .build(&serde_json::json!({"pattern": pattern}))
+            .expect("Invalid schema");
+        assert!(validator.is_valid(&serde_json::json!("private-a")));
+        assert!(!is_cached(&FANCY_REGEX_CACHE, pattern, [None; 3]));
     }
-    builder.build()
 }
@@ -0,0 +1,271 @@
+//! Suggested changes turning invalid instances into valid ones.
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{error::ValidationErrorKind, ValidationError, Validator};
+
+/// Fixes may uncover errors that were hidden by previous ones, e.g. in removed properties.
+const MAX_ROUNDS: usize = 16;
+
+/// A JSON Patch (RFC 6902) operation suggested by [`Validator::remediate`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum PatchOperation {
+    /// Remove the value at `path`.
+    Remove {
+        /// JSON Pointer to the value.
+        path: String,
+    },
+    /// Replace the value at `path` with `value`.
+    Replace {
+        /// JSON Pointer to the value.
+        path: String,
+        /// The new value.
+        value: Value,
+    },
+}
+
+impl PatchOperation {
+    /// JSON Pointer to the changed value.
+    #[must_use]
+    pub fn path(&self) -> &str {
+        match self {
+            PatchOperation::Remove { path } | PatchOperation::Replace { path, .. } => path,
+        }
+    }
+}
+
+/// Changes that make an instance valid, or as close to valid as feasible.
+///
+/// Only unambiguous fixes are suggested:
+///
+/// - properties rejected by `additionalProperties` or `unevaluatedProperties` are removed;
+/// - numbers outside of `minimum` / `maximum` are clamped, as are integers outside of
+///   `exclusiveMinimum` / `exclusiveMaximum`;
+/// - strings matching an `enum` option or `const` except for their case are replaced by it.
+///
+/// Other errors are left for a human to resolve, see [`Remediation::is_complete`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Remediation {
+    operations: Vec<PatchOperation>,
+    patched: Value,
+    complete: bool,
+}
+
+impl Remediation {
+    /// The suggested changes as JSON Patch operations, applicable in order.
+    #[must_use]
+    pub fn operations(&self) -> &[PatchOperation] {
+        &self.operations
+    }
+    /// The instance with all changes applied.
+    #[must_use]
+    pub fn patched(&self) -> &Value {
+        &self.patched
+    }
+    /// Whether the patched instance is valid.
+    #[must_use]
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
+}
+
+pub(crate) fn remediate(validator: &Validator, instance: &Value) -> Remediation {
+    let mut patched = instance.clone();
+    let mut operations: Vec<PatchOperation> = Vec::new();
+    for _ in 0..MAX_ROUNDS {
+        let (mut fixes, removals): (Vec<_>, Vec<_>) = validator
+            .iter_errors(&patched)
+            .flat_map(|error| fixes_for(&error))
+            .partition(|fix| matches!(fix, PatchOperation::Replace { .. }));
+        // Properties with invalid values may be reported as unevaluated as well, so they are
+        // removed only if fixing values does not make them valid
+        if fixes.is_empty() {
+            fixes = removals;
+        }
+        let mut applied = false;
+        for fix in fixes {
+            if apply(&mut patched, &fix) {
+                applied = true;
+                record(&mut operations, fix);
+            }
+        }
+        if !applied {
+            break;
+        }
+    }
+    let complete = validator.is_valid(&patched);
+    Remediation {
+        operations,
+        patched,
+        complete,
+    }
+}
+
+/// Record `operation`, replacing earlier operations it supersedes.
+fn record(operations: &mut Vec<PatchOperation>, operation: PatchOperation) {
+    if let PatchOperation::Replace { path, value } = &operation {
+        for previous in operations.iter_mut() {
+            if let PatchOperation::Replace {
+                path: previous_path,
+                value: previous_value,
+            } = previous
+            {
+                if previous_path == path {
+                    *previous_value = value.clone();
+                    return;
+                }
+            }
+        }
+    }
+    operations.push(operation);
+}
+
+fn fixes_for(error: &ValidationError<'_>) -> Vec<PatchOperation> {
+    let location = &error.instance_path;
+    let replace = |value: Value| {
+        vec![PatchOperation::Replace {
+            path: location.as_str().to_string(),
+            value,
+        }]
+    };
+    match &error.kind {
+        ValidationErrorKind::AdditionalProperties { unexpected, .. }
+        | ValidationErrorKind::UnevaluatedProperties { unexpected } => unexpected
+            .iter()
+            .map(|property| PatchOperation::Remove {
+                path: location.join(property).as_str().to_string(),
+            })
+            .collect(),
+        ValidationErrorKind::Minimum { limit } | ValidationErrorKind::Maximum { limit } => {
+            replace(limit.clone())
+        }
+        ValidationErrorKind::ExclusiveMinimum { limit } => {
+            match (error.instance.as_i64(), limit.as_i64()) {
+                (Some(_), Some(limit)) => limit
+                    .checked_add(1)
+                    .map_or_else(Vec::new, |value| replace(Value::from(value))),
+                _ => Vec::new(),
+            }
+        }
+        ValidationErrorKind::ExclusiveMaximum { limit } => {
+            match (error.instance.as_i64(), limit.as_i64()) {
+                (Some(_), Some(limit)) => limit
+                    .checked_sub(1)
+                    .map_or_else(Vec::new, |value| replace(Value::from(value))),
+                _ => Vec::new(),
+            }
+        }
+        ValidationErrorKind::Enum {
+            options: Value::Array(options),
+        } => matching_case(&error.instance, options).map_or_else(Vec::new, replace),
+        ValidationErrorKind::Constant { expected_value } => {
+            matching_case(&error.instance, std::slice::from_ref(expected_value))
+                .map_or_else(Vec::new, replace)
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// The only option equal to `instance` when ignoring ASCII case.
+fn matching_case(instance: &Value, options: &[Value]) -> Option<Value> {
+    let instance = instance.as_str()?;
+    let mut candidates = options.iter().filter(|option| {
+        option
+            .as_str()
+            .is_some_and(|option| option.eq_ignore_ascii_case(instance))
+    });
+    let candidate = candidates.next()?;
+    candidates.next().is_none().then(|| candidate.clone())
+}
+
+/// Apply `operation` to `instance`, returning whether it changed anything.
+fn apply(instance: &mut Value, operation: &PatchOperation) -> bool {
+    match operation {
+        PatchOperation::Remove { path } => {
+            let Some((parent, key)) = path.rsplit_once('/') else {
+                return false;
+            };
+            let key = key.replace("~1", "/").replace("~0", "~");
+            instance
+                .pointer_mut(parent)
+                .and_then(Value::as_object_mut)
+                .is_some_and(|object| object.remove(&key).is_some())
+        }
+        PatchOperation::Replace { path, value } => match instance.pointer_mut(path) {
+            Some(target) if target != value => {
+                *target = value.clone();
+                true
+            }
+            _ => false,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    use super::PatchOperation;
+
+    #[test_case(
+        &json!({"properties": {"a": {"type": "integer"}}, "additionalProperties": false}),
+        &json!({"a": 1, "b": 2, "c/d": 3}),
+        &json!([{"op": "remove", "path": "/b"}, {"op": "remove", "path": "/c~1d"}]),
+        &json!({"a": 1});
+        "additional properties"
+    )]
+    #[test_case(
+        &json!({"properties": {"age": {"minimum": 0, "maximum": 150}, "count": {"type": "integer", "exclusiveMinimum": 0}}}),
+        &json!({"age": 200, "count": -5}),
+        &json!([{"op": "replace", "path": "/age", "value": 150}, {"op": "replace", "path": "/count", "value": 1}]),
+        &json!({"age": 150, "count": 1});
+        "clamping"
+    )]
+    #[test_case(
+        &json!({"properties": {"color": {"enum": ["Red", "Green"]}, "kind": {"const": "user"}}}),
+        &json!({"color": "RED", "kind": "User"}),
+        &json!([{"op": "replace", "path": "/color", "value": "Red"}, {"op": "replace", "path": "/kind", "value": "user"}]),
+        &json!({"color": "Red", "kind": "user"});
+        "enum case"
+    )]
+    #[test_case(
+        &json!({"items": {"properties": {"a": {"maximum": 1}}, "unevaluatedProperties": false}}),
+        &json!([{"a": 5, "b": 1}]),
+        &json!([{"op": "replace", "path": "/0/a", "value": 1}, {"op": "remove", "path": "/0/b"}]),
+        &json!([{"a": 1}]);
+        "nested"
+    )]
+    #[test_case(
+        &json!({"type": "integer"}),
+        &json!(1),
+        &json!([]),
+        &json!(1);
+        "valid"
+    )]
+    fn complete(schema: &Value, instance: &Value, operations: &Value, patched: &Value) {
+        let validator = crate::validator_for(schema).expect("Invalid schema");
+        let remediation = validator.remediate(instance);
+        assert!(remediation.is_complete());
+        assert_eq!(
+            &serde_json::to_value(remediation.operations()).expect("Serializable"),
+            operations
+        );
+        assert_eq!(remediation.patched(), patched);
+    }
+
+    #[test_case(&json!({"required": ["a"], "additionalProperties": false}), &json!({"b": 1}); "unfixable error")]
+    #[test_case(&json!({"enum": ["a", "A"]}), &json!("b"); "no case match")]
+    #[test_case(&json!({"enum": ["ab", "AB"]}), &json!("Ab"); "ambiguous case")]
+    #[test_case(&json!({"exclusiveMaximum": 1.5}), &json!(2); "non-integer bound")]
+    fn incomplete(schema: &Value, instance: &Value) {
+        let validator = crate::validator_for(schema).expect("Invalid schema");
+        let remediation = validator.remediate(instance);
+        assert!(!remediation.is_complete());
+        assert!(remediation
+            .operations()
+            .iter()
+            .all(|operation| matches!(operation, PatchOperation::Remove { .. })));
+    }
+}
@@ -1,15 +1,28 @@
 //! Logic for retrieving external resources.
-use referencing::{Retrieve, Uri};
+use referencing::{Retrieve, Retrieved, Uri};
 use serde_json::Value;
 
-pub(crate) struct DefaultRetriever;
+/// The retriever used unless another one is set via
+/// [`ValidationOptions::with_retriever`](crate::ValidationOptions::with_retriever).
+///
+/// Fetches `http(s)` URIs with the `resolve-http` feature and `file` URIs with the `resolve-file`
+/// feature, e.g. for wrapping it in a custom retriever that adds caching or logging.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DefaultRetriever;
 
 impl Retrieve for DefaultRetriever {
-    #[allow(unused)]
     fn retrieve(
         &self,
         uri: &Uri<String>,
     ) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+        Retrieve::retrieve_bounded(self, uri, usize::MAX).map(Retrieved::into_document)
+    }
+    #[allow(unused)]
+    fn retrieve_bounded(
+        &self,
+        uri: &Uri<String>,
+        max_bytes: usize,
+    ) -> Result<Retrieved, Box<dyn std::error::Error + Send + Sync>> {
         #[cfg(target_arch = "wasm32")]
         {
             Err("External references are not supported in WASM".into())
@@ -19,7 +32,8 @@ impl Retrieve for DefaultRetriever {
             "http" | "https" => {
                 #[cfg(any(feature = "resolve-http", test))]
                 {
-                    Ok(reqwest::blocking::get(uri.as_str())?.json()?)
+                    let response = reqwest::blocking::get(uri.as_str())?;
+                    Ok(Retrieved::from_slice(&read_bounded(response, max_bytes)?)?)
                 }
                 #[cfg(not(any(feature = "resolve-http", test)))]
                 Err("`resolve-http` feature or a custom resolver is required to resolve external schemas via HTTP".into())
@@ -41,7 +55,7 @@ impl Retrieve for DefaultRetriever {
                         }
                     };
                     let file = std::fs::File::open(path)?;
-                    Ok(serde_json::from_reader(file)?)
+                    Ok(Retrieved::from_slice(&read_bounded(file, max_bytes)?)?)
                 }
                 #[cfg(not(any(feature = "resolve-file", test)))]
                 {
@@ -53,6 +67,28 @@ impl Retrieve for DefaultRetriever {
     }
 }
 
+/// Read at most `max_bytes`, failing with [`DocumentTooLarge`](referencing::DocumentTooLarge)
+/// instead of reading any further.
+#[cfg(all(
+    not(target_arch = "wasm32"),
+    any(feature = "resolve-http", feature = "resolve-file", test)
+))]
+fn read_bounded(
+    reader: impl std::io::Read,
+    max_bytes: usize,
+) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
+    use std::io::Read;
+
+    let mut body = Vec::new();
+    reader
+        .take((max_bytes as u64).saturating_add(1))
+        .read_to_end(&mut body)?;
+    if body.len() > max_bytes {
+        return Err(Box::new(referencing::DocumentTooLarge));
+    }
+    Ok(body)
+}
+
 #[cfg(feature = "resolve-async")]
 #[async_trait::async_trait]
 impl referencing::AsyncRetrieve for DefaultRetriever {
@@ -60,6 +96,16 @@ impl referencing::AsyncRetrieve for DefaultRetriever {
         &self,
         uri: &Uri<String>,
     ) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+        referencing::AsyncRetrieve::retrieve_bounded(self, uri, usize::MAX)
+            .await
+            .map(Retrieved::into_document)
+    }
+    #[allow(unused)]
+    async fn retrieve_bounded(
+        &self,
+        uri: &Uri<String>,
+        max_bytes: usize,
+    ) -> Result<Retrieved, Box<dyn std::error::Error + Send + Sync>> {
         #[cfg(target_arch = "wasm32")]
         {
             Err("External references are not supported in WASM".into())
@@ -69,7 +115,15 @@ impl referencing::AsyncRetrieve for DefaultRetriever {
             "http" | "https" => {
                 #[cfg(any(feature = "resolve-http", test))]
                 {
-                    Ok(reqwest::get(uri.as_str()).await?.json().await?)
+                    let mut response = reqwest::get(uri.as_str()).await?;
+                    let mut body = Vec::new();
+                    while let Some(chunk) = response.chunk().await? {
+                        if chunk.len() > max_bytes - body.len() {
+                            return Err(Box::new(referencing::DocumentTooLarge));
+                        }
+                        body.extend_from_slice(&chunk);
+                    }
+                    Ok(Retrieved::from_slice(&body)?)
                 }
                 #[cfg(not(any(feature = "resolve-http", test)))]
                 Err("`resolve-http` feature or a custom resolver is required to resolve external schemas via HTTP".into())
@@ -80,7 +134,7 @@ impl referencing::AsyncRetrieve for DefaultRetriever {
                     // File operations are blocking, so we use tokio's spawn_blocking
                     let path = uri.path().as_str().to_string();
                     let contents = tokio::task::spawn_blocking(
-                        move || -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+                        move || -> Result<Retrieved, Box<dyn std::error::Error + Send + Sync>> {
                             let path = {
                                 #[cfg(windows)]
                                 {
@@ -93,7 +147,7 @@ impl referencing::AsyncRetrieve for DefaultRetriever {
                                 }
                             };
                             let file = std::fs::File::open(path)?;
-                            Ok(serde_json::from_reader(file)?)
+                            Ok(Retrieved::from_slice(&read_bounded(file, max_bytes)?)?)
                         },
                     )
                     .await??;
@@ -109,6 +163,213 @@ impl referencing::AsyncRetrieve for DefaultRetriever {
     }
 }
 
+/// Restrictions on the documents retrieved by [`from_url`](crate::from_url).
+///
+/// By default, only `http(s)` URLs with the same scheme, host and port as the root schema are
+/// allowed, at most 32 documents are retrieved, and they may be up to 4 MiB large in total.
+#[cfg(all(feature = "resolve-http", not(target_arch = "wasm32")))]
+#[derive(Debug, Clone)]
+pub struct RetrievalPolicy {
+    allowed: Vec<String>,
+    max_documents: usize,
+    max_total_size: usize,
+}
+
+#[cfg(all(feature = "resolve-http", not(target_arch = "wasm32")))]
+impl Default for RetrievalPolicy {
+    fn default() -> Self {
+        RetrievalPolicy {
+            allowed: Vec::new(),
+            max_documents: 32,
+            max_total_size: 4 * 1024 * 1024,
+        }
+    }
+}
+
+#[cfg(all(feature = "resolve-http", not(target_arch = "wasm32")))]
+impl RetrievalPolicy {
+    /// Create a policy with the default restrictions.
+    #[must_use]
+    pub fn new() -> RetrievalPolicy {
+        RetrievalPolicy::default()
+    }
+    /// Allow URLs under `prefix`, e.g. `https://json.schemastore.org/`.
+    ///
+    /// URLs are allowed if their scheme, host and port are the same as in `prefix` and their
+    /// path starts with the path of `prefix`, up to a `/`. Redirects are followed only to allowed
+    /// URLs. Once any prefix is allowed, the host of the root schema is no longer allowed
+    /// implicitly.
+    #[must_use]
+    pub fn allow(mut self, prefix: impl Into<String>) -> Self {
+        self.allowed.push(prefix.into());
+        self
+    }
+    /// Maximum number of retrieved documents, including the root schema.
+    #[must_use]
+    pub fn max_documents(mut self, limit: usize) -> Self {
+        self.max_documents = limit;
+        self
+    }
+    /// Maximum size of the root schema and, separately, the total size of the documents it
+    /// references, in bytes. See
+    /// [`ValidationOptions::with_retrieval_limits`](crate::ValidationOptions::with_retrieval_limits).
+    #[must_use]
+    pub fn max_total_size(mut self, limit: usize) -> Self {
+        self.max_total_size = limit;
+        self
+    }
+}
+
+/// A URL prefix allowed by a [`RetrievalPolicy`].
+#[cfg(all(feature = "resolve-http", not(target_arch = "wasm32")))]
+#[derive(Debug, Clone)]
+struct AllowedPrefix {
+    scheme: String,
+    host: String,
+    port: Option<u16>,
+    path: String,
+}
+
+#[cfg(all(feature = "resolve-http", not(target_arch = "wasm32")))]
+impl AllowedPrefix {
+    fn new(url: &reqwest::Url) -> AllowedPrefix {
+        AllowedPrefix {
+            scheme: url.scheme().to_string(),
+            host: url.host_str().unwrap_or_default().to_string(),
+            port: url.port_or_known_default(),
+            path: url.path().to_string(),
+        }
+    }
+    fn matches(&self, url: &reqwest::Url) -> bool {
+        if !matches!(url.scheme(), "http" | "https")
+            || url.scheme() != self.scheme
+            || url.host_str() != Some(self.host.as_str())
+            || url.port_or_known_default() != self.port
+        {
+            return false;
+        }
+        let Some(rest) = url.path().strip_prefix(self.path.as_str()) else {
+            return false;
+        };
+        self.path.ends_with('/') || rest.is_empty() || rest.starts_with('/')
+    }
+}
+
+/// Fetches `http(s)` URLs allowed by a [`RetrievalPolicy`].
+#[cfg(all(feature = "resolve-http", not(target_arch = "wasm32")))]
+pub(crate) struct PolicyRetriever {
+    allowed: std::sync::Arc<[AllowedPrefix]>,
+    client: reqwest::blocking::Client,
+}
+
+#[cfg(all(feature = "resolve-http", not(target_arch = "wasm32")))]
+impl PolicyRetriever {
+    /// Create a retriever for the schema at `root`, which is allowed unless the policy lists
+    /// prefixes explicitly.
+    pub(crate) fn new(
+        policy: &RetrievalPolicy,
+        root: &str,
+    ) -> Result<PolicyRetriever, Box<dyn std::error::Error + Send + Sync>> {
+        let allowed: std::sync::Arc<[AllowedPrefix]> = if policy.allowed.is_empty() {
+            let mut root = reqwest::Url::parse(root)?;
+            root.set_path("/");
+            vec![AllowedPrefix::new(&root)].into()
+        } else {
+            policy
+                .allowed
+                .iter()
+                .map(|prefix| reqwest::Url::parse(prefix).map(|url| AllowedPrefix::new(&url)))
+                .collect::<Result<Vec<_>, _>>()?
+                .into()
+        };
+        let redirects = std::sync::Arc::clone(&allowed);
+        let client = reqwest::blocking::Client::builder()
+            .redirect(reqwest::redirect::Policy::custom(move |attempt| {
+                if attempt.previous().len() >= 10 {
+                    attempt.error("too many redirects")
+                } else if redirects.iter().any(|prefix| prefix.matches(attempt.url())) {
+                    attempt.follow()
+                } else {
+                    let error = format!(
+                        "Redirect to '{}' is not allowed by the retrieval policy",
+                        attempt.url()
+                    );
+                    attempt.error(error)
+                }
+            }))
+            .build()?;
+        Ok(PolicyRetriever { allowed, client })
+    }
+}
+
+#[cfg(all(feature = "resolve-http", not(target_arch = "wasm32")))]
+impl Retrieve for PolicyRetriever {
+    fn retrieve(
+        &self,
+        uri: &Uri<String>,
+    ) -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
+        Retrieve::retrieve_bounded(self, uri, usize::MAX).map(Retrieved::into_document)
+    }
+    fn retrieve_bounded(
+        &self,
+        uri: &Uri<String>,
+        max_bytes: usize,
+    ) -> Result<Retrieved, Box<dyn std::error::Error + Send + Sync>> {
+        let url = reqwest::Url::parse(uri.as_str())?;
+        if !self.allowed.iter().any(|prefix| prefix.matches(&url)) {
+            return Err(format!("'{uri}' is not allowed by the retrieval policy").into());
+        }
+        let response = match self.client.get(url).send() {
+            Ok(response) => response.error_for_status()?,
+            Err(error) if error.is_redirect() => {
+                // Surface the reason given by the redirect policy
+                return Err(match std::error::Error::source(&error) {
+                    Some(reason) => format!("{error}: {reason}").into(),
+                    None => error.into(),
+                });
+            }
+            Err(error) => return Err(error.into()),
+        };
+        Ok(Retrieved::from_slice(&read_bounded(response, max_bytes)?)?)
+    }
+}
+
+/// Retrieve the schema at `url` and build a validator for it, see [`from_url`](crate::from_url).
+#[cfg(all(feature = "resolve-http", not(target_arch = "wasm32")))]
+pub(crate) fn from_url(
+    url: &str,
+    policy: &RetrievalPolicy,
+) -> Result<crate::Validator, crate::ValidationError<'static>> {
+    let unretrievable = |source| referencing::Error::Unretrievable {
+        uri: url.to_string(),
+        source,
+    };
+    let uri = referencing::uri::from_str(url)?;
+    let retriever = PolicyRetriever::new(policy, url).map_err(unretrievable)?;
+    let schema = retriever
+        .retrieve_bounded(&uri, policy.max_total_size)
+        .map_err(|error| {
+            if error.is::<referencing::DocumentTooLarge>() {
+                referencing::Error::LimitExceeded {
+                    uri: url.to_string(),
+                    kind: referencing::LimitKind::TotalBytes,
+                    limit: policy.max_total_size,
+                }
+            } else {
+                unretrievable(error)
+            }
+        })?;
+    crate::options()
+        .with_base_uri(url)
+        .with_retriever(retriever)
+        .with_retrieval_limits(
+            policy.max_documents.saturating_sub(1),
+            policy.max_total_size,
+            usize::MAX,
+        )
+        .build(&schema.into_document())
+}
+
 #[cfg(all(test, not(target_arch = "wasm32")))]
 fn path_to_uri(path: &std::path::Path) -> String {
     use percent_encoding::{percent_encode, AsciiSet, CONTROLS};
@@ -210,6 +471,30 @@ mod tests {
         assert!(!validator.is_valid(&invalid));
     }
 
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_retrieve_from_file_with_limits() {
+        let mut temp_file = tempfile::NamedTempFile::new().expect("Failed to create temp file");
+        // Formatting whitespace counts while the document is read
+        write!(temp_file, "{{ \"type\" :  \"string\" }}").expect("Failed to write to temp file");
+        let schema = json!({"$ref": path_to_uri(temp_file.path())});
+        let build = |max_total_bytes| {
+            crate::options()
+                .with_retrieval_limits(usize::MAX, max_total_bytes, usize::MAX)
+                .build(&schema)
+        };
+
+        let validator = build(22).expect("Schema compilation failed");
+        assert!(validator.is_valid(&json!("foo")));
+        let error = build(21).expect_err("Size limit should be exceeded");
+        assert!(
+            error
+                .to_string()
+                .ends_with("exceeds the limit of 21 bytes in retrieved documents"),
+            "{error}"
+        );
+    }
+
     #[test]
     fn test_unknown_scheme() {
         let schema = json!({
@@ -277,6 +562,126 @@ mod tests {
         let invalid_instance = serde_json::json!({ "age": "thirty" });
         assert!(!validator.is_valid(&invalid_instance));
     }
+
+    #[cfg(all(feature = "resolve-http", not(target_arch = "wasm32")))]
+    fn serve(server: &mut mockito::Server, path: &str, body: &str) {
+        server
+            .mock("GET", path)
+            .with_header("content-type", "application/json")
+            .with_body(body)
+            .create();
+    }
+
+    #[test]
+    #[cfg(all(feature = "resolve-http", not(target_arch = "wasm32")))]
+    fn test_from_url() {
+        let mut server = mockito::Server::new();
+        serve(
+            &mut server,
+            "/root.json",
+            r#"{"properties": {"age": {"$ref": "age.json"}}}"#,
+        );
+        serve(&mut server, "/age.json", r#"{"type": "integer"}"#);
+
+        let validator = crate::from_url(
+            &format!("{}/root.json", server.url()),
+            crate::RetrievalPolicy::new(),
+        )
+        .expect("Schema compilation failed");
+        assert!(validator.is_valid(&json!({"age": 30})));
+        assert!(!validator.is_valid(&json!({"age": "thirty"})));
+    }
+
+    #[cfg(all(feature = "resolve-http", not(target_arch = "wasm32")))]
+    #[test_case::test_case(
+        r#"{"$ref": "https://example.com/age.json"}"#,
+        crate::RetrievalPolicy::new(),
+        "is not allowed by the retrieval policy";
+        "other host"
+    )]
+    #[test_case::test_case(
+        r#"{"$ref": "age.json"}"#,
+        crate::RetrievalPolicy::new().allow("https://example.com/"),
+        "is not allowed by the retrieval policy";
+        "root host not allowed"
+    )]
+    #[test_case::test_case(
+        r#"{"$ref": "age.json"}"#,
+        crate::RetrievalPolicy::new().max_documents(1),
+        "exceeds the limit of 0 retrieved documents";
+        "too many documents"
+    )]
+    #[test_case::test_case(
+        r#"{"$ref": "age.json"}"#,
+        crate::RetrievalPolicy::new().max_total_size(20),
+        "exceeds the limit of 20 bytes in retrieved documents";
+        "too large"
+    )]
+    fn test_from_url_policy(root: &str, policy: crate::RetrievalPolicy, expected: &str) {
+        let mut server = mockito::Server::new();
+        serve(&mut server, "/root.json", root);
+        serve(
+            &mut server,
+            "/age.json",
+            r#"{"type": "integer", "minimum": 0}"#,
+        );
+
+        let error = crate::from_url(&format!("{}/root.json", server.url()), policy)
+            .expect_err("Should fail")
+            .to_string();
+        assert!(error.contains(expected), "{error}");
+    }
+
+    #[test]
+    #[cfg(all(feature = "resolve-http", not(target_arch = "wasm32")))]
+    fn test_from_url_redirect_not_allowed() {
+        let mut server = mockito::Server::new();
+        serve(&mut server, "/root.json", r#"{"$ref": "moved.json"}"#);
+        server
+            .mock("GET", "/moved.json")
+            .with_status(302)
+            .with_header("location", "https://example.com/age.json")
+            .create();
+
+        let error = crate::from_url(
+            &format!("{}/root.json", server.url()),
+            crate::RetrievalPolicy::new(),
+        )
+        .expect_err("Should fail")
+        .to_string();
+        assert!(
+            error.contains(
+                "Redirect to 'https://example.com/age.json' is not allowed by the retrieval policy"
+            ),
+            "{error}"
+        );
+    }
+
+    #[cfg(all(feature = "resolve-http", not(target_arch = "wasm32")))]
+    #[test_case::test_case("https://example.com/", "https://example.com/a.json", true)]
+    #[test_case::test_case("https://example.com", "https://example.com/a.json", true)]
+    #[test_case::test_case("https://example.com:443/", "https://example.com/a.json", true)]
+    #[test_case::test_case("https://example.com/", "https://example.com.evil.net/a.json", false)]
+    #[test_case::test_case("https://example.com/", "https://example.com:8443/a.json", false)]
+    #[test_case::test_case("https://example.com/", "http://example.com/a.json", false)]
+    #[test_case::test_case("https://example.com/", "https://user@evil.net/a.json", false)]
+    #[test_case::test_case(
+        "https://example.com/schemas",
+        "https://example.com/schemas/a.json",
+        true
+    )]
+    #[test_case::test_case(
+        "https://example.com/schemas",
+        "https://example.com/schemas-evil/a.json",
+        false
+    )]
+    #[test_case::test_case("https://example.com/schemas/", "https://example.com/a.json", false)]
+    fn test_allowed_prefix(prefix: &str, url: &str, expected: bool) {
+        let prefix =
+            super::AllowedPrefix::new(&reqwest::Url::parse(prefix).expect("Invalid prefix"));
+        let url = reqwest::Url::parse(url).expect("Invalid URL");
+        assert_eq!(prefix.matches(&url), expected);
+    }
 }
 
 #[cfg(all(test, feature = "resolve-async", not(target_arch = "wasm32")))]
@@ -0,0 +1,407 @@
+//! Push-based validation of documents delivered as a sequence of parse events.
+//!
+//! Values are checked against subschemas that apply to them regardless of the rest of the
+//! document (e.g. via `properties`, `items` or `allOf`) as soon as they are complete, which allows
+//! rejecting huge invalid payloads before they are fully read. The whole document is validated
+//! once it is complete, so all errors are eventually reported, each of them exactly once.
+use std::{borrow::Cow, error, fmt};
+
+use ahash::AHashSet;
+use serde_json::{Map, Value};
+
+use crate::{
+    node::SchemaNode,
+    paths::{LazyLocation, Location, LocationSegment},
+    validator::Validate,
+    ValidationError, Validator,
+};
+
+/// A parse event fed to [`StreamValidator::push`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event<'a> {
+    /// Start of a JSON object.
+    StartObject,
+    /// Key of the next object member.
+    Key(Cow<'a, str>),
+    /// End of the current JSON object.
+    EndObject,
+    /// Start of a JSON array.
+    StartArray,
+    /// End of the current JSON array.
+    EndArray,
+    /// A complete value, usually a scalar.
+    Value(Value),
+}
+
+impl Event<'_> {
+    fn name(&self) -> &'static str {
+        match self {
+            Event::StartObject => "start of object",
+            Event::Key(_) => "object key",
+            Event::EndObject => "end of object",
+            Event::StartArray => "start of array",
+            Event::EndArray => "end of array",
+            Event::Value(_) => "value",
+        }
+    }
+}
+
+/// An event sequence that does not form a single JSON document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamError {
+    /// The event is not allowed at this position, e.g. a value where an object key is expected.
+    UnexpectedEvent { event: &'static str },
+    /// The document is not complete yet.
+    Incomplete,
+}
+
+impl fmt::Display for StreamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StreamError::UnexpectedEvent { event } => write!(f, "Unexpected {event}"),
+            StreamError::Incomplete => f.write_str("The document is incomplete"),
+        }
+    }
+}
+
+impl error::Error for StreamError {}
+
+enum Container {
+    Object {
+        members: Map<String, Value>,
+        key: Option<String>,
+    },
+    Array(Vec<Value>),
+}
+
+impl Container {
+    /// Location of the next value within this container.
+    fn next_segment(&self) -> LocationSegment<'_> {
+        match self {
+            Container::Object { key, .. } => {
+                LocationSegment::Property(key.as_deref().unwrap_or_default())
+            }
+            Container::Array(items) => LocationSegment::Index(items.len()),
+        }
+    }
+}
+
+struct Frame<'v> {
+    container: Container,
+    /// Subschemas that unconditionally apply to this container.
+    nodes: Vec<&'v SchemaNode>,
+}
+
+/// Validates a document delivered as a sequence of [`Event`]s.
+///
+/// Created via [`Validator::stream`].
+pub struct StreamValidator<'v> {
+    validator: &'v Validator,
+    stack: Vec<Frame<'v>>,
+    document: Option<Value>,
+    /// Errors reported so far, by location and message, as complete containers are checked again.
+    reported: AHashSet<(Location, Location, String)>,
+}
+
+impl<'v> StreamValidator<'v> {
+    pub(crate) fn new(validator: &'v Validator) -> StreamValidator<'v> {
+        StreamValidator {
+            validator,
+            stack: Vec::new(),
+            document: None,
+            reported: AHashSet::new(),
+        }
+    }
+
+    /// Feed the next event and return errors that became determinable with it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`StreamError::UnexpectedEvent`] if the event can't appear at this position.
+    /// Such events are ignored and the validator stays usable.
+    pub fn push(&mut self, event: Event<'_>) -> Result<Vec<ValidationError<'static>>, StreamError> {
+        let unexpected = StreamError::UnexpectedEvent {
+            event: event.name(),
+        };
+        match event {
+            Event::Key(key) => match self.stack.last_mut() {
+                Some(Frame {
+                    container: Container::Object { key: pending, .. },
+                    ..
+                }) if pending.is_none() => {
+                    *pending = Some(key.into_owned());
+                    Ok(Vec::new())
+                }
+                _ => Err(unexpected),
+            },
+            Event::StartObject | Event::StartArray => {
+                if !self.expects_value() {
+                    return Err(unexpected);
+                }
+                let nodes = self.nodes_for_next_value();
+                let container = if event == Event::StartObject {
+                    Container::Object {
+                        members: Map::new(),
+                        key: None,
+                    }
+                } else {
+                    Container::Array(Vec::new())
+                };
+                self.stack.push(Frame { container, nodes });
+                Ok(Vec::new())
+            }
+            Event::EndObject => match self.stack.last() {
+                Some(Frame {
+                    container: Container::Object { key: None, .. },
+                    ..
+                }) => Ok(self.end_container()),
+                _ => Err(unexpected),
+            },
+            Event::EndArray => match self.stack.last() {
+                Some(Frame {
+                    container: Container::Array(_),
+                    ..
+                }) => Ok(self.end_container()),
+                _ => Err(unexpected),
+            },
+            Event::Value(value) => {
+                if !self.expects_value() {
+                    return Err(unexpected);
+                }
+                let nodes = self.nodes_for_next_value();
+                Ok(self.complete(value, &nodes))
+            }
+        }
+    }
+
+    /// Return the assembled document.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`StreamError::Incomplete`] if the events did not form a complete document.
+    pub fn finish(self) -> Result<Value, StreamError> {
+        self.document.ok_or(StreamError::Incomplete)
+    }
+
+    fn expects_value(&self) -> bool {
+        match self.stack.last() {
+            None => self.document.is_none(),
+            Some(Frame {
+                container: Container::Object { key, .. },
+                ..
+            }) => key.is_some(),
+            Some(Frame {
+                container: Container::Array(_),
+                ..
+            }) => true,
+        }
+    }
+
+    /// Subschemas that unconditionally apply to the value that comes next.
+    fn nodes_for_next_value(&self) -> Vec<&'v SchemaNode> {
+        let Some(frame) = self.stack.last() else {
+            return vec![&self.validator.root];
+        };
+        let segment = frame.container.next_segment();
+        let mut nodes = Vec::new();
+        for node in &frame.nodes {
+            node.subschemas_at(segment, &mut |node| nodes.push(node));
+        }
+        nodes
+    }
+
+    fn end_container(&mut self) -> Vec<ValidationError<'static>> {
+        let frame = self.stack.pop().expect("Checked by the caller");
+        let value = match frame.container {
+            Container::Object { members, .. } => Value::Object(members),
+            Container::Array(items) => Value::Array(items),
+        };
+        self.complete(value, &frame.nodes)
+    }
+
+    /// Check a complete value and attach it to its parent.
+    fn complete(&mut self, value: Value, nodes: &[&SchemaNode]) -> Vec<ValidationError<'static>> {
+        let mut errors = Vec::new();
+        if self.stack.is_empty() {
+            errors.extend(
+                self.validator
+                    .iter_errors(&value)
+                    .map(ValidationError::to_owned),
+            );
+        } else if !nodes.is_empty() {
+            let segments: Vec<_> = self
+                .stack
+                .iter()
+                .map(|frame| frame.container.next_segment())
+                .collect();
+            with_location(&segments, &LazyLocation::new(), &mut |location| {
+                for node in nodes {
+                    errors.extend(
+                        node.iter_errors(&value, location)
+                            .map(ValidationError::to_owned),
+                    );
+                }
+            });
+        }
+        errors.retain(|error| {
+            self.reported.insert((
+                error.instance_path.clone(),
+                error.schema_path.clone(),
+                error.to_string(),
+            ))
+        });
+        match self.stack.last_mut() {
+            None => self.document = Some(value),
+            Some(frame) => match &mut frame.container {
+                Container::Object { members, key } => {
+                    members.insert(key.take().expect("Checked by the caller"), value);
+                }
+                Container::Array(items) => items.push(value),
+            },
+        }
+        errors
+    }
+}
+
+fn with_location(
+    segments: &[LocationSegment<'_>],
+    location: &LazyLocation,
+    f: &mut dyn FnMut(&LazyLocation),
+) {
+    match segments.split_first() {
+        None => f(location),
+        Some((segment, rest)) => with_location(rest, &location.push(*segment), f),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::{Event, StreamError};
+
+    fn events(value: &Value) -> Vec<Event<'_>> {
+        let mut events = Vec::new();
+        match value {
+            Value::Object(map) => {
+                events.push(Event::StartObject);
+                for (key, value) in map {
+                    events.push(Event::Key(key.into()));
+                    events.extend(self::events(value));
+                }
+                events.push(Event::EndObject);
+            }
+            Value::Array(items) => {
+                events.push(Event::StartArray);
+                for item in items {
+                    events.extend(self::events(item));
+                }
+                events.push(Event::EndArray);
+            }
+            _ => events.push(Event::Value(value.clone())),
+        }
+        events
+    }
+
+    #[test]
+    fn early_errors() {
+        let schema = json!({
+            "type": "object",
+            "required": ["name"],
+            "properties": {
+                "tags": {"items": {"type": "string"}},
+            },
+            "allOf": [{"properties": {"id": {"type": "integer"}}}]
+        });
+        let validator = crate::validator_for(&schema).expect("Invalid schema");
+        let instance = json!({"id": "a", "tags": ["x", 1, "y"]});
+        let mut stream = validator.stream();
+        let mut reported = Vec::new();
+        for (position, event) in events(&instance).into_iter().enumerate() {
+            for error in stream.push(event).expect("Valid events") {
+                reported.push((position, error.instance_path.to_string()));
+            }
+        }
+        assert_eq!(
+            reported,
+            vec![
+                (2, "/id".to_string()),
+                (6, "/tags/1".to_string()),
+                (9, String::new())
+            ]
+        );
+        assert_eq!(stream.finish().expect("Complete document"), instance);
+    }
+
+    #[test]
+    fn distinct_errors_at_the_same_location() {
+        let validator = crate::validator_for(&json!({
+            "properties": {"a": {"required": ["x", "y"]}}
+        }))
+        .expect("Invalid schema");
+        let mut stream = validator.stream();
+        let mut reported = Vec::new();
+        for event in events(&json!({"a": {}})) {
+            reported.extend(
+                stream
+                    .push(event)
+                    .expect("Valid events")
+                    .into_iter()
+                    .map(|error| error.to_string()),
+            );
+        }
+        assert_eq!(
+            reported,
+            [
+                "\"x\" is a required property",
+                "\"y\" is a required property"
+            ]
+        );
+    }
+
+    #[test]
+    fn conditional_subschemas_are_checked_at_the_end() {
+        let validator = crate::validator_for(&json!({
+            "anyOf": [{"items": {"type": "string"}}, {"items": {"type": "integer"}}]
+        }))
+        .expect("Invalid schema");
+        let mut stream = validator.stream();
+        let mut reported = Vec::new();
+        for event in events(&json!(["a", 1])) {
+            reported.push(stream.push(event).expect("Valid events").len());
+        }
+        assert_eq!(reported, vec![0, 0, 0, 1]);
+    }
+
+    #[test]
+    fn unexpected_events() {
+        let validator = crate::validator_for(&json!({})).expect("Invalid schema");
+        let mut stream = validator.stream();
+        assert_eq!(
+            stream.push(Event::Key("a".into())).expect_err("Unexpected"),
+            StreamError::UnexpectedEvent {
+                event: "object key"
+            }
+        );
+        stream.push(Event::StartObject).expect("Valid event");
+        assert_eq!(
+            stream.push(Event::Value(json!(1))).expect_err("Unexpected"),
+            StreamError::UnexpectedEvent { event: "value" }
+        );
+        assert_eq!(
+            stream.push(Event::EndArray).expect_err("Unexpected"),
+            StreamError::UnexpectedEvent {
+                event: "end of array"
+            }
+        );
+        stream.push(Event::EndObject).expect("Valid event");
+        assert_eq!(
+            stream.push(Event::StartArray).expect_err("Unexpected"),
+            StreamError::UnexpectedEvent {
+                event: "start of array"
+            }
+        );
+        assert_eq!(stream.finish(), Ok(json!({})));
+        assert_eq!(validator.stream().finish(), Err(StreamError::Incomplete));
+    }
+}
@@ -0,0 +1,21 @@
+//! Shared ownership in compiled validators.
+//!
+//! With `--cfg jsonschema_single_threaded`, locations are reference counted with `Rc` instead of
+//! `Arc`, and validators are neither `Send` nor `Sync`. This avoids atomic operations whenever
+//! errors and annotations are produced, which is measurable in single-threaded embedders.
+#[cfg(jsonschema_single_threaded)]
+pub(crate) use std::rc::Rc as Shared;
+#[cfg(not(jsonschema_single_threaded))]
+pub(crate) use std::sync::Arc as Shared;
+
+/// `Send + Sync`, unless built with `--cfg jsonschema_single_threaded`.
+#[cfg(not(jsonschema_single_threaded))]
+pub(crate) trait MaybeSendSync: Send + Sync {}
+#[cfg(not(jsonschema_single_threaded))]
+impl<T: Send + Sync + ?Sized> MaybeSendSync for T {}
+
+/// `Send + Sync`, unless built with `--cfg jsonschema_single_threaded`.
+#[cfg(jsonschema_single_threaded)]
+pub(crate) trait MaybeSendSync {}
+#[cfg(jsonschema_single_threaded)]
+impl<T: ?Sized> MaybeSendSync for T {}
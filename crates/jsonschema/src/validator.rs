@@ -2,14 +2,30 @@
 //! The main idea is to create a tree from the input JSON Schema. This tree will contain
 //! everything needed to perform such validation in runtime.
 use crate::{
+    batch::{BatchError, BatchReport, InstanceOutcome},
+    cancel::CancellationToken,
+    compiler::BuildState,
+    coverage::Coverage,
+    diagnostics::Diagnostic,
     error::{error, no_error, ErrorIterator},
-    node::SchemaNode,
-    output::{Annotations, ErrorDescription, Output, OutputUnit},
-    paths::LazyLocation,
+    eval::{EvalOptions, Evaluation},
+    evaluation::EvaluatedSubschema,
+    examples::{self, ExampleFailure},
+    node::{NodeId, NodeInfo, SchemaNode},
+    output::{Annotations, BasicOutput, ErrorDescription, Output, OutputUnit},
+    paths::{LazyLocation, Location, LocationSegment},
+    preprocess::{self, Preprocessed},
+    profile::ProfileEntry,
+    remediation::{self, Remediation},
+    stream::StreamValidator,
+    sync::{MaybeSendSync, Shared},
     Draft, ValidationError, ValidationOptions,
 };
+use referencing::{Registry, Uri};
 use serde_json::Value;
-use std::{collections::VecDeque, sync::Arc};
+use std::collections::VecDeque;
+#[cfg(jsonschema_single_threaded)]
+use std::rc::Rc;
 
 /// The Validate trait represents a predicate over some JSON value. Some validators are very simple
 /// predicates such as "a value which is a string", whereas others may be much more complex,
@@ -25,7 +41,7 @@ use std::{collections::VecDeque, sync::Arc};
 /// If you are implementing `Validate` it is often sufficient to implement `validate` and
 /// `is_valid`. `apply` is only necessary for validators which compose other validators. See the
 /// documentation for `apply` for more information.
-pub(crate) trait Validate: Send + Sync {
+pub(crate) trait Validate: MaybeSendSync {
     fn iter_errors<'i>(&self, instance: &'i Value, location: &LazyLocation) -> ErrorIterator<'i> {
         match self.validate(instance, location) {
             Ok(()) => no_error(),
@@ -37,6 +53,16 @@ pub(crate) trait Validate: Send + Sync {
     // not constructed
     fn is_valid(&self, instance: &Value) -> bool;
 
+    /// Like `is_valid`, but honouring the per-call settings carried by `location`, see
+    /// [`EvalOptions`].
+    fn is_valid_at(&self, instance: &Value, location: &LazyLocation) -> bool {
+        if location.evaluation.is_some_and(Evaluation::is_overriding) {
+            self.validate(instance, location).is_ok()
+        } else {
+            self.is_valid(instance)
+        }
+    }
+
     fn validate<'i>(
         &self,
         instance: &'i Value,
@@ -97,6 +123,26 @@ pub(crate) trait Validate: Send + Sync {
             PartialApplication::invalid_empty(errors)
         }
     }
+
+    /// Visit subschemas that apply to the child of an instance at `segment`, regardless of the
+    /// rest of the instance. Streaming validation uses them to check children as soon as they are
+    /// complete. Validators whose children depend on other parts of the instance visit nothing.
+    fn subschemas_at<'s>(
+        &'s self,
+        _segment: LocationSegment<'_>,
+        _visit: &mut dyn FnMut(&'s SchemaNode),
+    ) {
+    }
+
+    /// Visit the subschemas that accept `instance` and thereby make this validator accept it,
+    /// which explains why it is valid, e.g. for `not` errors. Validators that don't accept an
+    /// instance because of their subschemas visit nothing.
+    fn matching_subschemas<'s>(
+        &'s self,
+        _instance: &Value,
+        _visit: &mut dyn FnMut(&'s SchemaNode),
+    ) {
+    }
 }
 
 /// The result of applying a validator to an instance. As explained in the documentation for
@@ -168,11 +214,19 @@ impl<'a> PartialApplication<'a> {
 #[derive(Debug)]
 pub struct Validator {
     pub(crate) root: SchemaNode,
-    pub(crate) config: Arc<ValidationOptions>,
+    pub(crate) config: Shared<ValidationOptions>,
+    pub(crate) build: Shared<BuildState>,
+    pub(crate) external_references: Vec<Uri<String>>,
+    pub(crate) diagnostics: Vec<Diagnostic>,
+    pub(crate) nodes: Vec<NodeInfo>,
+    /// Resources the schema was compiled from, with the root schema at `base_uri`.
+    pub(crate) registry: Shared<Registry>,
+    pub(crate) base_uri: Uri<String>,
 }
 
 impl Validator {
-    /// Create a default [`ValidationOptions`] for configuring JSON Schema validation.
+    /// Create a default [`ValidationOptions`] for configuring JSON Schema validation, starting
+    /// from the defaults set via [`crate::set_global_defaults`], if any.
     ///
     /// Use this to set the draft version and other validation parameters.
     ///
@@ -187,7 +241,7 @@ impl Validator {
     /// ```
     #[must_use]
     pub fn options() -> ValidationOptions {
-        ValidationOptions::default()
+        ValidationOptions::from_global_defaults()
     }
     /// Create a default [`ValidationOptions`] configured for async validation.
     ///
@@ -215,9 +269,56 @@ impl Validator {
     /// For sync validation, use [`options`] instead.
     #[cfg(feature = "resolve-async")]
     #[must_use]
-    pub fn async_options() -> ValidationOptions<Arc<dyn referencing::AsyncRetrieve>> {
+    pub fn async_options() -> ValidationOptions<std::sync::Arc<dyn referencing::AsyncRetrieve>> {
         ValidationOptions::default()
     }
+    /// A shared validator for the `true` schema, which accepts every instance.
+    ///
+    /// It is built once, so it costs nothing to use as a placeholder, e.g. for generated schemas
+    /// that turn out to be `true` or `{}`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use jsonschema::Validator;
+    /// use serde_json::json;
+    ///
+    /// assert!(Validator::always_valid().is_valid(&json!({"any": "thing"})));
+    /// ```
+    #[cfg(not(jsonschema_single_threaded))]
+    #[must_use]
+    pub fn always_valid() -> &'static Validator {
+        &crate::meta::validators::ALWAYS_VALID
+    }
+    /// A shared validator for the `true` schema, which accepts every instance.
+    ///
+    /// It is built once per thread, so it costs nothing to use as a placeholder.
+    #[cfg(jsonschema_single_threaded)]
+    #[must_use]
+    pub fn always_valid() -> Rc<Validator> {
+        crate::meta::validators::ALWAYS_VALID.get()
+    }
+    /// A shared validator for the `false` schema, which rejects every instance.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use jsonschema::Validator;
+    /// use serde_json::json;
+    ///
+    /// assert!(!Validator::always_invalid().is_valid(&json!(null)));
+    /// ```
+    #[cfg(not(jsonschema_single_threaded))]
+    #[must_use]
+    pub fn always_invalid() -> &'static Validator {
+        &crate::meta::validators::ALWAYS_INVALID
+    }
+    /// A shared validator for the `false` schema, which rejects every instance.
+    #[cfg(jsonschema_single_threaded)]
+    #[must_use]
+    pub fn always_invalid() -> Rc<Validator> {
+        crate::meta::validators::ALWAYS_INVALID.get()
+    }
     /// Create a validator using the default options.
     pub fn new(schema: &Value) -> Result<Validator, ValidationError<'static>> {
         Self::options().build(schema)
@@ -230,20 +331,308 @@ impl Validator {
     /// Validate `instance` against `schema` and return the first error if any.
     #[inline]
     pub fn validate<'i>(&self, instance: &'i Value) -> Result<(), ValidationError<'i>> {
+        self.check_size_limits(instance, None)?;
         self.root.validate(instance, &LazyLocation::new())
     }
+    /// Validate `instance` like [`Validator::validate`], but stop as soon as `token` is cancelled.
+    ///
+    /// The token is checked before evaluating each subschema, so a cancelled or expired token
+    /// makes this method return an error of the [`ValidationErrorKind::Cancelled`] kind shortly
+    /// after. Subschemas evaluated only for their validity, e.g. `anyOf` branches, are not
+    /// interrupted.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use jsonschema::CancellationToken;
+    /// use serde_json::json;
+    /// use std::time::Duration;
+    ///
+    /// let validator = jsonschema::validator_for(&json!({"type": "string"}))
+    ///     .expect("Invalid schema");
+    /// let token = CancellationToken::with_timeout(Duration::from_secs(1));
+    /// assert!(validator.validate_with_cancel(&json!("foo"), &token).is_ok());
+    ///
+    /// token.cancel();
+    /// assert!(validator.validate_with_cancel(&json!("foo"), &token).is_err());
+    /// ```
+    ///
+    /// [`ValidationErrorKind::Cancelled`]: crate::error::ValidationErrorKind::Cancelled
+    pub fn validate_with_cancel<'i>(
+        &self,
+        instance: &'i Value,
+        token: &CancellationToken,
+    ) -> Result<(), ValidationError<'i>> {
+        let cancelled = || {
+            Err(ValidationError::cancelled(
+                Location::new(),
+                Location::new(),
+                instance,
+            ))
+        };
+        if token.is_cancelled() {
+            return cancelled();
+        }
+        self.check_size_limits(instance, None)?;
+        let evaluation = Evaluation::cancellable(token);
+        let result = self
+            .root
+            .validate(instance, &LazyLocation::evaluating(&evaluation));
+        // Branches evaluated after cancellation may report arbitrary results
+        if token.is_cancelled() {
+            return cancelled();
+        }
+        result
+    }
+    /// Validate `instance` like [`Validator::validate`], with settings overridden by `options`.
+    ///
+    /// The context of `options`, if any, is handed back to them once validation finishes.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use jsonschema::{EvalOptions, SizeLimits};
+    /// use serde_json::json;
+    ///
+    /// let validator = jsonschema::validator_for(&json!({"type": "array"}))
+    ///     .expect("Invalid schema");
+    /// let instance = json!([1, 2, 3]);
+    ///
+    /// let mut options = EvalOptions::new().with_size_limits(SizeLimits::new().max_items(2));
+    /// assert!(validator.validate_with(&instance, &mut options).is_err());
+    /// assert!(validator.validate(&instance).is_ok());
+    /// ```
+    pub fn validate_with<'i>(
+        &self,
+        instance: &'i Value,
+        options: &mut EvalOptions,
+    ) -> Result<(), ValidationError<'i>> {
+        let evaluation = Evaluation::new(options);
+        let result = self
+            .check_size_limits(instance, Some(&evaluation))
+            .and_then(|()| {
+                self.root
+                    .validate(instance, &LazyLocation::evaluating(&evaluation))
+            });
+        evaluation.finish(options);
+        result
+    }
+    /// Check `instance` like [`Validator::is_valid`], with settings overridden by `options`.
+    #[must_use]
+    pub fn is_valid_with(&self, instance: &Value, options: &mut EvalOptions) -> bool {
+        let evaluation = Evaluation::new(options);
+        // Keywords get the per-call settings only with a location
+        let result = self.check_size_limits(instance, Some(&evaluation)).is_ok()
+            && self
+                .root
+                .is_valid_at(instance, &LazyLocation::evaluating(&evaluation));
+        evaluation.finish(options);
+        result
+    }
+    /// Apply the schema like [`Validator::apply`] and evaluate the "basic" output right away,
+    /// with settings overridden by `options`.
+    #[must_use]
+    pub fn apply_with<'a>(
+        &'a self,
+        instance: &Value,
+        options: &mut EvalOptions,
+    ) -> BasicOutput<'a> {
+        let evaluation = Evaluation::new(options);
+        let output = Output::new(self, &self.root, instance).basic_in(&evaluation);
+        evaluation.finish(options);
+        output
+    }
+    /// Subschemas that accepted the value at `pointer` within `instance`, e.g. the matching branch
+    /// of `anyOf`.
+    ///
+    /// This is the counterpart of errors for valid data: it answers why a value was accepted.
+    /// Subschemas are listed in evaluation order, with parents before the subschemas they contain.
+    /// Only subschemas applied to exactly this location are included.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    ///
+    /// let schema = json!({
+    ///     "properties": {
+    ///         "id": {"anyOf": [{"type": "integer"}, {"type": "string", "format": "uuid"}]}
+    ///     }
+    /// });
+    /// let validator = jsonschema::validator_for(&schema).expect("Invalid schema");
+    ///
+    /// let path: Vec<_> = validator
+    ///     .evaluation_path(&json!({"id": 42}), "/id")
+    ///     .iter()
+    ///     .map(|subschema| subschema.keyword_location().to_string())
+    ///     .collect();
+    /// assert_eq!(path, ["/properties/id", "/properties/id/anyOf/0"]);
+    /// ```
+    #[must_use]
+    pub fn evaluation_path(&self, instance: &Value, pointer: &str) -> Vec<EvaluatedSubschema> {
+        let evaluation = Evaluation::recording(Some(pointer));
+        let _ = self
+            .root
+            .apply_rooted(instance, &LazyLocation::evaluating(&evaluation));
+        evaluation.recorded()
+    }
+    /// Metadata of all compiled subschemas, indexed by their [`NodeId`].
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    ///
+    /// let schema = json!({"properties": {"name": {"type": "string"}}});
+    /// let validator = jsonschema::validator_for(&schema).expect("Invalid schema");
+    ///
+    /// let id = validator.node_id("/properties/name").expect("Compiled subschema");
+    /// let node = validator.node(id).expect("Valid ID");
+    /// assert_eq!(node.keywords(), ["type"]);
+    /// assert_eq!(validator.nodes()[id.index()], *node);
+    /// ```
+    #[must_use]
+    pub fn nodes(&self) -> &[NodeInfo] {
+        &self.nodes
+    }
+    /// Metadata of the compiled subschema with the given ID.
+    #[must_use]
+    pub fn node(&self, id: NodeId) -> Option<&NodeInfo> {
+        self.nodes.get(id.index())
+    }
+    /// The ID of the compiled subschema at the keyword `location`, e.g. `/properties/name`.
+    #[must_use]
+    pub fn node_id(&self, location: &str) -> Option<NodeId> {
+        self.nodes
+            .iter()
+            .find(|node| node.location().as_str() == location)
+            .map(NodeInfo::id)
+    }
+    /// Create an empty [`Coverage`] of this validator's subschemas.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    ///
+    /// let schema = json!({"anyOf": [{"type": "integer"}, {"type": "string"}]});
+    /// let validator = jsonschema::validator_for(&schema).expect("Invalid schema");
+    ///
+    /// let mut coverage = validator.coverage();
+    /// validator.record_coverage(&json!(42), &mut coverage);
+    ///
+    /// let uncovered: Vec<_> = coverage.uncovered().collect();
+    /// assert_eq!(uncovered, ["/anyOf/1"]);
+    /// ```
+    #[must_use]
+    pub fn coverage(&self) -> Coverage {
+        Coverage::new(&self.nodes)
+    }
+    /// Mark subschemas accepting any location within `instance` as covered.
+    ///
+    /// `coverage` should be created by this validator via [`Validator::coverage`].
+    pub fn record_coverage(&self, instance: &Value, coverage: &mut Coverage) {
+        let evaluation = Evaluation::recording(None);
+        let _ = self
+            .root
+            .apply_rooted(instance, &LazyLocation::evaluating(&evaluation));
+        for subschema in evaluation.recorded() {
+            coverage.insert(subschema.keyword_location());
+        }
+    }
+    /// Evaluation counts and cumulative times of subschemas, by decreasing time.
+    ///
+    /// Only subschemas evaluated since the validator was built or [`Validator::reset_profile`]
+    /// was called are listed. Empty unless profiling is enabled via
+    /// [`ValidationOptions::should_profile`].
+    #[must_use]
+    pub fn profile(&self) -> Vec<ProfileEntry> {
+        self.build
+            .profiler
+            .as_ref()
+            .map(|profiler| profiler.entries())
+            .unwrap_or_default()
+    }
+    /// Clear the statistics returned by [`Validator::profile`], e.g. to profile a workload
+    /// after a warm-up.
+    pub fn reset_profile(&self) {
+        if let Some(profiler) = &self.build.profiler {
+            profiler.reset();
+        }
+    }
     /// Run validation against `instance` and return an iterator over [`ValidationError`] in the error case.
     #[inline]
     pub fn iter_errors<'i>(&'i self, instance: &'i Value) -> ErrorIterator<'i> {
+        if let Err(err) = self.check_size_limits(instance, None) {
+            return error(err);
+        }
         self.root.iter_errors(instance, &LazyLocation::new())
     }
+    /// Validate many instances, identified by names such as file names, and aggregate the
+    /// results into a [`BatchReport`].
+    pub fn validate_batch<'i, N>(
+        &self,
+        instances: impl IntoIterator<Item = (N, &'i Value)>,
+    ) -> BatchReport
+    where
+        N: Into<String>,
+    {
+        let preprocessors = self.config.preprocessors();
+        instances
+            .into_iter()
+            .map(|(name, instance)| {
+                if preprocessors.is_empty() {
+                    return InstanceOutcome::new(
+                        name,
+                        self.iter_errors(instance)
+                            .map(|error| BatchError::from(&error)),
+                    );
+                }
+                let (instance, transformations) =
+                    preprocess::preprocess(preprocessors, instance).into_parts();
+                InstanceOutcome::new(
+                    name,
+                    self.iter_errors(&instance)
+                        .map(|error| BatchError::from(&error)),
+                )
+                .with_transformations(transformations)
+            })
+            .collect()
+    }
+    /// Apply the preprocessors of this validator to a copy of `instance`, see
+    /// [`ValidationOptions::with_preprocessor`].
+    ///
+    /// [`ValidationOptions::with_preprocessor`]: crate::ValidationOptions::with_preprocessor
+    #[must_use]
+    pub fn preprocess(&self, instance: &Value) -> Preprocessed {
+        preprocess::preprocess(self.config.preprocessors(), instance)
+    }
+    /// Suggest changes that make `instance` valid, where feasible.
+    ///
+    /// See [`Remediation`] for the kinds of errors that are fixed.
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    ///
+    /// let schema = json!({
+    ///     "properties": {"size": {"enum": ["small", "large"]}, "count": {"maximum": 10}},
+    ///     "additionalProperties": false
+    /// });
+    /// let validator = jsonschema::validator_for(&schema).expect("Invalid schema");
+    /// let remediation = validator.remediate(&json!({"size": "Large", "count": 12, "color": "red"}));
+    ///
+    /// assert!(remediation.is_complete());
+    /// assert_eq!(remediation.patched(), &json!({"size": "large", "count": 10}));
+    /// ```
+    #[must_use]
+    pub fn remediate(&self, instance: &Value) -> Remediation {
+        remediation::remediate(self, instance)
+    }
     /// Run validation against `instance` but return a boolean result instead of an iterator.
     /// It is useful for cases, where it is important to only know the fact if the data is valid or not.
     /// This approach is much faster, than [`Validator::validate`].
     #[must_use]
     #[inline]
     pub fn is_valid(&self, instance: &Value) -> bool {
-        self.root.is_valid(instance)
+        self.check_size_limits(instance, None).is_ok() && self.root.is_valid(instance)
     }
     /// Apply the schema and return an [`Output`]. No actual work is done at this point, the
     /// evaluation of the schema is deferred until a method is called on the `Output`. This is
@@ -290,32 +679,179 @@ impl Validator {
         Output::new(self, &self.root, instance)
     }
 
+    #[inline]
+    pub(crate) fn check_size_limits<'i>(
+        &self,
+        instance: &'i Value,
+        evaluation: Option<&Evaluation>,
+    ) -> Result<(), ValidationError<'i>> {
+        let limits = evaluation
+            .and_then(Evaluation::size_limits)
+            .unwrap_or(*self.config.size_limits());
+        if limits.is_traversal_limited() {
+            limits.check(instance, &LazyLocation::new())
+        } else {
+            Ok(())
+        }
+    }
+
     /// The [`Draft`] which was used to build this validator.
     #[must_use]
     pub fn draft(&self) -> Draft {
         self.config.draft()
     }
 
+    /// Absolute URIs of external documents this validator depends on.
+    ///
+    /// Includes every document reached via `$ref` or `$dynamicRef` during compilation that is not
+    /// the root schema itself, whether it was provided as a resource or retrieved. Built-in
+    /// meta-schemas are not included. URIs are sorted and unique.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use serde_json::json;
+    /// use jsonschema::Resource;
+    ///
+    /// let schema = json!({"$ref": "https://example.com/person.json"});
+    /// let validator = jsonschema::options()
+    ///     .with_resource(
+    ///         "https://example.com/person.json",
+    ///         Resource::from_contents(json!({"type": "object"})).expect("Invalid resource"),
+    ///     )
+    ///     .build(&schema)
+    ///     .expect("Invalid schema");
+    ///
+    /// let references: Vec<_> = validator
+    ///     .external_references()
+    ///     .iter()
+    ///     .map(|uri| uri.as_str())
+    ///     .collect();
+    /// assert_eq!(references, ["https://example.com/person.json"]);
+    /// ```
+    #[must_use]
+    pub fn external_references(&self) -> &[Uri<String>] {
+        &self.external_references
+    }
+
+    /// Problems found in the schema during compilation, such as subschemas that can never be
+    /// satisfied or keywords that never apply.
+    ///
+    /// These do not prevent the schema from compiling, but almost always indicate an authoring bug.
+    /// Empty unless enabled via [`ValidationOptions::should_report_diagnostics`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use jsonschema::DiagnosticKind;
+    /// use serde_json::json;
+    ///
+    /// let schema = json!({"properties": {"age": {"type": "string", "minimum": 3}}});
+    /// let validator = jsonschema::options()
+    ///     .should_report_diagnostics(true)
+    ///     .build(&schema)
+    ///     .expect("Invalid schema");
+    ///
+    /// let diagnostic = &validator.diagnostics()[0];
+    /// assert_eq!(diagnostic.location().as_str(), "/properties/age");
+    /// assert_eq!(
+    ///     diagnostic.kind(),
+    ///     &DiagnosticKind::IneffectiveKeyword { keyword: "minimum".to_string() }
+    /// );
+    /// assert_eq!(
+    ///     diagnostic.to_string(),
+    ///     "/properties/age: 'minimum' never applies to the types allowed by 'type'"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    /// Validate every `examples` item and `default` value against the schema declaring it.
+    ///
+    /// All schemas reachable from the root schema are checked, including through references, but
+    /// not definitions that are never referenced. Values that the schema rejects are returned
+    /// with the canonical location of the schema, so drift between examples and schemas can be
+    /// caught e.g. in tests.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a referenced schema can not be resolved or compiled.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    ///
+    /// let schema = json!({
+    ///     "properties": {
+    ///         "age": {"type": "integer", "examples": [42, "forty-two"], "default": 0}
+    ///     }
+    /// });
+    /// let validator = jsonschema::validator_for(&schema).expect("Invalid schema");
+    ///
+    /// let failures = validator.check_examples().expect("Resolvable schema");
+    /// assert_eq!(failures.len(), 1);
+    /// assert_eq!(failures[0].schema_location(), "json-schema:///#/properties/age");
+    /// assert_eq!(
+    ///     failures[0].to_string(),
+    ///     r#"examples/1 of json-schema:///#/properties/age is invalid: "forty-two" is not of type "integer""#
+    /// );
+    /// ```
+    pub fn check_examples(&self) -> Result<Vec<ExampleFailure>, ValidationError<'static>> {
+        examples::check(self)
+    }
+
+    /// Start validating a document delivered as a sequence of parse [`Event`](crate::Event)s.
+    ///
+    /// Values are checked as soon as they are complete against subschemas that apply to them
+    /// regardless of the rest of the document, e.g. via `properties`, `items`, `allOf` or `$ref`.
+    /// The remaining errors are reported once the whole document is complete.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use jsonschema::Event;
+    /// use serde_json::json;
+    ///
+    /// let schema = json!({"items": {"type": "integer"}});
+    /// let validator = jsonschema::validator_for(&schema).expect("Invalid schema");
+    ///
+    /// let mut stream = validator.stream();
+    /// assert!(stream.push(Event::StartArray).expect("Valid event").is_empty());
+    /// // Rejected before the rest of the array is read
+    /// let errors = stream.push(Event::Value(json!("a"))).expect("Valid event");
+    /// assert_eq!(errors[0].instance_path.as_str(), "/0");
+    /// ```
+    #[must_use]
+    pub fn stream(&self) -> StreamValidator<'_> {
+        StreamValidator::new(self)
+    }
+
     /// The [`ValidationOptions`] that were used to build this validator.
     #[must_use]
-    pub fn config(&self) -> Arc<ValidationOptions> {
-        Arc::clone(&self.config)
+    pub fn config(&self) -> Shared<ValidationOptions> {
+        Shared::clone(&self.config)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::{
-        error::ValidationError,
+        error::{ValidationError, ValidationErrorKind},
+        eval::Evaluation,
         keywords::custom::Keyword,
         paths::{LazyLocation, Location},
         types::JsonType,
-        Validator,
+        validator::Validate,
+        CancellationToken, Resource, Validator,
     };
     use fancy_regex::Regex;
     use num_cmp::NumCmp;
     use once_cell::sync::Lazy;
     use serde_json::{json, Map, Value};
+    use test_case::test_case;
 
     #[cfg(not(target_arch = "wasm32"))]
     fn load(path: &str, idx: usize) -> Value {
@@ -342,6 +878,49 @@ mod tests {
         assert!(validator.validate(&value2).is_err());
     }
 
+    #[test_case(&json!({}))]
+    #[test_case(&json!(true))]
+    fn trivially_valid_schema_has_no_validators(schema: &Value) {
+        let validator = crate::validator_for(schema).unwrap();
+        assert_eq!(validator.root.validators().len(), 0);
+        assert!(validator.is_valid(&json!([1, "a"])));
+    }
+
+    #[test]
+    fn false_schemas_share_validator() {
+        let first = crate::validator_for(&json!(false)).unwrap();
+        let second = crate::validator_for(&json!(false)).unwrap();
+        let shared = |validator: &Validator| {
+            let validators: Vec<_> = validator.root.validators().collect();
+            assert_eq!(validators.len(), 1);
+            (validators[0] as *const dyn Validate).cast::<()>()
+        };
+        assert_eq!(shared(&first), shared(&second));
+        assert!(first.validate(&json!(1)).is_err());
+    }
+
+    #[test]
+    // It is an `Rc` with `jsonschema_single_threaded`
+    #[cfg_attr(not(jsonschema_single_threaded), allow(clippy::borrow_deref_ref))]
+    fn always_valid() {
+        let validator = Validator::always_valid();
+        assert!(std::ptr::eq(&*validator, &*Validator::always_valid()));
+        assert!(validator.validate(&json!({"a": 1})).is_ok());
+        assert!(validator.apply(&json!(1)).basic().is_valid());
+    }
+
+    #[test]
+    // It is an `Rc` with `jsonschema_single_threaded`
+    #[cfg_attr(not(jsonschema_single_threaded), allow(clippy::borrow_deref_ref))]
+    fn always_invalid() {
+        let validator = Validator::always_invalid();
+        assert!(std::ptr::eq(&*validator, &*Validator::always_invalid()));
+        let instance = json!(1);
+        let error = validator.validate(&instance).expect_err("Should fail");
+        assert!(matches!(error.kind, ValidationErrorKind::FalseSchema));
+        assert!(!validator.is_valid(&json!(null)));
+    }
+
     #[test]
     #[cfg(not(target_arch = "wasm32"))]
     fn validate_ref() {
@@ -606,8 +1185,236 @@ mod tests {
     }
 
     #[test]
+    #[cfg(not(jsonschema_single_threaded))]
     fn test_validator_is_send_and_sync() {
         fn assert_send_sync<T: Send + Sync>() {}
         assert_send_sync::<Validator>();
     }
+
+    #[test]
+    fn nodes() {
+        let schema = json!({
+            "$id": "https://example.com/root.json",
+            "properties": {
+                "name": {"type": "string", "minLength": 1},
+                "tags": {"items": {"$ref": "#/$defs/tag"}}
+            },
+            "$defs": {"tag": {"$id": "tag.json", "type": "string"}}
+        });
+        let validator = crate::validator_for(&schema).expect("Invalid schema");
+        let locations: Vec<_> = validator
+            .nodes()
+            .iter()
+            .map(|node| node.location().as_str())
+            .collect();
+        assert_eq!(
+            locations,
+            [
+                "",
+                "/properties/name",
+                "/properties/tags",
+                "/properties/tags/items",
+                "/properties/tags/items/$ref"
+            ]
+        );
+        for (index, node) in validator.nodes().iter().enumerate() {
+            assert_eq!(node.id().index(), index);
+            assert_eq!(validator.node_id(node.location().as_str()), Some(node.id()));
+        }
+        let name = validator
+            .node(validator.node_id("/properties/name").expect("Compiled"))
+            .expect("Valid ID");
+        assert_eq!(name.keywords(), ["minLength", "type"]);
+        let tag = validator
+            .node(
+                validator
+                    .node_id("/properties/tags/items/$ref")
+                    .expect("Compiled"),
+            )
+            .expect("Valid ID");
+        assert_eq!(
+            tag.base_uri().map(|uri| uri.as_str()),
+            Some("https://example.com/tag.json")
+        );
+        assert!(validator.node_id("/$defs/tag").is_none());
+        // IDs are stable across compilations
+        let other = crate::validator_for(&schema).expect("Invalid schema");
+        assert_eq!(other.nodes(), validator.nodes());
+    }
+
+    #[test]
+    fn external_references() {
+        let schema = json!({
+            "$id": "https://example.com/root.json",
+            "properties": {
+                "name": {"$ref": "name.json"},
+                "address": {"$ref": "https://example.com/address.json#/$defs/street"},
+                "local": {"$ref": "#/$defs/local"},
+                "embedded": {"$ref": "embedded.json"}
+            },
+            "$defs": {
+                "local": {"type": "string"},
+                "embedded": {"$id": "embedded.json", "type": "integer"}
+            }
+        });
+        let resource = |contents| Resource::from_contents(contents).expect("Invalid resource");
+        let validator = crate::options()
+            .with_resource(
+                "https://example.com/name.json",
+                resource(json!({"type": "string"})),
+            )
+            .with_resource(
+                "https://example.com/address.json",
+                resource(json!({"$defs": {"street": {"type": "string"}}})),
+            )
+            .with_resource("https://example.com/unused.json", resource(json!({})))
+            .build(&schema)
+            .expect("Invalid schema");
+        let references: Vec<_> = validator
+            .external_references()
+            .iter()
+            .map(|uri| uri.as_str())
+            .collect();
+        assert_eq!(
+            references,
+            [
+                "https://example.com/address.json",
+                "https://example.com/name.json"
+            ]
+        );
+    }
+
+    #[test]
+    fn no_external_references() {
+        let schema = json!({"$ref": "#/$defs/a", "$defs": {"a": {"type": "string"}}});
+        let validator = crate::validator_for(&schema).expect("Invalid schema");
+        assert!(validator.external_references().is_empty());
+    }
+
+    #[test_case(&json!({"anyOf": [{"type": "string"}, {"minimum": 1}]}), &json!(5), "", &["", "/anyOf/1"]; "anyOf branch")]
+    #[test_case(&json!({"oneOf": [{"type": "string"}, {"type": "integer"}]}), &json!([1]), "/0", &[]; "not evaluated")]
+    #[test_case(&json!({"items": {"$ref": "#/$defs/item"}, "$defs": {"item": {"type": "integer"}}}), &json!([1]), "/0", &["/items", "/items/$ref"]; "reference")]
+    #[test_case(&json!({"properties": {"a": {"type": "integer"}, "b": {"type": "string"}}}), &json!({"a": 1, "b": 2}), "/a", &["/properties/a"]; "invalid sibling")]
+    #[test_case(&json!({"properties": {"a": {"not": {"type": "integer"}}}}), &json!({"a": 1}), "/a", &[]; "rejected")]
+    fn evaluation_path(schema: &Value, instance: &Value, pointer: &str, expected: &[&str]) {
+        let validator = crate::validator_for(schema).expect("Invalid schema");
+        let path: Vec<_> = validator
+            .evaluation_path(instance, pointer)
+            .iter()
+            .map(|subschema| subschema.keyword_location().to_string())
+            .collect();
+        assert_eq!(path, expected);
+    }
+
+    #[test]
+    fn evaluation_path_excludes_nested_validators() {
+        struct Nested(Validator);
+
+        impl Keyword for Nested {
+            fn validate<'i>(
+                &self,
+                instance: &'i Value,
+                _: &LazyLocation,
+            ) -> Result<(), ValidationError<'i>> {
+                assert!(self.0.apply(instance).basic().is_valid());
+                Ok(())
+            }
+            fn is_valid(&self, instance: &Value) -> bool {
+                self.0.is_valid(instance)
+            }
+        }
+
+        let validator = crate::options()
+            .with_keyword("x-nested", |_, _, _| {
+                let nested =
+                    crate::validator_for(&json!({"properties": {"a": {"type": "integer"}}}))
+                        .expect("Invalid schema");
+                Ok(Box::new(Nested(nested)))
+            })
+            .build(&json!({"properties": {"a": {}}, "x-nested": true}))
+            .expect("Invalid schema");
+        let path: Vec<_> = validator
+            .evaluation_path(&json!({"a": 1}), "/a")
+            .iter()
+            .map(|subschema| subschema.keyword_location().to_string())
+            .collect();
+        assert_eq!(path, ["/properties/a"]);
+    }
+
+    #[test]
+    fn validate_with_cancel_expired_deadline() {
+        let validator = crate::validator_for(&json!({"type": "string"})).expect("Invalid schema");
+        let token = CancellationToken::with_deadline(std::time::Instant::now());
+        let instance = json!("foo");
+        let error = validator
+            .validate_with_cancel(&instance, &token)
+            .expect_err("Should be cancelled");
+        assert!(matches!(error.kind, ValidationErrorKind::Cancelled));
+        assert_eq!(error.to_string(), "Validation was cancelled");
+    }
+
+    #[test]
+    fn validate_with_cancel_during_validation() {
+        struct CancellingValidator {
+            token: CancellationToken,
+        }
+
+        impl Keyword for CancellingValidator {
+            fn validate<'i>(
+                &self,
+                _: &'i Value,
+                _: &LazyLocation,
+            ) -> Result<(), ValidationError<'i>> {
+                self.token.cancel();
+                Ok(())
+            }
+            fn is_valid(&self, _: &Value) -> bool {
+                self.token.cancel();
+                true
+            }
+        }
+
+        let token = CancellationToken::new();
+        let factory_token = token.clone();
+        let validator = crate::options()
+            .with_keyword("cancel", move |_, _, _| {
+                Ok(Box::new(CancellingValidator {
+                    token: factory_token.clone(),
+                }))
+            })
+            .build(&json!({"cancel": true, "type": "string"}))
+            .expect("Invalid schema");
+        let instance = json!("foo");
+        assert!(validator.validate(&instance).is_ok());
+        let error = validator
+            .validate_with_cancel(&instance, &token)
+            .expect_err("Should be cancelled");
+        assert!(matches!(error.kind, ValidationErrorKind::Cancelled));
+        // Cancellation is scoped to the call
+        assert!(validator.validate(&instance).is_ok());
+    }
+
+    #[test_case(&json!(true); "true")]
+    #[test_case(&json!(false); "false")]
+    #[test_case(&json!({"type": "integer"}); "keywords")]
+    fn cancellation_checked_in_every_node(schema: &Value) {
+        let validator = crate::validator_for(schema).expect("Invalid schema");
+        let token = CancellationToken::new();
+        token.cancel();
+        let instance = json!(1);
+        let evaluation = Evaluation::cancellable(&token);
+        let error = validator
+            .root
+            .validate(&instance, &LazyLocation::evaluating(&evaluation))
+            .expect_err("Should be cancelled");
+        assert!(matches!(error.kind, ValidationErrorKind::Cancelled));
+        // Regular validation never observes the token
+        assert_eq!(
+            validator
+                .root
+                .validate(&instance, &LazyLocation::new())
+                .is_ok(),
+            validator.is_valid(&instance)
+        );
+    }
 }
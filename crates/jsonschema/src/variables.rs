@@ -0,0 +1,150 @@
+//! Substitution of schema variables, see [`ValidationOptions::with_variables`].
+//!
+//! [`ValidationOptions::with_variables`]: crate::ValidationOptions::with_variables
+use serde_json::{Map, Value};
+
+use crate::{
+    analyze::{INSTANCE_KEYWORDS, SCHEMA_MAP_KEYWORDS},
+    paths::Location,
+    ValidationError,
+};
+
+/// The member of variable placeholders holding the variable path.
+const VARIABLE: &str = "$var";
+
+/// What a value within a schema is.
+#[derive(Clone, Copy)]
+enum Position {
+    /// A schema, or any other value whose object members may be keywords.
+    Schema,
+    /// An object mapping names to subschemas.
+    Names,
+}
+
+/// Replace the variable placeholders in `schema` with their values in `context`, returning `None`
+/// if there are none.
+pub(crate) fn substitute(
+    schema: &Value,
+    context: &Value,
+) -> Result<Option<Value>, ValidationError<'static>> {
+    substitute_at(schema, context, &Location::new(), Position::Schema)
+}
+
+fn substitute_at(
+    schema: &Value,
+    context: &Value,
+    location: &Location,
+    position: Position,
+) -> Result<Option<Value>, ValidationError<'static>> {
+    match schema {
+        Value::Object(object) => {
+            if let Some(path) = placeholder(object) {
+                return lookup(context, path)
+                    .cloned()
+                    .map(Some)
+                    .ok_or_else(|| ValidationError::undefined_variable(location.clone(), path));
+            }
+            let mut substituted: Option<Map<String, Value>> = None;
+            for (key, value) in object {
+                let position = match position {
+                    Position::Names => Position::Schema,
+                    // Instance data is kept as it is
+                    Position::Schema if INSTANCE_KEYWORDS.contains(&key.as_str()) => continue,
+                    Position::Schema if SCHEMA_MAP_KEYWORDS.contains(&key.as_str()) => {
+                        Position::Names
+                    }
+                    Position::Schema => Position::Schema,
+                };
+                if let Some(value) = substitute_at(value, context, &location.join(key), position)? {
+                    substituted
+                        .get_or_insert_with(|| object.clone())
+                        .insert(key.clone(), value);
+                }
+            }
+            Ok(substituted.map(Value::Object))
+        }
+        Value::Array(items) => {
+            let mut substituted: Option<Vec<Value>> = None;
+            for (idx, item) in items.iter().enumerate() {
+                if let Some(item) =
+                    substitute_at(item, context, &location.join(idx), Position::Schema)?
+                {
+                    substituted.get_or_insert_with(|| items.clone())[idx] = item;
+                }
+            }
+            Ok(substituted.map(Value::Array))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// The variable path of an object consisting of a single `$var` string, e.g. in
+/// `{"$var": "limits.batch"}`. Other objects with a `$var` member, e.g. `properties` declaring a
+/// property with this name, are kept as they are.
+fn placeholder(object: &Map<String, Value>) -> Option<&str> {
+    if object.len() == 1 {
+        object.get(VARIABLE).and_then(Value::as_str)
+    } else {
+        None
+    }
+}
+
+/// The value at a dot-separated path of object keys and array indexes.
+fn lookup<'a>(context: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.')
+        .try_fold(context, |value, segment| match value {
+            Value::Object(object) => object.get(segment),
+            Value::Array(items) => segment.parse::<usize>().ok().and_then(|idx| items.get(idx)),
+            _ => None,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::ValidationErrorKind;
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    fn context() -> Value {
+        json!({"limits": {"batch": 2, "tiers": [{"name": "free"}]}, "id": "tenant"})
+    }
+
+    #[test_case(&json!({"maxItems": {"$var": "limits.batch"}}), &json!({"maxItems": 2}))]
+    #[test_case(&json!({"prefixItems": [{"maxLength": {"$var": "limits.tiers.0.name"}}]}), &json!({"prefixItems": [{"maxLength": "free"}]}))]
+    #[test_case(&json!({"$defs": {"a": {"minItems": {"$var": "limits.batch"}}}}), &json!({"$defs": {"a": {"minItems": 2}}}))]
+    #[test_case(&json!({"properties": {"default": {"$var": "limits.tiers.0"}}}), &json!({"properties": {"default": {"name": "free"}}}); "property named like a literal keyword")]
+    fn substitute(schema: &Value, expected: &Value) {
+        assert_eq!(
+            super::substitute(schema, &context())
+                .expect("Defined variables")
+                .as_ref(),
+            Some(expected)
+        );
+    }
+
+    #[test_case(&json!({"type": "string"}); "no placeholders")]
+    #[test_case(&json!({"properties": {"$var": {"type": "string"}}}); "property named $var")]
+    #[test_case(&json!({"const": {"$var": "id", "other": 1}}); "other members")]
+    #[test_case(&json!({"const": {"$var": "id"}}); "const")]
+    #[test_case(&json!({"enum": [{"$var": "id"}]}); "enum")]
+    #[test_case(&json!({"properties": {"a": {"default": {"$var": "id"}, "examples": [{"$var": "id"}]}}}); "default and examples")]
+    fn unchanged(schema: &Value) {
+        assert_eq!(
+            super::substitute(schema, &context()).expect("Defined variables"),
+            None
+        );
+    }
+
+    #[test]
+    fn undefined() {
+        let schema = json!({"items": [{"maxLength": {"$var": "limits.name"}}]});
+        let error = super::substitute(&schema, &context()).expect_err("Undefined variable");
+        assert_eq!(error.to_string(), "Undefined schema variable 'limits.name'");
+        assert!(matches!(
+            error.kind,
+            ValidationErrorKind::UndefinedVariable { ref name } if name == "limits.name"
+        ));
+        assert_eq!(error.schema_path.as_str(), "/items/0/maxLength");
+        assert_eq!(error.instance_path.as_str(), "");
+    }
+}
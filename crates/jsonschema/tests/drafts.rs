@@ -0,0 +1,46 @@
+//! Run with a single draft enabled, e.g. `--no-default-features --features draft7`, to check
+//! that schemas without `$schema` fall back to an enabled draft.
+use jsonschema::Draft;
+use serde_json::json;
+
+#[test]
+fn default_draft_is_enabled() {
+    let schema = json!({"type": "string"});
+    let validator = jsonschema::validator_for(&schema).expect("Invalid schema");
+    assert!(validator.is_valid(&json!("a")));
+    assert!(!validator.is_valid(&json!(1)));
+    assert!(jsonschema::meta::is_valid(&schema));
+    assert!(jsonschema::meta::validate(&schema).is_ok());
+    assert!(!jsonschema::meta::is_valid(&json!({"type": 1})));
+    assert!(jsonschema::options()
+        .build(&schema)
+        .expect("Invalid schema")
+        .is_valid(&json!("a")));
+}
+
+#[test]
+fn disabled_drafts_are_rejected() {
+    for (draft, uri) in [
+        (Draft::Draft4, "http://json-schema.org/draft-04/schema#"),
+        (Draft::Draft6, "http://json-schema.org/draft-06/schema#"),
+        (Draft::Draft7, "http://json-schema.org/draft-07/schema#"),
+        (
+            Draft::Draft201909,
+            "https://json-schema.org/draft/2019-09/schema",
+        ),
+        (
+            Draft::Draft202012,
+            "https://json-schema.org/draft/2020-12/schema",
+        ),
+    ] {
+        let schema = json!({"$schema": uri, "type": "string"});
+        assert_eq!(
+            jsonschema::validator_for(&schema).is_ok(),
+            draft.is_enabled()
+        );
+        assert_eq!(
+            jsonschema::meta::try_is_valid(&schema).is_ok(),
+            draft.is_enabled()
+        );
+    }
+}
@@ -0,0 +1,28 @@
+//! Global defaults are process-wide, so they are tested in their own test binary.
+use serde_json::json;
+
+#[test]
+fn global_defaults() {
+    let schema = json!({"type": "string", "format": "even-length"});
+    let defaults = jsonschema::options()
+        .with_format("even-length", |value| value.len() % 2 == 0)
+        .should_validate_formats(true);
+    jsonschema::set_global_defaults(defaults).expect("Defaults are not set yet");
+    assert!(jsonschema::set_global_defaults(jsonschema::options()).is_err());
+
+    assert!(jsonschema::is_valid(&schema, &json!("ab")));
+    assert!(!jsonschema::is_valid(&schema, &json!("abc")));
+    assert!(jsonschema::validate(&schema, &json!("abc")).is_err());
+    let validator = jsonschema::validator_for(&schema).expect("Invalid schema");
+    assert!(!validator.is_valid(&json!("abc")));
+    let validator = jsonschema::draft7::new(&schema).expect("Invalid schema");
+    assert!(!validator.is_valid(&json!("abc")));
+    // Per-validator options take precedence
+    let validator = jsonschema::options()
+        .should_validate_formats(false)
+        .build(&schema)
+        .expect("Invalid schema");
+    assert!(validator.is_valid(&json!("abc")));
+    // Meta-schemas are unaffected
+    assert!(jsonschema::meta::is_valid(&json!({"format": "uri"})));
+}
@@ -105,6 +105,53 @@ use test_case::test_case;
         ]
     }); "invalid anyOf"
 }]
+#[test_case{
+    &json!({"anyOf": [{"type": "object"}, {"type": "array"}]}),
+    &json!(1),
+    &json!({
+        "valid": false,
+        "errors": [
+            {
+                "keywordLocation": "/anyOf",
+                "instanceLocation": "",
+                "error": "1 is not valid under any of the schemas listed in the 'anyOf' keyword, 'type' failed in every branch: [0] 1 is not of type \"object\"; [1] 1 is not of type \"array\""
+            }
+        ]
+    }); "invalid anyOf with the same reason in every branch"
+}]
+#[test_case{
+    &json!({"oneOf": [{"type": "object"}, {"type": "array"}]}),
+    &json!(1),
+    &json!({
+        "valid": false,
+        "errors": [
+            {
+                "keywordLocation": "/oneOf",
+                "instanceLocation": "",
+                "error": "1 is not valid under any of the schemas listed in the 'oneOf' keyword, 'type' failed in every branch: [0] 1 is not of type \"object\"; [1] 1 is not of type \"array\""
+            }
+        ]
+    }); "invalid oneOf with the same reason in every branch"
+}]
+#[test_case{
+    &json!({"anyOf": [{"properties": {"a": {"type": "object"}}}, {"properties": {"a": {"type": "array"}}}]}),
+    &json!({"a": 1}),
+    &json!({
+        "valid": false,
+        "errors": [
+            {
+                "keywordLocation": "/anyOf/0/properties/a/type",
+                "instanceLocation": "/a",
+                "error": "1 is not of type \"object\""
+            },
+            {
+                "keywordLocation": "/anyOf/1/properties/a/type",
+                "instanceLocation": "/a",
+                "error": "1 is not of type \"array\""
+            }
+        ]
+    }); "invalid anyOf failing below the current instance location"
+}]
 #[test_case{
     &json!({"oneOf": [{"type": "object", "someannotation": "somevalue"}, {"type": "string"}]}),
     &json!({"somekey": "some value"}),
@@ -163,6 +210,14 @@ use test_case::test_case;
     &json!({
         "valid": true,
         "annotations": [
+            {
+                "keywordLocation": "/if",
+                "instanceLocation": "",
+                "annotations": {
+                    "matched": true,
+                    "branch": "then"
+                }
+            },
             {
                 "keywordLocation": "/if",
                 "instanceLocation": "",
@@ -206,6 +261,14 @@ use test_case::test_case;
     &json!({
         "valid": true,
         "annotations": [
+            {
+                "keywordLocation": "/if",
+                "instanceLocation": "",
+                "annotations": {
+                    "matched": false,
+                    "branch": "else"
+                }
+            },
             {
                 "keywordLocation": "/else",
                 "instanceLocation": "",
@@ -243,6 +306,14 @@ use test_case::test_case;
     &json!({
         "valid": true,
         "annotations": [
+            {
+                "keywordLocation": "/if",
+                "instanceLocation": "",
+                "annotations": {
+                    "matched": true,
+                    "branch": "then"
+                }
+            },
             {
                 "keywordLocation": "/if",
                 "instanceLocation": "",
@@ -270,6 +341,14 @@ use test_case::test_case;
     &json!({
         "valid": true,
         "annotations": [
+            {
+                "keywordLocation": "/if",
+                "instanceLocation": "",
+                "annotations": {
+                    "matched": false,
+                    "branch": "else"
+                }
+            },
             {
                 "keywordLocation": "/else",
                 "instanceLocation": "",
@@ -315,6 +394,50 @@ use test_case::test_case;
         ]
     }); "invalid if-then-else else branch"
 }]
+#[test_case{
+    &json!({
+        "dependentSchemas": {
+            "credit_card": {"required": ["billing_address"], "cardannotation": "cardvalue"},
+            "iban": {"required": ["bank"]}
+        }
+    }),
+    &json!({"credit_card": 1, "billing_address": "street"}),
+    &json!({
+        "valid": true,
+        "annotations": [
+            {
+                "keywordLocation": "/dependentSchemas",
+                "instanceLocation": "",
+                "annotations": ["credit_card"]
+            },
+            {
+                "keywordLocation": "/dependentSchemas/credit_card",
+                "instanceLocation": "",
+                "annotations": {
+                    "cardannotation": "cardvalue"
+                }
+            },
+        ]
+    }); "valid dependentSchemas"
+}]
+#[test_case{
+    &json!({
+        "dependentSchemas": {
+            "credit_card": {"required": ["billing_address"]}
+        }
+    }),
+    &json!({"credit_card": 1}),
+    &json!({
+        "valid": false,
+        "errors": [
+            {
+                "keywordLocation": "/dependentSchemas/credit_card/required",
+                "instanceLocation": "",
+                "error": "\"billing_address\" is a required property"
+            },
+        ]
+    }); "invalid dependentSchemas"
+}]
 #[test_case{
     &json!({
         "type": "array",
@@ -579,6 +702,118 @@ use test_case::test_case;
         ]
     }); "valid propertyNames"
 }]
+#[test_case{
+    &json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "$ref": "#",
+        "x-widget": "tree"
+    }),
+    &json!({}),
+    &json!({
+        "valid": true,
+        "annotations": [
+            {
+                "keywordLocation": "",
+                "instanceLocation": "",
+                "annotations": {
+                    "$schema": "http://json-schema.org/draft-07/schema#",
+                    "x-widget": "tree"
+                }
+            },
+        ]
+    }); "annotations next to a self-referencing $ref"
+}]
+#[test_case{
+    &json!({
+        "$ref": "#",
+        "x-widget": "tree"
+    }),
+    &json!({}),
+    &json!({
+        "valid": true,
+        "annotations": [
+            {
+                "keywordLocation": "",
+                "instanceLocation": "",
+                "annotations": {
+                    "x-widget": "tree"
+                }
+            },
+        ]
+    }); "annotations next to a self-referencing $ref with adjacent validation"
+}]
+#[test_case{
+    &json!({
+        "$ref": "#/$defs/a",
+        "$defs": {
+            "a": {"$ref": "#/$defs/a", "x-widget": "tree"}
+        }
+    }),
+    &json!({}),
+    &json!({
+        "valid": true,
+        "annotations": [
+            {
+                "keywordLocation": "/$ref",
+                "instanceLocation": "",
+                "annotations": {
+                    "x-widget": "tree"
+                }
+            },
+        ]
+    }); "annotations next to a self-referencing $ref in a definition"
+}]
+#[test_case{
+    &json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "$ref": "#/definitions/a",
+        "definitions": {
+            "a": {"$ref": "#/definitions/a", "x-widget": "tree"}
+        }
+    }),
+    &json!({}),
+    &json!({
+        "valid": true,
+        "annotations": [
+            {
+                "keywordLocation": "",
+                "instanceLocation": "",
+                "annotations": {
+                    "$schema": "http://json-schema.org/draft-07/schema#",
+                    "definitions": {
+                        "a": {"$ref": "#/definitions/a", "x-widget": "tree"}
+                    }
+                }
+            },
+            {
+                "keywordLocation": "/$ref",
+                "instanceLocation": "",
+                "annotations": {
+                    "x-widget": "tree"
+                }
+            },
+        ]
+    }); "annotations next to a self-referencing $ref in a definition in Draft 7"
+}]
+#[test_case{
+    &json!({
+        "$ref": "#/$defs/a",
+        "$defs": {
+            "a": {"$ref": "#/$defs/a", "type": "string"}
+        }
+    }),
+    &json!(1),
+    &json!({
+        "valid": false,
+        "errors": [
+            {
+                "keywordLocation": "/$ref/type",
+                "instanceLocation": "",
+                "error": "1 is not of type \"string\""
+            },
+        ]
+    }); "validation next to a self-referencing $ref in a definition"
+}]
 fn test_basic_output(
     schema: &serde_json::Value,
     instance: &serde_json::Value,
@@ -589,6 +824,43 @@ fn test_basic_output(
     assert_eq!(&output, expected_output);
 }
 
+#[test]
+fn test_non_standard_annotations() {
+    let schema = json!({
+        "propertyOrder": ["name", "age"],
+        "properties": {
+            "name": {"type": "string", "x-widget": "text"},
+            "age": {"type": "integer", "x-widget": "spinner"}
+        }
+    });
+    let validator = jsonschema::validator_for(&schema).unwrap();
+    let output = validator
+        .apply(&json!({"name": "Alice", "age": 42}))
+        .basic();
+    let jsonschema::BasicOutput::Valid(units) = output else {
+        panic!("Should be valid");
+    };
+    let mut widgets: Vec<_> = units
+        .iter()
+        .filter_map(|unit| {
+            let widget = unit.value().get("x-widget").cloned()?;
+            Some((unit.instance_location().as_str().to_string(), widget))
+        })
+        .collect();
+    widgets.sort_by(|left, right| left.0.cmp(&right.0));
+    assert_eq!(
+        widgets,
+        [
+            ("/age".to_string(), json!("spinner")),
+            ("/name".to_string(), json!("text"))
+        ]
+    );
+    assert_eq!(
+        units[0].value().get("propertyOrder"),
+        Some(&json!(["name", "age"]))
+    );
+}
+
 /// These tests are separated from the rest of the basic output tests for convenience, there's
 /// nothing different about them but they are all tests of the additionalProperties keyword, which
 /// is complicated by the fact that there are eight different implementations based on the
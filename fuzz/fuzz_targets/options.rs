@@ -0,0 +1,33 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: (u8, &[u8], &[u8])| {
+    let (flags, schema, instance) = data;
+    let Ok(schema) = serde_json::from_slice(schema) else {
+        return;
+    };
+    let draft = match flags & 0b111 {
+        0 => jsonschema::Draft::Draft4,
+        1 => jsonschema::Draft::Draft6,
+        2 => jsonschema::Draft::Draft7,
+        3 => jsonschema::Draft::Draft201909,
+        _ => jsonschema::Draft::Draft202012,
+    };
+    let Ok(validator) = jsonschema::options()
+        .with_draft(draft)
+        .should_validate_formats(flags & 0b1000 != 0)
+        .build(&schema)
+    else {
+        return;
+    };
+    let Ok(instance) = serde_json::from_slice(instance) else {
+        return;
+    };
+    let _ = validator.is_valid(&instance);
+    let _ = validator.validate(&instance);
+    for error in validator.iter_errors(&instance) {
+        let _ = error.to_string();
+    }
+    let output = validator.apply(&instance).basic();
+    let _ = serde_json::to_value(output).expect("Failed to serialize");
+});
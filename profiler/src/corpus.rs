@@ -0,0 +1,52 @@
+//! Randomized instances for the `corpus` method.
+//!
+//! Instances come from the library generator and are checked with the validator, so keywords
+//! the generator does not understand only lower the yield.
+use jsonschema::{generate::Generator, Registry, Resource};
+use serde_json::Value;
+
+/// The URI the schema is registered under.
+const ROOT: &str = "json-schema:///";
+
+pub(crate) struct Corpus {
+    /// Instances accepted by the validator.
+    pub(crate) conforming: Vec<Value>,
+    /// Instances made invalid by a single change to a conforming one.
+    pub(crate) near_misses: Vec<Value>,
+}
+
+/// Generate up to `size` conforming and `size` near-miss instances.
+pub(crate) fn generate(
+    schema: &Value,
+    validator: &jsonschema::Validator,
+    size: usize,
+    seed: u64,
+) -> Result<Corpus, Box<dyn std::error::Error>> {
+    let registry = Registry::try_new(ROOT, Resource::from_contents(schema.clone())?)?;
+    let mut generator = Generator::new(&registry, ROOT, seed)?;
+    let mut corpus = Corpus {
+        conforming: Vec::with_capacity(size),
+        near_misses: Vec::with_capacity(size),
+    };
+    for _ in 0..size.saturating_mul(10) {
+        if corpus.conforming.len() >= size && corpus.near_misses.len() >= size {
+            break;
+        }
+        let Ok(instance) = generator.candidate() else {
+            continue;
+        };
+        if !validator.is_valid(&instance) {
+            continue;
+        }
+        if corpus.near_misses.len() < size {
+            let near_miss = generator.near_miss(&instance);
+            if !validator.is_valid(&near_miss) {
+                corpus.near_misses.push(near_miss);
+            }
+        }
+        if corpus.conforming.len() < size {
+            corpus.conforming.push(instance);
+        }
+    }
+    Ok(corpus)
+}
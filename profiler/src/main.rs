@@ -1,6 +1,12 @@
 use referencing::{Draft, Registry};
 use serde_json::Value;
-use std::fs;
+use std::{
+    fs,
+    time::{Duration, Instant},
+};
+
+mod corpus;
+mod threads;
 
 #[cfg(feature = "dhat-heap")]
 #[global_allocator]
@@ -9,8 +15,11 @@ static ALLOC: dhat::Alloc = dhat::Alloc;
 struct Args {
     iterations: usize,
     schema_path: String,
-    instance_path: String,
+    instance_path: Option<String>,
     method: String,
+    corpus_size: usize,
+    seed: u64,
+    threads: usize,
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -18,18 +27,58 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args = Args {
         iterations: args.value_from_str("--iterations")?,
         schema_path: args.value_from_str("--schema")?,
-        instance_path: args.value_from_str("--instance")?,
+        instance_path: args.opt_value_from_str("--instance")?,
         method: args.value_from_str("--method")?,
+        corpus_size: args.opt_value_from_str("--corpus-size")?.unwrap_or(1000),
+        seed: args.opt_value_from_str("--seed")?.unwrap_or(0),
+        threads: args.opt_value_from_str("--threads")?.unwrap_or(1),
     };
 
     let schema_str = fs::read_to_string(&args.schema_path)?;
-    let instance_str = fs::read_to_string(&args.instance_path)?;
-
     let schema: Value = serde_json::from_str(&schema_str)?;
-    let instance: Value = serde_json::from_str(&instance_str)?;
 
     let validator = jsonschema::validator_for(&schema)?;
 
+    // The corpus replaces the single instance
+    let (instance, corpus) = if args.method == "corpus" {
+        let corpus = corpus::generate(&schema, &validator, args.corpus_size, args.seed)?;
+        println!(
+            "Generated {} conforming and {} near-miss instances",
+            corpus.conforming.len(),
+            corpus.near_misses.len()
+        );
+        (Value::Null, Some(corpus))
+    } else {
+        let instance_path = args
+            .instance_path
+            .as_ref()
+            .ok_or("`--instance` is required for this method")?;
+        let instance_str = fs::read_to_string(instance_path)?;
+        (serde_json::from_str(&instance_str)?, None)
+    };
+
+    if args.threads > 1 {
+        let instances: Vec<&Value> = match &corpus {
+            Some(corpus) => corpus
+                .conforming
+                .iter()
+                .chain(&corpus.near_misses)
+                .collect(),
+            None => vec![&instance],
+        };
+        threads::run(
+            &args.method,
+            &validator,
+            &instances,
+            args.iterations,
+            args.threads,
+        )?;
+        return Ok(());
+    }
+
+    let mut conforming_time = Duration::ZERO;
+    let mut near_miss_time = Duration::ZERO;
+
     let input_resources = vec![(
         "http://example.com/schema",
         Draft::Draft202012.create_resource(schema.clone()),
@@ -52,15 +101,50 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             "apply" => {
                 let _ = validator.apply(&instance).basic();
             }
+            "corpus" => {
+                let corpus = corpus.as_ref().expect("Corpus is generated");
+                let start = Instant::now();
+                for instance in &corpus.conforming {
+                    let _ = validator.is_valid(instance);
+                }
+                conforming_time += start.elapsed();
+                let start = Instant::now();
+                for instance in &corpus.near_misses {
+                    let _ = validator.is_valid(instance);
+                }
+                near_miss_time += start.elapsed();
+            }
             "registry" => {
                 let _ = Registry::try_from_resources(input_resources.into_iter());
                 break;
             }
             _ => panic!(
-                "Invalid method. Use 'registry', 'build', 'is_valid', 'validate', 'iter_errors`, or 'apply'"
+                "Invalid method. Use 'registry', 'build', 'is_valid', 'validate', 'iter_errors`, 'apply', or 'corpus'"
             ),
         }
     }
 
+    if let Some(corpus) = &corpus {
+        report(
+            "conforming",
+            corpus.conforming.len(),
+            args.iterations,
+            conforming_time,
+        );
+        report(
+            "near-miss",
+            corpus.near_misses.len(),
+            args.iterations,
+            near_miss_time,
+        );
+    }
+
     Ok(())
 }
+
+/// Print the validation throughput of a part of the corpus, or of concurrent threads.
+fn report(kind: &str, instances: usize, iterations: usize, elapsed: Duration) {
+    let validations = instances * iterations;
+    let throughput = validations as f64 / elapsed.as_secs_f64().max(f64::EPSILON);
+    println!("{kind}: {validations} validations in {elapsed:?} ({throughput:.0} instances/s)");
+}
@@ -0,0 +1,91 @@
+//! Concurrent validation against a shared validator, for `--threads`.
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+use serde_json::Value;
+
+/// Validate `instances` `iterations` times on a single thread, then on `threads` threads at once,
+/// and print how the aggregate throughput scales.
+pub(crate) fn run(
+    method: &str,
+    validator: &jsonschema::Validator,
+    instances: &[&Value],
+    iterations: usize,
+    threads: usize,
+) -> Result<(), String> {
+    if !matches!(
+        method,
+        "is_valid" | "validate" | "iter_errors" | "apply" | "corpus"
+    ) {
+        return Err(format!(
+            "`--threads` does not support the '{method}' method"
+        ));
+    }
+    let (single, _) = measure(method, validator, instances, iterations, 1);
+    crate::report("1 thread", instances.len(), iterations, single);
+    let (wall, per_thread) = measure(method, validator, instances, iterations, threads);
+    crate::report(
+        &format!("{threads} threads"),
+        instances.len() * threads,
+        iterations,
+        wall,
+    );
+    let fastest = per_thread.iter().min().copied().unwrap_or_default();
+    let slowest = per_thread.iter().max().copied().unwrap_or_default();
+    println!("Per thread: fastest {fastest:?}, slowest {slowest:?}");
+    // With perfect scaling, each thread is as fast as a single one
+    let speedup = single.as_secs_f64() * threads as f64 / wall.as_secs_f64().max(f64::EPSILON);
+    println!(
+        "Scaling: {speedup:.2}x on {threads} threads ({:.0}% efficiency)",
+        speedup / threads as f64 * 100.0
+    );
+    Ok(())
+}
+
+/// The wall time of all threads, and the time each of them took.
+fn measure(
+    method: &str,
+    validator: &jsonschema::Validator,
+    instances: &[&Value],
+    iterations: usize,
+    threads: usize,
+) -> (Duration, Vec<Duration>) {
+    let start = Instant::now();
+    let per_thread = thread::scope(|scope| {
+        let handles: Vec<_> = (0..threads)
+            .map(|_| {
+                scope.spawn(|| {
+                    let start = Instant::now();
+                    for _ in 0..iterations {
+                        for instance in instances {
+                            validate(method, validator, instance);
+                        }
+                    }
+                    start.elapsed()
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().expect("Validation thread panicked"))
+            .collect()
+    });
+    (start.elapsed(), per_thread)
+}
+
+fn validate(method: &str, validator: &jsonschema::Validator, instance: &Value) {
+    match method {
+        "validate" => {
+            let _ = validator.validate(instance);
+        }
+        "iter_errors" => for _error in validator.iter_errors(instance) {},
+        "apply" => {
+            let _ = validator.apply(instance).basic();
+        }
+        _ => {
+            let _ = validator.is_valid(instance);
+        }
+    }
+}